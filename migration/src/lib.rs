@@ -12,6 +12,67 @@ mod m20260104_000010_add_referral_system;
 mod m20260104_000011_create_pending_invoices;
 mod m20260105_000012_update_commission_default;
 mod m20260106_000013_add_referral_code;
+mod m20260110_000014_create_license_devices;
+mod m20260112_000015_create_user_settings;
+mod m20260113_000016_create_faq;
+mod m20260114_000017_create_tickets;
+mod m20260114_000018_create_canned_responses;
+mod m20260115_000019_add_ticket_sla;
+mod m20260116_000020_create_ratings;
+mod m20260117_000021_create_announcements;
+mod m20260118_000022_create_api_tokens;
+mod m20260119_000023_create_instance_stats;
+mod m20260120_000024_add_downtime_alerts;
+mod m20260121_000025_create_goals;
+mod m20260122_000026_add_honeypots;
+mod m20260123_000027_add_security_alerts;
+mod m20260124_000028_create_hwid_exemptions;
+mod m20260125_000029_add_license_schedules;
+mod m20260126_000030_create_region_prices;
+mod m20260127_000031_create_sales;
+mod m20260128_000032_add_account_deletion;
+mod m20260129_000033_create_terms;
+mod m20260130_000034_create_custom_fields;
+mod m20260131_000035_add_products;
+mod m20260201_000036_create_sessions;
+mod m20260202_000037_create_storefronts;
+mod m20260203_000038_create_plans;
+mod m20260204_000039_create_admin_ops;
+mod m20260205_000040_add_auto_renew;
+mod m20260206_000041_add_expiry_notified_at;
+mod m20260207_000042_create_commission_boosts;
+mod m20260208_000043_create_promo_assets;
+mod m20260209_000044_create_freebie_claims;
+mod m20260210_000045_create_promo_campaigns;
+mod m20260211_000046_create_promo_codes;
+mod m20260212_000047_create_feature_flags;
+mod m20260213_000048_move_referral_stats_to_ledger;
+mod m20260214_000049_create_outbox;
+mod m20260215_000050_add_user_is_reseller;
+mod m20260216_000051_create_bot_usage;
+mod m20260217_000052_create_signing_keys;
+mod m20260218_000053_create_download_tokens;
+mod m20260219_000054_create_upgrade_offers;
+mod m20260220_000055_create_risk_scores;
+mod m20260221_000056_create_reviews;
+mod m20260222_000057_add_build_checksum;
+mod m20260223_000058_add_release_channels;
+mod m20260224_000059_add_license_blocked_until;
+mod m20260225_000060_add_license_loaned_by;
+mod m20260226_000061_create_build_artifacts;
+mod m20260227_000062_add_plan_calendar;
+mod m20260228_000063_create_downloads;
+mod m20260301_000064_add_quiet_hours;
+mod m20260302_000065_create_api_usage;
+mod m20260303_000066_add_refunds;
+mod m20260304_000067_add_session_ip;
+mod m20260305_000068_add_session_country;
+mod m20260306_000069_create_license_alerts;
+mod m20260307_000070_create_license_stats;
+mod m20260308_000071_create_stats_daily;
+mod m20260309_000072_add_leaderboard;
+mod m20260310_000073_create_error_reports;
+mod m20260311_000074_add_session_row_ids;
 
 pub struct Migrator;
 
@@ -31,6 +92,67 @@ impl MigratorTrait for Migrator {
       Box::new(m20260104_000011_create_pending_invoices::Migration),
       Box::new(m20260105_000012_update_commission_default::Migration),
       Box::new(m20260106_000013_add_referral_code::Migration),
+      Box::new(m20260110_000014_create_license_devices::Migration),
+      Box::new(m20260112_000015_create_user_settings::Migration),
+      Box::new(m20260113_000016_create_faq::Migration),
+      Box::new(m20260114_000017_create_tickets::Migration),
+      Box::new(m20260114_000018_create_canned_responses::Migration),
+      Box::new(m20260115_000019_add_ticket_sla::Migration),
+      Box::new(m20260116_000020_create_ratings::Migration),
+      Box::new(m20260117_000021_create_announcements::Migration),
+      Box::new(m20260118_000022_create_api_tokens::Migration),
+      Box::new(m20260119_000023_create_instance_stats::Migration),
+      Box::new(m20260120_000024_add_downtime_alerts::Migration),
+      Box::new(m20260121_000025_create_goals::Migration),
+      Box::new(m20260122_000026_add_honeypots::Migration),
+      Box::new(m20260123_000027_add_security_alerts::Migration),
+      Box::new(m20260124_000028_create_hwid_exemptions::Migration),
+      Box::new(m20260125_000029_add_license_schedules::Migration),
+      Box::new(m20260126_000030_create_region_prices::Migration),
+      Box::new(m20260127_000031_create_sales::Migration),
+      Box::new(m20260128_000032_add_account_deletion::Migration),
+      Box::new(m20260129_000033_create_terms::Migration),
+      Box::new(m20260130_000034_create_custom_fields::Migration),
+      Box::new(m20260131_000035_add_products::Migration),
+      Box::new(m20260201_000036_create_sessions::Migration),
+      Box::new(m20260202_000037_create_storefronts::Migration),
+      Box::new(m20260203_000038_create_plans::Migration),
+      Box::new(m20260204_000039_create_admin_ops::Migration),
+      Box::new(m20260205_000040_add_auto_renew::Migration),
+      Box::new(m20260206_000041_add_expiry_notified_at::Migration),
+      Box::new(m20260207_000042_create_commission_boosts::Migration),
+      Box::new(m20260208_000043_create_promo_assets::Migration),
+      Box::new(m20260209_000044_create_freebie_claims::Migration),
+      Box::new(m20260210_000045_create_promo_campaigns::Migration),
+      Box::new(m20260211_000046_create_promo_codes::Migration),
+      Box::new(m20260212_000047_create_feature_flags::Migration),
+      Box::new(m20260213_000048_move_referral_stats_to_ledger::Migration),
+      Box::new(m20260214_000049_create_outbox::Migration),
+      Box::new(m20260215_000050_add_user_is_reseller::Migration),
+      Box::new(m20260216_000051_create_bot_usage::Migration),
+      Box::new(m20260217_000052_create_signing_keys::Migration),
+      Box::new(m20260218_000053_create_download_tokens::Migration),
+      Box::new(m20260219_000054_create_upgrade_offers::Migration),
+      Box::new(m20260220_000055_create_risk_scores::Migration),
+      Box::new(m20260221_000056_create_reviews::Migration),
+      Box::new(m20260222_000057_add_build_checksum::Migration),
+      Box::new(m20260223_000058_add_release_channels::Migration),
+      Box::new(m20260224_000059_add_license_blocked_until::Migration),
+      Box::new(m20260225_000060_add_license_loaned_by::Migration),
+      Box::new(m20260226_000061_create_build_artifacts::Migration),
+      Box::new(m20260227_000062_add_plan_calendar::Migration),
+      Box::new(m20260228_000063_create_downloads::Migration),
+      Box::new(m20260301_000064_add_quiet_hours::Migration),
+      Box::new(m20260302_000065_create_api_usage::Migration),
+      Box::new(m20260303_000066_add_refunds::Migration),
+      Box::new(m20260304_000067_add_session_ip::Migration),
+      Box::new(m20260305_000068_add_session_country::Migration),
+      Box::new(m20260306_000069_create_license_alerts::Migration),
+      Box::new(m20260307_000070_create_license_stats::Migration),
+      Box::new(m20260308_000071_create_stats_daily::Migration),
+      Box::new(m20260309_000072_add_leaderboard::Migration),
+      Box::new(m20260310_000073_create_error_reports::Migration),
+      Box::new(m20260311_000074_add_session_row_ids::Migration),
     ]
   }
 }
@@ -0,0 +1,77 @@
+use sea_orm_migration::prelude::*;
+
+use super::m20251214_000002_create_licenses::Licenses;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+  async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    // Every distinct machine that opened a session with a license key.
+    // Used for the owner-facing sharing report.
+    manager
+      .create_table(
+        Table::create()
+          .table(LicenseDevices::Table)
+          .if_not_exists()
+          .col(
+            ColumnDef::new(LicenseDevices::Id)
+              .integer()
+              .not_null()
+              .auto_increment()
+              .primary_key(),
+          )
+          .col(ColumnDef::new(LicenseDevices::LicenseKey).string().not_null())
+          .col(ColumnDef::new(LicenseDevices::Hwid).string().not_null())
+          .col(ColumnDef::new(LicenseDevices::Ip).string().null())
+          .col(ColumnDef::new(LicenseDevices::FirstSeen).date_time().not_null())
+          .col(ColumnDef::new(LicenseDevices::LastSeen).date_time().not_null())
+          .col(
+            ColumnDef::new(LicenseDevices::Sessions)
+              .integer()
+              .not_null()
+              .default(0),
+          )
+          .foreign_key(
+            ForeignKey::create()
+              .name("fk_license_devices_license")
+              .from(LicenseDevices::Table, LicenseDevices::LicenseKey)
+              .to(Licenses::Table, Licenses::Key)
+              .on_delete(ForeignKeyAction::Cascade),
+          )
+          .to_owned(),
+      )
+      .await?;
+
+    manager
+      .create_index(
+        Index::create()
+          .name("idx_license_devices_key_hwid")
+          .table(LicenseDevices::Table)
+          .col(LicenseDevices::LicenseKey)
+          .col(LicenseDevices::Hwid)
+          .unique()
+          .to_owned(),
+      )
+      .await
+  }
+
+  async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager
+      .drop_table(Table::drop().table(LicenseDevices::Table).to_owned())
+      .await
+  }
+}
+
+#[derive(DeriveIden)]
+pub enum LicenseDevices {
+  Table,
+  Id,
+  LicenseKey,
+  Hwid,
+  Ip,
+  FirstSeen,
+  LastSeen,
+  Sessions,
+}
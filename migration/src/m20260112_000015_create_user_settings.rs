@@ -0,0 +1,66 @@
+use sea_orm_migration::prelude::*;
+
+use super::m20251214_000001_create_users::Users;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+  async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    // Per-user bot preferences, kept out of `users` so new settings
+    // don't touch the billing/referral row
+    manager
+      .create_table(
+        Table::create()
+          .table(UserSettings::Table)
+          .if_not_exists()
+          .col(
+            ColumnDef::new(UserSettings::TgUserId)
+              .big_integer()
+              .not_null()
+              .primary_key(),
+          )
+          .col(
+            ColumnDef::new(UserSettings::Language)
+              .string()
+              .not_null()
+              .default("en"),
+          )
+          .col(ColumnDef::new(UserSettings::OnboardedAt).date_time().null())
+          .foreign_key(
+            ForeignKey::create()
+              .name("fk_user_settings_user")
+              .from(UserSettings::Table, UserSettings::TgUserId)
+              .to(Users::Table, Users::TgUserId)
+              .on_delete(ForeignKeyAction::Cascade),
+          )
+          .to_owned(),
+      )
+      .await?;
+
+    // Existing users already know the bot, don't show them the wizard
+    let db = manager.get_connection();
+    db.execute_unprepared(
+      "INSERT INTO user_settings (tg_user_id, language, onboarded_at) \
+       SELECT tg_user_id, 'en', reg_date FROM users",
+    )
+    .await?;
+
+    Ok(())
+  }
+
+  async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager
+      .drop_table(Table::drop().table(UserSettings::Table).to_owned())
+      .await
+  }
+}
+
+#[derive(DeriveIden)]
+pub enum UserSettings {
+  Table,
+  TgUserId,
+  Language,
+  OnboardedAt,
+}
@@ -0,0 +1,43 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+  async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager
+      .create_table(
+        Table::create()
+          .table(Faq::Table)
+          .if_not_exists()
+          .col(
+            ColumnDef::new(Faq::Id)
+              .integer()
+              .not_null()
+              .auto_increment()
+              .primary_key(),
+          )
+          .col(ColumnDef::new(Faq::Question).string().not_null())
+          .col(ColumnDef::new(Faq::Answer).text().not_null())
+          .col(ColumnDef::new(Faq::Keywords).string().not_null().default(""))
+          .col(ColumnDef::new(Faq::CreatedAt).date_time().not_null())
+          .to_owned(),
+      )
+      .await
+  }
+
+  async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager.drop_table(Table::drop().table(Faq::Table).to_owned()).await
+  }
+}
+
+#[derive(DeriveIden)]
+pub enum Faq {
+  Table,
+  Id,
+  Question,
+  Answer,
+  Keywords,
+  CreatedAt,
+}
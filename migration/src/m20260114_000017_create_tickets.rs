@@ -0,0 +1,124 @@
+use sea_orm_migration::prelude::*;
+
+use super::m20251214_000001_create_users::Users;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+  async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager
+      .create_table(
+        Table::create()
+          .table(Tickets::Table)
+          .if_not_exists()
+          .col(
+            ColumnDef::new(Tickets::Id)
+              .integer()
+              .not_null()
+              .auto_increment()
+              .primary_key(),
+          )
+          .col(ColumnDef::new(Tickets::TgUserId).big_integer().not_null())
+          .col(
+            ColumnDef::new(Tickets::Status)
+              .string()
+              .not_null()
+              .default("open"),
+          )
+          .col(ColumnDef::new(Tickets::CreatedAt).date_time().not_null())
+          .col(ColumnDef::new(Tickets::UpdatedAt).date_time().not_null())
+          .foreign_key(
+            ForeignKey::create()
+              .name("fk_tickets_user")
+              .from(Tickets::Table, Tickets::TgUserId)
+              .to(Users::Table, Users::TgUserId)
+              .on_delete(ForeignKeyAction::Cascade),
+          )
+          .to_owned(),
+      )
+      .await?;
+
+    manager
+      .create_index(
+        Index::create()
+          .name("idx_tickets_user")
+          .table(Tickets::Table)
+          .col(Tickets::TgUserId)
+          .to_owned(),
+      )
+      .await?;
+
+    manager
+      .create_table(
+        Table::create()
+          .table(TicketMessages::Table)
+          .if_not_exists()
+          .col(
+            ColumnDef::new(TicketMessages::Id)
+              .integer()
+              .not_null()
+              .auto_increment()
+              .primary_key(),
+          )
+          .col(ColumnDef::new(TicketMessages::TicketId).integer().not_null())
+          .col(ColumnDef::new(TicketMessages::AuthorId).big_integer().not_null())
+          .col(
+            ColumnDef::new(TicketMessages::FromAdmin)
+              .boolean()
+              .not_null()
+              .default(false),
+          )
+          .col(ColumnDef::new(TicketMessages::Text).text().not_null())
+          .col(ColumnDef::new(TicketMessages::CreatedAt).date_time().not_null())
+          .foreign_key(
+            ForeignKey::create()
+              .name("fk_ticket_messages_ticket")
+              .from(TicketMessages::Table, TicketMessages::TicketId)
+              .to(Tickets::Table, Tickets::Id)
+              .on_delete(ForeignKeyAction::Cascade),
+          )
+          .to_owned(),
+      )
+      .await?;
+
+    manager
+      .create_index(
+        Index::create()
+          .name("idx_ticket_messages_ticket")
+          .table(TicketMessages::Table)
+          .col(TicketMessages::TicketId)
+          .to_owned(),
+      )
+      .await
+  }
+
+  async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager
+      .drop_table(Table::drop().table(TicketMessages::Table).to_owned())
+      .await?;
+    manager.drop_table(Table::drop().table(Tickets::Table).to_owned()).await
+  }
+}
+
+#[derive(DeriveIden)]
+pub enum Tickets {
+  Table,
+  Id,
+  TgUserId,
+  Status,
+  CreatedAt,
+  UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+pub enum TicketMessages {
+  Table,
+  Id,
+  TicketId,
+  AuthorId,
+  FromAdmin,
+  Text,
+  CreatedAt,
+}
@@ -0,0 +1,42 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+  async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager
+      .create_table(
+        Table::create()
+          .table(CannedResponses::Table)
+          .if_not_exists()
+          .col(
+            ColumnDef::new(CannedResponses::Name)
+              .string()
+              .not_null()
+              .primary_key(),
+          )
+          .col(ColumnDef::new(CannedResponses::Text).text().not_null())
+          .col(
+            ColumnDef::new(CannedResponses::CreatedAt).date_time().not_null(),
+          )
+          .to_owned(),
+      )
+      .await
+  }
+
+  async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager
+      .drop_table(Table::drop().table(CannedResponses::Table).to_owned())
+      .await
+  }
+}
+
+#[derive(DeriveIden)]
+pub enum CannedResponses {
+  Table,
+  Name,
+  Text,
+  CreatedAt,
+}
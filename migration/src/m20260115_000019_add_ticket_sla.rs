@@ -0,0 +1,81 @@
+use sea_orm_migration::prelude::*;
+
+use super::m20260114_000017_create_tickets::Tickets;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+  async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    // SQLite only supports one column per ALTER TABLE
+    manager
+      .alter_table(
+        Table::alter()
+          .table(Tickets::Table)
+          .add_column(
+            ColumnDef::new(TicketsExt::Priority)
+              .string()
+              .not_null()
+              .default("normal"),
+          )
+          .to_owned(),
+      )
+      .await?;
+
+    // When the ticket started waiting for a support reply
+    manager
+      .alter_table(
+        Table::alter()
+          .table(Tickets::Table)
+          .add_column(ColumnDef::new(TicketsExt::WaitingSince).date_time().null())
+          .to_owned(),
+      )
+      .await?;
+
+    // SLA reminders already sent for the current wait
+    manager
+      .alter_table(
+        Table::alter()
+          .table(Tickets::Table)
+          .add_column(
+            ColumnDef::new(TicketsExt::RemindersSent)
+              .integer()
+              .not_null()
+              .default(0),
+          )
+          .to_owned(),
+      )
+      .await?;
+
+    let db = manager.get_connection();
+    db.execute_unprepared(
+      "UPDATE tickets SET waiting_since = updated_at WHERE status = 'open'",
+    )
+    .await?;
+
+    Ok(())
+  }
+
+  async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    for column in [
+      TicketsExt::Priority,
+      TicketsExt::WaitingSince,
+      TicketsExt::RemindersSent,
+    ] {
+      manager
+        .alter_table(
+          Table::alter().table(Tickets::Table).drop_column(column).to_owned(),
+        )
+        .await?;
+    }
+    Ok(())
+  }
+}
+
+#[derive(DeriveIden)]
+enum TicketsExt {
+  Priority,
+  WaitingSince,
+  RemindersSent,
+}
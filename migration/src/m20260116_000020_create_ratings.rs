@@ -0,0 +1,68 @@
+use sea_orm_migration::prelude::*;
+
+use super::m20251214_000001_create_users::Users;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+  async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    // One row per satisfaction prompt, score stays NULL until answered
+    manager
+      .create_table(
+        Table::create()
+          .table(Ratings::Table)
+          .if_not_exists()
+          .col(
+            ColumnDef::new(Ratings::Id)
+              .integer()
+              .not_null()
+              .auto_increment()
+              .primary_key(),
+          )
+          .col(ColumnDef::new(Ratings::TgUserId).big_integer().not_null())
+          .col(ColumnDef::new(Ratings::Kind).string().not_null())
+          .col(ColumnDef::new(Ratings::Subject).string().not_null())
+          .col(ColumnDef::new(Ratings::Score).integer().null())
+          .col(ColumnDef::new(Ratings::AskedAt).date_time().not_null())
+          .col(ColumnDef::new(Ratings::RatedAt).date_time().null())
+          .foreign_key(
+            ForeignKey::create()
+              .name("fk_ratings_user")
+              .from(Ratings::Table, Ratings::TgUserId)
+              .to(Users::Table, Users::TgUserId)
+              .on_delete(ForeignKeyAction::Cascade),
+          )
+          .to_owned(),
+      )
+      .await?;
+
+    manager
+      .create_index(
+        Index::create()
+          .name("idx_ratings_kind_asked")
+          .table(Ratings::Table)
+          .col(Ratings::Kind)
+          .col(Ratings::AskedAt)
+          .to_owned(),
+      )
+      .await
+  }
+
+  async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager.drop_table(Table::drop().table(Ratings::Table).to_owned()).await
+  }
+}
+
+#[derive(DeriveIden)]
+pub enum Ratings {
+  Table,
+  Id,
+  TgUserId,
+  Kind,
+  Subject,
+  Score,
+  AskedAt,
+  RatedAt,
+}
@@ -0,0 +1,105 @@
+use sea_orm_migration::prelude::*;
+
+use super::m20251214_000001_create_users::Users;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+  async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager
+      .create_table(
+        Table::create()
+          .table(Announcements::Table)
+          .if_not_exists()
+          .col(
+            ColumnDef::new(Announcements::Id)
+              .integer()
+              .not_null()
+              .auto_increment()
+              .primary_key(),
+          )
+          .col(
+            ColumnDef::new(Announcements::Category)
+              .string()
+              .not_null()
+              .default("general"),
+          )
+          .col(ColumnDef::new(Announcements::Title).string().not_null())
+          .col(ColumnDef::new(Announcements::Body).text().not_null())
+          .col(ColumnDef::new(Announcements::CreatedAt).date_time().not_null())
+          .to_owned(),
+      )
+      .await?;
+
+    // Read markers, a missing row means unread
+    manager
+      .create_table(
+        Table::create()
+          .table(AnnouncementReads::Table)
+          .if_not_exists()
+          .col(
+            ColumnDef::new(AnnouncementReads::TgUserId)
+              .big_integer()
+              .not_null(),
+          )
+          .col(
+            ColumnDef::new(AnnouncementReads::AnnouncementId)
+              .integer()
+              .not_null(),
+          )
+          .col(
+            ColumnDef::new(AnnouncementReads::ReadAt).date_time().not_null(),
+          )
+          .primary_key(
+            Index::create()
+              .col(AnnouncementReads::TgUserId)
+              .col(AnnouncementReads::AnnouncementId),
+          )
+          .foreign_key(
+            ForeignKey::create()
+              .name("fk_announcement_reads_user")
+              .from(AnnouncementReads::Table, AnnouncementReads::TgUserId)
+              .to(Users::Table, Users::TgUserId)
+              .on_delete(ForeignKeyAction::Cascade),
+          )
+          .foreign_key(
+            ForeignKey::create()
+              .name("fk_announcement_reads_announcement")
+              .from(AnnouncementReads::Table, AnnouncementReads::AnnouncementId)
+              .to(Announcements::Table, Announcements::Id)
+              .on_delete(ForeignKeyAction::Cascade),
+          )
+          .to_owned(),
+      )
+      .await
+  }
+
+  async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager
+      .drop_table(Table::drop().table(AnnouncementReads::Table).to_owned())
+      .await?;
+    manager
+      .drop_table(Table::drop().table(Announcements::Table).to_owned())
+      .await
+  }
+}
+
+#[derive(DeriveIden)]
+pub enum Announcements {
+  Table,
+  Id,
+  Category,
+  Title,
+  Body,
+  CreatedAt,
+}
+
+#[derive(DeriveIden)]
+pub enum AnnouncementReads {
+  Table,
+  TgUserId,
+  AnnouncementId,
+  ReadAt,
+}
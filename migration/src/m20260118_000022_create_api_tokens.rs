@@ -0,0 +1,56 @@
+use sea_orm_migration::prelude::*;
+
+use super::m20251214_000001_create_users::Users;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+  async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    // Personal read-only API tokens, one per user. Only the SHA-256
+    // of the token is stored, the plain value is shown once in the bot.
+    manager
+      .create_table(
+        Table::create()
+          .table(ApiTokens::Table)
+          .if_not_exists()
+          .col(
+            ColumnDef::new(ApiTokens::TgUserId)
+              .big_integer()
+              .not_null()
+              .primary_key(),
+          )
+          .col(
+            ColumnDef::new(ApiTokens::TokenHash)
+              .string()
+              .not_null()
+              .unique_key(),
+          )
+          .col(ColumnDef::new(ApiTokens::CreatedAt).date_time().not_null())
+          .col(ColumnDef::new(ApiTokens::LastUsedAt).date_time().null())
+          .foreign_key(
+            ForeignKey::create()
+              .name("fk_api_tokens_user")
+              .from(ApiTokens::Table, ApiTokens::TgUserId)
+              .to(Users::Table, Users::TgUserId)
+              .on_delete(ForeignKeyAction::Cascade),
+          )
+          .to_owned(),
+      )
+      .await
+  }
+
+  async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager.drop_table(Table::drop().table(ApiTokens::Table).to_owned()).await
+  }
+}
+
+#[derive(DeriveIden)]
+pub enum ApiTokens {
+  Table,
+  TgUserId,
+  TokenHash,
+  CreatedAt,
+  LastUsedAt,
+}
@@ -0,0 +1,62 @@
+use sea_orm_migration::prelude::*;
+
+use super::m20251214_000001_create_users::Users;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+  async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager
+      .create_table(
+        Table::create()
+          .table(InstanceStats::Table)
+          .if_not_exists()
+          .col(ColumnDef::new(InstanceStats::TgUserId).big_integer().not_null())
+          .col(
+            ColumnDef::new(InstanceStats::InstanceId).string_len(64).not_null(),
+          )
+          .col(
+            ColumnDef::new(InstanceStats::RuntimeHours)
+              .double()
+              .not_null()
+              .default(0.0),
+          )
+          .col(ColumnDef::new(InstanceStats::Meta).json().null())
+          .col(ColumnDef::new(InstanceStats::FirstSeen).date_time().not_null())
+          .col(ColumnDef::new(InstanceStats::LastSeen).date_time().not_null())
+          .primary_key(
+            Index::create()
+              .col(InstanceStats::TgUserId)
+              .col(InstanceStats::InstanceId),
+          )
+          .foreign_key(
+            ForeignKey::create()
+              .name("fk_instance_stats_user")
+              .from(InstanceStats::Table, InstanceStats::TgUserId)
+              .to(Users::Table, Users::TgUserId)
+              .on_delete(ForeignKeyAction::Cascade),
+          )
+          .to_owned(),
+      )
+      .await
+  }
+
+  async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager
+      .drop_table(Table::drop().table(InstanceStats::Table).to_owned())
+      .await
+  }
+}
+
+#[derive(DeriveIden)]
+pub enum InstanceStats {
+  Table,
+  TgUserId,
+  InstanceId,
+  RuntimeHours,
+  Meta,
+  FirstSeen,
+  LastSeen,
+}
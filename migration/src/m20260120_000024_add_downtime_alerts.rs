@@ -0,0 +1,54 @@
+use sea_orm_migration::prelude::*;
+
+use super::m20260112_000015_create_user_settings::UserSettings;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+  async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    // Minutes without sessions before the user is pinged, null = disabled.
+    // SQLite only supports one column per ALTER TABLE
+    for column in [
+      SettingsExt::DowntimeAlertMins,
+      SettingsExt::FarmFromHour,
+      SettingsExt::FarmToHour,
+    ] {
+      manager
+        .alter_table(
+          Table::alter()
+            .table(UserSettings::Table)
+            .add_column(ColumnDef::new(column).integer().null())
+            .to_owned(),
+        )
+        .await?;
+    }
+    Ok(())
+  }
+
+  async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    for column in [
+      SettingsExt::DowntimeAlertMins,
+      SettingsExt::FarmFromHour,
+      SettingsExt::FarmToHour,
+    ] {
+      manager
+        .alter_table(
+          Table::alter()
+            .table(UserSettings::Table)
+            .drop_column(column)
+            .to_owned(),
+        )
+        .await?;
+    }
+    Ok(())
+  }
+}
+
+#[derive(DeriveIden)]
+enum SettingsExt {
+  DowntimeAlertMins,
+  FarmFromHour,
+  FarmToHour,
+}
@@ -0,0 +1,60 @@
+use sea_orm_migration::prelude::*;
+
+use super::m20251214_000001_create_users::Users;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+  async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager
+      .create_table(
+        Table::create()
+          .table(Goals::Table)
+          .if_not_exists()
+          .col(
+            ColumnDef::new(Goals::TgUserId)
+              .big_integer()
+              .not_null()
+              .primary_key(),
+          )
+          .col(ColumnDef::new(Goals::Kind).string().not_null())
+          .col(ColumnDef::new(Goals::Target).double().not_null())
+          // Progress of `day`, reset when telemetry arrives on a new day
+          .col(ColumnDef::new(Goals::Day).date().not_null())
+          .col(ColumnDef::new(Goals::Progress).double().not_null().default(0.0))
+          .col(ColumnDef::new(Goals::Streak).integer().not_null().default(0))
+          .col(
+            ColumnDef::new(Goals::BestStreak).integer().not_null().default(0),
+          )
+          .col(ColumnDef::new(Goals::LastAchieved).date().null())
+          .foreign_key(
+            ForeignKey::create()
+              .name("fk_goals_user")
+              .from(Goals::Table, Goals::TgUserId)
+              .to(Users::Table, Users::TgUserId)
+              .on_delete(ForeignKeyAction::Cascade),
+          )
+          .to_owned(),
+      )
+      .await
+  }
+
+  async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager.drop_table(Table::drop().table(Goals::Table).to_owned()).await
+  }
+}
+
+#[derive(DeriveIden)]
+pub enum Goals {
+  Table,
+  TgUserId,
+  Kind,
+  Target,
+  Day,
+  Progress,
+  Streak,
+  BestStreak,
+  LastAchieved,
+}
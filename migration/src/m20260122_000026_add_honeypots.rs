@@ -0,0 +1,89 @@
+use sea_orm_migration::prelude::*;
+
+use super::m20251214_000002_create_licenses::Licenses;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+  async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    // Keys that are never sold, any use of them means a leak
+    manager
+      .alter_table(
+        Table::alter()
+          .table(Licenses::Table)
+          .add_column(
+            ColumnDef::new(LicensesExt::IsHoneypot)
+              .boolean()
+              .not_null()
+              .default(false),
+          )
+          .to_owned(),
+      )
+      .await?;
+
+    manager
+      .create_table(
+        Table::create()
+          .table(Incidents::Table)
+          .if_not_exists()
+          .col(
+            ColumnDef::new(Incidents::Id)
+              .integer()
+              .not_null()
+              .auto_increment()
+              .primary_key(),
+          )
+          .col(ColumnDef::new(Incidents::Kind).string().not_null())
+          .col(ColumnDef::new(Incidents::LicenseKey).string().null())
+          .col(ColumnDef::new(Incidents::Ip).string().null())
+          .col(ColumnDef::new(Incidents::Hwid).string().null())
+          .col(ColumnDef::new(Incidents::Details).text().not_null())
+          .col(ColumnDef::new(Incidents::CreatedAt).date_time().not_null())
+          .to_owned(),
+      )
+      .await?;
+
+    manager
+      .create_index(
+        Index::create()
+          .name("idx_incidents_license_key")
+          .table(Incidents::Table)
+          .col(Incidents::LicenseKey)
+          .to_owned(),
+      )
+      .await
+  }
+
+  async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager
+      .drop_table(Table::drop().table(Incidents::Table).to_owned())
+      .await?;
+    manager
+      .alter_table(
+        Table::alter()
+          .table(Licenses::Table)
+          .drop_column(LicensesExt::IsHoneypot)
+          .to_owned(),
+      )
+      .await
+  }
+}
+
+#[derive(DeriveIden)]
+enum LicensesExt {
+  IsHoneypot,
+}
+
+#[derive(DeriveIden)]
+pub enum Incidents {
+  Table,
+  Id,
+  Kind,
+  LicenseKey,
+  Ip,
+  Hwid,
+  Details,
+  CreatedAt,
+}
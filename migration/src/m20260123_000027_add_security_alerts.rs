@@ -0,0 +1,67 @@
+use sea_orm_migration::prelude::*;
+
+use super::{
+  m20260110_000014_create_license_devices::LicenseDevices,
+  m20260112_000015_create_user_settings::UserSettings,
+};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+  async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    // ISO country code reported by the proxy in front of the server
+    manager
+      .alter_table(
+        Table::alter()
+          .table(LicenseDevices::Table)
+          .add_column(ColumnDef::new(DevicesExt::Country).string().null())
+          .to_owned(),
+      )
+      .await?;
+
+    manager
+      .alter_table(
+        Table::alter()
+          .table(UserSettings::Table)
+          .add_column(
+            ColumnDef::new(SettingsExt::SecurityAlerts)
+              .boolean()
+              .not_null()
+              .default(true),
+          )
+          .to_owned(),
+      )
+      .await
+  }
+
+  async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager
+      .alter_table(
+        Table::alter()
+          .table(UserSettings::Table)
+          .drop_column(SettingsExt::SecurityAlerts)
+          .to_owned(),
+      )
+      .await?;
+    manager
+      .alter_table(
+        Table::alter()
+          .table(LicenseDevices::Table)
+          .drop_column(DevicesExt::Country)
+          .to_owned(),
+      )
+      .await
+  }
+}
+
+#[derive(DeriveIden)]
+enum DevicesExt {
+  Country,
+}
+
+#[derive(DeriveIden)]
+enum SettingsExt {
+  SecurityAlerts,
+}
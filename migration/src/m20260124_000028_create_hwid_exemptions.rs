@@ -0,0 +1,49 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+  async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    // Machines or users allowed to run more licenses than the policy says.
+    // Exactly one of hwid / tg_user_id is set.
+    manager
+      .create_table(
+        Table::create()
+          .table(HwidExemptions::Table)
+          .if_not_exists()
+          .col(
+            ColumnDef::new(HwidExemptions::Id)
+              .integer()
+              .not_null()
+              .auto_increment()
+              .primary_key(),
+          )
+          .col(ColumnDef::new(HwidExemptions::Hwid).string().null())
+          .col(ColumnDef::new(HwidExemptions::TgUserId).big_integer().null())
+          .col(ColumnDef::new(HwidExemptions::Note).string().not_null())
+          .col(
+            ColumnDef::new(HwidExemptions::CreatedAt).date_time().not_null(),
+          )
+          .to_owned(),
+      )
+      .await
+  }
+
+  async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager
+      .drop_table(Table::drop().table(HwidExemptions::Table).to_owned())
+      .await
+  }
+}
+
+#[derive(DeriveIden)]
+pub enum HwidExemptions {
+  Table,
+  Id,
+  Hwid,
+  TgUserId,
+  Note,
+  CreatedAt,
+}
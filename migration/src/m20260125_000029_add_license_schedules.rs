@@ -0,0 +1,74 @@
+use sea_orm_migration::prelude::*;
+
+use super::{
+  m20251214_000002_create_licenses::Licenses,
+  m20260112_000015_create_user_settings::UserSettings,
+};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+  async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    // Local hours the key may be used in, unset means any time
+    for column in [LicensesExt::AllowedFromHour, LicensesExt::AllowedToHour] {
+      manager
+        .alter_table(
+          Table::alter()
+            .table(Licenses::Table)
+            .add_column(ColumnDef::new(column).integer().null())
+            .to_owned(),
+        )
+        .await?;
+    }
+
+    manager
+      .alter_table(
+        Table::alter()
+          .table(UserSettings::Table)
+          .add_column(
+            ColumnDef::new(SettingsExt::UtcOffsetMins)
+              .integer()
+              .not_null()
+              .default(0),
+          )
+          .to_owned(),
+      )
+      .await
+  }
+
+  async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager
+      .alter_table(
+        Table::alter()
+          .table(UserSettings::Table)
+          .drop_column(SettingsExt::UtcOffsetMins)
+          .to_owned(),
+      )
+      .await?;
+
+    for column in [LicensesExt::AllowedFromHour, LicensesExt::AllowedToHour] {
+      manager
+        .alter_table(
+          Table::alter()
+            .table(Licenses::Table)
+            .drop_column(column)
+            .to_owned(),
+        )
+        .await?;
+    }
+    Ok(())
+  }
+}
+
+#[derive(DeriveIden)]
+enum LicensesExt {
+  AllowedFromHour,
+  AllowedToHour,
+}
+
+#[derive(DeriveIden)]
+enum SettingsExt {
+  UtcOffsetMins,
+}
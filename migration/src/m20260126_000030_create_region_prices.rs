@@ -0,0 +1,106 @@
+use sea_orm_migration::prelude::*;
+
+use super::{
+  m20251214_000002_create_licenses::Licenses,
+  m20260104_000010_add_referral_system::Transactions,
+  m20260112_000015_create_user_settings::UserSettings,
+};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+  async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    // Price of regional plans in percent of the base price, by country code
+    manager
+      .create_table(
+        Table::create()
+          .table(RegionPrices::Table)
+          .if_not_exists()
+          .col(
+            ColumnDef::new(RegionPrices::Country)
+              .string()
+              .not_null()
+              .primary_key(),
+          )
+          .col(ColumnDef::new(RegionPrices::Percent).integer().not_null())
+          .col(ColumnDef::new(RegionPrices::CreatedAt).date_time().not_null())
+          .to_owned(),
+      )
+      .await?;
+
+    // Self-declared country, falls back to the one seen by the proxy
+    manager
+      .alter_table(
+        Table::alter()
+          .table(UserSettings::Table)
+          .add_column(ColumnDef::new(Ext::Country).string().null())
+          .to_owned(),
+      )
+      .await?;
+
+    // Country a regionally priced license is bound to
+    manager
+      .alter_table(
+        Table::alter()
+          .table(Licenses::Table)
+          .add_column(ColumnDef::new(Ext::Region).string().null())
+          .to_owned(),
+      )
+      .await?;
+
+    manager
+      .alter_table(
+        Table::alter()
+          .table(Transactions::Table)
+          .add_column(ColumnDef::new(Ext::Region).string().null())
+          .to_owned(),
+      )
+      .await
+  }
+
+  async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager
+      .alter_table(
+        Table::alter()
+          .table(Transactions::Table)
+          .drop_column(Ext::Region)
+          .to_owned(),
+      )
+      .await?;
+    manager
+      .alter_table(
+        Table::alter()
+          .table(Licenses::Table)
+          .drop_column(Ext::Region)
+          .to_owned(),
+      )
+      .await?;
+    manager
+      .alter_table(
+        Table::alter()
+          .table(UserSettings::Table)
+          .drop_column(Ext::Country)
+          .to_owned(),
+      )
+      .await?;
+    manager
+      .drop_table(Table::drop().table(RegionPrices::Table).to_owned())
+      .await
+  }
+}
+
+#[derive(DeriveIden)]
+pub enum RegionPrices {
+  Table,
+  Country,
+  Percent,
+  CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum Ext {
+  Country,
+  Region,
+}
@@ -0,0 +1,56 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+  async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    // Limited happy-hour windows with an extra discount on extensions
+    manager
+      .create_table(
+        Table::create()
+          .table(Sales::Table)
+          .if_not_exists()
+          .col(
+            ColumnDef::new(Sales::Id)
+              .integer()
+              .not_null()
+              .auto_increment()
+              .primary_key(),
+          )
+          .col(ColumnDef::new(Sales::Name).string().not_null())
+          .col(ColumnDef::new(Sales::Percent).integer().not_null())
+          .col(ColumnDef::new(Sales::StartsAt).date_time().not_null())
+          .col(ColumnDef::new(Sales::EndsAt).date_time().not_null())
+          .col(ColumnDef::new(Sales::CreatedAt).date_time().not_null())
+          .to_owned(),
+      )
+      .await?;
+
+    manager
+      .create_index(
+        Index::create()
+          .name("idx_sales_ends_at")
+          .table(Sales::Table)
+          .col(Sales::EndsAt)
+          .to_owned(),
+      )
+      .await
+  }
+
+  async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager.drop_table(Table::drop().table(Sales::Table).to_owned()).await
+  }
+}
+
+#[derive(DeriveIden)]
+pub enum Sales {
+  Table,
+  Id,
+  Name,
+  Percent,
+  StartsAt,
+  EndsAt,
+  CreatedAt,
+}
@@ -0,0 +1,42 @@
+use sea_orm_migration::prelude::*;
+
+use super::m20251214_000001_create_users::Users;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+  async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    // Self-service deletion: requested_at starts the cooling-off period,
+    // deleted_at marks accounts whose data was already anonymized
+    for column in [UsersExt::DeletionRequestedAt, UsersExt::DeletedAt] {
+      manager
+        .alter_table(
+          Table::alter()
+            .table(Users::Table)
+            .add_column(ColumnDef::new(column).date_time().null())
+            .to_owned(),
+        )
+        .await?;
+    }
+    Ok(())
+  }
+
+  async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    for column in [UsersExt::DeletionRequestedAt, UsersExt::DeletedAt] {
+      manager
+        .alter_table(
+          Table::alter().table(Users::Table).drop_column(column).to_owned(),
+        )
+        .await?;
+    }
+    Ok(())
+  }
+}
+
+#[derive(DeriveIden)]
+enum UsersExt {
+  DeletionRequestedAt,
+  DeletedAt,
+}
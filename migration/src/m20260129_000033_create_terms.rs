@@ -0,0 +1,92 @@
+use sea_orm_migration::prelude::*;
+
+use super::m20251214_000001_create_users::Users;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+  async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    // Every published revision of the terms, the highest version is current
+    manager
+      .create_table(
+        Table::create()
+          .table(Terms::Table)
+          .if_not_exists()
+          .col(
+            ColumnDef::new(Terms::Version)
+              .integer()
+              .not_null()
+              .auto_increment()
+              .primary_key(),
+          )
+          .col(ColumnDef::new(Terms::Text).text().not_null())
+          .col(ColumnDef::new(Terms::CreatedAt).date_time().not_null())
+          .to_owned(),
+      )
+      .await?;
+
+    manager
+      .create_table(
+        Table::create()
+          .table(TermsAcceptances::Table)
+          .if_not_exists()
+          .col(
+            ColumnDef::new(TermsAcceptances::TgUserId)
+              .big_integer()
+              .not_null(),
+          )
+          .col(ColumnDef::new(TermsAcceptances::Version).integer().not_null())
+          .col(
+            ColumnDef::new(TermsAcceptances::AcceptedAt)
+              .date_time()
+              .not_null(),
+          )
+          .primary_key(
+            Index::create()
+              .col(TermsAcceptances::TgUserId)
+              .col(TermsAcceptances::Version),
+          )
+          .foreign_key(
+            ForeignKey::create()
+              .name("fk_terms_acceptances_user")
+              .from(TermsAcceptances::Table, TermsAcceptances::TgUserId)
+              .to(Users::Table, Users::TgUserId)
+              .on_delete(ForeignKeyAction::Cascade),
+          )
+          .foreign_key(
+            ForeignKey::create()
+              .name("fk_terms_acceptances_terms")
+              .from(TermsAcceptances::Table, TermsAcceptances::Version)
+              .to(Terms::Table, Terms::Version)
+              .on_delete(ForeignKeyAction::Cascade),
+          )
+          .to_owned(),
+      )
+      .await
+  }
+
+  async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager
+      .drop_table(Table::drop().table(TermsAcceptances::Table).to_owned())
+      .await?;
+    manager.drop_table(Table::drop().table(Terms::Table).to_owned()).await
+  }
+}
+
+#[derive(DeriveIden)]
+enum Terms {
+  Table,
+  Version,
+  Text,
+  CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum TermsAcceptances {
+  Table,
+  TgUserId,
+  Version,
+  AcceptedAt,
+}
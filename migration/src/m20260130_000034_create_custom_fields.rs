@@ -0,0 +1,47 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+  async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    // Free-form admin metadata, target is a user id or a license key
+    // depending on scope, so deployments don't need a migration per field
+    manager
+      .create_table(
+        Table::create()
+          .table(CustomFields::Table)
+          .if_not_exists()
+          .col(ColumnDef::new(CustomFields::Scope).string().not_null())
+          .col(ColumnDef::new(CustomFields::Target).string().not_null())
+          .col(ColumnDef::new(CustomFields::Name).string().not_null())
+          .col(ColumnDef::new(CustomFields::Value).text().not_null())
+          .col(ColumnDef::new(CustomFields::UpdatedAt).date_time().not_null())
+          .primary_key(
+            Index::create()
+              .col(CustomFields::Scope)
+              .col(CustomFields::Target)
+              .col(CustomFields::Name),
+          )
+          .to_owned(),
+      )
+      .await
+  }
+
+  async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager
+      .drop_table(Table::drop().table(CustomFields::Table).to_owned())
+      .await
+  }
+}
+
+#[derive(DeriveIden)]
+enum CustomFields {
+  Table,
+  Scope,
+  Target,
+  Name,
+  Value,
+  UpdatedAt,
+}
@@ -0,0 +1,108 @@
+use sea_orm_migration::prelude::*;
+
+use super::{
+  m20251214_000002_create_licenses::Licenses,
+  m20251214_000004_create_builds::Builds,
+  m20260118_000022_create_api_tokens::ApiTokens,
+  m20260119_000023_create_instance_stats::InstanceStats,
+};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+  async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager
+      .create_table(
+        Table::create()
+          .table(Products::Table)
+          .if_not_exists()
+          .col(ColumnDef::new(Products::Slug).string().not_null().primary_key())
+          .col(ColumnDef::new(Products::Name).string().not_null())
+          .col(
+            ColumnDef::new(Products::PricePercent)
+              .integer()
+              .not_null()
+              .default(100),
+          )
+          .col(ColumnDef::new(Products::CreatedAt).date_time().not_null())
+          .to_owned(),
+      )
+      .await?;
+
+    // Everything sold so far belongs to the original panel
+    manager
+      .get_connection()
+      .execute_unprepared(
+        "INSERT INTO products (slug, name, price_percent, created_at) \
+        VALUES ('default', 'YACS Panel', 100, CURRENT_TIMESTAMP)",
+      )
+      .await?;
+
+    let tables: [DynIden; 3] = [
+      Licenses::Table.into_iden(),
+      Builds::Table.into_iden(),
+      InstanceStats::Table.into_iden(),
+    ];
+    for table in tables {
+      manager
+        .alter_table(
+          Table::alter()
+            .table(table)
+            .add_column(
+              ColumnDef::new(ProductExt::Product)
+                .string()
+                .not_null()
+                .default("default"),
+            )
+            .to_owned(),
+        )
+        .await?;
+    }
+
+    // Tokens without a product see every product of the user
+    manager
+      .alter_table(
+        Table::alter()
+          .table(ApiTokens::Table)
+          .add_column(ColumnDef::new(ProductExt::Product).string().null())
+          .to_owned(),
+      )
+      .await
+  }
+
+  async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    let tables: [DynIden; 4] = [
+      Licenses::Table.into_iden(),
+      Builds::Table.into_iden(),
+      InstanceStats::Table.into_iden(),
+      ApiTokens::Table.into_iden(),
+    ];
+    for table in tables {
+      manager
+        .alter_table(
+          Table::alter()
+            .table(table)
+            .drop_column(ProductExt::Product)
+            .to_owned(),
+        )
+        .await?;
+    }
+    manager.drop_table(Table::drop().table(Products::Table).to_owned()).await
+  }
+}
+
+#[derive(DeriveIden)]
+pub enum Products {
+  Table,
+  Slug,
+  Name,
+  PricePercent,
+  CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum ProductExt {
+  Product,
+}
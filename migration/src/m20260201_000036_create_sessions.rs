@@ -0,0 +1,78 @@
+use sea_orm_migration::prelude::*;
+
+use super::m20251214_000002_create_licenses::Licenses;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+  async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    // Durable copy of the in-memory sessions: open rows are restored on
+    // startup, closed ones stay as history
+    manager
+      .create_table(
+        Table::create()
+          .table(Sessions::Table)
+          .if_not_exists()
+          .col(
+            ColumnDef::new(Sessions::SessionId)
+              .string()
+              .not_null()
+              .primary_key(),
+          )
+          .col(ColumnDef::new(Sessions::LicenseKey).string().not_null())
+          .col(ColumnDef::new(Sessions::Hwid).string().null())
+          .col(ColumnDef::new(Sessions::StartedAt).date_time().not_null())
+          .col(ColumnDef::new(Sessions::LastSeen).date_time().not_null())
+          .col(ColumnDef::new(Sessions::EndedAt).date_time().null())
+          .col(ColumnDef::new(Sessions::EndReason).string().null())
+          .foreign_key(
+            ForeignKey::create()
+              .name("fk_sessions_license")
+              .from(Sessions::Table, Sessions::LicenseKey)
+              .to(Licenses::Table, Licenses::Key)
+              .on_delete(ForeignKeyAction::Cascade)
+              .on_update(ForeignKeyAction::Cascade),
+          )
+          .to_owned(),
+      )
+      .await?;
+
+    manager
+      .create_index(
+        Index::create()
+          .name("idx_sessions_license_key")
+          .table(Sessions::Table)
+          .col(Sessions::LicenseKey)
+          .to_owned(),
+      )
+      .await?;
+
+    manager
+      .create_index(
+        Index::create()
+          .name("idx_sessions_ended_at")
+          .table(Sessions::Table)
+          .col(Sessions::EndedAt)
+          .to_owned(),
+      )
+      .await
+  }
+
+  async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager.drop_table(Table::drop().table(Sessions::Table).to_owned()).await
+  }
+}
+
+#[derive(DeriveIden)]
+enum Sessions {
+  Table,
+  SessionId,
+  LicenseKey,
+  Hwid,
+  StartedAt,
+  LastSeen,
+  EndedAt,
+  EndReason,
+}
@@ -0,0 +1,78 @@
+use sea_orm_migration::prelude::*;
+
+use super::{
+  m20251214_000001_create_users::Users,
+  m20260131_000035_add_products::Products,
+};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+  async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    // One extra bot per product, branded for a partner
+    manager
+      .create_table(
+        Table::create()
+          .table(Storefronts::Table)
+          .if_not_exists()
+          .col(
+            ColumnDef::new(Storefronts::Product)
+              .string()
+              .not_null()
+              .primary_key(),
+          )
+          .col(ColumnDef::new(Storefronts::BotToken).string().not_null())
+          .col(ColumnDef::new(Storefronts::Title).string().not_null())
+          .col(ColumnDef::new(Storefronts::Support).string().not_null())
+          .col(ColumnDef::new(Storefronts::Welcome).text().null())
+          .col(ColumnDef::new(Storefronts::CreatedAt).date_time().not_null())
+          .foreign_key(
+            ForeignKey::create()
+              .name("fk_storefronts_product")
+              .from(Storefronts::Table, Storefronts::Product)
+              .to(Products::Table, Products::Slug)
+              .on_delete(ForeignKeyAction::Cascade),
+          )
+          .to_owned(),
+      )
+      .await?;
+
+    // Bot the user talks to, notifications are sent through it
+    manager
+      .alter_table(
+        Table::alter()
+          .table(Users::Table)
+          .add_column(ColumnDef::new(Storefronts::Storefront).string().null())
+          .to_owned(),
+      )
+      .await
+  }
+
+  async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager
+      .alter_table(
+        Table::alter()
+          .table(Users::Table)
+          .drop_column(Storefronts::Storefront)
+          .to_owned(),
+      )
+      .await?;
+    manager
+      .drop_table(Table::drop().table(Storefronts::Table).to_owned())
+      .await
+  }
+}
+
+#[derive(DeriveIden)]
+enum Storefronts {
+  Table,
+  Product,
+  BotToken,
+  Title,
+  Support,
+  Welcome,
+  CreatedAt,
+  Storefront,
+}
@@ -0,0 +1,68 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+  async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager
+      .create_table(
+        Table::create()
+          .table(Plans::Table)
+          .if_not_exists()
+          .col(ColumnDef::new(Plans::Name).string().not_null().primary_key())
+          .col(ColumnDef::new(Plans::Title).string().not_null())
+          .col(ColumnDef::new(Plans::Days).integer().not_null())
+          .col(ColumnDef::new(Plans::PriceNano).big_integer().not_null())
+          .col(
+            ColumnDef::new(Plans::MaxSessions)
+              .integer()
+              .not_null()
+              .default(1),
+          )
+          .col(
+            ColumnDef::new(Plans::Active)
+              .boolean()
+              .not_null()
+              .default(true),
+          )
+          .col(ColumnDef::new(Plans::UpdatedAt).date_time().not_null())
+          .to_owned(),
+      )
+      .await?;
+
+    // The prices that were hardcoded until now
+    manager
+      .get_connection()
+      .execute_unprepared(
+        "INSERT INTO plans \
+        (name, title, days, price_nano, max_sessions, active, updated_at) \
+        VALUES \
+        ('trial', '1 Day Trial', 1, 1000000, 1, 1, CURRENT_TIMESTAMP), \
+        ('month', '1 Month', 30, 10000000, 1, 1, CURRENT_TIMESTAMP), \
+        ('quarter', '3 Months', 90, 25000000, 1, 1, CURRENT_TIMESTAMP), \
+        ('halfyear', '6 Months', 180, 60000000, 1, 1, CURRENT_TIMESTAMP), \
+        ('year', '12 Months', 365, 120000000, 1, 1, CURRENT_TIMESTAMP), \
+        ('night', 'Night Month', 30, 6000000, 1, 1, CURRENT_TIMESTAMP)",
+      )
+      .await?;
+    Ok(())
+  }
+
+  async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager.drop_table(Table::drop().table(Plans::Table).to_owned()).await
+  }
+}
+
+#[derive(DeriveIden)]
+enum Plans {
+  Table,
+  Name,
+  Title,
+  Days,
+  PriceNano,
+  MaxSessions,
+  Active,
+  UpdatedAt,
+}
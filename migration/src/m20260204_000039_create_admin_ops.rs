@@ -0,0 +1,69 @@
+use sea_orm_migration::prelude::*;
+
+use super::m20251214_000001_create_users::Users;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+  async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    // Balance changes made by admins, kept so mistakes can be undone
+    manager
+      .create_table(
+        Table::create()
+          .table(AdminOps::Table)
+          .if_not_exists()
+          .col(
+            ColumnDef::new(AdminOps::Id)
+              .integer()
+              .not_null()
+              .auto_increment()
+              .primary_key(),
+          )
+          .col(ColumnDef::new(AdminOps::AdminId).big_integer().not_null())
+          .col(ColumnDef::new(AdminOps::UserId).big_integer().not_null())
+          .col(ColumnDef::new(AdminOps::Kind).string().not_null())
+          .col(ColumnDef::new(AdminOps::Amount).big_integer().not_null())
+          .col(ColumnDef::new(AdminOps::CreatedAt).date_time().not_null())
+          .col(ColumnDef::new(AdminOps::UndoneBy).big_integer().null())
+          .col(ColumnDef::new(AdminOps::UndoneAt).date_time().null())
+          .foreign_key(
+            ForeignKey::create()
+              .name("fk_admin_ops_user")
+              .from(AdminOps::Table, AdminOps::UserId)
+              .to(Users::Table, Users::TgUserId)
+              .on_delete(ForeignKeyAction::Cascade),
+          )
+          .to_owned(),
+      )
+      .await?;
+
+    manager
+      .create_index(
+        Index::create()
+          .name("idx_admin_ops_created_at")
+          .table(AdminOps::Table)
+          .col(AdminOps::CreatedAt)
+          .to_owned(),
+      )
+      .await
+  }
+
+  async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager.drop_table(Table::drop().table(AdminOps::Table).to_owned()).await
+  }
+}
+
+#[derive(DeriveIden)]
+enum AdminOps {
+  Table,
+  Id,
+  AdminId,
+  UserId,
+  Kind,
+  Amount,
+  CreatedAt,
+  UndoneBy,
+  UndoneAt,
+}
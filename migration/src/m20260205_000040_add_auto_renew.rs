@@ -0,0 +1,42 @@
+use sea_orm_migration::prelude::*;
+
+use super::m20251214_000002_create_licenses::Licenses;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+  async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    // Opt-in renewal from balance shortly before the key expires
+    manager
+      .alter_table(
+        Table::alter()
+          .table(Licenses::Table)
+          .add_column(
+            ColumnDef::new(LicensesExt::AutoRenew)
+              .boolean()
+              .not_null()
+              .default(false),
+          )
+          .to_owned(),
+      )
+      .await
+  }
+
+  async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager
+      .alter_table(
+        Table::alter()
+          .table(Licenses::Table)
+          .drop_column(LicensesExt::AutoRenew)
+          .to_owned(),
+      )
+      .await
+  }
+}
+
+#[derive(DeriveIden)]
+enum LicensesExt {
+  AutoRenew,
+}
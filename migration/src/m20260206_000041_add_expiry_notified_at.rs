@@ -0,0 +1,39 @@
+use sea_orm_migration::prelude::*;
+
+use super::m20251214_000002_create_licenses::Licenses;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+  async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    // Last expiry reminder, so each one goes out once
+    manager
+      .alter_table(
+        Table::alter()
+          .table(Licenses::Table)
+          .add_column(
+            ColumnDef::new(LicensesExt::ExpiryNotifiedAt).date_time().null(),
+          )
+          .to_owned(),
+      )
+      .await
+  }
+
+  async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager
+      .alter_table(
+        Table::alter()
+          .table(Licenses::Table)
+          .drop_column(LicensesExt::ExpiryNotifiedAt)
+          .to_owned(),
+      )
+      .await
+  }
+}
+
+#[derive(DeriveIden)]
+enum LicensesExt {
+  ExpiryNotifiedAt,
+}
@@ -0,0 +1,78 @@
+use sea_orm_migration::prelude::*;
+
+use super::m20251214_000001_create_users::Users;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+  async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    // Temporary referral promotions multiplying creators' commission
+    manager
+      .create_table(
+        Table::create()
+          .table(CommissionBoosts::Table)
+          .if_not_exists()
+          .col(
+            ColumnDef::new(CommissionBoosts::Id)
+              .integer()
+              .not_null()
+              .auto_increment()
+              .primary_key(),
+          )
+          .col(ColumnDef::new(CommissionBoosts::Percent).integer().not_null())
+          .col(ColumnDef::new(CommissionBoosts::CreatorId).big_integer().null())
+          .col(
+            ColumnDef::new(CommissionBoosts::StartsAt).date_time().not_null(),
+          )
+          .col(ColumnDef::new(CommissionBoosts::EndsAt).date_time().not_null())
+          .col(
+            ColumnDef::new(CommissionBoosts::CreatedAt).date_time().not_null(),
+          )
+          .col(
+            ColumnDef::new(CommissionBoosts::EndAnnounced)
+              .boolean()
+              .not_null()
+              .default(false),
+          )
+          .foreign_key(
+            ForeignKey::create()
+              .name("fk_commission_boosts_creator")
+              .from(CommissionBoosts::Table, CommissionBoosts::CreatorId)
+              .to(Users::Table, Users::TgUserId)
+              .on_delete(ForeignKeyAction::Cascade),
+          )
+          .to_owned(),
+      )
+      .await?;
+
+    manager
+      .create_index(
+        Index::create()
+          .name("idx_commission_boosts_ends_at")
+          .table(CommissionBoosts::Table)
+          .col(CommissionBoosts::EndsAt)
+          .to_owned(),
+      )
+      .await
+  }
+
+  async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager
+      .drop_table(Table::drop().table(CommissionBoosts::Table).to_owned())
+      .await
+  }
+}
+
+#[derive(DeriveIden)]
+enum CommissionBoosts {
+  Table,
+  Id,
+  Percent,
+  CreatorId,
+  StartsAt,
+  EndsAt,
+  CreatedAt,
+  EndAnnounced,
+}
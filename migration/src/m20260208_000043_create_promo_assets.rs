@@ -0,0 +1,48 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+  async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    // Banners and copy templates creators share with their invite link
+    manager
+      .create_table(
+        Table::create()
+          .table(PromoAssets::Table)
+          .if_not_exists()
+          .col(
+            ColumnDef::new(PromoAssets::Id)
+              .integer()
+              .not_null()
+              .auto_increment()
+              .primary_key(),
+          )
+          .col(ColumnDef::new(PromoAssets::Kind).string().not_null())
+          .col(ColumnDef::new(PromoAssets::Title).string().not_null())
+          .col(ColumnDef::new(PromoAssets::FileId).string().null())
+          .col(ColumnDef::new(PromoAssets::Text).text().null())
+          .col(ColumnDef::new(PromoAssets::CreatedAt).date_time().not_null())
+          .to_owned(),
+      )
+      .await
+  }
+
+  async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager
+      .drop_table(Table::drop().table(PromoAssets::Table).to_owned())
+      .await
+  }
+}
+
+#[derive(DeriveIden)]
+enum PromoAssets {
+  Table,
+  Id,
+  Kind,
+  Title,
+  FileId,
+  Text,
+  CreatedAt,
+}
@@ -0,0 +1,133 @@
+use sea_orm_migration::prelude::*;
+
+use super::{
+  m20251214_000001_create_users::Users,
+  m20251214_000006_create_free_games::FreeGames,
+  m20251218_000009_create_free_items::FreeItems,
+};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+  async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    // Freebies added by admins survive the Steam cache refresh
+    manager
+      .alter_table(
+        Table::alter()
+          .table(FreeGames::Table)
+          .add_column(
+            ColumnDef::new(FreebiesExt::Manual)
+              .boolean()
+              .not_null()
+              .default(false),
+          )
+          .to_owned(),
+      )
+      .await?;
+    manager
+      .alter_table(
+        Table::alter()
+          .table(FreeItems::Table)
+          .add_column(
+            ColumnDef::new(FreebiesExt::Manual)
+              .boolean()
+              .not_null()
+              .default(false),
+          )
+          .to_owned(),
+      )
+      .await?;
+
+    // Freebies users claimed from the bot
+    manager
+      .create_table(
+        Table::create()
+          .table(FreebieClaims::Table)
+          .if_not_exists()
+          .col(
+            ColumnDef::new(FreebieClaims::Id)
+              .integer()
+              .not_null()
+              .auto_increment()
+              .primary_key(),
+          )
+          .col(ColumnDef::new(FreebieClaims::TgUserId).big_integer().not_null())
+          .col(ColumnDef::new(FreebieClaims::Kind).string().not_null())
+          .col(ColumnDef::new(FreebieClaims::RefId).integer().not_null())
+          .col(ColumnDef::new(FreebieClaims::Name).string().not_null())
+          .col(ColumnDef::new(FreebieClaims::ClaimedAt).date_time().not_null())
+          .foreign_key(
+            ForeignKey::create()
+              .name("fk_freebie_claims_user")
+              .from(FreebieClaims::Table, FreebieClaims::TgUserId)
+              .to(Users::Table, Users::TgUserId)
+              .on_delete(ForeignKeyAction::Cascade),
+          )
+          .to_owned(),
+      )
+      .await?;
+
+    manager
+      .create_index(
+        Index::create()
+          .name("idx_freebie_claims_unique")
+          .table(FreebieClaims::Table)
+          .col(FreebieClaims::TgUserId)
+          .col(FreebieClaims::Kind)
+          .col(FreebieClaims::RefId)
+          .unique()
+          .to_owned(),
+      )
+      .await?;
+
+    manager
+      .create_index(
+        Index::create()
+          .name("idx_freebie_claims_claimed_at")
+          .table(FreebieClaims::Table)
+          .col(FreebieClaims::ClaimedAt)
+          .to_owned(),
+      )
+      .await
+  }
+
+  async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager
+      .drop_table(Table::drop().table(FreebieClaims::Table).to_owned())
+      .await?;
+    manager
+      .alter_table(
+        Table::alter()
+          .table(FreeItems::Table)
+          .drop_column(FreebiesExt::Manual)
+          .to_owned(),
+      )
+      .await?;
+    manager
+      .alter_table(
+        Table::alter()
+          .table(FreeGames::Table)
+          .drop_column(FreebiesExt::Manual)
+          .to_owned(),
+      )
+      .await
+  }
+}
+
+#[derive(DeriveIden)]
+enum FreebiesExt {
+  Manual,
+}
+
+#[derive(DeriveIden)]
+enum FreebieClaims {
+  Table,
+  Id,
+  TgUserId,
+  Kind,
+  RefId,
+  Name,
+  ClaimedAt,
+}
@@ -0,0 +1,62 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+  async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager
+      .create_table(
+        Table::create()
+          .table(PromoCampaigns::Table)
+          .if_not_exists()
+          .col(
+            ColumnDef::new(PromoCampaigns::Code)
+              .string()
+              .not_null()
+              .primary_key(),
+          )
+          .col(ColumnDef::new(PromoCampaigns::LicenseType).text().not_null())
+          .col(ColumnDef::new(PromoCampaigns::Days).integer().not_null())
+          .col(ColumnDef::new(PromoCampaigns::MaxClaims).integer().null())
+          .col(ColumnDef::new(PromoCampaigns::StartsAt).date_time().not_null())
+          .col(ColumnDef::new(PromoCampaigns::EndsAt).date_time().not_null())
+          .col(
+            ColumnDef::new(PromoCampaigns::CreatedAt).date_time().not_null(),
+          )
+          .to_owned(),
+      )
+      .await?;
+
+    // The free trial week that was hardcoded until now
+    manager
+      .get_connection()
+      .execute_unprepared(
+        "INSERT INTO promo_campaigns \
+        (code, license_type, days, max_claims, starts_at, ends_at, created_at) \
+        VALUES ('first_promo', 'trial', 7, NULL, \
+        '2025-12-14 13:00:00', '2025-12-21 23:59:59', CURRENT_TIMESTAMP)",
+      )
+      .await?;
+    Ok(())
+  }
+
+  async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager
+      .drop_table(Table::drop().table(PromoCampaigns::Table).to_owned())
+      .await
+  }
+}
+
+#[derive(DeriveIden)]
+enum PromoCampaigns {
+  Table,
+  Code,
+  LicenseType,
+  Days,
+  MaxClaims,
+  StartsAt,
+  EndsAt,
+  CreatedAt,
+}
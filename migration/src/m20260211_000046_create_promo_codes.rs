@@ -0,0 +1,44 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+  async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    // One-off codes for giveaways, redeemed codes land in claimed_promos
+    manager
+      .create_table(
+        Table::create()
+          .table(PromoCodes::Table)
+          .if_not_exists()
+          .col(
+            ColumnDef::new(PromoCodes::Code).string().not_null().primary_key(),
+          )
+          .col(ColumnDef::new(PromoCodes::Grant).text().not_null())
+          .col(ColumnDef::new(PromoCodes::Amount).big_integer().not_null())
+          .col(ColumnDef::new(PromoCodes::MaxUses).integer().null())
+          .col(ColumnDef::new(PromoCodes::ExpiresAt).date_time().null())
+          .col(ColumnDef::new(PromoCodes::CreatedAt).date_time().not_null())
+          .to_owned(),
+      )
+      .await
+  }
+
+  async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager
+      .drop_table(Table::drop().table(PromoCodes::Table).to_owned())
+      .await
+  }
+}
+
+#[derive(DeriveIden)]
+enum PromoCodes {
+  Table,
+  Code,
+  Grant,
+  Amount,
+  MaxUses,
+  ExpiresAt,
+  CreatedAt,
+}
@@ -0,0 +1,47 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+  async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    // Client feature overrides for a single user or license,
+    // target is a user id or a license key depending on scope
+    manager
+      .create_table(
+        Table::create()
+          .table(FeatureFlags::Table)
+          .if_not_exists()
+          .col(ColumnDef::new(FeatureFlags::Scope).string().not_null())
+          .col(ColumnDef::new(FeatureFlags::Target).string().not_null())
+          .col(ColumnDef::new(FeatureFlags::Feature).string().not_null())
+          .col(ColumnDef::new(FeatureFlags::Enabled).boolean().not_null())
+          .col(ColumnDef::new(FeatureFlags::UpdatedAt).date_time().not_null())
+          .primary_key(
+            Index::create()
+              .col(FeatureFlags::Scope)
+              .col(FeatureFlags::Target)
+              .col(FeatureFlags::Feature),
+          )
+          .to_owned(),
+      )
+      .await
+  }
+
+  async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager
+      .drop_table(Table::drop().table(FeatureFlags::Table).to_owned())
+      .await
+  }
+}
+
+#[derive(DeriveIden)]
+enum FeatureFlags {
+  Table,
+  Scope,
+  Target,
+  Feature,
+  Enabled,
+  UpdatedAt,
+}
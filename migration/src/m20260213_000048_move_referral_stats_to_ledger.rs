@@ -0,0 +1,110 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+  async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    // Referral stats were counted twice: on the referrer and as
+    // referral_bonus transactions. The ledger becomes the only source, so
+    // sales it misses (zero commission, or from before it existed) are
+    // carried over as bonus rows, the first one holding the missing amount.
+    manager
+      .get_connection()
+      .execute_unprepared(
+        "WITH RECURSIVE gap(user_id, sales, earnings) AS ( \
+          SELECT u.tg_user_id, \
+            u.referral_sales - (SELECT COUNT(*) FROM transactions t \
+              WHERE t.user_id = u.tg_user_id \
+              AND t.tx_type = 'referral_bonus'), \
+            u.referral_earnings - (SELECT COALESCE(SUM(t.amount), 0) \
+              FROM transactions t WHERE t.user_id = u.tg_user_id \
+              AND t.tx_type = 'referral_bonus') \
+          FROM users u \
+        ), \
+        missing(user_id, n, total, earnings) AS ( \
+          SELECT user_id, 1, MAX(sales, 1), earnings FROM gap \
+          WHERE sales > 0 OR earnings <> 0 \
+          UNION ALL \
+          SELECT user_id, n + 1, total, earnings FROM missing \
+          WHERE n < total \
+        ) \
+        INSERT INTO transactions \
+          (user_id, amount, tx_type, description, referrer_id, region, \
+          created_at) \
+        SELECT user_id, CASE WHEN n = 1 THEN earnings ELSE 0 END, \
+          'referral_bonus', 'Referral stats carried over', NULL, NULL, \
+          CURRENT_TIMESTAMP \
+        FROM missing",
+      )
+      .await?;
+
+    manager
+      .alter_table(
+        Table::alter()
+          .table(Users::Table)
+          .drop_column(Users::ReferralSales)
+          .to_owned(),
+      )
+      .await?;
+    manager
+      .alter_table(
+        Table::alter()
+          .table(Users::Table)
+          .drop_column(Users::ReferralEarnings)
+          .to_owned(),
+      )
+      .await
+  }
+
+  async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager
+      .alter_table(
+        Table::alter()
+          .table(Users::Table)
+          .add_column(
+            ColumnDef::new(Users::ReferralSales)
+              .integer()
+              .not_null()
+              .default(0),
+          )
+          .to_owned(),
+      )
+      .await?;
+    manager
+      .alter_table(
+        Table::alter()
+          .table(Users::Table)
+          .add_column(
+            ColumnDef::new(Users::ReferralEarnings)
+              .big_integer()
+              .not_null()
+              .default(0),
+          )
+          .to_owned(),
+      )
+      .await?;
+
+    manager
+      .get_connection()
+      .execute_unprepared(
+        "UPDATE users SET \
+          referral_sales = (SELECT COUNT(*) FROM transactions t \
+            WHERE t.user_id = users.tg_user_id \
+            AND t.tx_type = 'referral_bonus'), \
+          referral_earnings = (SELECT COALESCE(SUM(t.amount), 0) \
+            FROM transactions t WHERE t.user_id = users.tg_user_id \
+            AND t.tx_type = 'referral_bonus')",
+      )
+      .await?;
+    Ok(())
+  }
+}
+
+#[derive(DeriveIden)]
+enum Users {
+  Table,
+  ReferralSales,
+  ReferralEarnings,
+}
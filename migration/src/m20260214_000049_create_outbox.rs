@@ -0,0 +1,64 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+  async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    // Telegram messages written in the same transaction as the change
+    // they announce, sent by a worker once it committed
+    manager
+      .create_table(
+        Table::create()
+          .table(Outbox::Table)
+          .if_not_exists()
+          .col(
+            ColumnDef::new(Outbox::Id)
+              .integer()
+              .not_null()
+              .auto_increment()
+              .primary_key(),
+          )
+          .col(ColumnDef::new(Outbox::TgUserId).big_integer().not_null())
+          .col(ColumnDef::new(Outbox::Text).text().not_null())
+          .col(
+            ColumnDef::new(Outbox::Attempts).integer().not_null().default(0),
+          )
+          .col(ColumnDef::new(Outbox::LastError).text().null())
+          .col(ColumnDef::new(Outbox::NextAttemptAt).date_time().not_null())
+          .col(ColumnDef::new(Outbox::SentAt).date_time().null())
+          .col(ColumnDef::new(Outbox::CreatedAt).date_time().not_null())
+          .to_owned(),
+      )
+      .await?;
+
+    manager
+      .create_index(
+        Index::create()
+          .name("idx_outbox_due")
+          .table(Outbox::Table)
+          .col(Outbox::SentAt)
+          .col(Outbox::NextAttemptAt)
+          .to_owned(),
+      )
+      .await
+  }
+
+  async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager.drop_table(Table::drop().table(Outbox::Table).to_owned()).await
+  }
+}
+
+#[derive(DeriveIden)]
+enum Outbox {
+  Table,
+  Id,
+  TgUserId,
+  Text,
+  Attempts,
+  LastError,
+  NextAttemptAt,
+  SentAt,
+  CreatedAt,
+}
@@ -0,0 +1,55 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+  async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    // Daily counters of bot commands and callbacks, to see which menus
+    // are used and which flows fail
+    manager
+      .create_table(
+        Table::create()
+          .table(BotUsage::Table)
+          .if_not_exists()
+          .col(ColumnDef::new(BotUsage::Kind).string().not_null())
+          .col(ColumnDef::new(BotUsage::Name).string().not_null())
+          .col(ColumnDef::new(BotUsage::Day).date().not_null())
+          .col(
+            ColumnDef::new(BotUsage::Uses)
+              .big_integer()
+              .not_null()
+              .default(0),
+          )
+          .col(
+            ColumnDef::new(BotUsage::Failures)
+              .big_integer()
+              .not_null()
+              .default(0),
+          )
+          .primary_key(
+            Index::create()
+              .col(BotUsage::Kind)
+              .col(BotUsage::Name)
+              .col(BotUsage::Day),
+          )
+          .to_owned(),
+      )
+      .await
+  }
+
+  async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager.drop_table(Table::drop().table(BotUsage::Table).to_owned()).await
+  }
+}
+
+#[derive(DeriveIden)]
+enum BotUsage {
+  Table,
+  Kind,
+  Name,
+  Day,
+  Uses,
+  Failures,
+}
@@ -0,0 +1,44 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+  async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    // Ed25519 key pairs for offline license tokens, the newest one signs,
+    // older ones stay published until retired so issued tokens still verify
+    manager
+      .create_table(
+        Table::create()
+          .table(SigningKeys::Table)
+          .if_not_exists()
+          .col(
+            ColumnDef::new(SigningKeys::Kid)
+              .string()
+              .not_null()
+              .primary_key(),
+          )
+          .col(ColumnDef::new(SigningKeys::PrivateKey).string().not_null())
+          .col(ColumnDef::new(SigningKeys::PublicKey).string().not_null())
+          .col(ColumnDef::new(SigningKeys::CreatedAt).date_time().not_null())
+          .to_owned(),
+      )
+      .await
+  }
+
+  async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager
+      .drop_table(Table::drop().table(SigningKeys::Table).to_owned())
+      .await
+  }
+}
+
+#[derive(DeriveIden)]
+enum SigningKeys {
+  Table,
+  Kid,
+  PrivateKey,
+  PublicKey,
+  CreatedAt,
+}
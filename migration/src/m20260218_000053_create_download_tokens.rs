@@ -0,0 +1,64 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+  async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    // One-time download links, used rows are kept as the audit trail of
+    // who downloaded which build
+    manager
+      .create_table(
+        Table::create()
+          .table(DownloadTokens::Table)
+          .if_not_exists()
+          .col(
+            ColumnDef::new(DownloadTokens::Token)
+              .string()
+              .not_null()
+              .primary_key(),
+          )
+          .col(
+            ColumnDef::new(DownloadTokens::TgUserId).big_integer().not_null(),
+          )
+          .col(ColumnDef::new(DownloadTokens::Version).string().not_null())
+          .col(
+            ColumnDef::new(DownloadTokens::CreatedAt).date_time().not_null(),
+          )
+          .col(
+            ColumnDef::new(DownloadTokens::ExpiresAt).date_time().not_null(),
+          )
+          .col(ColumnDef::new(DownloadTokens::UsedAt).date_time().null())
+          .to_owned(),
+      )
+      .await?;
+
+    manager
+      .create_index(
+        Index::create()
+          .name("idx_download_tokens_user")
+          .table(DownloadTokens::Table)
+          .col(DownloadTokens::TgUserId)
+          .to_owned(),
+      )
+      .await
+  }
+
+  async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager
+      .drop_table(Table::drop().table(DownloadTokens::Table).to_owned())
+      .await
+  }
+}
+
+#[derive(DeriveIden)]
+enum DownloadTokens {
+  Table,
+  Token,
+  TgUserId,
+  Version,
+  CreatedAt,
+  ExpiresAt,
+  UsedAt,
+}
@@ -0,0 +1,45 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+  async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    // Starter discounts offered to heavy trial users, one per user ever
+    manager
+      .create_table(
+        Table::create()
+          .table(UpgradeOffers::Table)
+          .if_not_exists()
+          .col(
+            ColumnDef::new(UpgradeOffers::TgUserId)
+              .big_integer()
+              .not_null()
+              .primary_key(),
+          )
+          .col(ColumnDef::new(UpgradeOffers::Percent).integer().not_null())
+          .col(ColumnDef::new(UpgradeOffers::SentAt).date_time().not_null())
+          .col(ColumnDef::new(UpgradeOffers::ExpiresAt).date_time().not_null())
+          .col(ColumnDef::new(UpgradeOffers::UsedAt).date_time().null())
+          .to_owned(),
+      )
+      .await
+  }
+
+  async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager
+      .drop_table(Table::drop().table(UpgradeOffers::Table).to_owned())
+      .await
+  }
+}
+
+#[derive(DeriveIden)]
+enum UpgradeOffers {
+  Table,
+  TgUserId,
+  Percent,
+  SentAt,
+  ExpiresAt,
+  UsedAt,
+}
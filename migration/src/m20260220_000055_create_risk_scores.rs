@@ -0,0 +1,41 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+  async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    // Latest risk assessment of each user, refreshed by a cron job
+    manager
+      .create_table(
+        Table::create()
+          .table(RiskScores::Table)
+          .if_not_exists()
+          .col(
+            ColumnDef::new(RiskScores::TgUserId)
+              .big_integer()
+              .not_null()
+              .primary_key(),
+          )
+          .col(ColumnDef::new(RiskScores::Score).integer().not_null())
+          .col(ColumnDef::new(RiskScores::Factors).json().not_null())
+          .col(ColumnDef::new(RiskScores::UpdatedAt).date_time().not_null())
+          .to_owned(),
+      )
+      .await
+  }
+
+  async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager.drop_table(Table::drop().table(RiskScores::Table).to_owned()).await
+  }
+}
+
+#[derive(DeriveIden)]
+enum RiskScores {
+  Table,
+  TgUserId,
+  Score,
+  Factors,
+  UpdatedAt,
+}
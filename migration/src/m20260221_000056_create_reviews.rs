@@ -0,0 +1,69 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+  async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    // Actions held back by the risk rules until an admin decides
+    manager
+      .create_table(
+        Table::create()
+          .table(Reviews::Table)
+          .if_not_exists()
+          .col(
+            ColumnDef::new(Reviews::Id)
+              .integer()
+              .not_null()
+              .auto_increment()
+              .primary_key(),
+          )
+          .col(ColumnDef::new(Reviews::TgUserId).big_integer().not_null())
+          .col(ColumnDef::new(Reviews::Kind).string().not_null())
+          .col(ColumnDef::new(Reviews::Action).json().not_null())
+          .col(ColumnDef::new(Reviews::Reason).string().not_null())
+          .col(ColumnDef::new(Reviews::RequestedBy).big_integer().null())
+          .col(
+            ColumnDef::new(Reviews::Status)
+              .string()
+              .not_null()
+              .default("pending"),
+          )
+          .col(ColumnDef::new(Reviews::CreatedAt).date_time().not_null())
+          .col(ColumnDef::new(Reviews::DecidedAt).date_time().null())
+          .col(ColumnDef::new(Reviews::DecidedBy).big_integer().null())
+          .to_owned(),
+      )
+      .await?;
+
+    manager
+      .create_index(
+        Index::create()
+          .name("idx_reviews_status")
+          .table(Reviews::Table)
+          .col(Reviews::Status)
+          .to_owned(),
+      )
+      .await
+  }
+
+  async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager.drop_table(Table::drop().table(Reviews::Table).to_owned()).await
+  }
+}
+
+#[derive(DeriveIden)]
+enum Reviews {
+  Table,
+  Id,
+  TgUserId,
+  Kind,
+  Action,
+  Reason,
+  RequestedBy,
+  Status,
+  CreatedAt,
+  DecidedAt,
+  DecidedBy,
+}
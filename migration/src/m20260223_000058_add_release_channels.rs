@@ -0,0 +1,69 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+  async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager
+      .alter_table(
+        Table::alter()
+          .table(Builds::Table)
+          .add_column(
+            ColumnDef::new(Builds::Channel)
+              .string()
+              .not_null()
+              .default("stable"),
+          )
+          .to_owned(),
+      )
+      .await?;
+
+    manager
+      .alter_table(
+        Table::alter()
+          .table(UserSettings::Table)
+          .add_column(
+            ColumnDef::new(UserSettings::Channel)
+              .string()
+              .not_null()
+              .default("stable"),
+          )
+          .to_owned(),
+      )
+      .await
+  }
+
+  async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager
+      .alter_table(
+        Table::alter()
+          .table(UserSettings::Table)
+          .drop_column(UserSettings::Channel)
+          .to_owned(),
+      )
+      .await?;
+
+    manager
+      .alter_table(
+        Table::alter()
+          .table(Builds::Table)
+          .drop_column(Builds::Channel)
+          .to_owned(),
+      )
+      .await
+  }
+}
+
+#[derive(DeriveIden)]
+enum Builds {
+  Table,
+  Channel,
+}
+
+#[derive(DeriveIden)]
+enum UserSettings {
+  Table,
+  Channel,
+}
@@ -0,0 +1,139 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+  async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    // Files of a version for platforms other than the published one
+    manager
+      .create_table(
+        Table::create()
+          .table(BuildArtifacts::Table)
+          .if_not_exists()
+          .col(
+            ColumnDef::new(BuildArtifacts::Id)
+              .integer()
+              .not_null()
+              .auto_increment()
+              .primary_key(),
+          )
+          .col(ColumnDef::new(BuildArtifacts::Version).string().not_null())
+          .col(ColumnDef::new(BuildArtifacts::Platform).string().not_null())
+          .col(ColumnDef::new(BuildArtifacts::FilePath).string().not_null())
+          .col(ColumnDef::new(BuildArtifacts::Checksum).string().null())
+          .col(
+            ColumnDef::new(BuildArtifacts::CreatedAt).date_time().not_null(),
+          )
+          .to_owned(),
+      )
+      .await?;
+
+    manager
+      .create_index(
+        Index::create()
+          .name("idx_build_artifacts_version_platform")
+          .table(BuildArtifacts::Table)
+          .col(BuildArtifacts::Version)
+          .col(BuildArtifacts::Platform)
+          .unique()
+          .to_owned(),
+      )
+      .await?;
+
+    manager
+      .alter_table(
+        Table::alter()
+          .table(Builds::Table)
+          .add_column(
+            ColumnDef::new(Builds::Platform)
+              .string()
+              .not_null()
+              .default("windows-x64"),
+          )
+          .to_owned(),
+      )
+      .await?;
+
+    manager
+      .alter_table(
+        Table::alter()
+          .table(DownloadTokens::Table)
+          .add_column(ColumnDef::new(DownloadTokens::Platform).string().null())
+          .to_owned(),
+      )
+      .await?;
+
+    manager
+      .alter_table(
+        Table::alter()
+          .table(Sessions::Table)
+          .add_column(ColumnDef::new(Sessions::Platform).string().null())
+          .to_owned(),
+      )
+      .await
+  }
+
+  async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager
+      .alter_table(
+        Table::alter()
+          .table(Sessions::Table)
+          .drop_column(Sessions::Platform)
+          .to_owned(),
+      )
+      .await?;
+
+    manager
+      .alter_table(
+        Table::alter()
+          .table(DownloadTokens::Table)
+          .drop_column(DownloadTokens::Platform)
+          .to_owned(),
+      )
+      .await?;
+
+    manager
+      .alter_table(
+        Table::alter()
+          .table(Builds::Table)
+          .drop_column(Builds::Platform)
+          .to_owned(),
+      )
+      .await?;
+
+    manager
+      .drop_table(Table::drop().table(BuildArtifacts::Table).to_owned())
+      .await
+  }
+}
+
+#[derive(DeriveIden)]
+enum BuildArtifacts {
+  Table,
+  Id,
+  Version,
+  Platform,
+  FilePath,
+  Checksum,
+  CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum Builds {
+  Table,
+  Platform,
+}
+
+#[derive(DeriveIden)]
+enum DownloadTokens {
+  Table,
+  Platform,
+}
+
+#[derive(DeriveIden)]
+enum Sessions {
+  Table,
+  Platform,
+}
@@ -0,0 +1,62 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+  async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    // One row per started download, resumed ones aside
+    manager
+      .create_table(
+        Table::create()
+          .table(Downloads::Table)
+          .if_not_exists()
+          .col(
+            ColumnDef::new(Downloads::Id)
+              .integer()
+              .not_null()
+              .auto_increment()
+              .primary_key(),
+          )
+          .col(ColumnDef::new(Downloads::TgUserId).big_integer().not_null())
+          .col(ColumnDef::new(Downloads::LicenseKey).string().null())
+          .col(ColumnDef::new(Downloads::Version).string().not_null())
+          .col(ColumnDef::new(Downloads::Platform).string().null())
+          .col(ColumnDef::new(Downloads::Token).string().not_null())
+          .col(ColumnDef::new(Downloads::Ip).string().null())
+          .col(ColumnDef::new(Downloads::CreatedAt).date_time().not_null())
+          .to_owned(),
+      )
+      .await?;
+
+    manager
+      .create_index(
+        Index::create()
+          .name("idx_downloads_version")
+          .table(Downloads::Table)
+          .col(Downloads::Version)
+          .to_owned(),
+      )
+      .await
+  }
+
+  async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager
+      .drop_table(Table::drop().table(Downloads::Table).to_owned())
+      .await
+  }
+}
+
+#[derive(DeriveIden)]
+enum Downloads {
+  Table,
+  Id,
+  TgUserId,
+  LicenseKey,
+  Version,
+  Platform,
+  Token,
+  Ip,
+  CreatedAt,
+}
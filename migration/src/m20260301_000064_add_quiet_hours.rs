@@ -0,0 +1,86 @@
+use sea_orm_migration::prelude::*;
+
+use super::m20260112_000015_create_user_settings::UserSettings;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+  async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    // Local hours an admin doesn't want non-critical alerts in, null = never
+    for column in [SettingsExt::QuietFromHour, SettingsExt::QuietToHour] {
+      manager
+        .alter_table(
+          Table::alter()
+            .table(UserSettings::Table)
+            .add_column(ColumnDef::new(column).integer().null())
+            .to_owned(),
+        )
+        .await?;
+    }
+
+    // Alerts waiting for the end of an admin's quiet hours
+    manager
+      .create_table(
+        Table::create()
+          .table(HeldAlerts::Table)
+          .if_not_exists()
+          .col(
+            ColumnDef::new(HeldAlerts::Id)
+              .integer()
+              .not_null()
+              .auto_increment()
+              .primary_key(),
+          )
+          .col(ColumnDef::new(HeldAlerts::TgUserId).big_integer().not_null())
+          .col(ColumnDef::new(HeldAlerts::Text).text().not_null())
+          .col(ColumnDef::new(HeldAlerts::CreatedAt).date_time().not_null())
+          .to_owned(),
+      )
+      .await?;
+
+    manager
+      .create_index(
+        Index::create()
+          .name("idx_held_alerts_tg_user_id")
+          .table(HeldAlerts::Table)
+          .col(HeldAlerts::TgUserId)
+          .to_owned(),
+      )
+      .await
+  }
+
+  async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager
+      .drop_table(Table::drop().table(HeldAlerts::Table).to_owned())
+      .await?;
+
+    for column in [SettingsExt::QuietFromHour, SettingsExt::QuietToHour] {
+      manager
+        .alter_table(
+          Table::alter()
+            .table(UserSettings::Table)
+            .drop_column(column)
+            .to_owned(),
+        )
+        .await?;
+    }
+    Ok(())
+  }
+}
+
+#[derive(DeriveIden)]
+enum SettingsExt {
+  QuietFromHour,
+  QuietToHour,
+}
+
+#[derive(DeriveIden)]
+enum HeldAlerts {
+  Table,
+  Id,
+  TgUserId,
+  Text,
+  CreatedAt,
+}
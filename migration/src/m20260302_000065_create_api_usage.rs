@@ -0,0 +1,64 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+  async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    // Daily API requests per license, to find the keys generating the load
+    manager
+      .create_table(
+        Table::create()
+          .table(ApiUsage::Table)
+          .if_not_exists()
+          .col(ColumnDef::new(ApiUsage::LicenseKey).string().not_null())
+          .col(ColumnDef::new(ApiUsage::Day).date().not_null())
+          .col(ColumnDef::new(ApiUsage::Kind).string().not_null())
+          .col(
+            ColumnDef::new(ApiUsage::Requests)
+              .big_integer()
+              .not_null()
+              .default(0),
+          )
+          .col(
+            ColumnDef::new(ApiUsage::Throttled)
+              .big_integer()
+              .not_null()
+              .default(0),
+          )
+          .primary_key(
+            Index::create()
+              .col(ApiUsage::LicenseKey)
+              .col(ApiUsage::Day)
+              .col(ApiUsage::Kind),
+          )
+          .to_owned(),
+      )
+      .await?;
+
+    manager
+      .create_index(
+        Index::create()
+          .name("idx_api_usage_day")
+          .table(ApiUsage::Table)
+          .col(ApiUsage::Day)
+          .to_owned(),
+      )
+      .await
+  }
+
+  async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager.drop_table(Table::drop().table(ApiUsage::Table).to_owned()).await
+  }
+}
+
+#[derive(DeriveIden)]
+enum ApiUsage {
+  Table,
+  LicenseKey,
+  Day,
+  Kind,
+  Requests,
+  Throttled,
+}
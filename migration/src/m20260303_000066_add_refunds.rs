@@ -0,0 +1,55 @@
+use sea_orm_migration::prelude::*;
+
+use super::m20260104_000010_add_referral_system::Transactions;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+  async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    // Purchase a refund pays back, null for every other transaction
+    manager
+      .alter_table(
+        Table::alter()
+          .table(Transactions::Table)
+          .add_column(ColumnDef::new(Ext::RefundOf).integer().null())
+          .to_owned(),
+      )
+      .await?;
+
+    manager
+      .create_index(
+        Index::create()
+          .name("idx_transactions_refund_of")
+          .table(Transactions::Table)
+          .col(Ext::RefundOf)
+          .to_owned(),
+      )
+      .await
+  }
+
+  async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager
+      .drop_index(
+        Index::drop()
+          .name("idx_transactions_refund_of")
+          .table(Transactions::Table)
+          .to_owned(),
+      )
+      .await?;
+    manager
+      .alter_table(
+        Table::alter()
+          .table(Transactions::Table)
+          .drop_column(Ext::RefundOf)
+          .to_owned(),
+      )
+      .await
+  }
+}
+
+#[derive(DeriveIden)]
+enum Ext {
+  RefundOf,
+}
@@ -0,0 +1,36 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+  async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    // Country of the login address, from the CDN header or GeoIP
+    manager
+      .alter_table(
+        Table::alter()
+          .table(Sessions::Table)
+          .add_column(ColumnDef::new(Sessions::Country).string().null())
+          .to_owned(),
+      )
+      .await
+  }
+
+  async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager
+      .alter_table(
+        Table::alter()
+          .table(Sessions::Table)
+          .drop_column(Sessions::Country)
+          .to_owned(),
+      )
+      .await
+  }
+}
+
+#[derive(DeriveIden)]
+enum Sessions {
+  Table,
+  Country,
+}
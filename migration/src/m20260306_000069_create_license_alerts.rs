@@ -0,0 +1,59 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+  async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    // Licenses seen on too many machines or addresses, one row a day
+    manager
+      .create_table(
+        Table::create()
+          .table(LicenseAlerts::Table)
+          .if_not_exists()
+          .col(ColumnDef::new(LicenseAlerts::LicenseKey).string().not_null())
+          .col(ColumnDef::new(LicenseAlerts::Day).date().not_null())
+          .col(ColumnDef::new(LicenseAlerts::Hwids).integer().not_null())
+          .col(ColumnDef::new(LicenseAlerts::Ips).integer().not_null())
+          .col(ColumnDef::new(LicenseAlerts::Countries).integer().not_null())
+          .col(ColumnDef::new(LicenseAlerts::Sessions).integer().not_null())
+          .col(ColumnDef::new(LicenseAlerts::CreatedAt).date_time().not_null())
+          .primary_key(
+            Index::create()
+              .col(LicenseAlerts::LicenseKey)
+              .col(LicenseAlerts::Day),
+          )
+          .to_owned(),
+      )
+      .await?;
+
+    manager
+      .create_index(
+        Index::create()
+          .name("idx_license_alerts_day")
+          .table(LicenseAlerts::Table)
+          .col(LicenseAlerts::Day)
+          .to_owned(),
+      )
+      .await
+  }
+
+  async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager
+      .drop_table(Table::drop().table(LicenseAlerts::Table).to_owned())
+      .await
+  }
+}
+
+#[derive(DeriveIden)]
+enum LicenseAlerts {
+  Table,
+  LicenseKey,
+  Day,
+  Hwids,
+  Ips,
+  Countries,
+  Sessions,
+  CreatedAt,
+}
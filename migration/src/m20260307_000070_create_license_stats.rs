@@ -0,0 +1,85 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+  async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    // Telemetry totals of each key, next to the per-user ones
+    manager
+      .create_table(
+        Table::create()
+          .table(LicenseStats::Table)
+          .if_not_exists()
+          .col(
+            ColumnDef::new(LicenseStats::LicenseKey)
+              .string()
+              .not_null()
+              .primary_key(),
+          )
+          .col(
+            ColumnDef::new(LicenseStats::WeeklyXp)
+              .big_integer()
+              .not_null()
+              .default(0),
+          )
+          .col(
+            ColumnDef::new(LicenseStats::TotalXp)
+              .big_integer()
+              .not_null()
+              .default(0),
+          )
+          .col(
+            ColumnDef::new(LicenseStats::RuntimeHours)
+              .double()
+              .not_null()
+              .default(0.0),
+          )
+          .col(
+            ColumnDef::new(LicenseStats::LastUpdated).date_time().not_null(),
+          )
+          .to_owned(),
+      )
+      .await?;
+
+    // XP earned within one session, for clients that name it in metrics
+    manager
+      .alter_table(
+        Table::alter()
+          .table(Sessions::Table)
+          .add_column(
+            ColumnDef::new(Sessions::Xp).big_integer().not_null().default(0),
+          )
+          .to_owned(),
+      )
+      .await
+  }
+
+  async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager
+      .alter_table(
+        Table::alter().table(Sessions::Table).drop_column(Sessions::Xp).to_owned(),
+      )
+      .await?;
+    manager
+      .drop_table(Table::drop().table(LicenseStats::Table).to_owned())
+      .await
+  }
+}
+
+#[derive(DeriveIden)]
+enum LicenseStats {
+  Table,
+  LicenseKey,
+  WeeklyXp,
+  TotalXp,
+  RuntimeHours,
+  LastUpdated,
+}
+
+#[derive(DeriveIden)]
+enum Sessions {
+  Table,
+  Xp,
+}
@@ -0,0 +1,53 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+  async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    // End of day copy of user_stats, one row per user a day
+    manager
+      .create_table(
+        Table::create()
+          .table(StatsDaily::Table)
+          .if_not_exists()
+          .col(ColumnDef::new(StatsDaily::TgUserId).big_integer().not_null())
+          .col(ColumnDef::new(StatsDaily::Day).date().not_null())
+          .col(ColumnDef::new(StatsDaily::WeeklyXp).big_integer().not_null())
+          .col(ColumnDef::new(StatsDaily::TotalXp).big_integer().not_null())
+          .col(ColumnDef::new(StatsDaily::DropsCount).integer().not_null())
+          .col(ColumnDef::new(StatsDaily::RuntimeHours).double().not_null())
+          .primary_key(
+            Index::create().col(StatsDaily::TgUserId).col(StatsDaily::Day),
+          )
+          .to_owned(),
+      )
+      .await?;
+
+    manager
+      .create_index(
+        Index::create()
+          .name("idx_stats_daily_day")
+          .table(StatsDaily::Table)
+          .col(StatsDaily::Day)
+          .to_owned(),
+      )
+      .await
+  }
+
+  async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager.drop_table(Table::drop().table(StatsDaily::Table).to_owned()).await
+  }
+}
+
+#[derive(DeriveIden)]
+enum StatsDaily {
+  Table,
+  TgUserId,
+  Day,
+  WeeklyXp,
+  TotalXp,
+  DropsCount,
+  RuntimeHours,
+}
@@ -0,0 +1,84 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+  async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    // Users appear on /top only after opting in
+    manager
+      .alter_table(
+        Table::alter()
+          .table(UserSettings::Table)
+          .add_column(
+            ColumnDef::new(UserSettings::Leaderboard)
+              .boolean()
+              .not_null()
+              .default(false),
+          )
+          .to_owned(),
+      )
+      .await?;
+
+    // Boards are sorted by these
+    manager
+      .create_index(
+        Index::create()
+          .name("idx_user_stats_weekly_xp")
+          .table(UserStats::Table)
+          .col(UserStats::WeeklyXp)
+          .to_owned(),
+      )
+      .await?;
+    manager
+      .create_index(
+        Index::create()
+          .name("idx_user_stats_drops_count")
+          .table(UserStats::Table)
+          .col(UserStats::DropsCount)
+          .to_owned(),
+      )
+      .await
+  }
+
+  async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager
+      .drop_index(
+        Index::drop()
+          .name("idx_user_stats_drops_count")
+          .table(UserStats::Table)
+          .to_owned(),
+      )
+      .await?;
+    manager
+      .drop_index(
+        Index::drop()
+          .name("idx_user_stats_weekly_xp")
+          .table(UserStats::Table)
+          .to_owned(),
+      )
+      .await?;
+    manager
+      .alter_table(
+        Table::alter()
+          .table(UserSettings::Table)
+          .drop_column(UserSettings::Leaderboard)
+          .to_owned(),
+      )
+      .await
+  }
+}
+
+#[derive(DeriveIden)]
+enum UserSettings {
+  Table,
+  Leaderboard,
+}
+
+#[derive(DeriveIden)]
+enum UserStats {
+  Table,
+  WeeklyXp,
+  DropsCount,
+}
@@ -0,0 +1,51 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+  async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    // Client errors counted per day, message and build version
+    manager
+      .create_table(
+        Table::create()
+          .table(ErrorReports::Table)
+          .if_not_exists()
+          .col(ColumnDef::new(ErrorReports::Day).date().not_null())
+          .col(ColumnDef::new(ErrorReports::Message).string().not_null())
+          .col(ColumnDef::new(ErrorReports::Version).string().not_null())
+          .col(
+            ColumnDef::new(ErrorReports::Count)
+              .big_integer()
+              .not_null()
+              .default(0),
+          )
+          .col(ColumnDef::new(ErrorReports::LastKey).string().not_null())
+          .col(ColumnDef::new(ErrorReports::LastSeen).date_time().not_null())
+          .primary_key(
+            Index::create()
+              .col(ErrorReports::Day)
+              .col(ErrorReports::Message)
+              .col(ErrorReports::Version),
+          )
+          .to_owned(),
+      )
+      .await
+  }
+
+  async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager.drop_table(Table::drop().table(ErrorReports::Table).to_owned()).await
+  }
+}
+
+#[derive(DeriveIden)]
+enum ErrorReports {
+  Table,
+  Day,
+  Message,
+  Version,
+  Count,
+  LastKey,
+  LastSeen,
+}
@@ -0,0 +1,134 @@
+use sea_orm_migration::prelude::*;
+
+use super::m20251214_000002_create_licenses::Licenses;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+const COLUMNS: &str = "license_key, hwid, platform, ip, country, started_at, \
+  last_seen, ended_at, end_reason, xp";
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+  async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    // The client picks session ids and may reuse them, so rows get their
+    // own id and every start appends to the history. SQLite can't swap a
+    // primary key, the table is rebuilt.
+    create(manager, true).await?;
+    manager
+      .get_connection()
+      .execute_unprepared(&format!(
+        "INSERT INTO sessions_new (session_id, {COLUMNS}) \
+        SELECT session_id, {COLUMNS} FROM sessions ORDER BY started_at"
+      ))
+      .await?;
+    swap(manager).await
+  }
+
+  async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    // only the newest row of a reused id survives
+    create(manager, false).await?;
+    manager
+      .get_connection()
+      .execute_unprepared(&format!(
+        "INSERT INTO sessions_new (session_id, {COLUMNS}) \
+        SELECT session_id, {COLUMNS} FROM sessions \
+        WHERE id IN (SELECT MAX(id) FROM sessions GROUP BY session_id)"
+      ))
+      .await?;
+    swap(manager).await
+  }
+}
+
+async fn create(
+  manager: &SchemaManager<'_>,
+  row_ids: bool,
+) -> Result<(), DbErr> {
+  let mut table = Table::create();
+  table.table(SessionsNew::Table);
+  if row_ids {
+    table
+      .col(
+        ColumnDef::new(Sessions::Id)
+          .big_integer()
+          .not_null()
+          .auto_increment()
+          .primary_key(),
+      )
+      .col(ColumnDef::new(Sessions::SessionId).string().not_null());
+  } else {
+    table.col(
+      ColumnDef::new(Sessions::SessionId).string().not_null().primary_key(),
+    );
+  }
+  table
+    .col(ColumnDef::new(Sessions::LicenseKey).string().not_null())
+    .col(ColumnDef::new(Sessions::Hwid).string().null())
+    .col(ColumnDef::new(Sessions::Platform).string().null())
+    .col(ColumnDef::new(Sessions::Ip).string().null())
+    .col(ColumnDef::new(Sessions::Country).string().null())
+    .col(ColumnDef::new(Sessions::StartedAt).date_time().not_null())
+    .col(ColumnDef::new(Sessions::LastSeen).date_time().not_null())
+    .col(ColumnDef::new(Sessions::EndedAt).date_time().null())
+    .col(ColumnDef::new(Sessions::EndReason).string().null())
+    .col(ColumnDef::new(Sessions::Xp).big_integer().not_null().default(0))
+    .foreign_key(
+      ForeignKey::create()
+        .name("fk_sessions_license")
+        .from(SessionsNew::Table, Sessions::LicenseKey)
+        .to(Licenses::Table, Licenses::Key)
+        .on_delete(ForeignKeyAction::Cascade)
+        .on_update(ForeignKeyAction::Cascade),
+    );
+  manager.create_table(table.to_owned()).await
+}
+
+/// Replace the old table with the new one and index it again
+async fn swap(manager: &SchemaManager<'_>) -> Result<(), DbErr> {
+  manager.drop_table(Table::drop().table(Sessions::Table).to_owned()).await?;
+  manager
+    .rename_table(
+      Table::rename().table(SessionsNew::Table, Sessions::Table).to_owned(),
+    )
+    .await?;
+
+  for (name, col) in [
+    ("idx_sessions_license_key", Sessions::LicenseKey),
+    ("idx_sessions_ended_at", Sessions::EndedAt),
+    ("idx_sessions_session_id", Sessions::SessionId),
+  ] {
+    manager
+      .create_index(
+        Index::create()
+          .if_not_exists()
+          .name(name)
+          .table(Sessions::Table)
+          .col(col)
+          .to_owned(),
+      )
+      .await?;
+  }
+  Ok(())
+}
+
+#[derive(DeriveIden)]
+enum Sessions {
+  Table,
+  Id,
+  SessionId,
+  LicenseKey,
+  Hwid,
+  Platform,
+  Ip,
+  Country,
+  StartedAt,
+  LastSeen,
+  EndedAt,
+  EndReason,
+  Xp,
+}
+
+#[derive(DeriveIden)]
+enum SessionsNew {
+  Table,
+}
@@ -0,0 +1,336 @@
+//! Load generator for a running test instance: simulated clients heartbeat
+//! and submit telemetry while a probe times `/readyz`, whose database ping
+//! slows down first when SQLite is contended.
+//!
+//! ```text
+//! bench <base_url> <key>[,<key>...] [--clients 50] [--secs 60]
+//!       [--interval-ms 1000] [--stats-every 10] [--max-p99-ms 0]
+//! ```
+//!
+//! Clients take the keys round-robin, so give keys enough sessions for the
+//! clients sharing them. The API is rate limited per IP, 429s are counted
+//! apart from errors. With `--max-p99-ms` the run fails when the heartbeat
+//! p99 is slower, so it can gate a release.
+
+use std::{
+  collections::BTreeMap,
+  env,
+  io::Write,
+  process::ExitCode,
+  time::{Duration, Instant},
+};
+
+use base64::Engine;
+use flate2::{Compression, write::GzEncoder};
+use reqwest::{Client, StatusCode};
+
+struct Args {
+  base_url: String,
+  keys: Vec<String>,
+  clients: usize,
+  duration: Duration,
+  interval: Duration,
+  stats_every: u32,
+  max_p99: Option<Duration>,
+}
+
+const USAGE: &str = "Usage: bench <base_url> <key>[,<key>...] [--clients N] \
+  [--secs S] [--interval-ms MS] [--stats-every N] [--max-p99-ms MS]";
+
+fn parse_args() -> Result<Args, String> {
+  let mut positional = Vec::new();
+  let mut args = Args {
+    base_url: String::new(),
+    keys: Vec::new(),
+    clients: 50,
+    duration: Duration::from_secs(60),
+    interval: Duration::from_secs(1),
+    stats_every: 10,
+    max_p99: None,
+  };
+
+  let mut it = env::args().skip(1);
+  while let Some(arg) = it.next() {
+    let Some(flag) = arg.strip_prefix("--") else {
+      positional.push(arg);
+      continue;
+    };
+    let value: u64 = it
+      .next()
+      .and_then(|value| value.parse().ok())
+      .ok_or_else(|| format!("--{} expects a number", flag))?;
+    match flag {
+      "clients" => args.clients = value.max(1) as usize,
+      "secs" => args.duration = Duration::from_secs(value),
+      "interval-ms" => args.interval = Duration::from_millis(value.max(1)),
+      "stats-every" => args.stats_every = value as u32,
+      "max-p99-ms" => {
+        args.max_p99 = (value > 0).then(|| Duration::from_millis(value))
+      }
+      _ => return Err(format!("Unknown flag --{}", flag)),
+    }
+  }
+
+  let [base_url, keys] = positional.as_slice() else {
+    return Err(USAGE.to_string());
+  };
+  args.base_url = base_url.trim_end_matches('/').to_string();
+  args.keys = keys
+    .split(',')
+    .map(str::trim)
+    .filter(|key| !key.is_empty())
+    .map(str::to_string)
+    .collect();
+  if args.keys.is_empty() {
+    return Err(USAGE.to_string());
+  }
+  Ok(args)
+}
+
+/// Latencies and outcomes of one kind of request
+#[derive(Default)]
+struct Samples {
+  latencies: Vec<Duration>,
+  /// Requests by status, 0 for transport failures
+  statuses: BTreeMap<u16, u64>,
+}
+
+impl Samples {
+  fn record(&mut self, latency: Duration, status: Option<StatusCode>) {
+    self.latencies.push(latency);
+    *self.statuses.entry(status.map_or(0, |s| s.as_u16())).or_default() += 1;
+  }
+
+  fn merge(&mut self, other: Samples) {
+    self.latencies.extend(other.latencies);
+    for (status, count) in other.statuses {
+      *self.statuses.entry(status).or_default() += count;
+    }
+  }
+
+  /// Nearest-rank percentile
+  fn percentile(&self, p: f64) -> Duration {
+    let mut sorted = self.latencies.clone();
+    sorted.sort();
+    let rank = ((p / 100.0) * sorted.len() as f64).ceil() as usize;
+    sorted.get(rank.saturating_sub(1)).copied().unwrap_or_default()
+  }
+
+  fn report(&self, name: &str, elapsed: Duration) {
+    let count = self.latencies.len();
+    let ok = self.statuses.get(&200).copied().unwrap_or(0);
+    let limited = self.statuses.get(&429).copied().unwrap_or(0);
+    let ms = |d: Duration| d.as_secs_f64() * 1000.0;
+    println!(
+      "{:<10} {:>7} req {:>8.1} rps  ok {:>7}  429 {:>6}  err {:>6}  \
+      p50 {:>7.1}ms  p99 {:>7.1}ms  max {:>7.1}ms",
+      name,
+      count,
+      count as f64 / elapsed.as_secs_f64().max(f64::EPSILON),
+      ok,
+      limited,
+      count as u64 - ok - limited,
+      ms(self.percentile(50.0)),
+      ms(self.percentile(99.0)),
+      ms(self.latencies.iter().max().copied().unwrap_or_default()),
+    );
+    let other: Vec<String> = self
+      .statuses
+      .iter()
+      .filter(|(status, _)| !matches!(status, 200 | 429))
+      .map(|(status, count)| match status {
+        0 => format!("failed: {}", count),
+        status => format!("{}: {}", status, count),
+      })
+      .collect();
+    if !other.is_empty() {
+      println!("{:<10} {}", "", other.join(", "));
+    }
+  }
+}
+
+/// Telemetry the way clients send it, gzipped JSON in base64
+fn encode_stats(key: &str, instance: usize) -> String {
+  let payload = json::json!({
+    "type": "state",
+    "license_key": key,
+    "instance_id": format!("bench-{}", instance),
+    "data": { "state": "bench", "duration": 1.0 },
+  });
+  let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+  let _ = encoder.write_all(payload.to_string().as_bytes());
+  let compressed = encoder.finish().unwrap_or_default();
+  base64::prelude::BASE64_STANDARD.encode(compressed)
+}
+
+async fn timed(
+  request: reqwest::RequestBuilder,
+) -> (Duration, Option<StatusCode>) {
+  let start = Instant::now();
+  let status = match request.send().await {
+    Ok(response) => {
+      let status = response.status();
+      // the body is part of the latency a client sees
+      let _ = response.bytes().await;
+      Some(status)
+    }
+    Err(_) => None,
+  };
+  (start.elapsed(), status)
+}
+
+async fn run_client(
+  client: Client,
+  args: &Args,
+  id: usize,
+  deadline: Instant,
+) -> (Samples, Samples) {
+  let key = &args.keys[id % args.keys.len()];
+  let session_id = uuid::Uuid::new_v4().to_string();
+  let heartbeat = json::json!({
+    "key": key,
+    "machine_id": format!("bench-machine-{}", id),
+    "session_id": session_id,
+    "platform": "linux-x64",
+  });
+  let stats = json::json!({ "stats": encode_stats(key, id) });
+
+  let (mut heartbeats, mut metrics) = (Samples::default(), Samples::default());
+  // spread clients over the interval instead of firing in lockstep
+  let offset = args.interval.mul_f64(id as f64 / args.clients as f64);
+  tokio::time::sleep(offset).await;
+  let mut interval = tokio::time::interval(args.interval);
+
+  let mut beat = 0u32;
+  while Instant::now() < deadline {
+    interval.tick().await;
+    let request =
+      client.post(format!("{}/api/heartbeat", args.base_url)).json(&heartbeat);
+    let (latency, status) = timed(request).await;
+    heartbeats.record(latency, status);
+
+    beat += 1;
+    if args.stats_every > 0 && beat.is_multiple_of(args.stats_every) {
+      let request =
+        client.post(format!("{}/api/metrics", args.base_url)).json(&stats);
+      let (latency, status) = timed(request).await;
+      metrics.record(latency, status);
+    }
+  }
+
+  let logout = json::json!({
+    "key": key,
+    "machine_id": format!("bench-machine-{}", id),
+    "session_id": session_id,
+  });
+  let _ = client
+    .post(format!("{}/api/logout", args.base_url))
+    .json(&logout)
+    .send()
+    .await;
+  (heartbeats, metrics)
+}
+
+/// Times `/readyz` alongside the load, its database ping queues behind
+/// the writes of the clients
+async fn run_probe(
+  client: Client,
+  base_url: String,
+  deadline: Instant,
+) -> Samples {
+  let mut samples = Samples::default();
+  let mut interval = tokio::time::interval(Duration::from_millis(500));
+  while Instant::now() < deadline {
+    interval.tick().await;
+    let (latency, status) =
+      timed(client.get(format!("{}/readyz", base_url))).await;
+    samples.record(latency, status);
+  }
+  samples
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+  let args = match parse_args() {
+    Ok(args) => args,
+    Err(msg) => {
+      eprintln!("{}", msg);
+      return ExitCode::FAILURE;
+    }
+  };
+  let args: &'static Args = Box::leak(Box::new(args));
+
+  let client = match Client::builder()
+    .timeout(Duration::from_secs(10))
+    .pool_max_idle_per_host(args.clients)
+    .build()
+  {
+    Ok(client) => client,
+    Err(e) => {
+      eprintln!("Failed to build HTTP client: {}", e);
+      return ExitCode::FAILURE;
+    }
+  };
+
+  println!(
+    "{} clients on {} key(s) against {} for {}s, heartbeat every {}ms",
+    args.clients,
+    args.keys.len(),
+    args.base_url,
+    args.duration.as_secs(),
+    args.interval.as_millis()
+  );
+
+  let start = Instant::now();
+  let deadline = start + args.duration;
+  let probe =
+    tokio::spawn(run_probe(client.clone(), args.base_url.clone(), deadline));
+  let clients: Vec<_> = (0..args.clients)
+    .map(|id| tokio::spawn(run_client(client.clone(), args, id, deadline)))
+    .collect();
+
+  let (mut heartbeats, mut metrics) = (Samples::default(), Samples::default());
+  for handle in clients {
+    if let Ok((h, m)) = handle.await {
+      heartbeats.merge(h);
+      metrics.merge(m);
+    }
+  }
+  let probe = probe.await.unwrap_or_default();
+  let elapsed = start.elapsed();
+
+  println!();
+  heartbeats.report("heartbeat", elapsed);
+  metrics.report("metrics", elapsed);
+  probe.report("readyz", elapsed);
+
+  let p99 = heartbeats.percentile(99.0);
+  if let Some(max) = args.max_p99
+    && p99 > max
+  {
+    eprintln!(
+      "\nheartbeat p99 {:.1}ms is over the {}ms budget",
+      p99.as_secs_f64() * 1000.0,
+      max.as_millis()
+    );
+    return ExitCode::FAILURE;
+  }
+  ExitCode::SUCCESS
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_percentile() {
+    let mut samples = Samples::default();
+    for ms in 1..=100 {
+      samples.record(Duration::from_millis(ms), Some(StatusCode::OK));
+    }
+    assert_eq!(samples.percentile(50.0), Duration::from_millis(50));
+    assert_eq!(samples.percentile(99.0), Duration::from_millis(99));
+    assert_eq!(samples.percentile(100.0), Duration::from_millis(100));
+    assert_eq!(Samples::default().percentile(99.0), Duration::ZERO);
+  }
+}
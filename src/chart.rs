@@ -0,0 +1,76 @@
+//! PNG charts of daily stats, sent as photos by the bot. Nothing is drawn
+//! as text, numbers go in the caption, so no font has to be installed.
+
+use std::ops::Range;
+
+use plotters::{coord::Shift, prelude::*};
+
+const WIDTH: u32 = 800;
+const HEIGHT: u32 = 480;
+const XP_COLOR: RGBColor = RGBColor(66, 133, 244);
+const RUNTIME_COLOR: RGBColor = RGBColor(244, 160, 0);
+
+type Area<'a> = DrawingArea<BitMapBackend<'a>, Shift>;
+
+/// Daily XP as an area on top and runtime hours below, one point a day
+pub fn daily(xp: &[f64], runtime: &[f64]) -> anyhow::Result<Vec<u8>> {
+  let mut rgb = vec![0; (WIDTH * HEIGHT * 3) as usize];
+  {
+    let root =
+      BitMapBackend::with_buffer(&mut rgb, (WIDTH, HEIGHT)).into_drawing_area();
+    root.fill(&WHITE)?;
+    let (top, bottom) = root.split_vertically(HEIGHT / 2);
+    panel(&top, xp, XP_COLOR)?;
+    panel(&bottom, runtime, RUNTIME_COLOR)?;
+    root.present()?;
+  }
+  Ok(encode(&rgb))
+}
+
+fn panel(area: &Area, values: &[f64], color: RGBColor) -> anyhow::Result<()> {
+  let top = values.iter().copied().fold(0.0, f64::max);
+  // an empty or flat series still gets a visible baseline
+  let y: Range<f64> = 0.0..if top > 0.0 { top * 1.1 } else { 1.0 };
+  let x = 0.0..values.len().saturating_sub(1).max(1) as f64;
+
+  let mut chart = ChartBuilder::on(area).margin(16).build_cartesian_2d(x, y)?;
+  chart.draw_series(LineSeries::new(
+    [(0.0, 0.0), (values.len().saturating_sub(1).max(1) as f64, 0.0)],
+    BLACK.mix(0.3),
+  ))?;
+  chart.draw_series(
+    AreaSeries::new(
+      values.iter().enumerate().map(|(day, value)| (day as f64, *value)),
+      0.0,
+      color.mix(0.25),
+    )
+    .border_style(color.stroke_width(3)),
+  )?;
+  Ok(())
+}
+
+fn encode(rgb: &[u8]) -> Vec<u8> {
+  let row = WIDTH as usize * 3;
+  let mut raw = Vec::with_capacity((row + 1) * HEIGHT as usize);
+  for line in rgb.chunks(row) {
+    // filter type: none
+    raw.push(0);
+    raw.extend_from_slice(line);
+  }
+  crate::qr::png(WIDTH, HEIGHT, crate::qr::RGB, &raw)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_daily() {
+    const SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+    let png = daily(&[10.0, 0.0, 35.5, 20.0], &[1.0, 0.0, 4.5, 2.0]).unwrap();
+    assert!(png.starts_with(SIGNATURE));
+    // nothing to plot yet
+    assert!(daily(&[], &[]).unwrap().starts_with(SIGNATURE));
+    assert!(daily(&[0.0], &[0.0]).unwrap().starts_with(SIGNATURE));
+  }
+}
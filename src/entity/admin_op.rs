@@ -0,0 +1,48 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::user;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(EnumIter, DeriveActiveEnum, Serialize, Deserialize)]
+#[sea_orm(rs_type = "String", db_type = "Text")]
+pub enum AdminOpKind {
+  #[sea_orm(string_value = "deposit")]
+  Deposit,
+  #[sea_orm(string_value = "withdraw")]
+  Withdraw,
+}
+
+/// Balance change made by an admin command
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "admin_ops")]
+pub struct Model {
+  #[sea_orm(primary_key)]
+  pub id: i32,
+  pub admin_id: i64,
+  pub user_id: i64,
+  pub kind: AdminOpKind,
+  /// Always positive, the kind tells the direction
+  pub amount: i64,
+  pub created_at: DateTime,
+  pub undone_by: Option<i64>,
+  pub undone_at: Option<DateTime>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+  #[sea_orm(
+    belongs_to = "user::Entity",
+    from = "Column::UserId",
+    to = "user::Column::TgUserId"
+  )]
+  User,
+}
+
+impl Related<user::Entity> for Entity {
+  fn to() -> RelationDef {
+    Relation::User.def()
+  }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,56 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::announcement_read;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[derive(EnumIter, DeriveActiveEnum, Serialize, Deserialize)]
+#[sea_orm(rs_type = "String", db_type = "Text")]
+pub enum AnnouncementCategory {
+  #[sea_orm(string_value = "general")]
+  #[default]
+  General,
+  #[sea_orm(string_value = "release")]
+  Release,
+  #[sea_orm(string_value = "maintenance")]
+  Maintenance,
+  #[sea_orm(string_value = "offer")]
+  Offer,
+}
+
+impl AnnouncementCategory {
+  pub fn icon(&self) -> &'static str {
+    match self {
+      AnnouncementCategory::General => "📢",
+      AnnouncementCategory::Release => "🚀",
+      AnnouncementCategory::Maintenance => "🛠",
+      AnnouncementCategory::Offer => "🎁",
+    }
+  }
+}
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "announcements")]
+pub struct Model {
+  #[sea_orm(primary_key)]
+  pub id: i32,
+  pub category: AnnouncementCategory,
+  pub title: String,
+  #[sea_orm(column_type = "Text")]
+  pub body: String,
+  pub created_at: DateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+  #[sea_orm(has_many = "announcement_read::Entity")]
+  Reads,
+}
+
+impl Related<announcement_read::Entity> for Entity {
+  fn to() -> RelationDef {
+    Relation::Reads.def()
+  }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
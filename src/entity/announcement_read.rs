@@ -0,0 +1,44 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::{announcement, user};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "announcement_reads")]
+pub struct Model {
+  #[sea_orm(primary_key, auto_increment = false)]
+  pub tg_user_id: i64,
+  #[sea_orm(primary_key, auto_increment = false)]
+  pub announcement_id: i32,
+  pub read_at: DateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+  #[sea_orm(
+    belongs_to = "user::Entity",
+    from = "Column::TgUserId",
+    to = "user::Column::TgUserId"
+  )]
+  User,
+  #[sea_orm(
+    belongs_to = "announcement::Entity",
+    from = "Column::AnnouncementId",
+    to = "announcement::Column::Id"
+  )]
+  Announcement,
+}
+
+impl Related<user::Entity> for Entity {
+  fn to() -> RelationDef {
+    Relation::User.def()
+  }
+}
+
+impl Related<announcement::Entity> for Entity {
+  fn to() -> RelationDef {
+    Relation::Announcement.def()
+  }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
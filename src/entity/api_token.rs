@@ -0,0 +1,36 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::user;
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "api_tokens")]
+pub struct Model {
+  #[sea_orm(primary_key, auto_increment = false)]
+  pub tg_user_id: i64,
+  /// Hex SHA-256 of the token
+  #[sea_orm(unique)]
+  pub token_hash: String,
+  pub created_at: DateTime,
+  pub last_used_at: Option<DateTime>,
+  /// Restricts the token to one product
+  pub product: Option<String>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+  #[sea_orm(
+    belongs_to = "user::Entity",
+    from = "Column::TgUserId",
+    to = "user::Column::TgUserId"
+  )]
+  User,
+}
+
+impl Related<user::Entity> for Entity {
+  fn to() -> RelationDef {
+    Relation::User.def()
+  }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
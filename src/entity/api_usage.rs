@@ -0,0 +1,39 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(EnumIter, DeriveActiveEnum, Serialize, Deserialize)]
+#[sea_orm(rs_type = "String", db_type = "Text")]
+pub enum ApiKind {
+  #[sea_orm(string_value = "heartbeat")]
+  Heartbeat,
+  #[sea_orm(string_value = "validate")]
+  Validate,
+  #[sea_orm(string_value = "metrics")]
+  Metrics,
+  #[sea_orm(string_value = "batch")]
+  Batch,
+  /// Update checks that issued a download link
+  #[sea_orm(string_value = "download")]
+  Download,
+}
+
+/// API requests of one license on one day (UTC)
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "api_usage")]
+pub struct Model {
+  #[sea_orm(primary_key, auto_increment = false)]
+  pub license_key: String,
+  #[sea_orm(primary_key, auto_increment = false)]
+  pub day: Date,
+  #[sea_orm(primary_key, auto_increment = false)]
+  pub kind: ApiKind,
+  pub requests: i64,
+  /// Rejected by the rate limiter or the tier
+  pub throttled: i64,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
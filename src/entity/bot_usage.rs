@@ -0,0 +1,33 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(EnumIter, DeriveActiveEnum, Serialize, Deserialize)]
+#[sea_orm(rs_type = "String", db_type = "Text")]
+pub enum UsageKind {
+  #[sea_orm(string_value = "command")]
+  Command,
+  #[sea_orm(string_value = "callback")]
+  Callback,
+}
+
+/// How often a bot command or callback ran on one day (UTC)
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "bot_usage")]
+pub struct Model {
+  #[sea_orm(primary_key, auto_increment = false)]
+  pub kind: UsageKind,
+  /// Command without the slash, or callback variant
+  #[sea_orm(primary_key, auto_increment = false)]
+  pub name: String,
+  #[sea_orm(primary_key, auto_increment = false)]
+  pub day: Date,
+  pub uses: i64,
+  /// Handler returned an error
+  pub failures: i64,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -1,6 +1,42 @@
 use sea_orm::entity::prelude::*;
 use serde::{Deserialize, Serialize};
 
+/// Release track of a build, and the track a user follows
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[derive(EnumIter, DeriveActiveEnum, Serialize, Deserialize)]
+#[sea_orm(rs_type = "String", db_type = "Text")]
+#[serde(rename_all = "lowercase")]
+pub enum Channel {
+  #[sea_orm(string_value = "stable")]
+  #[default]
+  Stable,
+  #[sea_orm(string_value = "beta")]
+  Beta,
+}
+
+impl Channel {
+  pub fn parse(s: &str) -> Option<Self> {
+    match s.to_lowercase().as_str() {
+      "stable" => Some(Channel::Stable),
+      "beta" => Some(Channel::Beta),
+      _ => None,
+    }
+  }
+
+  pub fn name(self) -> &'static str {
+    match self {
+      Channel::Stable => "stable",
+      Channel::Beta => "beta",
+    }
+  }
+
+  /// Whether users following this channel get builds of `build`, beta
+  /// testers get stable releases too
+  pub fn serves(self, build: Channel) -> bool {
+    self == Channel::Beta || build == Channel::Stable
+  }
+}
+
 #[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
 #[sea_orm(table_name = "builds")]
 pub struct Model {
@@ -12,6 +48,12 @@ pub struct Model {
   pub is_active: bool,
   pub created_at: DateTime,
   pub downloads: i64,
+  pub product: String,
+  /// Hex SHA-256 of the file, None for builds published before checksums
+  pub checksum: Option<String>,
+  pub channel: Channel,
+  /// Platform of `file_path`, other platforms are build artifacts
+  pub platform: String,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -0,0 +1,22 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// File of a build for a platform other than the one it was published for
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "build_artifacts")]
+pub struct Model {
+  #[sea_orm(primary_key)]
+  pub id: i32,
+  pub version: String,
+  /// `os-arch`, e.g. `linux-arm64`
+  pub platform: String,
+  pub file_path: String,
+  /// Hex SHA-256 of the file
+  pub checksum: Option<String>,
+  pub created_at: DateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,18 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "canned_responses")]
+pub struct Model {
+  #[sea_orm(primary_key, auto_increment = false)]
+  pub name: String,
+  /// Reply template, may contain `{key}`-style placeholders
+  #[sea_orm(column_type = "Text")]
+  pub text: String,
+  pub created_at: DateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
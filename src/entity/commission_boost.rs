@@ -0,0 +1,31 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Window in which referral commission is multiplied
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "commission_boosts")]
+pub struct Model {
+  #[sea_orm(primary_key)]
+  pub id: i32,
+  /// Commission multiplier in percent, 200 doubles it
+  pub percent: i32,
+  /// Creator the boost is for, unset means everyone
+  pub creator_id: Option<i64>,
+  pub starts_at: DateTime,
+  pub ends_at: DateTime,
+  pub created_at: DateTime,
+  /// Creators were told the boost is over
+  pub end_announced: bool,
+}
+
+impl Model {
+  /// "2x", "1.5x"
+  pub fn multiplier(&self) -> String {
+    format!("{}x", self.percent as f64 / 100.0)
+  }
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
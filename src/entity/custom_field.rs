@@ -0,0 +1,33 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(EnumIter, DeriveActiveEnum, Serialize, Deserialize)]
+#[sea_orm(rs_type = "String", db_type = "Text")]
+pub enum FieldScope {
+  #[sea_orm(string_value = "user")]
+  User,
+  #[sea_orm(string_value = "license")]
+  License,
+}
+
+/// Admin-defined metadata attached to a user or a license
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "custom_fields")]
+pub struct Model {
+  #[sea_orm(primary_key, auto_increment = false)]
+  pub scope: FieldScope,
+  /// User id or license key
+  #[sea_orm(primary_key, auto_increment = false)]
+  pub target: String,
+  #[sea_orm(primary_key, auto_increment = false)]
+  pub name: String,
+  #[sea_orm(column_type = "Text")]
+  pub value: String,
+  pub updated_at: DateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
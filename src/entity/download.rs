@@ -0,0 +1,25 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Audit record of a build pulled through `/api/download`
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "downloads")]
+pub struct Model {
+  #[sea_orm(primary_key)]
+  pub id: i64,
+  pub tg_user_id: i64,
+  /// License that entitled the user to the build, None if it lapsed since
+  /// the link was issued
+  pub license_key: Option<String>,
+  pub version: String,
+  pub platform: Option<String>,
+  /// Download token redeemed for the file
+  pub token: String,
+  pub ip: Option<String>,
+  pub created_at: DateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,24 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// One-time link to a build, kept after use as a record of the download
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "download_tokens")]
+pub struct Model {
+  #[sea_orm(primary_key, auto_increment = false)]
+  pub token: String,
+  /// User the link was issued to
+  pub tg_user_id: i64,
+  pub version: String,
+  /// Artifact picked by the user, None for the published file
+  pub platform: Option<String>,
+  pub created_at: DateTime,
+  pub expires_at: DateTime,
+  /// When the download started, a token works only once
+  pub used_at: Option<DateTime>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
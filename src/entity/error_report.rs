@@ -0,0 +1,24 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// How often clients of a build reported one error on a day
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "error_reports")]
+pub struct Model {
+  #[sea_orm(primary_key, auto_increment = false)]
+  pub day: Date,
+  #[sea_orm(primary_key, auto_increment = false)]
+  pub message: String,
+  /// Build version of the client, `unknown` if it didn't say
+  #[sea_orm(primary_key, auto_increment = false)]
+  pub version: String,
+  pub count: i64,
+  /// Key of the latest report, to look the setup up
+  pub last_key: String,
+  pub last_seen: DateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,20 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "faq")]
+pub struct Model {
+  #[sea_orm(primary_key)]
+  pub id: i32,
+  pub question: String,
+  #[sea_orm(column_type = "Text")]
+  pub answer: String,
+  /// Space or comma separated search terms
+  pub keywords: String,
+  pub created_at: DateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,24 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+pub use super::custom_field::FieldScope;
+
+/// Turns a client feature on or off for one user or license
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "feature_flags")]
+pub struct Model {
+  #[sea_orm(primary_key, auto_increment = false)]
+  pub scope: FieldScope,
+  /// User id or license key
+  #[sea_orm(primary_key, auto_increment = false)]
+  pub target: String,
+  #[sea_orm(primary_key, auto_increment = false)]
+  pub feature: String,
+  pub enabled: bool,
+  pub updated_at: DateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
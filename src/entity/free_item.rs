@@ -9,6 +9,8 @@ pub struct Model {
   pub app_id: i32,
   pub name: String,
   pub updated_at: DateTime,
+  /// Added by an admin, kept when the Steam scan refreshes the cache
+  pub manual: bool,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
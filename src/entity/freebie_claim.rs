@@ -0,0 +1,48 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::user;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(EnumIter, DeriveActiveEnum, Serialize, Deserialize)]
+#[sea_orm(rs_type = "String", db_type = "Text")]
+pub enum FreebieKind {
+  /// Free Steam package, `ref_id` is the package id
+  #[sea_orm(string_value = "game")]
+  Game,
+  /// Free item definition, `ref_id` is the def id
+  #[sea_orm(string_value = "item")]
+  Item,
+}
+
+/// Freebie a user claimed from the bot
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "freebie_claims")]
+pub struct Model {
+  #[sea_orm(primary_key)]
+  pub id: i32,
+  pub tg_user_id: i64,
+  pub kind: FreebieKind,
+  pub ref_id: i32,
+  /// Name at claim time, the freebie may be gone later
+  pub name: String,
+  pub claimed_at: DateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+  #[sea_orm(
+    belongs_to = "user::Entity",
+    from = "Column::TgUserId",
+    to = "user::Column::TgUserId"
+  )]
+  User,
+}
+
+impl Related<user::Entity> for Entity {
+  fn to() -> RelationDef {
+    Relation::User.def()
+  }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
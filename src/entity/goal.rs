@@ -0,0 +1,57 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::user;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(EnumIter, DeriveActiveEnum, Serialize, Deserialize)]
+#[sea_orm(rs_type = "String", db_type = "Text")]
+pub enum GoalKind {
+  #[sea_orm(string_value = "xp")]
+  Xp,
+  /// Farming hours
+  #[sea_orm(string_value = "runtime")]
+  Runtime,
+}
+
+impl GoalKind {
+  pub fn format(&self, value: f64) -> String {
+    match self {
+      GoalKind::Xp => format!("{:.0} XP", value),
+      GoalKind::Runtime => format!("{:.1}h", value),
+    }
+  }
+}
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "goals")]
+pub struct Model {
+  #[sea_orm(primary_key, auto_increment = false)]
+  pub tg_user_id: i64,
+  pub kind: GoalKind,
+  pub target: f64,
+  /// Day (UTC) the progress belongs to
+  pub day: Date,
+  pub progress: f64,
+  pub streak: i32,
+  pub best_streak: i32,
+  pub last_achieved: Option<Date>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+  #[sea_orm(
+    belongs_to = "user::Entity",
+    from = "Column::TgUserId",
+    to = "user::Column::TgUserId"
+  )]
+  User,
+}
+
+impl Related<user::Entity> for Entity {
+  fn to() -> RelationDef {
+    Relation::User.def()
+  }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
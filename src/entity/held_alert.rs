@@ -0,0 +1,20 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Non-critical admin alert held back during the admin's quiet hours
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "held_alerts")]
+pub struct Model {
+  #[sea_orm(primary_key)]
+  pub id: i32,
+  pub tg_user_id: i64,
+  /// HTML message, keyboards are not kept
+  #[sea_orm(column_type = "Text")]
+  pub text: String,
+  pub created_at: DateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,19 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Admin-approved exception from the duplicate-HWID policy
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "hwid_exemptions")]
+pub struct Model {
+  #[sea_orm(primary_key)]
+  pub id: i32,
+  pub hwid: Option<String>,
+  pub tg_user_id: Option<i64>,
+  pub note: String,
+  pub created_at: DateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
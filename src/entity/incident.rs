@@ -0,0 +1,30 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(EnumIter, DeriveActiveEnum, Serialize, Deserialize)]
+#[sea_orm(rs_type = "String", db_type = "Text")]
+pub enum IncidentKind {
+  /// A honeypot key was used, the key database leaked
+  #[sea_orm(string_value = "honeypot")]
+  Honeypot,
+}
+
+/// Security events for admins to investigate
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "incidents")]
+pub struct Model {
+  #[sea_orm(primary_key)]
+  pub id: i32,
+  pub kind: IncidentKind,
+  pub license_key: Option<String>,
+  pub ip: Option<String>,
+  pub hwid: Option<String>,
+  pub details: String,
+  pub created_at: DateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
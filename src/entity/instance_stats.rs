@@ -0,0 +1,39 @@
+use json::Value;
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::user;
+
+/// Telemetry rollup for a single panel instance of a user
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "instance_stats")]
+pub struct Model {
+  #[sea_orm(primary_key, auto_increment = false)]
+  pub tg_user_id: i64,
+  #[sea_orm(primary_key, auto_increment = false)]
+  pub instance_id: String,
+  pub runtime_hours: f64,
+  /// json stats metadata, same shape as `user_stats.meta`
+  pub meta: Option<Value>,
+  pub first_seen: DateTime,
+  pub last_seen: DateTime,
+  pub product: String,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+  #[sea_orm(
+    belongs_to = "user::Entity",
+    from = "Column::TgUserId",
+    to = "user::Column::TgUserId"
+  )]
+  User,
+}
+
+impl Related<user::Entity> for Entity {
+  fn to() -> RelationDef {
+    Relation::User.def()
+  }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
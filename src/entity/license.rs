@@ -1,3 +1,4 @@
+use chrono::TimeDelta;
 use sea_orm::entity::prelude::*;
 use serde::{Deserialize, Serialize};
 
@@ -23,6 +24,36 @@ pub struct Model {
   pub is_blocked: bool,
   pub created_at: DateTime,
   pub max_sessions: i32,
+  /// Never sold, any heartbeat with it means the keys leaked
+  pub is_honeypot: bool,
+  /// Usage window in the owner's local hours, unset means any time
+  pub allowed_from_hour: Option<i32>,
+  pub allowed_to_hour: Option<i32>,
+  /// Country the key was sold for at a regional price, usage is bound to it
+  pub region: Option<String>,
+  pub product: String,
+  /// Extend from the owner's balance shortly before expiry
+  pub auto_renew: bool,
+  /// When the owner was last reminded that the key expires
+  pub expiry_notified_at: Option<DateTime>,
+  /// Temporary block, the key works again on its own after this moment
+  pub blocked_until: Option<DateTime>,
+  /// Admin who issued this short-lived support key, it is never sold and
+  /// stays out of stats
+  pub loaned_by: Option<i64>,
+}
+
+impl Model {
+  /// End of the cool-down the key is serving at `now`, if any
+  pub fn suspended_until(&self, now: DateTime) -> Option<DateTime> {
+    self.blocked_until.filter(|until| *until > now)
+  }
+
+  /// Whether the license ran out by `now`, `skew` keeps it alive a little
+  /// longer so a client clock lagging the server never disagrees with us
+  pub fn is_expired(&self, now: DateTime, skew: TimeDelta) -> bool {
+    self.expires_at + skew < now
+  }
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
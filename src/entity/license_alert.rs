@@ -0,0 +1,24 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// License used from more machines or addresses than one owner would,
+/// found by the daily sharing scan
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "license_alerts")]
+pub struct Model {
+  #[sea_orm(primary_key, auto_increment = false)]
+  pub license_key: String,
+  #[sea_orm(primary_key, auto_increment = false)]
+  pub day: Date,
+  /// Distinct machines, addresses and countries within the scan window
+  pub hwids: i32,
+  pub ips: i32,
+  pub countries: i32,
+  pub sessions: i32,
+  pub created_at: DateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
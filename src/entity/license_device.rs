@@ -0,0 +1,39 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::license;
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "license_devices")]
+pub struct Model {
+  #[sea_orm(primary_key)]
+  pub id: i32,
+  pub license_key: String,
+  pub hwid: String,
+  /// Last IP address the device connected from
+  pub ip: Option<String>,
+  /// Country code of the last connection, if the proxy reports it
+  pub country: Option<String>,
+  pub first_seen: DateTime,
+  pub last_seen: DateTime,
+  /// Number of sessions opened from this device
+  pub sessions: i32,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+  #[sea_orm(
+    belongs_to = "license::Entity",
+    from = "Column::LicenseKey",
+    to = "license::Column::Key"
+  )]
+  License,
+}
+
+impl Related<license::Entity> for Entity {
+  fn to() -> RelationDef {
+    Relation::License.def()
+  }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,20 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Telemetry totals of one license, the per-user rollup split by key.
+/// They stay with the key when it changes hands.
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "license_stats")]
+pub struct Model {
+  #[sea_orm(primary_key, auto_increment = false)]
+  pub license_key: String,
+  pub weekly_xp: i64,
+  pub total_xp: i64,
+  pub runtime_hours: f64,
+  pub last_updated: DateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -1,12 +1,57 @@
+pub mod admin_op;
+pub mod announcement;
+pub mod announcement_read;
+pub mod api_token;
+pub mod api_usage;
+pub mod bot_usage;
 pub mod build;
+pub mod build_artifact;
+pub mod canned_response;
+pub mod commission_boost;
+pub mod custom_field;
+pub mod download;
+pub mod download_token;
+pub mod error_report;
+pub mod faq;
+pub mod feature_flag;
 pub mod free_game;
 pub mod free_item;
+pub mod freebie_claim;
+pub mod goal;
+pub mod held_alert;
+pub mod hwid_exemption;
+pub mod incident;
+pub mod instance_stats;
 pub mod license;
+pub mod license_alert;
+pub mod license_device;
+pub mod license_stats;
+pub mod outbox;
 pub mod pending_invoice;
+pub mod plan;
+pub mod product;
 pub mod promo;
+pub mod promo_asset;
+pub mod promo_campaign;
+pub mod promo_code;
+pub mod rating;
+pub mod region_price;
+pub mod review;
+pub mod risk_score;
+pub mod sale;
+pub mod session;
+pub mod signing_key;
 pub mod stats;
+pub mod stats_daily;
+pub mod storefront;
+pub mod terms;
+pub mod terms_acceptance;
+pub mod ticket;
+pub mod ticket_message;
 pub mod transaction;
+pub mod upgrade_offer;
 pub mod user;
+pub mod user_settings;
 
 pub use license::LicenseType;
 #[allow(unused_imports)]
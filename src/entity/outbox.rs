@@ -0,0 +1,24 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Notification waiting to be sent to a user
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "outbox")]
+pub struct Model {
+  #[sea_orm(primary_key)]
+  pub id: i32,
+  pub tg_user_id: i64,
+  /// HTML message
+  #[sea_orm(column_type = "Text")]
+  pub text: String,
+  pub attempts: i32,
+  pub last_error: Option<String>,
+  pub next_attempt_at: DateTime,
+  pub sent_at: Option<DateTime>,
+  pub created_at: DateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,28 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Sellable attributes of a plan, the plan's behavior stays in code
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "plans")]
+pub struct Model {
+  /// Id of the plan in code, e.g. "month"
+  #[sea_orm(primary_key, auto_increment = false)]
+  pub name: String,
+  /// Shown to users
+  pub title: String,
+  pub days: i32,
+  /// Run for calendar months instead of `days`, month-based plans only
+  pub calendar: bool,
+  /// Base price before product, referral and other discounts
+  pub price_nano: i64,
+  /// Session limit of licenses bought with the plan
+  pub max_sessions: i32,
+  /// Inactive plans are not offered
+  pub active: bool,
+  pub updated_at: DateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
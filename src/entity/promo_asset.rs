@@ -0,0 +1,32 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(EnumIter, DeriveActiveEnum, Serialize, Deserialize)]
+#[sea_orm(rs_type = "String", db_type = "Text")]
+pub enum PromoAssetKind {
+  /// Image uploaded by an admin, stored as a Telegram file id
+  #[sea_orm(string_value = "banner")]
+  Banner,
+  /// Copy text with `{link}` and `{code}` placeholders
+  #[sea_orm(string_value = "template")]
+  Template,
+}
+
+/// Promo material served to creators
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "promo_assets")]
+pub struct Model {
+  #[sea_orm(primary_key)]
+  pub id: i32,
+  pub kind: PromoAssetKind,
+  pub title: String,
+  pub file_id: Option<String>,
+  pub text: Option<String>,
+  pub created_at: DateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
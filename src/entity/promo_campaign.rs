@@ -0,0 +1,31 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::LicenseType;
+
+/// Code users redeem with /promo for a free license
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "promo_campaigns")]
+pub struct Model {
+  /// Lowercase, also the `promo_name` of its claims
+  #[sea_orm(primary_key, auto_increment = false)]
+  pub code: String,
+  pub license_type: LicenseType,
+  pub days: i32,
+  /// None for unlimited claims
+  pub max_claims: Option<i32>,
+  pub starts_at: DateTime,
+  pub ends_at: DateTime,
+  pub created_at: DateTime,
+}
+
+impl Model {
+  pub fn is_running(&self, now: DateTime) -> bool {
+    now >= self.starts_at && now <= self.ends_at
+  }
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,35 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(EnumIter, DeriveActiveEnum, Serialize, Deserialize)]
+#[sea_orm(rs_type = "String", db_type = "Text")]
+pub enum PromoGrant {
+  /// `amount` nanoUSDT added to the balance
+  #[sea_orm(string_value = "balance")]
+  Balance,
+  /// Trial license for `amount` days
+  #[sea_orm(string_value = "trial")]
+  Trial,
+}
+
+/// Giveaway code users redeem with /redeem, unrelated to referral codes
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "promo_codes")]
+pub struct Model {
+  /// Lowercase
+  #[sea_orm(primary_key, auto_increment = false)]
+  pub code: String,
+  pub grant: PromoGrant,
+  pub amount: i64,
+  /// None for unlimited uses
+  pub max_uses: Option<i32>,
+  /// None for codes that never expire
+  pub expires_at: Option<DateTime>,
+  pub created_at: DateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,18 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Regional price of the month plan, in percent of the base price
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "region_prices")]
+pub struct Model {
+  /// ISO country code, upper case
+  #[sea_orm(primary_key, auto_increment = false)]
+  pub country: String,
+  pub percent: i32,
+  pub created_at: DateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,56 @@
+use json::Value;
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(EnumIter, DeriveActiveEnum, Serialize, Deserialize)]
+#[sea_orm(rs_type = "String", db_type = "Text")]
+pub enum ReviewKind {
+  #[sea_orm(string_value = "purchase")]
+  Purchase,
+  #[sea_orm(string_value = "extension")]
+  Extension,
+  #[sea_orm(string_value = "payout")]
+  Payout,
+  #[sea_orm(string_value = "role_change")]
+  RoleChange,
+  #[sea_orm(string_value = "transfer")]
+  Transfer,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(EnumIter, DeriveActiveEnum, Serialize, Deserialize)]
+#[sea_orm(rs_type = "String", db_type = "Text")]
+pub enum ReviewStatus {
+  #[sea_orm(string_value = "pending")]
+  Pending,
+  #[sea_orm(string_value = "approved")]
+  Approved,
+  #[sea_orm(string_value = "rejected")]
+  Rejected,
+}
+
+/// Action the risk rules held back until an admin decides on it
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "reviews")]
+pub struct Model {
+  #[sea_orm(primary_key)]
+  pub id: i32,
+  /// User the action is for
+  pub tg_user_id: i64,
+  pub kind: ReviewKind,
+  /// json `sv::review::Action`, everything needed to carry it out
+  pub action: Value,
+  pub reason: String,
+  /// Admin who started a payout or role change
+  pub requested_by: Option<i64>,
+  pub status: ReviewStatus,
+  pub created_at: DateTime,
+  pub decided_at: Option<DateTime>,
+  pub decided_by: Option<i64>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,21 @@
+use json::Value;
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Latest risk assessment of a user
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "risk_scores")]
+pub struct Model {
+  #[sea_orm(primary_key, auto_increment = false)]
+  pub tg_user_id: i64,
+  /// 0 to 100, sum of the factor points
+  pub score: i32,
+  /// json list of `sv::risk::Factor`
+  pub factors: Value,
+  pub updated_at: DateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
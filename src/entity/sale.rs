@@ -0,0 +1,20 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Happy-hour window with an extra discount on license extensions
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "sales")]
+pub struct Model {
+  #[sea_orm(primary_key)]
+  pub id: i32,
+  pub name: String,
+  pub percent: i32,
+  pub starts_at: DateTime,
+  pub ends_at: DateTime,
+  pub created_at: DateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
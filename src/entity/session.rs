@@ -0,0 +1,49 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::license;
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "sessions")]
+pub struct Model {
+  #[sea_orm(primary_key)]
+  pub id: i64,
+  /// Picked by the client, only one open row carries it
+  pub session_id: String,
+  pub license_key: String,
+  pub hwid: Option<String>,
+  /// Platform the client reported, older clients don't
+  pub platform: Option<String>,
+  /// Address the client logged in from
+  pub ip: Option<String>,
+  /// Country of `ip`, when known
+  pub country: Option<String>,
+  pub started_at: DateTime,
+  /// Flushed from memory by the GC, so it lags up to a minute
+  pub last_seen: DateTime,
+  /// Open sessions have no end and are restored on startup
+  pub ended_at: Option<DateTime>,
+  /// Why the session ended: expired, logout, kicked, dropped, invalid or
+  /// reused when the client started over with the same id
+  pub end_reason: Option<String>,
+  /// XP reported by metrics naming this session
+  pub xp: i64,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+  #[sea_orm(
+    belongs_to = "license::Entity",
+    from = "Column::LicenseKey",
+    to = "license::Column::Key"
+  )]
+  License,
+}
+
+impl Related<license::Entity> for Entity {
+  fn to() -> RelationDef {
+    Relation::License.def()
+  }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,21 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Ed25519 key pair offline license tokens are signed with
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "signing_keys")]
+pub struct Model {
+  #[sea_orm(primary_key, auto_increment = false)]
+  pub kid: String,
+  /// Base64 PKCS#8 document
+  #[serde(skip_serializing)]
+  pub private_key: String,
+  /// Base64 raw 32-byte public key
+  pub public_key: String,
+  pub created_at: DateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
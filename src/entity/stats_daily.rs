@@ -0,0 +1,21 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// `user_stats` as it stood at the end of a day, for trends over time
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "stats_daily")]
+pub struct Model {
+  #[sea_orm(primary_key, auto_increment = false)]
+  pub tg_user_id: i64,
+  #[sea_orm(primary_key, auto_increment = false)]
+  pub day: Date,
+  pub weekly_xp: i64,
+  pub total_xp: i64,
+  pub drops_count: i32,
+  pub runtime_hours: f64,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
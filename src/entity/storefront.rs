@@ -0,0 +1,38 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::product;
+
+/// White-label bot selling a single product under a partner's brand
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "storefronts")]
+pub struct Model {
+  #[sea_orm(primary_key, auto_increment = false)]
+  pub product: String,
+  pub bot_token: String,
+  /// Shown in the welcome message instead of the panel name
+  pub title: String,
+  /// Telegram username of the partner's support, without the @
+  pub support: String,
+  /// Replaces the whole welcome message when set
+  pub welcome: Option<String>,
+  pub created_at: DateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+  #[sea_orm(
+    belongs_to = "product::Entity",
+    from = "Column::Product",
+    to = "product::Column::Slug"
+  )]
+  Product,
+}
+
+impl Related<product::Entity> for Entity {
+  fn to() -> RelationDef {
+    Relation::Product.def()
+  }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,28 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::terms_acceptance;
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "terms")]
+pub struct Model {
+  #[sea_orm(primary_key)]
+  pub version: i32,
+  #[sea_orm(column_type = "Text")]
+  pub text: String,
+  pub created_at: DateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+  #[sea_orm(has_many = "terms_acceptance::Entity")]
+  Acceptances,
+}
+
+impl Related<terms_acceptance::Entity> for Entity {
+  fn to() -> RelationDef {
+    Relation::Acceptances.def()
+  }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,44 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::{terms, user};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "terms_acceptances")]
+pub struct Model {
+  #[sea_orm(primary_key, auto_increment = false)]
+  pub tg_user_id: i64,
+  #[sea_orm(primary_key, auto_increment = false)]
+  pub version: i32,
+  pub accepted_at: DateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+  #[sea_orm(
+    belongs_to = "user::Entity",
+    from = "Column::TgUserId",
+    to = "user::Column::TgUserId"
+  )]
+  User,
+  #[sea_orm(
+    belongs_to = "terms::Entity",
+    from = "Column::Version",
+    to = "terms::Column::Version"
+  )]
+  Terms,
+}
+
+impl Related<user::Entity> for Entity {
+  fn to() -> RelationDef {
+    Relation::User.def()
+  }
+}
+
+impl Related<terms::Entity> for Entity {
+  fn to() -> RelationDef {
+    Relation::Terms.def()
+  }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
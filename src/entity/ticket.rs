@@ -0,0 +1,74 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::{ticket_message, user};
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[derive(EnumIter, DeriveActiveEnum, Serialize, Deserialize)]
+#[sea_orm(rs_type = "String", db_type = "Text")]
+pub enum TicketStatus {
+  /// Waiting for a support reply
+  #[sea_orm(string_value = "open")]
+  #[default]
+  Open,
+  #[sea_orm(string_value = "answered")]
+  Answered,
+  #[sea_orm(string_value = "closed")]
+  Closed,
+}
+
+/// Queue order, higher goes first
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(EnumIter, DeriveActiveEnum, Serialize, Deserialize)]
+#[sea_orm(rs_type = "String", db_type = "Text")]
+pub enum TicketPriority {
+  #[sea_orm(string_value = "low")]
+  Low,
+  #[sea_orm(string_value = "normal")]
+  #[default]
+  Normal,
+  #[sea_orm(string_value = "high")]
+  High,
+}
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "tickets")]
+pub struct Model {
+  #[sea_orm(primary_key)]
+  pub id: i32,
+  pub tg_user_id: i64,
+  pub status: TicketStatus,
+  pub created_at: DateTime,
+  pub updated_at: DateTime,
+  pub priority: TicketPriority,
+  /// Set while the ticket waits for a support reply
+  pub waiting_since: Option<DateTime>,
+  /// SLA reminders sent during the current wait
+  pub reminders_sent: i32,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+  #[sea_orm(
+    belongs_to = "user::Entity",
+    from = "Column::TgUserId",
+    to = "user::Column::TgUserId"
+  )]
+  User,
+  #[sea_orm(has_many = "ticket_message::Entity")]
+  Messages,
+}
+
+impl Related<user::Entity> for Entity {
+  fn to() -> RelationDef {
+    Relation::User.def()
+  }
+}
+
+impl Related<ticket_message::Entity> for Entity {
+  fn to() -> RelationDef {
+    Relation::Messages.def()
+  }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
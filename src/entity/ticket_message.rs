@@ -0,0 +1,35 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::ticket;
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "ticket_messages")]
+pub struct Model {
+  #[sea_orm(primary_key)]
+  pub id: i32,
+  pub ticket_id: i32,
+  pub author_id: i64,
+  pub from_admin: bool,
+  #[sea_orm(column_type = "Text")]
+  pub text: String,
+  pub created_at: DateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+  #[sea_orm(
+    belongs_to = "ticket::Entity",
+    from = "Column::TicketId",
+    to = "ticket::Column::Id"
+  )]
+  Ticket,
+}
+
+impl Related<ticket::Entity> for Entity {
+  fn to() -> RelationDef {
+    Relation::Ticket.def()
+  }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
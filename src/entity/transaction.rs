@@ -16,6 +16,15 @@ pub enum TransactionType {
   ReferralBonus,
   #[sea_orm(string_value = "withdrawal")]
   Withdrawal,
+  /// Compensates an admin balance operation that was undone
+  #[sea_orm(string_value = "reversal")]
+  Reversal,
+  /// Balance granted by a redeemed promo code, not paid for
+  #[sea_orm(string_value = "promo_credit")]
+  PromoCredit,
+  /// Pays a purchase back, see `refund_of`
+  #[sea_orm(string_value = "refund")]
+  Refund,
 }
 
 #[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
@@ -29,6 +38,10 @@ pub struct Model {
   pub description: Option<String>,
   /// User ID of the referrer for this transaction (if applicable)
   pub referrer_id: Option<i64>,
+  /// Country whose regional price was charged
+  pub region: Option<String>,
+  /// Purchase transaction a refund pays back
+  pub refund_of: Option<i32>,
   pub created_at: DateTime,
 }
 
@@ -30,12 +30,17 @@ pub struct Model {
   pub commission_rate: i32,
   /// Discount percent for customers using this user's referral (default 3%)
   pub discount_percent: i32,
-  /// Total sales made through this user's referral
-  pub referral_sales: i32,
-  /// Total earnings from referrals (in nanoUSDT)
-  pub referral_earnings: i64,
   /// Custom referral code (only for creators/admins)
   pub referral_code: Option<String>,
+  /// Set by /delete_account, the data is wiped after the cooling-off period
+  pub deletion_requested_at: Option<DateTime>,
+  /// When the account was anonymized
+  pub deleted_at: Option<DateTime>,
+  /// Product of the white-label bot the user last started, None for the
+  /// main bot
+  pub storefront: Option<String>,
+  /// Partners selling keys on, not bound by the active license cap
+  pub is_reseller: bool,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -75,3 +80,10 @@ impl Related<transaction::Entity> for Entity {
 }
 
 impl ActiveModelBehavior for ActiveModel {}
+
+impl Model {
+  /// Resellers and admins may hold any number of active licenses
+  pub fn is_cap_exempt(&self) -> bool {
+    self.is_reseller || self.role == UserRole::Admin
+  }
+}
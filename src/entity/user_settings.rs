@@ -0,0 +1,52 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::{build::Channel, user};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "user_settings")]
+pub struct Model {
+  #[sea_orm(primary_key, auto_increment = false)]
+  pub tg_user_id: i64,
+  /// Preferred bot language code ("en", "ru")
+  pub language: String,
+  /// When the user finished (or skipped) the first-run onboarding
+  pub onboarded_at: Option<DateTime>,
+  /// Ping the user after this many minutes without sessions (opt-in)
+  pub downtime_alert_mins: Option<i32>,
+  /// Farming hours in UTC for downtime alerts, unset means all day
+  pub farm_from_hour: Option<i32>,
+  pub farm_to_hour: Option<i32>,
+  /// Notify about activations on new devices or from new countries
+  pub security_alerts: bool,
+  /// Timezone as an offset from UTC, used for license schedules
+  pub utc_offset_mins: i32,
+  /// Self-declared country code for regional pricing
+  pub country: Option<String>,
+  /// Release channel whose builds the user gets
+  pub channel: Channel,
+  /// Local hours (admins only) non-critical alerts are held in, `to` is
+  /// exclusive and the window may wrap midnight
+  pub quiet_from_hour: Option<i32>,
+  pub quiet_to_hour: Option<i32>,
+  /// Listed on /top under an alias
+  pub leaderboard: bool,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+  #[sea_orm(
+    belongs_to = "user::Entity",
+    from = "Column::TgUserId",
+    to = "user::Column::TgUserId"
+  )]
+  User,
+}
+
+impl Related<user::Entity> for Entity {
+  fn to() -> RelationDef {
+    Relation::User.def()
+  }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -4,11 +4,27 @@ use axum::{
   http::StatusCode,
   response::{IntoResponse, Response},
 };
+use chrono::{NaiveDateTime as DateTime, Utc};
 
 #[derive(Debug)]
 pub enum Promo {
+  NotFound,
   Inactive,
+  Exhausted,
   Claimed,
+  Exists,
+}
+
+/// Why a purchase was refused by the eligibility rules
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Ineligible {
+  Banned,
+  Country(String),
+  UnpaidInvoices(u64),
+  LicenseLimit(u64),
+  TrialTaken,
+  /// Risk score at or above the review threshold
+  HighRisk(i32),
 }
 
 #[derive(thiserror::Error, Debug)]
@@ -20,6 +36,8 @@ pub enum Error {
   UserNotFound,
   #[error("License expired or blocked")]
   LicenseInvalid,
+  #[error("License suspended until {0}")]
+  LicenseSuspended(DateTime),
   #[error("License already linked to another user")]
   LicenseAlreadyLinked,
   #[error("Session limit reached")]
@@ -44,12 +62,46 @@ pub enum Error {
   InvalidArgs(String),
   #[error("CryptoBot API error: {0}")]
   CryptoBot(String),
+  #[error("Payments are temporarily unavailable")]
+  PaymentsUnavailable,
   #[error("Invoice not found")]
   InvoiceNotFound,
+  #[error("Ticket not found")]
+  TicketNotFound,
+  #[error("Ticket is closed")]
+  TicketClosed,
+  #[error("Invalid or missing API token")]
+  Unauthorized,
+  #[error("Honeypot license used")]
+  Honeypot,
+  #[error("Too many licenses used on this machine")]
+  HwidLimit { trial: bool },
+  #[error("License used outside of its schedule")]
+  OutsideSchedule { from: i32, to: i32 },
+  #[error("Regional license used from another country")]
+  RegionMismatch(String),
+  #[error("Product not found: {0}")]
+  ProductNotFound(String),
+  #[error("Plan is not sold: {0}")]
+  PlanUnavailable(String),
+  #[error("License belongs to another product")]
+  WrongProduct(String),
+  #[error("Report export failed: {0}")]
+  Export(String),
+  #[error("Freebie not found")]
+  FreebieNotFound,
+  #[error("Freebie already claimed")]
+  FreebieClaimed,
+  #[error("Daily freebie limit reached: {0}")]
+  FreebieLimit(u64),
+  #[error("Purchase refused: {0:?}")]
+  Ineligible(Ineligible),
   #[error("DB error: {0}")]
   Database(#[from] sea_orm::DbErr),
   #[error("IO error: {0}")]
   Io(#[from] io::Error),
+  #[error("Server is under maintenance")]
+  Maintenance,
   #[error("Internal error: {0}")]
   Internal(String),
 }
@@ -60,14 +112,23 @@ impl Error {
       Error::LicenseNotFound => "Key not found".into(),
       Error::UserNotFound => "User not found".into(),
       Error::LicenseInvalid => "License expired or blocked".into(),
+      Error::LicenseSuspended(until) => format!(
+        "License is on a cool-down, it works again in {}",
+        crate::utils::format_duration(*until - Utc::now().naive_utc())
+      ),
       Error::LicenseAlreadyLinked => {
         "This license is already linked to another user".into()
       }
       Error::SessionLimitReached => "Session limit reached".into(),
+      Error::Promo(Promo::NotFound) => "Promo code not found".into(),
       Error::Promo(Promo::Inactive) => "Promo is not active right now".into(),
+      Error::Promo(Promo::Exhausted) => {
+        "This promo has run out, all codes are claimed".into()
+      }
       Error::Promo(Promo::Claimed) => {
         "You have already claimed this promo".into()
       }
+      Error::Promo(Promo::Exists) => "Promo code already exists".into(),
       Error::BuildNotFound => "Build not found".into(),
       Error::BuildInactive => "Build is already yanked".into(),
       Error::BuildAlreadyActive => "Build is already active".into(),
@@ -79,17 +140,75 @@ impl Error {
       }
       Error::InvalidArgs(msg) => msg.clone(),
       Error::CryptoBot(msg) => format!("Payment error: {}", msg),
+      Error::PaymentsUnavailable => "Crypto payments are temporarily down, \
+        try again later or contact support to top up manually"
+        .into(),
       Error::InvoiceNotFound => "Invoice not found".into(),
+      Error::TicketNotFound => "Ticket not found".into(),
+      Error::TicketClosed => "Ticket is already closed".into(),
+      Error::Unauthorized => "Invalid or missing API token".into(),
+      // indistinguishable from a wrong key on purpose
+      Error::Honeypot => "Key not found".into(),
+      Error::HwidLimit { trial: true } => {
+        "This machine has already used a trial key".into()
+      }
+      Error::HwidLimit { trial: false } => {
+        "Too many licenses used on this machine".into()
+      }
+      Error::OutsideSchedule { from, to } => format!(
+        "This license only works from {:02}:00 to {:02}:00 (your time)",
+        from, to
+      ),
+      Error::RegionMismatch(region) => format!(
+        "This key was bought at the regional price for {} and only works there",
+        region
+      ),
+      Error::ProductNotFound(slug) => format!("Unknown product '{}'", slug),
+      Error::PlanUnavailable(plan) => {
+        format!("The {} plan is not available right now", plan)
+      }
+      Error::WrongProduct(product) => {
+        format!("This key is for another product ({})", product)
+      }
+      Error::Export(msg) => format!("Report export failed: {}", msg),
+      Error::FreebieNotFound => "This freebie is no longer available".into(),
+      Error::FreebieClaimed => "You have already claimed this freebie".into(),
+      Error::FreebieLimit(limit) => {
+        format!("You can claim up to {} freebies a day, come back later", limit)
+      }
+      Error::Ineligible(Ineligible::Banned) => {
+        "Purchases are disabled for your account, contact support".into()
+      }
+      Error::Ineligible(Ineligible::Country(country)) => {
+        format!("Purchases are not available in your country ({})", country)
+      }
+      Error::Ineligible(Ineligible::UnpaidInvoices(max)) => format!(
+        "You have {} or more unpaid invoices, pay or let them expire first",
+        max
+      ),
+      Error::Ineligible(Ineligible::LicenseLimit(max)) => {
+        format!("You can hold up to {} active licenses at once", max)
+      }
+      Error::Ineligible(Ineligible::TrialTaken) => {
+        "The trial is only for new customers".into()
+      }
+      Error::Ineligible(Ineligible::HighRisk(_)) => {
+        "This purchase needs a manual review, contact support".into()
+      }
       Error::Database(e) => format!("Database error: {}", e),
       Error::Io(e) => format!("IO error: {}", e),
+      Error::Maintenance => {
+        "The server is under maintenance, try again in a minute".into()
+      }
       Error::Internal(msg) => format!("Internal error: {}", msg),
     }
   }
 }
 
-impl IntoResponse for Error {
-  fn into_response(self) -> Response {
-    let (status, message) = match &self {
+impl Error {
+  /// HTTP status and short message of the API error body
+  pub fn status(&self) -> (StatusCode, &str) {
+    match self {
       Error::Database(_) => {
         (StatusCode::INTERNAL_SERVER_ERROR, "Database error")
       }
@@ -98,18 +217,30 @@ impl IntoResponse for Error {
       Error::LicenseInvalid => {
         (StatusCode::FORBIDDEN, "License expired or blocked")
       }
+      Error::LicenseSuspended(_) => {
+        (StatusCode::FORBIDDEN, "License temporarily suspended")
+      }
       Error::LicenseAlreadyLinked => {
         (StatusCode::CONFLICT, "License already linked to another user")
       }
       Error::SessionLimitReached => {
         (StatusCode::CONFLICT, "Session limit reached")
       }
+      Error::Promo(Promo::NotFound) => {
+        (StatusCode::NOT_FOUND, "Promo code not found")
+      }
       Error::Promo(Promo::Inactive) => {
         (StatusCode::BAD_REQUEST, "Promo is not active")
       }
+      Error::Promo(Promo::Exhausted) => {
+        (StatusCode::GONE, "Promo is exhausted")
+      }
       Error::Promo(Promo::Claimed) => {
         (StatusCode::CONFLICT, "Promo already claimed")
       }
+      Error::Promo(Promo::Exists) => {
+        (StatusCode::CONFLICT, "Promo code already exists")
+      }
       Error::BuildNotFound => (StatusCode::NOT_FOUND, "Build not found"),
       Error::BuildInactive => (StatusCode::BAD_REQUEST, "Build already yanked"),
       Error::BuildAlreadyActive => {
@@ -129,13 +260,56 @@ impl IntoResponse for Error {
       }
       Error::InvalidArgs(msg) => (StatusCode::BAD_REQUEST, msg.as_str()),
       Error::CryptoBot(_) => (StatusCode::BAD_GATEWAY, "Payment service error"),
+      Error::PaymentsUnavailable => (
+        StatusCode::SERVICE_UNAVAILABLE,
+        "Payments are temporarily unavailable",
+      ),
       Error::InvoiceNotFound => (StatusCode::NOT_FOUND, "Invoice not found"),
+      Error::TicketNotFound => (StatusCode::NOT_FOUND, "Ticket not found"),
+      Error::TicketClosed => (StatusCode::CONFLICT, "Ticket is closed"),
+      Error::Unauthorized => {
+        (StatusCode::UNAUTHORIZED, "Invalid or missing API token")
+      }
+      Error::Honeypot => (StatusCode::NOT_FOUND, "License not found"),
+      Error::HwidLimit { .. } => {
+        (StatusCode::FORBIDDEN, "Too many licenses used on this machine")
+      }
+      Error::OutsideSchedule { .. } => {
+        (StatusCode::FORBIDDEN, "License used outside of its schedule")
+      }
+      Error::RegionMismatch(_) => {
+        (StatusCode::FORBIDDEN, "License used outside of its region")
+      }
+      Error::ProductNotFound(_) => (StatusCode::NOT_FOUND, "Product not found"),
+      Error::PlanUnavailable(_) => {
+        (StatusCode::NOT_FOUND, "Plan not available")
+      }
+      Error::WrongProduct(_) => {
+        (StatusCode::FORBIDDEN, "License belongs to another product")
+      }
+      Error::Export(_) => (StatusCode::BAD_GATEWAY, "Report export failed"),
+      Error::FreebieNotFound => (StatusCode::NOT_FOUND, "Freebie not found"),
+      Error::FreebieClaimed => {
+        (StatusCode::CONFLICT, "Freebie already claimed")
+      }
+      Error::FreebieLimit(_) => {
+        (StatusCode::TOO_MANY_REQUESTS, "Daily freebie limit reached")
+      }
+      Error::Ineligible(_) => (StatusCode::FORBIDDEN, "Purchase refused"),
       Error::Io(_) => (StatusCode::INTERNAL_SERVER_ERROR, "IO error"),
+      Error::Maintenance => {
+        (StatusCode::SERVICE_UNAVAILABLE, "Server is under maintenance")
+      }
       Error::Internal(_) => {
         (StatusCode::INTERNAL_SERVER_ERROR, "Internal error")
       }
-    };
+    }
+  }
+}
 
+impl IntoResponse for Error {
+  fn into_response(self) -> Response {
+    let (status, message) = self.status();
     let body = json::json!({
       "success": false,
       "error": message
@@ -0,0 +1,121 @@
+//! Country of an IP address from a range list such as the free DB-IP
+//! "IP to Country Lite" CSV: `start_ip,end_ip,country` per line.
+
+use std::{fmt, io, net::IpAddr, path::Path};
+
+/// Address ranges sorted by their start, both ends inclusive
+pub struct GeoIp {
+  v4: Vec<(u32, u32, [u8; 2])>,
+  v6: Vec<(u128, u128, [u8; 2])>,
+}
+
+impl fmt::Debug for GeoIp {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "GeoIp({} v4, {} v6 ranges)", self.v4.len(), self.v6.len())
+  }
+}
+
+fn country(code: &str) -> Option<[u8; 2]> {
+  match code.trim().trim_matches('"').as_bytes() {
+    &[a, b] if a.is_ascii_alphabetic() && b.is_ascii_alphabetic() => {
+      Some([a.to_ascii_uppercase(), b.to_ascii_uppercase()])
+    }
+    _ => None,
+  }
+}
+
+fn addr(ip: &str) -> Option<IpAddr> {
+  ip.trim().trim_matches('"').parse().ok()
+}
+
+impl GeoIp {
+  pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+    let csv = std::fs::read_to_string(path)?;
+    let geoip = Self::parse(&csv);
+    if geoip.v4.is_empty() && geoip.v6.is_empty() {
+      return Err(io::Error::new(
+        io::ErrorKind::InvalidData,
+        "no address ranges found",
+      ));
+    }
+    Ok(geoip)
+  }
+
+  /// Lines that don't parse, like a header or unassigned "ZZ" ranges,
+  /// are skipped
+  pub fn parse(csv: &str) -> Self {
+    let (mut v4, mut v6) = (Vec::new(), Vec::new());
+    for line in csv.lines() {
+      let mut fields = line.split(',');
+      let (Some(start), Some(end), Some(code)) =
+        (fields.next(), fields.next(), fields.next())
+      else {
+        continue;
+      };
+      let Some(code) = country(code).filter(|code| code != b"ZZ") else {
+        continue;
+      };
+      match (addr(start), addr(end)) {
+        (Some(IpAddr::V4(start)), Some(IpAddr::V4(end))) if start <= end => {
+          v4.push((start.into(), end.into(), code));
+        }
+        (Some(IpAddr::V6(start)), Some(IpAddr::V6(end))) if start <= end => {
+          v6.push((start.into(), end.into(), code));
+        }
+        _ => {}
+      }
+    }
+    v4.sort_unstable_by_key(|range| range.0);
+    v6.sort_unstable_by_key(|range| range.0);
+    Self { v4, v6 }
+  }
+
+  /// Two-letter country code, upper case
+  pub fn lookup(&self, ip: IpAddr) -> Option<String> {
+    let code = match ip {
+      IpAddr::V4(ip) => find(&self.v4, u32::from(ip)),
+      IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+        Some(ip) => find(&self.v4, u32::from(ip)),
+        None => find(&self.v6, u128::from(ip)),
+      },
+    }?;
+    Some(String::from_utf8_lossy(&code).into_owned())
+  }
+}
+
+fn find<T: Ord + Copy>(ranges: &[(T, T, [u8; 2])], ip: T) -> Option<[u8; 2]> {
+  // last range starting at or before the address
+  let index = ranges.partition_point(|range| range.0 <= ip).checked_sub(1)?;
+  let (_, end, code) = ranges[index];
+  (ip <= end).then_some(code)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_lookup() {
+    let geoip = GeoIp::parse(
+      "ip_start,ip_end,country\n\
+      1.0.0.0,1.0.0.255,AU\n\
+      \"1.0.4.0\",\"1.0.7.255\",\"au\"\n\
+      5.0.0.0,5.0.0.255,ZZ\n\
+      2.16.0.0,2.16.255.255,DE\n\
+      2001:db8::,2001:db8::ffff,NL\n\
+      garbage\n",
+    );
+    let lookup = |ip: &str| geoip.lookup(ip.parse().unwrap());
+
+    assert_eq!(lookup("1.0.0.7").as_deref(), Some("AU"));
+    assert_eq!(lookup("1.0.5.1").as_deref(), Some("AU"));
+    assert_eq!(lookup("2.16.3.4").as_deref(), Some("DE"));
+    assert_eq!(lookup("::ffff:2.16.3.4").as_deref(), Some("DE"));
+    assert_eq!(lookup("2001:db8::12").as_deref(), Some("NL"));
+    // gaps, unassigned ranges and addresses past the last range
+    assert_eq!(lookup("1.0.1.0"), None);
+    assert_eq!(lookup("5.0.0.1"), None);
+    assert_eq!(lookup("0.0.0.1"), None);
+    assert_eq!(lookup("9.9.9.9"), None);
+  }
+}
@@ -1,9 +1,12 @@
 #![allow(irrefutable_let_patterns)]
 
+mod chart;
 mod entity;
 mod error;
+mod geoip;
 mod plugins;
 mod prelude;
+mod qr;
 mod state;
 mod sv;
 mod utils;
@@ -16,6 +19,11 @@ use tracing_subscriber::{
 
 use crate::{plugins::*, prelude::*, state::AppState};
 
+/// Boolean variable, set by "true" or "1"
+fn flag(name: &str) -> bool {
+  env::var(name).map(|v| v == "true" || v == "1").unwrap_or(false)
+}
+
 /// Validate required environment variables and return detailed error messages
 fn validate_env() -> Result<(), String> {
   let mut missing: Vec<&str> = Vec::new();
@@ -50,6 +58,113 @@ fn validate_env() -> Result<(), String> {
     missing.push("SERVER_SECRET");
   }
 
+  for name in [
+    "TICKET_SLA_HOURS",
+    "BACKUP_KEEP_DAILY",
+    "BACKUP_KEEP_WEEKLY",
+    "HWID_MAX_LICENSES",
+    "HWID_MAX_TRIALS",
+    "HWID_WINDOW_HOURS",
+    "SHARING_MAX_HWIDS",
+    "SHARING_MAX_IPS",
+    "SHARING_WINDOW_HOURS",
+    "DELETION_COOLOFF_DAYS",
+    "UNDO_WINDOW_MINUTES",
+    "FREEBIE_DAILY_LIMIT",
+    "MAX_UNPAID_INVOICES",
+    "MAX_LICENSES_PER_USER",
+    "OFFLINE_TOKEN_HOURS",
+    "RISK_REVIEW_SCORE",
+    "TRIAL_NUDGE_DAYS",
+    "TRIAL_NUDGE_XP",
+    "TRIAL_NUDGE_PERCENT",
+    "TRIAL_NUDGE_VALID_HOURS",
+    "SUSPEND_STRIKES",
+    "SUSPEND_HOURS",
+    "ABUSE_DAYS",
+    "HEARTBEAT_MIN_INTERVAL_SECS",
+    "CLOCK_SKEW_SECS",
+    "DB_CHECK_INTERVAL_SECS",
+    "WAL_CHECKPOINT_MB",
+    "DISK_ALERT_MB",
+  ] {
+    if let Ok(value) = env::var(name)
+      && value.trim().parse::<u32>().is_err()
+    {
+      invalid.push(format!(
+        "{}: expected a non-negative integer ('{}')",
+        name,
+        value.trim()
+      ));
+    }
+  }
+
+  if let Ok(ids) = env::var("OWNER_IDS")
+    && ids.split(',').any(|id| id.trim().parse::<i64>().is_err())
+  {
+    invalid.push(format!("OWNER_IDS: expected user IDs ('{}')", ids.trim()));
+  }
+
+  if env::var("GOOGLE_SHEET_ID").is_ok()
+    && env::var("GOOGLE_SERVICE_ACCOUNT").is_err()
+  {
+    invalid.push(
+      "GOOGLE_SHEET_ID: GOOGLE_SERVICE_ACCOUNT key file is required".into(),
+    );
+  }
+
+  if let Ok(codes) = env::var("BLOCKED_COUNTRIES")
+    && codes
+      .split(',')
+      .any(|code| sv::pricing::normalize_country(code).is_none())
+  {
+    invalid.push(format!(
+      "BLOCKED_COUNTRIES: expected country codes ('{}')",
+      codes.trim()
+    ));
+  }
+
+  if let Ok(value) = env::var("TRIAL_NUDGE_PERCENT")
+    && value.trim().parse::<u32>().is_ok_and(|percent| percent >= 100)
+  {
+    invalid.push(format!(
+      "TRIAL_NUDGE_PERCENT: expected a percent below 100 ('{}')",
+      value.trim()
+    ));
+  }
+
+  if let Ok(value) = env::var("TRIAL_NUDGE_RUNTIME_HOURS")
+    && !value.trim().parse::<f64>().is_ok_and(|hours| hours >= 0.0)
+  {
+    invalid.push(format!(
+      "TRIAL_NUDGE_RUNTIME_HOURS: expected a non-negative number ('{}')",
+      value.trim()
+    ));
+  }
+
+  if let Ok(value) = env::var("DELETION_REFUND")
+    && let Err(e) = value.parse::<sv::account::RefundPolicy>()
+  {
+    invalid.push(format!("DELETION_REFUND: {}", e));
+  }
+
+  match env::var("APP_ENV").map(|value| value.parse::<state::Environment>()) {
+    Ok(Err(e)) => invalid.push(format!("APP_ENV: {}", e)),
+    // a staging copy must never take real payments
+    Ok(Ok(environment))
+      if environment.is_staging()
+        && env::var("CRYPTOBOT_API_TOKEN").is_ok()
+        && !flag("CRYPTOBOT_TESTNET") =>
+    {
+      invalid.push(
+        "CRYPTOBOT_API_TOKEN: staging only runs against the CryptoBot \
+        testnet, set CRYPTOBOT_TESTNET=true"
+          .into(),
+      );
+    }
+    _ => {}
+  }
+
   if !missing.is_empty() || !invalid.is_empty() {
     let mut msg = String::new();
     if !missing.is_empty() {
@@ -70,17 +185,174 @@ fn validate_env() -> Result<(), String> {
     );
     msg.push_str("  TELOXIDE_TOKEN - Telegram Bot API token\n");
     msg.push_str("  SERVER_SECRET  - Secret key for server authentication\n");
+    msg
+      .push_str("\nRun with --check to validate the configuration and exit.\n");
+    msg.push_str(
+      "Run with --decrypt <file> to decrypt a backup with BACKUP_KEY.\n",
+    );
     msg.push_str("\nOptional environment variables:\n");
+    msg.push_str(
+      "  APP_ENV        - prod or staging: stamps messages, only messages admins, testnet payments (default: prod)\n",
+    );
     msg.push_str("  DATABASE_URL   - SQLite database URL (default: sqlite:licenses.db?mode=rwc)\n");
     msg.push_str(
       "  BASE_URL       - Server base URL (default: http://localhost:3000)\n",
     );
+    msg.push_str(
+      "  TICKET_SLA_HOURS - Hours before unanswered tickets alert admins (default: 12, 0 disables)\n",
+    );
+    msg.push_str(
+      "  HWID_MAX_LICENSES - Distinct licenses per machine (default: 0, disabled)\n",
+    );
+    msg.push_str(
+      "  HWID_MAX_TRIALS - Distinct trial licenses per machine (default: 0, disabled)\n",
+    );
+    msg.push_str(
+      "  HWID_WINDOW_HOURS - Window for the HWID limits (default: 168)\n",
+    );
+    msg.push_str(
+      "  SHARING_MAX_HWIDS - Machines per license before the sharing digest flags it (default: 0, disabled)\n",
+    );
+    msg.push_str(
+      "  SHARING_MAX_IPS - Addresses per license before the sharing digest flags it (default: 0, disabled)\n",
+    );
+    msg.push_str(
+      "  SHARING_WINDOW_HOURS - Window for the sharing limits (default: 24)\n",
+    );
+    msg.push_str(
+      "  DELETION_COOLOFF_DAYS - Days before a deleted account is wiped (default: 7)\n",
+    );
+    msg.push_str(
+      "  DELETION_REFUND - Balance of deleted accounts: forfeit or manual (default: manual)\n",
+    );
+    msg.push_str(
+      "  OWNER_IDS - Admins allowed to /undo balance operations (default: first of ADMIN_IDS)\n",
+    );
+    msg.push_str(
+      "  UNDO_WINDOW_MINUTES - How long balance operations can be undone (default: 60)\n",
+    );
+    msg.push_str(
+      "  FREEBIE_DAILY_LIMIT - Freebies one user may claim per day (default: 3)\n",
+    );
+    msg.push_str(
+      "  PURCHASE_BLOCK_BANNED - Refuse purchases from users with a blocked key (default: false)\n",
+    );
+    msg.push_str(
+      "  BLOCKED_COUNTRIES - Comma-separated country codes purchases are refused from\n",
+    );
+    msg.push_str(
+      "  MAX_UNPAID_INVOICES - Open invoices that block purchases (default: 0, disabled)\n",
+    );
+    msg.push_str(
+      "  MAX_LICENSES_PER_USER - Active licenses per user, resellers exempt (default: 0, disabled)\n",
+    );
+    msg.push_str(
+      "  TRIAL_FIRST_ONLY - Sell the trial plan only to new customers (default: false)\n",
+    );
+    msg.push_str(
+      "  RISK_REVIEW_SCORE - Risk score from which purchases need a manual review (default: 0, disabled)\n",
+    );
+    msg.push_str(
+      "  GEOIP_CSV - start_ip,end_ip,country ranges (e.g. DB-IP Country Lite) to locate clients without a cf-ipcountry header\n",
+    );
+    msg.push_str(
+      "  TRANSFER_REVIEW - License transfers between users need an admin's approval (default: false)\n",
+    );
+    msg.push_str(
+      "  OFFLINE_TOKEN_HOURS - Lifetime of offline license tokens (default: 24)\n",
+    );
+    msg.push_str(
+      "  TRIAL_NUDGE_PERCENT - Starter discount for heavy trial users (default: 0, disabled)\n",
+    );
+    msg.push_str(
+      "  TRIAL_NUDGE_RUNTIME_HOURS - Farming hours that qualify a trial user (default: 24)\n",
+    );
+    msg.push_str(
+      "  TRIAL_NUDGE_XP - XP that qualifies a trial user (default: 0, ignored)\n",
+    );
+    msg.push_str(
+      "  TRIAL_NUDGE_DAYS - Only users registered this many days ago (default: 3)\n",
+    );
+    msg.push_str(
+      "  TRIAL_NUDGE_VALID_HOURS - How long the offer can be used (default: 48)\n",
+    );
+    msg.push_str(
+      "  SUSPEND_STRIKES - Session limit or sharing violations per hour that suspend a key (default: 0, disabled)\n",
+    );
+    msg.push_str(
+      "  SUSPEND_HOURS - Length of an automatic suspension (default: 24)\n",
+    );
+    msg.push_str(
+      "  ABUSE_DAYS - Days of the past week with rate-limited requests that halve a key's limits (default: 0, disabled)\n",
+    );
+    msg.push_str(
+      "  REQUIRE_NONCE - Refuse heartbeats and metrics without a signed nonce (default: false)\n",
+    );
+    msg.push_str(
+      "  HEARTBEAT_MIN_INTERVAL_SECS - Least seconds between heartbeats of one session (default: 5, 0 disables)\n",
+    );
+    msg.push_str(
+      "  CLOCK_SKEW_SECS - Client clock error tolerated around expiry (default: 120)\n",
+    );
+    msg.push_str(
+      "  DB_CHECK_INTERVAL_SECS - Database and disk size checks (default: 300, 0 disables)\n",
+    );
+    msg.push_str(
+      "  WAL_CHECKPOINT_MB - WAL size that forces a checkpoint (default: 64)\n",
+    );
+    msg.push_str(
+      "  DISK_ALERT_MB - Free disk space that alerts admins (default: 1024)\n",
+    );
+    msg.push_str(
+      "  ADMIN_API_KEYS - Comma-separated keys for /admin/api/* and /metrics (default: disabled)\n",
+    );
+    msg
+      .push_str("  GOOGLE_SHEET_ID - Spreadsheet to append daily metrics to\n");
+    msg.push_str(
+      "  GOOGLE_SERVICE_ACCOUNT - Path to the service account JSON key\n",
+    );
+    msg.push_str(
+      "  GOOGLE_SHEET_RANGE - Sheet the rows go to (default: Sheet1)\n",
+    );
+    msg.push_str("  REPORT_CSV_URL - URL daily metrics are POSTed to as CSV\n");
+    msg.push_str(
+      "  S3_BUCKET - Bucket backups are uploaded to (default: Telegram only)\n",
+    );
+    msg.push_str(
+      "  S3_ENDPOINT - S3-compatible endpoint (default: https://s3.amazonaws.com)\n",
+    );
+    msg.push_str("  S3_REGION - Bucket region (default: us-east-1)\n");
+    msg.push_str("  S3_ACCESS_KEY, S3_SECRET_KEY - Bucket credentials\n");
+    msg.push_str("  S3_PREFIX - Key prefix of backups (default: backups/)\n");
+    msg.push_str(
+      "  BACKUP_KEY - 32-byte hex or base64 key backups are encrypted with\n",
+    );
+    msg.push_str(
+      "  BACKUP_DIR - Where snapshots are kept (default: ./backups)\n",
+    );
+    msg.push_str(
+      "  BACKUP_KEEP_DAILY, BACKUP_KEEP_WEEKLY - Days and weeks of backups kept (default: 7, 4; both 0 keeps all)\n",
+    );
     return Err(msg);
   }
 
   Ok(())
 }
 
+/// Write the plain database next to an encrypted backup
+fn decrypt_backup(file: Option<&str>) -> anyhow::Result<String> {
+  let file = file.context("Usage: --decrypt <backup.db.enc>")?;
+  let key = env::var("BACKUP_KEY").context("BACKUP_KEY not set")?;
+  let key = sv::backup::BackupKey::parse(&key)?;
+
+  let plain = key.open(&std::fs::read(file)?)?;
+  let out = file
+    .strip_suffix(&format!(".{}", sv::backup::EXTENSION))
+    .map_or_else(|| format!("{}.db", file), str::to_string);
+  std::fs::write(&out, plain)?;
+  Ok(out)
+}
+
 #[tokio::main]
 async fn main() {
   dotenvy::dotenv().ok();
@@ -92,18 +364,39 @@ async fn main() {
     .with(tracing_subscriber::fmt::layer())
     .init();
 
+  // `--decrypt <file>` only needs the backup key, not a full configuration
+  let args: Vec<String> = env::args().collect();
+  if let Some(pos) = args.iter().position(|arg| arg == "--decrypt") {
+    match decrypt_backup(args.get(pos + 1).map(String::as_str)) {
+      Ok(out) => println!("Decrypted into {}", out),
+      Err(err) => {
+        eprintln!("Failed to decrypt backup: {}", err);
+        std::process::exit(1);
+      }
+    }
+    return;
+  }
+
   // Validate environment variables before proceeding
   if let Err(msg) = validate_env() {
     eprintln!("Configuration error:\n\n{}", msg);
     std::process::exit(1);
   }
 
-  let admins: HashSet<i64> = env::var("ADMIN_IDS")
+  let admin_ids: Vec<i64> = env::var("ADMIN_IDS")
     .expect("ADMIN_IDS not set")
     .split(',')
     .filter(|s| !s.trim().is_empty())
     .map(|id| id.trim().parse().expect("Invalid Admin ID format"))
     .collect();
+  let owners: HashSet<i64> = match env::var("OWNER_IDS") {
+    Ok(ids) => ids
+      .split(',')
+      .map(|id| id.trim().parse().expect("Invalid OWNER_IDS format"))
+      .collect(),
+    Err(_) => admin_ids.iter().take(1).copied().collect(),
+  };
+  let admins: HashSet<i64> = admin_ids.into_iter().collect();
 
   let db_url = env::var("DATABASE_URL")
     .unwrap_or_else(|_| "sqlite:licenses.db?mode=rwc".into());
@@ -114,17 +407,222 @@ async fn main() {
 
   info!("Starting License Server v{}", env!("CARGO_PKG_VERSION"));
 
-  let config = state::Config { base_url, ..Default::default() };
+  let mut config = state::Config { base_url, owners, ..Default::default() };
+  if let Ok(environment) = env::var("APP_ENV") {
+    config.environment = environment.parse().expect("Invalid APP_ENV format");
+  }
+  let staging = config.environment.is_staging();
+  if staging {
+    warn!("Running as staging, only admins get messages");
+  }
+  if let Ok(hours) = env::var("TICKET_SLA_HOURS") {
+    config.ticket_sla_hours =
+      hours.trim().parse().expect("Invalid TICKET_SLA_HOURS format");
+  }
+  if let Ok(max) = env::var("HWID_MAX_LICENSES") {
+    config.hwid_max_licenses =
+      max.trim().parse().expect("Invalid HWID_MAX_LICENSES format");
+  }
+  if let Ok(max) = env::var("HWID_MAX_TRIALS") {
+    config.hwid_max_trials =
+      max.trim().parse().expect("Invalid HWID_MAX_TRIALS format");
+  }
+  if let Ok(hours) = env::var("HWID_WINDOW_HOURS") {
+    config.hwid_window_hours =
+      hours.trim().parse().expect("Invalid HWID_WINDOW_HOURS format");
+  }
+  if let Ok(max) = env::var("SHARING_MAX_HWIDS") {
+    config.sharing_max_hwids =
+      max.trim().parse().expect("Invalid SHARING_MAX_HWIDS format");
+  }
+  if let Ok(max) = env::var("SHARING_MAX_IPS") {
+    config.sharing_max_ips =
+      max.trim().parse().expect("Invalid SHARING_MAX_IPS format");
+  }
+  if let Ok(hours) = env::var("SHARING_WINDOW_HOURS") {
+    config.sharing_window_hours =
+      hours.trim().parse().expect("Invalid SHARING_WINDOW_HOURS format");
+  }
+  if let Ok(days) = env::var("DELETION_COOLOFF_DAYS") {
+    config.deletion_cooloff_days =
+      days.trim().parse().expect("Invalid DELETION_COOLOFF_DAYS format");
+  }
+  if let Ok(mins) = env::var("UNDO_WINDOW_MINUTES") {
+    config.undo_window_mins =
+      mins.trim().parse().expect("Invalid UNDO_WINDOW_MINUTES format");
+  }
+  if let Ok(limit) = env::var("FREEBIE_DAILY_LIMIT") {
+    config.freebie_daily_limit =
+      limit.trim().parse().expect("Invalid FREEBIE_DAILY_LIMIT format");
+  }
+  config.transfer_review = flag("TRANSFER_REVIEW");
+  if let Ok(path) = env::var("GEOIP_CSV") {
+    let geoip = geoip::GeoIp::load(&path)
+      .unwrap_or_else(|e| panic!("Invalid GEOIP_CSV '{}': {}", path, e));
+    info!("GeoIP loaded: {:?}", geoip);
+    config.geoip = Some(Arc::new(geoip));
+  }
+  config.trusted_proxy = flag("TRUSTED_PROXY");
+  let rules = &mut config.purchase_rules;
+  rules.block_banned = flag("PURCHASE_BLOCK_BANNED");
+  rules.trial_first_only = flag("TRIAL_FIRST_ONLY");
+  if let Ok(codes) = env::var("BLOCKED_COUNTRIES") {
+    rules.blocked_countries =
+      codes.split(',').filter_map(sv::pricing::normalize_country).collect();
+  }
+  if let Ok(max) = env::var("MAX_UNPAID_INVOICES") {
+    rules.max_unpaid_invoices =
+      max.trim().parse().expect("Invalid MAX_UNPAID_INVOICES format");
+  }
+  if let Ok(max) = env::var("MAX_LICENSES_PER_USER") {
+    rules.max_licenses =
+      max.trim().parse().expect("Invalid MAX_LICENSES_PER_USER format");
+  }
+  if let Ok(score) = env::var("RISK_REVIEW_SCORE") {
+    rules.review_risk_score =
+      score.trim().parse().expect("Invalid RISK_REVIEW_SCORE format");
+  }
+  if let Ok(hours) = env::var("OFFLINE_TOKEN_HOURS") {
+    config.offline_token_hours =
+      hours.trim().parse().expect("Invalid OFFLINE_TOKEN_HOURS format");
+  }
+  let nudge = &mut config.trial_nudge;
+  if let Ok(percent) = env::var("TRIAL_NUDGE_PERCENT") {
+    nudge.percent =
+      percent.trim().parse().expect("Invalid TRIAL_NUDGE_PERCENT format");
+  }
+  if let Ok(hours) = env::var("TRIAL_NUDGE_RUNTIME_HOURS") {
+    nudge.min_runtime_hours =
+      hours.trim().parse().expect("Invalid TRIAL_NUDGE_RUNTIME_HOURS format");
+  }
+  if let Ok(xp) = env::var("TRIAL_NUDGE_XP") {
+    nudge.min_xp = xp.trim().parse().expect("Invalid TRIAL_NUDGE_XP format");
+  }
+  if let Ok(days) = env::var("TRIAL_NUDGE_DAYS") {
+    nudge.within_days =
+      days.trim().parse().expect("Invalid TRIAL_NUDGE_DAYS format");
+  }
+  if let Ok(hours) = env::var("TRIAL_NUDGE_VALID_HOURS") {
+    nudge.valid_hours =
+      hours.trim().parse().expect("Invalid TRIAL_NUDGE_VALID_HOURS format");
+  }
+  if let Ok(strikes) = env::var("SUSPEND_STRIKES") {
+    config.suspend_strikes =
+      strikes.trim().parse().expect("Invalid SUSPEND_STRIKES format");
+  }
+  if let Ok(days) = env::var("ABUSE_DAYS") {
+    config.abuse_days = days.trim().parse().expect("Invalid ABUSE_DAYS format");
+  }
+  config.require_nonce = flag("REQUIRE_NONCE");
+  if let Ok(secs) = env::var("HEARTBEAT_MIN_INTERVAL_SECS") {
+    config.heartbeat_min_interval_secs =
+      secs.trim().parse().expect("Invalid HEARTBEAT_MIN_INTERVAL_SECS format");
+  }
+  if let Ok(hours) = env::var("SUSPEND_HOURS") {
+    config.suspend_hours =
+      hours.trim().parse().expect("Invalid SUSPEND_HOURS format");
+  }
+  if let Ok(secs) = env::var("CLOCK_SKEW_SECS") {
+    config.clock_skew_secs =
+      secs.trim().parse().expect("Invalid CLOCK_SKEW_SECS format");
+  }
+  if let Ok(secs) = env::var("DB_CHECK_INTERVAL_SECS") {
+    config.db_check_interval_secs =
+      secs.trim().parse().expect("Invalid DB_CHECK_INTERVAL_SECS format");
+  }
+  if let Ok(mb) = env::var("WAL_CHECKPOINT_MB") {
+    let mb: u64 = mb.trim().parse().expect("Invalid WAL_CHECKPOINT_MB format");
+    config.wal_checkpoint_size = mb * 1024 * 1024;
+  }
+  if let Ok(mb) = env::var("DISK_ALERT_MB") {
+    let mb: u64 = mb.trim().parse().expect("Invalid DISK_ALERT_MB format");
+    config.disk_alert_space = mb * 1024 * 1024;
+  }
+  if let Ok(keys) = env::var("ADMIN_API_KEYS") {
+    config.admin_api_keys = keys
+      .split(',')
+      .map(str::trim)
+      .filter(|key| !key.is_empty())
+      .map(str::to_string)
+      .collect();
+    info!("Admin API enabled with {} key(s)", config.admin_api_keys.len());
+  }
+  if let Ok(key) = env::var("BACKUP_KEY") {
+    config.backup_key =
+      Some(sv::backup::BackupKey::parse(&key).expect("Invalid BACKUP_KEY"));
+    info!("Backups are encrypted");
+  }
+  if let Ok(dir) = env::var("BACKUP_DIR") {
+    config.backup_dir = dir;
+  }
+  // staging snapshots never rotate production ones out
+  if staging {
+    config.backup_dir = format!("{}/staging", config.backup_dir);
+  }
+  if let Ok(days) = env::var("BACKUP_KEEP_DAILY") {
+    config.backup_retention.daily =
+      days.trim().parse().expect("Invalid BACKUP_KEEP_DAILY format");
+  }
+  if let Ok(weeks) = env::var("BACKUP_KEEP_WEEKLY") {
+    config.backup_retention.weekly =
+      weeks.trim().parse().expect("Invalid BACKUP_KEEP_WEEKLY format");
+  }
+  if let Ok(bucket) = env::var("S3_BUCKET") {
+    let endpoint = env::var("S3_ENDPOINT")
+      .unwrap_or_else(|_| "https://s3.amazonaws.com".into());
+    let s3 = sv::s3::S3::new(
+      &endpoint,
+      env::var("S3_REGION").unwrap_or_else(|_| "us-east-1".into()),
+      bucket,
+      env::var("S3_ACCESS_KEY").expect("S3_ACCESS_KEY not set"),
+      env::var("S3_SECRET_KEY").expect("S3_SECRET_KEY not set"),
+      format!(
+        "{}{}",
+        if staging { "staging/" } else { "" },
+        env::var("S3_PREFIX").unwrap_or_else(|_| "backups/".into())
+      ),
+    )
+    .expect("Invalid S3_ENDPOINT");
+    info!("Backups go to S3 bucket {} at {}", s3.bucket(), endpoint);
+    config.backup_s3 = Some(s3);
+  }
+  if let Ok(policy) = env::var("DELETION_REFUND") {
+    config.deletion_refund =
+      policy.parse().expect("Invalid DELETION_REFUND format");
+  }
 
   // Initialize CryptoBot client if API token is configured
   let cryptobot = env::var("CRYPTOBOT_API_TOKEN").ok().map(|token| {
-    let use_testnet = env::var("CRYPTOBOT_TESTNET")
-      .map(|v| v == "true" || v == "1")
-      .unwrap_or(false);
-    info!("CryptoBot API enabled (testnet: {})", use_testnet);
+    let use_testnet = flag("CRYPTOBOT_TESTNET");
+    info!(
+      "CryptoBot API enabled (testnet: {}), webhook: /api/cryptobot/webhook",
+      use_testnet
+    );
     sv::cryptobot::CryptoBot::new(token, use_testnet)
   });
 
+  let mut report_sinks = Vec::new();
+  if let Ok(sheet_id) = env::var("GOOGLE_SHEET_ID") {
+    let key = env::var("GOOGLE_SERVICE_ACCOUNT")
+      .expect("GOOGLE_SERVICE_ACCOUNT not set");
+    let range =
+      env::var("GOOGLE_SHEET_RANGE").unwrap_or_else(|_| "Sheet1".into());
+    let sheets = sv::sheets::Sheets::from_key_file(key, sheet_id, range)
+      .expect("Invalid GOOGLE_SERVICE_ACCOUNT key");
+    info!("Daily reports go to a Google Sheet");
+    report_sinks.push(sv::report::Sink::Sheet(sheets));
+  }
+  if let Ok(url) = env::var("REPORT_CSV_URL") {
+    info!("Daily reports are posted as CSV to {}", url);
+    report_sinks.push(sv::report::Sink::csv(url));
+  }
+
+  // startup probe: everything above parsed, nothing bound or opened yet
+  if env::args().any(|arg| arg == "--check") {
+    println!("Configuration OK");
+    return;
+  }
+
   let app_state = Arc::new(
     AppState::with_config(&db_url, &token, admins, secret, config, cryptobot)
       .await,
@@ -136,7 +634,24 @@ async fn main() {
     .register(cron::Sync)
     .register(cron::Backup)
     .register(cron::StatsClean)
+    .register(cron::StatsSnapshot)
     .register(cron::YankedBuildsGC)
+    .register(cron::DbHealth)
+    .register(cron::PaymentsHealth)
+    .register(cron::Reconciliation)
+    .register(cron::SharingDigest)
+    .register(cron::ErrorDigest)
+    .register(cron::TicketSla)
+    .register(cron::AccountDeletion)
+    .register(cron::AutoRenew)
+    .register(cron::ExpiryNotifier)
+    .register(cron::TrialNudges)
+    .register(cron::RiskScoring)
+    .register(cron::CommissionBoosts)
+    .register(cron::Outbox)
+    .register(cron::HeldAlerts)
+    .register(cron::WeeklyReport)
+    .register(cron::DailyReport { sinks: report_sinks })
     //
     .register(steam::FreeGames)
     .register(steam::FreeRewards)
@@ -1,10 +1,24 @@
 use std::{path::Path, sync::Arc};
 
 use async_trait::async_trait;
-use teloxide::{prelude::*, types::ParseMode};
+use chrono::Timelike;
+use teloxide::{
+  prelude::*,
+  types::{InlineKeyboardButton, InlineKeyboardMarkup, ParseMode},
+  utils::html,
+};
 use tracing::{debug, error, info, warn};
 
-use crate::{plugins::Plugin, prelude::*, state::AppState, sv};
+use crate::{
+  entity::license,
+  plugins::{
+    Plugin,
+    telegram::{Callback, reconcile, sharing},
+  },
+  prelude::*,
+  state::{AppState, Services},
+  sv::{self, admin_alert::Urgency},
+};
 
 pub struct GC;
 
@@ -14,13 +28,100 @@ impl Plugin for GC {
     let mut interval = tokio::time::interval(Duration::from_secs(60));
     loop {
       interval.tick().await;
-      app.gc_sessions();
+      app.writable().await;
+      let emptied = app.gc_sessions();
+      if let Err(e) = app.persist_sessions().await {
+        error!("Failed to persist sessions: {}", e);
+      }
+      if let Err(e) = track_downtime(&app, &emptied).await {
+        error!("Downtime tracking failed: {}", e);
+      }
       app.gc_banned_sessions();
-      app.gc_download_tokens();
+      app.gc_rate_windows();
+      if let Err(e) = app.flush_api_usage().await {
+        error!("Failed to flush API usage: {}", e);
+      }
+      if let Err(e) = app.sv().download_tokens.cleanup().await {
+        error!("Failed to clean up download tokens: {}", e);
+      }
     }
   }
 }
 
+/// Remember when watchers went offline and ping them once the silence
+/// outlasts their threshold. Logouts don't count, only expired sessions.
+async fn track_downtime(app: &AppState, emptied: &[String]) -> Result<()> {
+  let sv = app.sv();
+  let now = Utc::now().naive_utc();
+
+  if emptied.is_empty() && app.offline_since.is_empty() {
+    return Ok(());
+  }
+  let watchers: HashMap<_, _> = sv
+    .settings
+    .downtime_watchers()
+    .await?
+    .into_iter()
+    .map(|settings| (settings.tg_user_id, settings))
+    .collect();
+
+  for key in emptied {
+    let Some(license) = sv.license.by_key(key).await? else {
+      continue;
+    };
+    let user_id = license.tg_user_id;
+    if !watchers.contains_key(&user_id) {
+      continue;
+    }
+    if !app.has_sessions(&sv.license.by_user(user_id, false).await?) {
+      app.offline_since.entry(user_id).or_insert(now);
+    }
+  }
+
+  let offline: Vec<_> =
+    app.offline_since.iter().map(|kv| (*kv.key(), *kv.value())).collect();
+  for (user_id, since) in offline {
+    let Some(settings) = watchers.get(&user_id) else {
+      app.offline_since.remove(&user_id);
+      continue;
+    };
+    let Some(mins) = settings.downtime_alert_mins else {
+      app.offline_since.remove(&user_id);
+      continue;
+    };
+
+    // back online, or stopped outside of the farming window on purpose
+    if app.has_sessions(&sv.license.by_user(user_id, false).await?)
+      || !sv::settings::in_farming_hours(settings, now.hour() as i32)
+    {
+      app.offline_since.remove(&user_id);
+      continue;
+    }
+
+    if (now - since).num_minutes() < mins as i64 {
+      continue;
+    }
+
+    app.offline_since.remove(&user_id);
+    let text = format!(
+      "⚠️ <b>Farming stopped</b>\n\n\
+      None of your panels has reported for {} (since {} UTC).\n\
+      Check your machine or network.\n\n\
+      <i>Change or disable alerts with /downtime</i>",
+      utils::format_duration(now - since),
+      since.format("%H:%M")
+    );
+    if let Some((bot, text)) = app.reach(user_id, text).await {
+      let _ = bot
+        .send_message(ChatId(user_id), text)
+        .parse_mode(ParseMode::Html)
+        .await;
+    }
+  }
+
+  Ok(())
+}
+
 pub struct Backup;
 
 #[async_trait]
@@ -46,6 +147,7 @@ impl Plugin for Backup {
 
     loop {
       interval.tick().await;
+      app.writable().await;
 
       info!("Starting scheduled backup...");
       if let Err(err) = app.perform_smart_backup().await {
@@ -89,6 +191,7 @@ impl Plugin for StatsClean {
         sleep_duration.as_secs() / 3600
       );
       tokio::time::sleep(sleep_duration).await;
+      app.writable().await;
 
       match sv::Stats::reset_weekly_xp(&app.db).await {
         Ok(_) => info!("Weekly XP stats reset successfully"),
@@ -98,6 +201,33 @@ impl Plugin for StatsClean {
   }
 }
 
+/// Copy of every user's stats taken at the end of each day, before the
+/// weekly reset clears Sunday's XP
+pub struct StatsSnapshot;
+
+#[async_trait]
+impl Plugin for StatsSnapshot {
+  async fn start(&self, app: Arc<AppState>) -> anyhow::Result<()> {
+    loop {
+      let now = Utc::now().naive_utc();
+      let mut next = now.date().and_hms_opt(23, 55, 0).expect("Invalid time");
+      if next <= now {
+        next += TimeDelta::days(1);
+      }
+      let sleep_duration =
+        (next - now).to_std().unwrap_or(Duration::from_secs(3600));
+      time::sleep(sleep_duration).await;
+      app.writable().await;
+
+      let day = Utc::now().date_naive();
+      match app.sv().stats.snapshot(day).await {
+        Ok(users) => info!("Stats of {} users snapshotted for {}", users, day),
+        Err(e) => error!("Failed to snapshot stats: {}", e),
+      }
+    }
+  }
+}
+
 pub struct Sync;
 
 #[async_trait]
@@ -148,6 +278,7 @@ impl Plugin for YankedBuildsGC {
 
     loop {
       interval.tick().await;
+      app.writable().await;
 
       if let Err(e) = run_yanked_builds_gc(&app).await {
         error!("YankedBuildsGC failed: {}", e);
@@ -156,7 +287,162 @@ impl Plugin for YankedBuildsGC {
   }
 }
 
-/// Get available disk space for the builds directory
+/// Watches the database, its WAL and the free disk space, checkpoints a WAL
+/// that grew too large and alerts admins before the disk fills up
+pub struct DbHealth;
+
+#[async_trait]
+impl Plugin for DbHealth {
+  async fn start(&self, app: Arc<AppState>) -> anyhow::Result<()> {
+    let interval_secs = app.config.db_check_interval_secs;
+    if interval_secs == 0 {
+      info!("DbHealth disabled via config (0 interval)");
+      return Ok(());
+    }
+
+    let mut interval = time::interval(Duration::from_secs(interval_secs));
+    // alert once per low-space episode, not on every check
+    let mut low_space = false;
+    loop {
+      interval.tick().await;
+      app.writable().await;
+      match check_db_health(&app, low_space).await {
+        Ok(low) => low_space = low,
+        Err(e) => error!("DbHealth failed: {}", e),
+      }
+    }
+  }
+}
+
+/// Probes CryptoBot while its circuit is open, so it closes without users
+/// hitting it, and tells admins once per outage and once on recovery
+pub struct PaymentsHealth;
+
+#[async_trait]
+impl Plugin for PaymentsHealth {
+  async fn start(&self, app: Arc<AppState>) -> anyhow::Result<()> {
+    let Some(cryptobot) = &app.cryptobot else {
+      return Ok(());
+    };
+
+    let mut interval = time::interval(Duration::from_secs(30));
+    let mut alerted: Option<sv::cryptobot::Outage> = None;
+    loop {
+      interval.tick().await;
+      app.writable().await;
+      if cryptobot.outage().is_some() && cryptobot.is_available() {
+        // half-open, the result feeds the breaker
+        let _ = cryptobot.get_me().await;
+      }
+
+      match (cryptobot.outage(), &alerted) {
+        (Some(outage), None) => {
+          warn!("CryptoBot circuit open: {}", outage.last_error);
+          app
+            .notify_admins(
+              Urgency::Critical,
+              &format!(
+                "💳 <b>CryptoBot is down</b>\n\n\
+                {} failed calls since {}, last: <code>{}</code>\n\n\
+                Add Funds switched to manual top-ups and payment checks \
+                are paused until it recovers.",
+                outage.failures,
+                utils::format_date(outage.since),
+                html::escape(&outage.last_error)
+              ),
+              None,
+            )
+            .await;
+          alerted = Some(outage);
+        }
+        (None, Some(outage)) => {
+          let down = Utc::now().naive_utc() - outage.since;
+          info!("CryptoBot recovered after {}", utils::format_duration(down));
+          app
+            .notify_admins(
+              Urgency::Critical,
+              &format!(
+                "✅ <b>CryptoBot is back</b> after {}, payments are \
+                automatic again",
+                utils::format_duration(down)
+              ),
+              None,
+            )
+            .await;
+          alerted = None;
+        }
+        _ => {}
+      }
+    }
+  }
+}
+
+async fn file_size(path: &Path) -> u64 {
+  tokio::fs::metadata(path).await.map(|meta| meta.len()).unwrap_or(0)
+}
+
+/// Whether free space is below the alert threshold after this check
+async fn check_db_health(
+  app: &AppState,
+  low_space: bool,
+) -> anyhow::Result<bool> {
+  let Some(path) = app.db_path().await? else {
+    return Ok(low_space);
+  };
+  let mb = |bytes: u64| bytes / (1024 * 1024);
+
+  let db_size = file_size(&path).await;
+  let mut wal = path.clone().into_os_string();
+  wal.push("-wal");
+  let wal = Path::new(&wal);
+  let mut wal_size = file_size(wal).await;
+  if wal_size > app.config.wal_checkpoint_size {
+    info!("WAL is {}MB, checkpointing", mb(wal_size));
+    if !app.checkpoint_wal().await? {
+      warn!("WAL checkpoint blocked by readers, {}MB left", mb(wal_size));
+    }
+    wal_size = file_size(wal).await;
+  }
+
+  let dir = path.parent().and_then(|dir| dir.to_str()).unwrap_or(".");
+  let Some(free_space) = get_available_space(dir) else {
+    debug!("Could not determine free disk space");
+    return Ok(low_space);
+  };
+  debug!(
+    "Database {}MB, WAL {}MB, {}MB free",
+    mb(db_size),
+    mb(wal_size),
+    mb(free_space)
+  );
+
+  let low = free_space < app.config.disk_alert_space;
+  if low && !low_space {
+    warn!("Low disk space: {}MB free", mb(free_space));
+    app
+      .notify_admins(
+        Urgency::Critical,
+        &format!(
+          "💾 <b>Low disk space</b>\n\n\
+          Free: {}MB (alert below {}MB)\n\
+          Database: {}MB\n\
+          WAL: {}MB\n\n\
+          Free up space before writes start failing.",
+          mb(free_space),
+          mb(app.config.disk_alert_space),
+          mb(db_size),
+          mb(wal_size)
+        ),
+        None,
+      )
+      .await;
+  } else if !low && low_space {
+    info!("Disk space recovered: {}MB free", mb(free_space));
+  }
+  Ok(low)
+}
+
+/// Get available disk space for a directory, the current one if missing
 fn get_available_space(builds_dir: &str) -> Option<u64> {
   let path = Path::new(builds_dir);
 
@@ -341,3 +627,874 @@ async fn notify_admins_no_yanked_builds(
     app.admins.len()
   );
 }
+
+/// Escalating reminders about tickets nobody answered within the SLA
+pub struct TicketSla;
+
+#[async_trait]
+impl Plugin for TicketSla {
+  async fn start(&self, app: Arc<AppState>) -> anyhow::Result<()> {
+    let sla_hours = app.config.ticket_sla_hours;
+    if sla_hours <= 0 {
+      info!("Ticket SLA reminders disabled via config (0 hours)");
+      return Ok(());
+    }
+
+    info!("Ticket SLA monitor started (SLA: {}h)", sla_hours);
+
+    let mut interval = time::interval(Duration::from_secs(10 * 60));
+    loop {
+      interval.tick().await;
+      app.writable().await;
+      if let Err(e) = remind_overdue_tickets(&app, sla_hours).await {
+        error!("Ticket SLA check failed: {}", e);
+      }
+    }
+  }
+}
+
+async fn remind_overdue_tickets(
+  app: &AppState,
+  sla_hours: i64,
+) -> anyhow::Result<()> {
+  let sv = app.sv();
+  let now = Utc::now().naive_utc();
+
+  for ticket in sv.ticket.overdue(TimeDelta::hours(sla_hours)).await? {
+    let waiting = ticket.waiting_since.map(|since| now - since);
+    let header = match ticket.reminders_sent {
+      0 => "⏰ <b>Ticket SLA breached</b>",
+      1 => "🔥 <b>Ticket still unanswered</b>",
+      _ => "🚨 <b>Ticket is rotting</b>",
+    };
+
+    let message = format!(
+      "{}\n\n\
+      Ticket <b>#{}</b> ({:?} priority) from <code>{}</code> \
+      has been waiting for {}.\n\n\
+      View: <code>/tickets {}</code>\n\
+      Reply: <code>/reply {} your answer</code>",
+      header,
+      ticket.id,
+      ticket.priority,
+      ticket.tg_user_id,
+      waiting.map(utils::format_duration).unwrap_or_default(),
+      ticket.id,
+      ticket.id
+    );
+    app.notify_admins(Urgency::Normal, &message, None).await;
+
+    warn!("Ticket #{} breached SLA, admins reminded", ticket.id);
+    sv.ticket.mark_reminded(ticket).await?;
+  }
+
+  Ok(())
+}
+
+/// Wipes accounts whose /delete_account cooling-off period ran out
+pub struct AccountDeletion;
+
+#[async_trait]
+impl Plugin for AccountDeletion {
+  async fn start(&self, app: Arc<AppState>) -> anyhow::Result<()> {
+    let mut interval = time::interval(Duration::from_secs(60 * 60));
+    loop {
+      interval.tick().await;
+      app.writable().await;
+      if let Err(e) = delete_due_accounts(&app).await {
+        error!("Account deletion failed: {}", e);
+      }
+    }
+  }
+}
+
+async fn delete_due_accounts(app: &AppState) -> anyhow::Result<()> {
+  let sv = app.sv();
+  let cooloff = TimeDelta::days(app.config.deletion_cooloff_days);
+
+  for user in sv.account.due(cooloff).await? {
+    let deleted = sv.account.anonymize(user.tg_user_id).await?;
+    for key in &deleted.revoked {
+      app.drop_sessions(key);
+    }
+    info!(
+      "Account {} deleted, {} license(s) revoked",
+      deleted.tg_user_id,
+      deleted.revoked.len()
+    );
+
+    let refund = match app.config.deletion_refund {
+      _ if deleted.balance <= 0 => String::new(),
+      sv::account::RefundPolicy::Manual => {
+        "\nSupport will contact you about refunding your balance.".into()
+      }
+      sv::account::RefundPolicy::Forfeit => {
+        "\nThe remaining balance was forfeited.".into()
+      }
+    };
+    let text = format!(
+      "🗑 <b>Your account was deleted</b>\n\n\
+      {} license(s) were revoked and your data was erased.{}",
+      deleted.revoked.len(),
+      refund
+    );
+    if let Some((bot, text)) = app.reach(deleted.tg_user_id, text).await {
+      let _ = bot
+        .send_message(ChatId(deleted.tg_user_id), text)
+        .parse_mode(ParseMode::Html)
+        .await;
+    }
+
+    if deleted.balance > 0
+      && app.config.deletion_refund == sv::account::RefundPolicy::Manual
+    {
+      let message = format!(
+        "🗑 <b>Account deleted</b>\n\n\
+        User <code>{}</code> left {:.2} USDT on the balance, \
+        refund it manually.",
+        deleted.tg_user_id,
+        deleted.balance as f64 / sv::referral::NANO_USDT as f64
+      );
+      app.notify_admins(Urgency::Normal, &message, None).await;
+    }
+  }
+
+  Ok(())
+}
+
+/// Hours of payments each nightly reconciliation covers, overlapping so
+/// webhooks landing around midnight are never missed
+const RECONCILE_HOURS: i64 = 48;
+
+/// Nightly cross-check of CryptoBot's paid invoices against credited
+/// deposits, admins only hear about discrepancies
+pub struct Reconciliation;
+
+#[async_trait]
+impl Plugin for Reconciliation {
+  async fn start(&self, app: Arc<AppState>) -> anyhow::Result<()> {
+    let Some(cryptobot) = &app.cryptobot else {
+      return Ok(());
+    };
+    if app.admins.is_empty() {
+      return Ok(());
+    }
+
+    loop {
+      let now = Utc::now().naive_utc();
+      let next = (now.date() + chrono::Days::new(1))
+        .and_hms_opt(2, 30, 0)
+        .expect("Invalid time");
+      let sleep_duration =
+        (next - now).to_std().unwrap_or(Duration::from_secs(3600));
+      time::sleep(sleep_duration).await;
+      app.writable().await;
+
+      if !cryptobot.is_available() {
+        warn!("CryptoBot is down, reconciliation skipped");
+        continue;
+      }
+      let since = Utc::now().naive_utc() - TimeDelta::hours(RECONCILE_HOURS);
+      match app.sv().reconcile.check(cryptobot, since).await {
+        Ok(report) if report.discrepancies.is_empty() => info!(
+          "Reconciliation: {} paid invoices, {} deposits, all match",
+          report.paid, report.credited
+        ),
+        Ok(report) => {
+          warn!(
+            "Reconciliation found {} discrepancies",
+            report.discrepancies.len()
+          );
+          let (text, keyboard) = reconcile::message(&report, RECONCILE_HOURS);
+          app.notify_admins(Urgency::Critical, &text, keyboard).await;
+        }
+        Err(e) => error!("Reconciliation failed: {}", e),
+      }
+    }
+  }
+}
+
+/// Findings of the sharing scan are kept this long
+const SHARING_KEEP_DAYS: u64 = 90;
+
+/// Nightly scan for licenses used from too many machines or addresses,
+/// sent to admins as a digest with ban buttons
+pub struct SharingDigest;
+
+#[async_trait]
+impl Plugin for SharingDigest {
+  async fn start(&self, app: Arc<AppState>) -> anyhow::Result<()> {
+    let policy = app.config.sharing_policy();
+    if !policy.is_enabled() || app.admins.is_empty() {
+      return Ok(());
+    }
+
+    loop {
+      let now = Utc::now().naive_utc();
+      let next = (now.date() + chrono::Days::new(1))
+        .and_hms_opt(3, 0, 0)
+        .expect("Invalid time");
+      let sleep_duration =
+        (next - now).to_std().unwrap_or(Duration::from_secs(3600));
+      time::sleep(sleep_duration).await;
+      app.writable().await;
+
+      let now = Utc::now().naive_utc();
+      let sv = app.sv();
+      match sv.sharing.scan(&policy, now).await {
+        Ok(alerts) if alerts.is_empty() => {
+          info!("Sharing scan: no licenses over the limits")
+        }
+        Ok(alerts) => {
+          warn!("Sharing scan flagged {} licenses", alerts.len());
+          let (text, keyboard) =
+            sharing::message(&alerts, policy.window.num_hours());
+          app.notify_admins(Urgency::Normal, &text, Some(keyboard)).await;
+        }
+        Err(e) => error!("Sharing scan failed: {}", e),
+      }
+      let before = now.date() - chrono::Days::new(SHARING_KEEP_DAYS);
+      if let Err(e) = sv.sharing.prune(before).await {
+        error!("Failed to prune sharing alerts: {}", e);
+      }
+    }
+  }
+}
+
+/// Days of client error counts kept
+const ERRORS_KEEP_DAYS: u64 = 30;
+/// Errors listed in the digest, the rest only counted
+const ERRORS_SHOWN: usize = 10;
+/// Builds listed under one error
+const ERROR_VERSIONS_SHOWN: usize = 5;
+/// Messages are cut to this in the digest
+const ERROR_SNIPPET: usize = 160;
+
+/// Morning summary of the errors clients reported the day before
+pub struct ErrorDigest;
+
+#[async_trait]
+impl Plugin for ErrorDigest {
+  async fn start(&self, app: Arc<AppState>) -> anyhow::Result<()> {
+    if app.admins.is_empty() {
+      return Ok(());
+    }
+
+    loop {
+      let now = Utc::now().naive_utc();
+      let next = (now.date() + chrono::Days::new(1))
+        .and_hms_opt(8, 0, 0)
+        .expect("Invalid time");
+      let sleep_duration =
+        (next - now).to_std().unwrap_or(Duration::from_secs(3600));
+      time::sleep(sleep_duration).await;
+      app.writable().await;
+
+      let day = next.date() - chrono::Days::new(1);
+      let sv = app.sv();
+      match sv.error_reports.digest(day).await {
+        Ok(errors) if errors.is_empty() => {
+          info!("No client errors reported on {}", day)
+        }
+        Ok(errors) => {
+          let text = error_digest(day, &errors);
+          app.notify_admins(Urgency::Normal, &text, None).await;
+        }
+        Err(e) => error!("Failed to build error digest: {}", e),
+      }
+      let before = day - chrono::Days::new(ERRORS_KEEP_DAYS);
+      if let Err(e) = sv.error_reports.prune(before).await {
+        error!("Failed to prune error reports: {}", e);
+      }
+    }
+  }
+}
+
+fn error_digest(
+  day: chrono::NaiveDate,
+  errors: &[sv::error_report::ErrorSummary],
+) -> String {
+  let total: i64 = errors.iter().map(|error| error.total).sum();
+  let mut text = format!(
+    "🐞 <b>Client errors</b> on {}\n\n\
+    <b>{} distinct</b>, {} reports\n",
+    day,
+    errors.len(),
+    total
+  );
+  for (i, error) in errors.iter().take(ERRORS_SHOWN).enumerate() {
+    let mut snippet: String =
+      error.message.chars().take(ERROR_SNIPPET).collect();
+    if snippet.len() < error.message.len() {
+      snippet.push('…');
+    }
+    let mut versions: Vec<String> = error
+      .versions
+      .iter()
+      .take(ERROR_VERSIONS_SHOWN)
+      .map(|(version, count)| format!("{} ×{}", html::escape(version), count))
+      .collect();
+    if error.versions.len() > ERROR_VERSIONS_SHOWN {
+      versions
+        .push(format!("+{} more", error.versions.len() - ERROR_VERSIONS_SHOWN));
+    }
+    text.push_str(&format!(
+      "\n{}. <code>{}</code>\n×{}: {}\nlast from <code>{}</code>\n",
+      i + 1,
+      html::escape(&snippet),
+      error.total,
+      versions.join(", "),
+      error.last_key
+    ));
+  }
+  if errors.len() > ERRORS_SHOWN {
+    text
+      .push_str(&format!("\n<i>…and {} more</i>", errors.len() - ERRORS_SHOWN));
+  }
+  text
+}
+
+/// Monday morning summary of the past week for admins
+pub struct WeeklyReport;
+
+#[async_trait]
+impl Plugin for WeeklyReport {
+  async fn start(&self, app: Arc<AppState>) -> anyhow::Result<()> {
+    if app.admins.is_empty() {
+      return Ok(());
+    }
+
+    loop {
+      let now = Utc::now().naive_utc();
+      let days_ahead = 7 - now.weekday().num_days_from_monday() as u64;
+      let next = (now.date() + chrono::Days::new(days_ahead))
+        .and_hms_opt(9, 0, 0)
+        .expect("Invalid time");
+      // it's Monday before 9:00, the report is due today
+      let next = if next - TimeDelta::days(7) > now {
+        next - TimeDelta::days(7)
+      } else {
+        next
+      };
+
+      let sleep_duration =
+        (next - now).to_std().unwrap_or(Duration::from_secs(3600));
+      info!(
+        "Weekly report scheduled in {} hours",
+        sleep_duration.as_secs() / 3600
+      );
+      time::sleep(sleep_duration).await;
+      app.writable().await;
+
+      match build_weekly_report(&app).await {
+        Ok(report) => app.notify_admins(Urgency::Normal, &report, None).await,
+        Err(e) => error!("Failed to build weekly report: {}", e),
+      }
+    }
+  }
+}
+
+async fn build_weekly_report(app: &AppState) -> anyhow::Result<String> {
+  use crate::entity::rating::RatingKind;
+
+  let sv = app.sv();
+  let since = Utc::now().naive_utc() - TimeDelta::days(7);
+
+  let new_users = sv.user.count_registered_since(since).await?;
+  let (purchases, revenue) = sv.balance.purchases_since(since).await?;
+  let tickets = sv.ticket.count_opened_since(since).await?;
+  let support = sv.rating.summary(RatingKind::Support, since).await?;
+  let purchase = sv.rating.summary(RatingKind::Purchase, since).await?;
+
+  let csat = |summary: &sv::rating::RatingSummary| match summary.average {
+    Some(avg) => {
+      format!("{:.2}/5 ({} of {} answered)", avg, summary.rated, summary.asked)
+    }
+    None => format!("no ratings ({} asked)", summary.asked),
+  };
+
+  Ok(format!(
+    "📊 <b>Weekly Report</b>\n\n\
+    <b>New users:</b> {}\n\
+    <b>Purchases:</b> {} ({:.2} USDT)\n\
+    <b>Tickets opened:</b> {}\n\n\
+    <b>CSAT support:</b> {}\n\
+    <b>CSAT purchases:</b> {}",
+    new_users,
+    purchases,
+    revenue as f64 / sv::referral::NANO_USDT as f64,
+    tickets,
+    csat(&support),
+    csat(&purchase)
+  ))
+}
+
+/// Daily numbers for people without bot access, sent shortly after midnight
+/// UTC for the day that just ended
+pub struct DailyReport {
+  pub sinks: Vec<sv::report::Sink>,
+}
+
+#[async_trait]
+impl Plugin for DailyReport {
+  async fn start(&self, app: Arc<AppState>) -> anyhow::Result<()> {
+    if self.sinks.is_empty() {
+      return Ok(());
+    }
+
+    loop {
+      let now = Utc::now().naive_utc();
+      let next = (now.date() + chrono::Days::new(1))
+        .and_hms_opt(0, 10, 0)
+        .expect("Invalid time");
+      let sleep_duration =
+        (next - now).to_std().unwrap_or(Duration::from_secs(3600));
+      time::sleep(sleep_duration).await;
+      app.writable().await;
+
+      let day = next.date() - chrono::Days::new(1);
+      let metrics = match app.sv().report.daily(day).await {
+        Ok(metrics) => metrics,
+        Err(e) => {
+          error!("Failed to collect daily metrics: {}", e);
+          continue;
+        }
+      };
+      for sink in &self.sinks {
+        match sink.send(&metrics).await {
+          Ok(()) => info!("Daily report for {} sent to {}", day, sink.name()),
+          Err(e) => {
+            error!("Daily report to {} failed: {}", sink.name(), e);
+            app
+              .notify_admins(
+                Urgency::Normal,
+                &format!(
+                  "⚠️ Daily report for {} was not delivered to the {}: {}",
+                  day,
+                  sink.name(),
+                  e
+                ),
+                None,
+              )
+              .await;
+          }
+        }
+      }
+    }
+  }
+}
+
+/// How long before expiry opted-in licenses are renewed
+const RENEW_AHEAD_HOURS: i64 = 24;
+
+/// Extends licenses with auto-renewal on from their owner's balance
+pub struct AutoRenew;
+
+#[async_trait]
+impl Plugin for AutoRenew {
+  async fn start(&self, app: Arc<AppState>) -> anyhow::Result<()> {
+    let mut interval = time::interval(Duration::from_secs(15 * 60));
+    loop {
+      interval.tick().await;
+      app.writable().await;
+      if let Err(e) = renew_due_licenses(&app).await {
+        error!("Auto-renewal failed: {}", e);
+      }
+    }
+  }
+}
+
+async fn renew_due_licenses(app: &AppState) -> anyhow::Result<()> {
+  let sv = app.sv();
+  let due = sv.license.renewal_due(TimeDelta::hours(RENEW_AHEAD_HOURS)).await?;
+
+  for license in due {
+    let rules = &app.config.purchase_rules;
+    let text = match renew(&app.db, &sv, &license, rules).await {
+      Ok((plan, expires_at, balance)) => {
+        info!("License {} auto-renewed with {}", license.key, plan);
+        format!(
+          "🔁 <b>License renewed</b>\n\n\
+          <code>{}</code> was extended by {} from your balance.\n\n\
+          <b>New Expiry:</b> {}\n\
+          <b>Balance:</b> {:.2} USDT",
+          license.key,
+          plan,
+          utils::format_date(expires_at),
+          balance as f64 / sv::referral::NANO_USDT as f64
+        )
+      }
+      Err(e) => {
+        warn!("Auto-renewal of {} failed: {}", license.key, e);
+        // retrying every tick would spam the owner, they turn it back on
+        sv.license.set_auto_renew(&license.key, false).await?;
+        format!(
+          "⚠️ <b>Auto-renewal failed</b>\n\n\
+          <code>{}</code> could not be renewed: {}\n\n\
+          Auto-renewal was switched off. Top up your balance and extend \
+          the key before {} to keep it working.",
+          license.key,
+          e.user_message(),
+          utils::format_date(license.expires_at)
+        )
+      }
+    };
+    if let Some((bot, text)) = app.reach(license.tg_user_id, text).await {
+      let _ = bot
+        .send_message(ChatId(license.tg_user_id), text)
+        .parse_mode(ParseMode::Html)
+        .await;
+    }
+  }
+
+  Ok(())
+}
+
+/// Charge the shortest extension plan and extend, returns the plan title,
+/// the new expiry and the balance left. The charge, the extension and the
+/// referrer's commission are one transaction, none happens without the
+/// others.
+async fn renew(
+  db: &DatabaseConnection,
+  sv: &Services<'_>,
+  license: &license::Model,
+  rules: &sv::eligibility::Rules,
+) -> Result<(String, DateTime, i64)> {
+  let user_id = license.tg_user_id;
+  let purchase = sv::eligibility::Purchase::Extend(license);
+  sv.eligibility.check(user_id, purchase, rules).await?;
+  let plan = sv::pricing::extension_plans(license)[0];
+  let quote =
+    sv.pricing.quote_extension(user_id, &license.product, plan).await?;
+  let referred_by = sv.user.by_id(user_id).await?.and_then(|u| u.referred_by);
+
+  let txn = db.begin().await?;
+  let balance = sv::Balance::charge_in(
+    &txn,
+    user_id,
+    quote.price,
+    Some(format!("Auto-renewal: {} for {}", quote.title, &license.key[..8])),
+    referred_by,
+    quote.region_code(),
+  )
+  .await?;
+  let expires_at = sv::License::extend_term_in(
+    &txn,
+    &license.key,
+    quote.term,
+    quote.region_code(),
+  )
+  .await?;
+  if quote.offer_percent > 0 {
+    sv::UpgradeOffers::redeem_in(&txn, user_id).await?;
+  }
+  if let Some(referrer_id) = referred_by {
+    sv::Referral::pay_commission_in(
+      &txn,
+      referrer_id,
+      Some(user_id),
+      quote.price,
+    )
+    .await?;
+  }
+  txn.commit().await?;
+  Ok((quote.title, expires_at, balance))
+}
+
+/// Offers a starter discount to trial users who farm a lot in their first
+/// days, instead of reminding everyone
+pub struct TrialNudges;
+
+#[async_trait]
+impl Plugin for TrialNudges {
+  async fn start(&self, app: Arc<AppState>) -> anyhow::Result<()> {
+    if !app.config.trial_nudge.enabled() {
+      info!("Trial upgrade offers disabled via config");
+      return Ok(());
+    }
+    let mut interval = time::interval(Duration::from_hours(1));
+    loop {
+      interval.tick().await;
+      app.writable().await;
+      if let Err(e) = send_trial_nudges(&app).await {
+        error!("Trial upgrade offers failed: {}", e);
+      }
+    }
+  }
+}
+
+async fn send_trial_nudges(app: &AppState) -> anyhow::Result<()> {
+  let sv = app.sv();
+  let nudge = &app.config.trial_nudge;
+
+  for stats in sv.upgrade_offers.candidates(nudge).await? {
+    let user_id = stats.tg_user_id;
+    // recorded first, so a user is never offered twice
+    let offer = sv.upgrade_offers.create(user_id, nudge).await?;
+    let text = format!(
+      "🚀 <b>You are getting a lot out of your trial</b>\n\n\
+      {:.0} hours of farming and {} XP so far.\n\n\
+      Here is <b>{}% off</b> your first paid plan, valid until {}.",
+      stats.runtime_hours,
+      stats.total_xp,
+      offer.percent,
+      utils::format_date(offer.expires_at)
+    );
+    let kb =
+      InlineKeyboardMarkup::new(vec![vec![InlineKeyboardButton::callback(
+        "💳 Buy License",
+        Callback::Buy.to_data(),
+      )]]);
+
+    let Some((bot, text)) = app.reach(user_id, text).await else {
+      continue;
+    };
+    let sent = bot
+      .send_message(ChatId(user_id), text)
+      .parse_mode(ParseMode::Html)
+      .reply_markup(kb)
+      .await;
+    if let Err(e) = sent {
+      debug!("Upgrade offer for {} not delivered: {}", user_id, e);
+    }
+  }
+
+  Ok(())
+}
+
+/// Refreshes the risk score of every user
+pub struct RiskScoring;
+
+#[async_trait]
+impl Plugin for RiskScoring {
+  async fn start(&self, app: Arc<AppState>) -> anyhow::Result<()> {
+    let mut interval = time::interval(Duration::from_hours(6));
+    loop {
+      interval.tick().await;
+      app.writable().await;
+      if let Err(e) = rescore_users(&app).await {
+        error!("Risk scoring failed: {}", e);
+      }
+    }
+  }
+}
+
+async fn rescore_users(app: &AppState) -> anyhow::Result<()> {
+  let sv = app.sv();
+  let mut high = 0;
+  for user in sv.user.all().await? {
+    if user.deleted_at.is_some() {
+      continue;
+    }
+    let risk = sv.risk.rescore(user.tg_user_id).await?;
+    if sv::risk::Level::of(risk.score) == sv::risk::Level::High {
+      high += 1;
+    }
+  }
+  info!("Risk scores refreshed, {} high-risk user(s)", high);
+  Ok(())
+}
+
+/// DMs owners a week, three days and a day before their key expires
+pub struct ExpiryNotifier;
+
+#[async_trait]
+impl Plugin for ExpiryNotifier {
+  async fn start(&self, app: Arc<AppState>) -> anyhow::Result<()> {
+    let mut interval = time::interval(Duration::from_hours(24));
+    loop {
+      interval.tick().await;
+      app.writable().await;
+      if let Err(e) = notify_expiring_licenses(&app).await {
+        error!("Expiry notifications failed: {}", e);
+      }
+    }
+  }
+}
+
+async fn notify_expiring_licenses(app: &AppState) -> anyhow::Result<()> {
+  let sv = app.sv();
+
+  for (license, days) in sv.license.expiry_reminders_due().await? {
+    let renewal = if license.auto_renew {
+      "Auto-renewal is on, it will be extended from your balance."
+    } else {
+      "Extend it now to keep your panels running."
+    };
+    let text = format!(
+      "⏳ <b>License expires in {} day{}</b>\n\n\
+      <code>{}</code> expires on {}.\n\n{}",
+      days,
+      if days == 1 { "" } else { "s" },
+      license.key,
+      utils::format_date(license.expires_at),
+      renewal
+    );
+    let kb =
+      InlineKeyboardMarkup::new(vec![vec![InlineKeyboardButton::callback(
+        "🔄 Extend",
+        Callback::ExtendLicenseKey(license.key.clone()).to_data(),
+      )]]);
+
+    if let Some((bot, text)) = app.reach(license.tg_user_id, text).await {
+      let sent = bot
+        .send_message(ChatId(license.tg_user_id), text)
+        .parse_mode(ParseMode::Html)
+        .reply_markup(kb)
+        .await;
+      if let Err(e) = sent {
+        debug!("Expiry reminder for {} not delivered: {}", license.key, e);
+      }
+    }
+    // blocked bots are not retried every day either
+    sv.license.mark_expiry_notified(&license.key).await?;
+  }
+
+  Ok(())
+}
+
+/// Delivers alerts held back during admins' quiet hours once they end
+pub struct HeldAlerts;
+
+#[async_trait]
+impl Plugin for HeldAlerts {
+  async fn start(&self, app: Arc<AppState>) -> anyhow::Result<()> {
+    let mut interval = time::interval(Duration::from_secs(5 * 60));
+    loop {
+      interval.tick().await;
+      app.writable().await;
+      if let Err(e) = release_held_alerts(&app).await {
+        error!("Releasing held alerts failed: {}", e);
+      }
+    }
+  }
+}
+
+async fn release_held_alerts(app: &AppState) -> anyhow::Result<()> {
+  let sv = app.sv();
+  let now = Utc::now().naive_utc();
+  for admin in sv.admin_alerts.holders().await? {
+    if sv.admin_alerts.is_quiet(admin, now).await? {
+      continue;
+    }
+    let held = sv.admin_alerts.held(admin).await?;
+    for text in sv::admin_alert::digest(&held) {
+      app
+        .bot
+        .send_message(ChatId(admin), text)
+        .parse_mode(ParseMode::Html)
+        .await?;
+    }
+    info!("Delivered {} held alert(s) to admin {}", held.len(), admin);
+    sv.admin_alerts.release(&held).await?;
+  }
+  Ok(())
+}
+
+/// Sends notifications queued in the outbox by committed transactions.
+/// A crash between sending and marking resends the message once more.
+pub struct Outbox;
+
+/// Messages sent per tick, well below Telegram's broadcast limits
+const OUTBOX_BATCH: u64 = 20;
+
+#[async_trait]
+impl Plugin for Outbox {
+  async fn start(&self, app: Arc<AppState>) -> anyhow::Result<()> {
+    let mut interval = time::interval(Duration::from_secs(5));
+    let mut ticks = 0u64;
+    loop {
+      interval.tick().await;
+      app.writable().await;
+      if let Err(e) = drain_outbox(&app).await {
+        error!("Outbox drain failed: {}", e);
+      }
+      // keep a week of sent messages for debugging
+      ticks += 1;
+      if ticks.is_multiple_of(720)
+        && let Err(e) = app.sv().outbox.cleanup(TimeDelta::days(7)).await
+      {
+        error!("Outbox cleanup failed: {}", e);
+      }
+    }
+  }
+}
+
+async fn drain_outbox(app: &AppState) -> Result<()> {
+  let sv = app.sv();
+  for message in sv.outbox.due(OUTBOX_BATCH).await? {
+    let Some((bot, text)) =
+      app.reach(message.tg_user_id, message.text.clone()).await
+    else {
+      // staging drops it instead of retrying forever
+      sv.outbox.mark_sent(message.id).await?;
+      continue;
+    };
+    let sent = bot
+      .send_message(ChatId(message.tg_user_id), text)
+      .parse_mode(ParseMode::Html)
+      .await;
+    match sent {
+      Ok(_) => sv.outbox.mark_sent(message.id).await?,
+      Err(e) => {
+        warn!(
+          "Outbox message #{} to {} failed: {}",
+          message.id, message.tg_user_id, e
+        );
+        sv.outbox.mark_failed(message, &e.to_string()).await?;
+      }
+    }
+  }
+  Ok(())
+}
+
+/// Tells creators when a commission boost they had is over
+pub struct CommissionBoosts;
+
+#[async_trait]
+impl Plugin for CommissionBoosts {
+  async fn start(&self, app: Arc<AppState>) -> anyhow::Result<()> {
+    let mut interval = time::interval(Duration::from_secs(5 * 60));
+    loop {
+      interval.tick().await;
+      app.writable().await;
+      if let Err(e) = announce_ended_boosts(&app).await {
+        error!("Commission boost broadcast failed: {}", e);
+      }
+    }
+  }
+}
+
+async fn announce_ended_boosts(app: &AppState) -> anyhow::Result<()> {
+  let sv = app.sv();
+
+  for boost in sv.referral.ended_boosts().await? {
+    let creators = match boost.creator_id {
+      Some(id) => sv.user.by_id(id).await?.into_iter().collect(),
+      None => sv.referral.all_creators().await?,
+    };
+    for creator in &creators {
+      let text = format!(
+        "🏁 <b>Commission boost ended</b>\n\n\
+        The {} commission boost is over, you earn your usual {}% again.\n\
+        Thanks for promoting!",
+        boost.multiplier(),
+        creator.commission_rate
+      );
+      if let Some((bot, text)) = app.reach(creator.tg_user_id, text).await {
+        let _ = bot
+          .send_message(ChatId(creator.tg_user_id), text)
+          .parse_mode(ParseMode::Html)
+          .await;
+      }
+    }
+    info!(
+      "Commission boost #{} ended, {} creator(s) told",
+      boost.id,
+      creators.len()
+    );
+    sv.referral.mark_boost_announced(boost.id).await?;
+  }
+
+  Ok(())
+}
@@ -0,0 +1,241 @@
+use std::sync::Arc;
+
+use axum::{
+  Json,
+  extract::{FromRequestParts, Path, Query, State},
+  http::{header, request::Parts},
+  response::IntoResponse,
+};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::{
+  entity::{license, transaction, user},
+  prelude::*,
+  state::AppState,
+};
+
+/// Largest page the list endpoints return
+const MAX_PAGE: u64 = 500;
+
+/// Caller holding one of `ADMIN_API_KEYS`, sent as `Authorization: Bearer`.
+/// Personal API tokens are not accepted, they may be shared with overlays.
+pub struct AdminKey;
+
+impl FromRequestParts<Arc<AppState>> for AdminKey {
+  type Rejection = Error;
+
+  async fn from_request_parts(
+    parts: &mut Parts,
+    app: &Arc<AppState>,
+  ) -> Result<Self> {
+    let key = parts
+      .headers
+      .get(header::AUTHORIZATION)
+      .and_then(|value| value.to_str().ok())
+      .and_then(|value| value.strip_prefix("Bearer "))
+      .ok_or(Error::Unauthorized)?;
+
+    // compare digests so the check takes the same time for every key
+    let digest = Sha256::digest(key.trim().as_bytes());
+    let known = app
+      .config
+      .admin_api_keys
+      .iter()
+      .any(|known| Sha256::digest(known.as_bytes()) == digest);
+    if !known {
+      return Err(Error::Unauthorized);
+    }
+    Ok(AdminKey)
+  }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Page {
+  #[serde(default)]
+  pub offset: u64,
+  #[serde(default = "default_limit")]
+  pub limit: u64,
+  /// Only rows of this user
+  pub user_id: Option<i64>,
+}
+
+fn default_limit() -> u64 {
+  100
+}
+
+impl Page {
+  fn limit(&self) -> u64 {
+    self.limit.min(MAX_PAGE)
+  }
+}
+
+#[derive(Debug, Serialize)]
+pub struct SessionInfo {
+  pub key: String,
+  pub session_id: String,
+  pub hwid: Option<String>,
+  pub license_type: license::LicenseType,
+  pub last_seen: DateTime,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ExtendReq {
+  pub days: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ExtendRes {
+  pub expires_at: DateTime,
+}
+
+pub async fn users(
+  State(app): State<Arc<AppState>>,
+  _: AdminKey,
+  Query(page): Query<Page>,
+) -> Result<Json<Vec<user::Model>>> {
+  Ok(Json(app.sv().user.page(page.offset, page.limit()).await?))
+}
+
+pub async fn licenses(
+  State(app): State<Arc<AppState>>,
+  _: AdminKey,
+  Query(page): Query<Page>,
+) -> Result<Json<Vec<license::Model>>> {
+  let sv = app.sv();
+  Ok(Json(sv.license.page(page.user_id, page.offset, page.limit()).await?))
+}
+
+pub async fn transactions(
+  State(app): State<Arc<AppState>>,
+  _: AdminKey,
+  Query(page): Query<Page>,
+) -> Result<Json<Vec<transaction::Model>>> {
+  let sv = app.sv();
+  Ok(Json(sv.balance.page(page.user_id, page.offset, page.limit()).await?))
+}
+
+/// Live sessions from memory
+pub async fn sessions(
+  State(app): State<Arc<AppState>>,
+  _: AdminKey,
+) -> Json<Vec<SessionInfo>> {
+  let mut sessions: Vec<_> = app
+    .sessions
+    .iter()
+    .flat_map(|entry| {
+      let key = entry.key().clone();
+      entry
+        .value()
+        .iter()
+        .map(|session| SessionInfo {
+          key: key.clone(),
+          session_id: session.session_id.clone(),
+          hwid: session.hwid_hash.clone(),
+          license_type: session.license_type.clone(),
+          last_seen: session.last_seen,
+        })
+        .collect::<Vec<_>>()
+    })
+    .collect();
+  sessions.sort_by_key(|session| std::cmp::Reverse(session.last_seen));
+  Json(sessions)
+}
+
+pub async fn ban(
+  State(app): State<Arc<AppState>>,
+  _: AdminKey,
+  Path(key): Path<String>,
+) -> Result<Json<license::Model>> {
+  app.sv().license.set_blocked(&key, true).await?;
+  app.drop_sessions(&key);
+  info!("Admin API blocked {}", key);
+  license_of(&app, &key).await
+}
+
+pub async fn unban(
+  State(app): State<Arc<AppState>>,
+  _: AdminKey,
+  Path(key): Path<String>,
+) -> Result<Json<license::Model>> {
+  app.sv().license.set_blocked(&key, false).await?;
+  info!("Admin API unblocked {}", key);
+  license_of(&app, &key).await
+}
+
+pub async fn extend(
+  State(app): State<Arc<AppState>>,
+  _: AdminKey,
+  Path(key): Path<String>,
+  Json(req): Json<ExtendReq>,
+) -> Result<Json<ExtendRes>> {
+  if req.days == 0 {
+    return Err(Error::InvalidArgs("Days must be positive".into()));
+  }
+  let expires_at = app.sv().license.extend(&key, req.days).await?;
+  info!("Admin API extended {} by {} day(s)", key, req.days);
+  Ok(Json(ExtendRes { expires_at }))
+}
+
+/// Prometheus text exposition of bot usage and live sessions
+pub async fn prometheus(
+  State(app): State<Arc<AppState>>,
+  _: AdminKey,
+) -> Result<impl IntoResponse> {
+  let totals = app.sv().bot_usage.totals(None).await?;
+  let label = |value: &str| value.replace('\\', "\\\\").replace('"', "\\\"");
+
+  let mut out = String::new();
+  out.push_str("# HELP bot_updates_total Bot commands and callbacks handled\n");
+  out.push_str("# TYPE bot_updates_total counter\n");
+  for row in &totals {
+    out.push_str(&format!(
+      "bot_updates_total{{kind=\"{:?}\",name=\"{}\"}} {}\n",
+      row.kind,
+      label(&row.name),
+      row.uses
+    ));
+  }
+  out.push_str("# HELP bot_failures_total Handlers that returned an error\n");
+  out.push_str("# TYPE bot_failures_total counter\n");
+  for row in &totals {
+    out.push_str(&format!(
+      "bot_failures_total{{kind=\"{:?}\",name=\"{}\"}} {}\n",
+      row.kind,
+      label(&row.name),
+      row.failures
+    ));
+  }
+
+  let sessions: usize = app.sessions.iter().map(|kv| kv.value().len()).sum();
+  out.push_str("# HELP license_sessions Live client sessions\n");
+  out.push_str("# TYPE license_sessions gauge\n");
+  out.push_str(&format!("license_sessions {}\n", sessions));
+  out.push_str("# HELP license_active_keys Keys with a live session\n");
+  out.push_str("# TYPE license_active_keys gauge\n");
+  out.push_str(&format!("license_active_keys {}\n", app.sessions.len()));
+
+  let mut rejects: Vec<_> = app
+    .telemetry_rejects
+    .iter()
+    .map(|kv| (kv.key().0.label(), kv.key().1, *kv.value()))
+    .collect();
+  rejects.sort();
+  out.push_str(
+    "# HELP telemetry_rejected_total Telemetry payloads refused or skipped\n",
+  );
+  out.push_str("# TYPE telemetry_rejected_total counter\n");
+  for (reason, schema, count) in rejects {
+    let schema = schema.map_or("unknown".to_string(), |v| v.to_string());
+    out.push_str(&format!(
+      "telemetry_rejected_total{{reason=\"{}\",schema=\"{}\"}} {}\n",
+      reason, schema, count
+    ));
+  }
+
+  Ok(([(header::CONTENT_TYPE, "text/plain; version=0.0.4")], out))
+}
+
+async fn license_of(app: &AppState, key: &str) -> Result<Json<license::Model>> {
+  app.sv().license.by_key(key).await?.map(Json).ok_or(Error::LicenseNotFound)
+}
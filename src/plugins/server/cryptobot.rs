@@ -0,0 +1,50 @@
+use std::sync::Arc;
+
+use axum::{
+  body::Bytes,
+  extract::State,
+  http::{HeaderMap, StatusCode},
+};
+
+use crate::{prelude::*, state::AppState, sv::cryptobot::WebhookUpdate};
+
+const SIGNATURE_HEADER: &str = "crypto-pay-api-signature";
+
+/// CryptoBot pushes paid invoices here, so balances are credited
+/// without the user pressing "Check Payments"
+pub async fn webhook(
+  State(app): State<Arc<AppState>>,
+  headers: HeaderMap,
+  body: Bytes,
+) -> Result<StatusCode> {
+  let Some(cryptobot) = &app.cryptobot else {
+    return Ok(StatusCode::NOT_FOUND);
+  };
+
+  let signature = headers
+    .get(SIGNATURE_HEADER)
+    .and_then(|value| value.to_str().ok())
+    .ok_or(Error::Unauthorized)?;
+  if !cryptobot.verify_webhook(&body, signature) {
+    warn!("CryptoBot webhook with a bad signature");
+    return Err(Error::Unauthorized);
+  }
+
+  let update: WebhookUpdate = json::from_slice(&body)
+    .map_err(|e| Error::InvalidArgs(format!("Bad webhook update: {}", e)))?;
+  if update.update_type != "invoice_paid" {
+    return Ok(StatusCode::OK);
+  }
+
+  // Unknown or already credited invoices are acknowledged as well,
+  // otherwise CryptoBot keeps retrying them
+  let Some(paid) =
+    app.sv().payment.process_paid(update.payload.invoice_id).await?
+  else {
+    return Ok(StatusCode::OK);
+  };
+
+  // the confirmation was queued with the deposit, cron::Outbox sends it
+  info!("Webhook credited invoice #{} to {}", paid.invoice_id, paid.user_id);
+  Ok(StatusCode::OK)
+}
@@ -1,25 +1,52 @@
-use std::{path::Path, sync::Arc};
+use std::{
+  io,
+  net::{IpAddr, SocketAddr},
+  path::Path,
+  sync::{Arc, atomic::Ordering},
+};
 
 use axum::{
   Json,
   body::Body,
-  extract::{Query, State},
-  http::{StatusCode, header},
-  response::IntoResponse,
+  extract::{ConnectInfo, Query, Request, State},
+  http::{HeaderMap, Method, StatusCode, header},
+  middleware::Next,
+  response::{IntoResponse, Response},
 };
+use hmac::{Hmac, Mac};
 use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use teloxide::{prelude::*, types::ParseMode, utils::html};
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
 use tokio_util::io::ReaderStream;
 
+use super::{
+  limits::{self, ApiScope},
+  replay,
+};
 use crate::{
+  entity::{goal, incident::IncidentKind, license},
+  plugins::telegram::support,
   prelude::*,
-  state::{AppState, Session},
+  state::{AppState, Config, Session},
+  sv::{self, admin_alert::Urgency, stats::RejectReason},
 };
 
+type HmacSha256 = Hmac<Sha256>;
+
 #[derive(Debug, Deserialize)]
 pub struct HeartbeatReq {
   pub key: String,
   pub machine_id: String,
   pub session_id: String,
+  /// Product the client is built for, older clients don't send it
+  #[serde(default)]
+  pub product: Option<String>,
+  /// `os-arch` of the running client, e.g. `linux-x64`
+  #[serde(default)]
+  pub platform: Option<String>,
+  #[serde(flatten)]
+  pub fresh: replay::Freshness,
 }
 
 #[derive(Debug, Serialize)]
@@ -29,16 +56,89 @@ pub struct HeartbeatRes {
   pub message: Option<String>,
   #[serde(skip_serializing_if = "Option::is_none")]
   pub magic_token: Option<i64>,
+  /// Machine-readable reason for policy rejections
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub code: Option<&'static str>,
+  /// Per-user or per-license overrides of client features
+  #[serde(skip_serializing_if = "HashMap::is_empty")]
+  pub features: HashMap<String, bool>,
+  /// Unix time on the server, the authoritative clock for expiry
+  pub server_time: i64,
+  /// See `sign_response`, set right before the response leaves
+  #[serde(skip_serializing_if = "String::is_empty")]
+  pub signature: String,
 }
 
 impl HeartbeatRes {
-  pub fn ok(magic: i64) -> Self {
-    Self { success: true, message: None, magic_token: Some(magic) }
+  pub fn ok(magic: i64, features: HashMap<String, bool>) -> Self {
+    Self {
+      success: true,
+      message: None,
+      magic_token: Some(magic),
+      code: None,
+      features,
+      server_time: Utc::now().timestamp(),
+      signature: String::new(),
+    }
   }
 
   pub fn invalid(message: impl Into<String>) -> Self {
-    Self { success: false, message: Some(message.into()), magic_token: None }
+    Self {
+      success: false,
+      message: Some(message.into()),
+      magic_token: None,
+      code: None,
+      features: HashMap::new(),
+      server_time: Utc::now().timestamp(),
+      signature: String::new(),
+    }
+  }
+
+  pub fn rejected(code: &'static str, message: impl Into<String>) -> Self {
+    Self { code: Some(code), ..Self::invalid(message) }
   }
+
+  /// "ok", the rejection code, or "invalid" for other failures
+  fn status(&self) -> &'static str {
+    match (self.success, self.code) {
+      (true, _) => "ok",
+      (false, Some(code)) => code,
+      (false, None) => "invalid",
+    }
+  }
+
+  fn signed(mut self, secret: &str, key: &str, nonce: &str) -> Self {
+    self.signature =
+      sign_response(secret, self.server_time, nonce, key, self.status());
+    self
+  }
+}
+
+/// Hex HMAC-SHA256 of `server_time:nonce:key:status` under the client key
+/// of the license, a local proxy answering "success" to everything can't
+/// produce it. The nonce of the request, empty if it had none, ties the
+/// answer to it so a captured one can't be replayed to the next request.
+fn sign_response(
+  secret: &str,
+  server_time: i64,
+  nonce: &str,
+  key: &str,
+  status: &str,
+) -> String {
+  let client_key = replay::client_key(secret, key);
+  let mut mac = HmacSha256::new_from_slice(client_key.as_bytes())
+    .expect("HMAC accepts keys of any size");
+  mac
+    .update(format!("{}:{}:{}:{}", server_time, nonce, key, status).as_bytes());
+  hex::encode(mac.finalize().into_bytes())
+}
+
+/// A refused client request, signed like any other answer before it leaves
+type Refusal = (StatusCode, HeaderMap, HeartbeatRes);
+
+fn refusal(err: Error) -> Refusal {
+  let (status, message) = err.status();
+  (status, HeaderMap::new(), HeartbeatRes::invalid(message))
 }
 
 fn generate_magic(session_id: &str, secret: &str) -> i64 {
@@ -51,10 +151,125 @@ fn generate_magic(session_id: &str, secret: &str) -> i64 {
   hash as i64
 }
 
+fn outside_schedule(from: i32, to: i32) -> (StatusCode, Json<HeartbeatRes>) {
+  let err = Error::OutsideSchedule { from, to };
+  (
+    StatusCode::FORBIDDEN,
+    Json(HeartbeatRes::rejected("outside_schedule", err.user_message())),
+  )
+}
+
+/// Country code and client address set by Cloudflare when the server runs
+/// behind it
+const COUNTRY_HEADER: &str = "cf-ipcountry";
+const CLIENT_IP_HEADER: &str = "cf-connecting-ip";
+/// Same for other proxies
+const FORWARDED_HEADER: &str = "x-forwarded-for";
+
+/// Where a request comes from. The proxy headers are only believed behind
+/// a trusted proxy, the socket peer and GeoIP are used otherwise
+struct Client {
+  ip: IpAddr,
+  country: Option<String>,
+}
+
+impl Client {
+  fn of(config: &Config, headers: &HeaderMap, addr: &SocketAddr) -> Self {
+    let header = |name: &str| {
+      config
+        .trusted_proxy
+        .then(|| headers.get(name)?.to_str().ok().map(str::to_string))
+        .flatten()
+    };
+    // the proxy appends the peer it saw, earlier entries are the client's
+    let forwarded = || {
+      let forwarded = header(FORWARDED_HEADER)?;
+      forwarded.rsplit(',').next().map(str::to_string)
+    };
+    let ip = header(CLIENT_IP_HEADER)
+      .or_else(forwarded)
+      .and_then(|ip| ip.trim().parse().ok())
+      .unwrap_or(addr.ip());
+    // "XX" and "T1" are unknown and Tor
+    let country = header(COUNTRY_HEADER)
+      .map(|c| c.to_uppercase())
+      .filter(|c| c.len() == 2 && c != "XX" && c != "T1")
+      .or_else(|| config.geoip.as_ref()?.lookup(ip));
+    Self { ip, country }
+  }
+}
+
 pub async fn heartbeat(
   State(app): State<Arc<AppState>>,
+  ConnectInfo(addr): ConnectInfo<SocketAddr>,
+  headers: HeaderMap,
   Json(req): Json<HeartbeatReq>,
+) -> Response {
+  let key = req.key.clone();
+  let nonce = req.fresh.nonce.clone().unwrap_or_default();
+  let sign = |res: HeartbeatRes| Json(res.signed(&app.secret, &key, &nonce));
+  // replays must not use up the pace of the real client
+  if let Err(rejection) = replay::check(&app, &req.key, &req.fresh) {
+    return (
+      rejection.status,
+      sign(HeartbeatRes::rejected("replayed", rejection.message)),
+    )
+      .into_response();
+  }
+  if let Err(rejection) = limits::pace(&app, &req.key, &req.session_id) {
+    return (
+      rejection.status,
+      rejection.headers,
+      sign(HeartbeatRes::rejected("too_frequent", rejection.message)),
+    )
+      .into_response();
+  }
+  let client = Client::of(&app.config, &headers, &addr);
+
+  // known sessions carry the tier, new ones need a lookup
+  let known = app
+    .sessions
+    .get(&req.key)
+    .and_then(|sessions| sessions.first().map(|s| s.license_type.clone()));
+  let license_type = match known {
+    Some(license_type) => Some(license_type),
+    None => app
+      .sv()
+      .license
+      .by_key(&req.key)
+      .await
+      .ok()
+      .flatten()
+      .map(|license| license.license_type),
+  };
+
+  // unknown keys are rejected by the heartbeat itself
+  let Some(license_type) = license_type else {
+    let (status, Json(res)) = handle_heartbeat(app.clone(), client, req).await;
+    return (status, sign(res)).into_response();
+  };
+
+  match limits::check(&app, &req.key, &license_type, ApiScope::Heartbeat) {
+    Ok(headers) => {
+      let (status, Json(res)) =
+        handle_heartbeat(app.clone(), client, req).await;
+      (status, headers, sign(res)).into_response()
+    }
+    Err(rejection) => (
+      rejection.status,
+      rejection.headers,
+      sign(HeartbeatRes::invalid(rejection.message)),
+    )
+      .into_response(),
+  }
+}
+
+async fn handle_heartbeat(
+  app: Arc<AppState>,
+  client: Client,
+  req: HeartbeatReq,
 ) -> (StatusCode, Json<HeartbeatRes>) {
+  let Client { ip, country } = client;
   let now = Utc::now().naive_utc();
   let magic = generate_magic(&req.session_id, &app.secret);
 
@@ -71,8 +286,16 @@ pub async fn heartbeat(
     && let Some(sess) =
       sessions.iter_mut().find(|s| s.session_id == req.session_id)
   {
+    if let Some(schedule) = sess.schedule
+      && !schedule.allows(now)
+    {
+      drop(sessions);
+      app.drop_sessions(&req.key);
+      return outside_schedule(schedule.from_hour, schedule.to_hour);
+    }
     sess.last_seen = now;
-    return (StatusCode::OK, Json(HeartbeatRes::ok(magic)));
+    let features = sess.features.clone();
+    return (StatusCode::OK, Json(HeartbeatRes::ok(magic, features)));
   }
 
   let license = match app.sv().license.validate(&req.key).await {
@@ -84,6 +307,13 @@ pub async fn heartbeat(
         Json(HeartbeatRes::invalid("Invalid license")),
       );
     }
+    Err(Error::Honeypot) => {
+      report_honeypot(&app, &req.key, ip, &req.machine_id).await;
+      return (
+        StatusCode::UNAUTHORIZED,
+        Json(HeartbeatRes::invalid("Invalid license")),
+      );
+    }
     Err(Error::LicenseInvalid) => {
       app.drop_sessions(&req.key);
       return (
@@ -91,6 +321,17 @@ pub async fn heartbeat(
         Json(HeartbeatRes::invalid("License expired or blocked")),
       );
     }
+    Err(Error::OutsideSchedule { from, to }) => {
+      app.drop_sessions(&req.key);
+      return outside_schedule(from, to);
+    }
+    Err(err @ Error::LicenseSuspended(_)) => {
+      app.drop_sessions(&req.key);
+      return (
+        StatusCode::FORBIDDEN,
+        Json(HeartbeatRes::rejected("suspended", err.user_message())),
+      );
+    }
     Err(_) => {
       return (
         StatusCode::INTERNAL_SERVER_ERROR,
@@ -99,30 +340,407 @@ pub async fn heartbeat(
     }
   };
 
-  let mut entry = app.sessions.entry(req.key.clone()).or_insert_with(Vec::new);
+  if let Some(product) = &req.product
+    && *product != license.product
+  {
+    let err = Error::WrongProduct(license.product.clone());
+    return (
+      StatusCode::FORBIDDEN,
+      Json(HeartbeatRes::rejected("wrong_product", err.user_message())),
+    );
+  }
+
+  if let Err(err) = sv::pricing::check_region(&license, country.as_deref()) {
+    return (
+      StatusCode::FORBIDDEN,
+      Json(HeartbeatRes::rejected("region_mismatch", err.user_message())),
+    );
+  }
+
+  let policy = app.config.hwid_policy();
+  match app.sv().hwid_policy.check(&license, &req.machine_id, &policy).await {
+    Ok(()) => {}
+    Err(err @ Error::HwidLimit { trial }) => {
+      let code = if trial { "hwid_trial_limit" } else { "hwid_license_limit" };
+      strike(&app, &license).await;
+      return (
+        StatusCode::FORBIDDEN,
+        Json(HeartbeatRes::rejected(code, err.user_message())),
+      );
+    }
+    Err(err) => {
+      warn!("HWID policy check failed for {}: {}", req.key, err);
+    }
+  }
+
+  // validate() already checked the window, this only caches it
+  let schedule = app.sv().license.schedule(&license).await.ok().flatten();
+  let features = match app.sv().feature_flag.resolve(&license).await {
+    Ok(features) => features,
+    Err(err) => {
+      warn!("Failed to load feature flags of {}: {}", req.key, err);
+      HashMap::new()
+    }
+  };
+
+  let mut entry = app.sessions.entry(req.key.clone()).or_default();
   entry.retain(|s| {
     (now - s.last_seen).num_seconds() < app.config.session_lifetime
   });
 
   let max_sessions = license.max_sessions as usize;
   if entry.len() >= max_sessions {
+    let active = entry.len();
+    drop(entry);
+    strike(&app, &license).await;
     return (
       StatusCode::CONFLICT,
       Json(HeartbeatRes::invalid(format!(
         "Session limit reached ({}/{})",
-        entry.len(),
-        max_sessions
+        active, max_sessions
       ))),
     );
   }
 
+  let platform = req
+    .platform
+    .as_deref()
+    .and_then(sv::build::normalize_platform)
+    .map(str::to_string);
   entry.push(Session {
-    session_id: req.session_id,
-    hwid_hash: Some(req.machine_id),
+    session_id: req.session_id.clone(),
+    hwid_hash: Some(req.machine_id.clone()),
+    platform: platform.clone(),
+    ip: Some(ip.to_string()),
+    country: country.clone(),
     last_seen: now,
+    license_type: license.license_type.clone(),
+    schedule,
+    features: features.clone(),
   });
+  drop(entry);
+
+  let ip = ip.to_string();
+  if let Err(err) = app
+    .sv()
+    .session
+    .start(
+      &req.key,
+      &req.session_id,
+      Some(&req.machine_id),
+      platform.as_deref(),
+      Some(&ip),
+      country.as_deref(),
+    )
+    .await
+  {
+    warn!("Failed to persist session of {}: {}", req.key, err);
+  }
+
+  match app
+    .sv()
+    .device
+    .touch(&req.key, &req.machine_id, Some(ip), country)
+    .await
+  {
+    Ok(Some(anomaly)) => {
+      let app = app.clone();
+      tokio::spawn(async move {
+        support::alert_anomaly(&app, &license, anomaly).await;
+      });
+    }
+    Ok(None) => {}
+    Err(err) => warn!("Failed to record device for {}: {}", req.key, err),
+  }
+
+  (StatusCode::OK, Json(HeartbeatRes::ok(magic, features)))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ValidateReq {
+  pub key: String,
+  pub machine_id: String,
+  #[serde(default)]
+  pub product: Option<String>,
+  #[serde(flatten)]
+  pub fresh: replay::Freshness,
+}
+
+impl ValidateReq {
+  fn sign(&self, secret: &str, refusal: Refusal) -> Response {
+    let (status, headers, res) = refusal;
+    let nonce = self.fresh.nonce.as_deref().unwrap_or_default();
+    (status, headers, Json(res.signed(secret, &self.key, nonce)))
+      .into_response()
+  }
+}
+
+#[derive(Debug, Serialize)]
+pub struct ValidateRes {
+  pub success: bool,
+  /// JWT with `sv::assertion::Assertion` claims
+  pub token: String,
+  pub expires_at: i64,
+  /// Unix time on the server, clients compare `expires_at` against it
+  /// rather than their own clock
+  pub server_time: i64,
+  /// Same scheme as the heartbeat one, with status "ok". Refusals are
+  /// signed heartbeat answers
+  pub signature: String,
+}
+
+fn replayed(rejection: limits::Rejection) -> Refusal {
+  let res = HeartbeatRes::rejected("replayed", rejection.message);
+  (rejection.status, rejection.headers, res)
+}
+
+/// Checks shared by `/api/validate`, `/api/offline-token` and
+/// `/api/client-key`, Ok carries the license and the rate limit headers
+async fn check_client(
+  app: &Arc<AppState>,
+  client: &Client,
+  req: &ValidateReq,
+) -> Result<(license::Model, HeaderMap), Refusal> {
+  let sv = app.sv();
+  let license = match sv.license.validate(&req.key).await {
+    Ok(license) => license,
+    Err(Error::Honeypot) => {
+      report_honeypot(app, &req.key, client.ip, &req.machine_id).await;
+      return Err(refusal(Error::LicenseNotFound));
+    }
+    Err(e) => return Err(refusal(e)),
+  };
+
+  let limit_headers = match limits::check(
+    app,
+    &req.key,
+    &license.license_type,
+    ApiScope::Validate,
+  ) {
+    Ok(headers) => headers,
+    Err(rejection) => {
+      return Err((
+        rejection.status,
+        rejection.headers,
+        HeartbeatRes::invalid(rejection.message),
+      ));
+    }
+  };
+
+  if let Some(product) = &req.product
+    && *product != license.product
+  {
+    return Err(refusal(Error::WrongProduct(license.product)));
+  }
+  if let Err(e) = sv::pricing::check_region(&license, client.country.as_deref())
+  {
+    return Err(refusal(e));
+  }
+  let policy = app.config.hwid_policy();
+  if let Err(e) = sv.hwid_policy.check(&license, &req.machine_id, &policy).await
+  {
+    return Err(refusal(e));
+  }
+  Ok((license, limit_headers))
+}
+
+/// Check a key and machine without opening a session and return a signed,
+/// short-lived assertion the client can verify locally
+pub async fn validate(
+  State(app): State<Arc<AppState>>,
+  ConnectInfo(addr): ConnectInfo<SocketAddr>,
+  headers: HeaderMap,
+  Json(req): Json<ValidateReq>,
+) -> Response {
+  let client = Client::of(&app.config, &headers, &addr);
+  if let Err(rejection) = replay::check(&app, &req.key, &req.fresh) {
+    return req.sign(&app.secret, replayed(rejection));
+  }
+  let (license, limit_headers) = match check_client(&app, &client, &req).await {
+    Ok(checked) => checked,
+    Err(refused) => return req.sign(&app.secret, refused),
+  };
+
+  let now = Utc::now().naive_utc();
+  let assertion = sv::assertion::Assertion::new(
+    &license,
+    &req.machine_id,
+    now,
+    sv::assertion::LIFETIME,
+    app.config.clock_skew(),
+  );
+  match assertion.sign(&app.secret) {
+    Ok(token) => {
+      let res = ValidateRes {
+        success: true,
+        token,
+        expires_at: assertion.exp,
+        server_time: assertion.iat,
+        signature: sign_response(
+          &app.secret,
+          assertion.iat,
+          req.fresh.nonce.as_deref().unwrap_or_default(),
+          &req.key,
+          "ok",
+        ),
+      };
+      (limit_headers, Json(res)).into_response()
+    }
+    Err(e) => req.sign(&app.secret, refusal(e)),
+  }
+}
+
+/// Ed25519-signed token the client keeps to run through server outages,
+/// verified against `/api/keys`
+pub async fn offline_token(
+  State(app): State<Arc<AppState>>,
+  ConnectInfo(addr): ConnectInfo<SocketAddr>,
+  headers: HeaderMap,
+  Json(req): Json<ValidateReq>,
+) -> Response {
+  if let Err(rejection) = replay::check(&app, &req.key, &req.fresh) {
+    return req.sign(&app.secret, replayed(rejection));
+  }
+  let client = Client::of(&app.config, &headers, &addr);
+  let limit_headers = match check_client(&app, &client, &req).await {
+    Ok((_, limit_headers)) => limit_headers,
+    Err(refused) => return req.sign(&app.secret, refused),
+  };
+
+  let lifetime = TimeDelta::hours(app.config.offline_token_hours);
+  match app
+    .sv()
+    .license
+    .issue_offline_token(&req.key, &req.machine_id, &app.keyring(), lifetime)
+    .await
+  {
+    Ok((token, claims)) => {
+      let res = ValidateRes {
+        success: true,
+        token,
+        expires_at: claims.exp,
+        server_time: claims.iat,
+        signature: sign_response(
+          &app.secret,
+          claims.iat,
+          req.fresh.nonce.as_deref().unwrap_or_default(),
+          &req.key,
+          "ok",
+        ),
+      };
+      (limit_headers, Json(res)).into_response()
+    }
+    Err(e) => req.sign(&app.secret, refusal(e)),
+  }
+}
+
+#[derive(Debug, Serialize)]
+pub struct ClientKeyRes {
+  /// See `replay::client_key`
+  pub client_key: String,
+}
+
+/// Key the client signs its requests with and checks answers against, for
+/// a license that passes the `/api/validate` checks. It can't be signed
+/// yet, so it needs no nonce.
+pub async fn client_key(
+  State(app): State<Arc<AppState>>,
+  ConnectInfo(addr): ConnectInfo<SocketAddr>,
+  headers: HeaderMap,
+  Json(req): Json<ValidateReq>,
+) -> Response {
+  let client = Client::of(&app.config, &headers, &addr);
+  match check_client(&app, &client, &req).await {
+    Ok((_, limit_headers)) => {
+      let client_key = replay::client_key(&app.secret, &req.key);
+      (limit_headers, Json(ClientKeyRes { client_key })).into_response()
+    }
+    Err(refused) => req.sign(&app.secret, refused),
+  }
+}
+
+#[derive(Debug, Serialize)]
+pub struct KeysRes {
+  pub keys: Vec<sv::signing_key::PublicKey>,
+}
+
+/// Public keys offline tokens may be signed with, the active one first
+pub async fn signing_keys(State(app): State<Arc<AppState>>) -> Json<KeysRes> {
+  Json(KeysRes { keys: app.keyring().public_keys() })
+}
+
+/// Count a session limit or sharing violation, too many of them put the key
+/// on a cool-down instead of banning it for good
+async fn strike(app: &AppState, license: &license::Model) {
+  if !app.strike(&license.key) {
+    return;
+  }
+
+  let duration = TimeDelta::hours(app.config.suspend_hours);
+  let until = match app.sv().license.suspend(&license.key, duration).await {
+    Ok(until) => until,
+    Err(err) => {
+      warn!("Failed to suspend {}: {}", license.key, err);
+      return;
+    }
+  };
+  app.drop_sessions(&license.key);
+
+  let text = format!(
+    "⏸ <b>License suspended</b>\n\n\
+    Key <code>{}</code> kept exceeding its session limit or was used on \
+    too many machines.\n\n\
+    It works again in {} ({}).",
+    license.key,
+    utils::format_duration(duration),
+    utils::format_date(until)
+  );
+  if let Some((bot, text)) = app.reach(license.tg_user_id, text).await {
+    let _ = bot
+      .send_message(ChatId(license.tg_user_id), text)
+      .parse_mode(ParseMode::Html)
+      .await;
+  }
+}
+
+/// Record the leak and alert admins, once per key and machine per hour
+async fn report_honeypot(
+  app: &AppState,
+  key: &str,
+  ip: IpAddr,
+  machine_id: &str,
+) {
+  let ip = ip.to_string();
+  let incident = app
+    .sv()
+    .incident
+    .record(
+      IncidentKind::Honeypot,
+      Some(key),
+      Some(&ip),
+      Some(machine_id),
+      "Heartbeat with a honeypot key",
+    )
+    .await;
 
-  (StatusCode::OK, Json(HeartbeatRes::ok(magic)))
+  match incident {
+    Ok(Some(incident)) => {
+      let text = format!(
+        "🍯 <b>Honeypot key used!</b> (incident #{})\n\n\
+        The key database or a reseller has leaked.\n\n\
+        Key: <code>{}</code>\n\
+        IP: <code>{}</code>\n\
+        HWID: <code>{}</code>",
+        incident.id,
+        key,
+        ip,
+        html::escape(machine_id)
+      );
+      app.notify_admins(Urgency::Critical, &text, None).await;
+    }
+    Ok(None) => {}
+    Err(e) => error!("Failed to record honeypot incident: {}", e),
+  }
 }
 
 #[derive(Debug, Deserialize)]
@@ -147,55 +765,288 @@ pub async fn logout(
 #[derive(Debug, Deserialize)]
 pub struct MetricsReq {
   pub stats: String,
+  /// How `stats` is compressed, gzip if absent
+  #[serde(default)]
+  pub encoding: sv::stats::Encoding,
+  #[serde(flatten)]
+  pub fresh: replay::Freshness,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MetricsBatchReq {
+  pub stats: Vec<String>,
+  /// Applies to every entry of `stats`
+  #[serde(default)]
+  pub encoding: sv::stats::Encoding,
+  #[serde(flatten)]
+  pub fresh: replay::Freshness,
+}
+
+fn rejection_response(rejection: limits::Rejection) -> Response {
+  let body = json::json!({ "success": false, "error": rejection.message });
+  (rejection.status, rejection.headers, Json(body)).into_response()
 }
 
 pub async fn submit_metrics(
   State(app): State<Arc<AppState>>,
   Json(req): Json<MetricsReq>,
-) -> Result<()> {
-  app.sv().stats.process_metric(&req.stats).await?;
-  Ok(())
+) -> Result<Response> {
+  let payload = sv::Stats::decode_metric(&req.stats, req.encoding)
+    .map_err(|rejected| app.reject_telemetry(rejected))?;
+  if !payload.is_supported() {
+    app.count_telemetry_reject(
+      RejectReason::Unsupported,
+      Some(payload.schema_version),
+    );
+  }
+  if let Err(rejection) = replay::check(&app, &payload.license_key, &req.fresh)
+  {
+    return Ok(rejection_response(rejection));
+  }
+  let sv = app.sv();
+  let license = sv
+    .license
+    .by_key(&payload.license_key)
+    .await?
+    .ok_or(Error::LicenseNotFound)?;
+
+  let headers = match limits::check(
+    &app,
+    &license.key,
+    &license.license_type,
+    ApiScope::Metrics,
+  ) {
+    Ok(headers) => headers,
+    Err(rejection) => return Ok(rejection_response(rejection)),
+  };
+
+  let goal = sv.stats.record_metric(&license, payload).await?;
+  if let Some(goal) = goal {
+    notify_goal(&app, &goal).await;
+  }
+  Ok(headers.into_response())
+}
+
+/// Several metrics of one license in a single request (Pro only)
+pub async fn submit_metrics_batch(
+  State(app): State<Arc<AppState>>,
+  Json(req): Json<MetricsBatchReq>,
+) -> Result<Response> {
+  if req.stats.len() > limits::BATCH_MAX {
+    return Err(Error::InvalidArgs(format!(
+      "At most {} metrics per batch",
+      limits::BATCH_MAX
+    )));
+  }
+
+  let payloads = req
+    .stats
+    .iter()
+    .map(|raw| {
+      sv::Stats::decode_metric(raw, req.encoding)
+        .map_err(|rejected| app.reject_telemetry(rejected))
+    })
+    .collect::<Result<Vec<_>>>()?;
+  for payload in payloads.iter().filter(|p| !p.is_supported()) {
+    app.count_telemetry_reject(
+      RejectReason::Unsupported,
+      Some(payload.schema_version),
+    );
+  }
+  let Some(first) = payloads.first() else {
+    return Ok(StatusCode::OK.into_response());
+  };
+  if payloads.iter().any(|p| p.license_key != first.license_key) {
+    return Err(Error::InvalidArgs(
+      "All metrics in a batch must use the same license".into(),
+    ));
+  }
+  if let Err(rejection) = replay::check(&app, &first.license_key, &req.fresh) {
+    return Ok(rejection_response(rejection));
+  }
+
+  let sv = app.sv();
+  let license = sv
+    .license
+    .by_key(&first.license_key)
+    .await?
+    .ok_or(Error::LicenseNotFound)?;
+
+  let headers = match limits::check(
+    &app,
+    &license.key,
+    &license.license_type,
+    ApiScope::Batch,
+  ) {
+    Ok(headers) => headers,
+    Err(rejection) => return Ok(rejection_response(rejection)),
+  };
+
+  for payload in payloads {
+    if let Some(goal) = sv.stats.record_metric(&license, payload).await? {
+      notify_goal(&app, &goal).await;
+    }
+  }
+  Ok(headers.into_response())
+}
+
+async fn notify_goal(app: &AppState, goal: &goal::Model) {
+  let text = format!(
+    "🎯 <b>Daily goal reached!</b>\n\n\
+    {} today. 🔥 Streak: {} day(s) (best {})",
+    goal.kind.format(goal.progress),
+    goal.streak,
+    goal.best_streak
+  );
+  if let Some((bot, text)) = app.reach(goal.tg_user_id, text).await {
+    let _ = bot
+      .send_message(ChatId(goal.tg_user_id), text)
+      .parse_mode(ParseMode::Html)
+      .await;
+  }
 }
 
+/// Liveness, the process is up and serving requests
 pub async fn health() -> &'static str {
   "OK"
 }
 
+#[derive(Debug, Serialize)]
+pub struct ReadyRes {
+  pub ready: bool,
+  /// Checks still failing
+  #[serde(skip_serializing_if = "Vec::is_empty")]
+  pub waiting: Vec<&'static str>,
+}
+
+/// Refuses writes while /restore swaps the database
+pub async fn maintenance(
+  State(app): State<Arc<AppState>>,
+  request: Request,
+  next: Next,
+) -> Response {
+  if app.maintenance.load(Ordering::Relaxed) && request.method() != Method::GET
+  {
+    return Error::Maintenance.into_response();
+  }
+  next.run(request).await
+}
+
+/// Readiness, 503 until the database is reachable, migrations are applied
+/// and the bot is connected
+pub async fn ready(State(app): State<Arc<AppState>>) -> Response {
+  let waiting = app.unready().await;
+  let status = if waiting.is_empty() {
+    StatusCode::OK
+  } else {
+    StatusCode::SERVICE_UNAVAILABLE
+  };
+  (status, Json(ReadyRes { ready: waiting.is_empty(), waiting }))
+    .into_response()
+}
+
 #[derive(Debug, Deserialize)]
 pub struct DownloadQuery {
   pub token: String,
 }
 
+/// Part of the file a request asks for
+#[derive(Debug, PartialEq, Eq)]
+enum ByteRange {
+  Full,
+  /// Inclusive bounds
+  Partial(u64, u64),
+  Unsatisfiable,
+}
+
+/// Single `bytes=` range of a `Range` header. Malformed headers and
+/// multiple ranges are ignored, the whole file is sent then.
+fn byte_range(header: Option<&str>, len: u64) -> ByteRange {
+  let Some(spec) = header.and_then(|h| h.trim().strip_prefix("bytes=")) else {
+    return ByteRange::Full;
+  };
+  let Some((start, end)) = spec.split_once('-').filter(|_| !spec.contains(','))
+  else {
+    return ByteRange::Full;
+  };
+  let last = len.saturating_sub(1);
+
+  let (start, end) = match (start.trim(), end.trim()) {
+    // last N bytes
+    ("", suffix) => match suffix.parse::<u64>() {
+      Ok(0) => return ByteRange::Unsatisfiable,
+      Ok(suffix) => (len.saturating_sub(suffix), last),
+      Err(_) => return ByteRange::Full,
+    },
+    (start, "") => match start.parse() {
+      Ok(start) => (start, last),
+      Err(_) => return ByteRange::Full,
+    },
+    (start, end) => match (start.parse(), end.parse::<u64>()) {
+      (Ok(start), Ok(end)) if start <= end => (start, end.min(last)),
+      _ => return ByteRange::Full,
+    },
+  };
+  if start >= len {
+    return ByteRange::Unsatisfiable;
+  }
+  ByteRange::Partial(start, end)
+}
+
 pub async fn download(
   State(app): State<Arc<AppState>>,
+  ConnectInfo(addr): ConnectInfo<SocketAddr>,
+  headers: HeaderMap,
   Query(query): Query<DownloadQuery>,
-) -> impl IntoResponse {
-  let version = match app.validate_download_token(&query.token) {
-    Some(v) => v,
-    None => {
-      return Err((
-        StatusCode::UNAUTHORIZED,
-        "Invalid or expired download token",
-      ));
-    }
+) -> Response {
+  let range = headers.get(header::RANGE).and_then(|v| v.to_str().ok());
+  let ip = Client::of(&app.config, &headers, &addr).ip.to_string();
+  let sv = app.sv();
+  let invalid_token = || {
+    (
+      StatusCode::UNAUTHORIZED,
+      "Invalid, expired or already used download token",
+    )
+      .into_response()
+  };
+  let internal_error = |e: Error| {
+    warn!("Failed to redeem download token: {}", e);
+    (StatusCode::INTERNAL_SERVER_ERROR, "Internal error").into_response()
+  };
+
+  let token = match sv.download_tokens.by_token(&query.token).await {
+    Ok(Some(token)) => token,
+    Ok(None) => return invalid_token(),
+    Err(e) => return internal_error(e),
   };
 
-  let build = match app.sv().build.by_version(&version).await {
+  let build = match sv.build.by_version(&token.version).await {
     Ok(Some(b)) if b.is_active => b,
     _ => {
-      return Err((StatusCode::NOT_FOUND, "Build not found"));
+      return (StatusCode::NOT_FOUND, "Build not found").into_response();
     }
   };
 
-  let path = Path::new(&build.file_path);
-  if !path.exists() {
-    return Err((StatusCode::NOT_FOUND, "Build file not found"));
-  }
-
-  let file = match tokio::fs::File::open(path).await {
-    Ok(f) => f,
+  let file_path =
+    match sv.build.file_for(&build, token.platform.as_deref()).await {
+      Ok(Some((file_path, _))) => file_path,
+      _ => {
+        return (StatusCode::NOT_FOUND, "Build not found").into_response();
+      }
+    };
+  let path = Path::new(&file_path);
+  let opened = match tokio::fs::File::open(path).await {
+    Ok(file) => file.metadata().await.map(|meta| (file, meta.len())),
+    Err(e) => Err(e),
+  };
+  let (mut file, len) = match opened {
+    Ok(opened) => opened,
+    Err(e) if e.kind() == io::ErrorKind::NotFound => {
+      return (StatusCode::NOT_FOUND, "Build file not found").into_response();
+    }
     Err(_) => {
-      return Err((StatusCode::INTERNAL_SERVER_ERROR, "Failed to open file"));
+      return (StatusCode::INTERNAL_SERVER_ERROR, "Failed to open file")
+        .into_response();
     }
   };
 
@@ -205,19 +1056,150 @@ pub async fn download(
     .unwrap_or("download.bin")
     .to_string();
 
-  let stream = ReaderStream::new(file);
-  let body = Body::from_stream(stream);
+  let (status, start, end) = match byte_range(range, len) {
+    ByteRange::Full => (StatusCode::OK, 0, len.saturating_sub(1)),
+    ByteRange::Partial(start, end) => (StatusCode::PARTIAL_CONTENT, start, end),
+    ByteRange::Unsatisfiable => {
+      return (
+        StatusCode::RANGE_NOT_SATISFIABLE,
+        [(header::CONTENT_RANGE, format!("bytes */{}", len))],
+      )
+        .into_response();
+    }
+  };
+
+  // the token is only spent once the file is there. A used one resumes
+  // the download it started, from the address that started it
+  let resumed = start > 0
+    && match sv.download_tokens.resumable(&token.token, &ip).await {
+      Ok(resumable) => resumable,
+      Err(e) => return internal_error(e),
+    };
+  let first = !resumed;
+  if first {
+    match sv.download_tokens.redeem(&token.token).await {
+      Ok(Some(_)) => {}
+      Ok(None) => return invalid_token(),
+      Err(e) => return internal_error(e),
+    }
+  }
 
-  // Increment download counter
-  let _ = app.sv().build.increment_downloads(&version).await;
+  if start > 0 && file.seek(io::SeekFrom::Start(start)).await.is_err() {
+    return (StatusCode::INTERNAL_SERVER_ERROR, "Failed to read file")
+      .into_response();
+  }
+  let length = if len == 0 { 0 } else { end - start + 1 };
+  let body = Body::from_stream(ReaderStream::new(file.take(length)));
 
-  let headers = [
-    (header::CONTENT_TYPE, "application/octet-stream".to_string()),
-    (
-      header::CONTENT_DISPOSITION,
-      format!("attachment; filename=\"{}\"", filename),
-    ),
-  ];
+  // resumed downloads were counted by their first request
+  if first {
+    let _ = sv.build.increment_downloads(&token.version).await;
+    if let Err(e) =
+      sv.download_tokens.record(&token, &build.product, Some(ip)).await
+    {
+      warn!("Failed to log download of {}: {}", token.version, e);
+    }
+  }
+
+  let mut response_headers = HeaderMap::new();
+  let mut set = |name, value: String| {
+    if let Ok(value) = value.parse() {
+      response_headers.insert(name, value);
+    }
+  };
+  set(header::CONTENT_TYPE, "application/octet-stream".to_string());
+  set(
+    header::CONTENT_DISPOSITION,
+    format!("attachment; filename=\"{}\"", filename),
+  );
+  set(header::ACCEPT_RANGES, "bytes".to_string());
+  set(header::CONTENT_LENGTH, length.to_string());
+  if status == StatusCode::PARTIAL_CONTENT {
+    set(header::CONTENT_RANGE, format!("bytes {}-{}/{}", start, end, len));
+  }
+
+  (status, response_headers, body).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
 
-  Ok((headers, body))
+  #[test]
+  fn test_byte_range() {
+    assert_eq!(byte_range(None, 100), ByteRange::Full);
+    assert_eq!(byte_range(Some("bytes=0-49"), 100), ByteRange::Partial(0, 49));
+    assert_eq!(byte_range(Some("bytes=50-"), 100), ByteRange::Partial(50, 99));
+    assert_eq!(byte_range(Some("bytes=-10"), 100), ByteRange::Partial(90, 99));
+    assert_eq!(
+      byte_range(Some("bytes=90-500"), 100),
+      ByteRange::Partial(90, 99)
+    );
+    assert_eq!(byte_range(Some("bytes=100-"), 100), ByteRange::Unsatisfiable);
+    assert_eq!(byte_range(Some("bytes=-0"), 100), ByteRange::Unsatisfiable);
+    // ignored, the whole file is sent
+    assert_eq!(byte_range(Some("bytes=0-1,5-9"), 100), ByteRange::Full);
+    assert_eq!(byte_range(Some("bytes=9-5"), 100), ByteRange::Full);
+    assert_eq!(byte_range(Some("items=0-5"), 100), ByteRange::Full);
+  }
+
+  #[test]
+  fn test_sign_response() {
+    // what a client computes with any HMAC-SHA256 implementation
+    assert_eq!(
+      sign_response("secret", 1700000000, "nonce-0001", "KEY", "ok"),
+      "2bdfdde5189a8dbc072ed2daa3a67c5f6287e8eab22751c631ffee796c4ecea1"
+    );
+
+    let res =
+      HeartbeatRes::rejected("suspended", "nope").signed("secret", "K", "N");
+    assert_eq!(res.status(), "suspended");
+    assert_eq!(
+      res.signature,
+      sign_response("secret", res.server_time, "N", "K", "suspended")
+    );
+    // any change of the signed fields changes the signature
+    let time = res.server_time;
+    let ok = sign_response("secret", time, "N", "K", "ok");
+    assert_ne!(res.signature, ok);
+    assert_ne!(ok, sign_response("other", time, "N", "K", "ok"));
+    assert_ne!(ok, sign_response("secret", time + 1, "N", "K", "ok"));
+    // nor does it answer another request
+    assert_ne!(ok, sign_response("secret", time, "M", "K", "ok"));
+  }
+
+  #[test]
+  fn test_client() {
+    let geoip = crate::geoip::GeoIp::parse(
+      "1.0.0.0,1.0.0.255,AU\n2.16.0.0,2.16.255.255,DE\n",
+    );
+    let mut config =
+      Config { geoip: Some(Arc::new(geoip)), ..Config::default() };
+    let addr: SocketAddr = "1.0.0.7:443".parse().unwrap();
+    let mut headers = HeaderMap::new();
+    headers.insert(COUNTRY_HEADER, "us".parse().unwrap());
+    headers.insert(FORWARDED_HEADER, "9.9.9.9, 2.16.3.4".parse().unwrap());
+    let client = |config: &Config, headers: &HeaderMap| {
+      let client = Client::of(config, headers, &addr);
+      (client.ip.to_string(), client.country)
+    };
+
+    // straight to the origin, the headers are whatever the client wants
+    assert_eq!(
+      client(&config, &headers),
+      ("1.0.0.7".into(), Some("AU".into()))
+    );
+
+    config.trusted_proxy = true;
+    assert_eq!(
+      client(&config, &headers),
+      ("2.16.3.4".into(), Some("US".into()))
+    );
+    headers.insert(COUNTRY_HEADER, "XX".parse().unwrap());
+    headers.insert(CLIENT_IP_HEADER, "2.16.0.1".parse().unwrap());
+    assert_eq!(
+      client(&config, &headers),
+      ("2.16.0.1".into(), Some("DE".into()))
+    );
+  }
 }
@@ -0,0 +1,115 @@
+use axum::http::{HeaderMap, HeaderValue, StatusCode};
+
+use crate::{
+  entity::{LicenseType, api_usage::ApiKind},
+  state::AppState,
+};
+
+/// Metrics sent in one batch request
+pub const BATCH_MAX: usize = 100;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ApiScope {
+  Heartbeat,
+  Validate,
+  Metrics,
+  Batch,
+}
+
+impl ApiScope {
+  fn name(self) -> &'static str {
+    match self {
+      ApiScope::Heartbeat => "heartbeat",
+      ApiScope::Validate => "validate",
+      ApiScope::Metrics => "metrics",
+      ApiScope::Batch => "batch",
+    }
+  }
+
+  fn kind(self) -> ApiKind {
+    match self {
+      ApiScope::Heartbeat => ApiKind::Heartbeat,
+      ApiScope::Validate => ApiKind::Validate,
+      ApiScope::Metrics => ApiKind::Metrics,
+      ApiScope::Batch => ApiKind::Batch,
+    }
+  }
+}
+
+/// Requests per minute a license tier gets, `None` if the endpoint
+/// isn't included in the tier at all
+pub fn limit(license_type: &LicenseType, scope: ApiScope) -> Option<u32> {
+  match (license_type, scope) {
+    (LicenseType::Trial, ApiScope::Heartbeat) => Some(6),
+    (LicenseType::Trial, ApiScope::Validate) => Some(6),
+    (LicenseType::Trial, ApiScope::Metrics) => Some(20),
+    (LicenseType::Trial, ApiScope::Batch) => None,
+    (LicenseType::Pro, ApiScope::Heartbeat) => Some(30),
+    (LicenseType::Pro, ApiScope::Validate) => Some(30),
+    (LicenseType::Pro, ApiScope::Metrics) => Some(120),
+    (LicenseType::Pro, ApiScope::Batch) => Some(10),
+  }
+}
+
+/// Refuse a heartbeat that came too soon after the previous one of the
+/// same session, before it costs a license lookup
+pub fn pace(
+  app: &AppState,
+  key: &str,
+  session_id: &str,
+) -> Result<(), Rejection> {
+  let Some(wait) = app.pace_heartbeat(key, session_id) else {
+    return Ok(());
+  };
+  let mut headers = HeaderMap::new();
+  headers.insert("retry-after", HeaderValue::from(wait));
+  Err(Rejection {
+    status: StatusCode::TOO_MANY_REQUESTS,
+    headers,
+    message: "Heartbeats are sent too often",
+  })
+}
+
+pub struct Rejection {
+  pub status: StatusCode,
+  pub headers: HeaderMap,
+  pub message: &'static str,
+}
+
+/// Count the request against the license tier, halved for keys that kept
+/// hitting it lately. Ok carries the rate limit headers for the response.
+pub fn check(
+  app: &AppState,
+  key: &str,
+  license_type: &LicenseType,
+  scope: ApiScope,
+) -> Result<HeaderMap, Rejection> {
+  let Some(limit) = limit(license_type, scope) else {
+    app.record_api(key, scope.kind(), false);
+    return Err(Rejection {
+      status: StatusCode::FORBIDDEN,
+      headers: HeaderMap::new(),
+      message: "Not available for your license type",
+    });
+  };
+
+  let limit = if app.is_abuser(key) { (limit / 2).max(1) } else { limit };
+  let status = app.hit_rate_limit(key, scope.name(), limit);
+  app.record_api(key, scope.kind(), status.allowed);
+
+  let mut headers = HeaderMap::new();
+  headers.insert("x-ratelimit-limit", HeaderValue::from(status.limit));
+  headers.insert("x-ratelimit-remaining", HeaderValue::from(status.remaining));
+  headers.insert("x-ratelimit-reset", HeaderValue::from(status.reset));
+
+  if status.allowed {
+    return Ok(headers);
+  }
+
+  headers.insert("retry-after", HeaderValue::from(status.reset));
+  Err(Rejection {
+    status: StatusCode::TOO_MANY_REQUESTS,
+    headers,
+    message: "Rate limit exceeded for your license tier",
+  })
+}
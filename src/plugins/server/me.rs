@@ -0,0 +1,98 @@
+use std::sync::Arc;
+
+use axum::{
+  Json,
+  extract::{FromRequestParts, State},
+  http::{header, request::Parts},
+};
+use serde::Serialize;
+
+use crate::{
+  entity::license::LicenseType, prelude::*, state::AppState,
+  sv::stats::UserStatsDisplay,
+};
+
+/// Owner of a personal API token, taken from `Authorization: Bearer`
+/// or from `?token=` for clients that can't set headers
+pub struct ApiUser {
+  pub tg_user_id: i64,
+  /// Set for product-scoped tokens
+  pub product: Option<String>,
+}
+
+impl FromRequestParts<Arc<AppState>> for ApiUser {
+  type Rejection = Error;
+
+  async fn from_request_parts(
+    parts: &mut Parts,
+    app: &Arc<AppState>,
+  ) -> Result<Self> {
+    let header = parts
+      .headers
+      .get(header::AUTHORIZATION)
+      .and_then(|value| value.to_str().ok())
+      .and_then(|value| value.strip_prefix("Bearer "))
+      .map(str::to_string);
+
+    let query = || {
+      parts
+        .uri
+        .query()?
+        .split('&')
+        .find_map(|pair| pair.strip_prefix("token=").map(str::to_string))
+    };
+
+    let token = header.or_else(query).ok_or(Error::Unauthorized)?;
+    let record = app.sv().api_token.authenticate(token.trim()).await?;
+    Ok(ApiUser { tg_user_id: record.tg_user_id, product: record.product })
+  }
+}
+
+#[derive(Debug, Serialize)]
+pub struct LicenseInfo {
+  /// Only a prefix, the token is read-only and may be shared with overlays
+  pub key: String,
+  pub product: String,
+  pub license_type: LicenseType,
+  pub expires_at: DateTime,
+  pub is_blocked: bool,
+  /// End of a temporary suspension
+  pub blocked_until: Option<DateTime>,
+  pub active: bool,
+}
+
+pub async fn stats(
+  State(app): State<Arc<AppState>>,
+  user: ApiUser,
+) -> Result<Json<UserStatsDisplay>> {
+  Ok(Json(app.sv().stats.display_stats(user.tg_user_id).await?))
+}
+
+pub async fn licenses(
+  State(app): State<Arc<AppState>>,
+  user: ApiUser,
+) -> Result<Json<Vec<LicenseInfo>>> {
+  let now = Utc::now().naive_utc();
+  let skew = app.config.clock_skew();
+  let licenses = app.sv().license.by_user(user.tg_user_id, false).await?;
+
+  Ok(Json(
+    licenses
+      .into_iter()
+      .filter(|license| {
+        user.product.as_ref().is_none_or(|p| *p == license.product)
+      })
+      .map(|license| LicenseInfo {
+        key: format!("{}…", license.key.chars().take(8).collect::<String>()),
+        active: !license.is_blocked
+          && !license.is_expired(now, skew)
+          && license.suspended_until(now).is_none(),
+        blocked_until: license.suspended_until(now),
+        product: license.product,
+        license_type: license.license_type,
+        expires_at: license.expires_at,
+        is_blocked: license.is_blocked,
+      })
+      .collect(),
+  ))
+}
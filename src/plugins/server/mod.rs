@@ -1,4 +1,11 @@
+mod admin;
+mod cryptobot;
 mod handlers;
+mod limits;
+mod me;
+mod overlay;
+mod public;
+mod replay;
 mod steam;
 
 use std::{net::SocketAddr, sync::Arc};
@@ -34,13 +41,39 @@ impl super::Plugin for Plugin {
 
     let router = Router::new()
       .route("/health", get(handlers::health))
+      .route("/livez", get(handlers::health))
+      .route("/readyz", get(handlers::ready))
       .route("/api/download", get(handlers::download))
       .route("/api/heartbeat", post(handlers::heartbeat))
+      .route("/api/validate", post(handlers::validate))
+      .route("/api/offline-token", post(handlers::offline_token))
+      .route("/api/client-key", post(handlers::client_key))
+      .route("/api/keys", get(handlers::signing_keys))
+      .route("/api/public/pricing", get(public::pricing))
+      .route("/api/manifest", get(public::manifest))
+      .route("/api/version", get(public::version))
       .route("/api/logout", post(handlers::logout))
       .route("/api/metrics", post(handlers::submit_metrics))
+      .route("/api/metrics/batch", post(handlers::submit_metrics_batch))
+      .route("/api/me/stats", get(me::stats))
+      .route("/api/me/licenses", get(me::licenses))
+      .route("/api/cryptobot/webhook", post(cryptobot::webhook))
+      .route("/overlay/{token}", get(overlay::overlay))
+      .route("/admin/api/users", get(admin::users))
+      .route("/admin/api/licenses", get(admin::licenses))
+      .route("/admin/api/licenses/{key}/ban", post(admin::ban))
+      .route("/admin/api/licenses/{key}/unban", post(admin::unban))
+      .route("/admin/api/licenses/{key}/extend", post(admin::extend))
+      .route("/admin/api/sessions", get(admin::sessions))
+      .route("/admin/api/transactions", get(admin::transactions))
+      .route("/metrics", get(admin::prometheus))
       // TODO: split configuration
       .route("/api/cache/steam/free-games", get(steam::free_games))
       .route("/api/cache/steam/free-items", get(steam::free_items))
+      .layer(axum::middleware::from_fn_with_state(
+        app.clone(),
+        handlers::maintenance,
+      ))
       .layer(
         ServiceBuilder::new()
           .layer(TraceLayer::new_for_http())
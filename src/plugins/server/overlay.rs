@@ -0,0 +1,65 @@
+use std::sync::Arc;
+
+use axum::{
+  extract::{Path, State},
+  response::Html,
+};
+
+use crate::{prelude::*, state::AppState};
+
+/// Seconds between reloads, OBS browser sources keep the page open for hours
+const REFRESH_SECS: u32 = 30;
+
+/// Transparent widget meant for an OBS browser source
+pub async fn overlay(
+  State(app): State<Arc<AppState>>,
+  Path(token): Path<String>,
+) -> Result<Html<String>> {
+  let sv = app.sv();
+  let owner = sv.api_token.authenticate(&token).await?;
+  let user_id = owner.tg_user_id;
+  let stats = sv.stats.display_stats(user_id).await?;
+
+  let sessions: usize = sv
+    .license
+    .by_user(user_id, false)
+    .await?
+    .iter()
+    .filter(|lic| owner.product.as_ref().is_none_or(|p| *p == lic.product))
+    .map(|lic| app.sessions.get(&lic.key).map(|s| s.len()).unwrap_or(0))
+    .sum();
+
+  Ok(Html(format!(
+    r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<meta http-equiv="refresh" content="{refresh}">
+<title>YACSP</title>
+<style>
+  body {{ margin: 0; background: transparent; color: #fff;
+    font: 600 20px/1.4 "Segoe UI", sans-serif;
+    text-shadow: 0 0 4px #000, 0 0 2px #000; }}
+  .grid {{ display: inline-grid; grid-template-columns: auto auto;
+    gap: 2px 14px; padding: 10px 14px; border-radius: 8px;
+    background: rgba(0, 0, 0, 0.35); }}
+  .label {{ opacity: 0.75; }}
+</style>
+</head>
+<body>
+<div class="grid">
+  <span class="label">Weekly XP</span><span>{weekly_xp}</span>
+  <span class="label">Drops</span><span>{drops}</span>
+  <span class="label">Runtime</span><span>{runtime:.1}h</span>
+  <span class="label">Sessions</span><span>{sessions}</span>
+</div>
+</body>
+</html>
+"#,
+    refresh = REFRESH_SECS,
+    weekly_xp = stats.weekly_xp,
+    drops = stats.drops_count,
+    runtime = stats.runtime_hours,
+    sessions = sessions,
+  )))
+}
@@ -0,0 +1,172 @@
+use std::sync::Arc;
+
+use axum::{
+  Json,
+  extract::{Query, State},
+  http::{HeaderMap, header},
+  response::{IntoResponse, Response},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+  entity::{api_usage::ApiKind, build::Channel},
+  prelude::*,
+  state::AppState,
+  sv,
+};
+
+/// Seconds the price list is served from memory and cached by browsers
+const PRICING_TTL: i64 = 60;
+
+/// Plans, list prices and sales for the marketing site
+pub async fn pricing(State(app): State<Arc<AppState>>) -> Result<Response> {
+  let catalog = app.pricing_catalog(TimeDelta::seconds(PRICING_TTL)).await?;
+  let cache = format!("public, max-age={}", PRICING_TTL);
+  Ok(([(header::CACHE_CONTROL, cache)], Json(catalog)).into_response())
+}
+
+#[derive(Deserialize)]
+pub struct ManifestQuery {
+  pub product: Option<String>,
+  /// Channel the client follows, stable by default
+  #[serde(default)]
+  pub channel: Channel,
+}
+
+#[derive(Serialize)]
+pub struct ManifestBuild {
+  pub version: String,
+  pub product: String,
+  pub changelog: Option<String>,
+  /// Hex SHA-256 of the file, to verify a finished download
+  pub sha256: Option<String>,
+  pub channel: Channel,
+  pub platform: String,
+  /// Files of the same version for other platforms
+  pub artifacts: Vec<ManifestArtifact>,
+  pub published_at: DateTime,
+}
+
+#[derive(Serialize)]
+pub struct ManifestArtifact {
+  pub platform: String,
+  pub sha256: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct Manifest {
+  pub builds: Vec<ManifestBuild>,
+}
+
+/// Downloadable builds, newest first
+pub async fn manifest(
+  State(app): State<Arc<AppState>>,
+  Query(query): Query<ManifestQuery>,
+) -> Result<Json<Manifest>> {
+  let sv = app.sv();
+  let mut builds = Vec::new();
+  for build in sv.build.active_on(query.channel).await? {
+    if query.product.as_ref().is_some_and(|p| *p != build.product) {
+      continue;
+    }
+    let artifacts = sv
+      .build
+      .artifacts(&build.version)
+      .await?
+      .into_iter()
+      .map(|artifact| ManifestArtifact {
+        platform: artifact.platform,
+        sha256: artifact.checksum,
+      })
+      .collect();
+    builds.push(ManifestBuild {
+      version: build.version,
+      product: build.product,
+      changelog: build.changelog,
+      sha256: build.checksum,
+      channel: build.channel,
+      platform: build.platform,
+      artifacts,
+      published_at: build.created_at,
+    });
+  }
+  Ok(Json(Manifest { builds }))
+}
+
+#[derive(Deserialize)]
+pub struct VersionQuery {
+  /// Version the client runs
+  pub current: Option<String>,
+  /// `os-arch` of the client, the published platform by default
+  pub platform: Option<String>,
+  pub product: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct VersionInfo {
+  pub version: String,
+  pub update_available: bool,
+  pub changelog: Option<String>,
+  /// Hex SHA-256 of the file for the requested platform
+  pub sha256: Option<String>,
+  /// One-time link, only for requests with a valid `X-License-Key`
+  pub download_url: Option<String>,
+  pub published_at: DateTime,
+}
+
+/// Latest build for the client to self-update to. With a valid license key
+/// in `X-License-Key` the owner's channel and product apply and a download
+/// link is issued.
+pub async fn version(
+  State(app): State<Arc<AppState>>,
+  headers: HeaderMap,
+  Query(query): Query<VersionQuery>,
+) -> Result<Json<VersionInfo>> {
+  let sv = app.sv();
+  let key = headers.get("x-license-key").and_then(|v| v.to_str().ok());
+  let license = match key {
+    Some(key) => sv.license.validate(key.trim()).await.ok(),
+    None => None,
+  };
+  let (product, channel) = match &license {
+    Some(license) => {
+      (license.product.clone(), sv.settings.channel(license.tg_user_id).await?)
+    }
+    None => (
+      query.product.unwrap_or_else(|| sv::product::DEFAULT.to_string()),
+      Channel::Stable,
+    ),
+  };
+
+  // builds are newest first
+  let build = sv
+    .build
+    .active_on(channel)
+    .await?
+    .into_iter()
+    .find(|build| build.product == product)
+    .ok_or(Error::BuildNotFound)?;
+
+  let platform =
+    query.platform.as_deref().and_then(sv::build::normalize_platform);
+  let file = sv.build.file_for(&build, platform).await?;
+  let download_url = match (&license, &file) {
+    (Some(license), Some(_)) => {
+      app.record_api(&license.key, ApiKind::Download, true);
+      let token = app
+        .create_download_token(license.tg_user_id, &build.version, platform)
+        .await?;
+      Some(format!("{}/api/download?token={}", app.config.base_url, token))
+    }
+    _ => None,
+  };
+
+  Ok(Json(VersionInfo {
+    update_available: query.current.as_deref() != Some(build.version.as_str()),
+    sha256: file.and_then(|(_, checksum)| checksum),
+    version: build.version,
+    changelog: build.changelog,
+    download_url,
+    published_at: build.created_at,
+  }))
+}
@@ -0,0 +1,160 @@
+use axum::http::{HeaderMap, StatusCode};
+use hmac::{Hmac, Mac};
+use serde::Deserialize;
+use sha2::Sha256;
+
+use super::limits::Rejection;
+use crate::{
+  prelude::*,
+  state::{AppState, NONCE_WINDOW_SECS},
+};
+
+type HmacSha256 = Hmac<Sha256>;
+
+const NONCE_MIN: usize = 8;
+const NONCE_MAX: usize = 64;
+
+/// Fields a client adds so a captured request can't be sent again. The
+/// signature is the hex HMAC-SHA256 of `timestamp:nonce:key` under the
+/// `client_key` of the license, older clients send none of them.
+#[derive(Debug, Default, Deserialize)]
+pub struct Freshness {
+  #[serde(default)]
+  pub timestamp: Option<i64>,
+  #[serde(default)]
+  pub nonce: Option<String>,
+  #[serde(default)]
+  pub signature: Option<String>,
+}
+
+/// Hex key a client signs the requests of a license with and checks the
+/// answers against, handed out by `/api/client-key`. It is derived from
+/// the server secret, which never has to ship with the client.
+pub fn client_key(secret: &str, key: &str) -> String {
+  let mut mac = HmacSha256::new_from_slice(secret.as_bytes())
+    .expect("HMAC accepts keys of any size");
+  mac.update(format!("client:{}", key).as_bytes());
+  hex::encode(mac.finalize().into_bytes())
+}
+
+fn reject(status: StatusCode, message: &'static str) -> Rejection {
+  Rejection { status, headers: HeaderMap::new(), message }
+}
+
+/// The nonce of a well-formed, recent and correctly signed request, `None`
+/// if the client didn't use the scheme
+fn verify<'f>(
+  secret: &str,
+  key: &str,
+  fresh: &'f Freshness,
+  now: i64,
+) -> Result<Option<&'f str>, Rejection> {
+  let (timestamp, nonce, signature) =
+    match (fresh.timestamp, &fresh.nonce, &fresh.signature) {
+      (None, None, None) => return Ok(None),
+      (Some(timestamp), Some(nonce), Some(signature)) => {
+        (timestamp, nonce, signature)
+      }
+      _ => {
+        return Err(reject(
+          StatusCode::BAD_REQUEST,
+          "timestamp, nonce and signature go together",
+        ));
+      }
+    };
+
+  if !(NONCE_MIN..=NONCE_MAX).contains(&nonce.len()) {
+    return Err(reject(StatusCode::BAD_REQUEST, "Invalid nonce"));
+  }
+  if (now - timestamp).abs() > NONCE_WINDOW_SECS {
+    return Err(reject(
+      StatusCode::UNAUTHORIZED,
+      "Request timestamp is too far from server time",
+    ));
+  }
+
+  let mut mac = HmacSha256::new_from_slice(client_key(secret, key).as_bytes())
+    .expect("HMAC accepts keys of any size");
+  mac.update(format!("{}:{}:{}", timestamp, nonce, key).as_bytes());
+  if !hex::decode(signature).is_ok_and(|sig| mac.verify_slice(&sig).is_ok()) {
+    return Err(reject(StatusCode::UNAUTHORIZED, "Invalid request signature"));
+  }
+  Ok(Some(nonce))
+}
+
+/// Refuse stale, forged or already seen requests of `key`. Unsigned ones
+/// pass unless `Config::require_nonce` is set.
+pub fn check(
+  app: &AppState,
+  key: &str,
+  fresh: &Freshness,
+) -> Result<(), Rejection> {
+  let now = Utc::now().timestamp();
+  match verify(&app.secret, key, fresh, now)? {
+    Some(nonce) if !app.remember_nonce(key, nonce) => {
+      Err(reject(StatusCode::CONFLICT, "Request was already received"))
+    }
+    Some(_) => Ok(()),
+    None if app.config.require_nonce => {
+      Err(reject(StatusCode::UNAUTHORIZED, "Signed request nonce required"))
+    }
+    None => Ok(()),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn signed(timestamp: i64, nonce: &str, key: &str) -> Freshness {
+    let client_key = client_key("secret", key);
+    let mut mac = HmacSha256::new_from_slice(client_key.as_bytes()).unwrap();
+    mac.update(format!("{}:{}:{}", timestamp, nonce, key).as_bytes());
+    Freshness {
+      timestamp: Some(timestamp),
+      nonce: Some(nonce.into()),
+      signature: Some(hex::encode(mac.finalize().into_bytes())),
+    }
+  }
+
+  #[test]
+  fn test_verify() {
+    let now = 1_700_000_000;
+    let status = |fresh: &Freshness, key: &str| {
+      verify("secret", key, fresh, now)
+        .map(|n| n.is_some())
+        .map_err(|r| r.status)
+    };
+
+    assert_eq!(status(&Freshness::default(), "KEY"), Ok(false));
+    assert_eq!(status(&signed(now, "nonce-0001", "KEY"), "KEY"), Ok(true));
+    // clocks a bit apart are fine
+    assert_eq!(status(&signed(now - 60, "nonce-0001", "KEY"), "KEY"), Ok(true));
+
+    let stale = signed(now - NONCE_WINDOW_SECS - 1, "nonce-0001", "KEY");
+    assert_eq!(status(&stale, "KEY"), Err(StatusCode::UNAUTHORIZED));
+    // signed for another key or with the nonce swapped
+    let other = signed(now, "nonce-0001", "OTHER");
+    assert_eq!(status(&other, "KEY"), Err(StatusCode::UNAUTHORIZED));
+    let swapped = Freshness {
+      nonce: Some("nonce-0002".into()),
+      ..signed(now, "nonce-0001", "KEY")
+    };
+    assert_eq!(status(&swapped, "KEY"), Err(StatusCode::UNAUTHORIZED));
+    // the server secret itself is no client key
+    let mut mac = HmacSha256::new_from_slice(b"secret").unwrap();
+    mac.update(format!("{}:nonce-0001:KEY", now).as_bytes());
+    let master = Freshness {
+      signature: Some(hex::encode(mac.finalize().into_bytes())),
+      ..signed(now, "nonce-0001", "KEY")
+    };
+    assert_eq!(status(&master, "KEY"), Err(StatusCode::UNAUTHORIZED));
+
+    let partial = Freshness { signature: None, ..signed(now, "nonce-01", "K") };
+    assert_eq!(status(&partial, "K"), Err(StatusCode::BAD_REQUEST));
+    assert_eq!(
+      status(&signed(now, "short", "K"), "K"),
+      Err(StatusCode::BAD_REQUEST)
+    );
+  }
+}
@@ -55,6 +55,7 @@ impl Plugin for FreeGames {
           let count = games.len();
           info!("Found {} free packages. Updating DB...", count);
 
+          app.writable().await;
           if let Err(e) = app.sv().steam.replace_free_games_cache(games).await {
             error!("Failed to update DB cache: {}", e);
           } else {
@@ -165,6 +166,7 @@ impl Plugin for FreeRewards {
           let count = items.len();
           info!("Found {} free items. Updating DB...", count);
 
+          app.writable().await;
           if let Err(e) = app.sv().steam.replace_free_items_cache(items).await {
             error!("Failed to update DB cache (Items): {}", e);
           } else {
@@ -209,6 +211,7 @@ async fn fetch_sih_rewards() -> anyhow::Result<Vec<free_item::Model>> {
       app_id: i.appid,
       name: i.community_item_data.item_name,
       updated_at: now,
+      manual: false,
     })
     .collect();
 
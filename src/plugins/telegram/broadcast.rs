@@ -0,0 +1,122 @@
+use std::sync::Arc;
+
+use teloxide::{
+  ApiError, RequestError,
+  prelude::*,
+  types::{MessageId, ParseMode},
+};
+use tracing::debug;
+
+use crate::{prelude::*, state::AppState};
+
+/// Pause between messages, Telegram allows about 30 per second
+const SEND_INTERVAL: Duration = Duration::from_millis(40);
+/// Progress message is edited this often
+const PROGRESS_EVERY: usize = 50;
+/// Attempts per user when Telegram asks to slow down
+const MAX_ATTEMPTS: u32 = 3;
+
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Tally {
+  pub sent: usize,
+  /// Blocked the bot or deleted their Telegram account
+  pub unreachable: usize,
+  pub failed: usize,
+}
+
+impl Tally {
+  fn done(&self) -> usize {
+    self.sent + self.unreachable + self.failed
+  }
+}
+
+/// Send `text` to every user in `targets`, reporting progress by editing
+/// `progress` in the admin's chat
+pub async fn run(
+  app: Arc<AppState>,
+  admin: Bot,
+  chat: ChatId,
+  progress: MessageId,
+  targets: Vec<i64>,
+  text: String,
+) -> Tally {
+  let total = targets.len();
+  let mut tally = Tally::default();
+
+  for user_id in targets {
+    let Some((bot, text)) = app.reach(user_id, text.as_str()).await else {
+      tally.unreachable += 1;
+      continue;
+    };
+    match send(&bot, user_id, &text).await {
+      Ok(()) => tally.sent += 1,
+      Err(RequestError::Api(
+        ApiError::BotBlocked
+        | ApiError::ChatNotFound
+        | ApiError::UserDeactivated,
+      )) => tally.unreachable += 1,
+      Err(e) => {
+        debug!("Broadcast to {} failed: {}", user_id, e);
+        tally.failed += 1;
+      }
+    }
+
+    if tally.done() % PROGRESS_EVERY == 0 && tally.done() < total {
+      let _ = admin
+        .edit_message_text(
+          chat,
+          progress,
+          report("📣 Broadcasting", total, &tally),
+        )
+        .parse_mode(ParseMode::Html)
+        .await;
+    }
+    time::sleep(SEND_INTERVAL).await;
+  }
+
+  let _ = admin
+    .edit_message_text(
+      chat,
+      progress,
+      report("✅ Broadcast finished", total, &tally),
+    )
+    .parse_mode(ParseMode::Html)
+    .await;
+  info!(
+    "Broadcast finished: {} sent, {} unreachable, {} failed",
+    tally.sent, tally.unreachable, tally.failed
+  );
+  tally
+}
+
+async fn send(bot: &Bot, user_id: i64, text: &str) -> ResponseResult<()> {
+  let mut attempt = 1;
+  loop {
+    let sent =
+      bot.send_message(ChatId(user_id), text).parse_mode(ParseMode::Html).await;
+    match sent {
+      Err(RequestError::RetryAfter(wait)) if attempt < MAX_ATTEMPTS => {
+        warn!("Broadcast throttled by Telegram for {}s", wait.seconds());
+        time::sleep(wait.duration()).await;
+        attempt += 1;
+      }
+      other => return other.map(|_| ()),
+    }
+  }
+}
+
+pub fn report(title: &str, total: usize, tally: &Tally) -> String {
+  format!(
+    "<b>{}</b>\n\n\
+    Progress: {}/{}\n\
+    ✅ Sent: {}\n\
+    🚫 Unreachable: {}\n\
+    ❌ Failed: {}",
+    title,
+    tally.done(),
+    total,
+    tally.sent,
+    tally.unreachable,
+    tally.failed
+  )
+}
@@ -1,19 +1,66 @@
-use std::{path::Path, sync::Arc, time::Duration};
+use std::{collections::HashSet, path::Path, sync::Arc};
 
 use reqwest::Url;
 use teloxide::{
   prelude::*,
-  types::{InlineKeyboardButton, InlineKeyboardMarkup},
+  types::{
+    FileId, InlineKeyboardButton, InlineKeyboardMarkup, InputFile, ParseMode,
+  },
+  utils::html,
 };
 
-use super::ReplyBot;
+use super::{
+  ReplyBot, gift,
+  i18n::{self, Lang, T},
+  reconcile, review, sharing, support,
+};
 use crate::{
-  entity::user::UserRole,
+  chart,
+  entity::{
+    TransactionType, build, build_artifact, faq, freebie_claim::FreebieKind,
+    instance_stats, license, product, promo_asset::PromoAssetKind,
+    rating::RatingKind, terms, user::UserRole,
+  },
   prelude::*,
+  qr::QrCode,
   state::{AppState, Services},
-  sv::referral::{NANO_USDT, ReferralStats},
+  sv::{
+    account::RefundPolicy,
+    admin_alert::Urgency,
+    eligibility::{Purchase, Rules},
+    goal,
+    pricing::{Plan, Quote, VOLUME_TIERS, extension_plans, volume_percent},
+    product::DEFAULT as DEFAULT_PRODUCT,
+    promo_kit,
+    referral::{NANO_USDT, ReferralStats},
+    review::Action,
+    stats::{DailyGain, INSTANCE_SILENT_MINS, MetaStats},
+  },
 };
 
+/// Pixels per QR module, a typical invite link comes out at 370-450px
+const QR_SCALE: usize = 10;
+
+/// Telegram caps callback data at 64 bytes, longer data is forged
+const DATA_MAX: usize = 64;
+
+/// Why callback data was rejected
+#[derive(thiserror::Error, Debug, Clone, PartialEq)]
+pub enum BadCallback {
+  #[error("data is {0} bytes long")]
+  TooLong(usize),
+  #[error("unknown callback `{0}`")]
+  Unknown(String),
+  /// Known callback with a missing or invalid argument
+  #[error("malformed `{0}` callback")]
+  Malformed(String),
+}
+
+/// Free-text argument: present and without control characters
+fn is_text(arg: &str) -> bool {
+  !arg.is_empty() && !arg.chars().any(char::is_control)
+}
+
 /// Callback data enum - provides type-safe callback handling
 #[derive(Debug, Clone, PartialEq)]
 pub enum Callback {
@@ -22,11 +69,14 @@ pub enum Callback {
   Trial,
   Download,
   DownloadVersion(String),
+  DownloadArtifact { version: String, platform: String },
   Buy,
-  BuyPlan(String),
+  BuyProduct(String),
+  BuyPlan { product: String, plan: String },
   ExtendLicense,
   ExtendLicenseKey(String),
   ExtendPlan { key: String, plan: String },
+  AutoRenew(String),
   AddFunds,
   PayCryptoAmount(String),
   PayCustomAmount,
@@ -36,6 +86,43 @@ pub enum Callback {
   SetRef,
   AboutReferral,
   MyReferrals,
+  PromoKit,
+  PromoQr,
+  PromoBanner(i32),
+  LicenseSecurity(String),
+  RegenerateKey(String),
+  RegenerateKeyConfirm(String),
+  Transfer { key: String, to: i64 },
+  Faq,
+  FaqEntry(i32),
+  TicketActions(i32),
+  TicketCanned(i32),
+  TicketCannedSend { ticket: i32, name: String },
+  TicketClose(i32),
+  Review { id: i32, approve: bool },
+  ReconCredit(i64),
+  ReconReverse(i32),
+  KickSession { key: String, tag: String },
+  BanKey(String),
+  Rate { id: i32, score: i32 },
+  Inbox,
+  InboxItem(i32),
+  InboxReadAll,
+  History(u64),
+  NotMe(i32),
+  SecurityAlertsOff,
+  Instances,
+  StatsChart,
+  ApiToken,
+  ApiTokenNew(Option<String>),
+  ApiTokenRevoke,
+  DeleteAccountConfirm,
+  DeleteAccountCancel,
+  AcceptTerms(i32),
+  Language,
+  SetLanguage(String),
+  Freebies,
+  FreebieClaim { kind: FreebieKind, id: i32 },
   Back,
 }
 
@@ -47,13 +134,20 @@ impl Callback {
       Callback::Trial => "trial".to_string(),
       Callback::Download => "download".to_string(),
       Callback::DownloadVersion(v) => format!("dl_ver:{}", v),
+      Callback::DownloadArtifact { version, platform } => {
+        format!("dl_art:{}:{}", platform, version)
+      }
       Callback::Buy => "buy".to_string(),
-      Callback::BuyPlan(plan) => format!("buy_plan:{}", plan),
+      Callback::BuyProduct(product) => format!("buy_prod:{}", product),
+      Callback::BuyPlan { product, plan } => {
+        format!("buy_plan:{}:{}", product, plan)
+      }
       Callback::ExtendLicense => "extend_lic".to_string(),
       Callback::ExtendLicenseKey(key) => format!("ext_key:{}", key),
       Callback::ExtendPlan { key, plan } => {
         format!("ext_plan:{}:{}", key, plan)
       }
+      Callback::AutoRenew(key) => format!("renew:{}", key),
       Callback::AddFunds => "add_funds".to_string(),
       Callback::PayCryptoAmount(a) => format!("pay_amt:{}", a),
       Callback::PayCustomAmount => "pay_custom".to_string(),
@@ -63,111 +157,403 @@ impl Callback {
       Callback::SetRef => "set_ref".to_string(),
       Callback::AboutReferral => "about_ref".to_string(),
       Callback::MyReferrals => "my_refs".to_string(),
+      Callback::PromoKit => "promo_kit".to_string(),
+      Callback::PromoQr => "promo_qr".to_string(),
+      Callback::PromoBanner(id) => format!("promo_ban:{}", id),
+      Callback::LicenseSecurity(key) => format!("lic_sec:{}", key),
+      Callback::RegenerateKey(key) => format!("regen:{}", key),
+      Callback::RegenerateKeyConfirm(key) => format!("regen_ok:{}", key),
+      Callback::Transfer { key, to } => format!("xfer:{}:{}", to, key),
+      Callback::Faq => "faq".to_string(),
+      Callback::FaqEntry(id) => format!("faq_q:{}", id),
+      Callback::TicketActions(id) => format!("tk:{}", id),
+      Callback::TicketCanned(id) => format!("tk_can:{}", id),
+      Callback::TicketCannedSend { ticket, name } => {
+        format!("tk_cs:{}:{}", ticket, name)
+      }
+      Callback::TicketClose(id) => format!("tk_close:{}", id),
+      Callback::Review { id, approve } => {
+        format!("rv:{}:{}", id, u8::from(*approve))
+      }
+      Callback::ReconCredit(invoice) => format!("rc_cr:{}", invoice),
+      Callback::ReconReverse(tx) => format!("rc_rv:{}", tx),
+      Callback::KickSession { key, tag } => format!("kick:{}:{}", tag, key),
+      Callback::BanKey(key) => format!("ban:{}", key),
+      Callback::Rate { id, score } => format!("rate:{}:{}", id, score),
+      Callback::Inbox => "inbox".to_string(),
+      Callback::InboxItem(id) => format!("inbox:{}", id),
+      Callback::InboxReadAll => "inbox_all".to_string(),
+      Callback::History(page) => format!("hist:{}", page),
+      Callback::NotMe(id) => format!("notme:{}", id),
+      Callback::SecurityAlertsOff => "sec_off".to_string(),
+      Callback::Instances => "instances".to_string(),
+      Callback::StatsChart => "chart".to_string(),
+      Callback::ApiToken => "api_tok".to_string(),
+      Callback::ApiTokenNew(None) => "api_new".to_string(),
+      Callback::ApiTokenNew(Some(product)) => format!("api_new:{}", product),
+      Callback::ApiTokenRevoke => "api_del".to_string(),
+      Callback::DeleteAccountConfirm => "del_acc_ok".to_string(),
+      Callback::DeleteAccountCancel => "del_acc_no".to_string(),
+      Callback::AcceptTerms(version) => format!("tos_ok:{}", version),
+      Callback::Language => "lang".to_string(),
+      Callback::Freebies => "freebies".to_string(),
+      Callback::FreebieClaim { kind: FreebieKind::Game, id } => {
+        format!("fb:g:{}", id)
+      }
+      Callback::FreebieClaim { kind: FreebieKind::Item, id } => {
+        format!("fb:i:{}", id)
+      }
+      Callback::SetLanguage(code) => format!("lang:{}", code),
       Callback::Back => "back".to_string(),
     }
   }
 
-  pub fn from_data(data: &str) -> Option<Self> {
-    match data {
-      "profile" => Some(Callback::Profile),
-      "license" => Some(Callback::License),
-      "trial" => Some(Callback::Trial),
-      "download" => Some(Callback::Download),
-      "buy" => Some(Callback::Buy),
-      "extend_lic" => Some(Callback::ExtendLicense),
-      "add_funds" => Some(Callback::AddFunds),
-      "pay_custom" => Some(Callback::PayCustomAmount),
-      "check_pay" => Some(Callback::CheckPayments),
-      "pay_man" => Some(Callback::PayManual),
-      "have_lic" => Some(Callback::HaveLicense),
-      "set_ref" => Some(Callback::SetRef),
-      "about_ref" => Some(Callback::AboutReferral),
-      "my_refs" => Some(Callback::MyReferrals),
-      "back" => Some(Callback::Back),
-      _ if data.starts_with("dl_ver:") => {
-        Some(Callback::DownloadVersion(data[7..].to_string()))
-      }
-      _ if data.starts_with("pay_amt:") => {
-        Some(Callback::PayCryptoAmount(data[8..].to_string()))
+  /// Decode data of one of our buttons. Anyone can send arbitrary data with
+  /// a crafted client, so nothing here trusts it: lengths are capped,
+  /// arguments must parse and free text may not carry control characters.
+  pub fn parse(data: &str) -> std::result::Result<Self, BadCallback> {
+    if data.len() > DATA_MAX {
+      return Err(BadCallback::TooLong(data.len()));
+    }
+    let (tag, arg) = match data.split_once(':') {
+      Some((tag, arg)) => (tag, Some(arg)),
+      None => (data, None),
+    };
+
+    let malformed = || BadCallback::Malformed(tag.to_string());
+    let text = |arg: Option<&str>| {
+      arg.filter(|arg| is_text(arg)).map(str::to_string).ok_or_else(malformed)
+    };
+    let id = |arg: Option<&str>| {
+      arg.and_then(|arg| arg.parse::<i32>().ok()).ok_or_else(malformed)
+    };
+    let pair = || arg.and_then(|arg| arg.split_once(':')).ok_or_else(malformed);
+
+    let callback = match (tag, arg) {
+      ("profile", None) => Callback::Profile,
+      ("license", None) => Callback::License,
+      ("trial", None) => Callback::Trial,
+      ("download", None) => Callback::Download,
+      ("buy", None) => Callback::Buy,
+      ("extend_lic", None) => Callback::ExtendLicense,
+      ("add_funds", None) => Callback::AddFunds,
+      ("pay_custom", None) => Callback::PayCustomAmount,
+      ("check_pay", None) => Callback::CheckPayments,
+      ("pay_man", None) => Callback::PayManual,
+      ("have_lic", None) => Callback::HaveLicense,
+      ("set_ref", None) => Callback::SetRef,
+      ("about_ref", None) => Callback::AboutReferral,
+      ("my_refs", None) => Callback::MyReferrals,
+      ("promo_kit", None) => Callback::PromoKit,
+      ("promo_qr", None) => Callback::PromoQr,
+      ("faq", None) => Callback::Faq,
+      ("inbox", None) => Callback::Inbox,
+      ("inbox_all", None) => Callback::InboxReadAll,
+      ("instances", None) => Callback::Instances,
+      ("chart", None) => Callback::StatsChart,
+      ("sec_off", None) => Callback::SecurityAlertsOff,
+      ("api_tok", None) => Callback::ApiToken,
+      ("api_new", None) => Callback::ApiTokenNew(None),
+      ("api_del", None) => Callback::ApiTokenRevoke,
+      ("del_acc_ok", None) => Callback::DeleteAccountConfirm,
+      ("del_acc_no", None) => Callback::DeleteAccountCancel,
+      ("back", None) => Callback::Back,
+      ("lang", None) => Callback::Language,
+      ("freebies", None) => Callback::Freebies,
+      ("dl_ver", _) => Callback::DownloadVersion(text(arg)?),
+      ("dl_art", _) => {
+        let (platform, version) = pair()?;
+        Callback::DownloadArtifact {
+          version: text(Some(version))?,
+          platform: text(Some(platform))?,
+        }
       }
-      _ if data.starts_with("buy_plan:") => {
-        Some(Callback::BuyPlan(data[9..].to_string()))
+      ("pay_amt", _) => Callback::PayCryptoAmount(text(arg)?),
+      ("buy_prod", _) => Callback::BuyProduct(text(arg)?),
+      ("buy_plan", _) => {
+        // buttons sent before products existed carry only the plan
+        let plan = text(arg)?;
+        let (product, plan) =
+          plan.split_once(':').unwrap_or((DEFAULT_PRODUCT, &plan));
+        Callback::BuyPlan {
+          product: text(Some(product))?,
+          plan: text(Some(plan))?,
+        }
       }
-      _ if data.starts_with("ext_key:") => {
-        Some(Callback::ExtendLicenseKey(data[8..].to_string()))
+      ("lic_sec", _) => Callback::LicenseSecurity(text(arg)?),
+      ("regen_ok", _) => Callback::RegenerateKeyConfirm(text(arg)?),
+      ("regen", _) => Callback::RegenerateKey(text(arg)?),
+      ("xfer", _) => {
+        let (to, key) = pair()?;
+        Callback::Transfer {
+          key: text(Some(key))?,
+          to: to.parse().map_err(|_| malformed())?,
+        }
       }
-      _ if data.starts_with("ext_plan:") => {
-        let parts: Vec<&str> = data[9..].splitn(2, ':').collect();
-        if parts.len() == 2 {
-          Some(Callback::ExtendPlan {
-            key: parts[0].to_string(),
-            plan: parts[1].to_string(),
-          })
-        } else {
-          None
+      ("faq_q", _) => Callback::FaqEntry(id(arg)?),
+      ("tk", _) => Callback::TicketActions(id(arg)?),
+      ("tk_can", _) => Callback::TicketCanned(id(arg)?),
+      ("tk_cs", _) => {
+        let (ticket, name) = pair()?;
+        Callback::TicketCannedSend {
+          ticket: id(Some(ticket))?,
+          name: text(Some(name))?,
         }
       }
-      _ => None,
-    }
+      ("tk_close", _) => Callback::TicketClose(id(arg)?),
+      ("rv", _) => {
+        let (review, approve) = pair()?;
+        let approve = match approve {
+          "1" => true,
+          "0" => false,
+          _ => return Err(malformed()),
+        };
+        Callback::Review { id: id(Some(review))?, approve }
+      }
+      ("rc_cr", _) => Callback::ReconCredit(
+        arg.and_then(|arg| arg.parse().ok()).ok_or_else(malformed)?,
+      ),
+      ("rc_rv", _) => Callback::ReconReverse(id(arg)?),
+      ("kick", _) => {
+        let (tag, key) = pair()?;
+        Callback::KickSession { key: text(Some(key))?, tag: text(Some(tag))? }
+      }
+      ("ban", _) => Callback::BanKey(text(arg)?),
+      ("hist", _) => Callback::History(
+        arg.and_then(|arg| arg.parse().ok()).ok_or_else(malformed)?,
+      ),
+      ("notme", _) => Callback::NotMe(id(arg)?),
+      ("inbox", _) => Callback::InboxItem(id(arg)?),
+      ("api_new", _) => Callback::ApiTokenNew(Some(text(arg)?)),
+      ("fb", _) => {
+        let (kind, claim) = pair()?;
+        let kind = match kind {
+          "g" => FreebieKind::Game,
+          "i" => FreebieKind::Item,
+          _ => return Err(malformed()),
+        };
+        Callback::FreebieClaim { kind, id: id(Some(claim))? }
+      }
+      ("lang", _) => Callback::SetLanguage(text(arg)?),
+      ("promo_ban", _) => Callback::PromoBanner(id(arg)?),
+      ("tos_ok", _) => Callback::AcceptTerms(id(arg)?),
+      ("rate", _) => {
+        let (rating, score) = pair()?;
+        Callback::Rate { id: id(Some(rating))?, score: id(Some(score))? }
+      }
+      ("renew", _) => Callback::AutoRenew(text(arg)?),
+      ("ext_key", _) => Callback::ExtendLicenseKey(text(arg)?),
+      ("ext_plan", _) => {
+        let (key, plan) = pair()?;
+        Callback::ExtendPlan { key: text(Some(key))?, plan: text(Some(plan))? }
+      }
+      _ => return Err(BadCallback::Unknown(tag.chars().take(16).collect())),
+    };
+    Ok(callback)
+  }
+
+  /// Callbacks that charge the user, gated behind the current terms
+  fn is_purchase(&self) -> bool {
+    matches!(
+      self,
+      Callback::BuyPlan { .. }
+        | Callback::ExtendPlan { .. }
+        | Callback::PayCryptoAmount(_)
+    )
+  }
+
+  /// Variant name without its data, for usage metrics
+  pub fn name(&self) -> String {
+    let debug = format!("{:?}", self);
+    let end = debug.find(['(', ' ', '{']).unwrap_or(debug.len());
+    debug[..end].to_string()
   }
 }
 
-pub fn main_menu(is_promo: bool) -> InlineKeyboardMarkup {
+/// Support username of the main bot
+pub const SUPPORT: &str = "y_a_c_s_p";
+
+/// Welcome message and main menu, branded on storefront bots
+pub fn home(bot: &ReplyBot, is_promo: bool) -> (String, InlineKeyboardMarkup) {
+  let lang = bot.lang;
+  let Some(brand) = &bot.brand else {
+    return (lang.t(T::Welcome).to_string(), main_menu(lang, is_promo));
+  };
+  let text = brand.welcome.clone().unwrap_or_else(|| {
+    i18n::fill(
+      lang.t(T::BrandWelcome),
+      &[
+        ("title", html::escape(&brand.title)),
+        ("support", brand.support.clone()),
+      ],
+    )
+  });
+  // the free trial is a key for the main product
+  (text, main_menu(lang, false))
+}
+
+/// Promo name used for the free trial week
+pub const TRIAL_PROMO: &str = "first_promo";
+
+pub fn main_menu(lang: Lang, is_promo: bool) -> InlineKeyboardMarkup {
+  let button = |text, callback: Callback| {
+    InlineKeyboardButton::callback(lang.t(text), callback.to_data())
+  };
   let mut rows = vec![
-    vec![InlineKeyboardButton::callback(
-      "👤 My Profile",
-      Callback::Profile.to_data(),
-    )],
-    vec![InlineKeyboardButton::callback(
-      "🔑 My License",
-      Callback::License.to_data(),
-    )],
     vec![
-      InlineKeyboardButton::callback("💳 Buy License", Callback::Buy.to_data()),
-      InlineKeyboardButton::callback(
-        "💵 Add Funds",
-        Callback::AddFunds.to_data(),
-      ),
+      button(T::MenuProfile, Callback::Profile),
+      button(T::MenuInbox, Callback::Inbox),
+    ],
+    vec![button(T::MenuLicense, Callback::License)],
+    vec![
+      button(T::MenuBuy, Callback::Buy),
+      button(T::MenuFunds, Callback::AddFunds),
+    ],
+    vec![
+      button(T::MenuDownload, Callback::Download),
+      button(T::MenuFaq, Callback::Faq),
     ],
-    vec![InlineKeyboardButton::callback(
-      "📥 Download Panel",
-      Callback::Download.to_data(),
-    )],
   ];
 
   if is_promo {
-    rows.push(vec![InlineKeyboardButton::callback(
-      "🆓 Get Free Trial",
-      Callback::Trial.to_data(),
-    )]);
+    rows.push(vec![button(T::MenuTrial, Callback::Trial)]);
   }
+  rows.push(vec![
+    button(T::MenuFreebies, Callback::Freebies),
+    InlineKeyboardButton::callback(
+      lang.t(T::MenuGift),
+      gift::Action::Redeem.to_data(),
+    ),
+  ]);
+  rows.push(vec![button(T::MenuLanguage, Callback::Language)]);
 
   InlineKeyboardMarkup::new(rows)
 }
 
-fn back_keyboard() -> InlineKeyboardMarkup {
+/// Language buttons shown by /lang and the main menu
+pub fn language_picker(lang: Lang) -> (String, InlineKeyboardMarkup) {
+  let buttons = Lang::ALL
+    .into_iter()
+    .map(|option| {
+      InlineKeyboardButton::callback(
+        option.label(),
+        Callback::SetLanguage(option.code().to_string()).to_data(),
+      )
+    })
+    .collect();
+  let kb = InlineKeyboardMarkup::new(vec![
+    buttons,
+    vec![InlineKeyboardButton::callback(
+      lang.t(T::BackToMenu),
+      Callback::Back.to_data(),
+    )],
+  ]);
+  (lang.t(T::LangPrompt).to_string(), kb)
+}
+
+fn back_keyboard(lang: Lang) -> InlineKeyboardMarkup {
   InlineKeyboardMarkup::new(vec![vec![InlineKeyboardButton::callback(
-    "« Back to Menu",
+    lang.t(T::BackToMenu),
     Callback::Back.to_data(),
   )]])
 }
 
+/// Telegram caps inline keyboards at 100 buttons
+const FAQ_BROWSE_LIMIT: usize = 50;
+
+/// List of all FAQ questions as buttons
+pub fn faq_browse(
+  lang: Lang,
+  entries: &[faq::Model],
+  support: &str,
+) -> (String, InlineKeyboardMarkup) {
+  if entries.is_empty() {
+    let text =
+      i18n::fill(lang.t(T::FaqEmpty), &[("support", support.to_string())]);
+    return (text, back_keyboard(lang));
+  }
+
+  let mut rows: Vec<_> = entries
+    .iter()
+    .take(FAQ_BROWSE_LIMIT)
+    .map(|entry| {
+      vec![InlineKeyboardButton::callback(
+        entry.question.clone(),
+        Callback::FaqEntry(entry.id).to_data(),
+      )]
+    })
+    .collect();
+  rows.push(vec![InlineKeyboardButton::callback(
+    lang.t(T::BackToMenu),
+    Callback::Back.to_data(),
+  )]);
+
+  (lang.t(T::FaqBrowse).to_string(), InlineKeyboardMarkup::new(rows))
+}
+
+/// A single answer with buttons to related questions
+pub fn faq_answer(
+  lang: Lang,
+  entry: &faq::Model,
+  related: &[faq::Model],
+  support: &str,
+) -> (String, InlineKeyboardMarkup) {
+  let text = format!(
+    "❓ <b>{}</b>\n\n{}\n\n<i>{}</i>",
+    html::escape(&entry.question),
+    entry.answer,
+    i18n::fill(lang.t(T::FaqNotHelpful), &[("support", support.to_string())])
+  );
+
+  let mut rows: Vec<_> = related
+    .iter()
+    .map(|entry| {
+      vec![InlineKeyboardButton::callback(
+        format!("🔎 {}", entry.question),
+        Callback::FaqEntry(entry.id).to_data(),
+      )]
+    })
+    .collect();
+  rows.push(vec![
+    InlineKeyboardButton::callback(lang.t(T::FaqAll), Callback::Faq.to_data()),
+    InlineKeyboardButton::callback(
+      lang.t(T::BackMenu),
+      Callback::Back.to_data(),
+    ),
+  ]);
+
+  (text, InlineKeyboardMarkup::new(rows))
+}
+
 /// Format balance in USDT (stored as nanoUSDT internally)
-fn format_usdt(nano_usdt: i64) -> String {
+pub fn format_usdt(nano_usdt: i64) -> String {
   format!("{:.2} USDT", nano_usdt as f64 / NANO_USDT as f64)
 }
 
 pub async fn handle(
   app: Arc<AppState>,
-  bot: ReplyBot,
+  mut bot: ReplyBot,
   data: &str,
 ) -> ResponseResult<()> {
   let sv = app.sv();
+  bot.localize(&sv).await;
 
-  let Some(callback) = Callback::from_data(data) else {
-    return Ok(());
+  let callback = match Callback::parse(data) {
+    Ok(callback) => callback,
+    Err(err) => {
+      warn!("Ignoring callback from {}: {}", bot.user_id, err);
+      return Ok(());
+    }
   };
 
+  if callback.is_purchase()
+    && let Ok(Some(terms)) = sv.terms.pending(bot.user_id).await
+  {
+    let (text, kb) = terms_screen(bot.lang, &terms);
+    bot.edit_with_keyboard(text, kb).await?;
+    return Ok(());
+  }
+
   match callback {
     Callback::Profile => {
       handle_profile_view(&sv, &bot).await?;
@@ -185,15 +571,23 @@ pub async fn handle(
         handle_download(&sv, &bot, &app).await?;
       } else {
         bot
-          .edit_with_keyboard("You have no active license!", back_keyboard())
+          .edit_with_keyboard(
+            bot.lang.t(T::NoActiveLicense),
+            back_keyboard(bot.lang),
+          )
           .await?;
       }
     }
     Callback::Buy => {
-      handle_buy_menu(&sv, &bot).await?;
+      let product = bot.product().map(str::to_string);
+      handle_buy_menu(&sv, &bot, product).await?;
+    }
+    Callback::BuyProduct(product) => {
+      handle_buy_menu(&sv, &bot, Some(product)).await?;
     }
-    Callback::BuyPlan(plan) => {
-      handle_buy_plan(&sv, &bot, &plan).await?;
+    Callback::BuyPlan { product, plan } => {
+      let rules = &app.config.purchase_rules;
+      handle_buy_plan(&app, &sv, &bot, &product, &plan, rules).await?;
     }
     Callback::ExtendLicense => {
       handle_extend_license_menu(&sv, &bot).await?;
@@ -201,8 +595,17 @@ pub async fn handle(
     Callback::ExtendLicenseKey(key) => {
       handle_extend_license_key(&sv, &bot, &key).await?;
     }
+    Callback::AutoRenew(key) => {
+      if let Ok(Some(license)) = sv.license.by_key(&key).await
+        && license.tg_user_id == bot.user_id
+      {
+        let _ = sv.license.set_auto_renew(&key, !license.auto_renew).await;
+      }
+      handle_extend_license_key(&sv, &bot, &key).await?;
+    }
     Callback::ExtendPlan { key, plan } => {
-      handle_extend_plan(&sv, &bot, &key, &plan).await?;
+      let rules = &app.config.purchase_rules;
+      handle_extend_plan(&app, &sv, &bot, &key, &plan, rules).await?;
     }
     Callback::AddFunds => {
       handle_add_funds(&sv, &bot, &app).await?;
@@ -211,15 +614,12 @@ pub async fn handle(
       handle_pay_crypto_amount(&sv, &bot, &app, &amount).await?;
     }
     Callback::PayCustomAmount => {
-      let text = "💵 <b>Custom Amount</b>\n\n\
-        To add a custom amount to your balance, use the command:\n\n\
-        <code>/fund AMOUNT</code>\n\n\
-        Examples:\n\
-        • <code>/fund 5</code> - Add 5 USDT\n\
-        • <code>/fund 15.5</code> - Add 15.5 USDT\n\n\
-        <i>Minimum deposit: 1 USDT</i>";
-
-      bot.edit_with_keyboard(text, back_keyboard()).await?;
+      bot
+        .edit_with_keyboard(
+          bot.lang.t(T::CustomAmount),
+          back_keyboard(bot.lang),
+        )
+        .await?;
     }
     Callback::CheckPayments => {
       handle_check_payments(&sv, &bot, &app).await?;
@@ -228,160 +628,1089 @@ pub async fn handle(
       let user = sv.user.by_id(bot.user_id).await.ok().flatten();
       let current_ref = user.as_ref().and_then(|u| u.referred_by);
 
-      let current_ref_display = if let Some(ref_id) = current_ref {
-        sv.referral
-          .display_code(ref_id)
-          .await
-          .map(|code| format!("<code>{}</code>", code))
-          .unwrap_or_else(|| "None".to_string())
-      } else {
-        "None".to_string()
-      };
+      let current_ref_display = match current_ref {
+        Some(ref_id) => sv.referral.display_code(ref_id).await,
+        None => None,
+      }
+      .map(|code| format!("<code>{}</code>", code))
+      .unwrap_or_else(|| bot.lang.t(T::NoneSet).to_string());
 
-      let text = format!(
-        "🔗 <b>Set Referral Code</b>\n\n\
-        A referral code can be a creator's custom code or a friend's User ID.\n\
-        When you have a referral code from a creator, you get a discount on purchases!\n\n\
-        <b>Your current referral code:</b> {}\n\n\
-        <b>To set/change:</b> <code>/ref CODE</code>\n\
-        <b>To clear:</b> <code>/ref clear</code>",
-        current_ref_display
+      let text = i18n::fill(
+        bot.lang.t(T::SetRefCode),
+        &[("current", current_ref_display)],
       );
-      bot.edit_with_keyboard(text, back_keyboard()).await?;
+      bot.edit_with_keyboard(text, back_keyboard(bot.lang)).await?;
     }
     Callback::PayManual => {
-      let text = "👤 <b>Manual Purchase</b>\n\n\
-        To purchase a license via USDT or other methods, please contact our support:\n\n\
-        👉 @y_a_c_s_p\n\n\
-        <i>Send a message with \"I want to buy license\"</i>";
+      let text = i18n::fill(
+        bot.lang.t(T::ManualPurchase),
+        &[("support", bot.support().to_string())],
+      );
 
       let kb = InlineKeyboardMarkup::new(vec![
         vec![InlineKeyboardButton::url(
-          "Open Chat with Support",
-          Url::parse("https://t.me/y_a_c_s_p").expect("invalid link, what???"),
+          bot.lang.t(T::OpenSupportChat),
+          Url::parse(&format!("https://t.me/{}", bot.support()))
+            .expect("invalid link, what???"),
+        )],
+        vec![InlineKeyboardButton::callback(
+          bot.lang.t(T::Back),
+          Callback::Buy.to_data(),
         )],
-        vec![InlineKeyboardButton::callback("« Back", Callback::Buy.to_data())],
       ]);
 
       bot.edit_with_keyboard(text, kb).await?;
     }
+    Callback::Faq => {
+      let entries = sv.faq.all().await.unwrap_or_default();
+      let (text, kb) = faq_browse(bot.lang, &entries, bot.support());
+      bot.edit_with_keyboard(text, kb).await?;
+    }
+    Callback::FaqEntry(id) => {
+      if let Ok(Some(entry)) = sv.faq.by_id(id).await {
+        let (text, kb) = faq_answer(bot.lang, &entry, &[], bot.support());
+        bot.edit_with_keyboard(text, kb).await?;
+      } else {
+        let entries = sv.faq.all().await.unwrap_or_default();
+        let (text, kb) = faq_browse(bot.lang, &entries, bot.support());
+        bot.edit_with_keyboard(text, kb).await?;
+      }
+    }
+    Callback::TicketActions(id) if app.admins.contains(&bot.user_id) => {
+      bot.edit_keyboard(support::ticket_keyboard(id)).await?;
+    }
+    Callback::TicketCanned(id) if app.admins.contains(&bot.user_id) => {
+      let responses = sv.canned.all().await.unwrap_or_default();
+      if responses.is_empty() {
+        bot
+          .reply_html(
+            "📭 No canned responses yet. Add one with \
+            <code>/canned add name text</code>",
+          )
+          .await?;
+        return Ok(());
+      }
+
+      let mut rows: Vec<_> = responses
+        .into_iter()
+        .map(|response| {
+          vec![InlineKeyboardButton::callback(
+            format!("📋 {}", response.name),
+            Callback::TicketCannedSend { ticket: id, name: response.name }
+              .to_data(),
+          )]
+        })
+        .collect();
+      rows.push(vec![InlineKeyboardButton::callback(
+        "« Cancel",
+        Callback::TicketActions(id).to_data(),
+      )]);
+      bot.edit_keyboard(InlineKeyboardMarkup::new(rows)).await?;
+    }
+    Callback::TicketCannedSend { ticket, name }
+      if app.admins.contains(&bot.user_id) =>
+    {
+      match support::send_canned(&app, ticket, bot.user_id, &name).await {
+        Ok(_) => {
+          bot.edit_keyboard(support::ticket_keyboard(ticket)).await?;
+          bot
+            .reply_html(format!(
+              "✅ Sent <b>{}</b> to ticket #{}",
+              html::escape(&name),
+              ticket
+            ))
+            .await?;
+        }
+        Err(e) => {
+          bot.reply_html(format!("❌ {}", e.user_message())).await?;
+        }
+      }
+    }
+    Callback::TicketClose(id) if app.admins.contains(&bot.user_id) => {
+      match support::close_ticket(&app, id).await {
+        Ok(_) => {
+          bot.edit_keyboard(InlineKeyboardMarkup::default()).await?;
+          bot.reply_html(format!("✅ Ticket #{} closed", id)).await?;
+        }
+        Err(e) => {
+          bot.reply_html(format!("❌ {}", e.user_message())).await?;
+        }
+      }
+    }
+    Callback::Review { id, approve } if app.admins.contains(&bot.user_id) => {
+      review::decide(&app, &bot, id, approve).await?;
+    }
+    Callback::ReconCredit(invoice) if app.admins.contains(&bot.user_id) => {
+      reconcile::credit(&app, &bot, invoice).await?;
+    }
+    Callback::ReconReverse(tx) if app.admins.contains(&bot.user_id) => {
+      reconcile::reverse(&app, &bot, tx).await?;
+    }
+    Callback::KickSession { key, tag } if app.admins.contains(&bot.user_id) => {
+      let text = match app.kick_session(&key, &tag) {
+        Some(session_id) => {
+          info!(
+            "Admin {} kicked session {} of {}",
+            bot.user_id, session_id, key
+          );
+          format!(
+            "✅ Session <code>{}…</code> of <code>{}</code> kicked",
+            html::escape(&session_id.chars().take(8).collect::<String>()),
+            key
+          )
+        }
+        None => "ℹ️ That session has already ended".to_string(),
+      };
+      bot.reply_html(text).await?;
+    }
+    Callback::BanKey(key) if app.admins.contains(&bot.user_id) => {
+      sharing::ban(&app, &bot, &key).await?;
+    }
+    Callback::TicketActions(_)
+    | Callback::TicketCanned(_)
+    | Callback::TicketCannedSend { .. }
+    | Callback::TicketClose(_)
+    | Callback::Review { .. }
+    | Callback::ReconCredit(_)
+    | Callback::ReconReverse(_)
+    | Callback::KickSession { .. }
+    | Callback::BanKey(_) => {}
+    Callback::Rate { id, score } => {
+      match sv.rating.rate(id, bot.user_id, score).await {
+        Ok(rating) => {
+          let stars = "⭐".repeat(score as usize);
+          bot
+            .edit_with_keyboard(
+              i18n::fill(
+                bot.lang.t(T::RateThanks),
+                &[("stars", stars.clone())],
+              ),
+              InlineKeyboardMarkup::default(),
+            )
+            .await?;
+
+          if score <= 2 {
+            let text = format!(
+              "😞 <b>Low {:?} rating</b>: {} from <code>{}</code> ({})",
+              rating.kind,
+              stars,
+              rating.tg_user_id,
+              html::escape(&rating.subject)
+            );
+            app.notify_admins(Urgency::Normal, &text, None).await;
+          }
+        }
+        Err(e) => {
+          bot.reply_html(format!("❌ {}", e.user_message())).await?;
+        }
+      }
+    }
+    Callback::Inbox => {
+      handle_inbox(&sv, &bot).await?;
+    }
+    Callback::InboxItem(id) => {
+      handle_inbox_item(&sv, &bot, id).await?;
+    }
+    Callback::InboxReadAll => {
+      if let Err(e) = sv.announcement.mark_all_read(bot.user_id).await {
+        warn!("Failed to mark inbox read for {}: {}", bot.user_id, e);
+      }
+      handle_inbox(&sv, &bot).await?;
+    }
+    Callback::NotMe(device_id) => {
+      handle_not_me(&sv, &bot, &app, device_id).await?;
+    }
+    Callback::SecurityAlertsOff => {
+      let text = match sv.settings.set_security_alerts(bot.user_id, false).await
+      {
+        Ok(()) => bot.lang.t(T::SecurityAlertsOff).to_string(),
+        Err(e) => format!("❌ {}", e.user_message()),
+      };
+      bot.reply_html(text).await?;
+    }
+    Callback::Instances => {
+      let instances = sv.stats.instances(bot.user_id).await.unwrap_or_default();
+      let text = i18n::fill(
+        bot.lang.t(T::InstancesTitle),
+        &[("list", instances_breakdown(bot.lang, &instances))],
+      );
+      let kb =
+        InlineKeyboardMarkup::new(vec![vec![InlineKeyboardButton::callback(
+          bot.lang.t(T::BackToProfile),
+          Callback::Profile.to_data(),
+        )]]);
+      bot.edit_with_keyboard(text, kb).await?;
+    }
+    Callback::StatsChart => {
+      let result = sv.stats.gains(Some(bot.user_id), CHART_DAYS).await;
+      match result {
+        Ok(gains)
+          if gains.iter().all(|g| g.xp == 0 && g.runtime_hours == 0.0) =>
+        {
+          bot.reply_html(bot.lang.t(T::ChartEmpty)).await?;
+        }
+        Ok(gains) => {
+          let best = gains.iter().map(|g| g.xp).max().unwrap_or(0);
+          let caption = i18n::fill(
+            bot.lang.t(T::ChartCaption),
+            &[
+              ("days", CHART_DAYS.to_string()),
+              ("xp", gains.iter().map(|g| g.xp).sum::<i64>().to_string()),
+              ("best", best.to_string()),
+              (
+                "runtime",
+                format!(
+                  "{:.1}",
+                  gains.iter().map(|g| g.runtime_hours).sum::<f64>()
+                ),
+              ),
+            ],
+          );
+          send_chart(&bot, &gains, caption).await?;
+        }
+        Err(e) => {
+          bot.reply_html(format!("❌ {}", e.user_message())).await?;
+        }
+      }
+    }
+    Callback::History(page) => {
+      match history_screen(&sv, bot.lang, bot.user_id, page).await {
+        Ok((text, kb)) => bot.edit_with_keyboard(text, kb).await?,
+        Err(e) => {
+          bot.reply_html(format!("❌ {}", e.user_message())).await?;
+        }
+      }
+    }
+    Callback::ApiToken => {
+      handle_api_token(&sv, &bot, &app, None).await?;
+    }
+    Callback::ApiTokenNew(product) => {
+      match sv.api_token.generate(bot.user_id, product).await {
+        Ok(token) => {
+          let note =
+            i18n::fill(bot.lang.t(T::ApiTokenCreated), &[("token", token)]);
+          handle_api_token(&sv, &bot, &app, Some(note)).await?;
+        }
+        Err(e) => {
+          bot.reply_html(format!("❌ {}", e.user_message())).await?;
+        }
+      }
+    }
+    Callback::ApiTokenRevoke => {
+      let note = match sv.api_token.revoke(bot.user_id).await {
+        Ok(true) => bot.lang.t(T::ApiTokenRevoked).to_string(),
+        Ok(false) => String::new(),
+        Err(e) => format!("❌ {}\n\n", e.user_message()),
+      };
+      handle_api_token(&sv, &bot, &app, Some(note)).await?;
+    }
+    Callback::DeleteAccountConfirm => {
+      match sv.account.request_deletion(bot.user_id).await {
+        Ok(requested_at) => {
+          let (text, kb) =
+            deletion_scheduled(&sv, &bot, &app, requested_at).await;
+          bot.edit_with_keyboard(text, kb).await?;
+        }
+        Err(e) => {
+          bot.reply_html(format!("❌ {}", e.user_message())).await?;
+        }
+      }
+    }
+    Callback::DeleteAccountCancel => {
+      let text = match sv.account.cancel_deletion(bot.user_id).await {
+        Ok(true) => T::DeletionCancelled,
+        Ok(false) => T::DeletionNotPending,
+        Err(_) => T::DeletionCancelFailed,
+      };
+      let text = bot.lang.t(text);
+      bot.edit_with_keyboard(text, back_keyboard(bot.lang)).await?;
+    }
+    Callback::AcceptTerms(version) => {
+      match sv.terms.accept(bot.user_id, version).await {
+        Ok(()) => {
+          let (welcome, kb) =
+            home(&bot, sv.license.is_promo_active(TRIAL_PROMO).await);
+          let text = format!("{}\n\n{}", bot.lang.t(T::TermsAccepted), welcome);
+          bot.edit_with_keyboard(text, kb).await?;
+        }
+        Err(e) => {
+          bot.reply_html(format!("❌ {}", e.user_message())).await?;
+          if let Ok(Some(terms)) = sv.terms.pending(bot.user_id).await {
+            let (text, kb) = terms_screen(bot.lang, &terms);
+            bot.reply_with_keyboard(text, kb).await?;
+          }
+        }
+      }
+    }
     Callback::Back => {
-      let text = "<b>Yet Another Counter Strike Panel!</b>\n\n\
-        Use the buttons below to navigate.\n\
-        Read docs: https://yacsp.gitbook.io/yacsp\n\
-        Contact support: @y_a_c_s_p";
-      bot
-        .edit_with_keyboard(text, main_menu(sv.license.is_promo_active()))
-        .await?;
+      let (text, kb) =
+        home(&bot, sv.license.is_promo_active(TRIAL_PROMO).await);
+      bot.edit_with_keyboard(text, kb).await?;
+    }
+    Callback::Freebies => {
+      handle_freebies(&sv, &bot, app.config.freebie_daily_limit).await?;
+    }
+    Callback::FreebieClaim { kind, id } => {
+      let limit = app.config.freebie_daily_limit;
+      let text = match sv.freebies.claim(bot.user_id, kind, id, limit).await {
+        Ok(freebie) => i18n::fill(
+          bot.lang.t(T::FreebieGranted),
+          &[("name", html::escape(&freebie.name)), ("url", freebie.url())],
+        ),
+        Err(e) => format!("❌ {}", e.user_message()),
+      };
+      let kb =
+        InlineKeyboardMarkup::new(vec![vec![InlineKeyboardButton::callback(
+          bot.lang.t(T::BackToFreebies),
+          Callback::Freebies.to_data(),
+        )]]);
+      bot.edit_with_keyboard(text, kb).await?;
+    }
+    Callback::Language => {
+      let (text, kb) = language_picker(bot.lang);
+      bot.edit_with_keyboard(text, kb).await?;
+    }
+    Callback::SetLanguage(code) => {
+      if let Some(lang) = Lang::parse(&code) {
+        if let Err(e) = sv.settings.set_language(bot.user_id, lang.code()).await
+        {
+          warn!("Failed to set language for {}: {}", bot.user_id, e);
+        }
+        bot.lang = lang;
+      }
+      let (welcome, kb) =
+        home(&bot, sv.license.is_promo_active(TRIAL_PROMO).await);
+      let text = format!("{}\n\n{}", bot.lang.t(T::LangChanged), welcome);
+      bot.edit_with_keyboard(text, kb).await?;
     }
     Callback::DownloadVersion(version) => {
-      handle_download_version(&sv, &bot, &app, &version).await?;
+      handle_download_version(&sv, &bot, &app, &version, None).await?;
     }
-    Callback::HaveLicense => {
-      let text = "🔑 <b>Link Your License</b>\n\n\
-        If you already have a license key, you can link it to your account.\n\n\
-        <b>To link a license:</b>\n\
-        Send the command: <code>/link YOUR_LICENSE_KEY</code>\n\n\
-        <b>Your User ID:</b> <code>{}</code>\n\n\
-        <i>Note: When purchasing, you can provide a referrer's user ID to get a discount!</i>";
-      bot
-        .edit_with_keyboard(
-          text.replace("{}", &bot.user_id.to_string()),
-          back_keyboard(),
-        )
+    Callback::DownloadArtifact { version, platform } => {
+      handle_download_version(&sv, &bot, &app, &version, Some(&platform))
         .await?;
     }
+    Callback::HaveLicense => {
+      let text = i18n::fill(
+        bot.lang.t(T::LinkLicense),
+        &[("id", bot.user_id.to_string())],
+      );
+      let keyboard = InlineKeyboardMarkup::new(vec![
+        vec![InlineKeyboardButton::callback(
+          bot.lang.t(T::MenuGift),
+          gift::Action::Redeem.to_data(),
+        )],
+        vec![InlineKeyboardButton::callback(
+          bot.lang.t(T::BackToMenu),
+          Callback::Back.to_data(),
+        )],
+      ]);
+      bot.edit_with_keyboard(text, keyboard).await?;
+    }
     Callback::AboutReferral => {
       handle_about_referral(&sv, &bot).await?;
     }
     Callback::MyReferrals => {
       handle_my_referrals(&sv, &bot).await?;
     }
+    Callback::PromoKit => {
+      handle_promo_kit(&sv, &bot).await?;
+    }
+    Callback::PromoQr => {
+      handle_promo_qr(&sv, &bot).await?;
+    }
+    Callback::PromoBanner(id) => {
+      handle_promo_banner(&sv, &bot, id).await?;
+    }
+    Callback::LicenseSecurity(key) => {
+      handle_license_security(&sv, &bot, &key).await?;
+    }
+    Callback::RegenerateKey(key) => {
+      let text =
+        i18n::fill(bot.lang.t(T::RegenerateKey), &[("key", key.clone())]);
+      let kb = InlineKeyboardMarkup::new(vec![
+        vec![InlineKeyboardButton::callback(
+          bot.lang.t(T::RegenerateYes),
+          Callback::RegenerateKeyConfirm(key.clone()).to_data(),
+        )],
+        vec![InlineKeyboardButton::callback(
+          bot.lang.t(T::Back),
+          Callback::LicenseSecurity(key).to_data(),
+        )],
+      ]);
+      bot.edit_with_keyboard(text, kb).await?;
+    }
+    Callback::RegenerateKeyConfirm(key) => {
+      match sv.license.regenerate_key(&key, bot.user_id).await {
+        Ok(license) => {
+          app.drop_sessions(&key);
+          let text =
+            i18n::fill(bot.lang.t(T::KeyRegenerated), &[("key", license.key)]);
+          bot.edit_with_keyboard(text, back_keyboard(bot.lang)).await?;
+        }
+        Err(e) => {
+          bot
+            .edit_with_keyboard(
+              format!("❌ {}", e.user_message()),
+              back_keyboard(bot.lang),
+            )
+            .await?;
+        }
+      }
+    }
+    Callback::Transfer { key, to } => {
+      handle_transfer(&app, &sv, &bot, &key, to).await?;
+    }
+  }
+
+  Ok(())
+}
+
+/// Screen asking the owner to confirm handing `key` over to `to`
+pub fn transfer_prompt(
+  lang: Lang,
+  license: &license::Model,
+  to: i64,
+  review: bool,
+) -> (String, InlineKeyboardMarkup) {
+  let text = i18n::fill(
+    lang.t(T::TransferPrompt),
+    &[
+      ("type", format!("{:?}", license.license_type)),
+      ("key", license.key.clone()),
+      ("expires", utils::format_date(license.expires_at)),
+      ("to", to.to_string()),
+      (
+        "review",
+        if review { lang.t(T::TransferNeedsReview) } else { "" }.to_string(),
+      ),
+    ],
+  );
+  let kb = InlineKeyboardMarkup::new(vec![
+    vec![InlineKeyboardButton::callback(
+      lang.t(T::TransferYes),
+      Callback::Transfer { key: license.key.clone(), to }.to_data(),
+    )],
+    vec![InlineKeyboardButton::callback(
+      lang.t(T::Back),
+      Callback::Back.to_data(),
+    )],
+  ]);
+  (text, kb)
+}
+
+async fn handle_transfer(
+  app: &AppState,
+  sv: &Services<'_>,
+  bot: &ReplyBot,
+  key: &str,
+  to: i64,
+) -> ResponseResult<()> {
+  let result = async {
+    if !app.config.transfer_review {
+      complete_transfer(app, key, bot.user_id, to).await?;
+      return Ok(i18n::fill(
+        bot.lang.t(T::Transferred),
+        &[("key", key.to_string()), ("to", to.to_string())],
+      ));
+    }
+    sv.license.transferable(key, bot.user_id, to).await?;
+    let action = Action::Transfer { key: key.to_string(), to };
+    review::request(app, bot.user_id, action, "license transfer", None).await?;
+    Ok::<_, Error>(bot.lang.t(T::TransferUnderReview).to_string())
+  }
+  .await;
+
+  let text = result.unwrap_or_else(|e| format!("❌ {}", e.user_message()));
+  bot.edit_with_keyboard(text, back_keyboard(bot.lang)).await
+}
+
+/// Move the key, cut off the machines running it and tell the new owner
+pub async fn complete_transfer(
+  app: &AppState,
+  key: &str,
+  from: i64,
+  to: i64,
+) -> Result<license::Model> {
+  let sv = app.sv();
+  let license = sv.license.transfer(key, from, to).await?;
+  app.drop_sessions(key);
+  info!("License {} transferred from {} to {}", key, from, to);
+
+  let text = i18n::fill(
+    Lang::of(&sv, to).await.t(T::LicenseReceived),
+    &[
+      ("from", from.to_string()),
+      ("type", format!("{:?}", license.license_type)),
+      ("key", key.to_string()),
+      ("expires", utils::format_date(license.expires_at)),
+    ],
+  );
+  if let Some((bot, text)) = app.reach(to, text).await {
+    let _ =
+      bot.send_message(ChatId(to), text).parse_mode(ParseMode::Html).await;
+  }
+  Ok(license)
+}
+
+async fn handle_profile_view(
+  sv: &Services<'_>,
+  bot: &ReplyBot,
+) -> ResponseResult<()> {
+  let user = sv.user.by_id(bot.user_id).await.ok().flatten();
+
+  let lang = bot.lang;
+  let (reg_date, balance, role) = match &user {
+    Some(u) => (utils::format_date(u.reg_date), u.balance, u.role.clone()),
+    None => (lang.t(T::Unknown).into(), 0, UserRole::User),
+  };
+
+  let stats = sv.stats.display_stats(bot.user_id).await.ok();
+
+  let role_str = match role {
+    UserRole::User => lang.t(T::RoleUser),
+    UserRole::Creator => lang.t(T::RoleCreator),
+    UserRole::Admin => lang.t(T::RoleAdmin),
+  };
+
+  let mut text = i18n::fill(
+    lang.t(T::Profile),
+    &[
+      ("id", bot.user_id.to_string()),
+      ("registered", reg_date),
+      ("balance", format_usdt(balance)),
+      ("role", role_str.to_string()),
+    ],
+  );
+
+  if let Some(s) = stats {
+    text.push_str(&i18n::fill(
+      lang.t(T::ProfileStats),
+      &[
+        ("weekly_xp", s.weekly_xp.to_string()),
+        ("total_xp", s.total_xp.to_string()),
+        ("drops", s.drops_count.to_string()),
+        ("runtime", format!("{:.1}", s.runtime_hours)),
+      ],
+    ));
+
+    let keys = match sv.license.by_user(bot.user_id, false).await {
+      Ok(licenses) => licenses.into_iter().map(|l| l.key).collect(),
+      Err(_) => Vec::new(),
+    };
+    // a single key would only repeat the totals
+    let per_license = sv.stats.by_licenses(keys).await.unwrap_or_default();
+    if per_license.len() > 1 {
+      text.push_str(lang.t(T::ProfileByLicense));
+      for s in per_license {
+        text.push_str(&i18n::fill(
+          lang.t(T::ProfileLicenseStats),
+          &[
+            ("key", s.license_key),
+            ("xp", s.total_xp.to_string()),
+            ("runtime", format!("{:.1}", s.runtime_hours)),
+          ],
+        ));
+      }
+    }
+
+    if let Ok(Some(goal)) = sv.goal.get(bot.user_id).await {
+      let today = Utc::now().date_naive();
+      text.push_str(&i18n::fill(
+        lang.t(T::ProfileGoal),
+        &[
+          ("progress", goal.kind.format(goal::today_progress(&goal, today))),
+          ("target", goal.kind.format(goal.target)),
+          ("streak", goal::current_streak(&goal, today).to_string()),
+          ("best", goal.best_streak.to_string()),
+        ],
+      ));
+    }
+
+    if let Some(meta) = s.meta {
+      if !meta.network.routes.is_empty() {
+        text.push_str(&i18n::fill(
+          lang.t(T::ProfileRoutes),
+          &[("routes", meta.network.routes.join(", "))],
+        ));
+      }
+
+      if meta.performance.avg_fps > 0.0 {
+        text.push_str(&i18n::fill(
+          lang.t(T::ProfilePerf),
+          &[
+            ("fps", format!("{:.0}", meta.performance.avg_fps)),
+            ("ram", meta.performance.avg_ram_mb.to_string()),
+          ],
+        ));
+      }
+
+      let mut states: Vec<_> = meta.states.clone().into_iter().collect();
+      states.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap());
+
+      if let Some((top_state, duration)) = states.first() {
+        text.push_str(&i18n::fill(
+          lang.t(T::ProfileTopState),
+          &[
+            ("state", top_state.clone()),
+            ("hours", format!("{:.1}", *duration / 3600.0)),
+          ],
+        ));
+      }
+    }
+  }
+
+  let profile_keyboard = InlineKeyboardMarkup::new(vec![
+    vec![
+      InlineKeyboardButton::callback(
+        lang.t(T::ProfileReferral),
+        Callback::AboutReferral.to_data(),
+      ),
+      InlineKeyboardButton::callback(
+        lang.t(T::ProfileApiToken),
+        Callback::ApiToken.to_data(),
+      ),
+    ],
+    vec![
+      InlineKeyboardButton::callback(
+        lang.t(T::ProfileInstances),
+        Callback::Instances.to_data(),
+      ),
+      InlineKeyboardButton::callback(
+        lang.t(T::ProfileHistory),
+        Callback::History(0).to_data(),
+      ),
+    ],
+    vec![InlineKeyboardButton::callback(
+      lang.t(T::ProfileChart),
+      Callback::StatsChart.to_data(),
+    )],
+    vec![InlineKeyboardButton::callback(
+      bot.lang.t(T::BackToMenu),
+      Callback::Back.to_data(),
+    )],
+  ]);
+
+  bot.edit_with_keyboard(text, profile_keyboard).await?;
+
+  Ok(())
+}
+
+/// Balance with unpaid invoices and shortcuts to spend or top it up
+pub async fn balance_screen(
+  sv: &Services<'_>,
+  lang: Lang,
+  user_id: i64,
+) -> Result<(String, InlineKeyboardMarkup)> {
+  let balance = sv.balance.get(user_id).await?;
+  let pending = sv.payment.pending_by_user(user_id).await?;
+
+  let mut text =
+    i18n::fill(lang.t(T::Balance), &[("balance", format_usdt(balance))]);
+  if !pending.is_empty() {
+    text.push_str(lang.t(T::BalancePending));
+    for invoice in &pending {
+      text.push_str(&i18n::fill(
+        lang.t(T::BalanceInvoice),
+        &[
+          ("amount", format_usdt(invoice.amount_nano)),
+          ("expires", utils::format_date(invoice.expires_at)),
+        ],
+      ));
+    }
+  }
+
+  let button = |key, callback: Callback| {
+    InlineKeyboardButton::callback(lang.t(key), callback.to_data())
+  };
+  let mut rows = vec![vec![
+    button(T::MenuFunds, Callback::AddFunds),
+    button(T::MenuBuy, Callback::Buy),
+  ]];
+  if !pending.is_empty() {
+    rows.push(vec![button(T::BalanceCheck, Callback::CheckPayments)]);
+  }
+  rows.push(vec![button(T::ProfileHistory, Callback::History(0))]);
+  Ok((text, InlineKeyboardMarkup::new(rows)))
+}
+
+/// Transactions listed per /history page
+const HISTORY_PER_PAGE: u64 = 10;
+
+/// One page of the user's transactions, newest first, with paging buttons
+pub async fn history_screen(
+  sv: &Services<'_>,
+  lang: Lang,
+  user_id: i64,
+  mut page: u64,
+) -> Result<(String, InlineKeyboardMarkup)> {
+  let (mut txs, pages) =
+    sv.balance.transactions_page(user_id, page, HISTORY_PER_PAGE).await?;
+  let back = vec![InlineKeyboardButton::callback(
+    lang.t(T::BackToProfile),
+    Callback::Profile.to_data(),
+  )];
+  if pages == 0 {
+    return Ok((
+      lang.t(T::HistoryEmpty).to_string(),
+      InlineKeyboardMarkup::new(vec![back]),
+    ));
+  }
+  // a button of a crafted or outdated page
+  if page >= pages {
+    page = pages - 1;
+    txs =
+      sv.balance.transactions_page(user_id, page, HISTORY_PER_PAGE).await?.0;
+  }
+
+  let mut text = i18n::fill(
+    lang.t(T::History),
+    &[("page", (page + 1).to_string()), ("pages", pages.to_string())],
+  );
+  for tx in &txs {
+    let kind = match tx.tx_type {
+      TransactionType::Deposit => T::TxDeposit,
+      TransactionType::Purchase => T::TxPurchase,
+      TransactionType::ReferralBonus => T::TxReferralBonus,
+      TransactionType::Withdrawal => T::TxWithdrawal,
+      TransactionType::Reversal => T::TxReversal,
+      TransactionType::PromoCredit => T::TxPromoCredit,
+      TransactionType::Refund => T::TxRefund,
+    };
+    text.push_str(&format!(
+      "\n\n<b>{:+.2} USDT</b> · {}\n<i>{}</i>",
+      tx.amount as f64 / NANO_USDT as f64,
+      lang.t(kind),
+      utils::format_date(tx.created_at)
+    ));
+    if let Some(description) = &tx.description {
+      text.push_str(&format!(" · {}", html::escape(description)));
+    }
+  }
+
+  let mut nav = Vec::new();
+  if page > 0 {
+    nav.push(InlineKeyboardButton::callback(
+      lang.t(T::HistoryNewer),
+      Callback::History(page - 1).to_data(),
+    ));
+  }
+  if page + 1 < pages {
+    nav.push(InlineKeyboardButton::callback(
+      lang.t(T::HistoryOlder),
+      Callback::History(page + 1).to_data(),
+    ));
+  }
+  let mut rows = Vec::new();
+  if !nav.is_empty() {
+    rows.push(nav);
+  }
+  rows.push(back);
+  Ok((text, InlineKeyboardMarkup::new(rows)))
+}
+
+fn terms_screen(
+  lang: Lang,
+  terms: &terms::Model,
+) -> (String, InlineKeyboardMarkup) {
+  let text = i18n::fill(
+    lang.t(T::Terms),
+    &[
+      ("version", terms.version.to_string()),
+      ("text", html::escape(&terms.text)),
+    ],
+  );
+  let kb = InlineKeyboardMarkup::new(vec![
+    vec![InlineKeyboardButton::callback(
+      lang.t(T::TermsAcceptButton),
+      Callback::AcceptTerms(terms.version).to_data(),
+    )],
+    vec![InlineKeyboardButton::callback(
+      lang.t(T::Back),
+      Callback::Back.to_data(),
+    )],
+  ]);
+  (text, kb)
+}
+
+/// Shows the terms on /start when the current version isn't accepted yet
+pub async fn prompt_terms(
+  sv: &Services<'_>,
+  bot: &ReplyBot,
+) -> ResponseResult<()> {
+  if let Ok(Some(terms)) = sv.terms.pending(bot.user_id).await {
+    let (text, kb) = terms_screen(bot.lang, &terms);
+    bot.reply_with_keyboard(text, kb).await?;
+  }
+  Ok(())
+}
+
+/// Entry point of /delete_account: asks for confirmation, or shows
+/// the pending request with a way to cancel it
+pub async fn handle_delete_account(
+  sv: &Services<'_>,
+  bot: &ReplyBot,
+  app: &AppState,
+) -> ResponseResult<()> {
+  let user = sv.user.by_id(bot.user_id).await.ok().flatten();
+  if let Some(requested_at) =
+    user.as_ref().and_then(|u| u.deletion_requested_at)
+  {
+    let (text, kb) = deletion_scheduled(sv, bot, app, requested_at).await;
+    bot.reply_with_keyboard(text, kb).await?;
+    return Ok(());
+  }
+
+  let now = Utc::now().naive_utc();
+  let active = sv
+    .license
+    .by_user(bot.user_id, false)
+    .await
+    .unwrap_or_default()
+    .iter()
+    .filter(|l| l.expires_at > now)
+    .count();
+  let balance = user.as_ref().map(|u| u.balance).unwrap_or(0);
+  let refund = match app.config.deletion_refund {
+    _ if balance <= 0 => T::RefundNothing,
+    RefundPolicy::Manual => T::RefundManual,
+    RefundPolicy::Forfeit => T::RefundForfeit,
+  };
+
+  let text = i18n::fill(
+    bot.lang.t(T::DeletePrompt),
+    &[
+      ("days", app.config.deletion_cooloff_days.to_string()),
+      ("active", active.to_string()),
+      ("balance", format_usdt(balance)),
+      ("refund", bot.lang.t(refund).to_string()),
+    ],
+  );
+  let kb = InlineKeyboardMarkup::new(vec![
+    vec![InlineKeyboardButton::callback(
+      bot.lang.t(T::DeleteYes),
+      Callback::DeleteAccountConfirm.to_data(),
+    )],
+    vec![InlineKeyboardButton::callback(
+      bot.lang.t(T::DeleteKeep),
+      Callback::Back.to_data(),
+    )],
+  ]);
+  bot.reply_with_keyboard(text, kb).await?;
+  Ok(())
+}
+
+async fn deletion_scheduled(
+  sv: &Services<'_>,
+  bot: &ReplyBot,
+  app: &AppState,
+  requested_at: DateTime,
+) -> (String, InlineKeyboardMarkup) {
+  let wipe_at =
+    requested_at + TimeDelta::days(app.config.deletion_cooloff_days);
+  let licenses =
+    sv.license.by_user(bot.user_id, false).await.unwrap_or_default();
+
+  let text = i18n::fill(
+    bot.lang.t(T::DeletionScheduled),
+    &[
+      ("date", crate::utils::format_date(wipe_at)),
+      ("licenses", licenses.len().to_string()),
+    ],
+  );
+  let kb =
+    InlineKeyboardMarkup::new(vec![vec![InlineKeyboardButton::callback(
+      bot.lang.t(T::DeletionCancel),
+      Callback::DeleteAccountCancel.to_data(),
+    )]]);
+  (text, kb)
+}
+
+/// One line per instance, flagging silent ones and the slowest one
+pub fn instances_breakdown(
+  lang: Lang,
+  instances: &[(instance_stats::Model, MetaStats)],
+) -> String {
+  if instances.is_empty() {
+    return lang.t(T::InstancesEmpty).to_string();
   }
 
-  Ok(())
+  let now = Utc::now().naive_utc();
+  let slowest = instances
+    .iter()
+    .filter(|(_, meta)| meta.performance.avg_fps > 0.0)
+    .min_by(|(_, a), (_, b)| {
+      a.performance.avg_fps.total_cmp(&b.performance.avg_fps)
+    })
+    .filter(|_| instances.len() > 1)
+    .map(|(rollup, _)| rollup.instance_id.as_str());
+
+  instances
+    .iter()
+    .map(|(rollup, meta)| {
+      let silent = now - rollup.last_seen;
+      let status = if silent.num_minutes() >= INSTANCE_SILENT_MINS {
+        i18n::fill(
+          lang.t(T::InstanceSilent),
+          &[("duration", lang.duration(silent))],
+        )
+      } else {
+        lang.t(T::InstanceActive).to_string()
+      };
+      let top_state = meta
+        .states
+        .iter()
+        .max_by(|a, b| a.1.total_cmp(b.1))
+        .map(|(state, _)| html::escape(state))
+        .unwrap_or("-".into());
+
+      let stats = i18n::fill(
+        lang.t(T::InstanceStats),
+        &[
+          ("runtime", format!("{:.1}", rollup.runtime_hours)),
+          ("fps", format!("{:.0}", meta.performance.avg_fps)),
+          ("ram", meta.performance.avg_ram_mb.to_string()),
+          ("state", top_state),
+        ],
+      );
+      format!(
+        "<b>{}</b>{} — {}\n{}",
+        html::escape(&rollup.instance_id),
+        if slowest == Some(rollup.instance_id.as_str()) { " 🐢" } else { "" },
+        status,
+        stats
+      )
+    })
+    .collect::<Vec<_>>()
+    .join("\n\n")
 }
 
-async fn handle_profile_view(
+/// Kick the reported machine and offer to revoke the key
+async fn handle_not_me(
   sv: &Services<'_>,
   bot: &ReplyBot,
+  app: &AppState,
+  device_id: i32,
 ) -> ResponseResult<()> {
-  let user = sv.user.by_id(bot.user_id).await.ok().flatten();
-
-  let (reg_date, balance, role) = match &user {
-    Some(u) => (utils::format_date(u.reg_date), u.balance, u.role.clone()),
-    None => ("Unknown".into(), 0, UserRole::User),
+  let device = sv.device.by_id(device_id).await.ok().flatten();
+  let license = match &device {
+    Some(device) => sv.license.by_key(&device.license_key).await.ok().flatten(),
+    None => None,
   };
-
-  let stats = sv.stats.display_stats(bot.user_id).await.ok();
-
-  let balance_str = format_usdt(balance);
-  let role_str = match role {
-    UserRole::User => "User",
-    UserRole::Creator => "Creator",
-    UserRole::Admin => "Admin",
+  let (Some(device), Some(license)) = (device, license) else {
+    bot.reply_html(bot.lang.t(T::KeyGone)).await?;
+    return Ok(());
   };
+  if license.tg_user_id != bot.user_id {
+    bot.reply_html(bot.lang.t(T::LicenseNotFound)).await?;
+    return Ok(());
+  }
 
-  let mut text = format!(
-    "👤 <b>My Profile</b>\n\n\
-    <b>User ID:</b> <code>{}</code>\n\
-    <b>Registered:</b> {}\n\
-    <b>Balance:</b> {}\n\
-    <b>Role:</b> {}",
-    bot.user_id, reg_date, balance_str, role_str
-  );
-
-  if let Some(s) = stats {
-    text.push_str(&format!(
-      "\n\n<b>📊 Farming Stats:</b>\n\
-        Weekly XP: {}\n\
-        Total XP: {}\n\
-        Drops: {}\n\
-        Runtime: {:.1}h",
-      s.weekly_xp, s.total_xp, s.drops_count, s.runtime_hours
-    ));
-
-    if let Some(meta) = s.meta {
-      if !meta.network.routes.is_empty() {
-        text.push_str(&format!(
-          "\n🌐 <b>Routes:</b> {}",
-          meta.network.routes.join(", ")
-        ));
-      }
-
-      if meta.performance.avg_fps > 0.0 {
-        text.push_str(&format!(
-          "\n🚀 <b>Perf:</b> {:.0} FPS | {} MB",
-          meta.performance.avg_fps, meta.performance.avg_ram_mb
-        ));
-      }
-
-      let mut states: Vec<_> = meta.states.clone().into_iter().collect();
-      states.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap());
-
-      if let Some((top_state, duration)) = states.first() {
-        text.push_str(&format!(
-          "\n⏳ <b>Top State:</b> {top_state} ({:.1}h)",
-          *duration / 3600.0
-        ));
-      }
-    }
+  // logging out also bans the session id for a while
+  let sessions: Vec<_> = app
+    .sessions
+    .get(&license.key)
+    .map(|sessions| {
+      sessions
+        .iter()
+        .filter(|s| s.hwid_hash.as_deref() == Some(device.hwid.as_str()))
+        .map(|s| s.session_id.clone())
+        .collect()
+    })
+    .unwrap_or_default();
+  for session_id in &sessions {
+    app.logout_session(&license.key, session_id);
   }
 
-  let profile_keyboard = InlineKeyboardMarkup::new(vec![
+  let text = i18n::fill(
+    bot.lang.t(T::SessionDropped),
+    &[("count", sessions.len().to_string())],
+  );
+  let kb = InlineKeyboardMarkup::new(vec![
     vec![InlineKeyboardButton::callback(
-      "🔗 About Referral",
-      Callback::AboutReferral.to_data(),
+      bot.lang.t(T::RegenerateMyKey),
+      Callback::RegenerateKey(license.key.clone()).to_data(),
     )],
     vec![InlineKeyboardButton::callback(
-      "« Back to Menu",
-      Callback::Back.to_data(),
+      bot.lang.t(T::SecurityReportButton),
+      Callback::LicenseSecurity(license.key).to_data(),
     )],
   ]);
+  bot.edit_with_keyboard(text, kb).await
+}
 
-  bot.edit_with_keyboard(text, profile_keyboard).await?;
+/// Personal read-only token for the stats API
+async fn handle_api_token(
+  sv: &Services<'_>,
+  bot: &ReplyBot,
+  app: &AppState,
+  note: Option<String>,
+) -> ResponseResult<()> {
+  let token = sv.api_token.by_user(bot.user_id).await.ok().flatten();
+
+  let products = sv.product.all().await.unwrap_or_default();
+
+  let lang = bot.lang;
+  let status = match &token {
+    Some(token) => i18n::fill(
+      lang.t(T::ApiTokenActive),
+      &[
+        (
+          "scope",
+          token
+            .product
+            .as_ref()
+            .and_then(|slug| products.iter().find(|p| p.slug == *slug))
+            .map_or(lang.t(T::ApiAllProducts), |p| p.name.as_str())
+            .to_string(),
+        ),
+        ("created", utils::format_date(token.created_at)),
+        (
+          "used",
+          token
+            .last_used_at
+            .map(utils::format_date)
+            .unwrap_or(lang.t(T::Never).into()),
+        ),
+      ],
+    ),
+    None => lang.t(T::ApiTokenMissing).to_string(),
+  };
 
-  Ok(())
+  let text = i18n::fill(
+    lang.t(T::ApiToken),
+    &[
+      ("status", status),
+      ("base", app.config.base_url.clone()),
+      ("note", note.unwrap_or_default()),
+    ],
+  );
+
+  let mut rows = vec![vec![InlineKeyboardButton::callback(
+    lang.t(if token.is_some() { T::ApiRegenerate } else { T::ApiGenerate }),
+    Callback::ApiTokenNew(None).to_data(),
+  )]];
+  // Scoped tokens only make sense once there is more than one product
+  if products.len() > 1 {
+    for product in &products {
+      rows.push(vec![InlineKeyboardButton::callback(
+        i18n::fill(lang.t(T::ApiOnlyFor), &[("product", product.name.clone())]),
+        Callback::ApiTokenNew(Some(product.slug.clone())).to_data(),
+      )]);
+    }
+  }
+  if token.is_some() {
+    rows.push(vec![InlineKeyboardButton::callback(
+      lang.t(T::ApiRevoke),
+      Callback::ApiTokenRevoke.to_data(),
+    )]);
+  }
+  rows.push(vec![InlineKeyboardButton::callback(
+    lang.t(T::BackToProfile),
+    Callback::Profile.to_data(),
+  )]);
+
+  bot.edit_with_keyboard(text, InlineKeyboardMarkup::new(rows)).await
 }
 
 /// Handle the "About Referral" button - shows different info based on user role
@@ -396,7 +1725,7 @@ async fn handle_about_referral(
 
   let profile_back_kb =
     InlineKeyboardMarkup::new(vec![vec![InlineKeyboardButton::callback(
-      "« Back to Profile",
+      bot.lang.t(T::BackToProfile),
       Callback::Profile.to_data(),
     )]]);
 
@@ -425,36 +1754,28 @@ async fn handle_about_referral(
           .map(|username| {
             format!("https://t.me/{}?start={}", username, code_display)
           })
-          .unwrap_or_else(|| "Unable to generate link".to_string());
+          .unwrap_or_else(|| bot.lang.t(T::RefInviteUnavailable).to_string());
 
         let code_note = if custom_code.is_some() {
-          format!(
-            "\n<i>Tip: Users can also use your ID <code>{}</code> as referral code.</i>",
-            bot.user_id
+          i18n::fill(
+            bot.lang.t(T::RefTipId),
+            &[("id", bot.user_id.to_string())],
           )
         } else {
-          "\n<i>Tip: Ask an admin to set a custom code with /setcode</i>"
-            .to_string()
+          bot.lang.t(T::RefTipSetCode).to_string()
         };
 
-        format!(
-          "🔗 <b>Referral Program (Creator)</b>\n\n\
-          <b>Your referral code:</b> <code>{code}</code>\n\n\
-          <b>📎 Invite Link:</b>\n\
-          <code>{invite_link}</code>\n\n\
-          <b>📊 Your Stats:</b>\n\
-          Commission rate: {commission_rate}%\n\
-          Customer discount: {discount_percent}%\n\
-          Total sales: {total_sales}\n\
-          Total earnings: {usdt}\n\n\
-          <b>💡 How it works:</b>\n\
-          Share your invite link or code (<code>{code}</code>) with others. When they click the link:\n\
-          • Your referral code is applied automatically\n\
-          • They get a {discount_percent}% discount on purchases\n\
-          • You earn {commission_rate}% commission on their purchases\n\n\
-          <i>Commissions are added to your balance automatically.</i>{code_note}",
-          usdt = format_usdt(total_earnings),
-          code = code_display,
+        i18n::fill(
+          bot.lang.t(T::RefCreator),
+          &[
+            ("commission", commission_rate.to_string()),
+            ("discount", discount_percent.to_string()),
+            ("sales", total_sales.to_string()),
+            ("earnings", format_usdt(total_earnings)),
+            ("note", code_note),
+            ("link", invite_link),
+            ("code", code_display.to_string()),
+          ],
         )
       } else {
         let invite_link = bot_username
@@ -462,26 +1783,26 @@ async fn handle_about_referral(
           .map(|username| {
             format!("https://t.me/{}?start={}", username, code_display)
           })
-          .unwrap_or_else(|| "Unable to generate link".to_string());
-
-        format!(
-          "🔗 <b>Referral Program (Creator)</b>\n\n\
-          <b>Your referral code:</b> <code>{}</code>\n\n\
-          <b>📎 Invite Link:</b>\n\
-          <code>{}</code>\n\n\
-          <i>Share this link with others to earn commission on their purchases.</i>",
-          code_display, invite_link
+          .unwrap_or_else(|| bot.lang.t(T::RefInviteUnavailable).to_string());
+
+        i18n::fill(
+          bot.lang.t(T::RefCreatorShort),
+          &[("link", invite_link), ("code", code_display.to_string())],
         )
       };
 
       // Creator keyboard with "My Referrals" button
       let creator_kb = InlineKeyboardMarkup::new(vec![
         vec![InlineKeyboardButton::callback(
-          "👥 My Referrals",
+          bot.lang.t(T::MyReferralsButton),
           Callback::MyReferrals.to_data(),
         )],
         vec![InlineKeyboardButton::callback(
-          "« Back to Profile",
+          bot.lang.t(T::PromoMaterialsButton),
+          Callback::PromoKit.to_data(),
+        )],
+        vec![InlineKeyboardButton::callback(
+          bot.lang.t(T::BackToProfile),
           Callback::Profile.to_data(),
         )],
       ]);
@@ -494,22 +1815,15 @@ async fn handle_about_referral(
         .map(|username| {
           format!("https://t.me/{}?start={}", username, bot.user_id)
         })
-        .unwrap_or_else(|| "Unable to generate link".to_string());
-
-      let text = format!(
-        "🔗 <b>Referral Program</b>\n\n\
-        <b>Your user ID:</b> <code>{code}</code>\n\n\
-        <b>📎 Invite Link:</b>\n\
-        <code>{invite_link}</code>\n\n\
-        <b>💡 Invite Friends & Earn!</b>\n\
-        Share your invite link with friends. When they click and start the bot:\n\
-        • Your referral code is applied automatically\n\
-        • You receive <b>{commission_rate}%</b> of their purchase as bonus balance\n\
-        • This bonus can be used to buy new licenses\n\n\
-        <b>Manual method:</b>\n\
-        Friends can also use <code>/ref {code}</code> to set you as their referrer.\n\n\
-        <i>Note: Only creators can have custom referral codes. Contact support to become a creator.</i>",
-        code = bot.user_id
+        .unwrap_or_else(|| bot.lang.t(T::RefInviteUnavailable).to_string());
+
+      let text = i18n::fill(
+        bot.lang.t(T::RefUser),
+        &[
+          ("commission", commission_rate.to_string()),
+          ("link", invite_link),
+          ("code", bot.user_id.to_string()),
+        ],
       );
 
       bot.edit_with_keyboard(text, profile_back_kb).await?;
@@ -519,6 +1833,220 @@ async fn handle_about_referral(
   Ok(())
 }
 
+/// Telegram caps inline keyboards at 100 buttons
+const FREEBIES_SHOWN: usize = 30;
+
+/// Handle "Freebies" button - unclaimed free games and items
+async fn handle_freebies(
+  sv: &Services<'_>,
+  bot: &ReplyBot,
+  limit: u64,
+) -> ResponseResult<()> {
+  let lang = bot.lang;
+  let available = sv.freebies.available(bot.user_id).await.unwrap_or_default();
+  if available.is_empty() {
+    bot
+      .edit_with_keyboard(lang.t(T::FreebiesEmpty), back_keyboard(lang))
+      .await?;
+    return Ok(());
+  }
+
+  let claimed = sv.freebies.claimed_today(bot.user_id).await.unwrap_or(0);
+  let mut text = i18n::fill(
+    lang.t(T::Freebies),
+    &[("claimed", claimed.to_string()), ("limit", limit.to_string())],
+  );
+  if available.len() > FREEBIES_SHOWN {
+    text.push_str(&i18n::fill(
+      lang.t(T::FreebiesMore),
+      &[("count", (available.len() - FREEBIES_SHOWN).to_string())],
+    ));
+  }
+
+  let mut rows: Vec<_> = available
+    .iter()
+    .take(FREEBIES_SHOWN)
+    .map(|freebie| {
+      let icon = match freebie.kind {
+        FreebieKind::Game => "🎮",
+        FreebieKind::Item => "🎁",
+      };
+      vec![InlineKeyboardButton::callback(
+        format!("{} {}", icon, freebie.name),
+        Callback::FreebieClaim { kind: freebie.kind, id: freebie.id }.to_data(),
+      )]
+    })
+    .collect();
+  rows.push(vec![InlineKeyboardButton::callback(
+    lang.t(T::BackToMenu),
+    Callback::Back.to_data(),
+  )]);
+
+  bot.edit_with_keyboard(text, InlineKeyboardMarkup::new(rows)).await?;
+  Ok(())
+}
+
+/// Referral code and invite link of a creator or admin, None for other users
+async fn creator_invite(
+  sv: &Services<'_>,
+  bot: &ReplyBot,
+) -> Option<(String, String)> {
+  let user = sv.user.by_id(bot.user_id).await.ok().flatten()?;
+  if user.role != UserRole::Creator && user.role != UserRole::Admin {
+    return None;
+  }
+
+  let code = user.referral_code.unwrap_or_else(|| bot.user_id.to_string());
+  let username = bot.inner.get_me().await.ok()?.username.clone()?;
+  let link = format!("https://t.me/{}?start={}", username, code);
+  Some((code, link))
+}
+
+/// Handle "Promo Materials" button - invite link, copy templates and banners
+async fn handle_promo_kit(
+  sv: &Services<'_>,
+  bot: &ReplyBot,
+) -> ResponseResult<()> {
+  let back_kb =
+    InlineKeyboardMarkup::new(vec![vec![InlineKeyboardButton::callback(
+      bot.lang.t(T::BackToReferral),
+      Callback::AboutReferral.to_data(),
+    )]]);
+
+  let Some((code, link)) = creator_invite(sv, bot).await else {
+    bot.edit_with_keyboard(bot.lang.t(T::PromoCreatorsOnly), back_kb).await?;
+    return Ok(());
+  };
+
+  let assets = sv.promo_kit.all().await.unwrap_or_default();
+
+  let mut text = i18n::fill(bot.lang.t(T::PromoKit), &[("link", link.clone())]);
+
+  let templates: Vec<_> = assets
+    .iter()
+    .filter(|asset| asset.kind == PromoAssetKind::Template)
+    .collect();
+  if !templates.is_empty() {
+    text.push_str(bot.lang.t(T::PromoTemplates));
+    for template in templates {
+      let body = promo_kit::render(
+        template.text.as_deref().unwrap_or_default(),
+        &link,
+        &code,
+      );
+      text.push_str(&format!(
+        "\n<b>{}</b>\n<pre>{}</pre>\n",
+        html::escape(&template.title),
+        html::escape(&body)
+      ));
+    }
+  }
+
+  let mut buttons = vec![vec![InlineKeyboardButton::callback(
+    bot.lang.t(T::PromoQrButton),
+    Callback::PromoQr.to_data(),
+  )]];
+  for banner in assets.iter().filter(|a| a.kind == PromoAssetKind::Banner) {
+    buttons.push(vec![InlineKeyboardButton::callback(
+      format!("🖼 {}", banner.title),
+      Callback::PromoBanner(banner.id).to_data(),
+    )]);
+  }
+  if buttons.len() == 1 {
+    text.push_str(bot.lang.t(T::PromoNoBanners));
+  }
+  buttons.push(vec![InlineKeyboardButton::callback(
+    bot.lang.t(T::BackToReferral),
+    Callback::AboutReferral.to_data(),
+  )]);
+
+  bot.edit_with_keyboard(text, InlineKeyboardMarkup::new(buttons)).await?;
+  Ok(())
+}
+
+/// Send the invite link as a QR code image
+async fn handle_promo_qr(
+  sv: &Services<'_>,
+  bot: &ReplyBot,
+) -> ResponseResult<()> {
+  let Some((_, link)) = creator_invite(sv, bot).await else {
+    return Ok(());
+  };
+  let Some(qr) = QrCode::encode(link.as_bytes()) else {
+    bot.reply_html(bot.lang.t(T::QrTooLong)).await?;
+    return Ok(());
+  };
+
+  bot
+    .inner
+    .send_photo(
+      bot.chat_id,
+      InputFile::memory(qr.to_png(QR_SCALE)).file_name("invite.png"),
+    )
+    .caption(i18n::fill(bot.lang.t(T::QrCaption), &[("link", link)]))
+    .parse_mode(ParseMode::Html)
+    .await?;
+  Ok(())
+}
+
+/// Days covered by the stats charts
+pub const CHART_DAYS: u64 = 30;
+
+/// Daily XP and runtime of `gains` as a photo
+pub async fn send_chart(
+  bot: &ReplyBot,
+  gains: &[DailyGain],
+  caption: String,
+) -> ResponseResult<()> {
+  let xp: Vec<_> = gains.iter().map(|g| g.xp as f64).collect();
+  let runtime: Vec<_> = gains.iter().map(|g| g.runtime_hours).collect();
+  let png = match chart::daily(&xp, &runtime) {
+    Ok(png) => png,
+    Err(e) => {
+      error!("Failed to render chart: {}", e);
+      bot.reply_html(caption).await?;
+      return Ok(());
+    }
+  };
+  bot
+    .inner
+    .send_photo(bot.chat_id, InputFile::memory(png).file_name("chart.png"))
+    .caption(bot.env.stamp(caption))
+    .parse_mode(ParseMode::Html)
+    .await?;
+  Ok(())
+}
+
+/// Send a banner with the creator's invite link as caption
+async fn handle_promo_banner(
+  sv: &Services<'_>,
+  bot: &ReplyBot,
+  id: i32,
+) -> ResponseResult<()> {
+  let Some((_, link)) = creator_invite(sv, bot).await else {
+    return Ok(());
+  };
+  let Some(file_id) =
+    sv.promo_kit.by_id(id).await.ok().flatten().and_then(|asset| asset.file_id)
+  else {
+    bot.reply_html(bot.lang.t(T::BannerRemoved)).await?;
+    return Ok(());
+  };
+
+  let sent = bot
+    .inner
+    .send_photo(bot.chat_id, InputFile::file_id(FileId(file_id)))
+    .caption(format!("<code>{}</code>", link))
+    .parse_mode(ParseMode::Html)
+    .await;
+  // file ids belong to the bot they were uploaded to
+  if let Err(e) = sent {
+    warn!("Failed to send banner #{}: {}", id, e);
+    bot.reply_html(bot.lang.t(T::BannerUnavailable)).await?;
+  }
+  Ok(())
+}
+
 /// Handle "My Referrals" button - shows list of users referred by this creator
 async fn handle_my_referrals(
   sv: &Services<'_>,
@@ -529,17 +2057,14 @@ async fn handle_my_referrals(
 
   let profile_back_kb =
     InlineKeyboardMarkup::new(vec![vec![InlineKeyboardButton::callback(
-      "« Back to Referral Info",
+      bot.lang.t(T::BackToReferral),
       Callback::AboutReferral.to_data(),
     )]]);
 
   // Only creators and admins can view their referrals list
   if role != UserRole::Creator && role != UserRole::Admin {
     bot
-      .edit_with_keyboard(
-        "❌ Only creators can view their referrals list.",
-        profile_back_kb,
-      )
+      .edit_with_keyboard(bot.lang.t(T::ReferralsCreatorsOnly), profile_back_kb)
       .await?;
     return Ok(());
   }
@@ -549,17 +2074,15 @@ async fn handle_my_referrals(
     sv.user.referred_by_user(bot.user_id).await.unwrap_or_default();
 
   if referrals.is_empty() {
-    let text = "👥 <b>My Referrals</b>\n\n\
-      <i>You haven't referred any users yet.</i>\n\n\
-      Share your referral code or invite link to start earning commissions!";
-    bot.edit_with_keyboard(text, profile_back_kb).await?;
+    bot
+      .edit_with_keyboard(bot.lang.t(T::ReferralsEmpty), profile_back_kb)
+      .await?;
     return Ok(());
   }
 
-  let mut text = format!(
-    "👥 <b>My Referrals</b>\n\n\
-    <b>Total referred users:</b> {}\n\n",
-    referrals.len()
+  let mut text = i18n::fill(
+    bot.lang.t(T::Referrals),
+    &[("count", referrals.len().to_string())],
   );
 
   let now = Utc::now().naive_utc();
@@ -579,18 +2102,19 @@ async fn handle_my_referrals(
 
     let status_icon = if has_active_license { "✅" } else { "⚪" };
 
-    text.push_str(&format!(
-      "<b>{}.</b> {} {}\n\
-      <code>{}</code> | Joined: {}\n\n",
-      i + 1,
-      status_icon,
-      username,
-      referral.tg_user_id,
-      reg_date
+    text.push_str(&i18n::fill(
+      bot.lang.t(T::ReferralLine),
+      &[
+        ("n", (i + 1).to_string()),
+        ("icon", status_icon.to_string()),
+        ("id", referral.tg_user_id.to_string()),
+        ("joined", reg_date),
+        ("name", username),
+      ],
     ));
   }
 
-  text.push_str("\n<i>✅ = has active license, ⚪ = no active license</i>");
+  text.push_str(bot.lang.t(T::ReferralsLegend));
 
   // Split message into chunks and send with keyboard on the last chunk
   bot.reply_html_chunked_with_keyboard(text, profile_back_kb).await?;
@@ -606,61 +2130,242 @@ async fn handle_license_edit(
 
   match sv.license.by_user(bot.user_id, false).await {
     Ok(licenses) if !licenses.is_empty() => {
-      let mut text = String::from("🔑 <b>Your Licenses:</b>\n");
+      let mut text = bot.lang.t(T::Licenses).to_string();
+      let mut rows = Vec::new();
 
       for license in licenses {
-        let status = if license.expires_at > now {
-          format!("⏳ {}", utils::format_duration(license.expires_at - now))
+        let status = if let Some(until) = license.suspended_until(now) {
+          i18n::fill(
+            bot.lang.t(T::LicenseSuspended),
+            &[("duration", bot.lang.duration(until - now))],
+          )
+        } else if license.expires_at > now {
+          format!("⏳ {}", bot.lang.duration(license.expires_at - now))
         } else {
-          "❌ Expired".into()
+          bot.lang.t(T::Expired).into()
         };
 
         text.push_str(&format!(
           "\n<code>{}</code>\n{} | {:?}\n",
           license.key, status, license.license_type
         ));
+
+        rows.push(vec![InlineKeyboardButton::callback(
+          i18n::fill(
+            bot.lang.t(T::SecurityReportKey),
+            &[("key", license.key[..8].to_string())],
+          ),
+          Callback::LicenseSecurity(license.key.clone()).to_data(),
+        )]);
       }
 
-      bot.edit_with_keyboard(text, back_keyboard()).await?;
+      rows.push(vec![InlineKeyboardButton::callback(
+        bot.lang.t(T::BackToMenu),
+        Callback::Back.to_data(),
+      )]);
+
+      bot.edit_with_keyboard(text, InlineKeyboardMarkup::new(rows)).await?;
+    }
+    _ => {
+      bot
+        .edit_with_keyboard(
+          bot.lang.t(T::NoActiveLicense),
+          back_keyboard(bot.lang),
+        )
+        .await?;
     }
+  }
+
+  Ok(())
+}
+
+/// Show the owner every machine that has used their key, so sharing or
+/// a leaked key can be spotted and the key regenerated.
+async fn handle_license_security(
+  sv: &Services<'_>,
+  bot: &ReplyBot,
+  key: &str,
+) -> ResponseResult<()> {
+  let license = match sv.license.by_key(key).await {
+    Ok(Some(l)) if l.tg_user_id == bot.user_id => l,
     _ => {
       bot
-        .edit_with_keyboard("You have no active license!", back_keyboard())
+        .edit_with_keyboard(
+          bot.lang.t(T::LicenseNotFound),
+          back_keyboard(bot.lang),
+        )
         .await?;
+      return Ok(());
+    }
+  };
+
+  let devices = sv.device.by_license(&license.key).await.unwrap_or_default();
+
+  let mut text = i18n::fill(
+    bot.lang.t(T::SecurityReport),
+    &[("key", license.key.clone()), ("devices", devices.len().to_string())],
+  );
+
+  if devices.is_empty() {
+    text.push_str(bot.lang.t(T::KeyUnused));
+  }
+
+  for (i, device) in devices.iter().enumerate() {
+    let hwid: String = device.hwid.chars().take(12).collect();
+    let network = device
+      .ip
+      .as_deref()
+      .map(utils::mask_ip)
+      .unwrap_or_else(|| bot.lang.t(T::Unknown).to_string());
+    let network = match &device.country {
+      Some(country) => format!("{} ({})", network, country),
+      None => network,
+    };
+
+    text.push_str(&i18n::fill(
+      bot.lang.t(T::DeviceLine),
+      &[
+        ("n", (i + 1).to_string()),
+        ("hwid", hwid),
+        ("first", utils::format_date(device.first_seen)),
+        ("last", utils::format_date(device.last_seen)),
+        ("sessions", device.sessions.to_string()),
+        ("network", network),
+      ],
+    ));
+  }
+
+  text.push_str(bot.lang.t(T::SecurityHint));
+
+  let kb = InlineKeyboardMarkup::new(vec![
+    vec![InlineKeyboardButton::callback(
+      bot.lang.t(T::NotMeRegenerate),
+      Callback::RegenerateKey(license.key.clone()).to_data(),
+    )],
+    vec![InlineKeyboardButton::callback(
+      bot.lang.t(T::Back),
+      Callback::License.to_data(),
+    )],
+  ]);
+
+  bot.reply_html_chunked_with_keyboard(text, kb).await?;
+  Ok(())
+}
+
+async fn handle_trial_claim(
+  sv: &Services<'_>,
+  bot: &ReplyBot,
+) -> ResponseResult<()> {
+  match sv.license.claim_promo(bot.user_id, TRIAL_PROMO).await {
+    Ok(license) => {
+      let text =
+        i18n::fill(bot.lang.t(T::TrialClaimed), &[("key", license.key)]);
+      bot.reply_with_keyboard(text, back_keyboard(bot.lang)).await?;
     }
+    Err(e) => {
+      let msg = match e {
+        Error::Promo(_) => e.user_message(),
+        _ => bot.lang.t(T::ErrorOccurred).into(),
+      };
+      bot.reply_with_keyboard(msg, back_keyboard(bot.lang)).await?;
+    }
+  }
+
+  Ok(())
+}
+
+/// How many announcements the inbox keeps visible
+const INBOX_LIMIT: u64 = 20;
+
+async fn handle_inbox(sv: &Services<'_>, bot: &ReplyBot) -> ResponseResult<()> {
+  let items = sv.announcement.inbox(bot.user_id, INBOX_LIMIT).await;
+  let items = items.unwrap_or_default();
+
+  if items.is_empty() {
+    bot
+      .edit_with_keyboard(bot.lang.t(T::InboxEmpty), back_keyboard(bot.lang))
+      .await?;
+    return Ok(());
+  }
+
+  let unread = sv.announcement.unread_count(bot.user_id).await.unwrap_or(0);
+  let text =
+    i18n::fill(bot.lang.t(T::Inbox), &[("unread", unread.to_string())]);
+
+  let mut rows: Vec<_> = items
+    .into_iter()
+    .map(|(item, is_read)| {
+      let label = format!(
+        "{}{} {}",
+        if is_read { "" } else { "🆕 " },
+        item.category.icon(),
+        item.title
+      );
+      vec![InlineKeyboardButton::callback(
+        label,
+        Callback::InboxItem(item.id).to_data(),
+      )]
+    })
+    .collect();
+
+  if unread > 0 {
+    rows.push(vec![InlineKeyboardButton::callback(
+      bot.lang.t(T::InboxReadAll),
+      Callback::InboxReadAll.to_data(),
+    )]);
   }
+  rows.push(vec![InlineKeyboardButton::callback(
+    bot.lang.t(T::BackToMenu),
+    Callback::Back.to_data(),
+  )]);
 
-  Ok(())
+  bot.edit_with_keyboard(text, InlineKeyboardMarkup::new(rows)).await
 }
 
-async fn handle_trial_claim(
+async fn handle_inbox_item(
   sv: &Services<'_>,
   bot: &ReplyBot,
+  id: i32,
 ) -> ResponseResult<()> {
-  let promo_name = "first_promo";
+  let Ok(Some(item)) = sv.announcement.by_id(id).await else {
+    return handle_inbox(sv, bot).await;
+  };
 
-  match sv.license.claim_promo(bot.user_id, promo_name).await {
-    Ok(license) => {
-      let text = format!(
-        "🎉 <b>Success!</b>\n\n\
-        Here is your FREE week license:\n\
-        <code>{}</code>\n\n\
-        Download the software using the Download button!",
-        license.key
-      );
-      bot.reply_with_keyboard(text, back_keyboard()).await?;
-    }
-    Err(e) => {
-      let msg = match e {
-        Error::Promo(Promo::Inactive) => "Promo is not active right now.",
-        Error::Promo(Promo::Claimed) => "You have already claimed this promo",
-        _ => "An error occurred.",
-      };
-      bot.reply_with_keyboard(msg, back_keyboard()).await?;
-    }
+  if let Err(e) = sv.announcement.mark_read(bot.user_id, id).await {
+    warn!("Failed to mark announcement {} read: {}", id, e);
   }
 
-  Ok(())
+  let text = format!(
+    "{} <b>{}</b>\n<i>{}</i>\n\n{}",
+    item.category.icon(),
+    html::escape(&item.title),
+    utils::format_date(item.created_at),
+    item.body
+  );
+  let kb = InlineKeyboardMarkup::new(vec![vec![
+    InlineKeyboardButton::callback(
+      bot.lang.t(T::BackToInbox),
+      Callback::Inbox.to_data(),
+    ),
+    InlineKeyboardButton::callback(
+      bot.lang.t(T::BackMenu),
+      Callback::Back.to_data(),
+    ),
+  ]]);
+  bot.edit_with_keyboard(text, kb).await
+}
+
+/// Products the user holds a non-blocked license for,
+/// storefronts only hand out their own product
+async fn owned_products(sv: &Services<'_>, bot: &ReplyBot) -> HashSet<String> {
+  sv.license
+    .by_user(bot.user_id, false)
+    .await
+    .unwrap_or_default()
+    .into_iter()
+    .map(|license| license.product)
+    .filter(|product| bot.product().is_none_or(|p| p == product))
+    .collect()
 }
 
 async fn handle_download(
@@ -668,45 +2373,63 @@ async fn handle_download(
   bot: &ReplyBot,
   app: &AppState,
 ) -> ResponseResult<()> {
-  let builds = sv.build.active().await.unwrap_or_default();
+  let owned = owned_products(sv, bot).await;
+  let channel = sv.settings.channel(bot.user_id).await.unwrap_or_default();
+  let builds: Vec<_> = sv
+    .build
+    .active_on(channel)
+    .await
+    .unwrap_or_default()
+    .into_iter()
+    .filter(|build| owned.contains(&build.product))
+    .collect();
 
   if builds.is_empty() {
     bot
-      .edit_with_keyboard(
-        "❌ No builds available yet. Contact support.",
-        back_keyboard(),
-      )
+      .edit_with_keyboard(bot.lang.t(T::NoBuilds), back_keyboard(bot.lang))
       .await?;
     return Ok(());
   }
 
   // If only one version available, download directly
   if builds.len() == 1 {
-    return handle_download_version(sv, bot, app, &builds[0].version).await;
+    return handle_download_version(sv, bot, app, &builds[0].version, None)
+      .await;
   }
 
-  // Multiple versions - show selection menu
+  // Multiple versions - show selection menu, builds are newest first
+  let products = sv.product.all().await.unwrap_or_default();
+  let mut seen = HashSet::new();
   let mut rows = Vec::new();
   for build in &builds {
-    let label = if Some(build.id) == builds.first().map(|b| b.id) {
-      format!("📥 v{} (latest)", build.version)
-    } else {
-      format!("📥 v{}", build.version)
+    let prefix = match owned.len() {
+      1 => String::new(),
+      _ => products
+        .iter()
+        .find(|p| p.slug == build.product)
+        .map_or(String::new(), |p| format!("{} ", p.name)),
     };
+    let latest = match seen.insert(build.product.as_str()) {
+      true => bot.lang.t(T::BuildLatest),
+      false => "",
+    };
+    let label = format!("📥 {}v{}{}", prefix, build.version, latest);
     rows.push(vec![InlineKeyboardButton::callback(
       label,
       Callback::DownloadVersion(build.version.clone()).to_data(),
     )]);
   }
   rows.push(vec![InlineKeyboardButton::callback(
-    "« Back to Menu",
+    bot.lang.t(T::BackToMenu),
     Callback::Back.to_data(),
   )]);
 
-  let text = "📥 <b>Select Version</b>\n\n\
-    Choose which version to download:";
-
-  bot.edit_with_keyboard(text, InlineKeyboardMarkup::new(rows)).await?;
+  bot
+    .edit_with_keyboard(
+      bot.lang.t(T::SelectVersion),
+      InlineKeyboardMarkup::new(rows),
+    )
+    .await?;
 
   Ok(())
 }
@@ -716,31 +2439,77 @@ async fn handle_download_version(
   bot: &ReplyBot,
   app: &AppState,
   version: &str,
+  platform: Option<&str>,
 ) -> ResponseResult<()> {
+  let owned = owned_products(sv, bot).await;
+  let channel = sv.settings.channel(bot.user_id).await.unwrap_or_default();
   match sv.build.by_version(version).await {
-    Ok(Some(build)) if build.is_active => {
-      let path = Path::new(&build.file_path);
+    Ok(Some(build))
+      if build.is_active
+        && owned.contains(&build.product)
+        && channel.serves(build.channel) =>
+    {
+      let artifacts =
+        sv.build.artifacts(&build.version).await.unwrap_or_default();
+      if platform.is_none() && !artifacts.is_empty() {
+        return platform_picker(bot, &build, &artifacts).await;
+      }
+      let Ok(Some((file_path, checksum))) =
+        sv.build.file_for(&build, platform).await
+      else {
+        bot
+          .edit_with_keyboard(
+            bot.lang.t(T::NoPlatformBuild),
+            back_keyboard(bot.lang),
+          )
+          .await?;
+        return Ok(());
+      };
+
+      let path = Path::new(&file_path);
       if path.exists() {
-        let token = app.create_download_token(&build.version);
+        let token = match app
+          .create_download_token(bot.user_id, &build.version, platform)
+          .await
+        {
+          Ok(token) => token,
+          Err(e) => {
+            let text = i18n::fill(
+              bot.lang.t(T::LinkFailed),
+              &[("error", e.user_message())],
+            );
+            bot.edit_with_keyboard(text, back_keyboard(bot.lang)).await?;
+            return Ok(());
+          }
+        };
         let download_url =
           format!("{}/api/download?token={}", app.config.base_url, token);
 
-        let text = format!(
-          "<b>YACS Panel v{}</b>\n\n\
-          {}\n\n\
-          📥 <a href=\"{}\">Click here to download</a>\n\n\
-          <i>⚠️ Link expires in 10 minutes</i>",
-          build.version,
-          build.changelog.as_deref().unwrap_or(""),
-          download_url
+        let name = match sv.product.get(&build.product).await {
+          Ok(product) => product.name,
+          Err(_) => "YACS Panel".to_string(),
+        };
+        let checksum = checksum
+          .map(|sum| format!("🔒 SHA-256: <code>{}</code>\n\n", sum))
+          .unwrap_or_default();
+        let text = i18n::fill(
+          bot.lang.t(T::DownloadLink),
+          &[
+            ("version", build.version.clone()),
+            ("platform", platform.unwrap_or(&build.platform).to_string()),
+            ("url", download_url),
+            ("checksum", checksum),
+            ("name", html::escape(&name)),
+            ("changelog", build.changelog.clone().unwrap_or_default()),
+          ],
         );
 
-        bot.edit_with_keyboard(text, back_keyboard()).await?;
+        bot.edit_without_preview(text, back_keyboard(bot.lang)).await?;
       } else {
         bot
           .edit_with_keyboard(
-            "❌ Build file not found. Contact support.",
-            back_keyboard(),
+            bot.lang.t(T::BuildFileMissing),
+            back_keyboard(bot.lang),
           )
           .await?;
       }
@@ -748,8 +2517,8 @@ async fn handle_download_version(
     _ => {
       bot
         .edit_with_keyboard(
-          "❌ Build not available. Contact support.",
-          back_keyboard(),
+          bot.lang.t(T::BuildUnavailable),
+          back_keyboard(bot.lang),
         )
         .await?;
     }
@@ -758,139 +2527,281 @@ async fn handle_download_version(
   Ok(())
 }
 
-/// Price constants in USDT
-const DAY_TRIAL_PRICE: f64 = 1.0;
-const MONTH_PRICE: f64 = 10.0;
-const QUARTER_PRICE: f64 = 25.0;
+/// Let the user pick one of the platforms a version was built for
+async fn platform_picker(
+  bot: &ReplyBot,
+  build: &build::Model,
+  artifacts: &[build_artifact::Model],
+) -> ResponseResult<()> {
+  let platforms = std::iter::once(build.platform.as_str())
+    .chain(artifacts.iter().map(|artifact| artifact.platform.as_str()));
+  let mut rows: Vec<_> = platforms
+    .map(|platform| {
+      vec![InlineKeyboardButton::callback(
+        format!("💻 {}", platform),
+        Callback::DownloadArtifact {
+          version: build.version.clone(),
+          platform: platform.to_string(),
+        }
+        .to_data(),
+      )]
+    })
+    .collect();
+  rows.push(vec![InlineKeyboardButton::callback(
+    bot.lang.t(T::BackToMenu),
+    Callback::Back.to_data(),
+  )]);
+
+  let text = i18n::fill(
+    bot.lang.t(T::ChoosePlatform),
+    &[("version", html::escape(&build.version))],
+  );
+  bot.edit_with_keyboard(text, InlineKeyboardMarkup::new(rows)).await?;
+  Ok(())
+}
+
+/// Price of a quote with the undiscounted price struck through
+fn quote_price(lang: Lang, quote: &Quote) -> String {
+  let price = quote.price as f64 / NANO_USDT as f64;
+  if quote.price >= quote.base {
+    return format!("<b>{:.2} USDT</b>", price);
+  }
+  let off = 100 - quote.price * 100 / quote.base;
+  format!(
+    "<s>{:.2}</s> <b>{:.2} USDT</b> {}",
+    quote.base as f64 / NANO_USDT as f64,
+    price,
+    i18n::fill(lang.t(T::PercentOff), &[("percent", off.to_string())])
+  )
+}
+
+/// Quotes for `plans`, in the same order, plans that are not sold are left out
+async fn quotes(
+  sv: &Services<'_>,
+  user_id: i64,
+  product: &str,
+  plans: &[Plan],
+  extension: bool,
+) -> Result<Vec<Quote>> {
+  let mut quotes = Vec::with_capacity(plans.len());
+  for &plan in plans {
+    let quote = if extension {
+      sv.pricing.quote_extension(user_id, product, plan).await
+    } else {
+      sv.pricing.quote(user_id, product, plan).await
+    };
+    match quote {
+      Ok(quote) => quotes.push(quote),
+      Err(Error::PlanUnavailable(_)) => {}
+      Err(e) => return Err(e),
+    }
+  }
+  Ok(quotes)
+}
+
+/// Product picker, shown instead of the plans when there are several
+async fn handle_product_picker(
+  bot: &ReplyBot,
+  products: &[product::Model],
+) -> ResponseResult<()> {
+  let mut rows: Vec<_> = products
+    .iter()
+    .map(|product| {
+      vec![InlineKeyboardButton::callback(
+        format!("📦 {}", product.name),
+        Callback::BuyProduct(product.slug.clone()).to_data(),
+      )]
+    })
+    .collect();
+  rows.push(vec![InlineKeyboardButton::callback(
+    bot.lang.t(T::ExtendLicenseButton),
+    Callback::ExtendLicense.to_data(),
+  )]);
+  rows.push(vec![InlineKeyboardButton::callback(
+    bot.lang.t(T::BackToMenu),
+    Callback::Back.to_data(),
+  )]);
 
-/// Nano USDT price constants
-const DAY_TRIAL_PRICE_NANO: i64 = NANO_USDT;
-const MONTH_PRICE_NANO: i64 = 10 * NANO_USDT;
-const QUARTER_PRICE_NANO: i64 = 25 * NANO_USDT;
+  let text = bot.lang.t(T::PickProduct);
+  bot.edit_with_keyboard(text, InlineKeyboardMarkup::new(rows)).await
+}
 
 async fn handle_buy_menu(
   sv: &Services<'_>,
   bot: &ReplyBot,
+  product: Option<String>,
 ) -> ResponseResult<()> {
+  let products = sv.product.all().await.unwrap_or_default();
+  let product = match product {
+    Some(product) => product,
+    None if products.len() > 1 => {
+      return handle_product_picker(bot, &products).await;
+    }
+    None => DEFAULT_PRODUCT.to_string(),
+  };
+
   let user = sv.user.by_id(bot.user_id).await.ok().flatten();
   let balance = user.as_ref().map(|u| u.balance).unwrap_or(0);
   let referred_by = user.as_ref().and_then(|u| u.referred_by);
   let balance_str = format_usdt(balance);
 
-  let discount_percent: i32 = sv.referral.discount_percent(referred_by).await;
-
-  let (month_price, quarter_price) = if discount_percent > 0 {
-    let month_discounted =
-      MONTH_PRICE * (100 - discount_percent) as f64 / 100.0;
-    let quarter_discounted =
-      QUARTER_PRICE * (100 - discount_percent) as f64 / 100.0;
-    (month_discounted, quarter_discounted)
-  } else {
-    (MONTH_PRICE, QUARTER_PRICE)
+  let quotes = match quotes(sv, bot.user_id, &product, &Plan::PURCHASE, false)
+    .await
+  {
+    Ok(quotes) => quotes,
+    Err(e) => {
+      let text =
+        i18n::fill(bot.lang.t(T::PricesFailed), &[("error", e.user_message())]);
+      bot.edit_with_keyboard(text, back_keyboard(bot.lang)).await?;
+      return Ok(());
+    }
   };
+  let Some(first) = quotes.first() else {
+    let text = bot.lang.t(T::NoPlansOnSale);
+    bot.edit_with_keyboard(text, back_keyboard(bot.lang)).await?;
+    return Ok(());
+  };
+  let trial = quotes.iter().find(|q| q.plan.is_trial());
+  let paid: Vec<_> = quotes.iter().filter(|q| !q.plan.is_trial()).collect();
 
-  let month_nano = (month_price * NANO_USDT as f64) as i64;
-  let quarter_nano = (quarter_price * NANO_USDT as f64) as i64;
-
-  let can_buy_trial = balance >= DAY_TRIAL_PRICE_NANO;
-  let can_buy_month = balance >= month_nano;
-  let can_buy_quarter = balance >= quarter_nano;
-
-  let mut text = format!(
-    "💳 <b>Buy License</b>\n\n\
-    <b>Your Balance:</b> {}\n\n\
-    <b>🧪 Try it first:</b>\n\
-    • 1 Day Trial: <b>{DAY_TRIAL_PRICE:.2} USDT</b>\n\n\
-    <b>Pricing:</b>\n",
-    balance_str
+  let title = match products.len() {
+    0 | 1 => String::new(),
+    _ => format!(" · {}", html::escape(&first.product.name)),
+  };
+  let lang = bot.lang;
+  let mut text = i18n::fill(
+    lang.t(T::BuyHeader),
+    &[("balance", balance_str), ("title", title)],
   );
+  if let Some(trial) = trial {
+    text.push_str(&i18n::fill(
+      lang.t(T::TryFirst),
+      &[("price", quote_price(lang, trial)), ("plan", trial.title.clone())],
+    ));
+  }
+  text.push_str(lang.t(T::Pricing));
 
-  if discount_percent > 0 {
+  for quote in &paid {
+    text.push_str(&format!(
+      "• {}: {}\n",
+      quote.title,
+      quote_price(bot.lang, quote)
+    ));
+    if let Some((from, to)) = quote.plan.schedule() {
+      text.push_str(&i18n::fill(
+        lang.t(T::NightHours),
+        &[("from", format!("{:02}", from)), ("to", format!("{:02}", to))],
+      ));
+    }
+  }
+
+  let referral_percent =
+    paid.iter().map(|q| q.referral_percent).max().unwrap_or(0);
+  if referral_percent > 0
+    && let Some(referrer) = referred_by
+  {
     let display_code = sv
       .referral
-      .display_code(referred_by.unwrap())
+      .display_code(referrer)
       .await
       .unwrap_or_else(|| "[referral]".into());
-
-    text.push_str(&format!(
-      "• 1 Month: <s>{MONTH_PRICE:.2}</s> <b>{month_price:.2} USDT</b> ({discount_percent}% off)\n\
-       • 3 Months: <s>{QUARTER_PRICE:.2}</s> <b>{quarter_price:.2} USDT</b> ({discount_percent}% off)\n\n\
-       <i>🎉 Discount from referral code <code>{display_code}</code></i>\n",
+    text.push_str(&i18n::fill(
+      lang.t(T::ReferralDiscount),
+      &[("code", display_code)],
     ));
-  } else {
-    text.push_str(&format!(
-      "• 1 Month: <b>{month_price:.2} USDT</b>\n\
-       • 3 Months: <b>{quarter_price:.2} USDT</b>\n",
+  }
+  let offer_percent = paid.iter().map(|q| q.offer_percent).max().unwrap_or(0);
+  if offer_percent > 0 {
+    text.push_str(&i18n::fill(
+      lang.t(T::StarterOffer),
+      &[("percent", offer_percent.to_string())],
+    ));
+  }
+  if let Some(region) = paid.iter().find_map(|q| q.region.as_ref()) {
+    text.push_str(&i18n::fill(
+      lang.t(T::RegionalPrice),
+      &[("country", region.country.clone())],
     ));
   }
 
-  if can_buy_trial {
-    text.push_str("\n<i>Select a plan to purchase with your balance:</i>");
+  let cheapest = quotes.iter().min_by_key(|q| q.price).unwrap_or(first);
+  if balance >= cheapest.price {
+    text.push_str(lang.t(T::SelectPlan));
   } else {
-    text.push_str(&format!(
-      "\n<i>💡 You need {} more to buy a trial license.</i>",
-      format_usdt(DAY_TRIAL_PRICE_NANO - balance)
+    let need = match cheapest.plan.is_trial() {
+      true => T::NeedMoreTrial,
+      false => T::NeedMore,
+    };
+    text.push_str(&i18n::fill(
+      lang.t(need),
+      &[("amount", format_usdt(cheapest.price - balance))],
     ));
   }
 
   if referred_by.is_none() {
-    text.push_str("\n\n<i>💡 Tip: Set a referral code to get a discount on monthly plans!</i>");
+    text.push_str(lang.t(T::SetRefTip));
   }
 
   let mut rows = Vec::new();
 
-  // Trial button (no discount applied)
-  if can_buy_trial {
-    rows.push(vec![InlineKeyboardButton::callback(
-      format!("🧪 1 Day Trial ({:.2} USDT)", DAY_TRIAL_PRICE),
-      Callback::BuyPlan("trial".to_string()).to_data(),
-    )]);
-  }
-
   // Buy buttons (only enabled if sufficient balance)
-  if can_buy_month {
-    rows.push(vec![InlineKeyboardButton::callback(
-      format!("📅 1 Month ({:.2} USDT)", month_price),
-      Callback::BuyPlan("month".to_string()).to_data(),
-    )]);
-  }
-  if can_buy_quarter {
+  for quote in &quotes {
+    if balance < quote.price {
+      continue;
+    }
+    let icon = match quote.plan {
+      Plan::Trial => "🧪",
+      Plan::Night => "🌙",
+      _ => "📅",
+    };
     rows.push(vec![InlineKeyboardButton::callback(
-      format!("📅 3 Months ({:.2} USDT)", quarter_price),
-      Callback::BuyPlan("quarter".to_string()).to_data(),
+      format!(
+        "{} {} ({:.2} USDT)",
+        icon,
+        quote.title,
+        quote.price as f64 / NANO_USDT as f64
+      ),
+      Callback::BuyPlan {
+        product: quote.product.slug.clone(),
+        plan: quote.plan.as_str().to_string(),
+      }
+      .to_data(),
     )]);
   }
 
   // Extend existing license button
   rows.push(vec![InlineKeyboardButton::callback(
-    "🔄 Extend License",
+    lang.t(T::ExtendLicenseButton),
     Callback::ExtendLicense.to_data(),
   )]);
 
   // Add funds button
   rows.push(vec![InlineKeyboardButton::callback(
-    "💵 Add Funds",
+    lang.t(T::MenuFunds),
     Callback::AddFunds.to_data(),
   )]);
 
   if referred_by.is_none() {
     rows.push(vec![InlineKeyboardButton::callback(
-      "🔗 Set Referral Code",
+      lang.t(T::SetRefButton),
       Callback::SetRef.to_data(),
     )]);
   }
 
   // Other options
   rows.push(vec![
-    InlineKeyboardButton::callback("👤 Manual", Callback::PayManual.to_data()),
     InlineKeyboardButton::callback(
-      "🔑 Link Key",
+      lang.t(T::ManualButton),
+      Callback::PayManual.to_data(),
+    ),
+    InlineKeyboardButton::callback(
+      lang.t(T::LinkKeyButton),
       Callback::HaveLicense.to_data(),
     ),
   ]);
 
   rows.push(vec![InlineKeyboardButton::callback(
-    "« Back to Menu",
+    lang.t(T::BackToMenu),
     Callback::Back.to_data(),
   )]);
 
@@ -898,124 +2809,178 @@ async fn handle_buy_menu(
   Ok(())
 }
 
-async fn handle_buy_plan(
+pub async fn handle_buy_plan(
+  app: &AppState,
   sv: &Services<'_>,
   bot: &ReplyBot,
+  product: &str,
   plan: &str,
+  rules: &Rules,
 ) -> ResponseResult<()> {
   let user = sv.user.by_id(bot.user_id).await.ok().flatten();
   let balance = user.as_ref().map(|u| u.balance).unwrap_or(0);
   let referred_by = user.as_ref().and_then(|u| u.referred_by);
 
-  let discount_percent: i32 = sv.referral.discount_percent(referred_by).await;
-
-  // Trial plan is not affected by discounts - fixed $1 price
-  let (price, days, plan_name, is_trial) = match plan {
-    "trial" => (DAY_TRIAL_PRICE_NANO, 1u64, "1 Day Trial", true),
-    "month" => {
-      let price = if discount_percent > 0 {
-        MONTH_PRICE_NANO * (100 - discount_percent) as i64 / 100
-      } else {
-        MONTH_PRICE_NANO
-      };
-      (price, 30u64, "1 Month", false)
-    }
-    "quarter" => {
-      let price = if discount_percent > 0 {
-        QUARTER_PRICE_NANO * (100 - discount_percent) as i64 / 100
-      } else {
-        QUARTER_PRICE_NANO
+  let Some(plan) = Plan::parse(plan) else {
+    bot
+      .edit_with_keyboard(bot.lang.t(T::InvalidPlan), back_keyboard(bot.lang))
+      .await?;
+    return Ok(());
+  };
+  if let Err(e) =
+    sv.eligibility.check(bot.user_id, Purchase::New(plan), rules).await
+  {
+    if let Error::Ineligible(Ineligible::HighRisk(score)) = e {
+      let action = Action::Purchase {
+        product: product.to_string(),
+        plan: plan.as_str().to_string(),
+        chat_id: bot.chat_id.0,
+        message_id: bot.message_id.0,
       };
-      (price, 90u64, "3 Months", false)
+      return review::hold(app, bot, action, score).await;
     }
-    _ => {
-      bot.edit_with_keyboard("❌ Invalid plan.", back_keyboard()).await?;
+    let text = format!("❌ {}", e.user_message());
+    bot.edit_with_keyboard(text, back_keyboard(bot.lang)).await?;
+    return Ok(());
+  }
+  let quote = match sv.pricing.quote(bot.user_id, product, plan).await {
+    Ok(quote) => quote,
+    Err(e) => {
+      let text =
+        i18n::fill(bot.lang.t(T::PricesFailed), &[("error", e.user_message())]);
+      bot.edit_with_keyboard(text, back_keyboard(bot.lang)).await?;
       return Ok(());
     }
   };
+  let price = quote.price;
 
   if balance < price {
     let needed = price - balance;
-    let text = format!(
-      "❌ <b>Insufficient Balance</b>\n\n\
-      <b>Required:</b> {}\n\
-      <b>Your balance:</b> {}\n\
-      <b>Needed:</b> {}\n\n\
-      <i>Add funds to your balance to purchase this plan.</i>",
-      format_usdt(price),
-      format_usdt(balance),
-      format_usdt(needed)
+    let text = i18n::fill(
+      bot.lang.t(T::InsufficientPurchase),
+      &[
+        ("price", format_usdt(price)),
+        ("balance", format_usdt(balance)),
+        ("needed", format_usdt(needed)),
+      ],
     );
     let kb = InlineKeyboardMarkup::new(vec![
       vec![InlineKeyboardButton::callback(
-        "💵 Add Funds",
+        bot.lang.t(T::MenuFunds),
         Callback::AddFunds.to_data(),
       )],
-      vec![InlineKeyboardButton::callback("« Back", Callback::Buy.to_data())],
+      vec![InlineKeyboardButton::callback(
+        bot.lang.t(T::Back),
+        Callback::Buy.to_data(),
+      )],
     ]);
     bot.edit_with_keyboard(text, kb).await?;
     return Ok(());
   }
 
   // For trial plan, don't pass referrer (no commission for trial purchases)
-  let spend_referrer = if is_trial { None } else { referred_by };
+  let spend_referrer = if plan.is_trial() { None } else { referred_by };
 
   // Purchase the license
   match sv
     .balance
-    .spend(
+    .spend_in(
       bot.user_id,
       price,
-      Some(format!("License purchase: {}", plan_name)),
+      Some(format!("License purchase: {}", quote.title)),
       spend_referrer,
+      quote.region_code(),
     )
     .await
   {
     Ok(new_balance) => {
+      if quote.offer_percent > 0 {
+        let _ = sv.upgrade_offers.redeem(bot.user_id).await;
+      }
       // If user was referred and this is NOT a trial, process referral commission
-      if !is_trial && let Some(referrer_id) = referred_by {
-        let _ = sv.referral.record_sale(referrer_id, price).await;
-        // Add commission to referrer's balance
-        let referrer_user = sv.user.by_id(referrer_id).await.ok().flatten();
-        if let Some(referrer) = referrer_user {
-          let commission = price * referrer.commission_rate as i64 / 100;
-          let _ = sv
-            .balance
-            .add_referral_bonus(referrer_id, commission, bot.user_id)
-            .await;
-        }
+      if let Some(referrer_id) = spend_referrer {
+        let _ = sv
+          .referral
+          .pay_commission(referrer_id, Some(bot.user_id), price)
+          .await;
       }
 
       // Generate license (use Pro type for paid trial as well)
       match sv
         .license
-        .create(bot.user_id, crate::entity::license::LicenseType::Pro, days)
+        .create_term(
+          bot.user_id,
+          crate::entity::license::LicenseType::Pro,
+          quote.term,
+        )
         .await
       {
         Ok(license) => {
-          let text = format!(
-            "✅ <b>Purchase Successful!</b>\n\n\
-            <b>Plan:</b> {}\n\
-            <b>License Key:</b> <code>{}</code>\n\
-            <b>Expires:</b> {}\n\n\
-            <b>New Balance:</b> {}\n\n\
-            <i>You can now download the panel!</i>",
-            plan_name,
-            license.key,
-            crate::utils::format_date(license.expires_at),
-            format_usdt(new_balance)
+          let mut notes = String::new();
+          if quote.max_sessions != license.max_sessions {
+            let _ = sv
+              .license
+              .set_max_sessions(&license.key, quote.max_sessions)
+              .await;
+            notes.push_str(&i18n::fill(
+              bot.lang.t(T::NoteSessions),
+              &[("count", quote.max_sessions.to_string())],
+            ));
+          }
+          if quote.product.slug != DEFAULT_PRODUCT {
+            let _ =
+              sv.license.set_product(&license.key, &quote.product.slug).await;
+            notes.push_str(&i18n::fill(
+              bot.lang.t(T::NoteProduct),
+              &[("name", html::escape(&quote.product.name))],
+            ));
+          }
+          if let Some((from, to)) = plan.schedule() {
+            let _ =
+              sv.license.set_schedule(&license.key, Some((from, to))).await;
+            notes.push_str(&i18n::fill(
+              bot.lang.t(T::NoteSchedule),
+              &[("from", format!("{:02}", from)), ("to", format!("{:02}", to))],
+            ));
+          }
+          if let Some(region) = quote.region_code() {
+            let _ =
+              sv.license.set_region(&license.key, Some(region.clone())).await;
+            notes.push_str(&i18n::fill(
+              bot.lang.t(T::NoteRegion),
+              &[("region", region)],
+            ));
+          }
+          let text = i18n::fill(
+            bot.lang.t(T::Purchased),
+            &[
+              ("plan", quote.title.clone()),
+              ("key", license.key.clone()),
+              ("expires", crate::utils::format_date(license.expires_at)),
+              ("balance", format_usdt(new_balance)),
+              ("notes", notes),
+            ],
           );
           let kb = InlineKeyboardMarkup::new(vec![
             vec![InlineKeyboardButton::callback(
-              "📥 Download Panel",
+              bot.lang.t(T::MenuDownload),
               Callback::Download.to_data(),
             )],
             vec![InlineKeyboardButton::callback(
-              "« Back to Menu",
+              bot.lang.t(T::BackToMenu),
               Callback::Back.to_data(),
             )],
           ]);
           bot.edit_with_keyboard(text, kb).await?;
+          support::ask_rating(
+            sv,
+            &bot.inner,
+            bot.user_id,
+            RatingKind::Purchase,
+            &license.key,
+            bot.lang.t(T::RatePurchase),
+          )
+          .await;
         }
         Err(e) => {
           // Refund on failure
@@ -1027,15 +2992,20 @@ async fn handle_buy_plan(
               Some("Refund: license creation failed".into()),
             )
             .await;
-          let text =
-            format!("❌ Failed to create license: {}", e.user_message());
-          bot.edit_with_keyboard(text, back_keyboard()).await?;
+          let text = i18n::fill(
+            bot.lang.t(T::LicenseCreateFailed),
+            &[("error", e.user_message())],
+          );
+          bot.edit_with_keyboard(text, back_keyboard(bot.lang)).await?;
         }
       }
     }
     Err(e) => {
-      let text = format!("❌ Failed to process payment: {}", e.user_message());
-      bot.edit_with_keyboard(text, back_keyboard()).await?;
+      let text = i18n::fill(
+        bot.lang.t(T::PaymentFailed),
+        &[("error", e.user_message())],
+      );
+      bot.edit_with_keyboard(text, back_keyboard(bot.lang)).await?;
     }
   }
 
@@ -1049,59 +3019,65 @@ async fn handle_add_funds(
 ) -> ResponseResult<()> {
   let user = sv.user.by_id(bot.user_id).await.ok().flatten();
   let balance = user.as_ref().map(|u| u.balance).unwrap_or(0);
-  let referred_by = user.as_ref().and_then(|u| u.referred_by);
 
-  let discount_percent: i32 = sv.referral.discount_percent(referred_by).await;
-
-  let (month_price, quarter_price) = if discount_percent > 0 {
-    let month_discounted =
-      MONTH_PRICE * (100 - discount_percent) as f64 / 100.0;
-    let quarter_discounted =
-      QUARTER_PRICE * (100 - discount_percent) as f64 / 100.0;
-    (month_discounted, quarter_discounted)
-  } else {
-    (MONTH_PRICE, QUARTER_PRICE)
+  let quotes = quotes(
+    sv,
+    bot.user_id,
+    DEFAULT_PRODUCT,
+    &[Plan::Month, Plan::Quarter],
+    false,
+  )
+  .await
+  .unwrap_or_default();
+  let price_of = |plan: Plan| {
+    let price = quotes
+      .iter()
+      .find(|q| q.plan == plan)
+      .map_or(plan.base_price(), |q| q.price);
+    price as f64 / NANO_USDT as f64
   };
+  let (month_price, quarter_price) =
+    (price_of(Plan::Month), price_of(Plan::Quarter));
+  let discount_percent =
+    quotes.iter().map(|q| q.referral_percent).max().unwrap_or(0);
 
-  let has_cryptobot = app.cryptobot.is_some();
+  // manual mode while CryptoBot is missing or its circuit is open
+  let has_cryptobot = app.cryptobot.as_ref().is_some_and(|c| c.is_available());
 
   let pending =
     sv.payment.pending_by_user(bot.user_id).await.unwrap_or_default();
   let pending_count = pending.len();
 
-  let mut text = format!(
-    "💵 <b>Add Funds</b>\n\n\
-    <b>Your Balance:</b> {}\n\n\
-    <b>Quick amounts:</b>\n\
-    • {:.2} USDT (1 month license)\n\
-    • {:.2} USDT (3 month license)\n",
-    format_usdt(balance),
-    month_price,
-    quarter_price
+  let lang = bot.lang;
+  let mut text = i18n::fill(
+    lang.t(T::AddFunds),
+    &[
+      ("balance", format_usdt(balance)),
+      ("month", format!("{:.2}", month_price)),
+      ("quarter", format!("{:.2}", quarter_price)),
+    ],
   );
 
   if discount_percent > 0 {
-    text.push_str(&format!(
-      "\n<i>🎉 {}% discount available from referral!</i>\n",
-      discount_percent
+    text.push_str(&i18n::fill(
+      lang.t(T::ReferralDiscountAvailable),
+      &[("percent", discount_percent.to_string())],
     ));
   }
 
   if pending_count > 0 {
-    text.push_str(&format!(
-      "\n<i>⏳ You have {} pending payment(s).</i>\n",
-      pending_count
+    text.push_str(&i18n::fill(
+      lang.t(T::PendingPayments),
+      &[("count", pending_count.to_string())],
     ));
   }
 
   if has_cryptobot {
-    text.push_str(
-      "\n<i>Select an amount or use /fund AMOUNT for custom amounts.</i>",
-    );
+    text.push_str(lang.t(T::SelectAmount));
+  } else if app.cryptobot.is_some() {
+    text.push_str(lang.t(T::CryptoDown));
   } else {
-    text.push_str(
-      "\n<i>⚠️ Automatic payments are being configured.\nContact support for manual deposits.</i>",
-    );
+    text.push_str(lang.t(T::PaymentsConfiguring));
   }
 
   let mut rows = Vec::new();
@@ -1118,27 +3094,28 @@ async fn handle_add_funds(
       ),
     ]);
     rows.push(vec![InlineKeyboardButton::callback(
-      "💵 Custom Amount",
+      bot.lang.t(T::CustomAmountButton),
       Callback::PayCustomAmount.to_data(),
     )]);
   }
 
   if pending_count > 0 {
     rows.push(vec![InlineKeyboardButton::callback(
-      "🔄 Check Payments",
+      bot.lang.t(T::BalanceCheck),
       Callback::CheckPayments.to_data(),
     )]);
   }
 
   if !has_cryptobot {
     rows.push(vec![InlineKeyboardButton::url(
-      "📞 Contact Support",
-      Url::parse("https://t.me/y_a_c_s_p").expect("invalid url"),
+      bot.lang.t(T::ContactSupport),
+      Url::parse(&format!("https://t.me/{}", bot.support()))
+        .expect("invalid url"),
     )]);
   }
 
   rows.push(vec![InlineKeyboardButton::callback(
-    "« Back to Menu",
+    bot.lang.t(T::BackToMenu),
     Callback::Back.to_data(),
   )]);
 
@@ -1155,8 +3132,8 @@ async fn handle_pay_crypto_amount(
   let Some(cryptobot) = &app.cryptobot else {
     bot
       .edit_with_keyboard(
-        "❌ CryptoBot payments are not configured. Contact support.",
-        back_keyboard(),
+        bot.lang.t(T::CryptoNotConfigured),
+        back_keyboard(bot.lang),
       )
       .await?;
     return Ok(());
@@ -1165,7 +3142,12 @@ async fn handle_pay_crypto_amount(
   let amount_usdt: f64 = match amount.parse() {
     Ok(a) => a,
     Err(_) => {
-      bot.edit_with_keyboard("❌ Invalid amount.", back_keyboard()).await?;
+      bot
+        .edit_with_keyboard(
+          bot.lang.t(T::InvalidAmount),
+          back_keyboard(bot.lang),
+        )
+        .await?;
       return Ok(());
     }
   };
@@ -1185,26 +3167,22 @@ async fn handle_pay_crypto_amount(
         .save_pending(invoice.invoice_id, bot.user_id, amount_usdt, referred_by)
         .await;
 
-      let text = format!(
-        "💳 <b>Payment Invoice Created</b>\n\n\
-        <b>Amount:</b> {} USDT\n\n\
-        Click the button below to pay via CryptoBot.\n\
-        The invoice expires in 1 hour.\n\n\
-        <i>After payment, click \"Check Payments\" to update your balance.</i>",
-        amount
+      let text = i18n::fill(
+        bot.lang.t(T::InvoiceCreated),
+        &[("amount", amount.to_string())],
       );
 
       let kb = InlineKeyboardMarkup::new(vec![
         vec![InlineKeyboardButton::url(
-          "💵 Pay Now",
+          bot.lang.t(T::PayNow),
           Url::parse(&invoice.bot_invoice_url).expect("invalid invoice url"),
         )],
         vec![InlineKeyboardButton::callback(
-          "🔄 Check Payments",
+          bot.lang.t(T::BalanceCheck),
           Callback::CheckPayments.to_data(),
         )],
         vec![InlineKeyboardButton::callback(
-          "« Back",
+          bot.lang.t(T::Back),
           Callback::AddFunds.to_data(),
         )],
       ]);
@@ -1212,14 +3190,14 @@ async fn handle_pay_crypto_amount(
       bot.edit_with_keyboard(text, kb).await?;
     }
     Err(e) => {
-      let text = format!(
-        "❌ Failed to create invoice: {}\n\n\
-        Please try again or contact support.",
-        e.user_message()
+      let mut text = i18n::fill(
+        bot.lang.t(T::InvoiceFailed),
+        &[("error", e.user_message())],
       );
+      text.push_str(bot.lang.t(T::TryAgainOrSupport));
       let kb =
         InlineKeyboardMarkup::new(vec![vec![InlineKeyboardButton::callback(
-          "« Back",
+          bot.lang.t(T::Back),
           Callback::AddFunds.to_data(),
         )]]);
       bot.edit_with_keyboard(text, kb).await?;
@@ -1237,33 +3215,36 @@ async fn handle_check_payments(
   let Some(cryptobot) = &app.cryptobot else {
     bot
       .edit_with_keyboard(
-        "❌ Payment verification is not configured.",
-        back_keyboard(),
+        bot.lang.t(T::VerificationNotConfigured),
+        back_keyboard(bot.lang),
       )
       .await?;
     return Ok(());
   };
 
+  if !cryptobot.is_available() {
+    bot
+      .edit_with_keyboard(bot.lang.t(T::ChecksPaused), back_keyboard(bot.lang))
+      .await?;
+    return Ok(());
+  }
+
   // Check for paid invoices and process them
   match sv.payment.check_and_process(cryptobot, bot.user_id).await {
     Ok(results) if !results.is_empty() => {
       let total: i64 = results.iter().map(|r| r.amount_nano).sum();
       let total_str = format_usdt(total);
 
-      let text = format!(
-        "✅ <b>Payment Received!</b>\n\n\
-        <b>{}</b> has been added to your balance.\n\n\
-        <i>Use your balance to purchase licenses in the Buy menu.</i>",
-        total_str
-      );
+      let text =
+        i18n::fill(bot.lang.t(T::PaymentReceived), &[("amount", total_str)]);
 
       let kb = InlineKeyboardMarkup::new(vec![
         vec![InlineKeyboardButton::callback(
-          "💳 Buy License",
+          bot.lang.t(T::MenuBuy),
           Callback::Buy.to_data(),
         )],
         vec![InlineKeyboardButton::callback(
-          "« Back to Menu",
+          bot.lang.t(T::BackToMenu),
           Callback::Back.to_data(),
         )],
       ]);
@@ -1276,50 +3257,41 @@ async fn handle_check_payments(
         sv.payment.pending_by_user(bot.user_id).await.unwrap_or_default();
 
       let text = if pending.is_empty() {
-        "📭 <b>No Pending Payments</b>\n\n\
-        You have no pending invoices.\n\
-        Create a new invoice to add funds to your balance."
-          .to_string()
+        bot.lang.t(T::NoPendingPayments).to_string()
       } else {
-        format!(
-          "⏳ <b>Waiting for Payment</b>\n\n\
-          You have {} pending invoice(s).\n\
-          Complete the payment in CryptoBot, then click \"Check Payments\" again.\n\n\
-          <i>Invoices expire after 1 hour.</i>",
-          pending.len()
+        i18n::fill(
+          bot.lang.t(T::WaitingPayment),
+          &[("count", pending.len().to_string())],
         )
       };
 
       let mut rows = Vec::new();
       if !pending.is_empty() {
         rows.push(vec![InlineKeyboardButton::callback(
-          "🔄 Check Payments",
+          bot.lang.t(T::BalanceCheck),
           Callback::CheckPayments.to_data(),
         )]);
       }
       rows.push(vec![InlineKeyboardButton::callback(
-        "💵 Add Funds",
+        bot.lang.t(T::MenuFunds),
         Callback::AddFunds.to_data(),
       )]);
       rows.push(vec![InlineKeyboardButton::callback(
-        "« Back to Menu",
+        bot.lang.t(T::BackToMenu),
         Callback::Back.to_data(),
       )]);
 
       bot.edit_with_keyboard(text, InlineKeyboardMarkup::new(rows)).await?;
     }
     Err(e) => {
-      let text = format!(
-        "❌ Failed to check payments: {}\n\n\
-        Please try again later.",
-        e.user_message()
-      );
+      let text =
+        i18n::fill(bot.lang.t(T::CheckFailed), &[("error", e.user_message())]);
       bot
         .edit_with_keyboard(
           text,
           InlineKeyboardMarkup::new(vec![vec![
             InlineKeyboardButton::callback(
-              "🔄 Try Again",
+              bot.lang.t(T::TryAgain),
               Callback::CheckPayments.to_data(),
             ),
           ]]),
@@ -1339,15 +3311,16 @@ async fn handle_extend_license_menu(
     sv.license.by_user(bot.user_id, false).await.unwrap_or_default();
 
   if licenses.is_empty() {
-    let text = "❌ <b>No Licenses Found</b>\n\n\
-      You don't have any licenses to extend.\n\
-      Purchase a new license first.";
+    let text = bot.lang.t(T::NoLicensesToExtend);
     let kb = InlineKeyboardMarkup::new(vec![
       vec![InlineKeyboardButton::callback(
-        "💳 Buy License",
+        bot.lang.t(T::MenuBuy),
+        Callback::Buy.to_data(),
+      )],
+      vec![InlineKeyboardButton::callback(
+        bot.lang.t(T::Back),
         Callback::Buy.to_data(),
       )],
-      vec![InlineKeyboardButton::callback("« Back", Callback::Buy.to_data())],
     ]);
     bot.edit_with_keyboard(text, kb).await?;
     return Ok(());
@@ -1357,19 +3330,17 @@ async fn handle_extend_license_menu(
   let balance = user.as_ref().map(|u| u.balance).unwrap_or(0);
   let now = Utc::now().naive_utc();
 
-  let mut text = format!(
-    "🔄 <b>Extend License</b>\n\n\
-    <b>Your Balance:</b> {}\n\n\
-    <b>Select a license to extend:</b>\n",
-    format_usdt(balance)
+  let mut text = i18n::fill(
+    bot.lang.t(T::ExtendHeader),
+    &[("balance", format_usdt(balance))],
   );
 
   let mut rows = Vec::new();
   for license in &licenses {
     let status = if license.expires_at > now {
-      format!("⏳ {}", crate::utils::format_duration(license.expires_at - now))
+      format!("⏳ {}", bot.lang.duration(license.expires_at - now))
     } else {
-      "❌ Expired".into()
+      bot.lang.t(T::Expired).into()
     };
 
     text.push_str(&format!(
@@ -1386,7 +3357,7 @@ async fn handle_extend_license_menu(
   }
 
   rows.push(vec![InlineKeyboardButton::callback(
-    "« Back",
+    bot.lang.t(T::Back),
     Callback::Buy.to_data(),
   )]);
 
@@ -1394,6 +3365,7 @@ async fn handle_extend_license_menu(
   Ok(())
 }
 
+/// Plans a license can be extended with, night keys stay night keys
 async fn handle_extend_license_key(
   sv: &Services<'_>,
   bot: &ReplyBot,
@@ -1402,99 +3374,127 @@ async fn handle_extend_license_key(
   let license = match sv.license.by_key(key).await {
     Ok(Some(l)) if l.tg_user_id == bot.user_id => l,
     _ => {
-      bot.edit_with_keyboard("❌ License not found.", back_keyboard()).await?;
+      bot
+        .edit_with_keyboard(
+          bot.lang.t(T::LicenseNotFound),
+          back_keyboard(bot.lang),
+        )
+        .await?;
       return Ok(());
     }
   };
 
   let user = sv.user.by_id(bot.user_id).await.ok().flatten();
   let balance = user.as_ref().map(|u| u.balance).unwrap_or(0);
-  let referred_by = user.as_ref().and_then(|u| u.referred_by);
   let now = Utc::now().naive_utc();
 
-  let discount_percent: i32 = sv.referral.discount_percent(referred_by).await;
-
-  let (month_price, quarter_price) = if discount_percent > 0 {
-    (
-      MONTH_PRICE * (100 - discount_percent) as f64 / 100.0,
-      QUARTER_PRICE * (100 - discount_percent) as f64 / 100.0,
-    )
-  } else {
-    (MONTH_PRICE, QUARTER_PRICE)
+  let plans = extension_plans(&license);
+  let quotes = match quotes(sv, bot.user_id, &license.product, plans, true)
+    .await
+  {
+    Ok(quotes) => quotes,
+    Err(e) => {
+      let text =
+        i18n::fill(bot.lang.t(T::PricesFailed), &[("error", e.user_message())]);
+      bot.edit_with_keyboard(text, back_keyboard(bot.lang)).await?;
+      return Ok(());
+    }
   };
 
-  let month_nano = (month_price * NANO_USDT as f64) as i64;
-  let quarter_nano = (quarter_price * NANO_USDT as f64) as i64;
-
   let status = if license.expires_at > now {
-    format!("⏳ {}", crate::utils::format_duration(license.expires_at - now))
+    format!("⏳ {}", bot.lang.duration(license.expires_at - now))
   } else {
-    "❌ Expired".into()
+    bot.lang.t(T::Expired).into()
   };
 
-  let mut text = format!(
-    "🔄 <b>Extend License</b>\n\n\
-    <b>License:</b> <code>{}</code>\n\
-    <b>Status:</b> {}\n\
-    <b>Expires:</b> {}\n\n\
-    <b>Your Balance:</b> {}\n\n\
-    <b>Extension Pricing:</b>\n",
-    license.key,
-    status,
-    crate::utils::format_date(license.expires_at),
-    format_usdt(balance)
+  let lang = bot.lang;
+  let mut text = i18n::fill(
+    lang.t(T::ExtendKey),
+    &[
+      ("key", license.key.clone()),
+      ("status", status),
+      ("expires", crate::utils::format_date(license.expires_at)),
+      ("balance", format_usdt(balance)),
+    ],
   );
 
-  if discount_percent > 0 {
-    text.push_str(&format!(
-      "• +1 Month: <s>10.00</s> <b>{:.2} USDT</b> ({}% off)\n\
-       • +3 Months: <s>25.00</s> <b>{:.2} USDT</b> ({}% off)\n",
-      month_price, discount_percent, quarter_price, discount_percent
-    ));
-  } else {
+  for quote in &quotes {
+    let bonus = if quote.volume_percent > 0 { " 🎁" } else { "" };
     text.push_str(&format!(
-      "• +1 Month: <b>{:.2} USDT</b>\n\
-       • +3 Months: <b>{:.2} USDT</b>\n",
-      month_price, quarter_price
+      "• +{}: {}{}\n",
+      quote.title,
+      quote_price(bot.lang, quote),
+      bonus
     ));
   }
 
-  let can_buy_month = balance >= month_nano;
-  let can_buy_quarter = balance >= quarter_nano;
+  // nudge towards longer extensions
+  if plans.iter().any(|p| volume_percent(p.months()) > 0) {
+    text.push_str(lang.t(T::BonusTiers));
+    for (months, percent) in VOLUME_TIERS.iter().rev() {
+      text.push_str(&i18n::fill(
+        lang.t(T::BonusTier),
+        &[("months", months.to_string()), ("percent", percent.to_string())],
+      ));
+    }
+  }
+  if let Some(sale) = quotes.iter().find_map(|q| q.sale.as_ref()) {
+    text.push_str(&i18n::fill(
+      lang.t(T::SaleLine),
+      &[
+        ("percent", sale.percent.to_string()),
+        ("duration", lang.duration(sale.ends_at - now)),
+        ("name", html::escape(&sale.name)),
+      ],
+    ));
+  }
 
-  if !can_buy_month {
-    text.push_str(&format!(
-      "\n<i>💡 You need {} more to extend by 1 month.</i>",
-      format_usdt(month_nano - balance)
+  let cheapest = quotes.iter().map(|q| q.price).min().unwrap_or(0);
+  if balance < cheapest {
+    text.push_str(&i18n::fill(
+      lang.t(T::NeedMoreExtend),
+      &[("amount", format_usdt(cheapest - balance))],
+    ));
+  }
+  if license.auto_renew
+    && let Some(quote) = quotes.first()
+  {
+    text.push_str(&i18n::fill(
+      lang.t(T::AutoRenewing),
+      &[("plan", quote.title.clone())],
     ));
   }
 
   let mut rows = Vec::new();
 
-  if can_buy_month {
-    rows.push(vec![InlineKeyboardButton::callback(
-      format!("+1 Month ({:.2} USDT)", month_price),
-      Callback::ExtendPlan { key: key.to_string(), plan: "month".to_string() }
-        .to_data(),
-    )]);
-  }
-  if can_buy_quarter {
+  for quote in quotes.iter().filter(|q| balance >= q.price) {
     rows.push(vec![InlineKeyboardButton::callback(
-      format!("+3 Months ({:.2} USDT)", quarter_price),
+      format!(
+        "+{} ({:.2} USDT)",
+        quote.title,
+        quote.price as f64 / NANO_USDT as f64
+      ),
       Callback::ExtendPlan {
         key: key.to_string(),
-        plan: "quarter".to_string(),
+        plan: quote.plan.as_str().to_string(),
       }
       .to_data(),
     )]);
   }
 
   rows.push(vec![InlineKeyboardButton::callback(
-    "💵 Add Funds",
+    lang.t(match license.auto_renew {
+      true => T::AutoRenewOn,
+      false => T::AutoRenewOff,
+    }),
+    Callback::AutoRenew(key.to_string()).to_data(),
+  )]);
+  rows.push(vec![InlineKeyboardButton::callback(
+    bot.lang.t(T::MenuFunds),
     Callback::AddFunds.to_data(),
   )]);
   rows.push(vec![InlineKeyboardButton::callback(
-    "« Back",
+    bot.lang.t(T::Back),
     Callback::ExtendLicense.to_data(),
   )]);
 
@@ -1502,68 +3502,87 @@ async fn handle_extend_license_key(
   Ok(())
 }
 
-async fn handle_extend_plan(
+pub async fn handle_extend_plan(
+  app: &AppState,
   sv: &Services<'_>,
   bot: &ReplyBot,
   key: &str,
   plan: &str,
+  rules: &Rules,
 ) -> ResponseResult<()> {
   let license = match sv.license.by_key(key).await {
     Ok(Some(l)) if l.tg_user_id == bot.user_id => l,
     _ => {
-      bot.edit_with_keyboard("❌ License not found.", back_keyboard()).await?;
+      bot
+        .edit_with_keyboard(
+          bot.lang.t(T::LicenseNotFound),
+          back_keyboard(bot.lang),
+        )
+        .await?;
       return Ok(());
     }
   };
+  if let Err(e) =
+    sv.eligibility.check(bot.user_id, Purchase::Extend(&license), rules).await
+  {
+    if let Error::Ineligible(Ineligible::HighRisk(score)) = e {
+      let action = Action::Extension {
+        key: key.to_string(),
+        plan: plan.to_string(),
+        chat_id: bot.chat_id.0,
+        message_id: bot.message_id.0,
+      };
+      return review::hold(app, bot, action, score).await;
+    }
+    let text = format!("❌ {}", e.user_message());
+    bot.edit_with_keyboard(text, back_keyboard(bot.lang)).await?;
+    return Ok(());
+  }
 
   let user = sv.user.by_id(bot.user_id).await.ok().flatten();
   let balance = user.as_ref().map(|u| u.balance).unwrap_or(0);
   let referred_by = user.as_ref().and_then(|u| u.referred_by);
 
-  let discount_percent: i32 = sv.referral.discount_percent(referred_by).await;
-
-  let (price, days, plan_name) = match plan {
-    "month" => {
-      let price = if discount_percent > 0 {
-        MONTH_PRICE_NANO * (100 - discount_percent) as i64 / 100
-      } else {
-        MONTH_PRICE_NANO
-      };
-      (price, 30u64, "1 Month")
-    }
-    "quarter" => {
-      let price = if discount_percent > 0 {
-        QUARTER_PRICE_NANO * (100 - discount_percent) as i64 / 100
-      } else {
-        QUARTER_PRICE_NANO
-      };
-      (price, 90u64, "3 Months")
-    }
-    _ => {
-      bot.edit_with_keyboard("❌ Invalid plan.", back_keyboard()).await?;
+  let Some(plan) =
+    Plan::parse(plan).filter(|plan| extension_plans(&license).contains(plan))
+  else {
+    bot
+      .edit_with_keyboard(bot.lang.t(T::InvalidPlan), back_keyboard(bot.lang))
+      .await?;
+    return Ok(());
+  };
+  let quote = match sv
+    .pricing
+    .quote_extension(bot.user_id, &license.product, plan)
+    .await
+  {
+    Ok(quote) => quote,
+    Err(e) => {
+      let text =
+        i18n::fill(bot.lang.t(T::PricesFailed), &[("error", e.user_message())]);
+      bot.edit_with_keyboard(text, back_keyboard(bot.lang)).await?;
       return Ok(());
     }
   };
+  let (price, plan_name) = (quote.price, &quote.title);
 
   if balance < price {
     let needed = price - balance;
-    let text = format!(
-      "❌ <b>Insufficient Balance</b>\n\n\
-      <b>Required:</b> {}\n\
-      <b>Your balance:</b> {}\n\
-      <b>Needed:</b> {}\n\n\
-      <i>Add funds to your balance to extend this license.</i>",
-      format_usdt(price),
-      format_usdt(balance),
-      format_usdt(needed)
+    let text = i18n::fill(
+      bot.lang.t(T::InsufficientExtension),
+      &[
+        ("price", format_usdt(price)),
+        ("balance", format_usdt(balance)),
+        ("needed", format_usdt(needed)),
+      ],
     );
     let kb = InlineKeyboardMarkup::new(vec![
       vec![InlineKeyboardButton::callback(
-        "💵 Add Funds",
+        bot.lang.t(T::MenuFunds),
         Callback::AddFunds.to_data(),
       )],
       vec![InlineKeyboardButton::callback(
-        "« Back",
+        bot.lang.t(T::Back),
         Callback::ExtendLicenseKey(key.to_string()).to_data(),
       )],
     ]);
@@ -1573,52 +3592,60 @@ async fn handle_extend_plan(
 
   match sv
     .balance
-    .spend(
+    .spend_in(
       bot.user_id,
       price,
       Some(format!("License extension: {} for {}", plan_name, &key[..8])),
       referred_by,
+      quote.region_code(),
     )
     .await
   {
     Ok(new_balance) => {
+      if quote.offer_percent > 0 {
+        let _ = sv.upgrade_offers.redeem(bot.user_id).await;
+      }
       if let Some(referrer_id) = referred_by {
-        let _ = sv.referral.record_sale(referrer_id, price).await;
-        let referrer_user = sv.user.by_id(referrer_id).await.ok().flatten();
-        if let Some(referrer) = referrer_user {
-          let commission = price * referrer.commission_rate as i64 / 100;
-          let _ = sv
-            .balance
-            .add_referral_bonus(referrer_id, commission, bot.user_id)
-            .await;
-        }
+        let _ = sv
+          .referral
+          .pay_commission(referrer_id, Some(bot.user_id), price)
+          .await;
       }
 
-      let duration = Duration::from_secs(days * 24 * 60 * 60);
-      match sv.license.expires(key, duration).await {
+      match sv.license.extend_term(key, quote.term).await {
         Ok(new_exp) => {
-          let text = format!(
-            "✅ <b>License Extended!</b>\n\n\
-            <b>License:</b> <code>{}</code>\n\
-            <b>Added:</b> {}\n\
-            <b>New Expiry:</b> {}\n\n\
-            <b>New Balance:</b> {}",
-            license.key,
-            plan_name,
-            crate::utils::format_date(new_exp),
-            format_usdt(new_balance)
+          if let Some(region) = quote.region_code() {
+            let _ = sv.license.set_region(key, Some(region)).await;
+          }
+          let text = i18n::fill(
+            bot.lang.t(T::Extended),
+            &[
+              ("key", license.key.clone()),
+              ("plan", plan_name.clone()),
+              ("expires", crate::utils::format_date(new_exp)),
+              ("balance", format_usdt(new_balance)),
+            ],
           );
           let kb = InlineKeyboardMarkup::new(vec![
             vec![InlineKeyboardButton::callback(
-              "📥 Download Panel",
+              bot.lang.t(T::MenuDownload),
               Callback::Download.to_data(),
             )],
             vec![InlineKeyboardButton::callback(
-              "« Back to Menu",
+              bot.lang.t(T::BackToMenu),
               Callback::Back.to_data(),
             )],
           ]);
           bot.edit_with_keyboard(text, kb).await?;
+          support::ask_rating(
+            sv,
+            &bot.inner,
+            bot.user_id,
+            RatingKind::Purchase,
+            &license.key,
+            bot.lang.t(T::RatePurchase),
+          )
+          .await;
         }
         Err(e) => {
           let _ = sv
@@ -1629,17 +3656,144 @@ async fn handle_extend_plan(
               Some("Refund: license extension failed".into()),
             )
             .await;
-          let text =
-            format!("❌ Failed to extend license: {}", e.user_message());
-          bot.edit_with_keyboard(text, back_keyboard()).await?;
+          let text = i18n::fill(
+            bot.lang.t(T::ExtendFailed),
+            &[("error", e.user_message())],
+          );
+          bot.edit_with_keyboard(text, back_keyboard(bot.lang)).await?;
         }
       }
     }
     Err(e) => {
-      let text = format!("❌ Failed to process payment: {}", e.user_message());
-      bot.edit_with_keyboard(text, back_keyboard()).await?;
+      let text = i18n::fill(
+        bot.lang.t(T::PaymentFailed),
+        &[("error", e.user_message())],
+      );
+      bot.edit_with_keyboard(text, back_keyboard(bot.lang)).await?;
     }
   }
 
   Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn samples() -> Vec<Callback> {
+    let text = || "ключ-🦀".to_string();
+    vec![
+      Callback::Profile,
+      Callback::Inbox,
+      Callback::InboxItem(7),
+      Callback::ApiTokenNew(None),
+      Callback::ApiTokenNew(Some(text())),
+      Callback::Language,
+      Callback::SetLanguage("ru".into()),
+      Callback::DownloadVersion("1.2.0".into()),
+      Callback::DownloadArtifact {
+        version: "1.2:beta".into(),
+        platform: "linux-x64".into(),
+      },
+      Callback::BuyPlan { product: "default".into(), plan: text() },
+      Callback::ExtendPlan { key: "KEY".into(), plan: "1mo:x".into() },
+      Callback::TicketCannedSend { ticket: -3, name: text() },
+      Callback::Review { id: 1, approve: false },
+      Callback::Transfer { key: "a:b".into(), to: -1 },
+      Callback::ReconCredit(i64::MAX),
+      Callback::ReconReverse(42),
+      Callback::KickSession { key: "KEY".into(), tag: "0a1b2c3d".into() },
+      Callback::BanKey(text()),
+      Callback::History(u64::MAX),
+      Callback::StatsChart,
+      Callback::Rate { id: 2, score: 5 },
+      Callback::FreebieClaim { kind: FreebieKind::Item, id: 9 },
+      Callback::AcceptTerms(i32::MAX),
+    ]
+  }
+
+  #[test]
+  fn test_roundtrip() {
+    for callback in samples() {
+      assert_eq!(Callback::parse(&callback.to_data()), Ok(callback));
+    }
+    // buttons sent before products existed
+    assert_eq!(
+      Callback::parse("buy_plan:1mo"),
+      Ok(Callback::BuyPlan {
+        product: DEFAULT_PRODUCT.into(),
+        plan: "1mo".into()
+      })
+    );
+  }
+
+  #[test]
+  fn test_rejects_malformed() {
+    let malformed = |tag: &str| Err(BadCallback::Malformed(tag.into()));
+    assert_eq!(Callback::parse("dl_ver:"), malformed("dl_ver"));
+    assert_eq!(Callback::parse("dl_ver"), malformed("dl_ver"));
+    assert_eq!(Callback::parse("faq_q:é"), malformed("faq_q"));
+    assert_eq!(Callback::parse("rv:1:2"), malformed("rv"));
+    assert_eq!(Callback::parse("fb:x:1"), malformed("fb"));
+    assert_eq!(Callback::parse("regen:a\u{0}b"), malformed("regen"));
+    assert_eq!(Callback::parse("🦀:1"), Err(BadCallback::Unknown("🦀".into())));
+    let long = format!("dl_ver:{}", "é".repeat(40));
+    assert_eq!(Callback::parse(&long), Err(BadCallback::TooLong(87)));
+  }
+
+  /// Random data never panics the parser, and whatever it accepts encodes
+  /// back to data that parses to the same callback
+  #[test]
+  fn test_parse_arbitrary() {
+    const PIECES: &[&str] = &[
+      "dl_ver",
+      "dl_art",
+      "buy_plan",
+      "ext_plan",
+      "tk_cs",
+      "rv",
+      "xfer",
+      "kick",
+      "ban",
+      "fb",
+      "rate",
+      "inbox",
+      "api_new",
+      "lang",
+      "faq_q",
+      "chart",
+      ":",
+      ":",
+      "0",
+      "1",
+      "-7",
+      "g",
+      "2147483648",
+      "é",
+      "🦀",
+      "\u{7}",
+      " ",
+      "",
+    ];
+    let mut seed = 0x2545_f491_4f6c_dd1du64;
+    let mut next = || {
+      seed ^= seed << 13;
+      seed ^= seed >> 7;
+      seed ^= seed << 17;
+      seed
+    };
+
+    for _ in 0..20_000 {
+      let len = next() % 6;
+      let data: String =
+        (0..len).map(|_| PIECES[next() as usize % PIECES.len()]).collect();
+      if let Ok(callback) = Callback::parse(&data) {
+        assert_eq!(
+          Callback::parse(&callback.to_data()),
+          Ok(callback),
+          "{data:?}"
+        );
+      }
+    }
+  }
+}
@@ -1,77 +1,352 @@
-use std::{path::Path, sync::Arc, time::Duration};
+use std::{collections::HashSet, path::Path, sync::Arc};
 
 use futures::future;
 use teloxide::{
+  net::Download,
   prelude::*,
-  types::{InputFile, ParseMode},
-  utils::command::{BotCommands, ParseError},
+  types::{InlineKeyboardButton, InlineKeyboardMarkup, InputFile, ParseMode},
+  utils::{
+    command::{BotCommands, ParseError},
+    html,
+  },
 };
 
-use super::ReplyBot;
+use super::{
+  ReplyBot, broadcast, callback, config_sync,
+  i18n::{self, Lang, T},
+  leaderboard,
+  onboarding::{self, OnboardingDialogue},
+  reconcile, review, support,
+};
 use crate::{
-  entity::{license::LicenseType, user::UserRole},
+  entity::{
+    announcement::AnnouncementCategory,
+    bot_usage::UsageKind,
+    build::Channel,
+    custom_field::{self, FieldScope},
+    freebie_claim::FreebieKind,
+    goal::GoalKind,
+    license::LicenseType,
+    promo_asset::PromoAssetKind,
+    promo_code::PromoGrant,
+    ticket::TicketPriority,
+    user::UserRole,
+    user_settings,
+  },
   prelude::*,
   state::{AppState, Services},
-  sv::referral::NANO_USDT,
+  sv::{
+    self,
+    admin_alert::Urgency,
+    license::{GIFT_BATCH_MAX, Term},
+    plan::PlanField,
+    pricing::Plan,
+    promo_code::Redeemed,
+    referral::NANO_USDT,
+    review::Action,
+    stats::Board,
+  },
 };
 
+/// Default window of /botstats
+const BOTSTATS_DAYS: i64 = 7;
+/// Rows per kind in /botstats
+const BOTSTATS_SHOWN: usize = 15;
+/// Default window of /apiusage
+const APIUSAGE_DAYS: i64 = 7;
+/// Licenses listed by /apiusage
+const APIUSAGE_SHOWN: u64 = 15;
+/// Days of a single license shown by /apiusage
+const APIUSAGE_HISTORY_DAYS: i64 = 14;
+/// Longest a support key given out with /loaner may run
+const LOANER_MAX_HOURS: i64 = 72;
+/// Loaners listed by /loaner without arguments
+const LOANERS_SHOWN: u64 = 15;
+/// Entries listed by /downloads
+const DOWNLOADS_SHOWN: u64 = 20;
+/// Snapshots listed per location by /backups
+const BACKUPS_SHOWN: usize = 20;
+/// Transactions /txns lists without a limit, and at most
+const TXNS_DEFAULT: u64 = 15;
+const TXNS_MAX: u64 = 30;
+/// Connections /sessions lists without a limit, and at most
+const SESSIONS_DEFAULT: u64 = 15;
+const SESSIONS_MAX: u64 = 50;
+/// Largest file the Bot API lets bots download
+const RESTORE_MAX_SIZE: u32 = 20 * 1024 * 1024;
+
+type PublishArgs = (String, String, Channel, String, String);
+
 fn parse_publish(
   input: String,
-) -> std::result::Result<(String, String, String), ParseError> {
+) -> std::result::Result<PublishArgs, ParseError> {
+  let usage = || {
+    ParseError::IncorrectFormat(
+      "Usage: /publish <filename> <version> [--channel beta] \
+      [--platform linux-x64] [changelog]"
+        .into(),
+    )
+  };
   let mut parts = input.splitn(3, ' ');
   let filename = parts.next().unwrap_or_default().to_string();
   let version = parts.next().unwrap_or_default().to_string();
-  let changelog = parts.next().unwrap_or_default().to_string();
+  let mut changelog = parts.next().unwrap_or_default().to_string();
 
   if filename.is_empty() || version.is_empty() {
-    return Err(ParseError::IncorrectFormat(
-      "Usage: /publish <filename> <version> [changelog]".into(),
-    ));
+    return Err(usage());
+  }
+
+  let mut channel = Channel::Stable;
+  let mut platform = sv::build::DEFAULT_PLATFORM;
+  while let Some(rest) = changelog.strip_prefix("--") {
+    let (flag, rest) = rest.split_once(' ').ok_or_else(usage)?;
+    let (value, rest) = rest.split_once(' ').unwrap_or((rest, ""));
+    match flag {
+      "channel" => channel = Channel::parse(value).ok_or_else(usage)?,
+      "platform" => {
+        platform = sv::build::normalize_platform(value).ok_or_else(usage)?
+      }
+      _ => return Err(usage()),
+    }
+    changelog = rest.to_string();
   }
 
-  Ok((filename, version, changelog))
+  Ok((filename, version, channel, platform.to_string(), changelog))
+}
+
+/// `<duration> [trial|pro]` of /gift and /genbulk, Pro unless told otherwise
+fn parse_gift(args: &[&str]) -> Option<(Term, LicenseType)> {
+  let (term, ty) = match args {
+    [term] => (term, "pro"),
+    [term, ty] => (term, *ty),
+    _ => return None,
+  };
+  let license_type = match ty.to_lowercase().as_str() {
+    "pro" => LicenseType::Pro,
+    "trial" => LicenseType::Trial,
+    _ => return None,
+  };
+  Some((Term::parse(term)?, license_type))
 }
 
 fn parse_buy(
   input: String,
-) -> std::result::Result<(Option<String>, Duration), ParseError> {
+) -> std::result::Result<(Option<String>, Term), ParseError> {
   let parts: Vec<&str> = input.split_whitespace().collect();
+  let term = |s: &str| {
+    Term::parse(s).ok_or_else(|| {
+      ParseError::IncorrectFormat(
+        format!(
+          "Invalid duration '{}'\nUsage: /buy <duration> or /buy <key> <duration>\nExamples: 30d, 2w, 1h30m, 1mo, 1q",
+          s
+        )
+        .into(),
+      )
+    })
+  };
 
   match parts.as_slice() {
     // /buy <duration> - generate new license
-    [duration_str] => {
-      let duration = humantime::parse_duration(duration_str).map_err(|e| {
-        ParseError::IncorrectFormat(
-          format!(
-            "Invalid duration '{}': {}\nUsage: /buy <duration> or /buy <key> <duration>\nExamples: 30d, 2w, 1h30m",
-            duration_str, e
-          )
-          .into(),
-        )
-      })?;
-      Ok((None, duration))
-    }
+    [duration] => Ok((None, term(duration)?)),
     // /buy <key> <duration> - extend existing license
-    [key, duration_str] => {
-      let duration = humantime::parse_duration(duration_str).map_err(|e| {
-        ParseError::IncorrectFormat(
-          format!(
-            "Invalid duration '{}': {}\nExamples: 30d, 2w, 1h30m, 7days",
-            duration_str, e
-          )
-          .into(),
-        )
-      })?;
-      Ok((Some(key.to_string()), duration))
-    }
+    [key, duration] => Ok((Some(key.to_string()), term(duration)?)),
     _ => Err(ParseError::IncorrectFormat(
-      "Usage:\n/buy <duration> - Generate new license\n/buy <key> <duration> - Extend existing license\nExamples: /buy 30d, /buy abc123 2w"
+      "Usage:\n/buy <duration> - Generate new license\n/buy <key> <duration> - Extend existing license\nExamples: /buy 30d, /buy abc123 1mo"
         .into(),
     )),
   }
 }
 
+/// Parse `question | answer [| keywords]`
+fn parse_faq_entry(input: &str) -> Result<(String, String, String)> {
+  let parts: Vec<&str> = input.splitn(3, '|').map(str::trim).collect();
+  match parts.as_slice() {
+    [question, answer, rest @ ..]
+      if !question.is_empty() && !answer.is_empty() =>
+    {
+      let keywords = rest.first().copied().unwrap_or_default();
+      Ok((question.to_string(), answer.to_string(), keywords.to_string()))
+    }
+    _ => Err(Error::InvalidArgs(
+      "Usage: /faqadd <question> | <answer> [| keywords]".into(),
+    )),
+  }
+}
+
+/// Alert threshold in minutes and optional UTC farming window
+type DowntimeArgs = (Option<i32>, Option<(i32, i32)>);
+
+/// Downtime alert settings: `off` or `<minutes> [HH-HH]`
+fn parse_downtime(args: &str) -> Result<DowntimeArgs> {
+  let usage = || {
+    Error::InvalidArgs(
+      "Usage: /downtime <minutes> [HH-HH] or /downtime off".into(),
+    )
+  };
+
+  let mut parts = args.split_whitespace();
+  let mins = match parts.next().ok_or_else(usage)? {
+    "off" => return Ok((None, None)),
+    mins => mins.parse::<i32>().map_err(|_| usage())?,
+  };
+  if !(5..=24 * 60).contains(&mins) {
+    return Err(Error::InvalidArgs(
+      "Threshold must be between 5 and 1440 minutes".into(),
+    ));
+  }
+
+  let hours = match parts.next() {
+    Some(window) => {
+      let (from, to) = window.split_once('-').ok_or_else(usage)?;
+      let from: i32 = from.parse().map_err(|_| usage())?;
+      let to: i32 = to.parse().map_err(|_| usage())?;
+      if !(0..24).contains(&from) || !(0..=24).contains(&to) || from == to {
+        return Err(Error::InvalidArgs(
+          "Farming hours must look like 8-23 (UTC)".into(),
+        ));
+      }
+      Some((from, to % 24))
+    }
+    None => None,
+  };
+
+  Ok((Some(mins), hours))
+}
+
+fn downtime_status(lang: Lang, settings: &user_settings::Model) -> String {
+  let Some(mins) = settings.downtime_alert_mins else {
+    return lang.t(T::DowntimeOff).to_string();
+  };
+
+  let hours = match (settings.farm_from_hour, settings.farm_to_hour) {
+    (Some(from), Some(to)) => format!("{:02}:00-{:02}:00 UTC", from, to),
+    _ => lang.t(T::DowntimeAllDay).to_string(),
+  };
+  i18n::fill(
+    lang.t(T::DowntimeOn),
+    &[("mins", mins.to_string()), ("hours", hours)],
+  )
+}
+
+/// Quiet hours: `off` or a local `HH-HH` window
+fn parse_quiet(args: &str) -> Result<Option<(i32, i32)>> {
+  let usage = || {
+    Error::InvalidArgs("Usage: /quiet <HH-HH>, e.g. 23-8, or /quiet off".into())
+  };
+  if args == "off" {
+    return Ok(None);
+  }
+  let (from, to) = args.split_once('-').ok_or_else(usage)?;
+  let from: i32 = from.trim().parse().map_err(|_| usage())?;
+  let to: i32 = to.trim().parse().map_err(|_| usage())?;
+  if !(0..24).contains(&from) || !(0..=24).contains(&to) || from == to % 24 {
+    return Err(usage());
+  }
+  Ok(Some((from, to % 24)))
+}
+
+fn quiet_status(settings: &user_settings::Model) -> String {
+  let offset = format_utc_offset(settings.utc_offset_mins);
+  match (settings.quiet_from_hour, settings.quiet_to_hour) {
+    (Some(from), Some(to)) => format!(
+      "🌙 <b>Quiet hours:</b> {:02}:00-{:02}:00 ({})\n\n\
+      Tickets, reports and reminders wait until morning, critical \
+      alerts still come through.\n\
+      Disable with <code>/quiet off</code>",
+      from, to, offset
+    ),
+    _ => format!(
+      "🔔 <b>Quiet hours are off</b>\n\n\
+      Hold non-critical alerts at night with <code>/quiet 23-8</code>, \
+      hours are in your timezone ({}, see /timezone).",
+      offset
+    ),
+  }
+}
+
+/// UTC offset in minutes from `+3`, `-5`, `+05:30` or `UTC+2`
+fn parse_utc_offset(args: &str) -> Result<i32> {
+  let invalid = || {
+    Error::InvalidArgs("Usage: /timezone <offset>, e.g. +3 or -05:30".into())
+  };
+
+  let args = args.trim();
+  let args = args
+    .strip_prefix("UTC")
+    .or_else(|| args.strip_prefix("utc"))
+    .unwrap_or(args);
+  if args.is_empty() {
+    return Ok(0);
+  }
+
+  let (sign, rest) = match args.as_bytes()[0] {
+    b'-' => (-1, &args[1..]),
+    b'+' => (1, &args[1..]),
+    _ => (1, args),
+  };
+  let (hours, mins) = rest.split_once(':').unwrap_or((rest, "0"));
+  let hours: i32 = hours.parse().map_err(|_| invalid())?;
+  let mins: i32 = mins.parse().map_err(|_| invalid())?;
+  if !(0..=14).contains(&hours) || !(0..60).contains(&mins) {
+    return Err(invalid());
+  }
+  Ok(sign * (hours * 60 + mins))
+}
+
+/// Commission multiplier as "2x", "1.5x" or "150%", in percent
+fn parse_boost_percent(input: &str) -> Option<i32> {
+  if let Some(times) = input.strip_suffix(['x', 'X']) {
+    let times: f64 = times.parse().ok()?;
+    return times.is_finite().then(|| (times * 100.0).round() as i32);
+  }
+  input.strip_suffix('%')?.parse().ok()
+}
+
+/// UTC start as "2026-02-10" or "2026-02-10T18:00"
+fn parse_start(input: &str) -> Option<DateTime> {
+  DateTime::parse_from_str(input, "%Y-%m-%dT%H:%M").ok().or_else(|| {
+    chrono::NaiveDate::parse_from_str(input, "%Y-%m-%d")
+      .ok()?
+      .and_hms_opt(0, 0, 0)
+  })
+}
+
+fn format_utc_offset(offset_mins: i32) -> String {
+  let sign = if offset_mins < 0 { '-' } else { '+' };
+  let abs = offset_mins.abs();
+  format!("UTC{}{:02}:{:02}", sign, abs / 60, abs % 60)
+}
+
+/// Freebies listed by /freebie, replies are capped at 4096 characters
+const FREEBIES_LISTED: usize = 40;
+
 /// Format balance in USDT (stored as nanoUSDT internally)
+fn list_snapshots(
+  text: &mut String,
+  location: &str,
+  snapshots: &[sv::backup::Snapshot],
+) {
+  let mb = |bytes: u64| bytes as f64 / (1024.0 * 1024.0);
+  let total: u64 = snapshots.iter().map(|s| s.size).sum();
+  text.push_str(&format!(
+    "\n<b>{}</b> · {} file(s), {:.1}MB\n",
+    location,
+    snapshots.len(),
+    mb(total)
+  ));
+  for snapshot in snapshots.iter().take(BACKUPS_SHOWN) {
+    text.push_str(&format!(
+      "{} <code>{}</code> {:.1}MB\n",
+      utils::format_date(snapshot.created_at),
+      snapshot.name,
+      mb(snapshot.size)
+    ));
+  }
+  if snapshots.len() > BACKUPS_SHOWN {
+    text.push_str(&format!("…and {} older\n", snapshots.len() - BACKUPS_SHOWN));
+  }
+}
+
 fn format_usdt(nano_usdt: i64) -> String {
   format!("{:.2} USDT", nano_usdt as f64 / NANO_USDT as f64)
 }
@@ -88,12 +363,45 @@ pub enum UserCommand {
   Help,
   #[command(description = "Link an existing license to your account")]
   Link(String),
+  #[command(description = "Give one of your licenses to another user")]
+  Transfer(String),
+  #[command(description = "Redeem a promo code for a free license")]
+  Promo(String),
+  #[command(description = "Redeem a giveaway code")]
+  Redeem(String),
   #[command(description = "Set your referral code for discounts")]
   Ref(String),
   #[command(description = "Add funds to your balance")]
   Fund(String),
+  #[command(description = "Show your balance and unpaid invoices")]
+  Balance,
+  #[command(description = "Show your balance transactions")]
+  History,
   #[command(description = "Set or clear your custom referral code")]
   MyCode(String),
+  #[command(description = "Search answers to common questions")]
+  Faq(String),
+  #[command(description = "Contact support")]
+  Ticket(String),
+  #[command(description = "Get pinged when your farming stops")]
+  Downtime(String),
+  #[command(description = "Set a daily XP or runtime goal")]
+  Goal(String),
+  #[command(description = "Show the weekly leaderboard")]
+  Top(String),
+  #[command(description = "Set your timezone for scheduled licenses")]
+  Timezone(String),
+  #[command(description = "Set your country for regional prices")]
+  Country(String),
+  #[command(description = "Switch between stable and beta builds")]
+  Channel(String),
+  #[command(description = "Change the bot language")]
+  Lang(String),
+  #[command(
+    rename = "delete_account",
+    description = "Delete your account and data"
+  )]
+  DeleteAccount,
 }
 
 /// Admin-only commands shown to admins in command hints.
@@ -105,40 +413,142 @@ pub enum UserCommand {
 pub enum AdminCommand {
   #[command(description = "Generate or extend license")]
   Buy(String),
+  #[command(description = "Create a gift key, e.g. /gift 30d pro")]
+  Gift(String),
+  #[command(description = "List unredeemed gift keys")]
+  Gifts,
+  #[command(description = "Create a batch of gift keys as a file")]
+  GenBulk(String),
   #[command(description = "Block license and drop sessions")]
   Ban(String),
   #[command(description = "Unblock license")]
   Unban(String),
+  #[command(description = "Block license for a while, e.g. /suspend <key> 24h")]
+  Suspend(String),
+  #[command(description = "Set concurrent session limit of a license")]
+  MaxSessions(String),
   #[command(description = "Show license or user details")]
   Info(String),
+  #[command(description = "Show recent connections of a license")]
+  Sessions(String),
   #[command(description = "Show active sessions count")]
   Stats,
   #[command(description = "List all registered users")]
   Users,
   #[command(description = "Manual database backup")]
   Backup,
+  #[command(description = "List stored backups")]
+  Backups,
+  #[command(description = "Restore the database from a backup")]
+  Restore(String),
+  #[command(description = "Export or import plans, promos, sales and flags")]
+  Config(String),
+  #[command(description = "Hold non-critical alerts at night")]
+  Quiet(String),
   #[command(description = "List all builds")]
   Builds,
   #[command(description = "Publish new build")]
   Publish(String),
+  #[command(description = "Add a build file for another platform")]
+  Artifact(String),
+  #[command(description = "Show who downloaded a build")]
+  Downloads(String),
   #[command(description = "Remove build from downloads")]
   Yank(String),
   #[command(description = "Reactivate yanked build")]
   Unyank(String),
   #[command(description = "Show global XP/drops summary")]
   GlobalStats,
+  #[command(description = "Show bot command and menu usage")]
+  BotStats(String),
+  #[command(description = "Show licenses generating the most API requests")]
+  ApiUsage(String),
+  #[command(description = "List, rotate or retire offline token keys")]
+  SignKey(String),
   #[command(description = "Set user role (user/creator/admin)")]
   SetRole(String),
+  #[command(description = "Approve or reject actions held by the risk rules")]
+  Review,
+  #[command(description = "Exempt a reseller from the active license cap")]
+  Reseller(String),
   #[command(description = "Configure referral settings")]
   SetRef(String),
   #[command(description = "Set custom referral code for user")]
   SetCode(String),
   #[command(description = "Show referral statistics")]
   RefStats,
+  #[command(description = "Run a temporary commission boost")]
+  RefBoost(String),
+  #[command(description = "Create or list promo code campaigns")]
+  NewPromo(String),
+  #[command(description = "Open or close the free trial promo")]
+  TrialPromo(String),
+  #[command(description = "Manage giveaway codes for balance or trial days")]
+  PromoCode(String),
   #[command(description = "Add balance to user")]
   Deposit(String),
   #[command(description = "Process user withdrawal")]
   Withdraw(String),
+  #[command(description = "Refund part or all of a user's purchase")]
+  Refund(String),
+  #[command(description = "Undo a recent deposit or withdrawal (owners)")]
+  Undo(String),
+  #[command(description = "Show a user's transactions with running balance")]
+  Txns(String),
+  #[command(description = "Cross-check CryptoBot invoices against deposits")]
+  Reconcile(String),
+  #[command(description = "Add FAQ entry")]
+  FaqAdd(String),
+  #[command(description = "Edit FAQ entry")]
+  FaqEdit(String),
+  #[command(description = "Delete FAQ entry")]
+  FaqDel(String),
+  #[command(description = "List open tickets or show one")]
+  Tickets(String),
+  #[command(description = "Set ticket priority")]
+  Priority(String),
+  #[command(description = "Reply to support ticket")]
+  Reply(String),
+  #[command(description = "Close support ticket")]
+  Close(String),
+  #[command(description = "Manage canned responses")]
+  Canned(String),
+  #[command(description = "Post announcement to users' inbox")]
+  Announce(String),
+  #[command(description = "Delete announcement")]
+  Unannounce(String),
+  #[command(description = "Message all users or those with active licenses")]
+  Broadcast(String),
+  #[command(description = "Generate honeypot keys")]
+  Honeypot(String),
+  #[command(description = "Issue a short-lived support key for a user")]
+  Loaner(String),
+  #[command(description = "Show recent security incidents")]
+  Incidents,
+  #[command(description = "Manage duplicate-HWID exemptions")]
+  Exempt(String),
+  #[command(description = "Manage regional prices")]
+  Region(String),
+  #[command(description = "Start or stop happy-hour sales")]
+  Sale(String),
+  #[command(description = "Show or publish terms of service")]
+  Terms(String),
+  #[command(description = "Manage custom fields on users and licenses")]
+  Field(String),
+  #[command(description = "Toggle client features for a user or license")]
+  Flag(String),
+  #[command(description = "Manage products")]
+  Product(String),
+  #[command(description = "List plans and their prices")]
+  Plans,
+  #[command(description = "Change price, length or limits of a plan")]
+  SetPlan(String),
+  #[command(description = "Manage white-label storefront bots")]
+  Storefront(String),
+  #[command(description = "Manage promo materials for creators")]
+  Asset(String),
+  #[command(description = "Manage freebies and see who claimed them")]
+  Freebie(String),
 }
 
 /// Internal command enum used for parsing all commands
@@ -148,38 +558,107 @@ pub enum Command {
   Start(String),
   Help,
   Link(String),
+  Transfer(String),
+  Promo(String),
+  Redeem(String),
   Ref(String),
   Fund(String),
+  Balance,
+  History,
   MyCode(String),
   Users,
   #[command(parse_with = parse_buy)]
   Buy {
     key: Option<String>,
-    duration: Duration,
+    duration: Term,
   },
+  Gift(String),
+  Gifts,
+  GenBulk(String),
   Ban(String),
   Unban(String),
+  Suspend(String),
+  MaxSessions(String),
   Info(String),
+  Sessions(String),
   Stats,
   Backup,
+  Backups,
+  Restore(String),
+  Config(String),
+  Quiet(String),
   Builds,
   #[command(parse_with = parse_publish)]
   Publish {
     filename: String,
     version: String,
+    channel: Channel,
+    platform: String,
     changelog: String,
   },
+  Artifact(String),
+  Downloads(String),
   Yank(String),
   Unyank(String),
   #[command(hide)]
   Deactivate(String),
   GlobalStats,
+  BotStats(String),
+  ApiUsage(String),
+  SignKey(String),
   SetRole(String),
+  Review,
+  Reseller(String),
   SetRef(String),
   SetCode(String),
   RefStats,
+  RefBoost(String),
+  NewPromo(String),
+  TrialPromo(String),
+  PromoCode(String),
   Deposit(String),
   Withdraw(String),
+  Refund(String),
+  Undo(String),
+  Txns(String),
+  Reconcile(String),
+  Faq(String),
+  FaqAdd(String),
+  FaqEdit(String),
+  FaqDel(String),
+  Ticket(String),
+  Downtime(String),
+  Goal(String),
+  Top(String),
+  Timezone(String),
+  Country(String),
+  Channel(String),
+  Lang(String),
+  #[command(rename = "delete_account")]
+  DeleteAccount,
+  Tickets(String),
+  Priority(String),
+  Reply(String),
+  Close(String),
+  Canned(String),
+  Announce(String),
+  Unannounce(String),
+  Broadcast(String),
+  Honeypot(String),
+  Loaner(String),
+  Incidents,
+  Exempt(String),
+  Region(String),
+  Sale(String),
+  Terms(String),
+  Field(String),
+  Flag(String),
+  Product(String),
+  Plans,
+  SetPlan(String),
+  Storefront(String),
+  Asset(String),
+  Freebie(String),
 }
 
 const ADMIN_HELP: &str = "\
@@ -188,41 +667,145 @@ const ADMIN_HELP: &str = "\
 <b>License Management:</b>
 /buy &lt;duration&gt; - Generate new license (e.g. 30d, 2w)
 /buy &lt;key&gt; &lt;duration&gt; - Extend existing license
+/gift &lt;duration&gt; [trial|pro] - Unlinked key, term starts on activation
+/gifts - Unredeemed gift keys
+/genbulk &lt;count&gt; &lt;duration&gt; [trial|pro] - Batch of gift keys as a file
 /ban &lt;key&gt; - Block license and drop sessions
-/unban &lt;key&gt; - Unblock license
+/unban &lt;key&gt; - Unblock license, lifts a suspension too
+/suspend &lt;key&gt; &lt;duration&gt; - Block license until the time runs out
+/maxsessions &lt;key&gt; &lt;n&gt; - Set concurrent session limit
 /info &lt;key|user_id&gt; - Show license or user details
+/sessions &lt;key&gt; [limit] - Recent connections with HWID, IP and duration
+/field &lt;key|user_id&gt; - List custom fields
+/field &lt;key|user_id&gt; &lt;name&gt; &lt;value&gt; - Set custom field
+/field del &lt;key|user_id&gt; &lt;name&gt; - Remove custom field
+/flag &lt;key|user_id&gt; - List client feature overrides
+/flag &lt;key|user_id&gt; &lt;feature&gt; &lt;on|off|clear&gt; - Toggle a feature, live clients get it with the next heartbeat
 
 <b>Build Management:</b>
 /builds - List all builds
-/publish &lt;file&gt; &lt;ver&gt; [log] - Publish new build
+/publish &lt;file&gt; &lt;ver&gt;[@product] [--channel beta] [--platform linux-x64] [log] - Publish new build
+/artifact &lt;file&gt; &lt;ver&gt; &lt;platform&gt; - Add the file of a version for another platform
+/downloads &lt;version&gt; - Recent downloads of a build
 /yank &lt;version&gt; - Remove build from downloads
 /unyank &lt;version&gt; - Reactivate yanked build
 
 <b>Referral System:</b>
 /setrole &lt;user_id&gt; &lt;role&gt; - Set user role (user/creator/admin)
+/reseller &lt;user_id&gt; [on|off] - Exempt from the active license cap
 /setref &lt;user_id&gt; [rate%] [discount%] - Configure referral settings
 /setcode &lt;user_id&gt; &lt;code|clear&gt; - Set custom referral code (creators only)
+/asset - List promo materials shown to creators
+/asset banner &lt;title&gt; - Add the photo you reply to as a banner
+/asset text &lt;title&gt; | &lt;text&gt; - Add a copy template (placeholders: {link}, {code})
+/asset del &lt;id&gt; - Remove promo material
 /refstats - Show referral statistics
+/refboost - List scheduled commission boosts
+/refboost &lt;2x|150%&gt; &lt;duration&gt; [from &lt;date&gt;] [user_id ...] - Boost commission for everyone or selected creators
+/refboost stop &lt;id&gt; - End a boost early
+
+<b>Promo Codes:</b>
+/newpromo - List promo campaigns with claims
+/newpromo &lt;code&gt; &lt;trial|pro&gt; &lt;days&gt; &lt;duration&gt; [max_claims] [from &lt;date&gt;] - Create a campaign users redeem with /promo
+/newpromo stop &lt;code&gt; - End a campaign early
+/trialpromo - Show the free trial promo window
+/trialpromo open &lt;duration&gt; [from &lt;date&gt;] - Offer the free trial week again
+/trialpromo close - Stop offering the free trial
+/promocode - List giveaway codes with uses
+/promocode add &lt;code&gt; &lt;balance &lt;usdt&gt;|trial &lt;days&gt;&gt; [max_uses] [for &lt;duration&gt;] - Create a code users redeem with /redeem
+/promocode del &lt;code&gt; - Delete a code
 
 <b>Balance Management:</b>
 /deposit &lt;user_id&gt; &lt;amount_usdt&gt; - Add balance (e.g. 10.5)
 /withdraw &lt;user_id&gt; &lt;amount_usdt&gt; - Process withdrawal
+/refund &lt;user_id&gt; &lt;amount_usdt&gt; [reason] - Pay back the newest purchase that covers it
+/undo [id] - List recent operations or undo one (owners only)
+/txns &lt;user_id&gt; [limit] - Ledger of a user with running balance
+/reconcile [hours] - Paid invoices missing a deposit and deposits nothing paid for
+
+<b>FAQ:</b>
+/faqadd &lt;question&gt; | &lt;answer&gt; [| keywords] - Add entry
+/faqedit &lt;id&gt; &lt;question&gt; | &lt;answer&gt; [| keywords] - Edit entry
+/faqdel &lt;id&gt; - Delete entry
+
+<b>Support:</b>
+/tickets [id] - List open tickets or show ticket history
+/priority &lt;id&gt; &lt;low|normal|high&gt; - Set ticket priority
+/reply &lt;id&gt; &lt;text&gt; - Reply to ticket
+/close &lt;id&gt; - Close ticket
+/canned - List canned responses
+/canned add &lt;name&gt; &lt;text&gt; - Add or update (placeholders: {key}, {expiry}, {user_id}, {ticket})
+/canned del &lt;name&gt; - Delete canned response
+
+<b>Announcements:</b>
+/announce [release|maintenance|offer] &lt;title&gt; | &lt;body&gt; - Post to inbox
+/unannounce &lt;id&gt; - Delete announcement
+/broadcast [active] &lt;text&gt; - Message every user, or only those with a running license
+
+<b>Pricing:</b>
+/region - List regional prices of the month plan
+/region &lt;CC&gt; &lt;percent&gt; - Set price for a country (e.g. IN 50)
+/region del &lt;CC&gt; - Remove regional price
+/sale - List running and upcoming sales
+/sale &lt;percent&gt; &lt;hours&gt; [name] - Start a happy hour on extensions
+/sale stop &lt;id&gt; - End a sale early
+/product - List products
+/product &lt;id&gt; &lt;percent&gt; &lt;name&gt; - Add or update product, priced relative to base plans
+/product key &lt;key&gt; &lt;id&gt; - Move license to another product
+/plans - List plans with prices, lengths and session limits
+/setplan &lt;plan&gt; price|days|sessions|title &lt;value&gt; - Change a plan
+/setplan &lt;plan&gt; on|off - Start or stop selling a plan
+/setplan &lt;plan&gt; calendar on|off - Run for calendar months instead of days
+/storefront - List white-label bots
+/storefront &lt;id&gt; &lt;token&gt; &lt;support&gt; &lt;title&gt; - Sell a product through its own bot
+/storefront welcome &lt;id&gt; [text] - Set or reset the welcome message
+/storefront del &lt;id&gt; - Remove a storefront
+/terms - Show current terms of service
+/terms &lt;text&gt; - Publish a new version, users must accept it again
+
+<b>Freebies:</b>
+/freebie - List free games and items with claims this week
+/freebie game &lt;pkg_id&gt; &lt;app_id&gt; &lt;name&gt; - Add a free game
+/freebie item &lt;def_id&gt; &lt;app_id&gt; &lt;name&gt; - Add a free item
+/freebie del &lt;game|item&gt; &lt;id&gt; - Remove a freebie
+/freebie history [user_id] - Latest claims
+
+<b>Security:</b>
+/honeypot [count] - Generate decoy keys that alert when used
+/loaner &lt;hours&gt; &lt;user_id&gt; - Short-lived support key, kept out of stats
+/loaner - Recently issued support keys
+/incidents - Show recent security incidents
+/review - Approve or reject purchases, payouts and role changes held for risk
+/exempt - List duplicate-HWID exemptions
+/exempt hwid|user &lt;value&gt; [note] - Allow more licenses per machine
+/exempt del &lt;id&gt; - Remove exemption
 
 <b>System:</b>
 /users - List all registered users
 /stats - Show active sessions count
 /globalstats - Show global XP/drops summary
+/botstats [days] - Command and menu usage, failures, unused commands
+/apiusage [days|key] - Top API talkers, or one license day by day
+/signkey [rotate|retire &lt;kid&gt;] - Offline token signing keys
 /backup - Manual database backup
+/backups - List stored backups
+/restore [confirm] - Reply to a backup file to check it, then restore it
+/config export - Plans, promos, sales, flags and quiet hours as a file
+/config import [confirm] - Reply to a config file to preview, then apply it
+/quiet [HH-HH|off] - Hold non-critical alerts in these local hours
 /help - Show this message";
 
 pub async fn handle(
   app: Arc<AppState>,
-  bot: ReplyBot,
+  mut bot: ReplyBot,
+  msg: Message,
   cmd: Command,
+  dialogue: OnboardingDialogue,
 ) -> ResponseResult<()> {
   let sv = app.sv();
 
   let _ = sv.user.get_or_create(bot.user_id).await;
+  bot.localize(&sv).await;
 
   match &cmd {
     Command::Start(ref_code) => {
@@ -246,25 +829,338 @@ pub async fn handle(
         }
       }
 
-      let text = "<b>Yet Another Counter Strike Panel!</b>\n\n\
-        Use the buttons below to navigate.\n\
-        Read docs: https://yacsp.gitbook.io/yacsp\n\
-        Contact support: @y_a_c_s_p";
-      bot
-        .reply_with_keyboard(
-          text,
-          super::callback::main_menu(sv.license.is_promo_active()),
-        )
-        .await?;
+      if let Err(e) = sv.user.set_storefront(bot.user_id, bot.product()).await {
+        warn!("Failed to remember storefront of {}: {}", bot.user_id, e);
+      }
+
+      if !sv.settings.is_onboarded(bot.user_id).await.unwrap_or(true) {
+        return onboarding::start(&bot, &dialogue).await;
+      }
+
+      let (text, kb) = super::callback::home(
+        &bot,
+        sv.license.is_promo_active(callback::TRIAL_PROMO).await,
+      );
+      bot.reply_with_keyboard(text, kb).await?;
+      super::callback::prompt_terms(&sv, &bot).await?;
     }
     Command::Help if app.admins.contains(&bot.user_id) => {
       bot.reply_html(ADMIN_HELP).await?;
       return Ok(());
     }
     Command::Help => {
-      bot
-        .reply_html("Use /start to access the main menu with buttons.")
-        .await?;
+      bot.reply_html(bot.lang.t(T::UseStart)).await?;
+      return Ok(());
+    }
+    Command::Balance => {
+      match callback::balance_screen(&sv, bot.lang, bot.user_id).await {
+        Ok((text, kb)) => bot.reply_with_keyboard(text, kb).await?,
+        Err(e) => bot.reply_html(format!("❌ {}", e.user_message())).await?,
+      };
+      return Ok(());
+    }
+    Command::History => {
+      match callback::history_screen(&sv, bot.lang, bot.user_id, 0).await {
+        Ok((text, kb)) => bot.reply_with_keyboard(text, kb).await?,
+        Err(e) => bot.reply_html(format!("❌ {}", e.user_message())).await?,
+      };
+      return Ok(());
+    }
+    Command::Lang(code) => {
+      let code = code.trim();
+      if code.is_empty() {
+        let (text, kb) = callback::language_picker(bot.lang);
+        bot.reply_with_keyboard(text, kb).await?;
+        return Ok(());
+      }
+
+      let Some(lang) = Lang::parse(code) else {
+        let codes = Lang::ALL.map(Lang::code).join(", ");
+        bot
+          .reply_html(i18n::fill(
+            bot.lang.t(T::LangUnknown),
+            &[("codes", codes)],
+          ))
+          .await?;
+        return Ok(());
+      };
+      if let Err(e) = sv.settings.set_language(bot.user_id, lang.code()).await {
+        bot.reply_html(format!("❌ {}", e.user_message())).await?;
+        return Ok(());
+      }
+      bot.lang = lang;
+
+      let (welcome, kb) = callback::home(
+        &bot,
+        sv.license.is_promo_active(callback::TRIAL_PROMO).await,
+      );
+      let text = format!("{}\n\n{}", lang.t(T::LangChanged), welcome);
+      bot.reply_with_keyboard(text, kb).await?;
+      return Ok(());
+    }
+    Command::Faq(query) => {
+      let query = query.trim();
+      if query.is_empty() {
+        let entries = sv.faq.all().await.unwrap_or_default();
+        let (text, kb) =
+          super::callback::faq_browse(bot.lang, &entries, bot.support());
+        bot.reply_with_keyboard(text, kb).await?;
+        return Ok(());
+      }
+
+      let found = sv.faq.search(query, 4).await.unwrap_or_default();
+      if let Some((best, related)) = found.split_first() {
+        let (text, kb) =
+          super::callback::faq_answer(bot.lang, best, related, bot.support());
+        bot.reply_with_keyboard(text, kb).await?;
+      } else {
+        let text = i18n::fill(
+          bot.lang.t(T::FaqNothingFound),
+          &[
+            ("query", html::escape(query)),
+            ("support", bot.support().to_string()),
+          ],
+        );
+        let entries = sv.faq.all().await.unwrap_or_default();
+        let (_, kb) =
+          super::callback::faq_browse(bot.lang, &entries, bot.support());
+        bot.reply_with_keyboard(text, kb).await?;
+      }
+      return Ok(());
+    }
+    Command::Ticket(text) => {
+      let text = text.trim();
+      if text.is_empty() {
+        let reply = match sv.ticket.active_for(bot.user_id).await {
+          Ok(Some(ticket)) => i18n::fill(
+            bot.lang.t(T::TicketStatus),
+            &[
+              ("id", ticket.id.to_string()),
+              ("status", format!("{:?}", ticket.status)),
+            ],
+          ),
+          _ => bot.lang.t(T::TicketHelp).to_string(),
+        };
+        bot.reply_html(reply).await?;
+        return Ok(());
+      }
+
+      match sv.ticket.submit(bot.user_id, text).await {
+        Ok((ticket, is_new)) => {
+          support::notify_new_message(&app, &ticket, is_new, text).await;
+          bot
+            .reply_html(i18n::fill(
+              bot.lang.t(T::TicketSent),
+              &[("id", ticket.id.to_string())],
+            ))
+            .await?;
+        }
+        Err(e) => {
+          bot.reply_html(format!("❌ {}", e.user_message())).await?;
+        }
+      }
+      return Ok(());
+    }
+    Command::Downtime(args) => {
+      let args = args.trim();
+      let reply = if args.is_empty() {
+        match sv.settings.get_or_create(bot.user_id).await {
+          Ok(settings) => downtime_status(bot.lang, &settings),
+          Err(e) => format!("❌ {}", e.user_message()),
+        }
+      } else {
+        let result = match parse_downtime(args) {
+          Ok((mins, hours)) => {
+            sv.settings.set_downtime_alert(bot.user_id, mins, hours).await
+          }
+          Err(e) => Err(e),
+        };
+        match result {
+          Ok(settings) => {
+            if settings.downtime_alert_mins.is_none() {
+              app.offline_since.remove(&bot.user_id);
+            }
+            format!(
+              "{}\n\n{}",
+              bot.lang.t(T::Saved),
+              downtime_status(bot.lang, &settings)
+            )
+          }
+          Err(e) => format!("❌ {}", e.user_message()),
+        }
+      };
+      bot.reply_html(reply).await?;
+      return Ok(());
+    }
+    Command::Top(args) => {
+      let board = match args.trim() {
+        "" | "xp" => Board::WeeklyXp,
+        "drops" => Board::Drops,
+        "on" | "off" => {
+          let enabled = args.trim() == "on";
+          let reply =
+            match sv.settings.set_leaderboard(bot.user_id, enabled).await {
+              Ok(()) if enabled => i18n::fill(
+                bot.lang.t(T::TopJoined),
+                &[("alias", leaderboard::alias(&app.secret, bot.user_id))],
+              ),
+              Ok(()) => bot.lang.t(T::TopLeft).to_string(),
+              Err(e) => format!("❌ {}", e.user_message()),
+            };
+          bot.reply_html(reply).await?;
+          return Ok(());
+        }
+        _ => {
+          bot.reply_html(bot.lang.t(T::TopUsage)).await?;
+          return Ok(());
+        }
+      };
+      let result = async {
+        let top = sv.stats.leaderboard(board, leaderboard::SHOWN).await?;
+        let rank = sv.stats.rank(board, bot.user_id).await?;
+        Ok::<_, Error>(leaderboard::message(
+          bot.lang,
+          &app.secret,
+          board,
+          &top,
+          bot.user_id,
+          rank,
+        ))
+      }
+      .await;
+      let reply = match result {
+        Ok(text) => text,
+        Err(e) => format!("❌ {}", e.user_message()),
+      };
+      bot.reply_html(reply).await?;
+      return Ok(());
+    }
+    Command::Timezone(args) => {
+      let args = args.trim();
+      let result = if args.is_empty() {
+        sv.settings.get_or_create(bot.user_id).await.map(|s| {
+          i18n::fill(
+            bot.lang.t(T::Timezone),
+            &[("offset", format_utc_offset(s.utc_offset_mins))],
+          )
+        })
+      } else {
+        match parse_utc_offset(args) {
+          Ok(offset) => {
+            sv.settings.set_utc_offset(bot.user_id, offset).await.map(|s| {
+              i18n::fill(
+                bot.lang.t(T::TimezoneSet),
+                &[("offset", format_utc_offset(s.utc_offset_mins))],
+              )
+            })
+          }
+          Err(e) => Err(e),
+        }
+      };
+      let reply = match result {
+        Ok(text) => text,
+        Err(e) => format!("❌ {}", e.user_message()),
+      };
+      bot.reply_html(reply).await?;
+      return Ok(());
+    }
+    Command::Channel(args) => {
+      let result = match args.trim() {
+        "" => sv.settings.channel(bot.user_id).await.map(|channel| {
+          i18n::fill(
+            bot.lang.t(T::Channel),
+            &[("channel", channel.name().to_string())],
+          )
+        }),
+        name => match Channel::parse(name) {
+          Some(channel) => {
+            sv.settings.set_channel(bot.user_id, channel).await.map(|_| {
+              i18n::fill(
+                bot.lang.t(T::ChannelSet),
+                &[("channel", channel.name().to_string())],
+              )
+            })
+          }
+          None => Ok(bot.lang.t(T::ChannelUsage).to_string()),
+        },
+      };
+      let reply = match result {
+        Ok(text) => text,
+        Err(e) => format!("❌ {}", e.user_message()),
+      };
+      bot.reply_html(reply).await?;
+      return Ok(());
+    }
+    Command::DeleteAccount => {
+      callback::handle_delete_account(&sv, &bot, &app).await?;
+      return Ok(());
+    }
+    Command::Country(args) => {
+      let args = args.trim();
+      let result = match args {
+        "" => sv.pricing.country(bot.user_id).await.map(|country| {
+          i18n::fill(
+            bot.lang.t(T::Country),
+            &[(
+              "country",
+              country.unwrap_or_else(|| bot.lang.t(T::Unknown).to_string()),
+            )],
+          )
+        }),
+        "auto" => sv
+          .settings
+          .set_country(bot.user_id, None)
+          .await
+          .map(|_| bot.lang.t(T::CountryAuto).to_string()),
+        code => match sv::pricing::normalize_country(code) {
+          Some(country) => sv
+            .settings
+            .set_country(bot.user_id, Some(country.clone()))
+            .await
+            .map(|_| {
+              i18n::fill(bot.lang.t(T::CountrySet), &[("country", country)])
+            }),
+          None => Ok(bot.lang.t(T::CountryUsage).to_string()),
+        },
+      };
+      let reply = match result {
+        Ok(text) => text,
+        Err(e) => format!("❌ {}", e.user_message()),
+      };
+      bot.reply_html(reply).await?;
+      return Ok(());
+    }
+    Command::Goal(args) => {
+      let args: Vec<&str> = args.split_whitespace().collect();
+      let reply = match args.as_slice() {
+        [] => bot.lang.t(T::GoalHelp).to_string(),
+        ["off"] => match sv.goal.remove(bot.user_id).await {
+          Ok(true) => bot.lang.t(T::GoalRemoved).to_string(),
+          Ok(false) => bot.lang.t(T::NoGoal).to_string(),
+          Err(e) => format!("❌ {}", e.user_message()),
+        },
+        [kind, target] => {
+          let kind = match *kind {
+            "xp" => Some(GoalKind::Xp),
+            "runtime" | "hours" => Some(GoalKind::Runtime),
+            _ => None,
+          };
+          match (kind, target.parse::<f64>()) {
+            (Some(kind), Ok(target)) => {
+              match sv.goal.set(bot.user_id, kind, target).await {
+                Ok(goal) => i18n::fill(
+                  bot.lang.t(T::GoalSet),
+                  &[("goal", goal.kind.format(goal.target))],
+                ),
+                Err(e) => format!("❌ {}", e.user_message()),
+              }
+            }
+            _ => bot.lang.t(T::GoalUsage).to_string(),
+          }
+        }
+        _ => bot.lang.t(T::GoalUsage).to_string(),
+      };
+      bot.reply_html(reply).await?;
       return Ok(());
     }
     Command::Link(key) => {
@@ -272,9 +1168,62 @@ pub async fn handle(
       match result {
         Ok(_) => {
           bot
-            .reply_html(format!(
-              "✅ License <code>{}</code> has been linked to your account!",
-              key.trim()
+            .reply_html(i18n::fill(
+              bot.lang.t(T::Linked),
+              &[("key", key.trim().to_string())],
+            ))
+            .await?;
+        }
+        Err(e) => {
+          bot.reply_html(format!("❌ {}", e.user_message())).await?;
+        }
+      }
+      return Ok(());
+    }
+    Command::Transfer(args) => {
+      let parts: Vec<&str> = args.split_whitespace().collect();
+      let target = match parts.as_slice() {
+        [key, to] => to.parse::<i64>().ok().map(|to| (*key, to)),
+        _ => None,
+      };
+      let Some((key, to)) = target else {
+        bot.reply_html(bot.lang.t(T::TransferUsage)).await?;
+        return Ok(());
+      };
+
+      match sv.license.transferable(key, bot.user_id, to).await {
+        Ok(license) => {
+          let (text, kb) = callback::transfer_prompt(
+            bot.lang,
+            &license,
+            to,
+            app.config.transfer_review,
+          );
+          bot.reply_with_keyboard(text, kb).await?;
+        }
+        Err(e) => {
+          bot.reply_html(format!("❌ {}", e.user_message())).await?;
+        }
+      }
+      return Ok(());
+    }
+    Command::Promo(code) => {
+      let code = code.trim();
+      if code.is_empty() {
+        bot.reply_html(bot.lang.t(T::PromoUsage)).await?;
+        return Ok(());
+      }
+
+      match sv.license.claim_promo(bot.user_id, code).await {
+        Ok(license) => {
+          bot
+            .reply_html(i18n::fill(
+              bot.lang.t(T::PromoRedeemed),
+              &[
+                ("type", format!("{:?}", license.license_type)),
+                ("expires", utils::format_date(license.expires_at)),
+                ("key", license.key),
+              ],
             ))
             .await?;
         }
@@ -284,13 +1233,37 @@ pub async fn handle(
       }
       return Ok(());
     }
+    Command::Redeem(code) => {
+      let code = code.trim();
+      if code.is_empty() {
+        bot.reply_html(bot.lang.t(T::RedeemUsage)).await?;
+        return Ok(());
+      }
+
+      let text = match sv.promo_code.redeem(bot.user_id, code).await {
+        Ok(Redeemed::Balance { amount, balance }) => i18n::fill(
+          bot.lang.t(T::RedeemedBalance),
+          &[("amount", format_usdt(amount)), ("balance", format_usdt(balance))],
+        ),
+        Ok(Redeemed::License(license)) => i18n::fill(
+          bot.lang.t(T::RedeemedLicense),
+          &[
+            ("expires", utils::format_date(license.expires_at)),
+            ("key", license.key),
+          ],
+        ),
+        Err(e) => format!("❌ {}", e.user_message()),
+      };
+      bot.reply_html(text).await?;
+      return Ok(());
+    }
     Command::Ref(arg) => {
       let arg = arg.trim();
       if arg.is_empty() || arg == "clear" || arg == "none" {
         // Clear referral code
         match sv.user.set_referred_by(bot.user_id, None).await {
           Ok(_) => {
-            bot.reply_html("✅ Your referral code has been cleared.").await?;
+            bot.reply_html(bot.lang.t(T::RefCleared)).await?;
           }
           Err(e) => {
             bot.reply_html(format!("❌ {}", e.user_message())).await?;
@@ -309,25 +1282,20 @@ pub async fn handle(
                   .map(|s| (s.discount_percent, s.can_withdraw))
                   .unwrap_or((0, false));
 
-                let text = if can_offer_discount && discount > 0 {
-                  format!(
-                    "✅ Referral code <code>{}</code> applied!\n\
-                    You will receive a {}% discount on purchases!",
-                    arg, discount
-                  )
+                let mut text = i18n::fill(
+                  bot.lang.t(T::ObCodeApplied),
+                  &[("code", html::escape(arg))],
+                );
+                if can_offer_discount && discount > 0 {
+                  text.push_str(&i18n::fill(
+                    bot.lang.t(T::RefDiscount),
+                    &[("discount", discount.to_string())],
+                  ));
                 } else if can_offer_discount {
-                  format!(
-                    "✅ Referral code <code>{}</code> applied!\n\
-                    This is a verified creator.",
-                    arg
-                  )
+                  text.push_str(bot.lang.t(T::RefVerified));
                 } else {
-                  format!(
-                    "✅ Referral code <code>{}</code> applied!\n\
-                    <i>Note: This user is not a verified creator, so no discount is available.</i>",
-                    arg
-                  )
-                };
+                  text.push_str(bot.lang.t(T::RefNotVerified));
+                }
                 bot.reply_html(text).await?;
               }
               Err(e) => {
@@ -354,22 +1322,13 @@ pub async fn handle(
         Ok(_) => {
           if let Some(c) = code_opt {
             bot
-              .reply_html(format!(
-                "✅ Your custom referral code is now set!\n\
-                <b>Code:</b> <code>{}</code>\n\n\
-                Share this code with others. They can use:\n\
-                <code>/ref {}</code>\n\
-                to set you as their referrer.",
-                c, c
+              .reply_html(i18n::fill(
+                bot.lang.t(T::MyCodeSet),
+                &[("code", html::escape(&c))],
               ))
               .await?;
           } else {
-            bot
-              .reply_html(
-                "✅ Your custom referral code has been cleared.\n\
-                Users can still use your user ID as referral code.",
-              )
-              .await?;
+            bot.reply_html(bot.lang.t(T::MyCodeCleared)).await?;
           }
         }
         Err(e) => {
@@ -381,31 +1340,25 @@ pub async fn handle(
     Command::Fund(amount_str) => {
       let amount_str = amount_str.trim();
       if amount_str.is_empty() {
-        bot.reply_html("Usage: /fund AMOUNT\nExample: /fund 10.5").await?;
+        bot.reply_html(bot.lang.t(T::FundUsage)).await?;
         return Ok(());
       }
 
       let amount_usdt: f64 = match amount_str.parse() {
         Ok(a) => a,
         Err(_) => {
-          bot
-            .reply_html(
-              "❌ Invalid amount. Use: /fund AMOUNT\nExample: /fund 10.5",
-            )
-            .await?;
+          bot.reply_html(bot.lang.t(T::FundInvalid)).await?;
           return Ok(());
         }
       };
 
       if amount_usdt < 1.0 {
-        bot.reply_html("❌ Minimum deposit is 1 USDT.").await?;
+        bot.reply_html(bot.lang.t(T::FundMinimum)).await?;
         return Ok(());
       }
 
       let Some(cryptobot) = &app.cryptobot else {
-        bot
-          .reply_html("❌ Payment system is not configured. Contact support.")
-          .await?;
+        bot.reply_html(bot.lang.t(T::PaymentsNotConfigured)).await?;
         return Ok(());
       };
 
@@ -427,20 +1380,20 @@ pub async fn handle(
             )
             .await;
 
-          let text = format!(
-            "💵 <b>Payment Invoice Created</b>\n\n\
-            <b>Amount:</b> {} USDT\n\n\
-            <a href=\"{}\">Click here to pay via CryptoBot</a>\n\n\
-            <i>After payment, use /start and click \"Check Payments\".</i>",
-            amount_usdt, invoice.bot_invoice_url
+          let text = i18n::fill(
+            bot.lang.t(T::FundInvoice),
+            &[
+              ("amount", amount_usdt.to_string()),
+              ("url", invoice.bot_invoice_url),
+            ],
           );
           bot.reply_html(text).await?;
         }
         Err(e) => {
           bot
-            .reply_html(format!(
-              "❌ Failed to create invoice: {}",
-              e.user_message()
+            .reply_html(i18n::fill(
+              bot.lang.t(T::InvoiceFailed),
+              &[("error", e.user_message())],
             ))
             .await?;
         }
@@ -451,18 +1404,30 @@ pub async fn handle(
   }
 
   if app.admins.contains(&bot.user_id) {
-    handle_admin_command(app, bot, cmd).await?;
+    handle_admin_command(app, bot, &msg, cmd).await?;
   }
 
   Ok(())
 }
 
+/// Custom fields block of /info, empty when there are none
+fn fields_section(fields: &[custom_field::Model]) -> String {
+  if fields.is_empty() {
+    return String::new();
+  }
+  let mut text = String::from("\n🏷 <b>Fields</b>\n");
+  for field in fields {
+    text.push_str(&format!("{}: {}\n", field.name, html::escape(&field.value)));
+  }
+  text
+}
+
 async fn process_info_command(
   sv: &Services<'_>,
   app: &AppState,
   bot: &ReplyBot,
   input: String,
-) -> Result<String> {
+) -> Result<(String, Option<InlineKeyboardMarkup>)> {
   let input = input.trim();
   if input.is_empty() {
     return Err(Error::InvalidArgs(
@@ -475,6 +1440,43 @@ async fn process_info_command(
     let username = bot.infer_username(ChatId(user_id)).await;
     let stats = sv.stats.display_stats(user_id).await?;
     let licenses = sv.license.by_user(user_id, true).await?;
+    let instances = sv.stats.instances(user_id).await?;
+    let today = Utc::now().date_naive();
+    // XP since the oldest snapshot of the month
+    let month_xp = sv
+      .stats
+      .trend(user_id, today, 30)
+      .await?
+      .first()
+      .map(|oldest| stats.total_xp as i64 - oldest.total_xp);
+    let per_license: HashMap<_, _> = sv
+      .stats
+      .by_licenses(licenses.iter().map(|l| l.key.clone()))
+      .await?
+      .into_iter()
+      .map(|s| (s.license_key.clone(), s))
+      .collect();
+    let fields =
+      sv.custom_field.of(FieldScope::User, &user_id.to_string()).await?;
+    let risk = match sv.risk.get(user_id).await? {
+      Some(risk) => {
+        let factors: Vec<_> = sv::risk::factors(&risk)
+          .iter()
+          .map(|f| format!("{} +{}", f.signal.label(), f.points))
+          .collect();
+        format!(
+          "{} {}/100{}",
+          sv::risk::Level::of(risk.score).icon(),
+          risk.score,
+          if factors.is_empty() {
+            String::new()
+          } else {
+            format!(" ({})", factors.join(", "))
+          }
+        )
+      }
+      None => "not scored yet".into(),
+    };
 
     let mut total_active_sessions = 0;
     let mut lic_text = String::new();
@@ -483,19 +1485,27 @@ async fn process_info_command(
       let active = app.sessions.get(&lic.key).map(|s| s.len()).unwrap_or(0);
       total_active_sessions += active;
 
-      let status_icon = if lic.is_blocked {
+      let status_icon = if lic.is_honeypot {
+        "🍯"
+      } else if lic.is_blocked {
         "⛔"
       } else if lic.expires_at < Utc::now().naive_utc() {
         "❌"
+      } else if lic.suspended_until(Utc::now().naive_utc()).is_some() {
+        "⏸"
       } else if active > 0 {
         "🟢"
       } else {
         "⚪"
       };
 
+      let usage = per_license
+        .get(&lic.key)
+        .map(|s| format!(" · {} XP, {:.1}h", s.total_xp, s.runtime_hours))
+        .unwrap_or_default();
       lic_text.push_str(&format!(
-        "{} <code>{}</code> ({:?})\n",
-        status_icon, lic.key, lic.license_type
+        "{} <code>{}</code> ({:?}){}\n",
+        status_icon, lic.key, lic.license_type, usage
       ));
     }
 
@@ -505,53 +1515,72 @@ async fn process_info_command(
       .map(|id| id.to_string())
       .unwrap_or_else(|| "None".to_string());
 
-    return Ok(format!(
+    let text = format!(
       "👤 <b>User Info</b>\n\
       ID: <code>{}</code>\n\
       Name: {}\n\
       Registered: {}\n\
       Balance: {}\n\
-      Referred by: {}\n\n\
+      Referred by: {}\n\
+      Risk: {}\n{}\n\
       📊 <b>Global Stats</b>\n\
       XP (Week/Total): {} / {}\n\
+      XP (30 days): {}\n\
       Runtime: {:.1}h\n\n\
       Repr:\n\
       {user:#?}\n\
       Total Sessions: {}\n\n\
       🔑 <b>Licenses ({})</b>\n\
+      {}\n\
+      🖥 <b>Instances ({})</b>\n\
       {}",
       user.tg_user_id,
       username,
       utils::format_date(user.reg_date),
       balance_str,
       referral_str,
+      risk,
+      fields_section(&fields),
       stats.weekly_xp,
       stats.total_xp,
+      month_xp.map_or("no snapshots yet".into(), |xp| format!("+{}", xp)),
       stats.runtime_hours,
       total_active_sessions,
       licenses.len(),
-      if lic_text.is_empty() { "No licenses" } else { &lic_text }
-    ));
+      if lic_text.is_empty() { "No licenses" } else { &lic_text },
+      instances.len(),
+      callback::instances_breakdown(bot.lang, &instances)
+    );
+    return Ok((text, None));
   }
 
   let key = input;
   let license = sv.license.by_key(key).await?.ok_or(Error::LicenseNotFound)?;
   let username = bot.infer_username(ChatId(license.tg_user_id)).await;
+  let history = sv.session.history(key, 5).await?;
 
   let sessions = app.sessions.get(key);
   let active_count = sessions.as_ref().map(|s| s.len()).unwrap_or(0);
   let now = Utc::now().naive_utc();
 
   let status = if license.is_blocked {
-    "⛔ BLOCKED"
+    "⛔ BLOCKED".to_string()
   } else if license.expires_at < now {
-    "❌ EXPIRED"
+    "❌ EXPIRED".to_string()
+  } else if let Some(until) = license.suspended_until(now) {
+    format!("⏸ SUSPENDED for {}", utils::format_duration(until - now))
   } else if active_count > 0 {
-    "🟢 ONLINE"
+    "🟢 ONLINE".to_string()
   } else {
-    "⚪ OFFLINE"
+    "⚪ OFFLINE".to_string()
   };
 
+  let fields = sv.custom_field.of(FieldScope::License, &license.key).await?;
+  let usage = sv.stats.by_licenses([license.key.clone()]).await?.pop();
+  let (weekly_xp, total_xp, runtime_hours) = usage
+    .map(|s| (s.weekly_xp, s.total_xp, s.runtime_hours))
+    .unwrap_or_default();
+
   let duration_left = if license.expires_at > now {
     utils::format_duration(license.expires_at - now)
   } else {
@@ -562,43 +1591,82 @@ async fn process_info_command(
     "🔑 <b>License Info</b>\n\n\
     <b>Key:</b> <code>{}</code>\n\
     <b>Type:</b> {:?}\n\
+    <b>Product:</b> {}\n\
     <b>Status:</b> {}\n\
-    <b>Owner:</b> {} (<code>{}</code>)\n\n\
+    <b>Owner:</b> {} (<code>{}</code>)\n{}\n\
     📅 <b>Timeline</b>\n\
     Created: {}\n\
     Expires: {} (in {})\n\n\
+    📊 <b>Stats</b>\n\
+    XP (Week/Total): {} / {}\n\
+    Runtime: {:.1}h\n\n\
     🖥 <b>Sessions ({}/{})</b>\n",
     license.key,
     license.license_type,
+    license.product,
     status,
     username,
     license.tg_user_id,
+    fields_section(&fields),
     utils::format_date(license.created_at),
     utils::format_date(license.expires_at),
     duration_left,
+    weekly_xp,
+    total_xp,
+    runtime_hours,
     active_count,
     license.max_sessions
   );
 
+  let mut kick_rows = Vec::new();
   if let Some(sess_list) = sessions {
     for (i, s) in sess_list.iter().enumerate() {
       text.push_str(&format!(
-        " {}. ID: <code>{}...</code>\n    HWID: <code>{}</code>\n",
+        " {}. ID: <code>{}...</code>\n    HWID: <code>{}</code>\n    \
+        IP: <code>{}</code>{}\n    Platform: {}\n",
         i + 1,
-        &s.session_id.chars().take(8).collect::<String>(),
-        s.hwid_hash.as_deref().unwrap_or("Unknown")
+        html::escape(&s.session_id.chars().take(8).collect::<String>()),
+        s.hwid_hash.as_deref().unwrap_or("Unknown"),
+        s.ip.as_deref().unwrap_or("unknown"),
+        s.country.as_ref().map(|c| format!(" ({})", c)).unwrap_or_default(),
+        s.platform.as_deref().unwrap_or("unknown")
       ));
+      kick_rows.push(vec![InlineKeyboardButton::callback(
+        format!("❌ Kick {}", i + 1),
+        callback::Callback::KickSession {
+          key: license.key.clone(),
+          tag: s.tag(),
+        }
+        .to_data(),
+      )]);
     }
   } else if active_count == 0 {
     text.push_str(" <i>No active sessions</i>");
   }
 
-  Ok(text)
-}
-
-async fn handle_admin_command(
-  app: Arc<AppState>,
+  let ended: Vec<_> = history.iter().filter(|s| s.ended_at.is_some()).collect();
+  if !ended.is_empty() {
+    text.push_str("\n\n📜 <b>Recent Sessions</b>\n");
+    for s in ended {
+      let ended_at = s.ended_at.unwrap_or(s.last_seen);
+      text.push_str(&format!(
+        " {} for {} ({})\n",
+        utils::format_date(s.started_at),
+        utils::format_duration(ended_at - s.started_at),
+        s.end_reason.as_deref().unwrap_or("unknown")
+      ));
+    }
+  }
+
+  let keyboard =
+    (!kick_rows.is_empty()).then(|| InlineKeyboardMarkup::new(kick_rows));
+  Ok((text, keyboard))
+}
+
+async fn handle_admin_command(
+  app: Arc<AppState>,
   bot: ReplyBot,
+  msg: &Message,
   cmd: Command,
 ) -> ResponseResult<()> {
   let sv = app.sv();
@@ -630,6 +1698,8 @@ async fn handle_admin_command(
     });
 
     let resolved_users = future::join_all(user_futures).await;
+    let fields =
+      sv.custom_field.all(FieldScope::User).await.unwrap_or_default();
 
     let mut text =
       format!("👥 <b>Users List (Total: {})</b>\n\n", resolved_users.len());
@@ -678,6 +1748,12 @@ async fn handle_admin_command(
         username,
         user.tg_user_id
       ));
+      if let Some(fields) = fields.get(&user.tg_user_id.to_string()) {
+        text.push_str(&format!(
+          "    🏷 {}\n",
+          html::escape(&sv::custom_field::format_inline(fields))
+        ));
+      }
     }
 
     // Use chunked reply to handle long user lists
@@ -685,14 +1761,25 @@ async fn handle_admin_command(
     return Ok(());
   }
 
+  if let Command::Review = cmd {
+    return review::queue(&app, &bot).await;
+  }
+
+  if let Command::Reconcile(args) = &cmd {
+    return reconcile::run(&app, &bot, args).await;
+  }
+
+  if let Command::Config(args) = &cmd {
+    return config_sync::run(&app, &bot, msg, args).await;
+  }
+
   let result: Result<String> = match cmd {
     Command::Buy { key, duration } => {
-      let duration_str = humantime::format_duration(duration);
+      let duration_str = duration.to_string();
       match key {
         // /buy <duration> - generate new license for admin
         None => {
-          let days = duration.as_secs() / 86400;
-          sv.license.create_gift(LicenseType::Pro, days).await.map(
+          sv.license.create_gift(LicenseType::Pro, duration).await.map(
             |l| {
               format!(
                 "✅ Key created ({}):\n<code>{}</code>\n\
@@ -715,6 +1802,91 @@ async fn handle_admin_command(
       }
     }
 
+    Command::Gift(args) => {
+      async {
+        let usage = || {
+          Error::InvalidArgs(
+            "Usage: /gift <duration> [trial|pro]\nExamples: 30d, 2w, 1mo".into(),
+          )
+        };
+        let args: Vec<&str> = args.split_whitespace().collect();
+        let (term, license_type) = parse_gift(&args).ok_or_else(usage)?;
+
+        let gift = sv.license.create_gift(license_type, term).await?;
+        info!("Admin {} created a {} gift key", bot.user_id, term);
+        Ok(format!(
+          "🎁 Gift key ({:?}, {}):\n<code>{}</code>\n\n\
+          The term starts when someone activates it.",
+          gift.license_type, term, gift.key
+        ))
+      }
+      .await
+    }
+
+    Command::GenBulk(args) => {
+      let result = async {
+        let usage = || {
+          Error::InvalidArgs(format!(
+            "Usage: /genbulk <count> <duration> [trial|pro], up to {} keys",
+            GIFT_BATCH_MAX
+          ))
+        };
+        let args: Vec<&str> = args.split_whitespace().collect();
+        let (count, rest) = args.split_first().ok_or_else(usage)?;
+        let count = count.parse::<usize>().map_err(|_| usage())?;
+        let (term, license_type) = parse_gift(rest).ok_or_else(usage)?;
+        let gifts = sv.license.create_gifts(license_type, term, count).await?;
+        Ok::<_, Error>((term, gifts))
+      }
+      .await;
+
+      match result {
+        Ok((term, gifts)) => {
+          info!(
+            "Admin {} generated {} gift keys of {}",
+            bot.user_id,
+            gifts.len(),
+            term
+          );
+          let keys: String =
+            gifts.iter().map(|gift| format!("{}\n", gift.key)).collect();
+          let name = format!(
+            "gifts-{}-{}.txt",
+            gifts.len(),
+            Utc::now().format("%Y%m%d-%H%M%S")
+          );
+          bot
+            .send_document(InputFile::memory(keys.into_bytes()).file_name(name))
+            .await?;
+        }
+        Err(e) => {
+          bot.reply_html(format!("❌ {}", html::escape(&e.user_message()))).await?;
+        }
+      }
+      return Ok(());
+    }
+
+    Command::Gifts => {
+      async {
+        let gifts = sv.license.gifts().await?;
+        if gifts.is_empty() {
+          return Ok("🎁 No unredeemed gift keys".to_string());
+        }
+        let mut text = format!("🎁 <b>Unredeemed gifts</b> ({})\n", gifts.len());
+        for gift in &gifts {
+          text.push_str(&format!(
+            "\n<code>{}</code> {:?}, {}, created {}",
+            gift.key,
+            gift.license_type,
+            utils::format_duration(gift.expires_at - gift.created_at),
+            utils::format_date(gift.created_at)
+          ));
+        }
+        Ok(text)
+      }
+      .await
+    }
+
     Command::Ban(key) => {
       let result = sv.license.set_blocked(&key, true).await;
       if result.is_ok() {
@@ -729,22 +1901,263 @@ async fn handle_admin_command(
       .await
       .map(|_| "✅ Key unblocked".into()),
 
-    Command::Info(input) => process_info_command(&sv, &app, &bot, input).await,
+    Command::Suspend(args) => {
+      async {
+        let usage =
+          || Error::InvalidArgs("Usage: /suspend <key> <duration>".into());
+        let (key, duration) = args.trim().split_once(' ').ok_or_else(usage)?;
+        let duration = utils::parse_duration(duration).ok_or_else(usage)?;
+
+        let until = sv.license.suspend(key, duration).await?;
+        app.drop_sessions(key);
+        Ok(format!(
+          "⏸ Key suspended until {}, sessions dropped",
+          utils::format_date(until)
+        ))
+      }
+      .await
+    }
+
+    Command::MaxSessions(args) => {
+      async {
+        let usage =
+          || Error::InvalidArgs("Usage: /maxsessions <key> <n>".into());
+        let (key, max) = args.trim().split_once(' ').ok_or_else(usage)?;
+        let max = max.trim().parse::<i32>().map_err(|_| usage())?;
+
+        let license = sv.license.set_max_sessions(key, max).await?;
+        // running sessions over the new limit have to log in again
+        let active = app.sessions.get(&license.key).map(|s| s.len());
+        let dropped = active.is_some_and(|n| n > max as usize);
+        if dropped {
+          app.drop_sessions(&license.key);
+        }
+        Ok(format!(
+          "✅ <code>{}</code> allows {} session(s){}",
+          license.key,
+          license.max_sessions,
+          if dropped { ", sessions dropped" } else { "" }
+        ))
+      }
+      .await
+    }
+
+    Command::Sessions(args) => {
+      async {
+        let usage = || {
+          Error::InvalidArgs(format!(
+            "Usage: /sessions <key> [limit], up to {}",
+            SESSIONS_MAX
+          ))
+        };
+        let mut args = args.split_whitespace();
+        let key = args.next().ok_or_else(usage)?;
+        let limit = match args.next() {
+          None => SESSIONS_DEFAULT,
+          Some(limit) => limit
+            .parse()
+            .ok()
+            .filter(|limit| (1..=SESSIONS_MAX).contains(limit))
+            .ok_or_else(usage)?,
+        };
+        let license =
+          sv.license.by_key(key).await?.ok_or(Error::LicenseNotFound)?;
+
+        let history = sv.session.history(&license.key, limit).await?;
+        let mut text = format!(
+          "🖥 <b>Sessions of</b> <code>{}</code>\n",
+          license.key
+        );
+        if history.is_empty() {
+          text.push_str("\nNo connections yet.");
+          return Ok(text);
+        }
+
+        // many machines or addresses in a short list hint at a shared key
+        let hwids: HashSet<_> =
+          history.iter().filter_map(|s| s.hwid.as_deref()).collect();
+        let ips: HashSet<_> =
+          history.iter().filter_map(|s| s.ip.as_deref()).collect();
+        let countries: HashSet<_> =
+          history.iter().filter_map(|s| s.country.as_deref()).collect();
+        text.push_str(&format!(
+          "{} connection(s) from {} machine(s), {} address(es) \
+          and {} country(ies)\n",
+          history.len(),
+          hwids.len(),
+          ips.len(),
+          countries.len()
+        ));
+        let flagged = sv.sharing.history(&license.key, 5).await?;
+        if !flagged.is_empty() {
+          let days: Vec<_> = flagged
+            .iter()
+            .map(|alert| {
+              format!("{} ({}/{})", alert.day, alert.hwids, alert.ips)
+            })
+            .collect();
+          text.push_str(&format!(
+            "⚠️ Flagged for sharing (machines/addresses): {}\n",
+            days.join(", ")
+          ));
+        }
+
+        let now = Utc::now().naive_utc();
+        for s in &history {
+          let (length, outcome) = match s.ended_at {
+            Some(ended_at) => (
+              ended_at - s.started_at,
+              s.end_reason.clone().unwrap_or_else(|| "unknown".into()),
+            ),
+            None => (now - s.started_at, "🟢 live".to_string()),
+          };
+          text.push_str(&format!(
+            "\n{} for {} ({}){}\n    HWID <code>{}</code> · IP <code>{}</code>{} · {}",
+            utils::format_date(s.started_at),
+            utils::format_duration(length),
+            html::escape(&outcome),
+            if s.xp > 0 { format!(", {} XP", s.xp) } else { String::new() },
+            html::escape(s.hwid.as_deref().unwrap_or("unknown")),
+            s.ip.as_deref().unwrap_or("unknown"),
+            s.country.as_ref().map(|c| format!(" ({})", c)).unwrap_or_default(),
+            html::escape(s.platform.as_deref().unwrap_or("unknown"))
+          ));
+        }
+        Ok(text)
+      }
+      .await
+    }
+
+    Command::Info(input) => {
+      match process_info_command(&sv, &app, &bot, input).await {
+        Ok((text, Some(keyboard))) => bot.reply_with_keyboard(text, keyboard).await?,
+        Ok((text, None)) => bot.reply_html(text).await?,
+        Err(e) => bot.reply_html(format!("❌ {}", e.user_message())).await?,
+      };
+      return Ok(());
+    }
     Command::Backup => {
-      if app.perform_backup(bot.chat_id).await.is_err() {
+      // the raw database only goes out when backups aren't encrypted
+      if app.perform_backup(bot.chat_id).await.is_err()
+        && app.config.backup_key.is_none()
+      {
         bot.send_document(InputFile::file("licenses.db")).await?;
       }
       return Ok(());
     }
+    Command::Restore(args) => {
+      let apply = match args.trim() {
+        "" => false,
+        "confirm" => true,
+        _ => {
+          bot.reply_html("❌ Usage: /restore [confirm]").await?;
+          return Ok(());
+        }
+      };
+      let Some(document) =
+        msg.reply_to_message().and_then(|reply| reply.document())
+      else {
+        bot
+          .reply_html(
+            "❌ Reply to a backup file with /restore to check it, \
+            then with <code>/restore confirm</code> to restore it",
+          )
+          .await?;
+        return Ok(());
+      };
+      if document.file.size > RESTORE_MAX_SIZE {
+        bot.reply_html("❌ Backup is too large for the Bot API (20MB)").await?;
+        return Ok(());
+      }
+
+      let file = bot.inner.get_file(document.file.id.clone()).await?;
+      let mut data = Vec::with_capacity(file.size as usize);
+      bot.inner.download_file(&file.path, &mut data).await?;
+
+      let restored = app.restore(data, apply).await;
+      let text = match restored {
+        Ok(restored) => {
+          let summary = format!(
+            "{} tables, {} users, {} licenses",
+            restored.tables, restored.users, restored.licenses
+          );
+          match restored.previous {
+            Some(previous) => {
+              warn!("Admin {} restored the database", bot.user_id);
+              format!(
+                "✅ <b>Database restored</b>\n\n{}\n\
+                The replaced data was saved to <code>{}</code>",
+                summary,
+                previous.display()
+              )
+            }
+            None => format!(
+              "🔍 <b>Backup is valid</b>\n\n{}\n\n\
+              Reply to it with <code>/restore confirm</code> to replace the \
+              live data. Writes pause for the swap.",
+              summary
+            ),
+          }
+        }
+        Err(e) => format!("❌ Restore failed: {}", html::escape(&e.to_string())),
+      };
+      bot.reply_html(text).await?;
+      return Ok(());
+    }
+    Command::Quiet(args) => {
+      async {
+        let args = args.trim();
+        let settings = if args.is_empty() {
+          sv.settings.get_or_create(bot.user_id).await?
+        } else {
+          let hours = parse_quiet(args)?;
+          sv.settings.set_quiet_hours(bot.user_id, hours).await?
+        };
+        Ok(quiet_status(&settings))
+      }
+      .await
+    }
+    Command::Backups => {
+      async {
+        let retention = app.config.backup_retention;
+        let mut text = format!(
+          "🗄 <b>Backups</b> (keeping {} daily, {} weekly)\n",
+          retention.daily, retention.weekly
+        );
+
+        let local =
+          sv::backup::local(Path::new(&app.config.backup_dir)).await?;
+        list_snapshots(&mut text, &app.config.backup_dir, &local);
+        if let Some(s3) = &app.config.backup_s3 {
+          match s3.list().await {
+            Ok(remote) => {
+              list_snapshots(&mut text, &format!("s3://{}", s3.bucket()), &remote)
+            }
+            Err(e) => {
+              text.push_str(&format!("\n<b>s3://{}</b>\n⚠️ {}\n", s3.bucket(), e))
+            }
+          }
+        }
+        Ok(text)
+      }
+      .await
+    }
     Command::Builds => match sv.build.all().await {
       Ok(builds) if !builds.is_empty() => {
         let mut text = String::from("<b>All Builds:</b>\n");
         for build in builds {
           let status = if build.is_active { "✅" } else { "❌" };
+          let mut platforms = vec![build.platform.clone()];
+          let artifacts =
+            sv.build.artifacts(&build.version).await.unwrap_or_default();
+          platforms.extend(artifacts.into_iter().map(|a| a.platform));
           text.push_str(&format!(
-            "\n{} <b>v{}</b>\n{} downloads\n{}\n",
+            "\n{} <b>v{}</b> · {} · {}\n{}\n{} downloads\n{}\n",
             status,
             build.version,
+            build.product,
+            build.channel.name(),
+            platforms.join(", "),
             build.downloads,
             utils::format_date(build.created_at)
           ));
@@ -759,7 +2172,7 @@ async fn handle_admin_command(
       Err(e) => Err(e),
     },
 
-    Command::Publish { filename, version, changelog } => {
+    Command::Publish { filename, version, channel, platform, changelog } => {
       async {
         let file_path = format!("{}/{}", app.config.builds_directory, filename);
         let path = Path::new(&file_path);
@@ -774,11 +2187,52 @@ async fn handle_admin_command(
         let changelog_opt =
           if changelog.is_empty() { None } else { Some(changelog.clone()) };
 
-        let build =
-          sv.build.create(version.clone(), file_path, changelog_opt).await?;
+        // `1.2@tool` publishes for another product
+        let (version, product) =
+          version.split_once('@').unwrap_or((&version, sv::product::DEFAULT));
+        let build = sv
+          .build
+          .create(
+            version.to_string(),
+            file_path,
+            changelog_opt,
+            product,
+            channel,
+            &platform,
+          )
+          .await?;
 
-        // Notify users with active licenses about the new version
-        let active_users = sv.user.with_active_licenses().await.unwrap_or_default();
+        // Keep the release in the inbox for users who miss the DM, beta
+        // builds are only announced to their testers
+        let body = if changelog.is_empty() {
+          "Use /start to download the latest build.".to_string()
+        } else {
+          format!("<code>{}</code>", html::escape(&changelog))
+        };
+        if channel == Channel::Stable
+          && let Err(e) = sv
+            .announcement
+            .create(
+              AnnouncementCategory::Release,
+              &format!("Version {} released", build.version),
+              &body,
+            )
+            .await
+        {
+          warn!("Failed to post release announcement: {}", e);
+        }
+
+        // Notify users with active licenses on the build's channel
+        let mut active_users = sv
+          .user
+          .with_active_licenses(&build.product)
+          .await
+          .unwrap_or_default();
+        if channel == Channel::Beta {
+          let testers =
+            sv.settings.on_channel(Channel::Beta).await.unwrap_or_default();
+          active_users.retain(|user| testers.contains(&user.tg_user_id));
+        }
         let mut notified = 0;
         let mut failed = 0;
 
@@ -800,8 +2254,12 @@ async fn handle_admin_command(
             )
           };
 
-          match app
-            .bot
+          let Some((bot, notification)) =
+            app.reach(user.tg_user_id, notification).await
+          else {
+            continue;
+          };
+          match bot
             .send_message(ChatId(user.tg_user_id), notification)
             .parse_mode(ParseMode::Html)
             .await
@@ -814,12 +2272,18 @@ async fn handle_admin_command(
         Ok(format!(
           "✅ Build published!\n\n\
           <b>Version:</b> {}\n\
+          <b>Channel:</b> {}\n\
+          <b>Platform:</b> {}\n\
           <b>File:</b> {}\n\
+          <b>SHA-256:</b> <code>{}</code>\n\
           <b>Created:</b> {}\n\n\
           📢 <b>Notifications:</b>\n\
           Sent: {} | Failed: {}",
           build.version,
+          build.channel.name(),
+          build.platform,
           build.file_path,
+          build.checksum.as_deref().unwrap_or("-"),
           utils::format_date(build.created_at),
           notified,
           failed
@@ -828,6 +2292,75 @@ async fn handle_admin_command(
       .await
     }
 
+    Command::Artifact(args) => {
+      async {
+        let parts: Vec<&str> = args.split_whitespace().collect();
+        let [filename, version, platform] = parts.as_slice() else {
+          return Err(Error::InvalidArgs(
+            "Usage: /artifact <filename> <version> <platform>".into(),
+          ));
+        };
+        let platform =
+          sv::build::normalize_platform(platform).ok_or_else(|| {
+            Error::InvalidArgs(format!(
+              "Unknown platform, use one of: {}",
+              sv::build::PLATFORMS.join(", ")
+            ))
+          })?;
+
+        let file_path = format!("{}/{}", app.config.builds_directory, filename);
+        if !Path::new(&file_path).exists() {
+          return Err(Error::InvalidArgs(format!(
+            "File not found: {}",
+            file_path
+          )));
+        }
+
+        let artifact =
+          sv.build.add_artifact(version, platform, file_path).await?;
+        Ok(format!(
+          "✅ v{} is now available for {}\n\n\
+          <b>File:</b> {}\n\
+          <b>SHA-256:</b> <code>{}</code>",
+          artifact.version,
+          artifact.platform,
+          artifact.file_path,
+          artifact.checksum.as_deref().unwrap_or("-")
+        ))
+      }
+      .await
+    }
+
+    Command::Downloads(version) => {
+      async {
+        let version = version.trim();
+        if version.is_empty() {
+          return Err(Error::InvalidArgs("Usage: /downloads <version>".into()));
+        }
+        let (recent, total) =
+          sv.download_tokens.downloads(version, DOWNLOADS_SHOWN).await?;
+        if recent.is_empty() {
+          return Ok(format!("No downloads of v{} yet", version));
+        }
+
+        let mut text =
+          format!("📥 <b>Downloads of v{}</b> ({} total)\n", version, total);
+        for download in recent {
+          text.push_str(&format!(
+            "\n{} <code>{}</code> {}\n  key <code>{}</code>, token <code>{}</code>, {}\n",
+            utils::format_date(download.created_at),
+            download.tg_user_id,
+            download.platform.as_deref().unwrap_or("default"),
+            download.license_key.as_deref().unwrap_or("-"),
+            download.token.chars().take(8).collect::<String>(),
+            download.ip.as_deref().unwrap_or("unknown IP")
+          ));
+        }
+        Ok(text)
+      }
+      .await
+    }
+
     Command::Yank(version) | Command::Deactivate(version) => {
       async {
         let build =
@@ -865,9 +2398,10 @@ async fn handle_admin_command(
     }
 
     Command::GlobalStats => {
-      async {
+      let result = async {
         let stats = sv.stats.aggregate().await?;
-        Ok(format!(
+        let gains = sv.stats.gains(None, callback::CHART_DAYS).await?;
+        let text = format!(
           "📊 <b>Global Stats</b>\n\n\
           <b>XP:</b>\n\
           Weekly: {}\n\
@@ -880,7 +2414,188 @@ async fn handle_admin_command(
           stats.total_drops,
           stats.total_runtime_hours,
           stats.active_instances
-        ))
+        );
+        Ok::<_, Error>((text, gains))
+      }
+      .await;
+
+      match result {
+        Ok((text, gains)) => {
+          bot.reply_html(text).await?;
+          if gains.iter().any(|g| g.xp > 0 || g.runtime_hours > 0.0) {
+            let caption = format!(
+              "📈 <b>Last {} days</b>, XP a day on top, runtime below\n\
+              XP: +{}\nRuntime: {:.1}h",
+              callback::CHART_DAYS,
+              gains.iter().map(|g| g.xp).sum::<i64>(),
+              gains.iter().map(|g| g.runtime_hours).sum::<f64>()
+            );
+            callback::send_chart(&bot, &gains, caption).await?;
+          }
+        }
+        Err(e) => {
+          bot.reply_html(format!("❌ {}", e.user_message())).await?;
+        }
+      }
+      return Ok(());
+    }
+
+    Command::BotStats(args) => {
+      async {
+        let days = match args.trim() {
+          "" => BOTSTATS_DAYS,
+          days => days.parse::<i64>().ok().filter(|d| *d > 0).ok_or_else(
+            || Error::InvalidArgs("Usage: /botstats [days]".into()),
+          )?,
+        };
+        let since = Utc::now().date_naive() - TimeDelta::days(days - 1);
+        let totals = sv.bot_usage.totals(Some(since)).await?;
+
+        let mut text = format!("📊 <b>Bot usage, last {} day(s)</b>\n", days);
+        for kind in [UsageKind::Command, UsageKind::Callback] {
+          let rows: Vec<_> = totals.iter().filter(|t| t.kind == kind).collect();
+          text.push_str(&format!("\n<b>{:?}s:</b> {}\n", kind, rows.len()));
+          for row in rows.iter().take(BOTSTATS_SHOWN) {
+            text.push_str(&format!("{} — {}", html::escape(&row.name), row.uses));
+            if row.failures > 0 {
+              text.push_str(&format!(" ({} failed)", row.failures));
+            }
+            text.push('\n');
+          }
+        }
+
+        let mut failing: Vec<_> =
+          totals.iter().filter(|t| t.failures > 0).collect();
+        failing.sort_by_key(|t| std::cmp::Reverse(t.failures * 100 / t.uses));
+        if !failing.is_empty() {
+          text.push_str("\n<b>Most failing:</b>\n");
+          for row in failing.iter().take(5) {
+            text.push_str(&format!(
+              "{} — {}% of {}\n",
+              html::escape(&row.name),
+              row.failures * 100 / row.uses,
+              row.uses
+            ));
+          }
+        }
+
+        let mut commands = UserCommand::bot_commands();
+        commands.extend(AdminCommand::bot_commands());
+        let unused: Vec<_> = commands
+          .iter()
+          .map(|c| c.command.trim_start_matches('/').to_string())
+          .filter(|name| {
+            !totals.iter().any(|t| t.kind == UsageKind::Command && t.name == *name)
+          })
+          .collect();
+        if !unused.is_empty() {
+          text.push_str(&format!("\n<b>Unused commands:</b> {}", unused.join(", ")));
+        }
+        Ok(text)
+      }
+      .await
+    }
+
+    Command::ApiUsage(args) => {
+      async {
+        let arg = args.trim();
+        if !arg.is_empty() && arg.parse::<i64>().is_err() {
+          let since =
+            Utc::now().date_naive() - TimeDelta::days(APIUSAGE_HISTORY_DAYS - 1);
+          let rows = sv.api_usage.history(arg, since).await?;
+          if rows.is_empty() {
+            return Ok(format!(
+              "📭 No API requests of <code>{}</code> in {} days",
+              html::escape(arg),
+              APIUSAGE_HISTORY_DAYS
+            ));
+          }
+
+          let mut text = format!("📡 <b>API usage of</b> <code>{}</code>", html::escape(arg));
+          if app.is_abuser(arg) {
+            text.push_str("\n🐢 Limits halved for repeated throttling");
+          }
+          let mut day = None;
+          for row in &rows {
+            if day != Some(row.day) {
+              text.push_str(&format!("\n\n<b>{}</b>", row.day));
+              day = Some(row.day);
+            }
+            text.push_str(&format!("\n{:?} — {}", row.kind, row.requests));
+            if row.throttled > 0 {
+              text.push_str(&format!(" ({} throttled)", row.throttled));
+            }
+          }
+          return Ok(text);
+        }
+
+        let days = match arg {
+          "" => APIUSAGE_DAYS,
+          days => days.parse::<i64>().ok().filter(|d| *d > 0).ok_or_else(
+            || Error::InvalidArgs("Usage: /apiusage [days|key]".into()),
+          )?,
+        };
+        let since = Utc::now().date_naive() - TimeDelta::days(days - 1);
+        let top = sv.api_usage.top(since, APIUSAGE_SHOWN).await?;
+        if top.is_empty() {
+          return Ok(format!("📭 No API requests in the last {} day(s)", days));
+        }
+
+        let mut text = format!("📡 <b>Top API talkers, last {} day(s)</b>\n", days);
+        for talker in &top {
+          text.push_str(&format!(
+            "\n<code>{}</code> — {}",
+            talker.license_key, talker.requests
+          ));
+          if talker.throttled > 0 {
+            text.push_str(&format!(" ({} throttled)", talker.throttled));
+          }
+          if app.is_abuser(&talker.license_key) {
+            text.push_str(" 🐢");
+          }
+        }
+        text.push_str("\n\n<i>Details: /apiusage &lt;key&gt;</i>");
+        Ok(text)
+      }
+      .await
+    }
+
+    Command::SignKey(args) => {
+      async {
+        let parts: Vec<&str> = args.split_whitespace().collect();
+        let done = match parts.as_slice() {
+          [] => None,
+          ["rotate"] => {
+            let key = sv.signing_keys.rotate().await?;
+            Some(format!("✅ Key {} now signs new tokens", key.kid))
+          }
+          ["retire", kid] => {
+            sv.signing_keys.retire(kid).await?;
+            Some(format!("✅ Key {} retired", kid))
+          }
+          _ => {
+            return Err(Error::InvalidArgs(
+              "Usage: /signkey [rotate | retire <kid>]".into(),
+            ));
+          }
+        };
+        if done.is_some() {
+          app.reload_keyring().await?;
+        }
+
+        let mut text = done.map(|d| d + "\n\n").unwrap_or_default();
+        text.push_str("🔑 <b>Offline token keys</b>\n");
+        // newest first, that one signs
+        for (i, key) in sv.signing_keys.all().await?.iter().enumerate() {
+          let active = if i == 0 { " (active)" } else { "" };
+          text.push_str(&format!(
+            "\n<code>{}</code>{} — {}",
+            key.kid,
+            active,
+            utils::format_date(key.created_at)
+          ));
+        }
+        Ok(text)
       }
       .await
     }
@@ -910,6 +2625,19 @@ async fn handle_admin_command(
                 ));
               }
             };
+            // promotions of risky users wait for a second look
+            if role != UserRole::User
+              && let Some(score) = review::flagged(&app, user_id).await?
+            {
+              let action = Action::RoleChange { role };
+              let review =
+                review::submit(&app, user_id, action, score, Some(bot.user_id))
+                  .await?;
+              return Ok(format!(
+                "⏳ User {} has risk score {}, the role change waits in                 review #{}",
+                user_id, score, review.id
+              ));
+            }
             sv.user.set_role(user_id, role.clone()).await?;
             Ok(format!("✅ User {} role set to {:?}", user_id, role))
           }
@@ -921,16 +2649,48 @@ async fn handle_admin_command(
       .await
     }
 
-    Command::SetRef(args) => {
+    Command::Reseller(args) => {
       async {
+        let usage =
+          || Error::InvalidArgs("Usage: /reseller <user_id> [on|off]".into());
         let parts: Vec<&str> = args.split_whitespace().collect();
-        // Configure referral settings for a user (user_id is their referral code)
-        let (user_id, rate, discount) = match parts.as_slice() {
-          [user_id_str] => {
-            let user_id = user_id_str
-              .parse::<i64>()
-              .map_err(|_| Error::InvalidArgs("Invalid user ID".into()))?;
-            (user_id, None, None)
+        let (user_id, on) = match parts.as_slice() {
+          [user_id] => (*user_id, None),
+          [user_id, "on"] => (*user_id, Some(true)),
+          [user_id, "off"] => (*user_id, Some(false)),
+          _ => return Err(usage()),
+        };
+        let user_id = user_id.parse::<i64>().map_err(|_| usage())?;
+        if let Some(on) = on {
+          sv.user.set_reseller(user_id, on).await?;
+        }
+        let user = sv.user.by_id(user_id).await?.ok_or(Error::UserNotFound)?;
+        let active = sv.license.active_count(user_id).await?;
+        let cap = match app.config.purchase_rules.max_licenses {
+          0 => "no cap".to_string(),
+          max => format!("cap {}", max),
+        };
+        Ok(format!(
+          "User {}: reseller {}, {} active license(s), {}",
+          user_id,
+          if user.is_reseller { "on" } else { "off" },
+          active,
+          cap
+        ))
+      }
+      .await
+    }
+
+    Command::SetRef(args) => {
+      async {
+        let parts: Vec<&str> = args.split_whitespace().collect();
+        // Configure referral settings for a user (user_id is their referral code)
+        let (user_id, rate, discount) = match parts.as_slice() {
+          [user_id_str] => {
+            let user_id = user_id_str
+              .parse::<i64>()
+              .map_err(|_| Error::InvalidArgs("Invalid user ID".into()))?;
+            (user_id, None, None)
           }
           [user_id_str, rate_str] => {
             let user_id = user_id_str
@@ -1027,127 +2787,1754 @@ async fn handle_admin_command(
       .await
     }
 
-    Command::RefStats => {
+    Command::FaqAdd(args) => {
       async {
-        let creators = sv.referral.all_creators().await?;
-        if creators.is_empty() {
-          return Ok("📭 No creators/admins with referral capability.".into());
-        }
+        let (question, answer, keywords) = parse_faq_entry(&args)?;
+        let entry = sv.faq.add(&question, &answer, &keywords).await?;
+        Ok(format!("✅ FAQ #{} added", entry.id))
+      }
+      .await
+    }
 
-        let mut text = String::from("<b>📊 Referral Statistics</b>\n\n");
-        let mut total_sales = 0;
-        let mut total_earnings = 0i64;
+    Command::FaqEdit(args) => {
+      async {
+        let (id, rest) = args.trim().split_once(' ').ok_or_else(|| {
+          Error::InvalidArgs(
+            "Usage: /faqedit <id> <question> | <answer> [| keywords]".into(),
+          )
+        })?;
+        let id = id
+          .parse::<i32>()
+          .map_err(|_| Error::InvalidArgs("Invalid FAQ ID".into()))?;
 
-        for user in &creators {
-          let earnings_str = format_usdt(user.referral_earnings);
-          let code_display = user
-            .referral_code
-            .as_ref()
-            .map(|c| format!("<code>{}</code>", c))
-            .unwrap_or_else(|| format!("ID: <code>{}</code>", user.tg_user_id));
+        let (question, answer, keywords) = parse_faq_entry(rest)?;
+        sv.faq.edit(id, &question, &answer, &keywords).await?;
+        Ok(format!("✅ FAQ #{} updated", id))
+      }
+      .await
+    }
+
+    Command::FaqDel(args) => {
+      async {
+        let id = args
+          .trim()
+          .parse::<i32>()
+          .map_err(|_| Error::InvalidArgs("Usage: /faqdel <id>".into()))?;
+        sv.faq.remove(id).await?;
+        Ok(format!("✅ FAQ #{} deleted", id))
+      }
+      .await
+    }
+
+    Command::Tickets(id) if !id.trim().is_empty() => {
+      async {
+        let id = id
+          .trim()
+          .parse::<i32>()
+          .map_err(|_| Error::InvalidArgs("Usage: /tickets [id]".into()))?;
+        let ticket = sv.ticket.by_id(id).await?.ok_or(Error::TicketNotFound)?;
+
+        let mut text = format!(
+          "<b>🎫 Ticket #{}</b> • <code>{}</code> • {:?} • {:?} priority\n\n",
+          ticket.id, ticket.tg_user_id, ticket.status, ticket.priority
+        );
+        // keep the history within a single Telegram message
+        let messages = sv.ticket.messages(id).await?;
+        let skip = messages.len().saturating_sub(20);
+        if skip > 0 {
+          text.push_str(&format!("<i>… {} earlier messages</i>\n\n", skip));
+        }
+        for message in messages.into_iter().skip(skip) {
+          let author = if message.from_admin { "🛠 Support" } else { "👤 User" };
           text.push_str(&format!(
-            "{} ({})\n\
-            Rate: {}% | Discount: {}%\n\
-            Sales: {} | Earned: {}\n\n",
-            code_display,
-            user.tg_user_id,
-            user.commission_rate,
-            user.discount_percent,
-            user.referral_sales,
-            earnings_str
+            "<b>{}</b> <i>{}</i>\n{}\n\n",
+            author,
+            utils::format_date(message.created_at),
+            html::escape(&message.text)
           ));
-          total_sales += user.referral_sales;
-          total_earnings += user.referral_earnings;
         }
+        Ok(text)
+      }
+      .await
+    }
 
-        text.push_str(&format!(
-          "<b>Total:</b>\n\
-          Creators: {}\n\
-          Sales: {}\n\
-          Commissions: {}",
-          creators.len(),
-          total_sales,
-          format_usdt(total_earnings)
-        ));
+    Command::Tickets(_) => {
+      async {
+        let tickets = sv.ticket.unresolved().await?;
+        if tickets.is_empty() {
+          return Ok("📭 No open tickets.".into());
+        }
 
+        let now = Utc::now().naive_utc();
+        let sla = TimeDelta::hours(app.config.ticket_sla_hours);
+        let mut text = format!(
+          "<b>🎫 Ticket Queue ({})</b>\n\
+          <i>Sorted by priority, then by age</i>\n\n",
+          tickets.len()
+        );
+        for ticket in tickets {
+          let icon = match ticket.priority {
+            TicketPriority::High => "🔴",
+            TicketPriority::Normal => "🟡",
+            TicketPriority::Low => "⚪",
+          };
+          let state = match ticket.waiting_since {
+            Some(since) => {
+              let waiting = now - since;
+              let breached = sla > TimeDelta::zero() && waiting >= sla;
+              format!(
+                "{}waiting {}",
+                if breached { "⏰ " } else { "" },
+                utils::format_duration(waiting)
+              )
+            }
+            None => "answered".to_string(),
+          };
+          text.push_str(&format!(
+            "{} #{} • <code>{}</code> • {}\n",
+            icon, ticket.id, ticket.tg_user_id, state
+          ));
+        }
         Ok(text)
       }
       .await
     }
 
-    Command::Deposit(args) => {
+    Command::Priority(args) => {
       async {
+        let usage = || {
+          Error::InvalidArgs("Usage: /priority <id> <low|normal|high>".into())
+        };
         let parts: Vec<&str> = args.split_whitespace().collect();
-        match parts.as_slice() {
-          [user_id_str, amount_str] => {
-            let user_id = user_id_str
-              .parse::<i64>()
-              .map_err(|_| Error::InvalidArgs("Invalid user ID".into()))?;
-            // Parse as USDT (e.g., "10.5" = 10.5 USDT)
-            let amount_usdt: f64 = amount_str
-              .parse()
-              .map_err(|_| Error::InvalidArgs("Invalid amount".into()))?;
-            let amount_nano = (amount_usdt * NANO_USDT as f64) as i64;
+        let [id, priority] = parts.as_slice() else {
+          return Err(usage());
+        };
+        let id = id.parse::<i32>().map_err(|_| usage())?;
+        let priority = match priority.to_lowercase().as_str() {
+          "low" => TicketPriority::Low,
+          "normal" => TicketPriority::Normal,
+          "high" => TicketPriority::High,
+          _ => return Err(usage()),
+        };
 
-            if amount_nano <= 0 {
-              return Err(Error::InvalidArgs("Amount must be positive".into()));
+        sv.ticket.set_priority(id, priority).await?;
+        Ok(format!("✅ Ticket #{} priority set to {:?}", id, priority))
+      }
+      .await
+    }
+
+    Command::Reply(args) => {
+      async {
+        let usage = || Error::InvalidArgs("Usage: /reply <id> <text>".into());
+        let (id, text) = args.trim().split_once(' ').ok_or_else(usage)?;
+        let id = id.parse::<i32>().map_err(|_| usage())?;
+
+        support::send_reply(&app, id, bot.user_id, text.trim()).await?;
+        Ok(format!("✅ Reply sent to ticket #{}", id))
+      }
+      .await
+    }
+
+    Command::Close(args) => {
+      async {
+        let id = args
+          .trim()
+          .parse::<i32>()
+          .map_err(|_| Error::InvalidArgs("Usage: /close <id>".into()))?;
+
+        support::close_ticket(&app, id).await?;
+        Ok(format!("✅ Ticket #{} closed", id))
+      }
+      .await
+    }
+
+    Command::Canned(args) => {
+      async {
+        let args = args.trim();
+        let (action, rest) = args.split_once(' ').unwrap_or((args, ""));
+
+        match action {
+          "" | "list" => {
+            let responses = sv.canned.all().await?;
+            if responses.is_empty() {
+              return Ok("📭 No canned responses.".into());
             }
 
-            let new_balance = sv
-              .balance
-              .deposit(user_id, amount_nano, Some("Admin deposit".into()))
-              .await?;
-            Ok(format!(
-              "✅ Deposited {} to user {}\n\
-              New balance: {}",
-              format_usdt(amount_nano),
-              user_id,
-              format_usdt(new_balance)
-            ))
+            let mut text = String::from("<b>📋 Canned Responses</b>\n\n");
+            for response in responses {
+              text.push_str(&format!(
+                "<b>{}</b>\n{}\n\n",
+                response.name,
+                html::escape(&response.text)
+              ));
+            }
+            Ok(text)
+          }
+          "add" => {
+            let (name, text) = rest.trim().split_once(' ').ok_or_else(|| {
+              Error::InvalidArgs("Usage: /canned add <name> <text>".into())
+            })?;
+
+            let valid = name.len() <= support::CANNED_NAME_MAX
+              && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+            if !valid {
+              return Err(Error::InvalidArgs(format!(
+                "Name must be up to {} latin letters, digits or '_'",
+                support::CANNED_NAME_MAX
+              )));
+            }
+
+            sv.canned.set(name, text.trim()).await?;
+            Ok(format!("✅ Canned response <b>{}</b> saved", name))
+          }
+          "del" => {
+            let name = rest.trim();
+            sv.canned.remove(name).await?;
+            Ok(format!("✅ Canned response <b>{}</b> deleted", name))
           }
           _ => Err(Error::InvalidArgs(
-            "Usage: /deposit <user_id> <amount_usdt>".into(),
+            "Usage: /canned [list] | add <name> <text> | del <name>".into(),
           )),
         }
       }
       .await
     }
 
-    Command::Withdraw(args) => {
+    Command::Announce(args) => {
+      async {
+        let args = args.trim();
+        let (first, rest) = args.split_once(' ').unwrap_or((args, ""));
+        let (category, rest) = match first.to_lowercase().as_str() {
+          "release" => (AnnouncementCategory::Release, rest),
+          "maintenance" => (AnnouncementCategory::Maintenance, rest),
+          "offer" => (AnnouncementCategory::Offer, rest),
+          _ => (AnnouncementCategory::General, args),
+        };
+
+        let (title, body) = rest
+          .split_once('|')
+          .map(|(title, body)| (title.trim(), body.trim()))
+          .filter(|(title, body)| !title.is_empty() && !body.is_empty())
+          .ok_or_else(|| {
+            Error::InvalidArgs(
+              "Usage: /announce [release|maintenance|offer] <title> | <body>"
+                .into(),
+            )
+          })?;
+
+        let item = sv.announcement.create(category, title, body).await?;
+        Ok(format!(
+          "✅ Announcement #{} posted to the inbox {}",
+          item.id,
+          category.icon()
+        ))
+      }
+      .await
+    }
+
+    Command::Broadcast(args) => {
+      let args = args.trim();
+      let (active_only, text) = match args.split_once(char::is_whitespace) {
+        Some(("active", text)) => (true, text.trim()),
+        _ => (false, args),
+      };
+      if text.is_empty() {
+        Err(Error::InvalidArgs("Usage: /broadcast [active] <text>".into()))
+      } else {
+        // the admin gets it first, so broken HTML fails once and not per user
+        if let Err(e) = bot.reply_html(text).await {
+          let text = format!(
+            "❌ Message can not be sent: {}",
+            html::escape(&e.to_string())
+          );
+          bot.reply_html(text).await?;
+          return Ok(());
+        }
+        let targets = sv.user.broadcast_targets(active_only).await;
+        // staging only ever talks to admins
+        let targets = targets.map(|mut targets| {
+          if app.config.environment.is_staging() {
+            targets.retain(|id| app.admins.contains(id));
+          }
+          targets
+        });
+        match targets {
+          Ok(targets) if targets.is_empty() => {
+            Ok("No users to broadcast to.".to_string())
+          }
+          Ok(targets) => {
+            let status = broadcast::report(
+              "📣 Broadcasting",
+              targets.len(),
+              &Default::default(),
+            );
+            let progress = bot.reply_html(status).await?;
+            tokio::spawn(broadcast::run(
+              app.clone(),
+              bot.inner.clone(),
+              bot.chat_id,
+              progress.id,
+              targets,
+              text.to_string(),
+            ));
+            return Ok(());
+          }
+          Err(e) => Err(e),
+        }
+      }
+    }
+
+    Command::Unannounce(args) => {
+      async {
+        let id = args
+          .trim()
+          .parse::<i32>()
+          .map_err(|_| Error::InvalidArgs("Usage: /unannounce <id>".into()))?;
+        sv.announcement.remove(id).await?;
+        Ok(format!("✅ Announcement #{} deleted", id))
+      }
+      .await
+    }
+
+    Command::Honeypot(args) => {
+      async {
+        let args = args.trim();
+        let count = if args.is_empty() {
+          1
+        } else {
+          args.parse::<usize>().ok().filter(|n| (1..=20).contains(n)).ok_or(
+            Error::InvalidArgs("Usage: /honeypot [count], up to 20".into()),
+          )?
+        };
+
+        let mut keys = String::new();
+        for _ in 0..count {
+          let license = sv.license.create_honeypot(bot.user_id).await?;
+          keys.push_str(&format!("<code>{}</code>\n", license.key));
+        }
+        Ok(format!(
+          "🍯 <b>Honeypot keys</b>\n\n{}\n\
+          Never sell or share these. Plant them in the key database or \
+          give each reseller its own, any use raises an incident.",
+          keys
+        ))
+      }
+      .await
+    }
+
+    Command::Loaner(args) => {
       async {
         let parts: Vec<&str> = args.split_whitespace().collect();
-        match parts.as_slice() {
-          [user_id_str, amount_str] => {
-            let user_id = user_id_str
-              .parse::<i64>()
-              .map_err(|_| Error::InvalidArgs("Invalid user ID".into()))?;
-            // Parse as USDT (e.g., "10.5" = 10.5 USDT)
-            let amount_usdt: f64 = amount_str
-              .parse()
-              .map_err(|_| Error::InvalidArgs("Invalid amount".into()))?;
-            let amount_nano = (amount_usdt * NANO_USDT as f64) as i64;
+        let (hours, user_id) = match parts.as_slice() {
+          [] => {
+            let loaners = sv.license.loaners(LOANERS_SHOWN).await?;
+            if loaners.is_empty() {
+              return Ok("No support keys issued yet".to_string());
+            }
+            let now = Utc::now().naive_utc();
+            let mut text = String::from("🧰 <b>Support keys</b>\n");
+            for loaner in loaners {
+              let state = if loaner.expires_at > now { "⏳" } else { "⌛" };
+              text.push_str(&format!(
+                "\n{} <code>{}</code>\nfor <code>{}</code> by <code>{}</code>, \
+                {} – {}\n",
+                state,
+                loaner.key,
+                loaner.tg_user_id,
+                loaner.loaned_by.unwrap_or_default(),
+                utils::format_date(loaner.created_at),
+                utils::format_date(loaner.expires_at)
+              ));
+            }
+            return Ok(text);
+          }
+          [hours, user_id] => (hours.parse::<i64>(), user_id.parse::<i64>()),
+          _ => (Ok(0), Ok(0)),
+        };
+        let (Ok(hours), Ok(user_id)) = (hours, user_id) else {
+          return Err(Error::InvalidArgs(
+            "Usage: /loaner <hours> <user_id>".into(),
+          ));
+        };
+        if !(1..=LOANER_MAX_HOURS).contains(&hours) || user_id <= 0 {
+          return Err(Error::InvalidArgs(format!(
+            "Usage: /loaner <hours> <user_id>, up to {} hours",
+            LOANER_MAX_HOURS
+          )));
+        }
 
-            if amount_nano <= 0 {
-              return Err(Error::InvalidArgs("Amount must be positive".into()));
+        let loaner =
+          sv.license.create_loaner(bot.user_id, user_id, hours).await?;
+        info!(
+          "Admin {} issued support key {} for user {} ({}h)",
+          bot.user_id, loaner.key, user_id, hours
+        );
+        app
+          .notify_admins(
+            Urgency::Normal, &format!(
+              "🧰 Admin <code>{}</code> issued a {}h support key for \
+              <code>{}</code>",
+              bot.user_id, hours, user_id
+            ),
+            None,
+          )
+          .await;
+        Ok(format!(
+          "🧰 <b>Support key</b> for <code>{}</code>\n\n\
+          <code>{}</code>\n\
+          Expires: {}\n\n\
+          It is not billed and stays out of stats.",
+          user_id,
+          loaner.key,
+          utils::format_date(loaner.expires_at)
+        ))
+      }
+      .await
+    }
+
+    Command::Exempt(args) => {
+      async {
+        let args = args.trim();
+        let (action, rest) = args.split_once(' ').unwrap_or((args, ""));
+        let (value, note) = rest.trim().split_once(' ').unwrap_or((rest, ""));
+        let usage = || {
+          Error::InvalidArgs(
+            "Usage: /exempt [hwid|user <value> [note] | del <id>]".into(),
+          )
+        };
+
+        match action {
+          "" => {
+            let exemptions = sv.hwid_policy.exemptions().await?;
+            let policy = app.config.hwid_policy();
+            let mut text = format!(
+              "<b>🖥 Duplicate-HWID Policy</b>\n\
+              Licenses per machine: {}\n\
+              Trials per machine: {}\n\
+              Window: {}h\n\n",
+              policy.max_licenses,
+              policy.max_trials,
+              policy.window.num_hours()
+            );
+            if exemptions.is_empty() {
+              text.push_str("No exemptions.");
             }
+            for exemption in exemptions {
+              let target = match (exemption.hwid, exemption.tg_user_id) {
+                (Some(hwid), _) => format!("HWID <code>{}</code>", html::escape(&hwid)),
+                (_, Some(id)) => format!("user <code>{}</code>", id),
+                _ => "-".to_string(),
+              };
+              text.push_str(&format!(
+                "#{} {} {}\n",
+                exemption.id,
+                target,
+                html::escape(&exemption.note)
+              ));
+            }
+            Ok(text)
+          }
+          "del" => {
+            let id = value.parse::<i32>().map_err(|_| usage())?;
+            sv.hwid_policy.unexempt(id).await?;
+            Ok(format!("✅ Exemption #{} removed", id))
+          }
+          "hwid" | "user" if !value.is_empty() => {
+            let target = if action == "hwid" {
+              sv::hwid_policy::Exempt::Hwid(value.to_string())
+            } else {
+              sv::hwid_policy::Exempt::User(
+                value.parse().map_err(|_| usage())?,
+              )
+            };
+            let exemption = sv.hwid_policy.exempt(target, note.trim()).await?;
+            Ok(format!("✅ Exemption #{} added", exemption.id))
+          }
+          _ => Err(usage()),
+        }
+      }
+      .await
+    }
 
-            let new_balance = sv.balance.withdraw(user_id, amount_nano).await?;
+    Command::Region(args) => {
+      async {
+        let args: Vec<&str> = args.split_whitespace().collect();
+        match args.as_slice() {
+          [] => {
+            let regions = sv.pricing.regions().await?;
+            if regions.is_empty() {
+              return Ok("No regional prices set.".to_string());
+            }
+            let month = sv.plan.get(Plan::Month).await?.price_nano;
+            let mut text = String::from("<b>🌍 Regional Prices</b>\n\n");
+            for region in regions {
+              let price = month * region.percent as i64 / 100;
+              text.push_str(&format!(
+                "{}: {}% ({} / month)\n",
+                region.country,
+                region.percent,
+                format_usdt(price)
+              ));
+            }
+            Ok(text)
+          }
+          ["del", country] => {
+            sv.pricing.remove_region(country).await?;
+            Ok(format!("✅ Regional price for {} removed", country))
+          }
+          [country, percent] => {
+            let percent = percent.trim_end_matches('%').parse().map_err(|_| {
+              Error::InvalidArgs("Percent must be a number".into())
+            })?;
+            let region = sv.pricing.set_region(country, percent).await?;
             Ok(format!(
-              "✅ Withdrawal of {} processed for user {}\n\
-              New balance: {}",
-              format_usdt(amount_nano),
-              user_id,
-              format_usdt(new_balance)
+              "✅ Month plan costs {}% in {}",
+              region.percent, region.country
             ))
           }
           _ => Err(Error::InvalidArgs(
-            "Usage: /withdraw <user_id> <amount_usdt>".into(),
+            "Usage: /region [<CC> <percent> | del <CC>]".into(),
           )),
         }
       }
       .await
     }
 
+    Command::Flag(args) => {
+      async {
+        let usage = || {
+          Error::InvalidArgs(
+            "Usage: /flag <key | user_id> [<feature> <on|off|clear>]".into(),
+          )
+        };
+        let args: Vec<&str> = args.split_whitespace().collect();
+        let Some(&target) = args.first() else {
+          return Err(usage());
+        };
+        let scope = if target.parse::<i64>().is_ok() {
+          FieldScope::User
+        } else {
+          FieldScope::License
+        };
+
+        let text = match args[1..] {
+          [] => {
+            let flags = sv.feature_flag.of(scope, target).await?;
+            if flags.is_empty() {
+              return Ok("No feature overrides.".into());
+            }
+            let mut text = format!(
+              "<b>🚩 Features of</b> <code>{}</code>\n\n",
+              html::escape(target)
+            );
+            for flag in flags {
+              text.push_str(&format!(
+                "{} {}\n",
+                if flag.enabled { "🟢" } else { "🔴" },
+                flag.feature
+              ));
+            }
+            return Ok(text);
+          }
+          [feature, "clear"] => {
+            sv.feature_flag.clear(scope, target, feature).await?;
+            format!("✅ {} reset to the client default", html::escape(feature))
+          }
+          [feature, state @ ("on" | "off")] => {
+            let flag =
+              sv.feature_flag.set(scope, target, feature, state == "on").await?;
+            format!("✅ {} turned {}", flag.feature, state)
+          }
+          _ => return Err(usage()),
+        };
+
+        let keys = match scope {
+          FieldScope::License => vec![target.to_string()],
+          FieldScope::User => sv
+            .license
+            .by_user(target.parse().map_err(|_| usage())?, true)
+            .await?
+            .into_iter()
+            .map(|license| license.key)
+            .collect(),
+        };
+        app.refresh_features(&keys).await?;
+        Ok(text)
+      }
+      .await
+    }
+    Command::Field(args) => {
+      async {
+        let usage = || {
+          Error::InvalidArgs(
+            "Usage: /field [del] <key | user_id> [<name> [value]]".into(),
+          )
+        };
+        let scope_of = |target: &str| {
+          if target.parse::<i64>().is_ok() {
+            FieldScope::User
+          } else {
+            FieldScope::License
+          }
+        };
+
+        let mut parts = args.trim().splitn(3, char::is_whitespace);
+        match (parts.next(), parts.next(), parts.next().map(str::trim)) {
+          (Some("del"), Some(target), Some(name)) => {
+            sv.custom_field.remove(scope_of(target), target, name).await?;
+            Ok(format!("✅ Field {} removed", html::escape(name)))
+          }
+          (Some(target), Some(name), Some(value)) if !target.is_empty() => {
+            let field =
+              sv.custom_field.set(scope_of(target), target, name, value).await?;
+            Ok(format!(
+              "✅ {} = {}",
+              field.name,
+              html::escape(&field.value)
+            ))
+          }
+          (Some(target), None, None) if !target.is_empty() => {
+            let fields = sv.custom_field.of(scope_of(target), target).await?;
+            if fields.is_empty() {
+              return Ok("No custom fields.".into());
+            }
+            let mut text = format!(
+              "<b>🏷 Fields of</b> <code>{}</code>\n\n",
+              html::escape(target)
+            );
+            for field in fields {
+              text.push_str(&format!(
+                "{} = {}\n",
+                field.name,
+                html::escape(&field.value)
+              ));
+            }
+            Ok(text)
+          }
+          _ => Err(usage()),
+        }
+      }
+      .await
+    }
+
+    Command::Product(args) => {
+      async {
+        let usage = || {
+          Error::InvalidArgs(
+            "Usage: /product [<id> <percent> <name> | key <key> <id>]".into(),
+          )
+        };
+        let args: Vec<&str> = args.split_whitespace().collect();
+        match args.as_slice() {
+          [] => {
+            let mut text = String::from("<b>📦 Products</b>\n\n");
+            for product in sv.product.all().await? {
+              text.push_str(&format!(
+                "<code>{}</code> {} · {}% of base prices\n",
+                product.slug,
+                html::escape(&product.name),
+                product.price_percent
+              ));
+            }
+            Ok(text)
+          }
+          ["key", key, product] => {
+            let license = sv.license.set_product(key, product).await?;
+            app.drop_sessions(&license.key);
+            Ok(format!(
+              "✅ <code>{}</code> moved to {}",
+              license.key, license.product
+            ))
+          }
+          [slug, percent, name @ ..] if !name.is_empty() => {
+            let percent =
+              percent.trim_end_matches('%').parse().map_err(|_| usage())?;
+            let product =
+              sv.product.upsert(slug, &name.join(" "), percent).await?;
+            Ok(format!(
+              "✅ {} (<code>{}</code>) at {}% of base prices",
+              html::escape(&product.name),
+              product.slug,
+              product.price_percent
+            ))
+          }
+          _ => Err(usage()),
+        }
+      }
+      .await
+    }
+
+    Command::Plans => {
+      async {
+        let mut text = String::from("<b>🗓 Plans</b>\n\n");
+        for (kind, plan) in sv.plan.all().await? {
+          text.push_str(&format!(
+            "{} <code>{}</code> {} · {} · {} · {} session(s)\n",
+            if plan.active { "✅" } else { "⛔" },
+            kind.as_str(),
+            html::escape(&plan.title),
+            sv::plan::term(kind, &plan),
+            format_usdt(plan.price_nano),
+            plan.max_sessions
+          ));
+        }
+        text.push_str(
+          "\n<i>Prices are before product, referral and other discounts</i>",
+        );
+        Ok(text)
+      }
+      .await
+    }
+
+    Command::SetPlan(args) => {
+      async {
+        let usage = || {
+          Error::InvalidArgs(
+            "Usage: /setplan <plan> <price|days|sessions|title|calendar> \
+            <value> or /setplan <plan> <on|off>"
+              .into(),
+          )
+        };
+        let mut parts = args.trim().splitn(3, ' ');
+        let kind = parts.next().filter(|s| !s.is_empty()).ok_or_else(usage)?;
+        let kind = Plan::parse(kind).ok_or_else(|| {
+          Error::InvalidArgs(format!(
+            "Unknown plan, expected one of: {}",
+            Plan::ALL.map(Plan::as_str).join(", ")
+          ))
+        })?;
+        let field = parts.next().ok_or_else(usage)?;
+        let field = PlanField::parse(field, parts.next())?;
+
+        let plan = sv.plan.set(kind, field).await?;
+        Ok(format!(
+          "✅ {} <code>{}</code>: {}, {}, {} session(s){}",
+          html::escape(&plan.title),
+          plan.name,
+          sv::plan::term(kind, &plan),
+          format_usdt(plan.price_nano),
+          plan.max_sessions,
+          if plan.active { "" } else { ", not sold" }
+        ))
+      }
+      .await
+    }
+
+    Command::Storefront(args) => {
+      async {
+        let usage = || {
+          Error::InvalidArgs(
+            "Usage: /storefront [<id> <token> <support> <title> | \
+            welcome <id> [text] | del <id>]"
+              .into(),
+          )
+        };
+        let parts: Vec<&str> = args.split_whitespace().collect();
+        match parts.as_slice() {
+          [] => {
+            let storefronts = sv.storefront.all().await?;
+            if storefronts.is_empty() {
+              return Ok("No storefronts, only the main bot is running.".into());
+            }
+            let mut text = String::from("<b>🏪 Storefronts</b>\n\n");
+            for storefront in storefronts {
+              let running = app.brand_bots.contains_key(&storefront.product);
+              text.push_str(&format!(
+                "{} <code>{}</code> {} · @{}{}\n",
+                if running { "🟢" } else { "⚪" },
+                storefront.product,
+                html::escape(&storefront.title),
+                storefront.support,
+                if storefront.welcome.is_some() { " · custom welcome" } else { "" }
+              ));
+            }
+            Ok(text)
+          }
+          ["del", product] => {
+            if !sv.storefront.remove(product).await? {
+              return Err(Error::ProductNotFound(product.to_string()));
+            }
+            Ok(format!(
+              "✅ Storefront <code>{}</code> removed, its bot stops after a \
+              restart",
+              product
+            ))
+          }
+          ["welcome", product, ..] => {
+            // keep the admin's line breaks
+            let text = args
+              .trim()
+              .strip_prefix("welcome")
+              .map(str::trim_start)
+              .and_then(|rest| rest.strip_prefix(*product))
+              .map(str::trim)
+              .filter(|text| !text.is_empty());
+            sv.storefront.set_welcome(product, text).await?;
+            Ok(format!(
+              "✅ Welcome of <code>{}</code> {}, applies after a restart",
+              product,
+              if text.is_some() { "updated" } else { "reset" }
+            ))
+          }
+          [product, token, support, title @ ..] if !title.is_empty() => {
+            let storefront = sv
+              .storefront
+              .upsert(product, token, &title.join(" "), support)
+              .await?;
+            if app.brand_bots.contains_key(&storefront.product) {
+              return Ok(format!(
+                "✅ Storefront <code>{}</code> updated, applies after a \
+                restart",
+                storefront.product
+              ));
+            }
+
+            let bot = Bot::new(&storefront.bot_token);
+            app.brand_bots.insert(storefront.product.clone(), bot.clone());
+            let product = storefront.product.clone();
+            tokio::spawn(super::dispatch(
+              app.clone(),
+              bot,
+              Some(Arc::new(storefront)),
+            ));
+            Ok(format!("✅ Storefront <code>{}</code> started", product))
+          }
+          _ => Err(usage()),
+        }
+      }
+      .await
+    }
+
+    Command::Asset(args) => {
+      async {
+        let args = args.trim();
+        let (action, rest) = args.split_once(' ').unwrap_or((args, ""));
+        let rest = rest.trim();
+
+        match action {
+          "" | "list" => {
+            let assets = sv.promo_kit.all().await?;
+            if assets.is_empty() {
+              return Ok("📭 No promo materials.".into());
+            }
+
+            let mut text = String::from("<b>📦 Promo Materials</b>\n\n");
+            for asset in assets {
+              match asset.kind {
+                PromoAssetKind::Banner => text.push_str(&format!(
+                  "#{} 🖼 {}\n",
+                  asset.id,
+                  html::escape(&asset.title)
+                )),
+                PromoAssetKind::Template => text.push_str(&format!(
+                  "#{} 📝 {}\n<i>{}</i>\n",
+                  asset.id,
+                  html::escape(&asset.title),
+                  html::escape(asset.text.as_deref().unwrap_or_default())
+                )),
+              }
+            }
+            Ok(text)
+          }
+          "banner" => {
+            let photo = msg
+              .reply_to_message()
+              .and_then(|reply| reply.photo())
+              .and_then(|sizes| sizes.last())
+              .ok_or_else(|| {
+                Error::InvalidArgs(
+                  "Reply to a photo with /asset banner <title>".into(),
+                )
+              })?;
+
+            let asset =
+              sv.promo_kit.add_banner(rest, &photo.file.id.0).await?;
+            Ok(format!(
+              "✅ Banner #{} <b>{}</b> added",
+              asset.id,
+              html::escape(&asset.title)
+            ))
+          }
+          "text" => {
+            let (title, text) = rest.split_once('|').ok_or_else(|| {
+              Error::InvalidArgs("Usage: /asset text <title> | <text>".into())
+            })?;
+
+            let asset = sv.promo_kit.add_template(title, text).await?;
+            Ok(format!(
+              "✅ Template #{} <b>{}</b> added",
+              asset.id,
+              html::escape(&asset.title)
+            ))
+          }
+          "del" => {
+            let id = rest.parse::<i32>().map_err(|_| {
+              Error::InvalidArgs("Usage: /asset del <id>".into())
+            })?;
+            sv.promo_kit.remove(id).await?;
+            Ok(format!("✅ Promo material #{} removed", id))
+          }
+          _ => Err(Error::InvalidArgs(
+            "Usage: /asset [list] | banner <title> | text <title> | <text> \
+            | del <id>"
+              .into(),
+          )),
+        }
+      }
+      .await
+    }
+
+    Command::Freebie(args) => {
+      async {
+        let usage = || {
+          Error::InvalidArgs(
+            "Usage: /freebie [game|item <id> <app_id> <name> | \
+            del <game|item> <id> | history [user_id]]"
+              .into(),
+          )
+        };
+        let parse_kind = |kind: &str| match kind {
+          "game" => Ok(FreebieKind::Game),
+          "item" => Ok(FreebieKind::Item),
+          _ => Err(usage()),
+        };
+        let parts: Vec<&str> = args.split_whitespace().collect();
+
+        match parts.as_slice() {
+          [] => {
+            let freebies = sv.freebies.all().await?;
+            if freebies.is_empty() {
+              return Ok("📭 No freebies, the Steam scan hasn't found any.".into());
+            }
+
+            let week_ago = Utc::now().naive_utc() - TimeDelta::days(7);
+            let counts = sv.freebies.claim_counts(week_ago).await?;
+            let mut text = format!(
+              "<b>🎁 Freebies</b> ({}, limit {}/day per user)\n\n",
+              freebies.len(),
+              app.config.freebie_daily_limit
+            );
+            for freebie in freebies.iter().take(FREEBIES_LISTED) {
+              let icon = match freebie.kind {
+                FreebieKind::Game => "🎮",
+                FreebieKind::Item => "🎁",
+              };
+              text.push_str(&format!(
+                "{} <code>{}</code> {} · app {} · {} claim(s){}\n",
+                icon,
+                freebie.id,
+                html::escape(&freebie.name),
+                freebie.app_id,
+                counts.get(&(freebie.kind, freebie.id)).unwrap_or(&0),
+                if freebie.manual { " · manual" } else { "" }
+              ));
+            }
+            if freebies.len() > FREEBIES_LISTED {
+              text.push_str(&format!(
+                "<i>…and {} more</i>",
+                freebies.len() - FREEBIES_LISTED
+              ));
+            }
+            Ok(text)
+          }
+          ["history", rest @ ..] => {
+            let user = match rest {
+              [] => None,
+              [id] => Some(id.parse::<i64>().map_err(|_| usage())?),
+              _ => return Err(usage()),
+            };
+            let claims = sv.freebies.history(user, 20).await?;
+            if claims.is_empty() {
+              return Ok("📭 No claims yet.".into());
+            }
+
+            let mut text = String::from("<b>🎁 Latest Claims</b>\n\n");
+            for claim in claims {
+              text.push_str(&format!(
+                "{} · <code>{}</code> · {}\n",
+                utils::format_date(claim.claimed_at),
+                claim.tg_user_id,
+                html::escape(&claim.name)
+              ));
+            }
+            Ok(text)
+          }
+          ["del", kind, id] => {
+            let kind = parse_kind(kind)?;
+            let id = id.parse::<i32>().map_err(|_| usage())?;
+            sv.freebies.remove(kind, id).await?;
+            Ok(format!("✅ Freebie <code>{}</code> removed", id))
+          }
+          [kind, id, app_id, name @ ..] if !name.is_empty() => {
+            let kind = parse_kind(kind)?;
+            let id = id.parse::<i32>().map_err(|_| usage())?;
+            let app_id = app_id.parse::<i32>().map_err(|_| usage())?;
+            let freebie =
+              sv.freebies.add(kind, id, app_id, &name.join(" ")).await?;
+            Ok(format!(
+              "✅ <b>{}</b> added, users can claim it from 🎁 Freebies",
+              html::escape(&freebie.name)
+            ))
+          }
+          _ => Err(usage()),
+        }
+      }
+      .await
+    }
+
+    Command::Terms(text) => {
+      async {
+        if text.trim().is_empty() {
+          let Some(terms) = sv.terms.current().await? else {
+            return Ok("No terms published, purchases are not gated.".into());
+          };
+          return Ok(format!(
+            "<b>📜 Terms v{}</b> · {}\n\n{}",
+            terms.version,
+            utils::format_date(terms.created_at),
+            html::escape(&terms.text)
+          ));
+        }
+
+        let terms = sv.terms.publish(&text).await?;
+        Ok(format!(
+          "✅ Terms v{} published, users will be asked to accept them \
+          before their next purchase",
+          terms.version
+        ))
+      }
+      .await
+    }
+
+    Command::Sale(args) => {
+      async {
+        let usage = || {
+          Error::InvalidArgs(
+            "Usage: /sale [<percent> <hours> [name] | stop <id>]".into(),
+          )
+        };
+        let args: Vec<&str> = args.split_whitespace().collect();
+        match args.as_slice() {
+          [] => {
+            let sales = sv.pricing.upcoming_sales().await?;
+            if sales.is_empty() {
+              return Ok("No sales running.".to_string());
+            }
+            let mut text = String::from("<b>⚡ Sales</b>\n\n");
+            for sale in sales {
+              text.push_str(&format!(
+                "#{} {} · {}% off\n{} - {}\n\n",
+                sale.id,
+                html::escape(&sale.name),
+                sale.percent,
+                utils::format_date(sale.starts_at),
+                utils::format_date(sale.ends_at)
+              ));
+            }
+            Ok(text)
+          }
+          ["stop", id] => {
+            let id = id.parse().map_err(|_| usage())?;
+            sv.pricing.stop_sale(id).await?;
+            Ok(format!("✅ Sale #{} ended", id))
+          }
+          [percent, hours, name @ ..] => {
+            let percent: i32 =
+              percent.trim_end_matches('%').parse().map_err(|_| usage())?;
+            let hours: i64 = hours.parse().map_err(|_| usage())?;
+            if !(1..=24 * 14).contains(&hours) {
+              return Err(Error::InvalidArgs(
+                "Sale must last from 1 hour to 2 weeks".into(),
+              ));
+            }
+            let name = match name.join(" ") {
+              name if name.is_empty() => "Happy hour".to_string(),
+              name => name,
+            };
+            let sale = sv
+              .pricing
+              .start_sale(&name, percent, TimeDelta::hours(hours))
+              .await?;
+            Ok(format!(
+              "✅ Sale #{} started: {}% off extensions until {}",
+              sale.id,
+              sale.percent,
+              utils::format_date(sale.ends_at)
+            ))
+          }
+          _ => Err(usage()),
+        }
+      }
+      .await
+    }
+
+    Command::NewPromo(args) => {
+      async {
+        let usage = || {
+          Error::InvalidArgs(
+            "Usage: /newpromo [<code> <trial|pro> <days> <duration> \
+            [max_claims] [from <date>] | stop <code>]"
+              .into(),
+          )
+        };
+        let args: Vec<&str> = args.split_whitespace().collect();
+        match args.as_slice() {
+          [] => {
+            let campaigns = sv.promo_campaign.all().await?;
+            if campaigns.is_empty() {
+              return Ok("No promo campaigns yet.".to_string());
+            }
+            let now = Utc::now().naive_utc();
+            let mut text = String::from("<b>🎟 Promo Campaigns</b>\n\n");
+            for campaign in campaigns {
+              let claims = sv.promo_campaign.claims(&campaign.code).await?;
+              let limit = match campaign.max_claims {
+                Some(max) => format!("{}/{}", claims, max),
+                None => claims.to_string(),
+              };
+              let status = if campaign.is_running(now) {
+                "🟢"
+              } else if campaign.starts_at > now {
+                "⏳"
+              } else {
+                "⚪"
+              };
+              text.push_str(&format!(
+                "{} <code>{}</code> {:?} {}d · {} claim(s)\n{} - {}\n\n",
+                status,
+                html::escape(&campaign.code),
+                campaign.license_type,
+                campaign.days,
+                limit,
+                utils::format_date(campaign.starts_at),
+                utils::format_date(campaign.ends_at)
+              ));
+            }
+            Ok(text)
+          }
+          ["stop", code] => {
+            let campaign = sv.promo_campaign.stop(code).await?;
+            Ok(format!(
+              "✅ Promo <code>{}</code> ended, {} claim(s) stay valid",
+              html::escape(&campaign.code),
+              sv.promo_campaign.claims(&campaign.code).await?
+            ))
+          }
+          [code, ty, days, duration, rest @ ..] => {
+            let license_type = match ty.to_lowercase().as_str() {
+              "trial" => LicenseType::Trial,
+              "pro" => LicenseType::Pro,
+              _ => return Err(usage()),
+            };
+            let days = days.parse::<i32>().map_err(|_| usage())?;
+            let duration = utils::parse_duration(duration).ok_or_else(usage)?;
+            let (max_claims, rest) = match rest {
+              [max, rest @ ..] if *max != "from" => {
+                (Some(max.parse::<i32>().map_err(|_| usage())?), rest)
+              }
+              rest => (None, rest),
+            };
+            let starts_at = match rest {
+              [] => Utc::now().naive_utc(),
+              ["from", start] => parse_start(start).ok_or_else(|| {
+                Error::InvalidArgs(
+                  "Start must look like 2026-02-10 or 2026-02-10T18:00 (UTC)"
+                    .into(),
+                )
+              })?,
+              _ => return Err(usage()),
+            };
+
+            let campaign = sv
+              .promo_campaign
+              .create(code, license_type, days, max_claims, starts_at, duration)
+              .await?;
+            Ok(format!(
+              "✅ Promo <code>{}</code>: {} days of {:?} from {} until {}\n\
+              Users redeem it with <code>/promo {}</code>",
+              html::escape(&campaign.code),
+              campaign.days,
+              campaign.license_type,
+              utils::format_date(campaign.starts_at),
+              utils::format_date(campaign.ends_at),
+              html::escape(&campaign.code)
+            ))
+          }
+          _ => Err(usage()),
+        }
+      }
+      .await
+    }
+    Command::TrialPromo(args) => {
+      async {
+        let usage = || {
+          Error::InvalidArgs(
+            "Usage: /trialpromo [open <duration> [from <date>] | close]".into(),
+          )
+        };
+        let args: Vec<&str> = args.split_whitespace().collect();
+        let campaign = match args.as_slice() {
+          [] => sv
+            .promo_campaign
+            .get(callback::TRIAL_PROMO)
+            .await?
+            .ok_or(Error::Promo(Promo::NotFound))?,
+          ["open", duration, rest @ ..] => {
+            let duration = utils::parse_duration(duration).ok_or_else(usage)?;
+            let starts_at = match rest {
+              [] => Utc::now().naive_utc(),
+              ["from", start] => parse_start(start).ok_or_else(|| {
+                Error::InvalidArgs(
+                  "Start must look like 2026-02-10 or 2026-02-10T18:00 (UTC)"
+                    .into(),
+                )
+              })?,
+              _ => return Err(usage()),
+            };
+            sv.promo_campaign
+              .reschedule(callback::TRIAL_PROMO, starts_at, duration)
+              .await?
+          }
+          ["close"] => sv.promo_campaign.stop(callback::TRIAL_PROMO).await?,
+          _ => return Err(usage()),
+        };
+
+        let status = if campaign.is_running(Utc::now().naive_utc()) {
+          "🟢 open"
+        } else if campaign.starts_at > Utc::now().naive_utc() {
+          "⏳ scheduled"
+        } else {
+          "⚪ closed"
+        };
+        Ok(format!(
+          "<b>🆓 Free Trial Promo</b>: {}\n{} - {}\n{} claim(s) so far",
+          status,
+          utils::format_date(campaign.starts_at),
+          utils::format_date(campaign.ends_at),
+          sv.promo_campaign.claims(&campaign.code).await?
+        ))
+      }
+      .await
+    }
+    Command::PromoCode(args) => {
+      async {
+        let usage = || {
+          Error::InvalidArgs(
+            "Usage: /promocode [add <code> <balance <usdt>|trial <days>> \
+            [max_uses] [for <duration>] | del <code>]"
+              .into(),
+          )
+        };
+        let args: Vec<&str> = args.split_whitespace().collect();
+        match args.as_slice() {
+          [] => {
+            let codes = sv.promo_code.all().await?;
+            if codes.is_empty() {
+              return Ok("No promo codes yet.".to_string());
+            }
+            let now = Utc::now().naive_utc();
+            let mut text = String::from("<b>🎟 Promo Codes</b>\n\n");
+            for code in codes {
+              let uses = sv.promo_code.uses(&code.code).await?;
+              let grant = match code.grant {
+                PromoGrant::Balance => format_usdt(code.amount),
+                PromoGrant::Trial => format!("{}d trial", code.amount),
+              };
+              let uses = match code.max_uses {
+                Some(max) => format!("{}/{}", uses, max),
+                None => uses.to_string(),
+              };
+              let expiry = match code.expires_at {
+                Some(at) if at < now => "expired".to_string(),
+                Some(at) => format!("until {}", utils::format_date(at)),
+                None => "no expiry".to_string(),
+              };
+              text.push_str(&format!(
+                "<code>{}</code> · {} · {} use(s) · {}\n",
+                html::escape(&code.code),
+                grant,
+                uses,
+                expiry
+              ));
+            }
+            Ok(text)
+          }
+          ["del", code] => {
+            sv.promo_code.remove(code).await?;
+            Ok(format!("✅ Promo code <code>{}</code> deleted", html::escape(code)))
+          }
+          ["add", code, grant, amount, rest @ ..] => {
+            let (grant, amount) = match *grant {
+              "balance" => {
+                let usdt: f64 = amount.parse().map_err(|_| usage())?;
+                (PromoGrant::Balance, (usdt * NANO_USDT as f64) as i64)
+              }
+              "trial" => {
+                (PromoGrant::Trial, amount.parse::<i64>().map_err(|_| usage())?)
+              }
+              _ => return Err(usage()),
+            };
+            let (max_uses, rest) = match rest {
+              [max, rest @ ..] if *max != "for" => {
+                (Some(max.parse::<i32>().map_err(|_| usage())?), rest)
+              }
+              rest => (None, rest),
+            };
+            let expires_at = match rest {
+              [] => None,
+              ["for", duration] => {
+                let duration = utils::parse_duration(duration).ok_or_else(usage)?;
+                Some(Utc::now().naive_utc() + duration)
+              }
+              _ => return Err(usage()),
+            };
+
+            let code = sv
+              .promo_code
+              .create(code, grant, amount, max_uses, expires_at)
+              .await?;
+            Ok(format!(
+              "✅ Promo code <code>{}</code> created\n\
+              Users redeem it with <code>/redeem {}</code>",
+              html::escape(&code.code),
+              html::escape(&code.code)
+            ))
+          }
+          _ => Err(usage()),
+        }
+      }
+      .await
+    }
+    Command::RefBoost(args) => {
+      async {
+        let usage = || {
+          Error::InvalidArgs(
+            "Usage: /refboost [<2x|150%> <duration> [from <date>] \
+            [user_id ...] | stop <id>]"
+              .into(),
+          )
+        };
+        let args: Vec<&str> = args.split_whitespace().collect();
+        match args.as_slice() {
+          [] => {
+            let boosts = sv.referral.upcoming_boosts().await?;
+            if boosts.is_empty() {
+              return Ok("No commission boosts scheduled.".to_string());
+            }
+            let mut text = String::from("<b>🚀 Commission Boosts</b>\n\n");
+            for boost in boosts {
+              let target = match boost.creator_id {
+                Some(id) => format!("creator <code>{}</code>", id),
+                None => "everyone".into(),
+              };
+              text.push_str(&format!(
+                "#{} {} for {}\n{} - {}\n\n",
+                boost.id,
+                boost.multiplier(),
+                target,
+                utils::format_date(boost.starts_at),
+                utils::format_date(boost.ends_at)
+              ));
+            }
+            Ok(text)
+          }
+          ["stop", id] => {
+            let id = id.trim_start_matches('#').parse().map_err(|_| usage())?;
+            let boost = sv.referral.stop_boost(id).await?;
+            Ok(format!("✅ Boost #{} ({}) stopped", boost.id, boost.multiplier()))
+          }
+          [percent, duration, rest @ ..] => {
+            let percent = parse_boost_percent(percent).ok_or_else(usage)?;
+            let duration = utils::parse_duration(duration).ok_or_else(usage)?;
+            let (starts_at, creators) = match rest {
+              ["from", start, creators @ ..] => {
+                let start = parse_start(start).ok_or_else(|| {
+                  Error::InvalidArgs(
+                    "Start must look like 2026-02-10 or 2026-02-10T18:00 (UTC)"
+                      .into(),
+                  )
+                })?;
+                (start, creators)
+              }
+              creators => (Utc::now().naive_utc(), creators),
+            };
+            let creators = creators
+              .iter()
+              .map(|id| id.parse::<i64>())
+              .collect::<std::result::Result<Vec<_>, _>>()
+              .map_err(|_| Error::InvalidArgs("Invalid user ID".into()))?;
+
+            let boosts = sv
+              .referral
+              .start_boost(percent, starts_at, duration, &creators)
+              .await?;
+            let boost = &boosts[0];
+            let target = match creators.len() {
+              0 => "all creators".to_string(),
+              n => format!("{} creator(s)", n),
+            };
+            Ok(format!(
+              "✅ {} commission for {} from {} until {}",
+              boost.multiplier(),
+              target,
+              utils::format_date(boost.starts_at),
+              utils::format_date(boost.ends_at)
+            ))
+          }
+          _ => Err(usage()),
+        }
+      }
+      .await
+    }
+
+    Command::Incidents => {
+      async {
+        let incidents = sv.incident.recent(15).await?;
+        if incidents.is_empty() {
+          return Ok("✅ No incidents recorded.".into());
+        }
+
+        let mut text = String::from("<b>🚨 Recent Incidents</b>\n\n");
+        for incident in incidents {
+          text.push_str(&format!(
+            "#{} {:?} · {}\n\
+            Key: <code>{}</code> | IP: <code>{}</code>\n\
+            HWID: <code>{}</code>\n{}\n\n",
+            incident.id,
+            incident.kind,
+            utils::format_date(incident.created_at),
+            incident.license_key.as_deref().unwrap_or("-"),
+            incident.ip.as_deref().unwrap_or("-"),
+            html::escape(incident.hwid.as_deref().unwrap_or("-")),
+            html::escape(&incident.details)
+          ));
+        }
+        Ok(text)
+      }
+      .await
+    }
+
+    Command::RefStats => {
+      async {
+        let creators = sv.referral.all_creators().await?;
+        if creators.is_empty() {
+          return Ok("📭 No creators/admins with referral capability.".into());
+        }
+
+        let totals = sv.referral.all_totals().await?;
+        let mut text = String::from("<b>📊 Referral Statistics</b>\n\n");
+        let mut total_sales = 0;
+        let mut total_earnings = 0i64;
+
+        for user in &creators {
+          let (sales, earnings) =
+            totals.get(&user.tg_user_id).copied().unwrap_or((0, 0));
+          let earnings_str = format_usdt(earnings);
+          let code_display = user
+            .referral_code
+            .as_ref()
+            .map(|c| format!("<code>{}</code>", c))
+            .unwrap_or_else(|| format!("ID: <code>{}</code>", user.tg_user_id));
+          text.push_str(&format!(
+            "{} ({})\n\
+            Rate: {}% | Discount: {}%\n\
+            Sales: {} | Earned: {}\n\n",
+            code_display,
+            user.tg_user_id,
+            user.commission_rate,
+            user.discount_percent,
+            sales,
+            earnings_str
+          ));
+          total_sales += sales;
+          total_earnings += earnings;
+        }
+
+        text.push_str(&format!(
+          "<b>Total:</b>\n\
+          Creators: {}\n\
+          Sales: {}\n\
+          Commissions: {}",
+          creators.len(),
+          total_sales,
+          format_usdt(total_earnings)
+        ));
+
+        Ok(text)
+      }
+      .await
+    }
+
+    Command::Deposit(args) => {
+      async {
+        let parts: Vec<&str> = args.split_whitespace().collect();
+        match parts.as_slice() {
+          [user_id_str, amount_str] => {
+            let user_id = user_id_str
+              .parse::<i64>()
+              .map_err(|_| Error::InvalidArgs("Invalid user ID".into()))?;
+            // Parse as USDT (e.g., "10.5" = 10.5 USDT)
+            let amount_usdt: f64 = amount_str
+              .parse()
+              .map_err(|_| Error::InvalidArgs("Invalid amount".into()))?;
+            let amount_nano = (amount_usdt * NANO_USDT as f64) as i64;
+
+            if amount_nano <= 0 {
+              return Err(Error::InvalidArgs("Amount must be positive".into()));
+            }
+
+            let (op, new_balance) =
+              sv.admin_op.deposit(bot.user_id, user_id, amount_nano).await?;
+            Ok(format!(
+              "✅ Deposited {} to user {}\n\
+              New balance: {}\n\
+              <i>Undo with /undo {}</i>",
+              format_usdt(amount_nano),
+              user_id,
+              format_usdt(new_balance),
+              op.id
+            ))
+          }
+          _ => Err(Error::InvalidArgs(
+            "Usage: /deposit <user_id> <amount_usdt>".into(),
+          )),
+        }
+      }
+      .await
+    }
+
+    Command::Refund(args) => {
+      async {
+        let usage = || {
+          Error::InvalidArgs(
+            "Usage: /refund <user_id> <amount_usdt> [reason]".into(),
+          )
+        };
+        let mut parts = args.trim().splitn(3, char::is_whitespace);
+        let user_id: i64 =
+          parts.next().and_then(|id| id.parse().ok()).ok_or_else(usage)?;
+        let amount_usdt: f64 = parts
+          .next()
+          .and_then(|amount| amount.parse().ok())
+          .ok_or_else(usage)?;
+        let amount_nano = (amount_usdt * NANO_USDT as f64) as i64;
+        let reason = parts
+          .next()
+          .map(str::trim)
+          .filter(|reason| !reason.is_empty())
+          .map(String::from);
+
+        let refunded =
+          sv.balance.refund(user_id, amount_nano, reason.clone()).await?;
+        info!(
+          "Admin {} refunded {} to user {} for purchase #{}",
+          bot.user_id, amount_nano, user_id, refunded.purchase.id
+        );
+
+        let mut text = format!(
+          "💸 <b>Refund credited</b>\n\n\
+          {} was returned to your balance.",
+          format_usdt(amount_nano)
+        );
+        if let Some(reason) = &reason {
+          text.push_str(&format!("\n<b>Reason:</b> {}", html::escape(reason)));
+        }
+        if let Some((user_bot, text)) = app.reach(user_id, text).await {
+          let _ = user_bot
+            .send_message(ChatId(user_id), text)
+            .parse_mode(ParseMode::Html)
+            .await;
+        }
+
+        Ok(format!(
+          "✅ Refunded {} to user {} (tx #{})\n\
+          Purchase #{} of {}{}\n\
+          New balance: {}",
+          format_usdt(amount_nano),
+          user_id,
+          refunded.refund.id,
+          refunded.purchase.id,
+          format_usdt(-refunded.purchase.amount),
+          refunded
+            .purchase
+            .description
+            .as_deref()
+            .map(|d| format!(" · {}", html::escape(d)))
+            .unwrap_or_default(),
+          format_usdt(refunded.balance)
+        ))
+      }
+      .await
+    }
+
+    Command::Withdraw(args) => {
+      async {
+        let parts: Vec<&str> = args.split_whitespace().collect();
+        match parts.as_slice() {
+          [user_id_str, amount_str] => {
+            let user_id = user_id_str
+              .parse::<i64>()
+              .map_err(|_| Error::InvalidArgs("Invalid user ID".into()))?;
+            // Parse as USDT (e.g., "10.5" = 10.5 USDT)
+            let amount_usdt: f64 = amount_str
+              .parse()
+              .map_err(|_| Error::InvalidArgs("Invalid amount".into()))?;
+            let amount_nano = (amount_usdt * NANO_USDT as f64) as i64;
+
+            if amount_nano <= 0 {
+              return Err(Error::InvalidArgs("Amount must be positive".into()));
+            }
+
+            if let Some(score) = review::flagged(&app, user_id).await? {
+              let action = Action::Payout { amount: amount_nano };
+              let review =
+                review::submit(&app, user_id, action, score, Some(bot.user_id))
+                  .await?;
+              return Ok(format!(
+                "⏳ User {} has risk score {}, the withdrawal waits in                 review #{}",
+                user_id, score, review.id
+              ));
+            }
+            let (op, new_balance) =
+              sv.admin_op.withdraw(bot.user_id, user_id, amount_nano).await?;
+            Ok(format!(
+              "✅ Withdrawal of {} processed for user {}\n\
+              New balance: {}\n\
+              <i>Undo with /undo {}</i>",
+              format_usdt(amount_nano),
+              user_id,
+              format_usdt(new_balance),
+              op.id
+            ))
+          }
+          _ => Err(Error::InvalidArgs(
+            "Usage: /withdraw <user_id> <amount_usdt>".into(),
+          )),
+        }
+      }
+      .await
+    }
+
+    Command::Undo(args) => {
+      async {
+        if !app.config.owners.contains(&bot.user_id) {
+          return Err(Error::InvalidArgs(
+            "Only owners can undo balance operations".into(),
+          ));
+        }
+        let window = TimeDelta::minutes(app.config.undo_window_mins);
+
+        let args = args.trim();
+        if args.is_empty() {
+          let ops = sv.admin_op.recent(sv::admin_op::HISTORY).await?;
+          if ops.is_empty() {
+            return Ok("No balance operations yet.".into());
+          }
+          let now = Utc::now().naive_utc();
+          let mut text = String::from("<b>↩️ Recent Balance Operations</b>\n\n");
+          for op in ops {
+            let status = match op.undone_by {
+              Some(by) => format!(" · undone by {}", by),
+              None if now - op.created_at > window => " · expired".into(),
+              None => String::new(),
+            };
+            text.push_str(&format!(
+              "#{} {:?} {} · user {} by {} · {}{}\n",
+              op.id,
+              op.kind,
+              format_usdt(op.amount),
+              op.user_id,
+              op.admin_id,
+              utils::format_date(op.created_at),
+              status
+            ));
+          }
+          text.push_str(&format!(
+            "\n<i>Operations can be undone for {} minutes</i>",
+            app.config.undo_window_mins
+          ));
+          return Ok(text);
+        }
+
+        let id = args.trim_start_matches('#').parse().map_err(|_| {
+          Error::InvalidArgs("Usage: /undo [operation id]".into())
+        })?;
+        let (op, balance) = sv.admin_op.undo(id, bot.user_id, window).await?;
+        Ok(format!(
+          "✅ {:?} #{} of {} undone\nUser {} balance: {}",
+          op.kind,
+          op.id,
+          format_usdt(op.amount),
+          op.user_id,
+          format_usdt(balance)
+        ))
+      }
+      .await
+    }
+    Command::Txns(args) => {
+      async {
+        let usage = || {
+          Error::InvalidArgs(format!(
+            "Usage: /txns <user_id> [limit], up to {}",
+            TXNS_MAX
+          ))
+        };
+        let mut args = args.split_whitespace();
+        let user_id: i64 =
+          args.next().and_then(|id| id.parse().ok()).ok_or_else(usage)?;
+        let limit = match args.next() {
+          None => TXNS_DEFAULT,
+          Some(limit) => limit
+            .parse()
+            .ok()
+            .filter(|limit| (1..=TXNS_MAX).contains(limit))
+            .ok_or_else(usage)?,
+        };
+
+        let ledger = sv.balance.ledger(user_id, limit).await?;
+        let mut text = format!(
+          "📒 <b>Ledger of</b> <code>{}</code>\n\n\
+          <b>Balance:</b> {}\n\
+          <b>Transactions:</b> {}\n",
+          user_id,
+          format_usdt(ledger.balance),
+          ledger.total
+        );
+        if ledger.drift != 0 {
+          text.push_str(&format!(
+            "⚠️ <b>Balance is {:+.2} USDT off the transactions</b>\n",
+            ledger.drift as f64 / NANO_USDT as f64
+          ));
+        }
+        if ledger.entries.is_empty() {
+          text.push_str("\nNo transactions yet.");
+          return Ok(text);
+        }
+        for (tx, after) in &ledger.entries {
+          text.push_str(&format!(
+            "\n#{} {} <b>{:+.2}</b> {:?} → {}",
+            tx.id,
+            utils::format_date(tx.created_at),
+            tx.amount as f64 / NANO_USDT as f64,
+            tx.tx_type,
+            format_usdt(*after)
+          ));
+          if let Some(description) = &tx.description {
+            text.push_str(&format!(" · {}", html::escape(description)));
+          }
+        }
+        if ledger.total > ledger.entries.len() as u64 {
+          text.push_str(&format!(
+            "\n\n<i>Newest {} of {} shown</i>",
+            ledger.entries.len(),
+            ledger.total
+          ));
+        }
+        Ok(text)
+      }
+      .await
+    }
+
     _ => return Ok(()),
   };
 
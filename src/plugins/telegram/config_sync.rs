@@ -0,0 +1,135 @@
+use teloxide::{net::Download, prelude::*, types::InputFile, utils::html};
+
+use super::ReplyBot;
+use crate::{
+  prelude::*,
+  state::AppState,
+  sv::config_sync::{Change, Diff, Snapshot},
+};
+
+/// Largest file the Bot API lets bots download
+const SNAPSHOT_MAX_SIZE: u32 = 20 * 1024 * 1024;
+const USAGE: &str =
+  "Usage: /config export, or reply to a snapshot with /config import [confirm]";
+
+/// One line per changed entry, grouped by section in snapshot order
+fn describe(diffs: &[Diff]) -> String {
+  let mut text = String::new();
+  for diff in diffs {
+    let key = html::escape(&diff.key);
+    let line = match &diff.change {
+      Change::Add => format!("➕ {} <code>{}</code>", diff.section, key),
+      Change::Update(fields) => format!(
+        "✏️ {} <code>{}</code>: {}",
+        diff.section,
+        key,
+        fields.join(", ")
+      ),
+      Change::Skip(reason) => format!(
+        "⏭ {} <code>{}</code>: {}",
+        diff.section,
+        key,
+        html::escape(reason)
+      ),
+    };
+    text.push('\n');
+    text.push_str(&line);
+  }
+  text
+}
+
+/// /config export|import [confirm], copy settings between servers
+pub(super) async fn run(
+  app: &AppState,
+  bot: &ReplyBot,
+  msg: &Message,
+  args: &str,
+) -> ResponseResult<()> {
+  let mut args = args.split_whitespace();
+  let (action, confirm) = (args.next(), args.next());
+  if args.next().is_some() {
+    bot.reply_html(format!("❌ {}", USAGE)).await?;
+    return Ok(());
+  }
+
+  match (action, confirm) {
+    (Some("export"), None) => {
+      let snapshot = match app.sv().config_sync.export().await {
+        Ok(snapshot) => snapshot,
+        Err(e) => {
+          bot.reply_html(format!("❌ {}", e.user_message())).await?;
+          return Ok(());
+        }
+      };
+      let name =
+        format!("config-{}.json", snapshot.exported_at.format("%Y%m%d-%H%M%S"));
+      bot
+        .send_document(
+          InputFile::memory(snapshot.to_json().into_bytes()).file_name(name),
+        )
+        .await?;
+    }
+    (Some("import"), None | Some("confirm")) => {
+      let apply = confirm.is_some();
+      let Some(document) =
+        msg.reply_to_message().and_then(|reply| reply.document())
+      else {
+        bot
+          .reply_html(
+            "❌ Reply to a config snapshot with /config import to preview it, \
+            then with <code>/config import confirm</code> to apply it",
+          )
+          .await?;
+        return Ok(());
+      };
+      if document.file.size > SNAPSHOT_MAX_SIZE {
+        bot
+          .reply_html("❌ Snapshot is too large for the Bot API (20MB)")
+          .await?;
+        return Ok(());
+      }
+
+      let file = bot.inner.get_file(document.file.id.clone()).await?;
+      let mut data = Vec::with_capacity(file.size as usize);
+      bot.inner.download_file(&file.path, &mut data).await?;
+
+      let result = async {
+        let snapshot = Snapshot::parse(&data)?;
+        let sync = app.sv().config_sync;
+        let diffs = if apply {
+          sync.apply(&snapshot).await?
+        } else {
+          sync.diff(&snapshot).await?
+        };
+        Ok::<_, Error>((snapshot, diffs))
+      }
+      .await;
+
+      let text = match result {
+        Ok((_, diffs)) if diffs.is_empty() => {
+          "✅ Nothing differs from this server".to_string()
+        }
+        Ok((snapshot, diffs)) if apply => {
+          warn!(
+            "Admin {} imported the config exported at {}",
+            bot.user_id, snapshot.exported_at
+          );
+          format!("✅ <b>Config imported</b>\n{}", describe(&diffs))
+        }
+        Ok((snapshot, diffs)) => format!(
+          "🔍 <b>Import preview</b>, exported {}\n{}\n\n\
+          Reply to it with <code>/config import confirm</code> to apply. \
+          Entries missing from the snapshot are kept.",
+          snapshot.exported_at.format("%Y-%m-%d %H:%M UTC"),
+          describe(&diffs)
+        ),
+        Err(e) => format!("❌ {}", html::escape(&e.user_message())),
+      };
+      bot.reply_html_chunked(text).await?;
+    }
+    _ => {
+      bot.reply_html(format!("❌ {}", USAGE)).await?;
+    }
+  }
+  Ok(())
+}
@@ -0,0 +1,161 @@
+use std::sync::Arc;
+
+use teloxide::{
+  dispatching::dialogue::{Dialogue, InMemStorage},
+  prelude::*,
+  types::{InlineKeyboardButton, InlineKeyboardMarkup},
+  utils::html,
+};
+
+use super::{
+  Brand, ReplyBot,
+  callback::{TRIAL_PROMO, home},
+  i18n::{self, Lang, T},
+};
+use crate::{prelude::*, state::AppState};
+
+/// Whether the 🎁 Redeem Gift button waits for a key
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum Gift {
+  #[default]
+  Idle,
+  Key,
+}
+
+pub type GiftStorage = InMemStorage<Gift>;
+pub type GiftDialogue = Dialogue<Gift, GiftStorage>;
+
+pub const PREFIX: &str = "gift:";
+
+/// Gift buttons, routed here by the `gift:` prefix
+#[derive(Debug, Clone, PartialEq)]
+pub enum Action {
+  Redeem,
+  Cancel,
+}
+
+impl Action {
+  pub fn to_data(&self) -> String {
+    match self {
+      Action::Redeem => "gift:redeem".to_string(),
+      Action::Cancel => "gift:cancel".to_string(),
+    }
+  }
+
+  pub fn from_data(data: &str) -> Option<Self> {
+    match data {
+      "gift:redeem" => Some(Action::Redeem),
+      "gift:cancel" => Some(Action::Cancel),
+      _ => None,
+    }
+  }
+}
+
+fn keyboard(lang: Lang) -> InlineKeyboardMarkup {
+  InlineKeyboardMarkup::new(vec![vec![InlineKeyboardButton::callback(
+    lang.t(T::BackToMenu),
+    Action::Cancel.to_data(),
+  )]])
+}
+
+async fn exit(dialogue: &GiftDialogue) {
+  if let Err(e) = dialogue.exit().await {
+    warn!("Failed to reset gift state: {}", e);
+  }
+}
+
+pub async fn handle_callback(
+  app: Arc<AppState>,
+  bot: Bot,
+  query: CallbackQuery,
+  dialogue: GiftDialogue,
+  brand: Brand,
+) -> ResponseResult<()> {
+  let (Some(data), Some(msg)) = (query.data.as_ref(), query.message.as_ref())
+  else {
+    return Ok(());
+  };
+
+  let mut bot = ReplyBot::new(
+    bot,
+    query.from.id.0 as i64,
+    msg.chat().id,
+    msg.id(),
+    brand,
+    app.config.environment,
+  );
+  bot.inner.answer_callback_query(query.id.clone()).await?;
+
+  let Some(action) = Action::from_data(data) else {
+    return Ok(());
+  };
+
+  let sv = app.sv();
+  bot.localize(&sv).await;
+
+  match action {
+    Action::Redeem => {
+      if let Err(e) = dialogue.update(Gift::Key).await {
+        warn!("Failed to update gift state: {}", e);
+      }
+      bot
+        .edit_with_keyboard(bot.lang.t(T::GiftPrompt), keyboard(bot.lang))
+        .await?;
+    }
+    Action::Cancel => {
+      exit(&dialogue).await;
+      let (text, kb) =
+        home(&bot, sv.license.is_promo_active(TRIAL_PROMO).await);
+      bot.edit_with_keyboard(text, kb).await?;
+    }
+  }
+
+  Ok(())
+}
+
+/// Text entered after 🎁 Redeem Gift, linking the key starts its term
+pub async fn receive_key(
+  app: Arc<AppState>,
+  mut bot: ReplyBot,
+  msg: Message,
+  dialogue: GiftDialogue,
+) -> ResponseResult<()> {
+  let sv = app.sv();
+  bot.localize(&sv).await;
+
+  let Some(key) = msg.text().map(str::trim).filter(|s| !s.is_empty()) else {
+    bot.reply_html(bot.lang.t(T::GiftSendKey)).await?;
+    return Ok(());
+  };
+
+  match sv.license.link_to_user(key, bot.user_id).await {
+    Ok(license) => {
+      exit(&dialogue).await;
+      info!("User {} redeemed gift key {}", bot.user_id, license.key);
+      bot
+        .reply_html(i18n::fill(
+          bot.lang.t(T::GiftRedeemed),
+          &[
+            ("type", format!("{:?}", license.license_type)),
+            ("key", license.key),
+            ("expires", utils::format_date(license.expires_at)),
+          ],
+        ))
+        .await?;
+    }
+    Err(e) => {
+      bot
+        .reply_with_keyboard(
+          format!(
+            "❌ {}\n\n{}",
+            html::escape(&e.user_message()),
+            bot.lang.t(T::GiftTryAgain)
+          ),
+          keyboard(bot.lang),
+        )
+        .await?;
+    }
+  }
+
+  Ok(())
+}
@@ -0,0 +1,1298 @@
+//! Bot texts in every supported language.
+//! Dynamic parts are `{name}` placeholders filled with [`fill`].
+
+use crate::{prelude::*, state::Services, sv::canned};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Lang {
+  #[default]
+  En,
+  Ru,
+}
+
+impl Lang {
+  pub const ALL: [Lang; 2] = [Lang::En, Lang::Ru];
+
+  /// Code stored in user settings
+  pub fn code(self) -> &'static str {
+    match self {
+      Lang::En => "en",
+      Lang::Ru => "ru",
+    }
+  }
+
+  /// Accepts stored codes and Telegram ones like "ru-RU"
+  pub fn parse(code: &str) -> Option<Self> {
+    let code = code.split(['-', '_']).next()?.to_lowercase();
+    Self::ALL.into_iter().find(|lang| lang.code() == code)
+  }
+
+  pub fn label(self) -> &'static str {
+    match self {
+      Lang::En => "🇬🇧 English",
+      Lang::Ru => "🇷🇺 Русский",
+    }
+  }
+
+  /// Language a user chose, for messages that don't answer them directly
+  pub async fn of(sv: &Services<'_>, tg_user_id: i64) -> Self {
+    match sv.settings.get_or_create(tg_user_id).await {
+      Ok(settings) => Lang::parse(&settings.language).unwrap_or_default(),
+      Err(e) => {
+        warn!("Failed to load language of {}: {}", tg_user_id, e);
+        Lang::default()
+      }
+    }
+  }
+
+  pub fn t(self, text: T) -> &'static str {
+    text.get(self)
+  }
+
+  /// Duration with translated units, e.g. `2d 5h` or `2д 5ч`
+  pub fn duration(self, duration: TimeDelta) -> String {
+    let units = [T::UnitDay, T::UnitHour, T::UnitMinute].map(|t| self.t(t));
+    utils::humanize(duration, units)
+  }
+}
+
+/// Replace `{name}` placeholders of a translated text
+pub fn fill(text: &str, vars: &[(&str, String)]) -> String {
+  canned::expand(text, vars)
+}
+
+macro_rules! texts {
+  ($($key:ident => $en:expr, $ru:expr;)*) => {
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum T {
+      $($key,)*
+    }
+
+    impl T {
+      #[cfg(test)]
+      const ALL: &[T] = &[$(T::$key,)*];
+
+      pub fn get(self, lang: Lang) -> &'static str {
+        match (self, lang) {
+          $(
+            (T::$key, Lang::En) => $en,
+            (T::$key, Lang::Ru) => $ru,
+          )*
+        }
+      }
+    }
+  };
+}
+
+texts! {
+  Welcome =>
+    "<b>Yet Another Counter Strike Panel!</b>\n\n\
+    Use the buttons below to navigate.\n\
+    Read docs: https://yacsp.gitbook.io/yacsp\n\
+    Contact support: @y_a_c_s_p",
+    "<b>Yet Another Counter Strike Panel!</b>\n\n\
+    Используйте кнопки ниже для навигации.\n\
+    Документация: https://yacsp.gitbook.io/yacsp\n\
+    Поддержка: @y_a_c_s_p";
+  BrandWelcome =>
+    "<b>{title}</b>\n\n\
+    Use the buttons below to navigate.\n\
+    Contact support: @{support}",
+    "<b>{title}</b>\n\n\
+    Используйте кнопки ниже для навигации.\n\
+    Поддержка: @{support}";
+  UseStart =>
+    "Use /start to access the main menu with buttons.",
+    "Откройте главное меню с кнопками командой /start.";
+
+  UnitDay => "d", "д";
+  UnitHour => "h", "ч";
+  UnitMinute => "m", "мин";
+
+  MenuProfile => "👤 My Profile", "👤 Мой профиль";
+  MenuInbox => "📬 Inbox", "📬 Входящие";
+  MenuLicense => "🔑 My License", "🔑 Моя лицензия";
+  MenuBuy => "💳 Buy License", "💳 Купить лицензию";
+  MenuFunds => "💵 Add Funds", "💵 Пополнить баланс";
+  MenuDownload => "📥 Download Panel", "📥 Скачать панель";
+  MenuFaq => "❓ FAQ", "❓ FAQ";
+  MenuTrial => "🆓 Get Free Trial", "🆓 Бесплатный пробный период";
+  MenuLanguage => "🌐 Language", "🌐 Язык";
+  MenuFreebies => "🎁 Freebies", "🎁 Халява";
+  MenuGift => "🎁 Redeem Gift", "🎁 Активировать подарок";
+  BackToMenu => "« Back to Menu", "« В меню";
+  BackMenu => "« Menu", "« Меню";
+  BackToProfile => "« Back to Profile", "« В профиль";
+  NoActiveLicense =>
+    "You have no active license!",
+    "У вас нет активной лицензии!";
+
+  FaqEmpty =>
+    "❓ <b>FAQ</b>\n\nNo questions yet. Contact support: @{support}",
+    "❓ <b>FAQ</b>\n\nВопросов пока нет. Поддержка: @{support}";
+  FaqBrowse =>
+    "❓ <b>Frequently Asked Questions</b>\n\n\
+    Pick a question below or search with <code>/faq your question</code>.",
+    "❓ <b>Частые вопросы</b>\n\n\
+    Выберите вопрос ниже или найдите ответ: <code>/faq ваш вопрос</code>.";
+  FaqNotHelpful =>
+    "Didn't help? Contact support: @{support}",
+    "Не помогло? Напишите в поддержку: @{support}";
+  FaqNothingFound =>
+    "🤷 Nothing found for <i>{query}</i>.\n\n\
+    Browse all questions or contact support: @{support}",
+    "🤷 По запросу <i>{query}</i> ничего не найдено.\n\n\
+    Посмотрите все вопросы или напишите в поддержку: @{support}";
+  FaqAll => "📚 All questions", "📚 Все вопросы";
+
+  Profile =>
+    "👤 <b>My Profile</b>\n\n\
+    <b>User ID:</b> <code>{id}</code>\n\
+    <b>Registered:</b> {registered}\n\
+    <b>Balance:</b> {balance}\n\
+    <b>Role:</b> {role}",
+    "👤 <b>Мой профиль</b>\n\n\
+    <b>ID:</b> <code>{id}</code>\n\
+    <b>Регистрация:</b> {registered}\n\
+    <b>Баланс:</b> {balance}\n\
+    <b>Роль:</b> {role}";
+  ProfileStats =>
+    "\n\n<b>📊 Farming Stats:</b>\n\
+    Weekly XP: {weekly_xp}\n\
+    Total XP: {total_xp}\n\
+    Drops: {drops}\n\
+    Runtime: {runtime}h",
+    "\n\n<b>📊 Статистика фарма:</b>\n\
+    XP за неделю: {weekly_xp}\n\
+    Всего XP: {total_xp}\n\
+    Дропы: {drops}\n\
+    Время работы: {runtime} ч";
+  ProfileByLicense =>
+    "\n\n<b>🔑 By license:</b>",
+    "\n\n<b>🔑 По ключам:</b>";
+  ProfileLicenseStats =>
+    "\n<code>{key}</code>: {xp} XP, {runtime}h",
+    "\n<code>{key}</code>: {xp} XP, {runtime} ч";
+  ProfileGoal =>
+    "\n🎯 <b>Daily goal:</b> {progress} / {target}\n\
+    🔥 <b>Streak:</b> {streak} day(s) (best {best})",
+    "\n🎯 <b>Цель на день:</b> {progress} / {target}\n\
+    🔥 <b>Серия:</b> {streak} дн. (рекорд {best})";
+  ProfileRoutes => "\n🌐 <b>Routes:</b> {routes}", "\n🌐 <b>Маршруты:</b> {routes}";
+  ProfilePerf =>
+    "\n🚀 <b>Perf:</b> {fps} FPS | {ram} MB",
+    "\n🚀 <b>Производительность:</b> {fps} FPS | {ram} МБ";
+  ProfileTopState =>
+    "\n⏳ <b>Top State:</b> {state} ({hours}h)",
+    "\n⏳ <b>Основное состояние:</b> {state} ({hours} ч)";
+  Unknown => "Unknown", "Неизвестно";
+  RoleUser => "User", "Пользователь";
+  RoleCreator => "Creator", "Автор";
+  RoleAdmin => "Admin", "Администратор";
+  ProfileReferral => "🔗 About Referral", "🔗 Реферальная программа";
+  ProfileApiToken => "🔑 API Token", "🔑 API-токен";
+  ProfileInstances => "🖥 Instances", "🖥 Экземпляры";
+  ProfileHistory => "📜 Transactions", "📜 Операции";
+  ProfileChart => "📈 Chart", "📈 График";
+  ChartCaption =>
+    "📈 <b>Last {days} days</b>\n\
+    XP: +{xp} (best day +{best})\n\
+    Runtime: {runtime}h",
+    "📈 <b>Последние {days} дн.</b>\n\
+    XP: +{xp} (лучший день +{best})\n\
+    Время работы: {runtime} ч";
+  ChartEmpty =>
+    "📈 Nothing to chart yet, stats are collected daily.",
+    "📈 Пока нечего показать, статистика собирается раз в день.";
+
+  History =>
+    "📜 <b>Transactions</b> ({page}/{pages})",
+    "📜 <b>Операции</b> ({page}/{pages})";
+  HistoryEmpty =>
+    "📜 <b>Transactions</b>\n\nNo transactions yet.",
+    "📜 <b>Операции</b>\n\nОпераций пока нет.";
+  HistoryNewer => "« Newer", "« Новее";
+  Balance =>
+    "💰 <b>Balance:</b> {balance}",
+    "💰 <b>Баланс:</b> {balance}";
+  BalancePending =>
+    "\n\n⏳ <b>Awaiting payment:</b>",
+    "\n\n⏳ <b>Ожидают оплаты:</b>";
+  BalanceInvoice =>
+    "\n• {amount}, until {expires}",
+    "\n• {amount}, до {expires}";
+  BalanceCheck => "🔄 Check Payments", "🔄 Проверить оплату";
+  HistoryOlder => "Older »", "Старее »";
+  TxDeposit => "Deposit", "Пополнение";
+  TxPurchase => "Purchase", "Покупка";
+  TxReferralBonus => "Referral bonus", "Реферальный бонус";
+  TxWithdrawal => "Withdrawal", "Вывод";
+  TxRefund => "Refund", "Возврат";
+  TxReversal => "Reversal", "Отмена";
+  TxPromoCredit => "Promo code", "Промокод";
+
+  Freebies =>
+    "🎁 <b>Freebies</b>\n\n\
+    Free games and items you can grab right now.\n\
+    Claimed today: {claimed} / {limit}",
+    "🎁 <b>Халява</b>\n\n\
+    Бесплатные игры и предметы, которые можно забрать прямо сейчас.\n\
+    Получено сегодня: {claimed} / {limit}";
+  FreebiesEmpty =>
+    "🎁 <b>Freebies</b>\n\nNothing new to claim, check back later.",
+    "🎁 <b>Халява</b>\n\nНовых раздач нет, загляните позже.";
+  FreebiesMore => "\n\n<i>…and {count} more</i>", "\n\n<i>…и ещё {count}</i>";
+  FreebieGranted =>
+    "✅ <b>{name}</b> is yours!\n\nPick it up on Steam: {url}",
+    "✅ <b>{name}</b> теперь ваш!\n\nЗаберите в Steam: {url}";
+  BackToFreebies => "« Back to Freebies", "« К халяве";
+
+  LangPrompt =>
+    "🌐 <b>Language</b>\n\nChoose the language of the bot:",
+    "🌐 <b>Язык</b>\n\nВыберите язык бота:";
+  LangChanged => "✅ Language set to English", "✅ Язык изменён на русский";
+  LangUnknown =>
+    "❌ Unknown language, use one of: {codes}",
+    "❌ Неизвестный язык, доступны: {codes}";
+
+  ObWelcome =>
+    "👋 <b>Welcome!</b>\n\n\
+    Let's get you set up, it only takes a minute.\n\n\
+    Please choose your language:",
+    "👋 <b>Добро пожаловать!</b>\n\n\
+    Давайте всё настроим, это займёт минуту.\n\n\
+    Выберите язык:";
+  ObSkip => "Skip setup »", "Пропустить настройку »";
+  ObNext => "Next »", "Далее »";
+  ObNotNow => "Not now »", "Не сейчас »";
+  ObIntro =>
+    "🎯 <b>What is YACSP?</b>\n\n\
+    <b>Yet Another Counter Strike Panel</b> manages your CS2 farm: \
+    it runs your accounts, collects weekly drops and XP, \
+    and tracks everything in one place.\n\n\
+    Read docs: https://yacsp.gitbook.io/yacsp",
+    "🎯 <b>Что такое YACSP?</b>\n\n\
+    <b>Yet Another Counter Strike Panel</b> управляет вашей фермой CS2: \
+    запускает аккаунты, собирает еженедельные дропы и XP \
+    и показывает всё в одном месте.\n\n\
+    Документация: https://yacsp.gitbook.io/yacsp";
+  ObTrial =>
+    "🆓 <b>Free Trial</b>\n\n\
+    A promo is running right now: you can try the panel \
+    for a week for free.",
+    "🆓 <b>Пробный период</b>\n\n\
+    Сейчас идёт акция: панель можно попробовать \
+    бесплатно в течение недели.";
+  ObClaimTrial => "🆓 Claim Free Trial", "🆓 Получить пробный период";
+  ObTrialKey =>
+    "🎉 Your free trial key: <code>{key}</code>\n\n",
+    "🎉 Ваш пробный ключ: <code>{key}</code>\n\n";
+  ObLicensing =>
+    "🔑 <b>How licensing works</b>\n\n\
+    • A license key unlocks the panel for a fixed period\n\
+    • Buy or extend keys with your balance from <b>💳 Buy License</b>\n\
+    • Already have a key? Link it with <code>/link KEY</code>\n\
+    • Download the panel from <b>📥 Download Panel</b> \
+    and enter your key on first launch",
+    "🔑 <b>Как работают лицензии</b>\n\n\
+    • Ключ открывает доступ к панели на определённый срок\n\
+    • Покупайте и продлевайте ключи с баланса в разделе <b>💳 Купить лицензию</b>\n\
+    • Уже есть ключ? Привяжите его: <code>/link КЛЮЧ</code>\n\
+    • Скачайте панель в разделе <b>📥 Скачать панель</b> \
+    и введите ключ при первом запуске";
+  ObReferral =>
+    "🔗 <b>Referral Code</b>\n\n\
+    Got a code from a creator or a friend? \
+    Send it as a message to get a discount on purchases.\n\n\
+    You can always set it later with <code>/ref CODE</code>.",
+    "🔗 <b>Реферальный код</b>\n\n\
+    Получили код от автора или друга? \
+    Отправьте его сообщением, чтобы получить скидку на покупки.\n\n\
+    Его можно указать и позже: <code>/ref КОД</code>.";
+  ObStale =>
+    "Use /start to open the main menu.",
+    "Откройте главное меню командой /start.";
+  ObSendCode =>
+    "Please send your referral code as text.",
+    "Отправьте реферальный код текстом.";
+  ObCodeApplied =>
+    "✅ Referral code <code>{code}</code> applied!",
+    "✅ Реферальный код <code>{code}</code> применён!";
+  ObOwnCode =>
+    "❌ You can't use your own referral code.",
+    "❌ Нельзя использовать собственный реферальный код.";
+  ObTryAgain =>
+    "Try again or skip this step.",
+    "Попробуйте ещё раз или пропустите этот шаг.";
+
+  GiftPrompt =>
+    "🎁 <b>Redeem a Gift</b>\n\n\
+    Send the gift key as a message. \
+    Its term starts now, not when the gift was bought.",
+    "🎁 <b>Активация подарка</b>\n\n\
+    Отправьте ключ подарка сообщением. \
+    Срок пойдёт с момента активации, а не покупки.";
+  GiftSendKey =>
+    "Please send the gift key as text.",
+    "Отправьте ключ подарка текстом.";
+  GiftRedeemed =>
+    "🎉 <b>Gift redeemed!</b>\n\n\
+    Your {type} license <code>{key}</code> is now linked to your account \
+    and valid until {expires}.",
+    "🎉 <b>Подарок активирован!</b>\n\n\
+    Лицензия {type} <code>{key}</code> привязана к вашему аккаунту \
+    и действует до {expires}.";
+  GiftTryAgain =>
+    "Check the key and try again.",
+    "Проверьте ключ и попробуйте ещё раз.";
+
+  Back => "« Back", "« Назад";
+  NoneSet => "None", "Нет";
+  Never => "never", "никогда";
+  Expired => "❌ Expired", "❌ Истекла";
+  LicenseNotFound => "❌ License not found.", "❌ Лицензия не найдена.";
+  KeyGone => "❌ This key no longer exists.", "❌ Этот ключ больше не существует.";
+  ErrorOccurred => "An error occurred.", "Произошла ошибка.";
+  InvalidPlan => "❌ Invalid plan.", "❌ Неизвестный тариф.";
+  InvalidAmount => "❌ Invalid amount.", "❌ Неверная сумма.";
+  PricesFailed =>
+    "❌ Failed to load prices: {error}",
+    "❌ Не удалось загрузить цены: {error}";
+  PaymentFailed =>
+    "❌ Failed to process payment: {error}",
+    "❌ Не удалось провести оплату: {error}";
+  ContactSupport => "📞 Contact Support", "📞 Написать в поддержку";
+  RatePurchase =>
+    "How was your purchase experience?",
+    "Как вам покупка?";
+  RateThanks =>
+    "🙏 Thanks for your feedback! {stars}",
+    "🙏 Спасибо за отзыв! {stars}";
+
+  SetRefCode =>
+    "🔗 <b>Set Referral Code</b>\n\n\
+    A referral code can be a creator's custom code or a friend's User ID.\n\
+    When you have a referral code from a creator, you get a discount on purchases!\n\n\
+    <b>Your current referral code:</b> {current}\n\n\
+    <b>To set/change:</b> <code>/ref CODE</code>\n\
+    <b>To clear:</b> <code>/ref clear</code>",
+    "🔗 <b>Реферальный код</b>\n\n\
+    Реферальный код — это собственный код автора или User ID друга.\n\
+    С реферальным кодом автора вы получаете скидку на покупки!\n\n\
+    <b>Ваш текущий реферальный код:</b> {current}\n\n\
+    <b>Указать или сменить:</b> <code>/ref КОД</code>\n\
+    <b>Убрать:</b> <code>/ref clear</code>";
+  RefCleared =>
+    "✅ Your referral code has been cleared.",
+    "✅ Реферальный код убран.";
+  RefDiscount =>
+    "\nYou will receive a {discount}% discount on purchases!",
+    "\nВы будете получать скидку {discount}% на покупки!";
+  RefVerified =>
+    "\nThis is a verified creator.",
+    "\nЭто подтверждённый автор.";
+  RefNotVerified =>
+    "\n<i>Note: This user is not a verified creator, so no discount is available.</i>",
+    "\n<i>Этот пользователь не подтверждённый автор, поэтому скидки нет.</i>";
+  MyCodeSet =>
+    "✅ Your custom referral code is now set!\n\
+    <b>Code:</b> <code>{code}</code>\n\n\
+    Share this code with others. They can use:\n\
+    <code>/ref {code}</code>\n\
+    to set you as their referrer.",
+    "✅ Ваш реферальный код установлен!\n\
+    <b>Код:</b> <code>{code}</code>\n\n\
+    Поделитесь им с другими. Чтобы указать вас рефералом, достаточно:\n\
+    <code>/ref {code}</code>";
+  MyCodeCleared =>
+    "✅ Your custom referral code has been cleared.\n\
+    Users can still use your user ID as referral code.",
+    "✅ Ваш реферальный код удалён.\n\
+    Ваш User ID по-прежнему работает как реферальный код.";
+
+  RefInviteUnavailable => "Unable to generate link", "Не удалось создать ссылку";
+  RefTipId =>
+    "\n<i>Tip: Users can also use your ID <code>{id}</code> as referral code.</i>",
+    "\n<i>Совет: ваш ID <code>{id}</code> тоже работает как реферальный код.</i>";
+  RefTipSetCode =>
+    "\n<i>Tip: Ask an admin to set a custom code with /setcode</i>",
+    "\n<i>Совет: попросите администратора задать собственный код через /setcode</i>";
+  RefCreator =>
+    "🔗 <b>Referral Program (Creator)</b>\n\n\
+    <b>Your referral code:</b> <code>{code}</code>\n\n\
+    <b>📎 Invite Link:</b>\n\
+    <code>{link}</code>\n\n\
+    <b>📊 Your Stats:</b>\n\
+    Commission rate: {commission}%\n\
+    Customer discount: {discount}%\n\
+    Total sales: {sales}\n\
+    Total earnings: {earnings}\n\n\
+    <b>💡 How it works:</b>\n\
+    Share your invite link or code (<code>{code}</code>) with others. When they click the link:\n\
+    • Your referral code is applied automatically\n\
+    • They get a {discount}% discount on purchases\n\
+    • You earn {commission}% commission on their purchases\n\n\
+    <i>Commissions are added to your balance automatically.</i>{note}",
+    "🔗 <b>Реферальная программа (автор)</b>\n\n\
+    <b>Ваш реферальный код:</b> <code>{code}</code>\n\n\
+    <b>📎 Ссылка-приглашение:</b>\n\
+    <code>{link}</code>\n\n\
+    <b>📊 Ваша статистика:</b>\n\
+    Комиссия: {commission}%\n\
+    Скидка покупателям: {discount}%\n\
+    Всего продаж: {sales}\n\
+    Всего заработано: {earnings}\n\n\
+    <b>💡 Как это работает:</b>\n\
+    Поделитесь ссылкой или кодом (<code>{code}</code>). Когда человек переходит по ссылке:\n\
+    • ваш реферальный код применяется автоматически\n\
+    • он получает скидку {discount}% на покупки\n\
+    • вы получаете {commission}% комиссии с его покупок\n\n\
+    <i>Комиссия зачисляется на баланс автоматически.</i>{note}";
+  RefCreatorShort =>
+    "🔗 <b>Referral Program (Creator)</b>\n\n\
+    <b>Your referral code:</b> <code>{code}</code>\n\n\
+    <b>📎 Invite Link:</b>\n\
+    <code>{link}</code>\n\n\
+    <i>Share this link with others to earn commission on their purchases.</i>",
+    "🔗 <b>Реферальная программа (автор)</b>\n\n\
+    <b>Ваш реферальный код:</b> <code>{code}</code>\n\n\
+    <b>📎 Ссылка-приглашение:</b>\n\
+    <code>{link}</code>\n\n\
+    <i>Делитесь ссылкой и получайте комиссию с покупок приглашённых.</i>";
+  RefUser =>
+    "🔗 <b>Referral Program</b>\n\n\
+    <b>Your user ID:</b> <code>{code}</code>\n\n\
+    <b>📎 Invite Link:</b>\n\
+    <code>{link}</code>\n\n\
+    <b>💡 Invite Friends & Earn!</b>\n\
+    Share your invite link with friends. When they click and start the bot:\n\
+    • Your referral code is applied automatically\n\
+    • You receive <b>{commission}%</b> of their purchase as bonus balance\n\
+    • This bonus can be used to buy new licenses\n\n\
+    <b>Manual method:</b>\n\
+    Friends can also use <code>/ref {code}</code> to set you as their referrer.\n\n\
+    <i>Note: Only creators can have custom referral codes. Contact support to become a creator.</i>",
+    "🔗 <b>Реферальная программа</b>\n\n\
+    <b>Ваш User ID:</b> <code>{code}</code>\n\n\
+    <b>📎 Ссылка-приглашение:</b>\n\
+    <code>{link}</code>\n\n\
+    <b>💡 Приглашайте друзей и зарабатывайте!</b>\n\
+    Поделитесь ссылкой с друзьями. Когда они запустят бота по ней:\n\
+    • ваш реферальный код применяется автоматически\n\
+    • вы получаете <b>{commission}%</b> от их покупок на бонусный баланс\n\
+    • бонусом можно оплачивать новые лицензии\n\n\
+    <b>Вручную:</b>\n\
+    Друзья могут указать вас командой <code>/ref {code}</code>.\n\n\
+    <i>Собственные коды бывают только у авторов. Чтобы стать автором, напишите в поддержку.</i>";
+  MyReferralsButton => "👥 My Referrals", "👥 Мои рефералы";
+  PromoMaterialsButton => "📦 Promo Materials", "📦 Промоматериалы";
+  BackToReferral => "« Back to Referral Info", "« К реферальной программе";
+  PromoCreatorsOnly =>
+    "❌ Promo materials are available to creators only.",
+    "❌ Промоматериалы доступны только авторам.";
+  PromoKit =>
+    "📦 <b>Promo Materials</b>\n\n\
+    <b>📎 Your invite link:</b>\n\
+    <code>{link}</code>\n",
+    "📦 <b>Промоматериалы</b>\n\n\
+    <b>📎 Ваша ссылка-приглашение:</b>\n\
+    <code>{link}</code>\n";
+  PromoTemplates =>
+    "\n<b>📝 Ready-made posts</b> <i>(tap to copy)</i>\n",
+    "\n<b>📝 Готовые посты</b> <i>(нажмите, чтобы скопировать)</i>\n";
+  PromoNoBanners =>
+    "\n<i>No banners yet, ask an admin to upload some.</i>",
+    "\n<i>Баннеров пока нет, попросите администратора загрузить.</i>";
+  PromoQrButton => "🔳 QR Code", "🔳 QR-код";
+  QrTooLong =>
+    "❌ Invite link is too long for a QR code.",
+    "❌ Ссылка слишком длинная для QR-кода.";
+  QrCaption =>
+    "🔳 Your invite QR code\n<code>{link}</code>",
+    "🔳 Ваш QR-код приглашения\n<code>{link}</code>";
+  BannerRemoved => "❌ This banner was removed.", "❌ Этот баннер удалён.";
+  BannerUnavailable =>
+    "❌ This banner is not available here.",
+    "❌ Этот баннер здесь недоступен.";
+  ReferralsCreatorsOnly =>
+    "❌ Only creators can view their referrals list.",
+    "❌ Список рефералов доступен только авторам.";
+  ReferralsEmpty =>
+    "👥 <b>My Referrals</b>\n\n\
+    <i>You haven't referred any users yet.</i>\n\n\
+    Share your referral code or invite link to start earning commissions!",
+    "👥 <b>Мои рефералы</b>\n\n\
+    <i>Вы ещё никого не пригласили.</i>\n\n\
+    Поделитесь реферальным кодом или ссылкой, чтобы начать получать комиссию!";
+  Referrals =>
+    "👥 <b>My Referrals</b>\n\n\
+    <b>Total referred users:</b> {count}\n\n",
+    "👥 <b>Мои рефералы</b>\n\n\
+    <b>Всего приглашено:</b> {count}\n\n";
+  ReferralLine =>
+    "<b>{n}.</b> {icon} {name}\n\
+    <code>{id}</code> | Joined: {joined}\n\n",
+    "<b>{n}.</b> {icon} {name}\n\
+    <code>{id}</code> | С нами с {joined}\n\n";
+  ReferralsLegend =>
+    "\n<i>✅ = has active license, ⚪ = no active license</i>",
+    "\n<i>✅ — есть активная лицензия, ⚪ — нет активной лицензии</i>";
+
+  ApiTokenCreated =>
+    "✅ New token (shown only once, keep it private):\n<code>{token}</code>\n\n",
+    "✅ Новый токен (показывается один раз, храните его в секрете):\n<code>{token}</code>\n\n";
+  ApiTokenRevoked => "🗑 Token revoked.\n\n", "🗑 Токен отозван.\n\n";
+  ApiTokenActive =>
+    "<b>Status:</b> ✅ Active\n\
+    <b>Scope:</b> {scope}\n\
+    <b>Created:</b> {created}\n\
+    <b>Last used:</b> {used}",
+    "<b>Статус:</b> ✅ Активен\n\
+    <b>Доступ:</b> {scope}\n\
+    <b>Создан:</b> {created}\n\
+    <b>Использован:</b> {used}";
+  ApiTokenMissing =>
+    "<b>Status:</b> ❌ No token yet",
+    "<b>Статус:</b> ❌ Токена пока нет";
+  ApiAllProducts => "all products", "все продукты";
+  ApiToken =>
+    "{note}🔑 <b>API Token</b>\n\n\
+    A read-only token to fetch your stats from scripts and dashboards. \
+    It can't buy, extend or change anything.\n\n\
+    {status}\n\n\
+    <b>Endpoints:</b>\n\
+    <code>GET {base}/api/me/stats</code>\n\
+    <code>GET {base}/api/me/licenses</code>\n\
+    Pass it as <code>Authorization: Bearer TOKEN</code>.\n\n\
+    🎥 <b>OBS overlay:</b> add a Browser source with\n\
+    <code>{base}/overlay/TOKEN</code>",
+    "{note}🔑 <b>API-токен</b>\n\n\
+    Токен только для чтения: получайте статистику из скриптов и дашбордов. \
+    Он не может покупать, продлевать или что-либо менять.\n\n\
+    {status}\n\n\
+    <b>Эндпоинты:</b>\n\
+    <code>GET {base}/api/me/stats</code>\n\
+    <code>GET {base}/api/me/licenses</code>\n\
+    Передавайте его как <code>Authorization: Bearer TOKEN</code>.\n\n\
+    🎥 <b>Оверлей для OBS:</b> добавьте источник «Браузер» с адресом\n\
+    <code>{base}/overlay/TOKEN</code>";
+  ApiRegenerate => "♻️ Regenerate", "♻️ Перевыпустить";
+  ApiGenerate => "➕ Generate", "➕ Создать";
+  ApiOnlyFor => "➕ Only for {product}", "➕ Только для {product}";
+  ApiRevoke => "🗑 Revoke", "🗑 Отозвать";
+
+  InstancesTitle =>
+    "🖥 <b>Instances</b>\n\n{list}",
+    "🖥 <b>Экземпляры</b>\n\n{list}";
+  InstancesEmpty =>
+    "No per-instance data yet. \
+    It appears once your panel reports with an instance id.",
+    "Данных по экземплярам пока нет. \
+    Они появятся, когда панель начнёт передавать id экземпляра.";
+  InstanceSilent => "💤 silent {duration}", "💤 молчит {duration}";
+  InstanceActive => "🟢 active", "🟢 активен";
+  InstanceStats =>
+    "Runtime: {runtime}h | FPS: {fps} | RAM: {ram} MB | Top state: {state}",
+    "Время работы: {runtime} ч | FPS: {fps} | ОЗУ: {ram} МБ | Основное состояние: {state}";
+
+  Licenses => "🔑 <b>Your Licenses:</b>\n", "🔑 <b>Ваши лицензии:</b>\n";
+  LicenseSuspended =>
+    "⏸ Suspended, works again in {duration}",
+    "⏸ Приостановлена, снова заработает через {duration}";
+  SecurityReportKey =>
+    "🛡 Security report ({key}...)",
+    "🛡 Отчёт безопасности ({key}...)";
+  SecurityReportButton => "🛡 Security report", "🛡 Отчёт безопасности";
+  SecurityReport =>
+    "🛡 <b>Security Report</b>\n\n\
+    <b>License:</b> <code>{key}</code>\n\
+    <b>Devices seen:</b> {devices}\n",
+    "🛡 <b>Отчёт безопасности</b>\n\n\
+    <b>Лицензия:</b> <code>{key}</code>\n\
+    <b>Устройств:</b> {devices}\n";
+  KeyUnused =>
+    "\n<i>This key has not been used yet.</i>\n",
+    "\n<i>Этот ключ ещё не использовался.</i>\n";
+  DeviceLine =>
+    "\n<b>{n}.</b> HWID <code>{hwid}</code>\n\
+    Network: {network}\n\
+    First seen: {first}\n\
+    Last seen: {last} ({sessions} sessions)\n",
+    "\n<b>{n}.</b> HWID <code>{hwid}</code>\n\
+    Сеть: {network}\n\
+    Впервые: {first}\n\
+    Последний раз: {last} (сессий: {sessions})\n";
+  SecurityHint =>
+    "\n<i>Don't recognize a device? Regenerate the key to revoke access.</i>",
+    "\n<i>Не узнаёте устройство? Перевыпустите ключ, чтобы закрыть доступ.</i>";
+  NotMeRegenerate =>
+    "⚠️ This wasn't me — regenerate my key",
+    "⚠️ Это не я — перевыпустить ключ";
+  SecurityAlertsOff =>
+    "🔕 Sign-in alerts are off. \
+    You can still review devices in each license's security report.",
+    "🔕 Уведомления о входах отключены. \
+    Устройства по-прежнему видны в отчёте безопасности каждой лицензии.";
+  SessionDropped =>
+    "🚫 <b>Session dropped</b>\n\n\
+    Disconnected {count} session(s) of that machine.\n\n\
+    Whoever used it still knows your key. \
+    Regenerate it to lock them out for good.",
+    "🚫 <b>Сессия сброшена</b>\n\n\
+    Отключено сессий этой машины: {count}.\n\n\
+    Тот, кто ей пользовался, всё ещё знает ваш ключ. \
+    Перевыпустите его, чтобы закрыть доступ навсегда.";
+  RegenerateMyKey => "🔄 Regenerate my key", "🔄 Перевыпустить ключ";
+  RegenerateKey =>
+    "⚠️ <b>Regenerate License Key</b>\n\n\
+    The key <code>{key}</code> will stop working immediately and every \
+    machine using it will be disconnected.\n\n\
+    You will get a new key with the same expiry. Continue?",
+    "⚠️ <b>Перевыпуск ключа</b>\n\n\
+    Ключ <code>{key}</code> сразу перестанет работать, а все машины, \
+    которые его используют, будут отключены.\n\n\
+    Вы получите новый ключ с тем же сроком действия. Продолжить?";
+  RegenerateYes => "✅ Yes, regenerate", "✅ Да, перевыпустить";
+  KeyRegenerated =>
+    "✅ <b>Key Regenerated</b>\n\n\
+    Your new license key:\n<code>{key}</code>\n\n\
+    The old key has been revoked. Update it in the panel on \
+    your own machines.",
+    "✅ <b>Ключ перевыпущен</b>\n\n\
+    Ваш новый ключ:\n<code>{key}</code>\n\n\
+    Старый ключ отозван. Обновите его в панели \
+    на своих машинах.";
+  LinkLicense =>
+    "🔑 <b>Link Your License</b>\n\n\
+    If you already have a license key, you can link it to your account.\n\n\
+    <b>To link a license:</b>\n\
+    Send the command: <code>/link YOUR_LICENSE_KEY</code>\n\n\
+    <b>Your User ID:</b> <code>{id}</code>\n\n\
+    <i>Note: When purchasing, you can provide a referrer's user ID to get a discount!</i>",
+    "🔑 <b>Привязка лицензии</b>\n\n\
+    Если у вас уже есть ключ, его можно привязать к аккаунту.\n\n\
+    <b>Чтобы привязать лицензию,</b>\n\
+    отправьте команду: <code>/link ВАШ_КЛЮЧ</code>\n\n\
+    <b>Ваш User ID:</b> <code>{id}</code>\n\n\
+    <i>При покупке можно указать User ID реферала и получить скидку!</i>";
+  Linked =>
+    "✅ License <code>{key}</code> has been linked to your account!",
+    "✅ Лицензия <code>{key}</code> привязана к вашему аккаунту!";
+  TrialClaimed =>
+    "🎉 <b>Success!</b>\n\n\
+    Here is your FREE week license:\n\
+    <code>{key}</code>\n\n\
+    Download the software using the Download button!",
+    "🎉 <b>Готово!</b>\n\n\
+    Ваша БЕСПЛАТНАЯ лицензия на неделю:\n\
+    <code>{key}</code>\n\n\
+    Скачайте программу кнопкой «Скачать панель»!";
+
+  TransferUsage =>
+    "Usage: /transfer &lt;key&gt; &lt;user_id&gt;\n\
+    The recipient finds their User ID in 👤 My Profile.",
+    "Использование: /transfer &lt;ключ&gt; &lt;user_id&gt;\n\
+    Получатель найдёт свой User ID в разделе 👤 Мой профиль.";
+  TransferPrompt =>
+    "⚠️ <b>Transfer License</b>\n\n\
+    The {type} key <code>{key}</code>, valid until {expires}, will move to user \
+    <code>{to}</code>. Every machine using it is disconnected and \
+    auto-renewal is turned off.{review}\n\n\
+    You can't take it back afterwards. Continue?",
+    "⚠️ <b>Передача лицензии</b>\n\n\
+    Ключ {type} <code>{key}</code>, действующий до {expires}, перейдёт \
+    пользователю <code>{to}</code>. Все машины с ним будут отключены, \
+    автопродление выключится.{review}\n\n\
+    Вернуть его потом не получится. Продолжить?";
+  TransferNeedsReview =>
+    "\n\nAn admin has to approve the transfer first.",
+    "\n\nСначала передачу должен одобрить администратор.";
+  TransferYes => "✅ Yes, transfer", "✅ Да, передать";
+  Transferred =>
+    "✅ <b>License Transferred</b>\n\n\
+    <code>{key}</code> now belongs to user <code>{to}</code>.",
+    "✅ <b>Лицензия передана</b>\n\n\
+    <code>{key}</code> теперь принадлежит пользователю <code>{to}</code>.";
+  TransferUnderReview =>
+    "⏳ <b>Under review</b>\n\n\
+    An admin has to approve this transfer. You get a message as soon as \
+    it is decided, the key keeps working until then.",
+    "⏳ <b>На проверке</b>\n\n\
+    Передачу должен одобрить администратор. Мы напишем, как только \
+    решение будет принято, а до тех пор ключ продолжает работать.";
+  LicenseReceived =>
+    "🎁 <b>License Received</b>\n\n\
+    User <code>{from}</code> transferred the {type} key <code>{key}</code> to you. \
+    It is valid until {expires}.",
+    "🎁 <b>Вы получили лицензию</b>\n\n\
+    Пользователь <code>{from}</code> передал вам ключ {type} <code>{key}</code>. \
+    Он действует до {expires}.";
+
+  Terms =>
+    "📜 <b>Terms of Service</b> (v{version})\n\n{text}\n\n\
+    <i>Please accept the terms to buy or extend licenses.</i>",
+    "📜 <b>Условия использования</b> (v{version})\n\n{text}\n\n\
+    <i>Примите условия, чтобы покупать и продлевать лицензии.</i>";
+  TermsAcceptButton => "✅ I accept", "✅ Принимаю";
+  TermsAccepted =>
+    "✅ <b>Terms accepted</b>\n\nThanks! You can buy and extend licenses now.",
+    "✅ <b>Условия приняты</b>\n\nСпасибо! Теперь можно покупать и продлевать лицензии.";
+
+  RefundNothing => "nothing to refund", "возвращать нечего";
+  RefundManual => "refunded by support", "вернёт поддержка";
+  RefundForfeit => "forfeited", "сгорит";
+  DeletePrompt =>
+    "⚠️ <b>Delete your account?</b>\n\n\
+    After a {days}-day cooling-off period:\n\
+    • {active} active license(s) will be revoked\n\
+    • stats, devices, tokens and settings will be erased\n\
+    • balance {balance}: {refund}\n\n\
+    You can cancel any time before that.",
+    "⚠️ <b>Удалить аккаунт?</b>\n\n\
+    Через {days} дн. ожидания:\n\
+    • будут отозваны активные лицензии: {active}\n\
+    • статистика, устройства, токены и настройки будут удалены\n\
+    • баланс {balance}: {refund}\n\n\
+    До этого момента удаление можно отменить.";
+  DeleteYes => "🗑 Yes, delete my account", "🗑 Да, удалить аккаунт";
+  DeleteKeep => "« Keep it", "« Оставить";
+  DeletionScheduled =>
+    "🗑 <b>Account deletion scheduled</b>\n\n\
+    Your data will be erased on <b>{date}</b> UTC and {licenses} license(s) \
+    revoked. Panels stop working at that moment.\n\n\
+    Changed your mind? Cancel below or with /delete_account.",
+    "🗑 <b>Удаление аккаунта запланировано</b>\n\n\
+    Данные будут удалены <b>{date}</b> UTC, лицензий будет отозвано: {licenses}. \
+    В этот момент панели перестанут работать.\n\n\
+    Передумали? Отмените кнопкой ниже или командой /delete_account.";
+  DeletionCancel => "↩️ Cancel deletion", "↩️ Отменить удаление";
+  DeletionCancelled =>
+    "✅ <b>Deletion cancelled</b>\n\nYour account stays.",
+    "✅ <b>Удаление отменено</b>\n\nВаш аккаунт сохранён.";
+  DeletionNotPending =>
+    "Nothing to cancel, your account is not being deleted.",
+    "Отменять нечего, ваш аккаунт не удаляется.";
+  DeletionCancelFailed =>
+    "❌ Failed to cancel, please try again.",
+    "❌ Не удалось отменить, попробуйте ещё раз.";
+
+  InboxEmpty =>
+    "📬 <b>Inbox</b>\n\nNothing here yet.",
+    "📬 <b>Входящие</b>\n\nЗдесь пока пусто.";
+  Inbox =>
+    "📬 <b>Inbox</b>\n\n\
+    Releases, maintenance notices and offers you may have missed.\n\
+    <b>Unread:</b> {unread}",
+    "📬 <b>Входящие</b>\n\n\
+    Релизы, уведомления о работах и предложения, которые вы могли пропустить.\n\
+    <b>Непрочитанных:</b> {unread}";
+  InboxReadAll => "✅ Mark all as read", "✅ Отметить всё прочитанным";
+  BackToInbox => "« Inbox", "« Входящие";
+
+  NoBuilds =>
+    "❌ No builds available yet. Contact support.",
+    "❌ Сборок пока нет. Напишите в поддержку.";
+  BuildLatest => " (latest)", " (последняя)";
+  SelectVersion =>
+    "📥 <b>Select Version</b>\n\n\
+    Choose which version to download:",
+    "📥 <b>Выбор версии</b>\n\n\
+    Выберите версию для загрузки:";
+  ChoosePlatform =>
+    "📥 <b>v{version}</b>\n\nChoose your platform:",
+    "📥 <b>v{version}</b>\n\nВыберите платформу:";
+  NoPlatformBuild =>
+    "❌ Build not available for this platform.",
+    "❌ Для этой платформы сборки нет.";
+  LinkFailed =>
+    "❌ Failed to create a link: {error}",
+    "❌ Не удалось создать ссылку: {error}";
+  DownloadLink =>
+    "<b>{name} v{version}</b> ({platform})\n\n\
+    {changelog}\n\n\
+    📥 <a href=\"{url}\">Click here to download</a>\n\n\
+    {checksum}\
+    <i>⚠️ The link works once and expires in 10 minutes</i>",
+    "<b>{name} v{version}</b> ({platform})\n\n\
+    {changelog}\n\n\
+    📥 <a href=\"{url}\">Нажмите, чтобы скачать</a>\n\n\
+    {checksum}\
+    <i>⚠️ Ссылка одноразовая и действует 10 минут</i>";
+  BuildFileMissing =>
+    "❌ Build file not found. Contact support.",
+    "❌ Файл сборки не найден. Напишите в поддержку.";
+  BuildUnavailable =>
+    "❌ Build not available. Contact support.",
+    "❌ Сборка недоступна. Напишите в поддержку.";
+
+  PercentOff => "({percent}% off)", "(скидка {percent}%)";
+  ExtendLicenseButton => "🔄 Extend License", "🔄 Продлить лицензию";
+  SetRefButton => "🔗 Set Referral Code", "🔗 Указать реферальный код";
+  ManualButton => "👤 Manual", "👤 Вручную";
+  LinkKeyButton => "🔑 Link Key", "🔑 Привязать ключ";
+  PickProduct =>
+    "💳 <b>Buy License</b>\n\nWhich product do you need a key for?",
+    "💳 <b>Покупка лицензии</b>\n\nДля какого продукта нужен ключ?";
+  NoPlansOnSale =>
+    "💳 <b>Buy License</b>\n\nNo plans are on sale right now.",
+    "💳 <b>Покупка лицензии</b>\n\nСейчас нет тарифов в продаже.";
+  BuyHeader =>
+    "💳 <b>Buy License{title}</b>\n\n<b>Your Balance:</b> {balance}\n\n",
+    "💳 <b>Покупка лицензии{title}</b>\n\n<b>Ваш баланс:</b> {balance}\n\n";
+  TryFirst =>
+    "<b>🧪 Try it first:</b>\n• {plan}: {price}\n\n",
+    "<b>🧪 Сначала попробуйте:</b>\n• {plan}: {price}\n\n";
+  Pricing => "<b>Pricing:</b>\n", "<b>Цены:</b>\n";
+  NightHours =>
+    "<i>  Works only from {from}:00 to {to}:00 your time, set it with /timezone</i>\n",
+    "<i>  Работает только с {from}:00 до {to}:00 по вашему времени, см. /timezone</i>\n";
+  ReferralDiscount =>
+    "\n<i>🎉 Discount from referral code <code>{code}</code></i>\n",
+    "\n<i>🎉 Скидка по реферальному коду <code>{code}</code></i>\n";
+  StarterOffer =>
+    "\n<i>🚀 Your starter offer: extra {percent}% off the first paid plan</i>\n",
+    "\n<i>🚀 Ваше стартовое предложение: ещё {percent}% скидки на первый платный тариф</i>\n";
+  RegionalPrice =>
+    "\n<i>🌍 Regional price for {country}, such keys only work from {country}</i>\n",
+    "\n<i>🌍 Региональная цена для {country}, такие ключи работают только из {country}</i>\n";
+  SelectPlan =>
+    "\n<i>Select a plan to purchase with your balance:</i>",
+    "\n<i>Выберите тариф для покупки с баланса:</i>";
+  NeedMore =>
+    "\n<i>💡 You need {amount} more to buy a license.</i>",
+    "\n<i>💡 Для покупки лицензии не хватает {amount}.</i>";
+  NeedMoreTrial =>
+    "\n<i>💡 You need {amount} more to buy a trial license.</i>",
+    "\n<i>💡 Для покупки пробной лицензии не хватает {amount}.</i>";
+  SetRefTip =>
+    "\n\n<i>💡 Tip: Set a referral code to get a discount on monthly plans!</i>",
+    "\n\n<i>💡 Совет: укажите реферальный код и получите скидку на месячные тарифы!</i>";
+  InsufficientPurchase =>
+    "❌ <b>Insufficient Balance</b>\n\n\
+    <b>Required:</b> {price}\n\
+    <b>Your balance:</b> {balance}\n\
+    <b>Needed:</b> {needed}\n\n\
+    <i>Add funds to your balance to purchase this plan.</i>",
+    "❌ <b>Недостаточно средств</b>\n\n\
+    <b>Стоимость:</b> {price}\n\
+    <b>Ваш баланс:</b> {balance}\n\
+    <b>Не хватает:</b> {needed}\n\n\
+    <i>Пополните баланс, чтобы купить этот тариф.</i>";
+  InsufficientExtension =>
+    "❌ <b>Insufficient Balance</b>\n\n\
+    <b>Required:</b> {price}\n\
+    <b>Your balance:</b> {balance}\n\
+    <b>Needed:</b> {needed}\n\n\
+    <i>Add funds to your balance to extend this license.</i>",
+    "❌ <b>Недостаточно средств</b>\n\n\
+    <b>Стоимость:</b> {price}\n\
+    <b>Ваш баланс:</b> {balance}\n\
+    <b>Не хватает:</b> {needed}\n\n\
+    <i>Пополните баланс, чтобы продлить эту лицензию.</i>";
+  NoteSessions =>
+    "<b>Sessions:</b> up to {count} at once\n",
+    "<b>Сессии:</b> до {count} одновременно\n";
+  NoteProduct => "<b>Product:</b> {name}\n", "<b>Продукт:</b> {name}\n";
+  NoteSchedule =>
+    "<b>Works:</b> {from}:00-{to}:00 your time (/timezone)\n",
+    "<b>Работает:</b> {from}:00-{to}:00 по вашему времени (/timezone)\n";
+  NoteRegion => "<b>Region:</b> {region}\n", "<b>Регион:</b> {region}\n";
+  Purchased =>
+    "✅ <b>Purchase Successful!</b>\n\n\
+    <b>Plan:</b> {plan}\n\
+    <b>License Key:</b> <code>{key}</code>\n\
+    <b>Expires:</b> {expires}\n\
+    {notes}\n\
+    <b>New Balance:</b> {balance}\n\n\
+    <i>You can now download the panel!</i>",
+    "✅ <b>Покупка прошла успешно!</b>\n\n\
+    <b>Тариф:</b> {plan}\n\
+    <b>Ключ:</b> <code>{key}</code>\n\
+    <b>Действует до:</b> {expires}\n\
+    {notes}\n\
+    <b>Новый баланс:</b> {balance}\n\n\
+    <i>Теперь можно скачать панель!</i>";
+  LicenseCreateFailed =>
+    "❌ Failed to create license: {error}",
+    "❌ Не удалось создать лицензию: {error}";
+
+  ExtendHeader =>
+    "🔄 <b>Extend License</b>\n\n\
+    <b>Your Balance:</b> {balance}\n\n\
+    <b>Select a license to extend:</b>\n",
+    "🔄 <b>Продление лицензии</b>\n\n\
+    <b>Ваш баланс:</b> {balance}\n\n\
+    <b>Выберите лицензию для продления:</b>\n";
+  NoLicensesToExtend =>
+    "❌ <b>No Licenses Found</b>\n\n\
+    You don't have any licenses to extend.\n\
+    Purchase a new license first.",
+    "❌ <b>Лицензий нет</b>\n\n\
+    Вам нечего продлевать.\n\
+    Сначала купите лицензию.";
+  ExtendKey =>
+    "🔄 <b>Extend License</b>\n\n\
+    <b>License:</b> <code>{key}</code>\n\
+    <b>Status:</b> {status}\n\
+    <b>Expires:</b> {expires}\n\n\
+    <b>Your Balance:</b> {balance}\n\n\
+    <b>Extension Pricing:</b>\n",
+    "🔄 <b>Продление лицензии</b>\n\n\
+    <b>Лицензия:</b> <code>{key}</code>\n\
+    <b>Статус:</b> {status}\n\
+    <b>Действует до:</b> {expires}\n\n\
+    <b>Ваш баланс:</b> {balance}\n\n\
+    <b>Цены продления:</b>\n";
+  BonusTiers => "\n<b>🎁 Bonus tiers:</b>\n", "\n<b>🎁 Бонусные уровни:</b>\n";
+  BonusTier =>
+    "• {months}+ months: {percent}% off\n",
+    "• от {months} мес.: скидка {percent}%\n";
+  SaleLine =>
+    "\n⚡ <b>{name}:</b> extra {percent}% off for {duration}\n",
+    "\n⚡ <b>{name}:</b> ещё {percent}% скидки, осталось {duration}\n";
+  NeedMoreExtend =>
+    "\n<i>💡 You need {amount} more to extend this license.</i>",
+    "\n<i>💡 Для продления не хватает {amount}.</i>";
+  AutoRenewing =>
+    "\n🔁 <b>Auto-renewal is on:</b> +{plan} is charged from your balance \
+    a day before expiry.\n",
+    "\n🔁 <b>Автопродление включено:</b> +{plan} списывается с баланса \
+    за день до окончания.\n";
+  AutoRenewOn => "🔁 Auto-renew: On", "🔁 Автопродление: вкл";
+  AutoRenewOff => "🔁 Auto-renew: Off", "🔁 Автопродление: выкл";
+  Extended =>
+    "✅ <b>License Extended!</b>\n\n\
+    <b>License:</b> <code>{key}</code>\n\
+    <b>Added:</b> {plan}\n\
+    <b>New Expiry:</b> {expires}\n\n\
+    <b>New Balance:</b> {balance}",
+    "✅ <b>Лицензия продлена!</b>\n\n\
+    <b>Лицензия:</b> <code>{key}</code>\n\
+    <b>Добавлено:</b> {plan}\n\
+    <b>Действует до:</b> {expires}\n\n\
+    <b>Новый баланс:</b> {balance}";
+  ExtendFailed =>
+    "❌ Failed to extend license: {error}",
+    "❌ Не удалось продлить лицензию: {error}";
+
+  CustomAmount =>
+    "💵 <b>Custom Amount</b>\n\n\
+    To add a custom amount to your balance, use the command:\n\n\
+    <code>/fund AMOUNT</code>\n\n\
+    Examples:\n\
+    • <code>/fund 5</code> - Add 5 USDT\n\
+    • <code>/fund 15.5</code> - Add 15.5 USDT\n\n\
+    <i>Minimum deposit: 1 USDT</i>",
+    "💵 <b>Своя сумма</b>\n\n\
+    Чтобы пополнить баланс на произвольную сумму, используйте команду:\n\n\
+    <code>/fund СУММА</code>\n\n\
+    Примеры:\n\
+    • <code>/fund 5</code> — пополнить на 5 USDT\n\
+    • <code>/fund 15.5</code> — пополнить на 15.5 USDT\n\n\
+    <i>Минимальное пополнение: 1 USDT</i>";
+  CustomAmountButton => "💵 Custom Amount", "💵 Своя сумма";
+  ManualPurchase =>
+    "👤 <b>Manual Purchase</b>\n\n\
+    To purchase a license via USDT or other methods, please contact our support:\n\n\
+    👉 @{support}\n\n\
+    <i>Send a message with \"I want to buy license\"</i>",
+    "👤 <b>Покупка вручную</b>\n\n\
+    Чтобы купить лицензию за USDT или другим способом, напишите в поддержку:\n\n\
+    👉 @{support}\n\n\
+    <i>Отправьте сообщение «Хочу купить лицензию»</i>";
+  OpenSupportChat => "Open Chat with Support", "Открыть чат с поддержкой";
+  AddFunds =>
+    "💵 <b>Add Funds</b>\n\n\
+    <b>Your Balance:</b> {balance}\n\n\
+    <b>Quick amounts:</b>\n\
+    • {month} USDT (1 month license)\n\
+    • {quarter} USDT (3 month license)\n",
+    "💵 <b>Пополнение баланса</b>\n\n\
+    <b>Ваш баланс:</b> {balance}\n\n\
+    <b>Быстрые суммы:</b>\n\
+    • {month} USDT (лицензия на 1 месяц)\n\
+    • {quarter} USDT (лицензия на 3 месяца)\n";
+  ReferralDiscountAvailable =>
+    "\n<i>🎉 {percent}% discount available from referral!</i>\n",
+    "\n<i>🎉 Доступна скидка {percent}% по реферальному коду!</i>\n";
+  PendingPayments =>
+    "\n<i>⏳ You have {count} pending payment(s).</i>\n",
+    "\n<i>⏳ Ожидают оплаты: {count}.</i>\n";
+  SelectAmount =>
+    "\n<i>Select an amount or use /fund AMOUNT for custom amounts.</i>",
+    "\n<i>Выберите сумму или укажите свою: /fund СУММА.</i>";
+  CryptoDown =>
+    "\n⚠️ <b>Crypto payments are temporarily down.</b>\n\
+    <i>Contact support to top up manually. Invoices you already paid \
+    are still credited.</i>",
+    "\n⚠️ <b>Криптоплатежи временно недоступны.</b>\n\
+    <i>Напишите в поддержку, чтобы пополнить баланс вручную. Уже оплаченные \
+    счета всё равно будут зачислены.</i>";
+  PaymentsConfiguring =>
+    "\n<i>⚠️ Automatic payments are being configured.\nContact support for manual deposits.</i>",
+    "\n<i>⚠️ Автоматические платежи настраиваются.\nДля пополнения вручную напишите в поддержку.</i>";
+  CryptoNotConfigured =>
+    "❌ CryptoBot payments are not configured. Contact support.",
+    "❌ Оплата через CryptoBot не настроена. Напишите в поддержку.";
+  PaymentsNotConfigured =>
+    "❌ Payment system is not configured. Contact support.",
+    "❌ Платёжная система не настроена. Напишите в поддержку.";
+  InvoiceCreated =>
+    "💳 <b>Payment Invoice Created</b>\n\n\
+    <b>Amount:</b> {amount} USDT\n\n\
+    Click the button below to pay via CryptoBot.\n\
+    The invoice expires in 1 hour.\n\n\
+    <i>Your balance is updated automatically after payment. \
+    If it isn't, click \"Check Payments\".</i>",
+    "💳 <b>Счёт создан</b>\n\n\
+    <b>Сумма:</b> {amount} USDT\n\n\
+    Нажмите кнопку ниже, чтобы оплатить через CryptoBot.\n\
+    Счёт действует 1 час.\n\n\
+    <i>Баланс обновится автоматически после оплаты. \
+    Если этого не произошло, нажмите «Проверить оплату».</i>";
+  PayNow => "💵 Pay Now", "💵 Оплатить";
+  InvoiceFailed =>
+    "❌ Failed to create invoice: {error}",
+    "❌ Не удалось создать счёт: {error}";
+  TryAgainOrSupport =>
+    "\n\nPlease try again or contact support.",
+    "\n\nПопробуйте ещё раз или напишите в поддержку.";
+  FundUsage =>
+    "Usage: /fund AMOUNT\nExample: /fund 10.5",
+    "Использование: /fund СУММА\nПример: /fund 10.5";
+  FundInvalid =>
+    "❌ Invalid amount. Use: /fund AMOUNT\nExample: /fund 10.5",
+    "❌ Неверная сумма. Используйте: /fund СУММА\nПример: /fund 10.5";
+  FundMinimum => "❌ Minimum deposit is 1 USDT.", "❌ Минимальное пополнение — 1 USDT.";
+  FundInvoice =>
+    "💵 <b>Payment Invoice Created</b>\n\n\
+    <b>Amount:</b> {amount} USDT\n\n\
+    <a href=\"{url}\">Click here to pay via CryptoBot</a>\n\n\
+    <i>After payment, use /start and click \"Check Payments\".</i>",
+    "💵 <b>Счёт создан</b>\n\n\
+    <b>Сумма:</b> {amount} USDT\n\n\
+    <a href=\"{url}\">Оплатить через CryptoBot</a>\n\n\
+    <i>После оплаты откройте /start и нажмите «Проверить оплату».</i>";
+  VerificationNotConfigured =>
+    "❌ Payment verification is not configured.",
+    "❌ Проверка платежей не настроена.";
+  ChecksPaused =>
+    "⏸ <b>Payment checks are paused</b>\n\n\
+    CryptoBot is not responding right now. Your pending invoices are \
+    safe, check them again in a few minutes.",
+    "⏸ <b>Проверка платежей приостановлена</b>\n\n\
+    CryptoBot сейчас не отвечает. Ваши счета в безопасности, \
+    проверьте их снова через несколько минут.";
+  PaymentReceived =>
+    "✅ <b>Payment Received!</b>\n\n\
+    <b>{amount}</b> has been added to your balance.\n\n\
+    <i>Use your balance to purchase licenses in the Buy menu.</i>",
+    "✅ <b>Оплата получена!</b>\n\n\
+    <b>{amount}</b> зачислено на баланс.\n\n\
+    <i>Покупайте лицензии с баланса в разделе «Купить лицензию».</i>";
+  NoPendingPayments =>
+    "📭 <b>No Pending Payments</b>\n\n\
+    You have no pending invoices.\n\
+    Create a new invoice to add funds to your balance.",
+    "📭 <b>Нет ожидающих платежей</b>\n\n\
+    У вас нет неоплаченных счетов.\n\
+    Создайте новый счёт, чтобы пополнить баланс.";
+  WaitingPayment =>
+    "⏳ <b>Waiting for Payment</b>\n\n\
+    You have {count} pending invoice(s).\n\
+    Complete the payment in CryptoBot, then click \"Check Payments\" again.\n\n\
+    <i>Invoices expire after 1 hour.</i>",
+    "⏳ <b>Ожидание оплаты</b>\n\n\
+    Неоплаченных счетов: {count}.\n\
+    Завершите оплату в CryptoBot и снова нажмите «Проверить оплату».\n\n\
+    <i>Счета действуют 1 час.</i>";
+  CheckFailed =>
+    "❌ Failed to check payments: {error}\n\n\
+    Please try again later.",
+    "❌ Не удалось проверить платежи: {error}\n\n\
+    Попробуйте позже.";
+  TryAgain => "🔄 Try Again", "🔄 Повторить";
+
+  PromoUsage => "Usage: /promo &lt;code&gt;", "Использование: /promo &lt;код&gt;";
+  PromoRedeemed =>
+    "🎉 <b>Promo redeemed!</b>\n\n\
+    Your {type} license, valid until {expires}:\n<code>{key}</code>",
+    "🎉 <b>Промокод активирован!</b>\n\n\
+    Ваша лицензия {type}, действует до {expires}:\n<code>{key}</code>";
+  RedeemUsage => "Usage: /redeem &lt;code&gt;", "Использование: /redeem &lt;код&gt;";
+  RedeemedBalance =>
+    "🎉 <b>Code redeemed!</b>\n\n\
+    {amount} added to your balance, now {balance}.",
+    "🎉 <b>Код активирован!</b>\n\n\
+    На баланс зачислено {amount}, теперь на нём {balance}.";
+  RedeemedLicense =>
+    "🎉 <b>Code redeemed!</b>\n\n\
+    Your trial license, valid until {expires}:\n<code>{key}</code>",
+    "🎉 <b>Код активирован!</b>\n\n\
+    Ваша пробная лицензия, действует до {expires}:\n<code>{key}</code>";
+
+  TicketStatus =>
+    "🎫 Your ticket #{id} is <b>{status}</b>.\n\n\
+    Add details with <code>/ticket your message</code>",
+    "🎫 Ваше обращение #{id}: <b>{status}</b>.\n\n\
+    Дополните его: <code>/ticket ваше сообщение</code>";
+  TicketHelp =>
+    "🎫 <b>Contact Support</b>\n\n\
+    Describe your problem: <code>/ticket your message</code>\n\n\
+    <i>Tip: many answers are already in /faq</i>",
+    "🎫 <b>Связь с поддержкой</b>\n\n\
+    Опишите проблему: <code>/ticket ваше сообщение</code>\n\n\
+    <i>Совет: многие ответы уже есть в /faq</i>";
+  TicketSent =>
+    "✅ Sent to support (ticket #{id}). We'll reply here.",
+    "✅ Отправлено в поддержку (обращение #{id}). Ответим здесь.";
+
+  Saved => "✅ Saved.", "✅ Сохранено.";
+  DowntimeOff =>
+    "🔕 <b>Downtime alerts are off</b>\n\n\
+    Get a message when all your panels stop reporting:\n\
+    <code>/downtime 15</code> - after 15 minutes, any time\n\
+    <code>/downtime 30 8-23</code> - only between 08:00 and 23:00 UTC",
+    "🔕 <b>Уведомления о простое выключены</b>\n\n\
+    Получайте сообщение, когда все ваши панели перестают отвечать:\n\
+    <code>/downtime 15</code> — через 15 минут, в любое время\n\
+    <code>/downtime 30 8-23</code> — только с 08:00 до 23:00 UTC";
+  DowntimeAllDay => "all day", "весь день";
+  DowntimeOn =>
+    "🔔 <b>Downtime alerts are on</b>\n\n\
+    Threshold: {mins} min\n\
+    Farming hours: {hours}\n\n\
+    Disable with <code>/downtime off</code>",
+    "🔔 <b>Уведомления о простое включены</b>\n\n\
+    Порог: {mins} мин\n\
+    Часы фарма: {hours}\n\n\
+    Отключить: <code>/downtime off</code>";
+
+  TopUsage =>
+    "❌ Use /top, /top drops, /top on or /top off",
+    "❌ Используйте /top, /top drops, /top on или /top off";
+  TopJoined =>
+    "✅ You are on /top as <b>{alias}</b>",
+    "✅ Вы в /top под именем <b>{alias}</b>";
+  TopLeft => "✅ You are no longer on /top", "✅ Вас больше нет в /top";
+  TopXp =>
+    "🏆 <b>Top farmers</b> by weekly XP\n\n",
+    "🏆 <b>Лучшие фармеры</b> по XP за неделю\n\n";
+  TopDrops =>
+    "🏆 <b>Top farmers</b> by drops\n\n",
+    "🏆 <b>Лучшие фармеры</b> по дропам\n\n";
+  UnitXp => "XP", "XP";
+  UnitDrops => "drops", "дроп.";
+  TopEmpty =>
+    "<i>Nobody on the board yet</i>\n",
+    "<i>В таблице пока никого нет</i>\n";
+  TopRank =>
+    "\nYou are #{rank} as <b>{alias}</b>. Leave with <code>/top off</code>",
+    "\nВы на {rank} месте как <b>{alias}</b>. Выйти: <code>/top off</code>";
+  TopJoin =>
+    "\nJoin with <code>/top on</code>, only a nickname is shown. \
+    Farm a little to get a place.",
+    "\nПрисоединяйтесь командой <code>/top on</code>, показывается только псевдоним. \
+    Пофармите немного, чтобы попасть в таблицу.";
+  TopSeeDrops =>
+    "\nAlso see <code>/top drops</code>",
+    "\nСмотрите также <code>/top drops</code>";
+  TopSeeXp =>
+    "\nAlso see <code>/top</code> for XP",
+    "\nСмотрите также <code>/top</code> для XP";
+
+  Timezone =>
+    "🕒 <b>Timezone:</b> {offset}\n\n\
+    Night plans work in your local time.\n\
+    Change it with <code>/timezone +3</code> or \
+    <code>/timezone -05:30</code>",
+    "🕒 <b>Часовой пояс:</b> {offset}\n\n\
+    Ночные тарифы работают по вашему местному времени.\n\
+    Изменить: <code>/timezone +3</code> или \
+    <code>/timezone -05:30</code>";
+  TimezoneSet => "✅ Timezone set to {offset}", "✅ Часовой пояс: {offset}";
+  Channel =>
+    "🧪 <b>Channel:</b> {channel}\n\n\
+    Beta builds arrive early but may be less stable.\n\
+    Switch with <code>/channel beta</code> or \
+    <code>/channel stable</code>",
+    "🧪 <b>Канал:</b> {channel}\n\n\
+    Бета-сборки выходят раньше, но могут быть менее стабильными.\n\
+    Переключить: <code>/channel beta</code> или \
+    <code>/channel stable</code>";
+  ChannelSet => "✅ You now get {channel} builds", "✅ Теперь вы получаете сборки {channel}";
+  ChannelUsage =>
+    "❌ Use /channel stable or /channel beta",
+    "❌ Используйте /channel stable или /channel beta";
+  Country =>
+    "🌍 <b>Country:</b> {country}\n\n\
+    Regional prices depend on it. Keys bought at a regional price \
+    only work from that country.\n\
+    Set it with <code>/country DE</code>, or \
+    <code>/country auto</code> to detect it from your panels.",
+    "🌍 <b>Страна:</b> {country}\n\n\
+    От неё зависят региональные цены. Ключи, купленные по региональной цене, \
+    работают только из этой страны.\n\
+    Указать: <code>/country DE</code>, или \
+    <code>/country auto</code>, чтобы определять её по вашим панелям.";
+  CountryAuto =>
+    "✅ Country will be detected automatically",
+    "✅ Страна будет определяться автоматически";
+  CountrySet => "✅ Country set to {country}", "✅ Страна: {country}";
+  CountryUsage =>
+    "❌ Use a two-letter country code, e.g. /country DE",
+    "❌ Укажите двухбуквенный код страны, например /country DE";
+  GoalHelp =>
+    "🎯 <b>Daily Goal</b>\n\n\
+    Set a goal and build a streak:\n\
+    <code>/goal xp 5000</code> - XP per day\n\
+    <code>/goal runtime 8</code> - farming hours per day\n\
+    <code>/goal off</code> - remove the goal\n\n\
+    Progress is shown in your profile.",
+    "🎯 <b>Цель на день</b>\n\n\
+    Поставьте цель и держите серию:\n\
+    <code>/goal xp 5000</code> — XP в день\n\
+    <code>/goal runtime 8</code> — часов фарма в день\n\
+    <code>/goal off</code> — убрать цель\n\n\
+    Прогресс виден в профиле.";
+  GoalRemoved => "✅ Daily goal removed.", "✅ Цель на день убрана.";
+  NoGoal => "You have no daily goal.", "У вас нет цели на день.";
+  GoalSet =>
+    "✅ Daily goal set: {goal}. Good luck!",
+    "✅ Цель на день: {goal}. Удачи!";
+  GoalUsage =>
+    "❌ Usage: /goal &lt;xp|runtime&gt; &lt;amount&gt;",
+    "❌ Использование: /goal &lt;xp|runtime&gt; &lt;количество&gt;";
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  /// Placeholders in order of appearance
+  fn placeholders(text: &str) -> Vec<&str> {
+    text
+      .split('{')
+      .skip(1)
+      .filter_map(|part| part.split_once('}').map(|(name, _)| name))
+      .collect()
+  }
+
+  #[test]
+  fn test_translations_match() {
+    for &text in T::ALL {
+      let en = text.get(Lang::En);
+      let ru = text.get(Lang::Ru);
+      assert!(!en.is_empty() && !ru.is_empty(), "{:?} is empty", text);
+
+      let (mut en_vars, mut ru_vars) = (placeholders(en), placeholders(ru));
+      en_vars.sort();
+      ru_vars.sort();
+      assert_eq!(en_vars, ru_vars, "{:?} placeholders differ", text);
+    }
+
+    assert_eq!(Lang::parse("ru-RU"), Some(Lang::Ru));
+    assert_eq!(Lang::parse("EN"), Some(Lang::En));
+    assert_eq!(Lang::parse("de"), None);
+  }
+}
@@ -0,0 +1,82 @@
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+use super::i18n::{self, Lang, T};
+use crate::sv::stats::Board;
+
+/// Places listed on /top
+pub const SHOWN: u64 = 10;
+
+const ADJECTIVES: &[&str] = &[
+  "Swift", "Quiet", "Lucky", "Brave", "Sly", "Calm", "Bold", "Keen", "Wild",
+  "Sunny", "Frosty", "Stormy", "Misty", "Rusty", "Silent", "Golden",
+];
+const ANIMALS: &[&str] = &[
+  "Fox", "Owl", "Lynx", "Wolf", "Otter", "Hawk", "Bear", "Raven", "Badger",
+  "Heron", "Moose", "Falcon", "Hare", "Seal", "Crab", "Yak",
+];
+
+/// Stable nickname of a user on /top. Keyed by the server secret so the
+/// Telegram id can't be guessed back from it.
+pub fn alias(secret: &str, tg_user_id: i64) -> String {
+  let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes())
+    .expect("HMAC accepts keys of any size");
+  mac.update(format!("leaderboard:{}", tg_user_id).as_bytes());
+  let hash = mac.finalize().into_bytes();
+  format!(
+    "{} {} #{:02}",
+    ADJECTIVES[hash[0] as usize % ADJECTIVES.len()],
+    ANIMALS[hash[1] as usize % ANIMALS.len()],
+    hash[2] % 100
+  )
+}
+
+fn medal(place: usize) -> String {
+  match place {
+    1 => "🥇".into(),
+    2 => "🥈".into(),
+    3 => "🥉".into(),
+    _ => format!("{}.", place),
+  }
+}
+
+/// The board with the reader's own place, or how to join it
+pub fn message(
+  lang: Lang,
+  secret: &str,
+  board: Board,
+  top: &[(i64, i64)],
+  viewer: i64,
+  rank: Option<u64>,
+) -> String {
+  let (title, unit, other) = match board {
+    Board::WeeklyXp => (T::TopXp, T::UnitXp, T::TopSeeDrops),
+    Board::Drops => (T::TopDrops, T::UnitDrops, T::TopSeeXp),
+  };
+  let mut text = lang.t(title).to_string();
+  if top.is_empty() {
+    text.push_str(lang.t(T::TopEmpty));
+  }
+  for (i, &(tg_user_id, value)) in top.iter().enumerate() {
+    let name = alias(secret, tg_user_id);
+    let name =
+      if tg_user_id == viewer { format!("<b>{}</b>", name) } else { name };
+    text.push_str(&format!(
+      "{} {} · {} {}\n",
+      medal(i + 1),
+      name,
+      value,
+      lang.t(unit)
+    ));
+  }
+
+  match rank {
+    Some(rank) => text.push_str(&i18n::fill(
+      lang.t(T::TopRank),
+      &[("rank", rank.to_string()), ("alias", alias(secret, viewer))],
+    )),
+    None => text.push_str(lang.t(T::TopJoin)),
+  }
+  text.push_str(lang.t(other));
+  text
+}
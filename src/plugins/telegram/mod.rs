@@ -1,21 +1,46 @@
+mod broadcast;
 mod callback;
 mod command;
+mod config_sync;
+mod gift;
+mod i18n;
+mod leaderboard;
+mod onboarding;
+pub mod reconcile;
+mod review;
+pub mod sharing;
+pub mod support;
 
-use std::{collections::HashSet, sync::Arc};
+use std::{
+  collections::HashSet,
+  sync::{Arc, atomic::Ordering},
+};
 
+pub use callback::Callback;
 use command::{AdminCommand, Command, UserCommand};
+use futures::future::BoxFuture;
+use gift::{Gift, GiftDialogue, GiftStorage};
+use i18n::Lang;
+use onboarding::{Onboarding, OnboardingDialogue, OnboardingStorage};
 use teloxide::{
   Bot, RequestError,
   dispatching::{Dispatcher, HandlerExt, UpdateFilterExt},
   prelude::*,
   types::{
     BotCommandScope, CallbackQuery, ChatId, InlineKeyboardMarkup, InputFile,
-    Message, MessageId, ParseMode, Update,
+    LinkPreviewOptions, Message, MessageId, ParseMode, Update,
   },
   utils::command::BotCommands,
 };
 
-use crate::{prelude::*, state::AppState};
+use crate::{
+  entity::{bot_usage::UsageKind, storefront},
+  prelude::*,
+  state::{AppState, Environment, Services},
+};
+
+/// Storefront a bot sells for, None for the main bot
+pub type Brand = Option<Arc<storefront::Model>>;
 
 pub struct Plugin;
 
@@ -60,60 +85,245 @@ async fn setup_commands(bot: &Bot, admins: &HashSet<i64>) {
   );
 }
 
+/// Run the main bot and one bot per storefront on the same handlers
 pub async fn run_bot(app: Arc<AppState>) {
   info!("Starting Telegram bot...");
+  // readiness waits for the bot, so don't start dispatching blind
+  loop {
+    match app.bot.get_me().await {
+      Ok(me) => {
+        info!("Logged in as @{}", me.username());
+        app.bot_ready.store(true, Ordering::Relaxed);
+        break;
+      }
+      Err(e) => {
+        warn!("Failed to reach Telegram, retrying: {}", e);
+        time::sleep(Duration::from_secs(5)).await;
+      }
+    }
+  }
 
-  let bot = app.bot.clone();
+  let storefronts = app.sv().storefront.all().await.unwrap_or_else(|e| {
+    warn!("Failed to load storefronts: {}", e);
+    Vec::new()
+  });
 
-  // Set up command hints for users and admins
-  setup_commands(&bot, &app.admins).await;
+  let mut bots = vec![dispatch(app.clone(), app.bot.clone(), None)];
+  for storefront in storefronts {
+    info!("Starting storefront bot for {}", storefront.product);
+    let bot = Bot::new(&storefront.bot_token);
+    app.brand_bots.insert(storefront.product.clone(), bot.clone());
+    bots.push(dispatch(app.clone(), bot, Some(Arc::new(storefront))));
+  }
+  futures::future::join_all(bots).await;
+}
 
-  let handler = teloxide::dptree::entry()
-    .branch(Update::filter_message().filter_command::<Command>().endpoint({
-      let app = app.clone();
-      move |bot: Bot, msg: Message, cmd: Command| {
+/// Boxed to break the cycle with /storefront, which starts new bots
+fn dispatch(
+  app: Arc<AppState>,
+  bot: Bot,
+  brand: Brand,
+) -> BoxFuture<'static, ()> {
+  Box::pin(async move {
+    // Set up command hints for users and admins
+    setup_commands(&bot, &app.admins).await;
+
+    let handler = teloxide::dptree::entry()
+      // nothing may write while /restore swaps the database
+      .branch(
+        teloxide::dptree::filter({
+          let app = app.clone();
+          move || app.maintenance.load(Ordering::Relaxed)
+        })
+        .endpoint(|bot: Bot, update: Update| async move {
+          if let Some(chat) = update.chat() {
+            bot
+              .send_message(chat.id, Error::Maintenance.user_message())
+              .await?;
+          }
+          Ok(())
+        }),
+      )
+      .branch(Update::filter_message().filter_command::<Command>().endpoint({
         let app = app.clone();
-        let bot = ReplyBot::new(bot, msg.chat.id.0, msg.chat.id, msg.id);
-        command::handle(app, bot, cmd)
-      }
-    }))
-    .branch(Update::filter_callback_query().endpoint({
-      let app = app.clone();
-      move |bot: Bot, query: CallbackQuery| {
+        move |bot: Bot,
+              msg: Message,
+              cmd: Command,
+              storage: Arc<OnboardingStorage>,
+              brand: Brand| {
+          let app = app.clone();
+          let dialogue = OnboardingDialogue::new(storage, msg.chat.id);
+          let bot = ReplyBot::new(
+            bot,
+            msg.chat.id.0,
+            msg.chat.id,
+            msg.id,
+            brand,
+            app.config.environment,
+          );
+          let name = command_name(&msg);
+          async move {
+            let res =
+              command::handle(app.clone(), bot, msg, cmd, dialogue).await;
+            record_usage(&app, UsageKind::Command, &name, res.is_ok()).await;
+            res
+          }
+        }
+      }))
+      // free text is only expected while the wizard asks for a referral code
+      .branch(
+        Update::filter_message()
+          .enter_dialogue::<Message, OnboardingStorage, Onboarding>()
+          .branch(teloxide::dptree::case![Onboarding::Referral].endpoint({
+            let app = app.clone();
+            move |bot: Bot,
+                  msg: Message,
+                  dialogue: OnboardingDialogue,
+                  brand: Brand| {
+              let bot = ReplyBot::from_message(
+                bot,
+                &msg,
+                brand,
+                app.config.environment,
+              );
+              onboarding::receive_referral(app.clone(), bot, msg, dialogue)
+            }
+          })),
+      )
+      // or after 🎁 Redeem Gift asked for a key
+      .branch(
+        Update::filter_message()
+          .enter_dialogue::<Message, GiftStorage, Gift>()
+          .branch(teloxide::dptree::case![Gift::Key].endpoint({
+            let app = app.clone();
+            move |bot: Bot,
+                  msg: Message,
+                  dialogue: GiftDialogue,
+                  brand: Brand| {
+              let bot = ReplyBot::from_message(
+                bot,
+                &msg,
+                brand,
+                app.config.environment,
+              );
+              gift::receive_key(app.clone(), bot, msg, dialogue)
+            }
+          })),
+      )
+      .branch(
+        Update::filter_callback_query()
+          .filter(|query: CallbackQuery| {
+            query.data.is_some_and(|data| data.starts_with(onboarding::PREFIX))
+          })
+          .enter_dialogue::<CallbackQuery, OnboardingStorage, Onboarding>()
+          .endpoint({
+            let app = app.clone();
+            move |bot: Bot,
+                  query: CallbackQuery,
+                  dialogue: OnboardingDialogue,
+                  brand: Brand| {
+              onboarding::handle_callback(
+                app.clone(),
+                bot,
+                query,
+                dialogue,
+                brand,
+              )
+            }
+          }),
+      )
+      .branch(
+        Update::filter_callback_query()
+          .filter(|query: CallbackQuery| {
+            query.data.is_some_and(|data| data.starts_with(gift::PREFIX))
+          })
+          .enter_dialogue::<CallbackQuery, GiftStorage, Gift>()
+          .endpoint({
+            let app = app.clone();
+            move |bot: Bot,
+                  query: CallbackQuery,
+                  dialogue: GiftDialogue,
+                  brand: Brand| {
+              gift::handle_callback(app.clone(), bot, query, dialogue, brand)
+            }
+          }),
+      )
+      .branch(Update::filter_callback_query().endpoint({
         let app = app.clone();
-        callback_handle(app, bot, query)
-      }
-    }));
+        move |bot: Bot, query: CallbackQuery, brand: Brand| {
+          let app = app.clone();
+          callback_handle(app, bot, query, brand)
+        }
+      }));
 
-  Dispatcher::builder(bot, handler).build().dispatch().await;
+    Dispatcher::builder(bot, handler)
+      .dependencies(teloxide::dptree::deps![
+        OnboardingStorage::new(),
+        GiftStorage::new(),
+        brand
+      ])
+      .build()
+      .dispatch()
+      .await;
+  })
 }
 
 async fn callback_handle(
   app: Arc<AppState>,
   bot: Bot,
   query: CallbackQuery,
+  brand: Brand,
 ) -> ResponseResult<()> {
   if let Some(data) = query.data
     && let Some(msg) = query.message.as_ref()
   {
-    let bot =
-      ReplyBot::new(bot, query.from.id.0 as i64, msg.chat().id, msg.id());
+    let bot = ReplyBot::new(
+      bot,
+      query.from.id.0 as i64,
+      msg.chat().id,
+      msg.id(),
+      brand,
+      app.config.environment,
+    );
 
     // answer callback to remove loading state
     bot.inner.answer_callback_query(query.id.clone()).await?;
 
-    callback::handle(app, bot, &data).await
+    let name = Callback::parse(&data).ok().map(|callback| callback.name());
+    let res = callback::handle(app.clone(), bot, &data).await;
+    if let Some(name) = name {
+      record_usage(&app, UsageKind::Callback, &name, res.is_ok()).await;
+    }
+    res
   } else {
     Ok(())
   }
 }
 
+/// `/start@bot args` -> `start`
+fn command_name(msg: &Message) -> String {
+  let command = msg.text().and_then(|text| text.split_whitespace().next());
+  let command = command.unwrap_or_default().trim_start_matches('/');
+  command.split('@').next().unwrap_or_default().to_lowercase()
+}
+
+async fn record_usage(app: &AppState, kind: UsageKind, name: &str, ok: bool) {
+  if let Err(e) = app.sv().bot_usage.record(kind, name, ok).await {
+    warn!("Failed to record usage of {}: {}", name, e);
+  }
+}
+
 #[derive(Debug, Clone)]
 struct ReplyBot {
   inner: Bot,
   pub user_id: i64,
   pub chat_id: ChatId,
   pub message_id: MessageId,
+  pub brand: Brand,
+  /// Language of the user, English until [`ReplyBot::localize`] runs
+  pub lang: Lang,
+  /// Staging bots stamp every message they send
+  env: Environment,
 }
 
 impl ReplyBot {
@@ -122,8 +332,41 @@ impl ReplyBot {
     user_id: i64,
     chat_id: ChatId,
     message_id: MessageId,
+    brand: Brand,
+    env: Environment,
+  ) -> Self {
+    let lang = Lang::default();
+    Self { inner, user_id, chat_id, message_id, brand, lang, env }
+  }
+
+  /// Pick up the language the user chose
+  pub async fn localize(&mut self, sv: &Services<'_>) {
+    self.lang = Lang::of(sv, self.user_id).await;
+  }
+
+  /// Reply to the sender of a plain message
+  pub fn from_message(
+    inner: Bot,
+    msg: &Message,
+    brand: Brand,
+    env: Environment,
   ) -> Self {
-    Self { inner, user_id, chat_id, message_id }
+    let user_id =
+      msg.from.as_ref().map(|u| u.id.0 as i64).unwrap_or(msg.chat.id.0);
+    Self::new(inner, user_id, msg.chat.id, msg.id, brand, env)
+  }
+
+  /// Product sold by the storefront, None on the main bot
+  pub fn product(&self) -> Option<&str> {
+    self.brand.as_ref().map(|brand| brand.product.as_str())
+  }
+
+  /// Support username without the @
+  pub fn support(&self) -> &str {
+    self
+      .brand
+      .as_ref()
+      .map_or(callback::SUPPORT, |brand| brand.support.as_str())
   }
 
   async fn reply_html(
@@ -132,7 +375,7 @@ impl ReplyBot {
   ) -> ResponseResult<Message> {
     self
       .inner
-      .send_message(self.chat_id, text.into())
+      .send_message(self.chat_id, self.env.stamp(text))
       .parse_mode(ParseMode::Html)
       .await
   }
@@ -143,7 +386,7 @@ impl ReplyBot {
     &self,
     text: impl Into<String>,
   ) -> ResponseResult<Message> {
-    let chunks = utils::chunk_message(&text.into(), 0);
+    let chunks = utils::chunk_message(&self.env.stamp(text), 0);
     let mut last_msg = None;
 
     for chunk in chunks {
@@ -168,7 +411,7 @@ impl ReplyBot {
     text: impl Into<String>,
     keyboard: InlineKeyboardMarkup,
   ) -> ResponseResult<Message> {
-    let chunks = utils::chunk_message(&text.into(), 0);
+    let chunks = utils::chunk_message(&self.env.stamp(text), 0);
     let total_chunks = chunks.len();
     let mut last_msg = None;
 
@@ -202,7 +445,7 @@ impl ReplyBot {
   ) -> ResponseResult<Message> {
     self
       .inner
-      .send_message(self.chat_id, text.into())
+      .send_message(self.chat_id, self.env.stamp(text))
       .parse_mode(ParseMode::Html)
       .reply_markup(keyboard)
       .await
@@ -215,8 +458,45 @@ impl ReplyBot {
   ) -> ResponseResult<()> {
     self
       .inner
-      .edit_message_text(self.chat_id, self.message_id, text.into())
+      .edit_message_text(self.chat_id, self.message_id, self.env.stamp(text))
+      .parse_mode(ParseMode::Html)
+      .reply_markup(keyboard)
+      .await?;
+    Ok(())
+  }
+
+  /// Same as `edit_with_keyboard` without a link preview, whose fetch
+  /// would use up one-time links
+  pub async fn edit_without_preview(
+    &self,
+    text: impl Into<String>,
+    keyboard: InlineKeyboardMarkup,
+  ) -> ResponseResult<()> {
+    let no_preview = LinkPreviewOptions {
+      is_disabled: true,
+      url: None,
+      prefer_small_media: false,
+      prefer_large_media: false,
+      show_above_text: false,
+    };
+    self
+      .inner
+      .edit_message_text(self.chat_id, self.message_id, self.env.stamp(text))
       .parse_mode(ParseMode::Html)
+      .link_preview_options(no_preview)
+      .reply_markup(keyboard)
+      .await?;
+    Ok(())
+  }
+
+  /// Replace only the inline keyboard of the message
+  pub async fn edit_keyboard(
+    &self,
+    keyboard: InlineKeyboardMarkup,
+  ) -> ResponseResult<()> {
+    self
+      .inner
+      .edit_message_reply_markup(self.chat_id, self.message_id)
       .reply_markup(keyboard)
       .await?;
     Ok(())
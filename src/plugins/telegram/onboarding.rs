@@ -0,0 +1,336 @@
+use std::sync::Arc;
+
+use teloxide::{
+  dispatching::dialogue::{Dialogue, InMemStorage},
+  prelude::*,
+  types::{InlineKeyboardButton, InlineKeyboardMarkup},
+  utils::html,
+};
+
+use super::{
+  Brand, ReplyBot,
+  callback::{TRIAL_PROMO, home, prompt_terms},
+  i18n::{self, Lang, T},
+};
+use crate::{
+  prelude::*,
+  state::{AppState, Services},
+};
+
+/// Steps of the first-run wizard shown on the first `/start`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum Onboarding {
+  #[default]
+  Idle,
+  Language,
+  Intro,
+  Trial,
+  Licensing,
+  Referral,
+}
+
+pub type OnboardingStorage = InMemStorage<Onboarding>;
+pub type OnboardingDialogue = Dialogue<Onboarding, OnboardingStorage>;
+
+pub const PREFIX: &str = "ob:";
+
+/// Wizard buttons, routed here by the `ob:` prefix
+#[derive(Debug, Clone, PartialEq)]
+pub enum Action {
+  Lang(String),
+  Next,
+  ClaimTrial,
+  Skip,
+}
+
+impl Action {
+  pub fn to_data(&self) -> String {
+    match self {
+      Action::Lang(code) => format!("ob:lang:{}", code),
+      Action::Next => "ob:next".to_string(),
+      Action::ClaimTrial => "ob:trial".to_string(),
+      Action::Skip => "ob:skip".to_string(),
+    }
+  }
+
+  pub fn from_data(data: &str) -> Option<Self> {
+    match data {
+      "ob:next" => Some(Action::Next),
+      "ob:trial" => Some(Action::ClaimTrial),
+      "ob:skip" => Some(Action::Skip),
+      _ => data
+        .strip_prefix("ob:lang:")
+        .filter(|code| code.len() <= 8 && code.chars().all(char::is_alphabetic))
+        .map(|code| Action::Lang(code.to_string())),
+    }
+  }
+}
+
+fn step_keyboard(
+  lang: Lang,
+  mut rows: Vec<Vec<InlineKeyboardButton>>,
+) -> InlineKeyboardMarkup {
+  rows.push(vec![InlineKeyboardButton::callback(
+    lang.t(T::ObSkip),
+    Action::Skip.to_data(),
+  )]);
+  InlineKeyboardMarkup::new(rows)
+}
+
+fn next_button(lang: Lang, label: T) -> Vec<InlineKeyboardButton> {
+  vec![InlineKeyboardButton::callback(lang.t(label), Action::Next.to_data())]
+}
+
+async fn set_step(dialogue: &OnboardingDialogue, step: Onboarding) {
+  if let Err(e) = dialogue.update(step).await {
+    warn!("Failed to update onboarding state: {}", e);
+  }
+}
+
+/// Begin the wizard with the language picker
+pub async fn start(
+  bot: &ReplyBot,
+  dialogue: &OnboardingDialogue,
+) -> ResponseResult<()> {
+  set_step(dialogue, Onboarding::Language).await;
+
+  let buttons = Lang::ALL
+    .into_iter()
+    .map(|lang| {
+      InlineKeyboardButton::callback(
+        lang.label(),
+        Action::Lang(lang.code().to_string()).to_data(),
+      )
+    })
+    .collect();
+
+  bot
+    .reply_with_keyboard(
+      bot.lang.t(T::ObWelcome),
+      step_keyboard(bot.lang, vec![buttons]),
+    )
+    .await?;
+  Ok(())
+}
+
+pub async fn handle_callback(
+  app: Arc<AppState>,
+  bot: Bot,
+  query: CallbackQuery,
+  dialogue: OnboardingDialogue,
+  brand: Brand,
+) -> ResponseResult<()> {
+  let (Some(data), Some(msg)) = (query.data.as_ref(), query.message.as_ref())
+  else {
+    return Ok(());
+  };
+
+  let mut bot = ReplyBot::new(
+    bot,
+    query.from.id.0 as i64,
+    msg.chat().id,
+    msg.id(),
+    brand,
+    app.config.environment,
+  );
+  bot.inner.answer_callback_query(query.id.clone()).await?;
+
+  let Some(action) = Action::from_data(data) else {
+    return Ok(());
+  };
+
+  let sv = app.sv();
+  bot.localize(&sv).await;
+  let step = dialogue.get().await.ok().flatten().unwrap_or_default();
+
+  match (step, action) {
+    (_, Action::Skip) => finish(&sv, &bot, &dialogue).await?,
+    (Onboarding::Language, Action::Lang(code)) => {
+      let lang = Lang::parse(&code).unwrap_or_default();
+      if let Err(e) = sv.settings.set_language(bot.user_id, lang.code()).await {
+        warn!("Failed to set language for {}: {}", bot.user_id, e);
+      }
+      bot.lang = lang;
+      show_intro(&bot, &dialogue).await?;
+    }
+    (Onboarding::Intro, Action::Next) => {
+      if trial_available(&sv, &bot).await {
+        show_trial(&bot, &dialogue).await?;
+      } else {
+        show_licensing(&bot, &dialogue, None).await?;
+      }
+    }
+    (Onboarding::Trial, Action::ClaimTrial) => {
+      let note = match sv.license.claim_promo(bot.user_id, TRIAL_PROMO).await {
+        Ok(license) => {
+          i18n::fill(bot.lang.t(T::ObTrialKey), &[("key", license.key)])
+        }
+        Err(e) => format!("❌ {}\n\n", e.user_message()),
+      };
+      show_licensing(&bot, &dialogue, Some(note)).await?;
+    }
+    (Onboarding::Trial, Action::Next) => {
+      show_licensing(&bot, &dialogue, None).await?;
+    }
+    (Onboarding::Licensing, Action::Next) => {
+      let has_referrer = sv
+        .user
+        .by_id(bot.user_id)
+        .await
+        .ok()
+        .flatten()
+        .is_some_and(|u| u.referred_by.is_some());
+
+      if has_referrer {
+        finish(&sv, &bot, &dialogue).await?;
+      } else {
+        show_referral(&bot, &dialogue).await?;
+      }
+    }
+    // stale button from a previous run (the state lives in memory)
+    _ => {
+      bot.reply_html(bot.lang.t(T::ObStale)).await?;
+    }
+  }
+
+  Ok(())
+}
+
+/// Text entered while the wizard waits for a referral code
+pub async fn receive_referral(
+  app: Arc<AppState>,
+  mut bot: ReplyBot,
+  msg: Message,
+  dialogue: OnboardingDialogue,
+) -> ResponseResult<()> {
+  let sv = app.sv();
+  bot.localize(&sv).await;
+
+  let Some(code) = msg.text().map(str::trim).filter(|s| !s.is_empty()) else {
+    bot.reply_html(bot.lang.t(T::ObSendCode)).await?;
+    return Ok(());
+  };
+
+  match sv.referral.resolve_code(code).await {
+    Ok(referrer_id) if referrer_id != bot.user_id => {
+      match sv.user.set_referred_by(bot.user_id, Some(referrer_id)).await {
+        Ok(_) => {
+          bot
+            .reply_html(i18n::fill(
+              bot.lang.t(T::ObCodeApplied),
+              &[("code", html::escape(code))],
+            ))
+            .await?;
+          finish(&sv, &bot, &dialogue).await?;
+        }
+        Err(e) => {
+          bot.reply_html(format!("❌ {}", e.user_message())).await?;
+        }
+      }
+    }
+    Ok(_) => {
+      bot.reply_html(bot.lang.t(T::ObOwnCode)).await?;
+    }
+    Err(e) => {
+      bot
+        .reply_with_keyboard(
+          format!("❌ {}\n\n{}", e.user_message(), bot.lang.t(T::ObTryAgain)),
+          step_keyboard(bot.lang, vec![]),
+        )
+        .await?;
+    }
+  }
+
+  Ok(())
+}
+
+/// The trial week is a key for the main product, storefronts don't offer it
+async fn trial_available(sv: &Services<'_>, bot: &ReplyBot) -> bool {
+  bot.brand.is_none()
+    && sv.license.is_promo_active(TRIAL_PROMO).await
+    && !sv.license.has_claimed(bot.user_id, TRIAL_PROMO).await.unwrap_or(true)
+}
+
+async fn show_intro(
+  bot: &ReplyBot,
+  dialogue: &OnboardingDialogue,
+) -> ResponseResult<()> {
+  set_step(dialogue, Onboarding::Intro).await;
+
+  let lang = bot.lang;
+  bot
+    .edit_with_keyboard(
+      lang.t(T::ObIntro),
+      step_keyboard(lang, vec![next_button(lang, T::ObNext)]),
+    )
+    .await
+}
+
+async fn show_trial(
+  bot: &ReplyBot,
+  dialogue: &OnboardingDialogue,
+) -> ResponseResult<()> {
+  set_step(dialogue, Onboarding::Trial).await;
+
+  let lang = bot.lang;
+  let rows = vec![
+    vec![InlineKeyboardButton::callback(
+      lang.t(T::ObClaimTrial),
+      Action::ClaimTrial.to_data(),
+    )],
+    next_button(lang, T::ObNotNow),
+  ];
+  bot.edit_with_keyboard(lang.t(T::ObTrial), step_keyboard(lang, rows)).await
+}
+
+async fn show_licensing(
+  bot: &ReplyBot,
+  dialogue: &OnboardingDialogue,
+  note: Option<String>,
+) -> ResponseResult<()> {
+  set_step(dialogue, Onboarding::Licensing).await;
+
+  let lang = bot.lang;
+  let text = format!("{}{}", note.unwrap_or_default(), lang.t(T::ObLicensing));
+  bot
+    .edit_with_keyboard(
+      text,
+      step_keyboard(lang, vec![next_button(lang, T::ObNext)]),
+    )
+    .await
+}
+
+async fn show_referral(
+  bot: &ReplyBot,
+  dialogue: &OnboardingDialogue,
+) -> ResponseResult<()> {
+  set_step(dialogue, Onboarding::Referral).await;
+
+  bot
+    .edit_with_keyboard(
+      bot.lang.t(T::ObReferral),
+      step_keyboard(bot.lang, vec![]),
+    )
+    .await
+}
+
+async fn exit(dialogue: &OnboardingDialogue) {
+  if let Err(e) = dialogue.exit().await {
+    warn!("Failed to reset onboarding state: {}", e);
+  }
+}
+
+async fn finish(
+  sv: &Services<'_>,
+  bot: &ReplyBot,
+  dialogue: &OnboardingDialogue,
+) -> ResponseResult<()> {
+  if let Err(e) = sv.settings.mark_onboarded(bot.user_id).await {
+    warn!("Failed to mark {} as onboarded: {}", bot.user_id, e);
+  }
+  exit(dialogue).await;
+
+  let (text, kb) = home(bot, sv.license.is_promo_active(TRIAL_PROMO).await);
+  bot.reply_with_keyboard(text, kb).await?;
+  prompt_terms(sv, bot).await
+}
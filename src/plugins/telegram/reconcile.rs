@@ -0,0 +1,179 @@
+use teloxide::{
+  prelude::*,
+  types::{InlineKeyboardButton, InlineKeyboardMarkup},
+};
+
+use super::{
+  ReplyBot,
+  callback::{Callback, format_usdt},
+};
+use crate::{
+  prelude::*,
+  state::AppState,
+  sv::reconcile::{Discrepancy, PaidInvoice, Report},
+};
+
+/// Discrepancies listed with a button, the rest only counted
+const SHOWN: usize = 20;
+/// Hours /reconcile covers without an argument
+const DEFAULT_HOURS: i64 = 24;
+const MAX_HOURS: i64 = 31 * 24;
+
+/// Report for admins, with fix buttons unless everything matches
+pub fn message(
+  report: &Report,
+  hours: i64,
+) -> (String, Option<InlineKeyboardMarkup>) {
+  let mut text = format!(
+    "🧾 <b>Payment reconciliation</b>, last {}h\n\n\
+    <b>Paid invoices:</b> {}\n\
+    <b>Credited deposits:</b> {}\n",
+    hours, report.paid, report.credited
+  );
+  if report.discrepancies.is_empty() {
+    text.push_str("\n✅ Everything matches");
+    return (text, None);
+  }
+
+  text.push_str(&format!(
+    "\n⚠️ <b>{} discrepancies</b>\n",
+    report.discrepancies.len()
+  ));
+  let mut rows = Vec::new();
+  for discrepancy in report.discrepancies.iter().take(SHOWN) {
+    match discrepancy {
+      Discrepancy::Uncredited(invoice) => {
+        text.push_str(&format!(
+          "\n• Invoice #{} paid {} for <code>{}</code>, not credited",
+          invoice.invoice_id,
+          format_usdt(invoice.amount_nano),
+          invoice.user_id
+        ));
+        rows.push(vec![InlineKeyboardButton::callback(
+          format!("💰 Credit invoice #{}", invoice.invoice_id),
+          Callback::ReconCredit(invoice.invoice_id).to_data(),
+        )]);
+      }
+      Discrepancy::Unpaid { tx, invoice_id } => {
+        text.push_str(&format!(
+          "\n• Deposit #{} of {} to <code>{}</code>, invoice #{} not paid",
+          tx.id,
+          format_usdt(tx.amount),
+          tx.user_id,
+          invoice_id
+        ));
+        rows.push(vec![InlineKeyboardButton::callback(
+          format!("↩️ Reverse deposit #{}", tx.id),
+          Callback::ReconReverse(tx.id).to_data(),
+        )]);
+      }
+    }
+  }
+  if report.discrepancies.len() > SHOWN {
+    text.push_str(&format!(
+      "\n\n<i>…and {} more, fix these and run /reconcile again</i>",
+      report.discrepancies.len() - SHOWN
+    ));
+  }
+  (text, Some(InlineKeyboardMarkup::new(rows)))
+}
+
+/// /reconcile [hours], the nightly check on demand
+pub(super) async fn run(
+  app: &AppState,
+  bot: &ReplyBot,
+  args: &str,
+) -> ResponseResult<()> {
+  let result = async {
+    let hours = match args.trim() {
+      "" => DEFAULT_HOURS,
+      hours => hours
+        .parse::<i64>()
+        .ok()
+        .filter(|hours| (1..=MAX_HOURS).contains(hours))
+        .ok_or_else(|| {
+          Error::InvalidArgs(format!(
+            "Usage: /reconcile [hours], up to {}",
+            MAX_HOURS
+          ))
+        })?,
+    };
+    let cryptobot = app.cryptobot.as_ref().ok_or(Error::PaymentsUnavailable)?;
+    let since = Utc::now().naive_utc() - TimeDelta::hours(hours);
+    let report = app.sv().reconcile.check(cryptobot, since).await?;
+    Ok::<_, Error>(message(&report, hours))
+  }
+  .await;
+
+  match result {
+    Ok((text, Some(keyboard))) => {
+      bot.reply_with_keyboard(text, keyboard).await?
+    }
+    Ok((text, None)) => bot.reply_html(text).await?,
+    Err(e) => bot.reply_html(format!("❌ {}", e.user_message())).await?,
+  };
+  Ok(())
+}
+
+/// Credit a missed invoice once CryptoBot confirms it is paid
+pub(super) async fn credit(
+  app: &AppState,
+  bot: &ReplyBot,
+  invoice_id: i64,
+) -> ResponseResult<()> {
+  let result = async {
+    let cryptobot = app.cryptobot.as_ref().ok_or(Error::PaymentsUnavailable)?;
+    let invoice = cryptobot.get_invoice(invoice_id).await?;
+    let invoice = PaidInvoice::from_invoice(&invoice).ok_or_else(|| {
+      Error::InvalidArgs(format!(
+        "Invoice #{} is not a paid deposit",
+        invoice_id
+      ))
+    })?;
+    let credited = app.sv().reconcile.credit(&invoice).await?;
+    Ok::<_, Error>((invoice, credited))
+  }
+  .await;
+
+  let text = match result {
+    Ok((invoice, Some(balance))) => {
+      info!(
+        "Admin {} credited missed invoice #{} to user {}",
+        bot.user_id, invoice_id, invoice.user_id
+      );
+      format!(
+        "✅ Invoice #{} credited, {} added to <code>{}</code> (balance {})",
+        invoice_id,
+        format_usdt(invoice.amount_nano),
+        invoice.user_id,
+        format_usdt(balance)
+      )
+    }
+    Ok((_, None)) => format!("ℹ️ Invoice #{} is already credited", invoice_id),
+    Err(e) => format!("❌ {}", e.user_message()),
+  };
+  bot.reply_html(text).await?;
+  Ok(())
+}
+
+/// Take back a deposit whose invoice was never paid
+pub(super) async fn reverse(
+  app: &AppState,
+  bot: &ReplyBot,
+  tx_id: i32,
+) -> ResponseResult<()> {
+  let text = match app.sv().reconcile.reverse(tx_id).await {
+    Ok((user_id, balance)) => {
+      info!("Admin {} reversed deposit #{}", bot.user_id, tx_id);
+      format!(
+        "↩️ Deposit #{} reversed, <code>{}</code> now has {}",
+        tx_id,
+        user_id,
+        format_usdt(balance)
+      )
+    }
+    Err(e) => format!("❌ {}", e.user_message()),
+  };
+  bot.reply_html(text).await?;
+  Ok(())
+}
@@ -0,0 +1,324 @@
+use std::sync::Arc;
+
+use teloxide::{
+  prelude::*,
+  types::{InlineKeyboardButton, InlineKeyboardMarkup, MessageId, ParseMode},
+  utils::html,
+};
+
+use super::{
+  ReplyBot,
+  callback::{self, format_usdt},
+};
+use crate::{
+  entity::review,
+  prelude::*,
+  state::AppState,
+  sv::{self, admin_alert::Urgency, review::Action},
+};
+
+/// Reviews sent by /review at once
+const QUEUE_SHOWN: usize = 10;
+
+/// Risk score of the user if it calls for a review
+pub async fn flagged(app: &AppState, tg_user_id: i64) -> Result<Option<i32>> {
+  let threshold = app.config.purchase_rules.review_risk_score;
+  if threshold <= 0 {
+    return Ok(None);
+  }
+  let score = app.sv().risk.score(tg_user_id).await?;
+  Ok((score >= threshold).then_some(score))
+}
+
+fn keyboard(id: i32) -> InlineKeyboardMarkup {
+  InlineKeyboardMarkup::new(vec![vec![
+    InlineKeyboardButton::callback(
+      "✅ Approve",
+      callback::Callback::Review { id, approve: true }.to_data(),
+    ),
+    InlineKeyboardButton::callback(
+      "❌ Reject",
+      callback::Callback::Review { id, approve: false }.to_data(),
+    ),
+  ]])
+}
+
+fn describe(action: &Action) -> String {
+  match action {
+    Action::Purchase { product, plan, .. } => {
+      format!("Purchase of {} ({})", html::escape(plan), html::escape(product))
+    }
+    Action::Extension { key, plan, .. } => {
+      format!("Extension of <code>{}</code> by {}", key, html::escape(plan))
+    }
+    Action::Payout { amount } => {
+      format!("Withdrawal of {}", format_usdt(*amount))
+    }
+    Action::RoleChange { role } => format!("Role change to {:?}", role),
+    Action::Transfer { key, to } => {
+      format!("Transfer of <code>{}</code> to <code>{}</code>", key, to)
+    }
+  }
+}
+
+/// Review with the context an admin needs to decide
+async fn context(app: &AppState, review: &review::Model) -> String {
+  let what = match sv::review::action(review) {
+    Ok(action) => describe(&action),
+    Err(e) => e.user_message(),
+  };
+  let factors = match app.sv().risk.get(review.tg_user_id).await {
+    Ok(Some(risk)) => sv::risk::factors(&risk)
+      .iter()
+      .map(|f| format!("{} +{}", f.signal.label(), f.points))
+      .collect::<Vec<_>>()
+      .join(", "),
+    _ => String::new(),
+  };
+  let mut text = format!(
+    "🔍 <b>Review #{}</b>\n\n\
+    <b>User:</b> <code>{}</code>\n\
+    <b>Action:</b> {}\n\
+    <b>Reason:</b> {}\n",
+    review.id,
+    review.tg_user_id,
+    what,
+    html::escape(&review.reason)
+  );
+  if !factors.is_empty() {
+    text.push_str(&format!("<b>Signals:</b> {}\n", factors));
+  }
+  if let Some(admin) = review.requested_by {
+    text.push_str(&format!("<b>Requested by:</b> <code>{}</code>\n", admin));
+  }
+  text.push_str(&format!(
+    "<b>Queued:</b> {}\n\n<i>Details: /info {}</i>",
+    utils::format_date(review.created_at),
+    review.tg_user_id
+  ));
+  text
+}
+
+/// Queue an action held for its risk score
+pub async fn submit(
+  app: &AppState,
+  tg_user_id: i64,
+  action: Action,
+  score: i32,
+  requested_by: Option<i64>,
+) -> Result<review::Model> {
+  let reason = format!("risk score {}", score);
+  request(app, tg_user_id, action, &reason, requested_by).await
+}
+
+/// Queue the action and tell the admins, unless one of its kind is already
+/// waiting
+pub async fn request(
+  app: &AppState,
+  tg_user_id: i64,
+  action: Action,
+  reason: &str,
+  requested_by: Option<i64>,
+) -> Result<review::Model> {
+  let (review, created) =
+    app.sv().reviews.submit(tg_user_id, action, reason, requested_by).await?;
+  if created {
+    let text = context(app, &review).await;
+    app.notify_admins(Urgency::Normal, &text, Some(keyboard(review.id))).await;
+  }
+  Ok(review)
+}
+
+/// Park a purchase of a flagged user, its screen is updated once decided
+pub async fn hold(
+  app: &AppState,
+  bot: &ReplyBot,
+  action: Action,
+  score: i32,
+) -> ResponseResult<()> {
+  let text = match submit(app, bot.user_id, action, score, None).await {
+    Ok(_) => "⏳ <b>Under review</b>\n\n\
+      This purchase needs a quick manual check. This message is updated \
+      as soon as it is decided, nothing is charged until then."
+      .to_string(),
+    Err(e) => format!("❌ {}", e.user_message()),
+  };
+  bot.edit_with_keyboard(text, InlineKeyboardMarkup::default()).await
+}
+
+/// /review, pending reviews oldest first with their buttons
+pub async fn queue(app: &AppState, bot: &ReplyBot) -> ResponseResult<()> {
+  let pending = match app.sv().reviews.pending().await {
+    Ok(pending) => pending,
+    Err(e) => {
+      bot.reply_html(format!("❌ {}", e.user_message())).await?;
+      return Ok(());
+    }
+  };
+  if pending.is_empty() {
+    bot.reply_html("📭 Nothing to review.").await?;
+    return Ok(());
+  }
+
+  for review in pending.iter().take(QUEUE_SHOWN) {
+    let text = context(app, review).await;
+    bot.reply_with_keyboard(text, keyboard(review.id)).await?;
+  }
+  if pending.len() > QUEUE_SHOWN {
+    bot
+      .reply_html(format!(
+        "<i>… {} more, decide on these first</i>",
+        pending.len() - QUEUE_SHOWN
+      ))
+      .await?;
+  }
+  Ok(())
+}
+
+/// Record the decision and carry the action on from where it was held
+pub async fn decide(
+  app: &Arc<AppState>,
+  bot: &ReplyBot,
+  id: i32,
+  approve: bool,
+) -> ResponseResult<()> {
+  let review = match app.sv().reviews.decide(id, bot.user_id, approve).await {
+    Ok(review) => review,
+    Err(e) => {
+      bot.reply_html(format!("❌ {}", e.user_message())).await?;
+      return Ok(());
+    }
+  };
+  bot.edit_keyboard(InlineKeyboardMarkup::default()).await?;
+
+  let outcome = match resume(app, &review, approve, bot.user_id).await {
+    Ok(outcome) => outcome,
+    Err(e) => format!("❌ {}", e.user_message()),
+  };
+  let verdict = if approve { "approved" } else { "rejected" };
+  bot.reply_html(format!("Review #{} {}: {}", id, verdict, outcome)).await?;
+  Ok(())
+}
+
+/// Bot editing the screen the user saw when the purchase was held
+async fn user_bot(
+  app: &AppState,
+  tg_user_id: i64,
+  chat_id: i64,
+  message_id: i32,
+) -> ReplyBot {
+  let sv = app.sv();
+  let storefront = sv.user.by_id(tg_user_id).await.ok().flatten();
+  let brand = match storefront.and_then(|user| user.storefront) {
+    Some(product) => sv.storefront.get(&product).await.ok().flatten(),
+    None => None,
+  };
+  let mut bot = ReplyBot::new(
+    app.user_bot(tg_user_id).await,
+    tg_user_id,
+    ChatId(chat_id),
+    MessageId(message_id),
+    brand.map(Arc::new),
+    app.config.environment,
+  );
+  bot.localize(&sv).await;
+  bot
+}
+
+async fn resume(
+  app: &AppState,
+  review: &review::Model,
+  approve: bool,
+  admin_id: i64,
+) -> Result<String> {
+  let sv = app.sv();
+  let user_id = review.tg_user_id;
+  // the risk check already happened, everything else is checked again
+  let rules = sv::eligibility::Rules {
+    review_risk_score: 0,
+    ..app.config.purchase_rules.clone()
+  };
+  let sent = |e: teloxide::RequestError| Error::Internal(e.to_string());
+
+  match sv::review::action(review)? {
+    Action::Purchase { chat_id, message_id, .. }
+    | Action::Extension { chat_id, message_id, .. }
+      if !approve =>
+    {
+      let bot = user_bot(app, user_id, chat_id, message_id).await;
+      let text = format!(
+        "❌ <b>Purchase declined</b>\n\n\
+        It did not pass the manual review, nothing was charged. \
+        Contact @{} if you think this is a mistake.",
+        bot.support()
+      );
+      bot
+        .edit_with_keyboard(text, InlineKeyboardMarkup::default())
+        .await
+        .map_err(sent)?;
+      Ok("user told".into())
+    }
+    Action::Purchase { product, plan, chat_id, message_id } => {
+      let bot = user_bot(app, user_id, chat_id, message_id).await;
+      callback::handle_buy_plan(app, &sv, &bot, &product, &plan, &rules)
+        .await
+        .map_err(sent)?;
+      Ok("purchase resumed".into())
+    }
+    Action::Extension { key, plan, chat_id, message_id } => {
+      let bot = user_bot(app, user_id, chat_id, message_id).await;
+      callback::handle_extend_plan(app, &sv, &bot, &key, &plan, &rules)
+        .await
+        .map_err(sent)?;
+      Ok("extension resumed".into())
+    }
+    Action::Payout { .. } | Action::RoleChange { .. } if !approve => {
+      Ok("nothing changed".into())
+    }
+    Action::Payout { amount } => {
+      let admin = review.requested_by.unwrap_or(admin_id);
+      let (op, balance) = sv.admin_op.withdraw(admin, user_id, amount).await?;
+      Ok(format!(
+        "withdrawal of {} processed, new balance {} (/undo {})",
+        format_usdt(amount),
+        format_usdt(balance),
+        op.id
+      ))
+    }
+    Action::RoleChange { role } => {
+      sv.user.set_role(user_id, role.clone()).await?;
+      Ok(format!("role set to {:?}", role))
+    }
+    Action::Transfer { key, .. } if !approve => {
+      let text = format!(
+        "❌ <b>Transfer declined</b>\n\n\
+        <code>{}</code> stays on your account.",
+        key
+      );
+      if let Some((bot, text)) = app.reach(user_id, text).await {
+        bot
+          .send_message(ChatId(user_id), text)
+          .parse_mode(ParseMode::Html)
+          .await
+          .map_err(sent)?;
+      }
+      Ok("user told".into())
+    }
+    Action::Transfer { key, to } => {
+      callback::complete_transfer(app, &key, user_id, to).await?;
+      let text = format!(
+        "✅ <b>Transfer approved</b>\n\n\
+        <code>{}</code> now belongs to <code>{}</code>.",
+        key, to
+      );
+      if let Some((bot, text)) = app.reach(user_id, text).await {
+        bot
+          .send_message(ChatId(user_id), text)
+          .parse_mode(ParseMode::Html)
+          .await
+          .map_err(sent)?;
+      }
+      Ok(format!("license moved to <code>{}</code>", to))
+    }
+  }
+}
@@ -0,0 +1,62 @@
+use teloxide::{
+  prelude::*,
+  types::{InlineKeyboardButton, InlineKeyboardMarkup},
+};
+
+use super::{ReplyBot, callback::Callback};
+use crate::{entity::license_alert, prelude::*, state::AppState};
+
+/// Findings listed with a ban button, the rest only counted
+const SHOWN: usize = 20;
+
+/// Daily digest of licenses that look shared, worst first
+pub fn message(
+  alerts: &[license_alert::Model],
+  hours: i64,
+) -> (String, InlineKeyboardMarkup) {
+  let mut text = format!(
+    "🕵️ <b>Possible key sharing</b>, last {}h\n\n\
+    <b>{} licenses</b> over the limits\n",
+    hours,
+    alerts.len()
+  );
+  let mut rows = Vec::new();
+  for alert in alerts.iter().take(SHOWN) {
+    text.push_str(&format!(
+      "\n• <code>{}</code>: {} machines, {} addresses, {} countries, \
+      {} sessions",
+      alert.license_key,
+      alert.hwids,
+      alert.ips,
+      alert.countries,
+      alert.sessions
+    ));
+    rows.push(vec![InlineKeyboardButton::callback(
+      format!("🚫 Ban {}", alert.license_key),
+      Callback::BanKey(alert.license_key.clone()).to_data(),
+    )]);
+  }
+  if alerts.len() > SHOWN {
+    text.push_str(&format!("\n\n<i>…and {} more</i>", alerts.len() - SHOWN));
+  }
+  text.push_str("\n\nSee <code>/sessions &lt;key&gt;</code> before banning");
+  (text, InlineKeyboardMarkup::new(rows))
+}
+
+/// Block a key from the digest and disconnect it
+pub(super) async fn ban(
+  app: &AppState,
+  bot: &ReplyBot,
+  key: &str,
+) -> ResponseResult<()> {
+  let text = match app.sv().license.set_blocked(key, true).await {
+    Ok(_) => {
+      app.drop_sessions(key);
+      info!("Admin {} banned shared key {}", bot.user_id, key);
+      format!("🚫 <code>{}</code> blocked, sessions dropped", key)
+    }
+    Err(e) => format!("❌ {}", e.user_message()),
+  };
+  bot.reply_html(text).await?;
+  Ok(())
+}
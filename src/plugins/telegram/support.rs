@@ -0,0 +1,238 @@
+use teloxide::{
+  prelude::*,
+  types::{InlineKeyboardButton, InlineKeyboardMarkup, ParseMode},
+  utils::html,
+};
+
+use super::callback::Callback;
+use crate::{
+  entity::{license, rating::RatingKind, ticket},
+  prelude::*,
+  state::{AppState, Services},
+  sv::{admin_alert::Urgency, canned, device::Anomaly},
+};
+
+/// Tell the owner about an unusual activation of their key
+pub async fn alert_anomaly(
+  app: &AppState,
+  license: &license::Model,
+  anomaly: Anomaly,
+) {
+  let sv = app.sv();
+  match sv.settings.get_or_create(license.tg_user_id).await {
+    Ok(settings) if settings.security_alerts => {}
+    Ok(_) => return,
+    Err(e) => {
+      warn!("Failed to load settings of {}: {}", license.tg_user_id, e);
+      return;
+    }
+  }
+
+  let (device_id, what) = match &anomaly {
+    Anomaly::NewDevice { device_id } => (*device_id, "a new device"),
+    Anomaly::NewCountry { device_id, .. } => (*device_id, "a new country"),
+  };
+  let Ok(Some(device)) = sv.device.by_id(device_id).await else {
+    return;
+  };
+
+  let text = format!(
+    "🛡 <b>New sign-in</b>\n\n\
+    Your key <code>{}…</code> was just activated from {}.\n\n\
+    HWID: <code>{}</code>\n\
+    Network: {}\n\
+    Country: {}\n\n\
+    If this was you, no action is needed.",
+    license.key.chars().take(8).collect::<String>(),
+    what,
+    html::escape(&device.hwid.chars().take(12).collect::<String>()),
+    device.ip.as_deref().map(utils::mask_ip).unwrap_or("unknown".into()),
+    device.country.as_deref().unwrap_or("unknown"),
+  );
+  let kb = InlineKeyboardMarkup::new(vec![
+    vec![InlineKeyboardButton::callback(
+      "⚠️ That wasn't me",
+      Callback::NotMe(device.id).to_data(),
+    )],
+    vec![InlineKeyboardButton::callback(
+      "🔕 Turn off these alerts",
+      Callback::SecurityAlertsOff.to_data(),
+    )],
+  ]);
+
+  let Some((bot, text)) = app.reach(license.tg_user_id, text).await else {
+    return;
+  };
+  let _ = bot
+    .send_message(ChatId(license.tg_user_id), text)
+    .parse_mode(ParseMode::Html)
+    .reply_markup(kb)
+    .await;
+}
+
+/// Canned response names end up in callback data (64 bytes max)
+pub const CANNED_NAME_MAX: usize = 32;
+
+/// Admin actions attached to a ticket notification
+pub fn ticket_keyboard(ticket_id: i32) -> InlineKeyboardMarkup {
+  InlineKeyboardMarkup::new(vec![vec![
+    InlineKeyboardButton::callback(
+      "📋 Canned reply",
+      Callback::TicketCanned(ticket_id).to_data(),
+    ),
+    InlineKeyboardButton::callback(
+      "✅ Close",
+      Callback::TicketClose(ticket_id).to_data(),
+    ),
+  ]])
+}
+
+/// Forward a new user message to the admins
+pub async fn notify_new_message(
+  app: &AppState,
+  ticket: &ticket::Model,
+  is_new: bool,
+  text: &str,
+) {
+  let title = if is_new { "🎫 New ticket" } else { "💬 Ticket update" };
+  let message = format!(
+    "{} <b>#{}</b> from <code>{}</code>\n\n{}\n\n\
+    Reply: <code>/reply {} your answer</code>",
+    title,
+    ticket.id,
+    ticket.tg_user_id,
+    html::escape(text),
+    ticket.id
+  );
+  app
+    .notify_admins(Urgency::Normal, &message, Some(ticket_keyboard(ticket.id)))
+    .await;
+}
+
+/// Record a support reply and deliver it to the ticket owner
+pub async fn send_reply(
+  app: &AppState,
+  ticket_id: i32,
+  admin_id: i64,
+  text: &str,
+) -> Result<ticket::Model> {
+  let ticket = app.sv().ticket.reply(ticket_id, admin_id, text).await?;
+
+  let message = format!(
+    "💬 <b>Support reply</b> (ticket #{})\n\n{}\n\n\
+    <i>To answer, send /ticket your message</i>",
+    ticket.id, text
+  );
+  if let Some((bot, message)) = app.reach(ticket.tg_user_id, message).await {
+    bot
+      .send_message(ChatId(ticket.tg_user_id), message)
+      .parse_mode(ParseMode::Html)
+      .await
+      .map_err(|e| {
+        Error::Internal(format!("Failed to deliver reply: {}", e))
+      })?;
+  }
+
+  Ok(ticket)
+}
+
+/// Close the ticket, let the owner know and ask how it went
+pub async fn close_ticket(app: &AppState, id: i32) -> Result<ticket::Model> {
+  let ticket = app.sv().ticket.close(id).await?;
+  let text = format!("✅ Your support ticket #{} was closed.", ticket.id);
+  let Some((bot, text)) = app.reach(ticket.tg_user_id, text).await else {
+    return Ok(ticket);
+  };
+
+  let _ = bot.send_message(ChatId(ticket.tg_user_id), text).await;
+
+  ask_rating(
+    &app.sv(),
+    &bot,
+    ticket.tg_user_id,
+    RatingKind::Support,
+    &ticket.id.to_string(),
+    "How satisfied are you with the support you received?",
+  )
+  .await;
+
+  Ok(ticket)
+}
+
+/// Send a 1-5 rating prompt. Users are free to ignore it.
+pub async fn ask_rating(
+  sv: &Services<'_>,
+  bot: &Bot,
+  tg_user_id: i64,
+  kind: RatingKind,
+  subject: &str,
+  question: &str,
+) {
+  let prompt = match sv.rating.request(tg_user_id, kind, subject).await {
+    Ok(prompt) => prompt,
+    Err(e) => {
+      warn!("Failed to create rating prompt for {}: {}", tg_user_id, e);
+      return;
+    }
+  };
+
+  let buttons: Vec<_> = (1..=5)
+    .map(|score| {
+      InlineKeyboardButton::callback(
+        format!("{}⭐", score),
+        Callback::Rate { id: prompt.id, score }.to_data(),
+      )
+    })
+    .collect();
+
+  let _ = bot
+    .send_message(ChatId(tg_user_id), format!("🙏 {}", question))
+    .reply_markup(InlineKeyboardMarkup::new(vec![buttons]))
+    .await;
+}
+
+/// Placeholder values for canned responses, based on the ticket owner
+pub async fn canned_vars(
+  sv: &Services<'_>,
+  ticket: &ticket::Model,
+) -> Vec<(&'static str, String)> {
+  let now = Utc::now().naive_utc();
+  let license = sv
+    .license
+    .by_user(ticket.tg_user_id, false)
+    .await
+    .unwrap_or_default()
+    .into_iter()
+    .filter(|license| license.expires_at > now)
+    .max_by_key(|license| license.expires_at);
+
+  let (key, expiry) = match license {
+    Some(license) => (license.key, utils::format_date(license.expires_at)),
+    None => ("(no active license)".into(), "-".into()),
+  };
+
+  vec![
+    ("key", key),
+    ("expiry", expiry),
+    ("user_id", ticket.tg_user_id.to_string()),
+    ("ticket", ticket.id.to_string()),
+  ]
+}
+
+/// Expand a canned response for the ticket and send it
+pub async fn send_canned(
+  app: &AppState,
+  ticket_id: i32,
+  admin_id: i64,
+  name: &str,
+) -> Result<ticket::Model> {
+  let sv = app.sv();
+  let response = sv.canned.by_name(name).await?.ok_or_else(|| {
+    Error::InvalidArgs(format!("Canned response '{}' not found", name))
+  })?;
+  let ticket =
+    sv.ticket.by_id(ticket_id).await?.ok_or(Error::TicketNotFound)?;
+
+  let text = canned::expand(&response.text, &canned_vars(&sv, &ticket).await);
+  send_reply(app, ticket_id, admin_id, &text).await
+}
@@ -16,5 +16,5 @@ pub use sea_orm::{
 pub use tokio::time;
 pub use tracing::{error, info, warn};
 
-pub use crate::error::{Error, Promo, Result};
+pub use crate::error::{Error, Ineligible, Promo, Result};
 pub(crate) use crate::utils;
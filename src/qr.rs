@@ -0,0 +1,475 @@
+//! Minimal QR code encoder for invite links.
+//! Byte mode at error correction level M, versions 1 to 10 (up to 213 bytes).
+
+use std::io::Write;
+
+use flate2::{Compression, Crc, write::ZlibEncoder};
+
+/// Error correction codewords per block and `(blocks, data codewords)` of
+/// both block groups, per version at level M
+const BLOCKS: [(usize, [(usize, usize); 2]); 10] = [
+  (10, [(1, 16), (0, 0)]),
+  (16, [(1, 28), (0, 0)]),
+  (26, [(1, 44), (0, 0)]),
+  (18, [(2, 32), (0, 0)]),
+  (24, [(2, 43), (0, 0)]),
+  (16, [(4, 27), (0, 0)]),
+  (18, [(4, 31), (0, 0)]),
+  (22, [(2, 38), (2, 39)]),
+  (22, [(3, 36), (2, 37)]),
+  (26, [(4, 43), (1, 44)]),
+];
+
+/// Centers of alignment patterns per version
+const ALIGNMENT: [&[usize]; 10] = [
+  &[],
+  &[6, 18],
+  &[6, 22],
+  &[6, 26],
+  &[6, 30],
+  &[6, 34],
+  &[6, 22, 38],
+  &[6, 24, 42],
+  &[6, 26, 46],
+  &[6, 28, 50],
+];
+
+/// Light modules around the code required by scanners
+const QUIET_ZONE: usize = 4;
+
+pub struct QrCode {
+  size: usize,
+  modules: Vec<bool>,
+  function: Vec<bool>,
+}
+
+impl QrCode {
+  /// Encode data in the smallest version that fits, None if it is too long
+  pub fn encode(data: &[u8]) -> Option<Self> {
+    (1..=BLOCKS.len()).find_map(|version| {
+      let (ec, groups) = BLOCKS[version - 1];
+      let capacity = groups.iter().map(|(n, len)| n * len).sum::<usize>();
+      let count_bits = if version < 10 { 8 } else { 16 };
+      if 4 + count_bits + data.len() * 8 > capacity * 8 {
+        return None;
+      }
+
+      let data = data_codewords(data, count_bits, capacity);
+      Some(Self::build(version, &interleave(&data, ec, groups)))
+    })
+  }
+
+  /// Whether the module at column `x`, row `y` is dark
+  pub fn get(&self, x: usize, y: usize) -> bool {
+    self.modules[y * self.size + x]
+  }
+
+  fn set(&mut self, x: usize, y: usize, dark: bool) {
+    self.modules[y * self.size + x] = dark;
+  }
+
+  fn set_function(&mut self, x: usize, y: usize, dark: bool) {
+    self.set(x, y, dark);
+    self.function[y * self.size + x] = true;
+  }
+
+  fn build(version: usize, codewords: &[u8]) -> Self {
+    let size = 17 + 4 * version;
+    let mut qr = Self {
+      size,
+      modules: vec![false; size * size],
+      function: vec![false; size * size],
+    };
+
+    qr.draw_function_patterns(version);
+    qr.draw_codewords(codewords);
+
+    let best = (0..8)
+      .min_by_key(|&mask| {
+        qr.apply_mask(mask);
+        qr.draw_format(mask);
+        let penalty = qr.penalty();
+        qr.apply_mask(mask);
+        penalty
+      })
+      .unwrap_or(0);
+    qr.apply_mask(best);
+    qr.draw_format(best);
+    qr
+  }
+
+  fn draw_function_patterns(&mut self, version: usize) {
+    let size = self.size;
+    for i in 0..size {
+      self.set_function(6, i, i % 2 == 0);
+      self.set_function(i, 6, i % 2 == 0);
+    }
+
+    for (cx, cy) in [(3, 3), (size - 4, 3), (3, size - 4)] {
+      for dy in -4..=4_isize {
+        for dx in -4..=4_isize {
+          let (x, y) = (cx as isize + dx, cy as isize + dy);
+          if (0..size as isize).contains(&x) && (0..size as isize).contains(&y)
+          {
+            let dist = dx.abs().max(dy.abs());
+            self.set_function(x as usize, y as usize, dist != 2 && dist != 4);
+          }
+        }
+      }
+    }
+
+    let centers = ALIGNMENT[version - 1];
+    let last = centers.len().saturating_sub(1);
+    for (i, &cx) in centers.iter().enumerate() {
+      for (j, &cy) in centers.iter().enumerate() {
+        // corners taken by finder patterns
+        if (i == 0 && (j == 0 || j == last)) || (i == last && j == 0) {
+          continue;
+        }
+        for dy in -2..=2_isize {
+          for dx in -2..=2_isize {
+            let (x, y) = (cx as isize + dx, cy as isize + dy);
+            self.set_function(
+              x as usize,
+              y as usize,
+              dx.abs().max(dy.abs()) != 1,
+            );
+          }
+        }
+      }
+    }
+
+    // reserve the format area, the real bits are drawn after masking
+    self.draw_format(0);
+
+    if version >= 7 {
+      let mut rem = version;
+      for _ in 0..12 {
+        rem = (rem << 1) ^ ((rem >> 11) * 0x1F25);
+      }
+      let bits = version << 12 | rem;
+      for i in 0..18 {
+        let dark = bits >> i & 1 == 1;
+        let (a, b) = (size - 11 + i % 3, i / 3);
+        self.set_function(a, b, dark);
+        self.set_function(b, a, dark);
+      }
+    }
+  }
+
+  fn draw_format(&mut self, mask: usize) {
+    let bits = format_bits(mask);
+    let bit = |i: usize| bits >> i & 1 == 1;
+    let size = self.size;
+
+    for i in 0..6 {
+      self.set_function(8, i, bit(i));
+    }
+    self.set_function(8, 7, bit(6));
+    self.set_function(8, 8, bit(7));
+    self.set_function(7, 8, bit(8));
+    for i in 9..15 {
+      self.set_function(14 - i, 8, bit(i));
+    }
+
+    for i in 0..8 {
+      self.set_function(size - 1 - i, 8, bit(i));
+    }
+    for i in 8..15 {
+      self.set_function(8, size - 15 + i, bit(i));
+    }
+    // always dark
+    self.set_function(8, size - 8, true);
+  }
+
+  /// Place data bits in the two-column zigzag from the bottom right corner
+  fn draw_codewords(&mut self, codewords: &[u8]) {
+    let size = self.size;
+    let total = codewords.len() * 8;
+    let mut i = 0;
+    let mut right = size - 1;
+
+    while right >= 1 {
+      if right == 6 {
+        right = 5;
+      }
+      for vert in 0..size {
+        for j in 0..2 {
+          let x = right - j;
+          let upward = (right + 1) & 2 == 0;
+          let y = if upward { size - 1 - vert } else { vert };
+          if !self.function[y * size + x] && i < total {
+            self.set(x, y, codewords[i >> 3] >> (7 - (i & 7)) & 1 == 1);
+            i += 1;
+          }
+        }
+      }
+      if right < 2 {
+        break;
+      }
+      right -= 2;
+    }
+  }
+
+  /// XOR the data modules with a mask pattern, applying twice undoes it
+  fn apply_mask(&mut self, mask: usize) {
+    for y in 0..self.size {
+      for x in 0..self.size {
+        let flip = match mask {
+          0 => (x + y) % 2 == 0,
+          1 => y % 2 == 0,
+          2 => x % 3 == 0,
+          3 => (x + y) % 3 == 0,
+          4 => (x / 3 + y / 2) % 2 == 0,
+          5 => x * y % 2 + x * y % 3 == 0,
+          6 => (x * y % 2 + x * y % 3) % 2 == 0,
+          _ => ((x + y) % 2 + x * y % 3) % 2 == 0,
+        };
+        let idx = y * self.size + x;
+        if flip && !self.function[idx] {
+          self.modules[idx] ^= true;
+        }
+      }
+    }
+  }
+
+  /// Score how hard the symbol is to scan, lower is better
+  fn penalty(&self) -> usize {
+    const FINDER: [bool; 11] =
+      [true, false, true, true, true, false, true, false, false, false, false];
+
+    let size = self.size;
+    let mut penalty = 0;
+    let lines = (0..size).flat_map(|i| {
+      [
+        (0..size).map(|j| self.get(j, i)).collect::<Vec<_>>(),
+        (0..size).map(|j| self.get(i, j)).collect::<Vec<_>>(),
+      ]
+    });
+
+    for line in lines {
+      for run in line.chunk_by(|a, b| a == b) {
+        if run.len() >= 5 {
+          penalty += run.len() - 2;
+        }
+      }
+      for window in line.windows(FINDER.len()) {
+        if window.iter().eq(FINDER.iter())
+          || window.iter().rev().eq(FINDER.iter())
+        {
+          penalty += 40;
+        }
+      }
+    }
+
+    for y in 0..size - 1 {
+      for x in 0..size - 1 {
+        let dark = self.get(x, y);
+        if dark == self.get(x + 1, y)
+          && dark == self.get(x, y + 1)
+          && dark == self.get(x + 1, y + 1)
+        {
+          penalty += 3;
+        }
+      }
+    }
+
+    let dark = self.modules.iter().filter(|&&m| m).count();
+    let total = size * size;
+    // 10 points per 5% away from an even balance
+    penalty + (dark * 20).abs_diff(total * 10) / total * 10
+  }
+
+  /// Render as a grayscale PNG with `scale` pixels per module
+  pub fn to_png(&self, scale: usize) -> Vec<u8> {
+    let side = (self.size + 2 * QUIET_ZONE) * scale;
+
+    let mut raw = Vec::with_capacity((side + 1) * side);
+    for py in 0..side {
+      // filter type: none
+      raw.push(0);
+      for px in 0..side {
+        let (x, y) = (px / scale, py / scale);
+        let dark = (QUIET_ZONE..QUIET_ZONE + self.size).contains(&x)
+          && (QUIET_ZONE..QUIET_ZONE + self.size).contains(&y)
+          && self.get(x - QUIET_ZONE, y - QUIET_ZONE);
+        raw.push(if dark { 0 } else { 255 });
+      }
+    }
+
+    png(side as u32, side as u32, GRAYSCALE, &raw)
+  }
+}
+
+/// PNG color types of 8-bit samples
+pub const GRAYSCALE: u8 = 0;
+pub const RGB: u8 = 2;
+
+/// Wrap scanlines, each starting with its filter type, into a PNG
+pub fn png(width: u32, height: u32, color: u8, raw: &[u8]) -> Vec<u8> {
+  let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+  // writing into a Vec can not fail
+  let _ = encoder.write_all(raw);
+  let idat = encoder.finish().unwrap_or_default();
+
+  let mut ihdr = Vec::with_capacity(13);
+  ihdr.extend_from_slice(&width.to_be_bytes());
+  ihdr.extend_from_slice(&height.to_be_bytes());
+  // 8-bit samples, deflate, no interlace
+  ihdr.extend_from_slice(&[8, color, 0, 0, 0]);
+
+  let mut png = b"\x89PNG\r\n\x1a\n".to_vec();
+  png_chunk(&mut png, b"IHDR", &ihdr);
+  png_chunk(&mut png, b"IDAT", &idat);
+  png_chunk(&mut png, b"IEND", &[]);
+  png
+}
+
+fn png_chunk(png: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+  let mut crc = Crc::new();
+  crc.update(kind);
+  crc.update(data);
+
+  png.extend_from_slice(&(data.len() as u32).to_be_bytes());
+  png.extend_from_slice(kind);
+  png.extend_from_slice(data);
+  png.extend_from_slice(&crc.sum().to_be_bytes());
+}
+
+/// Level M with the mask, protected by BCH(15, 5)
+fn format_bits(mask: usize) -> usize {
+  // level M is encoded as 00
+  let data = mask;
+  let mut rem = data;
+  for _ in 0..10 {
+    rem = (rem << 1) ^ ((rem >> 9) * 0x537);
+  }
+  (data << 10 | rem) ^ 0x5412
+}
+
+fn push_bits(bits: &mut Vec<bool>, value: usize, len: usize) {
+  bits.extend((0..len).rev().map(|i| value >> i & 1 == 1));
+}
+
+/// Mode, length and payload padded to the data capacity
+fn data_codewords(data: &[u8], count_bits: usize, capacity: usize) -> Vec<u8> {
+  let mut bits = Vec::with_capacity(capacity * 8);
+  push_bits(&mut bits, 0b0100, 4);
+  push_bits(&mut bits, data.len(), count_bits);
+  for &byte in data {
+    push_bits(&mut bits, byte as usize, 8);
+  }
+
+  let terminator = (capacity * 8 - bits.len()).min(4);
+  push_bits(&mut bits, 0, terminator);
+  let padding = (8 - bits.len() % 8) % 8;
+  push_bits(&mut bits, 0, padding);
+
+  let mut bytes: Vec<u8> = bits
+    .chunks(8)
+    .map(|byte| byte.iter().fold(0, |acc, &bit| acc << 1 | bit as u8))
+    .collect();
+  for pad in [0xEC, 0x11].into_iter().cycle() {
+    if bytes.len() >= capacity {
+      break;
+    }
+    bytes.push(pad);
+  }
+  bytes
+}
+
+/// Split data into blocks, add error correction and interleave them
+fn interleave(data: &[u8], ec: usize, groups: [(usize, usize); 2]) -> Vec<u8> {
+  let divisor = rs_divisor(ec);
+  let mut blocks = Vec::new();
+  let mut offset = 0;
+  for (count, len) in groups {
+    for _ in 0..count {
+      let block = &data[offset..offset + len];
+      blocks.push((block, rs_remainder(block, &divisor)));
+      offset += len;
+    }
+  }
+
+  let longest = groups.iter().map(|&(_, len)| len).max().unwrap_or(0);
+  let mut out = Vec::with_capacity(data.len() + ec * blocks.len());
+  for i in 0..longest {
+    out.extend(blocks.iter().filter_map(|(block, _)| block.get(i)));
+  }
+  for i in 0..ec {
+    out.extend(blocks.iter().map(|(_, ecc)| ecc[i]));
+  }
+  out
+}
+
+/// Multiply in GF(256) modulo x^8 + x^4 + x^3 + x^2 + 1
+fn gf_mul(x: u8, y: u8) -> u8 {
+  let mut z = 0u8;
+  for i in (0..8).rev() {
+    z = (z << 1) ^ ((z >> 7) * 0x1D);
+    z ^= ((y >> i) & 1) * x;
+  }
+  z
+}
+
+/// Reed-Solomon generator polynomial without its leading term
+fn rs_divisor(degree: usize) -> Vec<u8> {
+  let mut result = vec![0u8; degree];
+  result[degree - 1] = 1;
+  let mut root = 1u8;
+  for _ in 0..degree {
+    for j in 0..degree {
+      result[j] = gf_mul(result[j], root);
+      if j + 1 < degree {
+        result[j] ^= result[j + 1];
+      }
+    }
+    root = gf_mul(root, 0x02);
+  }
+  result
+}
+
+fn rs_remainder(data: &[u8], divisor: &[u8]) -> Vec<u8> {
+  let mut result = vec![0u8; divisor.len()];
+  for &byte in data {
+    let factor = byte ^ result.remove(0);
+    result.push(0);
+    for (r, &d) in result.iter_mut().zip(divisor) {
+      *r ^= gf_mul(d, factor);
+    }
+  }
+  result
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_error_correction_and_layout() {
+    // "01234567" at 1-M from the specification
+    let data = [
+      0x10, 0x20, 0x0C, 0x56, 0x61, 0x80, 0xEC, 0x11, 0xEC, 0x11, 0xEC, 0x11,
+      0xEC, 0x11, 0xEC, 0x11,
+    ];
+    assert_eq!(
+      rs_remainder(&data, &rs_divisor(10)),
+      [0xA5, 0x24, 0xD4, 0xC1, 0xED, 0x36, 0xC7, 0x87, 0x2C, 0x55]
+    );
+    assert_eq!(format_bits(0), 0b101010000010010);
+
+    let link = b"https://t.me/license_bot?start=creator";
+    let qr = QrCode::encode(link).unwrap();
+    assert_eq!(qr.size, 29);
+    // finder pattern corners and the dark module
+    assert!(qr.get(0, 0) && qr.get(28, 0) && qr.get(0, 28));
+    assert!(!qr.get(7, 7) && qr.get(8, 21));
+
+    assert_eq!(QrCode::encode(&[b'a'; 200]).unwrap().size, 57);
+    assert!(QrCode::encode(&[b'a'; 214]).is_none());
+
+    let png = qr.to_png(4);
+    assert!(png.starts_with(b"\x89PNG\r\n\x1a\n"));
+    assert!(png.ends_with(&[0xAE, 0x42, 0x60, 0x82]));
+  }
+}
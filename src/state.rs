@@ -1,28 +1,65 @@
 use std::{
   collections::HashSet,
   hash::{DefaultHasher, Hash, Hasher},
-  path::Path,
-  sync::atomic::{AtomicU64, Ordering},
+  path::{Path, PathBuf},
+  str::FromStr,
+  sync::{
+    Arc, RwLock,
+    atomic::{AtomicBool, AtomicU64, Ordering},
+  },
 };
 
 use migration::Migrator;
+use sea_orm::{
+  ConnectOptions, Statement,
+  sqlx::sqlite::{SqliteJournalMode, SqliteSynchronous},
+};
 use teloxide::{
   Bot,
   prelude::*,
-  types::{InputFile, ParseMode},
+  types::{InlineKeyboardMarkup, InputFile, ParseMode},
 };
 use tokio::fs;
-use tracing::{debug, info};
-use uuid::Uuid;
+use tracing::{debug, info, warn};
 
-use crate::{entity::license, prelude::*, sv};
+use crate::{
+  entity::{api_usage::ApiKind, license},
+  prelude::*,
+  sv::{
+    self,
+    admin_alert::Urgency,
+    pricing::Catalog,
+    signing_key::Keyring,
+    stats::{RejectReason, Rejected},
+  },
+};
 
 #[derive(Debug, Clone)]
 #[allow(dead_code)]
 pub struct Session {
   pub session_id: String,
   pub hwid_hash: Option<String>,
+  /// Platform the client reported on login
+  pub platform: Option<String>,
+  /// Address and its country at login
+  pub ip: Option<String>,
+  pub country: Option<String>,
   pub last_seen: DateTime,
+  /// Tier used for rate limiting without a DB lookup
+  pub license_type: license::LicenseType,
+  /// Usage window checked on every heartbeat, not only on login
+  pub schedule: Option<sv::license::Schedule>,
+  /// Feature overrides sent with every heartbeat
+  pub features: HashMap<String, bool>,
+}
+
+impl Session {
+  /// Short handle of the client-chosen session id, fits in button data
+  pub fn tag(&self) -> String {
+    let mut hasher = DefaultHasher::new();
+    self.session_id.hash(&mut hasher);
+    format!("{:08x}", hasher.finish() as u32)
+  }
 }
 
 pub type Sessions = DashMap<String, Vec<Session>>;
@@ -38,17 +75,79 @@ pub struct BannedSession {
 /// Maps session_id to BannedSession
 pub type BannedSessions = DashMap<String, BannedSession>;
 
-/// Download token stored in DashMap with expiry
+/// Fixed one-minute request window of a license on one endpoint
 #[derive(Debug, Clone)]
-pub struct DownloadToken {
-  pub version: String,
-  pub created_at: DateTime,
+pub struct RateWindow {
+  pub started_at: DateTime,
+  pub count: u32,
 }
 
-pub type DownloadTokens = DashMap<String, DownloadToken>;
+pub type RateWindows = DashMap<(String, &'static str), RateWindow>;
+
+/// Seconds a signed request stays valid, and its nonce remembered
+pub const NONCE_WINDOW_SECS: i64 = 5 * 60;
+/// Seconds enforcement strikes of a license are counted over
+const STRIKE_WINDOW_SECS: i64 = 60 * 60;
+/// Days of throttled requests looked back on for `abuse_days`
+const ABUSE_WINDOW_DAYS: i64 = 7;
+/// Days of API usage rollups kept
+const API_USAGE_RETENTION_DAYS: i64 = 90;
+
+/// Outcome of a rate limit check, sent back as `X-RateLimit-*` headers
+#[derive(Debug, Clone)]
+pub struct RateStatus {
+  pub allowed: bool,
+  pub limit: u32,
+  pub remaining: u32,
+  /// Seconds until the window resets
+  pub reset: i64,
+}
+
+/// Users whose last session expired, with the time it happened
+pub type OfflineSince = DashMap<i64, DateTime>;
+
+/// Put in front of every message a staging bot sends
+const STAGING_BANNER: &str = "🧪 STAGING\n\n";
+
+/// Deployment the server runs as
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Environment {
+  #[default]
+  Production,
+  /// Runs on a copy of the data: messages are watermarked, only admins get
+  /// them and payments go through the CryptoBot testnet
+  Staging,
+}
+
+impl Environment {
+  pub fn is_staging(self) -> bool {
+    self == Environment::Staging
+  }
+
+  /// The text with the staging banner in front, unchanged in production
+  pub fn stamp(self, text: impl Into<String>) -> String {
+    match self {
+      Environment::Production => text.into(),
+      Environment::Staging => format!("{}{}", STAGING_BANNER, text.into()),
+    }
+  }
+}
+
+impl FromStr for Environment {
+  type Err = String;
+
+  fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+    match s.trim() {
+      "prod" | "production" => Ok(Environment::Production),
+      "staging" => Ok(Environment::Staging),
+      other => Err(format!("expected prod or staging ('{}')", other)),
+    }
+  }
+}
 
 #[derive(Debug, Clone)]
 pub struct Config {
+  pub environment: Environment,
   pub builds_directory: String,
   pub session_lifetime: i64,
   pub banned_session_lifetime: i64,
@@ -57,11 +156,101 @@ pub struct Config {
   pub base_url: String,
   pub gc_min_free_space: u64,
   pub gc_check_interval_secs: u64,
+  /// Hours a ticket may wait for a reply before admins are reminded
+  /// (0 disables SLA reminders)
+  pub ticket_sla_hours: i64,
+  /// Distinct licenses one machine may use within `hwid_window_hours`
+  /// (0 disables the check)
+  pub hwid_max_licenses: u64,
+  /// Same for trial keys, to stop farming trials from many accounts
+  pub hwid_max_trials: u64,
+  pub hwid_window_hours: i64,
+  /// Distinct machines and addresses one license may be seen on within
+  /// `sharing_window_hours` before the daily digest flags it (0 ignores)
+  pub sharing_max_hwids: u64,
+  pub sharing_max_ips: u64,
+  pub sharing_window_hours: i64,
+  /// Days between /delete_account and the actual wipe
+  pub deletion_cooloff_days: i64,
+  pub deletion_refund: sv::account::RefundPolicy,
+  /// Admins allowed to /undo balance operations
+  pub owners: HashSet<i64>,
+  /// Minutes an admin balance operation can still be undone
+  pub undo_window_mins: i64,
+  /// Freebies one user may claim per day
+  pub freebie_daily_limit: u64,
+  /// Keys of the admin REST API, empty disables it
+  pub admin_api_keys: Vec<String>,
+  /// Country of client addresses when no CDN header tells it
+  pub geoip: Option<Arc<crate::geoip::GeoIp>>,
+  /// The server is only reachable through the CDN, so its client headers
+  /// can be believed. Anyone can send them straight to the origin otherwise
+  pub trusted_proxy: bool,
+  /// Checked before every purchase, extension and renewal
+  pub purchase_rules: sv::eligibility::Rules,
+  /// License transfers between users wait for an admin
+  pub transfer_review: bool,
+  /// Lifetime of offline license tokens
+  pub offline_token_hours: i64,
+  /// Upgrade offers for trial users who farm a lot
+  pub trial_nudge: sv::upgrade_offer::Nudge,
+  /// Session limit or sharing violations within an hour that suspend the
+  /// key, 0 never suspends
+  pub suspend_strikes: u32,
+  /// Length of an automatic suspension
+  pub suspend_hours: i64,
+  /// Days of the past week with throttled requests that halve a key's rate
+  /// limits, 0 never does
+  pub abuse_days: u32,
+  /// Seconds a session has to wait between two heartbeats, earlier ones
+  /// are refused before touching the database. 0 disables it
+  pub heartbeat_min_interval_secs: i64,
+  /// Heartbeats, validations and metrics without a signed nonce are refused
+  pub require_nonce: bool,
+  /// Client clock error tolerated around license and token expiry
+  pub clock_skew_secs: i64,
+  /// How often the database and disk sizes are checked, 0 disables it
+  pub db_check_interval_secs: u64,
+  /// WAL size that triggers a checkpoint
+  pub wal_checkpoint_size: u64,
+  /// Free disk space below which admins are alerted
+  pub disk_alert_space: u64,
+  /// Off-site copy of backups, Telegram stays the fallback
+  pub backup_s3: Option<sv::s3::S3>,
+  /// Backups are encrypted with it before leaving the server
+  pub backup_key: Option<sv::backup::BackupKey>,
+  /// Where snapshots are kept between rotations
+  pub backup_dir: String,
+  /// Snapshots kept locally and in the bucket, older ones are pruned
+  pub backup_retention: sv::backup::Retention,
+}
+
+impl Config {
+  pub fn hwid_policy(&self) -> sv::hwid_policy::Policy {
+    sv::hwid_policy::Policy {
+      max_licenses: self.hwid_max_licenses,
+      max_trials: self.hwid_max_trials,
+      window: TimeDelta::hours(self.hwid_window_hours),
+    }
+  }
+
+  pub fn sharing_policy(&self) -> sv::sharing::Policy {
+    sv::sharing::Policy {
+      max_hwids: self.sharing_max_hwids,
+      max_ips: self.sharing_max_ips,
+      window: TimeDelta::hours(self.sharing_window_hours),
+    }
+  }
+
+  pub fn clock_skew(&self) -> TimeDelta {
+    TimeDelta::seconds(self.clock_skew_secs)
+  }
 }
 
 impl Default for Config {
   fn default() -> Self {
     Self {
+      environment: Environment::default(),
       builds_directory: String::from("./builds"),
       session_lifetime: 120,
       banned_session_lifetime: 30 * 60,
@@ -70,6 +259,38 @@ impl Default for Config {
       base_url: String::from("http://localhost:3000"),
       gc_min_free_space: 500 * 1024 * 1024, // 500MB
       gc_check_interval_secs: 60,
+      ticket_sla_hours: 12,
+      hwid_max_licenses: 0,
+      hwid_max_trials: 0,
+      hwid_window_hours: 24 * 7,
+      sharing_max_hwids: 0,
+      sharing_max_ips: 0,
+      sharing_window_hours: 24,
+      deletion_cooloff_days: 7,
+      deletion_refund: sv::account::RefundPolicy::Manual,
+      owners: HashSet::new(),
+      undo_window_mins: 60,
+      freebie_daily_limit: sv::freebie::DEFAULT_DAILY_LIMIT,
+      admin_api_keys: Vec::new(),
+      geoip: None,
+      trusted_proxy: false,
+      purchase_rules: sv::eligibility::Rules::default(),
+      transfer_review: false,
+      offline_token_hours: 24,
+      trial_nudge: sv::upgrade_offer::Nudge::default(),
+      suspend_strikes: 0,
+      suspend_hours: 24,
+      abuse_days: 0,
+      heartbeat_min_interval_secs: 5,
+      require_nonce: false,
+      clock_skew_secs: 120,
+      db_check_interval_secs: 5 * 60,
+      wal_checkpoint_size: 64 * 1024 * 1024, // 64MB
+      disk_alert_space: 1024 * 1024 * 1024,  // 1GB
+      backup_s3: None,
+      backup_key: None,
+      backup_dir: String::from("./backups"),
+      backup_retention: sv::backup::Retention::default(),
     }
   }
 }
@@ -77,11 +298,51 @@ impl Default for Config {
 #[allow(dead_code)]
 pub struct Services<'a> {
   pub user: sv::User<'a>,
+  pub account: sv::Account<'a>,
+  pub admin_alerts: sv::AdminAlerts<'a>,
+  pub admin_op: sv::AdminOp<'a>,
+  pub announcement: sv::Announcement<'a>,
+  pub api_token: sv::ApiToken<'a>,
+  pub api_usage: sv::ApiUsage<'a>,
   pub stats: sv::Stats<'a>,
+  pub bot_usage: sv::BotUsage<'a>,
   pub build: sv::Build<'a>,
+  pub canned: sv::Canned<'a>,
+  pub config_sync: sv::ConfigSync<'a>,
+  pub custom_field: sv::CustomField<'a>,
+  pub device: sv::Device<'a>,
+  pub download_tokens: sv::DownloadTokens<'a>,
+  pub eligibility: sv::Eligibility<'a>,
+  pub error_reports: sv::ErrorReports<'a>,
+  pub faq: sv::Faq<'a>,
+  pub feature_flag: sv::FeatureFlag<'a>,
+  pub freebies: sv::Freebies<'a>,
+  pub goal: sv::Goal<'a>,
+  pub hwid_policy: sv::HwidPolicy<'a>,
+  pub incident: sv::Incident<'a>,
   pub license: sv::License<'a>,
+  pub outbox: sv::Outbox<'a>,
+  pub plan: sv::Plan<'a>,
+  pub pricing: sv::Pricing<'a>,
+  pub product: sv::Product<'a>,
+  pub promo_campaign: sv::PromoCampaign<'a>,
+  pub promo_code: sv::PromoCode<'a>,
+  pub promo_kit: sv::PromoKit<'a>,
   pub steam: sv::Steam<'a>,
+  pub storefront: sv::Storefront<'a>,
+  pub terms: sv::Terms<'a>,
+  pub ticket: sv::Ticket<'a>,
+  pub upgrade_offers: sv::UpgradeOffers<'a>,
   pub referral: sv::Referral<'a>,
+  pub report: sv::Report<'a>,
+  pub reviews: sv::Reviews<'a>,
+  pub reconcile: sv::Reconcile<'a>,
+  pub risk: sv::Risk<'a>,
+  pub rating: sv::Rating<'a>,
+  pub session: sv::Session<'a>,
+  pub settings: sv::Settings<'a>,
+  pub sharing: sv::Sharing<'a>,
+  pub signing_keys: sv::SigningKeys<'a>,
   pub balance: sv::Balance<'a>,
   pub payment: sv::Payment<'a>,
   pub cryptobot: Option<&'a sv::cryptobot::CryptoBot>,
@@ -90,16 +351,62 @@ pub struct Services<'a> {
 pub struct AppState {
   pub db: DatabaseConnection,
   pub bot: Bot,
+  /// Storefront bots by product, filled when the Telegram plugin starts
+  pub brand_bots: DashMap<String, Bot>,
   pub admins: HashSet<i64>,
   // TODO: replace this dashmaps with custom wrappers that stores time of expiration
   pub sessions: Sessions,
   pub banned_sessions: BannedSessions,
-  pub download_tokens: DownloadTokens,
+  pub offline_since: OfflineSince,
+  pub rate_windows: RateWindows,
+  /// Last accepted heartbeat by license key and session id
+  pub last_beats: DashMap<(String, String), DateTime>,
+  /// Heartbeats of the whole license in the current pacing interval
+  pub license_beats: DashMap<String, RateWindow>,
+  /// Nonces of signed requests by license key, see `NONCE_WINDOW_SECS`
+  pub nonces: DashMap<(String, String), DateTime>,
+  /// Enforcement violations by license key
+  pub strikes: DashMap<String, RateWindow>,
+  /// API requests by license since the last flush to the daily rollup
+  pub api_usage: DashMap<(String, ApiKind), sv::api_usage::Counts>,
+  /// Refused telemetry by reason and claimed schema since the start
+  pub telemetry_rejects: DashMap<(RejectReason, Option<u32>), u64>,
+  /// Keys with halved rate limits, see `Config::abuse_days`
+  api_abusers: RwLock<HashSet<String>>,
   pub secret: String,
   pub config: Config,
   pub cryptobot: Option<sv::cryptobot::CryptoBot>,
+  /// Offline token signing keys, swapped on rotation
+  keyring: RwLock<Arc<Keyring>>,
+  /// Last public price list and when it was built
+  pricing_cache: RwLock<Option<(DateTime, Arc<Catalog>)>>,
   // Backup deduplication
   backup_hash: AtomicU64,
+  /// Set once the main bot reached Telegram
+  pub bot_ready: AtomicBool,
+  /// Set while /restore swaps the database, writes are refused meanwhile
+  pub maintenance: AtomicBool,
+}
+
+/// What a restored backup holds
+#[derive(Debug)]
+pub struct Restored {
+  pub tables: usize,
+  pub users: u64,
+  pub licenses: u64,
+  /// Snapshot of the database it replaced, None on a dry run
+  pub previous: Option<PathBuf>,
+}
+
+async fn table_names(db: &DatabaseConnection) -> Result<Vec<String>> {
+  let rows = db
+    .query_all(Statement::from_string(
+      sea_orm::DatabaseBackend::Sqlite,
+      "SELECT name FROM sqlite_master \
+      WHERE type = 'table' AND name NOT LIKE 'sqlite_%'",
+    ))
+    .await?;
+  Ok(rows.iter().filter_map(|row| row.try_get_by_index(0).ok()).collect())
 }
 
 // TODO: we need to transactions too
@@ -111,6 +418,7 @@ fn hash_licenses(licenses: &[license::Model]) -> u64 {
     lic.is_blocked.hash(&mut hasher);
     lic.expires_at.and_utc().timestamp().hash(&mut hasher);
     lic.max_sessions.hash(&mut hasher);
+    lic.blocked_until.map(|at| at.and_utc().timestamp()).hash(&mut hasher);
   }
   hasher.finish()
 }
@@ -143,40 +451,398 @@ impl AppState {
     cryptobot: Option<sv::cryptobot::CryptoBot>,
   ) -> Self {
     info!("Connecting to database...");
+    // WAL lets readers run alongside the writer, NORMAL sync is durable
+    // enough with it and spares an fsync per commit
+    let mut options = ConnectOptions::new(db_url);
+    options.map_sqlx_sqlite_opts(|opts| {
+      opts
+        .journal_mode(SqliteJournalMode::Wal)
+        .synchronous(SqliteSynchronous::Normal)
+        .busy_timeout(Duration::from_secs(5))
+    });
     let db =
-      Database::connect(db_url).await.expect("Failed to connect to database");
+      Database::connect(options).await.expect("Failed to connect to database");
 
     info!("Running migrations...");
     Migrator::up(&db, None).await.expect("Failed to run migrations");
 
-    Self {
+    let keyring = sv::SigningKeys::new(&db)
+      .keyring()
+      .await
+      .expect("Failed to load signing keys");
+
+    let state = Self {
       db,
       sessions: DashMap::new(),
       banned_sessions: DashMap::new(),
-      download_tokens: DashMap::new(),
+      offline_since: DashMap::new(),
+      rate_windows: DashMap::new(),
+      last_beats: DashMap::new(),
+      license_beats: DashMap::new(),
+      nonces: DashMap::new(),
+      strikes: DashMap::new(),
+      api_usage: DashMap::new(),
+      telemetry_rejects: DashMap::new(),
+      api_abusers: RwLock::new(HashSet::new()),
       bot: Bot::new(bot_token),
+      brand_bots: DashMap::new(),
       admins,
       secret,
       config,
       cryptobot,
+      keyring: RwLock::new(Arc::new(keyring)),
+      pricing_cache: RwLock::new(None),
       backup_hash: AtomicU64::new(0),
+      bot_ready: AtomicBool::new(false),
+      maintenance: AtomicBool::new(false),
+    };
+
+    match state.restore_sessions().await {
+      Ok(0) => {}
+      Ok(restored) => info!("Restored {} sessions", restored),
+      Err(err) => warn!("Failed to restore sessions: {}", err),
+    }
+    state
+  }
+
+  pub fn keyring(&self) -> Arc<Keyring> {
+    self.keyring.read().unwrap_or_else(|e| e.into_inner()).clone()
+  }
+
+  /// Pick up rotated or retired signing keys
+  pub async fn reload_keyring(&self) -> Result<()> {
+    let keyring = Arc::new(self.sv().signing_keys.keyring().await?);
+    *self.keyring.write().unwrap_or_else(|e| e.into_inner()) = keyring;
+    Ok(())
+  }
+
+  /// Public price list, rebuilt once it is older than `ttl`
+  pub async fn pricing_catalog(&self, ttl: TimeDelta) -> Result<Arc<Catalog>> {
+    let now = Utc::now().naive_utc();
+    let cached = self
+      .pricing_cache
+      .read()
+      .unwrap_or_else(|e| e.into_inner())
+      .as_ref()
+      .filter(|(built_at, _)| now - *built_at < ttl)
+      .map(|(_, catalog)| catalog.clone());
+    if let Some(catalog) = cached {
+      return Ok(catalog);
     }
+
+    let catalog = Arc::new(self.sv().pricing.catalog().await?);
+    *self.pricing_cache.write().unwrap_or_else(|e| e.into_inner()) =
+      Some((now, catalog.clone()));
+    Ok(catalog)
+  }
+
+  /// Bot the user talks to, falls back to the main one
+  pub async fn user_bot(&self, tg_user_id: i64) -> Bot {
+    let storefront = self
+      .sv()
+      .user
+      .by_id(tg_user_id)
+      .await
+      .ok()
+      .flatten()
+      .and_then(|user| user.storefront);
+    storefront
+      .and_then(|product| self.brand_bots.get(&product).map(|bot| bot.clone()))
+      .unwrap_or_else(|| self.bot.clone())
+  }
+
+  /// Bot and stamped text of a message to a user. None on staging unless
+  /// the user is an admin, so a copied database never messages customers.
+  pub async fn reach(
+    &self,
+    tg_user_id: i64,
+    text: impl Into<String>,
+  ) -> Option<(Bot, String)> {
+    if self.config.environment.is_staging()
+      && !self.admins.contains(&tg_user_id)
+    {
+      debug!("Staging, not messaging user {}", tg_user_id);
+      return None;
+    }
+    let text = self.config.environment.stamp(text);
+    Some((self.user_bot(tg_user_id).await, text))
   }
 
   pub fn sv(&self) -> Services<'_> {
     Services {
       user: sv::User::new(&self.db),
+      account: sv::Account::new(&self.db),
+      admin_alerts: sv::AdminAlerts::new(&self.db),
+      admin_op: sv::AdminOp::new(&self.db),
+      announcement: sv::Announcement::new(&self.db),
+      api_token: sv::ApiToken::new(&self.db),
+      api_usage: sv::ApiUsage::new(&self.db),
       stats: sv::Stats::new(&self.db),
+      bot_usage: sv::BotUsage::new(&self.db),
       build: sv::Build::new(&self.db),
-      license: sv::License::new(&self.db),
+      canned: sv::Canned::new(&self.db),
+      config_sync: sv::ConfigSync::new(&self.db),
+      custom_field: sv::CustomField::new(&self.db),
+      device: sv::Device::new(&self.db),
+      download_tokens: sv::DownloadTokens::new(&self.db),
+      eligibility: sv::Eligibility::new(&self.db),
+      error_reports: sv::ErrorReports::new(&self.db),
+      faq: sv::Faq::new(&self.db),
+      feature_flag: sv::FeatureFlag::new(&self.db),
+      freebies: sv::Freebies::new(&self.db),
+      goal: sv::Goal::new(&self.db),
+      hwid_policy: sv::HwidPolicy::new(&self.db),
+      incident: sv::Incident::new(&self.db),
+      license: sv::License::new(&self.db)
+        .capped(self.config.purchase_rules.max_licenses)
+        .with_skew(self.config.clock_skew()),
+      outbox: sv::Outbox::new(&self.db),
+      plan: sv::Plan::new(&self.db),
+      pricing: sv::Pricing::new(&self.db),
+      product: sv::Product::new(&self.db),
+      promo_campaign: sv::PromoCampaign::new(&self.db),
+      promo_code: sv::PromoCode::new(&self.db),
+      promo_kit: sv::PromoKit::new(&self.db),
       steam: sv::Steam::new(&self.db),
+      storefront: sv::Storefront::new(&self.db),
+      terms: sv::Terms::new(&self.db),
+      ticket: sv::Ticket::new(&self.db),
+      upgrade_offers: sv::UpgradeOffers::new(&self.db),
       referral: sv::Referral::new(&self.db),
+      report: sv::Report::new(&self.db),
+      reviews: sv::Reviews::new(&self.db),
+      reconcile: sv::Reconcile::new(&self.db),
+      risk: sv::Risk::new(&self.db),
+      rating: sv::Rating::new(&self.db),
+      session: sv::Session::new(&self.db),
+      settings: sv::Settings::new(&self.db),
+      sharing: sv::Sharing::new(&self.db),
+      signing_keys: sv::SigningKeys::new(&self.db),
       balance: sv::Balance::new(&self.db),
       payment: sv::Payment::new(&self.db),
       cryptobot: self.cryptobot.as_ref(),
     }
   }
 
+  /// Copy the database into the backup directory, encrypted when a backup
+  /// key is set. Returns the file to send, which then carries the `.enc`
+  /// suffix.
+  async fn snapshot(&self, manual: bool) -> anyhow::Result<PathBuf> {
+    let dir = Path::new(&self.config.backup_dir);
+    fs::create_dir_all(dir).await?;
+    let path = dir.join(sv::backup::file_name(manual, Utc::now().naive_utc()));
+    if path.exists() {
+      let _ = fs::remove_file(&path).await;
+    }
+
+    let query =
+      format!("VACUUM INTO '{}'", path.to_string_lossy().replace('\'', "''"));
+    self
+      .db
+      .execute(sea_orm::Statement::from_string(
+        sea_orm::DatabaseBackend::Sqlite,
+        query,
+      ))
+      .await?;
+
+    let Some(key) = &self.config.backup_key else {
+      return Ok(path);
+    };
+    let sealed = fs::read(&path)
+      .await
+      .map_err(anyhow::Error::from)
+      .and_then(|plain| key.seal(&plain).map_err(anyhow::Error::from));
+    // the plain copy must not outlive this call, even on failure
+    let _ = fs::remove_file(&path).await;
+    let mut encrypted = path.into_os_string();
+    encrypted.push(format!(".{}", sv::backup::EXTENSION));
+    let encrypted = PathBuf::from(encrypted);
+    fs::write(&encrypted, sealed?).await?;
+    Ok(encrypted)
+  }
+
+  /// Check an uploaded backup and, with `apply`, replace the live data with
+  /// it. The file must be intact, hold licenses and migrate to this
+  /// version; the live database is snapshotted first and writes are
+  /// refused until the swap is over.
+  pub async fn restore(
+    &self,
+    data: Vec<u8>,
+    apply: bool,
+  ) -> anyhow::Result<Restored> {
+    let plain = sv::backup::unpack(data, self.config.backup_key.as_ref())?;
+    let dir = Path::new(&self.config.backup_dir);
+    fs::create_dir_all(dir).await?;
+    let stamp = Utc::now().format("%Y-%m-%d_%H-%M-%S");
+    let path = dir.join(format!("restore_{}.db", stamp));
+    fs::write(&path, plain).await?;
+
+    let result = self.restore_from(&path, apply).await;
+    for suffix in ["", "-wal", "-shm"] {
+      let mut file = path.clone().into_os_string();
+      file.push(suffix);
+      let _ = fs::remove_file(file).await;
+    }
+    result
+  }
+
+  async fn restore_from(
+    &self,
+    path: &Path,
+    apply: bool,
+  ) -> anyhow::Result<Restored> {
+    let url = format!("sqlite:{}?mode=rw", path.to_string_lossy());
+    let restored = Database::connect(&url).await?;
+    let checked = async {
+      let integrity = restored
+        .query_one(Statement::from_string(
+          sea_orm::DatabaseBackend::Sqlite,
+          "PRAGMA integrity_check",
+        ))
+        .await?
+        .and_then(|row| row.try_get_by_index::<String>(0).ok());
+      anyhow::ensure!(
+        integrity.as_deref() == Some("ok"),
+        "Integrity check failed: {}",
+        integrity.unwrap_or_default()
+      );
+      let tables = table_names(&restored).await?;
+      anyhow::ensure!(
+        tables.iter().any(|table| table == "licenses"),
+        "Not a license database"
+      );
+
+      Migrator::up(&restored, None).await?;
+      Ok(Restored {
+        tables: table_names(&restored).await?.len(),
+        users: crate::entity::user::Entity::find().count(&restored).await?,
+        licenses: license::Entity::find().count(&restored).await?,
+        previous: None,
+      })
+    }
+    .await;
+    restored.close().await?;
+    let mut summary = checked?;
+    if !apply {
+      return Ok(summary);
+    }
+
+    self.maintenance.store(true, Ordering::Relaxed);
+    let swapped = async {
+      let previous = self.snapshot(true).await?;
+      self.swap_database(path).await?;
+      anyhow::Ok(previous)
+    }
+    .await;
+    self.maintenance.store(false, Ordering::Relaxed);
+    summary.previous = Some(swapped?);
+
+    // caches of the old data
+    self.reload_keyring().await?;
+    *self.pricing_cache.write().unwrap_or_else(|e| e.into_inner()) = None;
+    self.backup_hash.store(0, Ordering::Relaxed);
+    Ok(summary)
+  }
+
+  /// Wait out a database swap of /restore, background jobs call it before
+  /// they write
+  pub async fn writable(&self) {
+    while self.maintenance.load(Ordering::Relaxed) {
+      tokio::time::sleep(Duration::from_secs(1)).await;
+    }
+  }
+
+  /// Replace every table's rows with those of the database at `path` in
+  /// one transaction, so readers see either the old or the new data
+  async fn swap_database(&self, path: &Path) -> anyhow::Result<()> {
+    use sea_orm::sqlx::{self, Row};
+
+    // ATTACH is per connection, the whole swap has to stay on this one
+    let pool = self.db.get_sqlite_connection_pool();
+    let mut conn = pool.acquire().await?;
+    sqlx::query("ATTACH DATABASE ? AS restored")
+      .bind(path.to_string_lossy().into_owned())
+      .execute(&mut *conn)
+      .await?;
+
+    let copied = async {
+      sqlx::query("BEGIN IMMEDIATE").execute(&mut *conn).await?;
+      sqlx::query("PRAGMA defer_foreign_keys = ON").execute(&mut *conn).await?;
+      let tables: Vec<String> = sqlx::query(
+        "SELECT name FROM main.sqlite_master \
+        WHERE type = 'table' AND name NOT LIKE 'sqlite_%'",
+      )
+      .fetch_all(&mut *conn)
+      .await?
+      .iter()
+      .map(|row| row.get(0))
+      .collect();
+
+      for table in tables {
+        sqlx::query(&format!("DELETE FROM main.\"{}\"", table))
+          .execute(&mut *conn)
+          .await?;
+        // the restored copy was migrated, so both sides have the columns
+        let columns: Vec<String> =
+          sqlx::query(&format!("PRAGMA restored.table_info(\"{}\")", table))
+            .fetch_all(&mut *conn)
+            .await?
+            .iter()
+            .map(|row| format!("\"{}\"", row.get::<String, _>("name")))
+            .collect();
+        if columns.is_empty() {
+          continue;
+        }
+        let columns = columns.join(", ");
+        sqlx::query(&format!(
+          "INSERT INTO main.\"{table}\" ({columns}) \
+          SELECT {columns} FROM restored.\"{table}\"",
+        ))
+        .execute(&mut *conn)
+        .await?;
+      }
+      sqlx::query("COMMIT").execute(&mut *conn).await?;
+      anyhow::Ok(())
+    }
+    .await;
+    if copied.is_err() {
+      let _ = sqlx::query("ROLLBACK").execute(&mut *conn).await;
+    }
+    let _ = sqlx::query("DETACH DATABASE restored").execute(&mut *conn).await;
+    copied
+  }
+
+  /// Drop snapshots the retention policy no longer wants, locally and in
+  /// the bucket. Returns how many were removed.
+  pub async fn prune_backups(&self) -> anyhow::Result<usize> {
+    let retention = self.config.backup_retention;
+    let dir = Path::new(&self.config.backup_dir);
+    let mut pruned = 0;
+
+    let local = sv::backup::local(dir).await?;
+    for snapshot in retention.expired(&local) {
+      match fs::remove_file(dir.join(&snapshot.name)).await {
+        Ok(()) => pruned += 1,
+        Err(e) => warn!("Failed to prune backup {}: {}", snapshot.name, e),
+      }
+    }
+
+    if let Some(s3) = &self.config.backup_s3 {
+      let remote = s3.list().await?;
+      for snapshot in retention.expired(&remote) {
+        match s3.delete(&snapshot.name).await {
+          Ok(()) => pruned += 1,
+          Err(e) => warn!("Failed to prune S3 backup {}: {}", snapshot.name, e),
+        }
+      }
+    }
+
+    if pruned > 0 {
+      info!("Pruned {} old backup(s)", pruned);
+    }
+    Ok(pruned)
+  }
+
   /// Perform backup only when license data changes.
   /// Changes in metrics/stats tables are not a reason to backup.
   pub async fn perform_smart_backup(&self) -> anyhow::Result<()> {
@@ -198,76 +864,294 @@ impl AppState {
     }
 
     let timestamp = Utc::now().format("%Y-%m-%d_%H-%M-%S");
-    let filename = format!("backup_{}.db", timestamp);
-    let path = Path::new(&filename);
+    let path = self.snapshot(false).await?;
+    let filename = path
+      .file_name()
+      .map(|name| name.to_string_lossy().into_owned())
+      .unwrap_or_default();
 
-    if path.exists() {
-      let _ = fs::remove_file(path).await;
+    let mut caption = format!(
+      "📦 <b>Database Backup</b>\nLicense changes detected.\nTime: {}",
+      timestamp
+    );
+    if let Some(s3) = &self.config.backup_s3 {
+      let uploaded = match fs::read(&path).await {
+        Ok(body) => s3.put(&filename, body).await,
+        Err(e) => Err(e.into()),
+      };
+      match uploaded {
+        Ok(key) => {
+          info!("Backup uploaded to s3://{}/{}", s3.bucket(), key);
+          if let Err(e) = self.prune_backups().await {
+            warn!("Backup pruning failed: {}", e);
+          }
+          return Ok(());
+        }
+        Err(e) => {
+          warn!("S3 backup upload failed, sending to Telegram: {}", e);
+          caption.push_str("\n⚠️ S3 upload failed, check the logs");
+        }
+      }
     }
 
-    let query = format!("VACUUM INTO '{}'", filename);
-    self
-      .db
-      .execute(sea_orm::Statement::from_string(
-        sea_orm::DatabaseBackend::Sqlite,
-        query,
-      ))
-      .await?;
-
+    let caption = self.config.environment.stamp(caption);
     for &admin in self.admins.iter() {
-      let doc = InputFile::file(path);
-      let caption = format!(
-        "📦 <b>Database Backup</b>\nLicense changes detected.\nTime: {}",
-        timestamp
-      );
+      let doc = InputFile::file(&path);
 
       let _ = self
         .bot
         .send_document(ChatId(admin), doc)
-        .caption(caption)
+        .caption(&caption)
         .parse_mode(ParseMode::Html)
         .await;
     }
 
-    let _ = fs::remove_file(path).await;
+    if let Err(e) = self.prune_backups().await {
+      warn!("Backup pruning failed: {}", e);
+    }
     Ok(())
   }
 
-  pub async fn perform_backup(&self, chat_id: ChatId) -> anyhow::Result<()> {
-    let timestamp = Utc::now().format("%Y-%m-%d_%H-%M-%S");
-    let filename = format!("manual_backup_{}.db", timestamp);
+  /// Checks that keep the instance from taking traffic, empty when ready
+  pub async fn unready(&self) -> Vec<&'static str> {
+    let mut failing = Vec::new();
+    if self.db.ping().await.is_err() {
+      failing.push("database");
+    }
+    if Migrator::get_pending_migrations(&self.db)
+      .await
+      .is_ok_and(|pending| !pending.is_empty())
+    {
+      failing.push("migrations");
+    }
+    if !self.bot_ready.load(Ordering::Relaxed) {
+      failing.push("bot");
+    }
+    if self.maintenance.load(Ordering::Relaxed) {
+      failing.push("maintenance");
+    }
+    failing
+  }
 
-    let query = format!("VACUUM INTO '{}'", filename);
-    self
+  /// File behind the main database, None when it lives in memory
+  pub async fn db_path(&self) -> Result<Option<PathBuf>> {
+    let row = self
       .db
-      .execute(sea_orm::Statement::from_string(
-        sea_orm::DatabaseBackend::Sqlite,
-        query,
+      .query_one(Statement::from_string(
+        self.db.get_database_backend(),
+        "PRAGMA database_list",
       ))
       .await?;
+    let file: Option<String> =
+      row.map(|row| row.try_get("", "file")).transpose()?;
+    Ok(file.filter(|file| !file.is_empty()).map(PathBuf::from))
+  }
 
-    let path = Path::new(&filename);
-    let _ = self.bot.send_document(chat_id, InputFile::file(path)).await;
-    let _ = fs::remove_file(path).await;
+  /// Copy the WAL back into the database and truncate it, false if readers
+  /// kept it from finishing
+  pub async fn checkpoint_wal(&self) -> Result<bool> {
+    let row = self
+      .db
+      .query_one(Statement::from_string(
+        self.db.get_database_backend(),
+        "PRAGMA wal_checkpoint(TRUNCATE)",
+      ))
+      .await?;
+    let busy: i32 =
+      row.map(|row| row.try_get_by_index(0)).transpose()?.unwrap_or(0);
+    Ok(busy == 0)
+  }
 
+  /// Send an HTML message to every admin, delivery errors are only logged
+  /// Normal alerts wait out an admin's quiet hours and lose their keyboard
+  /// on the way, critical ones go out right away
+  pub async fn notify_admins(
+    &self,
+    urgency: Urgency,
+    text: &str,
+    keyboard: Option<InlineKeyboardMarkup>,
+  ) {
+    let alerts = sv::AdminAlerts::new(&self.db);
+    let now = Utc::now().naive_utc();
+    for &admin in self.admins.iter() {
+      if urgency == Urgency::Normal {
+        let held = match alerts.is_quiet(admin, now).await {
+          Ok(true) => alerts.hold(admin, text).await.map(|()| true),
+          quiet => quiet,
+        };
+        match held {
+          Ok(true) => continue,
+          Ok(false) => {}
+          Err(e) => warn!("Failed to hold alert for admin {}: {}", admin, e),
+        }
+      }
+
+      let mut request = self
+        .bot
+        .send_message(ChatId(admin), self.config.environment.stamp(text))
+        .parse_mode(ParseMode::Html);
+      if let Some(keyboard) = keyboard.clone() {
+        request = request.reply_markup(keyboard);
+      }
+      if let Err(e) = request.await {
+        warn!("Failed to notify admin {}: {}", admin, e);
+      }
+    }
+  }
+
+  pub async fn perform_backup(&self, chat_id: ChatId) -> anyhow::Result<()> {
+    let path = self.snapshot(true).await?;
+    self.bot.send_document(chat_id, InputFile::file(path)).await?;
     Ok(())
   }
 
-  pub fn gc_sessions(&self) {
+  /// Drop stale sessions, returns keys that have no sessions left
+  pub fn gc_sessions(&self) -> Vec<String> {
     let now = Utc::now().naive_utc();
     let timeout = self.config.session_lifetime;
 
-    self.sessions.retain(|_key, sessions| {
+    let mut emptied = Vec::new();
+    self.sessions.retain(|key, sessions| {
       sessions.retain(|s| (now - s.last_seen).num_seconds() < timeout);
+      if sessions.is_empty() {
+        emptied.push(key.clone());
+      }
       !sessions.is_empty()
     });
+    emptied
+  }
+
+  /// Flush heartbeats of live sessions to the database and close the rows
+  /// of sessions that expired since the last run
+  pub async fn persist_sessions(&self) -> Result<()> {
+    let seen: Vec<_> = self
+      .sessions
+      .iter()
+      .flat_map(|kv| {
+        kv.value()
+          .iter()
+          .map(|s| (s.session_id.clone(), s.last_seen))
+          .collect::<Vec<_>>()
+      })
+      .collect();
+
+    let sv = self.sv();
+    sv.session.touch(&seen).await?;
+    sv.session
+      .expire_stale(TimeDelta::seconds(self.config.session_lifetime))
+      .await?;
+    Ok(())
+  }
+
+  /// Load sessions still open in the database, so clients don't have to
+  /// log in again after a restart. Licenses are checked again on the way.
+  pub async fn restore_sessions(&self) -> Result<usize> {
+    let sv = self.sv();
+    sv.session
+      .expire_stale(TimeDelta::seconds(self.config.session_lifetime))
+      .await?;
+
+    let mut restored = 0;
+    for row in sv.session.active().await? {
+      let license = match sv.license.validate(&row.license_key).await {
+        Ok(license) => license,
+        Err(
+          Error::LicenseNotFound
+          | Error::LicenseInvalid
+          | Error::Honeypot
+          | Error::OutsideSchedule { .. },
+        ) => {
+          sv.session.end(&row.session_id, "invalid").await?;
+          continue;
+        }
+        Err(err) => return Err(err),
+      };
+      let schedule = sv.license.schedule(&license).await.ok().flatten();
+      let features = sv.feature_flag.resolve(&license).await?;
+
+      self.sessions.entry(row.license_key).or_default().push(Session {
+        session_id: row.session_id,
+        hwid_hash: row.hwid,
+        platform: row.platform,
+        ip: row.ip,
+        country: row.country,
+        last_seen: row.last_seen,
+        license_type: license.license_type,
+        schedule,
+        features,
+      });
+      restored += 1;
+    }
+    Ok(restored)
+  }
+
+  /// Close the database rows of sessions that left memory on purpose
+  fn end_sessions(&self, key: &str, session_id: Option<&str>, reason: &str) {
+    let db = self.db.clone();
+    let key = key.to_string();
+    let session_id = session_id.map(str::to_string);
+    let reason = reason.to_string();
+    tokio::spawn(async move {
+      let sv = sv::Session::new(&db);
+      let res = match &session_id {
+        Some(session_id) => sv.end(session_id, &reason).await,
+        None => sv.end_license(&key, &reason).await,
+      };
+      if let Err(err) = res {
+        warn!("Failed to end sessions of {}: {}", key, err);
+      }
+    });
+  }
+
+  pub fn has_sessions(&self, licenses: &[license::Model]) -> bool {
+    licenses.iter().any(|lic| self.sessions.contains_key(&lic.key))
+  }
+
+  /// Push changed feature flags to the live sessions of these licenses
+  pub async fn refresh_features(&self, keys: &[String]) -> Result<()> {
+    let sv = self.sv();
+    for key in keys {
+      if !self.sessions.contains_key(key) {
+        continue;
+      }
+      let Some(license) = sv.license.by_key(key).await? else {
+        continue;
+      };
+      let features = sv.feature_flag.resolve(&license).await?;
+      if let Some(mut sessions) = self.sessions.get_mut(key) {
+        for session in sessions.iter_mut() {
+          session.features = features.clone();
+        }
+      }
+    }
+    Ok(())
   }
 
   pub fn drop_sessions(&self, key: &str) {
-    self.sessions.remove(key);
+    if self.sessions.remove(key).is_some() {
+      self.end_sessions(key, None, "dropped");
+    }
   }
 
   pub fn logout_session(&self, key: &str, session_id: &str) -> bool {
+    self.remove_session(key, session_id, "logout")
+  }
+
+  /// Drop the session of `key` with this `Session::tag`, an admin decision.
+  /// Returns the id of the session kicked.
+  pub fn kick_session(&self, key: &str, tag: &str) -> Option<String> {
+    let session_id = self
+      .sessions
+      .get(key)?
+      .iter()
+      .find(|s| s.tag() == tag)
+      .map(|s| s.session_id.clone())?;
+    self.remove_session(key, &session_id, "kicked").then_some(session_id)
+  }
+
+  /// Forget the session and ban its id for a while, so the client can't
+  /// keep heartbeating with it
+  fn remove_session(&self, key: &str, session_id: &str, reason: &str) -> bool {
     let now = Utc::now().naive_utc();
 
     let mut removed = false;
@@ -283,6 +1167,7 @@ impl AppState {
     }
 
     if removed {
+      self.end_sessions(key, Some(session_id), reason);
       self.banned_sessions.insert(
         session_id.to_string(),
         BannedSession { key: key.to_string(), banned_at: now },
@@ -292,53 +1177,214 @@ impl AppState {
     removed
   }
 
-  pub fn is_session_banned(&self, session_id: &str) -> bool {
+  /// Count a request of `key` against `limit` requests per minute
+  pub fn hit_rate_limit(
+    &self,
+    key: &str,
+    scope: &'static str,
+    limit: u32,
+  ) -> RateStatus {
     let now = Utc::now().naive_utc();
-    let timeout = self.config.banned_session_lifetime;
+    let mut window = self
+      .rate_windows
+      .entry((key.to_string(), scope))
+      .or_insert(RateWindow { started_at: now, count: 0 });
 
-    if let Some(banned) = self.banned_sessions.get(session_id) {
-      return (now - banned.banned_at).num_seconds() < timeout;
+    if (now - window.started_at).num_seconds() >= 60 {
+      *window = RateWindow { started_at: now, count: 0 };
+    }
+
+    let allowed = window.count < limit;
+    if allowed {
+      window.count += 1;
+    }
+
+    RateStatus {
+      allowed,
+      limit,
+      remaining: limit - window.count,
+      reset: 60 - (now - window.started_at).num_seconds(),
     }
-    false
   }
 
-  pub fn gc_banned_sessions(&self) {
+  /// Accept a heartbeat of the session unless the previous one was less
+  /// than `heartbeat_min_interval_secs` ago, then the seconds left to wait.
+  /// Session ids are the client's pick, so the license as a whole gets one
+  /// beat per open session and one to open another within the interval.
+  pub fn pace_heartbeat(&self, key: &str, session_id: &str) -> Option<i64> {
+    let secs = self.config.heartbeat_min_interval_secs;
+    if secs <= 0 {
+      return None;
+    }
+    let interval = TimeDelta::seconds(secs);
+    // round up, retrying after a truncated wait would be refused again
+    let wait = |until: DateTime, now: DateTime| {
+      Some(((until - now).num_milliseconds() + 999) / 1000)
+    };
+
     let now = Utc::now().naive_utc();
-    let timeout = self.config.banned_session_lifetime;
+    let mut last = self
+      .last_beats
+      .entry((key.to_string(), session_id.to_string()))
+      .or_insert(now - interval);
+    let next = *last + interval;
+    if now < next {
+      return wait(next, now);
+    }
+
+    let open = self.sessions.get(key).map_or(0, |sessions| sessions.len());
+    let mut window = self
+      .license_beats
+      .entry(key.to_string())
+      .or_insert(RateWindow { started_at: now, count: 0 });
+    if now - window.started_at >= interval {
+      *window = RateWindow { started_at: now, count: 0 };
+    }
+    if window.count as usize > open {
+      return wait(window.started_at + interval, now);
+    }
+    window.count += 1;
+    *last = now;
+    None
+  }
+
+  /// Count a telemetry payload that was refused or skipped
+  pub fn count_telemetry_reject(
+    &self,
+    reason: RejectReason,
+    schema_version: Option<u32>,
+  ) {
+    *self.telemetry_rejects.entry((reason, schema_version)).or_default() += 1;
+  }
+
+  /// Count a payload `Stats::decode_metric` refused, handing its error back
+  pub fn reject_telemetry(&self, rejected: Rejected) -> Error {
+    self.count_telemetry_reject(rejected.reason, rejected.schema_version);
+    rejected.error
+  }
+
+  /// Record a request nonce of `key`, false if it was already used
+  pub fn remember_nonce(&self, key: &str, nonce: &str) -> bool {
+    let now = Utc::now().naive_utc();
+    self.nonces.insert((key.to_string(), nonce.to_string()), now).is_none()
+  }
 
+  pub fn gc_rate_windows(&self) {
+    let now = Utc::now().naive_utc();
+    self.rate_windows.retain(|_, w| (now - w.started_at).num_seconds() < 60);
+    let lifetime = self.config.session_lifetime;
+    self.last_beats.retain(|_, last| (now - *last).num_seconds() < lifetime);
     self
-      .banned_sessions
-      .retain(|_, bs| (now - bs.banned_at).num_seconds() < timeout);
+      .license_beats
+      .retain(|_, w| (now - w.started_at).num_seconds() < lifetime);
+    // a timestamp may be off by the window either way
+    self
+      .nonces
+      .retain(|_, seen| (now - *seen).num_seconds() < 2 * NONCE_WINDOW_SECS);
+    self
+      .strikes
+      .retain(|_, w| (now - w.started_at).num_seconds() < STRIKE_WINDOW_SECS);
   }
 
-  pub fn create_download_token(&self, version: &str) -> String {
-    let token = Uuid::new_v4().to_string();
+  /// Record an enforcement violation of `key`, returns whether it crossed
+  /// the threshold and the key should be suspended
+  pub fn strike(&self, key: &str) -> bool {
+    let threshold = self.config.suspend_strikes;
+    if threshold == 0 {
+      return false;
+    }
+
     let now = Utc::now().naive_utc();
-    self.download_tokens.insert(
-      token.clone(),
-      DownloadToken { version: version.to_string(), created_at: now },
-    );
-    token
+    let mut window = self
+      .strikes
+      .entry(key.to_string())
+      .or_insert(RateWindow { started_at: now, count: 0 });
+    if (now - window.started_at).num_seconds() >= STRIKE_WINDOW_SECS {
+      *window = RateWindow { started_at: now, count: 0 };
+    }
+    window.count += 1;
+
+    if window.count >= threshold {
+      *window = RateWindow { started_at: now, count: 0 };
+      return true;
+    }
+    false
+  }
+
+  /// Count an API request of `key` towards its daily usage
+  pub fn record_api(&self, key: &str, kind: ApiKind, allowed: bool) {
+    let mut counts = self.api_usage.entry((key.to_string(), kind)).or_default();
+    counts.requests += 1;
+    if !allowed {
+      counts.throttled += 1;
+    }
+  }
+
+  /// Whether the key kept hitting its limits lately
+  pub fn is_abuser(&self, key: &str) -> bool {
+    self.api_abusers.read().unwrap_or_else(|e| e.into_inner()).contains(key)
+  }
+
+  /// Move the counted requests into the daily rollup and refresh the keys
+  /// whose limits are halved
+  pub async fn flush_api_usage(&self) -> Result<()> {
+    let keys: Vec<_> =
+      self.api_usage.iter().map(|entry| entry.key().clone()).collect();
+    let counts: Vec<_> =
+      keys.into_iter().filter_map(|key| self.api_usage.remove(&key)).collect();
+
+    let sv = self.sv();
+    let today = Utc::now().date_naive();
+    if !counts.is_empty() {
+      sv.api_usage.add(today, &counts).await?;
+    }
+    sv.api_usage
+      .prune(today - TimeDelta::days(API_USAGE_RETENTION_DAYS))
+      .await?;
+
+    let abusers = match self.config.abuse_days {
+      0 => HashSet::new(),
+      days => {
+        let since = today - TimeDelta::days(ABUSE_WINDOW_DAYS - 1);
+        sv.api_usage.abusers(since, days as usize).await?.into_iter().collect()
+      }
+    };
+    *self.api_abusers.write().unwrap_or_else(|e| e.into_inner()) = abusers;
+    Ok(())
   }
 
-  pub fn validate_download_token(&self, token: &str) -> Option<String> {
+  pub fn is_session_banned(&self, session_id: &str) -> bool {
     let now = Utc::now().naive_utc();
-    let timeout = self.config.download_token_lifetime;
+    let timeout = self.config.banned_session_lifetime;
 
-    if let Some(dt) = self.download_tokens.get(token)
-      && (now - dt.created_at).num_seconds() < timeout
-    {
-      return Some(dt.version.clone());
+    if let Some(banned) = self.banned_sessions.get(session_id) {
+      return (now - banned.banned_at).num_seconds() < timeout;
     }
-    None
+    false
   }
 
-  pub fn gc_download_tokens(&self) {
+  pub fn gc_banned_sessions(&self) {
     let now = Utc::now().naive_utc();
-    let timeout = self.config.download_token_lifetime;
+    let timeout = self.config.banned_session_lifetime;
 
     self
+      .banned_sessions
+      .retain(|_, bs| (now - bs.banned_at).num_seconds() < timeout);
+  }
+
+  /// One-time link to a build for a user
+  pub async fn create_download_token(
+    &self,
+    tg_user_id: i64,
+    version: &str,
+    platform: Option<&str>,
+  ) -> Result<String> {
+    let lifetime = TimeDelta::seconds(self.config.download_token_lifetime);
+    let token = self
+      .sv()
       .download_tokens
-      .retain(|_, dt| (now - dt.created_at).num_seconds() < timeout);
+      .create(tg_user_id, version, platform, lifetime)
+      .await?;
+    Ok(token.token)
   }
 }
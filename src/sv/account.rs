@@ -0,0 +1,272 @@
+use std::str::FromStr;
+
+use sea_orm::sea_query::Expr;
+
+use crate::{
+  entity::{
+    announcement_read, api_token,
+    custom_field::{self, FieldScope},
+    download, goal, instance_stats, license, license_device, session, stats,
+    ticket_message, user, user_settings,
+  },
+  prelude::*,
+};
+
+/// What happens to the balance left on a deleted account
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RefundPolicy {
+  /// The balance is dropped with the account
+  Forfeit,
+  /// Admins are asked to pay the balance out by hand
+  Manual,
+}
+
+impl FromStr for RefundPolicy {
+  type Err = String;
+
+  fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+    match s.trim() {
+      "forfeit" => Ok(RefundPolicy::Forfeit),
+      "manual" => Ok(RefundPolicy::Manual),
+      other => Err(format!("expected forfeit or manual ('{}')", other)),
+    }
+  }
+}
+
+/// Result of wiping an account
+#[derive(Debug, Clone)]
+pub struct Deleted {
+  pub tg_user_id: i64,
+  /// Keys that were blocked, their sessions should be dropped
+  pub revoked: Vec<String>,
+  /// Balance the account had before it was zeroed
+  pub balance: i64,
+}
+
+pub struct Account<'a> {
+  db: &'a DatabaseConnection,
+}
+
+impl<'a> Account<'a> {
+  pub fn new(db: &'a DatabaseConnection) -> Self {
+    Self { db }
+  }
+
+  /// Start the cooling-off period, returns when it started.
+  /// Asking again keeps the original date.
+  pub async fn request_deletion(&self, tg_user_id: i64) -> Result<DateTime> {
+    let user = user::Entity::find_by_id(tg_user_id)
+      .one(self.db)
+      .await?
+      .ok_or(Error::UserNotFound)?;
+
+    if user.deleted_at.is_some() {
+      return Err(Error::InvalidArgs("Account is already deleted".into()));
+    }
+    if let Some(requested_at) = user.deletion_requested_at {
+      return Ok(requested_at);
+    }
+
+    let now = Utc::now().naive_utc();
+    user::ActiveModel { deletion_requested_at: Set(Some(now)), ..user.into() }
+      .update(self.db)
+      .await?;
+    Ok(now)
+  }
+
+  /// Returns false if no deletion was pending
+  pub async fn cancel_deletion(&self, tg_user_id: i64) -> Result<bool> {
+    let Some(user) = user::Entity::find_by_id(tg_user_id)
+      .one(self.db)
+      .await?
+      .filter(|u| u.deletion_requested_at.is_some() && u.deleted_at.is_none())
+    else {
+      return Ok(false);
+    };
+
+    user::ActiveModel { deletion_requested_at: Set(None), ..user.into() }
+      .update(self.db)
+      .await?;
+    Ok(true)
+  }
+
+  /// Accounts whose cooling-off period is over
+  pub async fn due(&self, cooloff: TimeDelta) -> Result<Vec<user::Model>> {
+    let cutoff = Utc::now().naive_utc() - cooloff;
+    Ok(
+      user::Entity::find()
+        .filter(user::Column::DeletionRequestedAt.lte(cutoff))
+        .filter(user::Column::DeletedAt.is_null())
+        .all(self.db)
+        .await?,
+    )
+  }
+
+  /// Revoke licenses and erase everything personal about the user.
+  /// The user row and transactions stay for accounting, stripped of data.
+  pub async fn anonymize(&self, tg_user_id: i64) -> Result<Deleted> {
+    let txn = self.db.begin().await?;
+
+    let user = user::Entity::find_by_id(tg_user_id)
+      .one(&txn)
+      .await?
+      .ok_or(Error::UserNotFound)?;
+
+    let revoked: Vec<String> = license::Entity::find()
+      .filter(license::Column::TgUserId.eq(tg_user_id))
+      .filter(license::Column::IsBlocked.eq(false))
+      .all(&txn)
+      .await?
+      .into_iter()
+      .map(|l| l.key)
+      .collect();
+    license::Entity::update_many()
+      .col_expr(license::Column::IsBlocked, true.into())
+      .filter(license::Column::TgUserId.eq(tg_user_id))
+      .exec(&txn)
+      .await?;
+
+    let keys: Vec<String> = license::Entity::find()
+      .filter(license::Column::TgUserId.eq(tg_user_id))
+      .all(&txn)
+      .await?
+      .into_iter()
+      .map(|l| l.key)
+      .collect();
+    license_device::Entity::delete_many()
+      .filter(license_device::Column::LicenseKey.is_in(keys.clone()))
+      .exec(&txn)
+      .await?;
+    // the session history stays for the stats, without the machine and
+    // where it was
+    session::Entity::update_many()
+      .col_expr(session::Column::Hwid, Expr::value(None::<String>))
+      .col_expr(session::Column::Ip, Expr::value(None::<String>))
+      .col_expr(session::Column::Country, Expr::value(None::<String>))
+      .filter(session::Column::LicenseKey.is_in(keys))
+      .exec(&txn)
+      .await?;
+    download::Entity::update_many()
+      .col_expr(download::Column::Ip, Expr::value(None::<String>))
+      .filter(download::Column::TgUserId.eq(tg_user_id))
+      .exec(&txn)
+      .await?;
+
+    api_token::Entity::delete_many()
+      .filter(api_token::Column::TgUserId.eq(tg_user_id))
+      .exec(&txn)
+      .await?;
+    instance_stats::Entity::delete_many()
+      .filter(instance_stats::Column::TgUserId.eq(tg_user_id))
+      .exec(&txn)
+      .await?;
+    stats::Entity::delete_many()
+      .filter(stats::Column::TgUserId.eq(tg_user_id))
+      .exec(&txn)
+      .await?;
+    goal::Entity::delete_many()
+      .filter(goal::Column::TgUserId.eq(tg_user_id))
+      .exec(&txn)
+      .await?;
+    announcement_read::Entity::delete_many()
+      .filter(announcement_read::Column::TgUserId.eq(tg_user_id))
+      .exec(&txn)
+      .await?;
+    user_settings::Entity::delete_by_id(tg_user_id).exec(&txn).await?;
+    custom_field::Entity::delete_many()
+      .filter(custom_field::Column::Scope.eq(FieldScope::User))
+      .filter(custom_field::Column::Target.eq(tg_user_id.to_string()))
+      .exec(&txn)
+      .await?;
+
+    ticket_message::Entity::update_many()
+      .col_expr(ticket_message::Column::Text, "[deleted]".into())
+      .filter(ticket_message::Column::AuthorId.eq(tg_user_id))
+      .filter(ticket_message::Column::FromAdmin.eq(false))
+      .exec(&txn)
+      .await?;
+
+    let balance = user.balance;
+    user::ActiveModel {
+      balance: Set(0),
+      referral_code: Set(None),
+      deletion_requested_at: Set(None),
+      deleted_at: Set(Some(Utc::now().naive_utc())),
+      ..user.into()
+    }
+    .update(&txn)
+    .await?;
+
+    txn.commit().await?;
+    Ok(Deleted { tg_user_id, revoked, balance })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{
+    entity::LicenseType,
+    sv::{self, test_utils::test_db},
+  };
+
+  #[tokio::test]
+  async fn test_deletion_after_cooloff() {
+    let db = test_db::setup().await;
+    let sv = Account::new(&db);
+
+    let license =
+      sv::License::new(&db).create(1, LicenseType::Pro, 30).await.unwrap();
+    sv::Balance::new(&db).deposit(1, 500, None).await.unwrap();
+    sv::Session::new(&db)
+      .start(
+        &license.key,
+        "s1",
+        Some("hwid"),
+        None,
+        Some("1.2.3.4"),
+        Some("DE"),
+      )
+      .await
+      .unwrap();
+    sv::ApiToken::new(&db).generate(1, None).await.unwrap();
+    let tokens = sv::DownloadTokens::new(&db);
+    let token =
+      tokens.create(1, "1.0.0", None, TimeDelta::minutes(10)).await.unwrap();
+    let download = tokens
+      .record(&token, &license.product, Some("1.2.3.4".into()))
+      .await
+      .unwrap();
+
+    let requested = sv.request_deletion(1).await.unwrap();
+    assert_eq!(sv.request_deletion(1).await.unwrap(), requested);
+    assert!(sv.due(TimeDelta::days(7)).await.unwrap().is_empty());
+
+    assert!(sv.cancel_deletion(1).await.unwrap());
+    assert!(!sv.cancel_deletion(1).await.unwrap());
+
+    sv.request_deletion(1).await.unwrap();
+    let due = sv.due(TimeDelta::zero()).await.unwrap();
+    assert_eq!(due.len(), 1);
+
+    let deleted = sv.anonymize(1).await.unwrap();
+    assert_eq!(deleted.revoked, vec![license.key.clone()]);
+    assert_eq!(deleted.balance, 500);
+
+    assert!(matches!(
+      sv::License::new(&db).validate(&license.key).await,
+      Err(Error::LicenseInvalid)
+    ));
+    assert!(sv::ApiToken::new(&db).by_user(1).await.unwrap().is_none());
+    let session = session::Entity::find().one(&db).await.unwrap().unwrap();
+    assert_eq!((session.hwid, session.ip, session.country), (None, None, None));
+    let download = download::Entity::find_by_id(download.id)
+      .one(&db)
+      .await
+      .unwrap()
+      .unwrap();
+    assert_eq!(download.ip, None);
+    assert!(sv.due(TimeDelta::zero()).await.unwrap().is_empty());
+    assert!(sv.request_deletion(1).await.is_err());
+  }
+}
@@ -0,0 +1,186 @@
+use chrono::Timelike;
+
+use crate::{
+  entity::{held_alert, user_settings},
+  prelude::*,
+};
+
+/// Telegram refuses longer messages, held alerts are batched below it
+const MESSAGE_MAX: usize = 4000;
+
+/// How an admin alert treats quiet hours
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Urgency {
+  /// Payment failures, security and integrity problems: always delivered
+  Critical,
+  /// Tickets, ratings, reports, reminders: held until the quiet hours end
+  Normal,
+}
+
+/// Whether `now` (UTC) falls into the admin's quiet hours
+pub fn is_quiet(settings: &user_settings::Model, now: DateTime) -> bool {
+  let (Some(from), Some(to)) =
+    (settings.quiet_from_hour, settings.quiet_to_hour)
+  else {
+    return false;
+  };
+  let local = now + TimeDelta::minutes(settings.utc_offset_mins as i64);
+  let hour = local.hour() as i32;
+  if from <= to {
+    (from..to).contains(&hour)
+  } else {
+    hour >= from || hour < to
+  }
+}
+
+/// Held alerts as few messages as fit, oldest first
+pub fn digest(alerts: &[held_alert::Model]) -> Vec<String> {
+  let mut messages = Vec::new();
+  let mut text =
+    format!("🌅 <b>{} alert(s) held during quiet hours</b>", alerts.len());
+  for alert in alerts {
+    let entry = format!(
+      "\n\n<i>{}</i>\n{}",
+      alert.created_at.format("%Y-%m-%d %H:%M UTC"),
+      alert.text
+    );
+    // alerts are never split, their HTML tags must stay balanced
+    if !text.is_empty() && text.len() + entry.len() > MESSAGE_MAX {
+      messages.push(std::mem::take(&mut text));
+    }
+    if text.is_empty() {
+      text.push_str(entry.trim_start());
+    } else {
+      text.push_str(&entry);
+    }
+  }
+  if !text.is_empty() {
+    messages.push(text);
+  }
+  messages
+}
+
+pub struct AdminAlerts<'a> {
+  db: &'a DatabaseConnection,
+}
+
+impl<'a> AdminAlerts<'a> {
+  pub fn new(db: &'a DatabaseConnection) -> Self {
+    Self { db }
+  }
+
+  pub async fn is_quiet(&self, tg_user_id: i64, now: DateTime) -> Result<bool> {
+    let settings =
+      user_settings::Entity::find_by_id(tg_user_id).one(self.db).await?;
+    Ok(settings.is_some_and(|settings| is_quiet(&settings, now)))
+  }
+
+  pub async fn hold(&self, tg_user_id: i64, text: &str) -> Result<()> {
+    held_alert::ActiveModel {
+      id: NotSet,
+      tg_user_id: Set(tg_user_id),
+      text: Set(text.to_string()),
+      created_at: Set(Utc::now().naive_utc()),
+    }
+    .insert(self.db)
+    .await?;
+    Ok(())
+  }
+
+  /// Admins with alerts waiting
+  pub async fn holders(&self) -> Result<Vec<i64>> {
+    Ok(
+      held_alert::Entity::find()
+        .select_only()
+        .column(held_alert::Column::TgUserId)
+        .distinct()
+        .into_tuple()
+        .all(self.db)
+        .await?,
+    )
+  }
+
+  pub async fn held(&self, tg_user_id: i64) -> Result<Vec<held_alert::Model>> {
+    Ok(
+      held_alert::Entity::find()
+        .filter(held_alert::Column::TgUserId.eq(tg_user_id))
+        .order_by_asc(held_alert::Column::Id)
+        .all(self.db)
+        .await?,
+    )
+  }
+
+  /// Forget alerts once they were delivered
+  pub async fn release(&self, alerts: &[held_alert::Model]) -> Result<()> {
+    held_alert::Entity::delete_many()
+      .filter(held_alert::Column::Id.is_in(alerts.iter().map(|a| a.id)))
+      .exec(self.db)
+      .await?;
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::sv::{self, test_utils::test_db};
+
+  #[tokio::test]
+  async fn test_quiet_hours() {
+    let db = test_db::setup().await;
+    let alerts = AdminAlerts::new(&db);
+    let at = |h: u32| {
+      chrono::NaiveDate::from_ymd_opt(2026, 3, 1)
+        .unwrap()
+        .and_hms_opt(h, 30, 0)
+        .unwrap()
+    };
+
+    // no settings, nothing is held
+    assert!(!alerts.is_quiet(1, at(3)).await.unwrap());
+
+    // 23-08 in UTC+3 is 20:00-05:00 UTC
+    let settings = sv::Settings::new(&db);
+    settings.set_utc_offset(1, 180).await.unwrap();
+    settings.set_quiet_hours(1, Some((23, 8))).await.unwrap();
+    assert!(alerts.is_quiet(1, at(20)).await.unwrap());
+    assert!(alerts.is_quiet(1, at(1)).await.unwrap());
+    assert!(!alerts.is_quiet(1, at(5)).await.unwrap());
+    assert!(!alerts.is_quiet(1, at(19)).await.unwrap());
+
+    settings.set_quiet_hours(1, None).await.unwrap();
+    assert!(!alerts.is_quiet(1, at(1)).await.unwrap());
+  }
+
+  #[tokio::test]
+  async fn test_hold_and_release() {
+    let db = test_db::setup().await;
+    let alerts = AdminAlerts::new(&db);
+
+    alerts.hold(1, "<b>first</b>").await.unwrap();
+    alerts.hold(1, "second").await.unwrap();
+    alerts.hold(2, "other admin").await.unwrap();
+    let mut holders = alerts.holders().await.unwrap();
+    holders.sort();
+    assert_eq!(holders, [1, 2]);
+
+    let held = alerts.held(1).await.unwrap();
+    let messages = digest(&held);
+    assert_eq!(messages.len(), 1);
+    assert!(messages[0].starts_with("🌅 <b>2 alert(s)"));
+    assert!(messages[0].find("first") < messages[0].find("second"));
+
+    alerts.release(&held).await.unwrap();
+    assert!(alerts.held(1).await.unwrap().is_empty());
+    assert_eq!(alerts.holders().await.unwrap(), [2]);
+
+    // long batches are split between alerts
+    let long = "x".repeat(1500);
+    for _ in 0..5 {
+      alerts.hold(3, &long).await.unwrap();
+    }
+    let messages = digest(&alerts.held(3).await.unwrap());
+    assert_eq!(messages.len(), 3);
+    assert!(messages.iter().all(|m| m.len() <= MESSAGE_MAX));
+  }
+}
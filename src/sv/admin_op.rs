@@ -0,0 +1,200 @@
+use crate::{
+  entity::{
+    TransactionType,
+    admin_op::{self, AdminOpKind},
+    transaction, user,
+  },
+  prelude::*,
+  sv,
+};
+
+/// Operations listed by `/undo` without arguments
+pub const HISTORY: u64 = 10;
+
+pub struct AdminOp<'a> {
+  db: &'a DatabaseConnection,
+}
+
+impl<'a> AdminOp<'a> {
+  pub fn new(db: &'a DatabaseConnection) -> Self {
+    Self { db }
+  }
+
+  /// Credit the user and remember who did it, returns the new balance
+  pub async fn deposit(
+    &self,
+    admin_id: i64,
+    user_id: i64,
+    amount: i64,
+  ) -> Result<(admin_op::Model, i64)> {
+    let txn = self.db.begin().await?;
+    let balance = sv::Balance::credit_in(
+      &txn,
+      user_id,
+      amount,
+      TransactionType::Deposit,
+      Some("Admin deposit".into()),
+    )
+    .await?;
+    let op =
+      Self::record(&txn, admin_id, user_id, AdminOpKind::Deposit, amount)
+        .await?;
+    txn.commit().await?;
+    Ok((op, balance))
+  }
+
+  /// Pay out the user's balance and remember who did it
+  pub async fn withdraw(
+    &self,
+    admin_id: i64,
+    user_id: i64,
+    amount: i64,
+  ) -> Result<(admin_op::Model, i64)> {
+    let txn = self.db.begin().await?;
+    let balance = sv::Balance::withdraw_in(&txn, user_id, amount).await?;
+    let op =
+      Self::record(&txn, admin_id, user_id, AdminOpKind::Withdraw, amount)
+        .await?;
+    txn.commit().await?;
+    Ok((op, balance))
+  }
+
+  /// The operation as `/undo` finds it, written with the balance change
+  async fn record(
+    txn: &sea_orm::DatabaseTransaction,
+    admin_id: i64,
+    user_id: i64,
+    kind: AdminOpKind,
+    amount: i64,
+  ) -> Result<admin_op::Model> {
+    let op = admin_op::ActiveModel {
+      id: NotSet,
+      admin_id: Set(admin_id),
+      user_id: Set(user_id),
+      kind: Set(kind),
+      amount: Set(amount),
+      created_at: Set(Utc::now().naive_utc()),
+      undone_by: Set(None),
+      undone_at: Set(None),
+    };
+    Ok(op.insert(txn).await?)
+  }
+
+  /// Latest operations, newest first
+  pub async fn recent(&self, limit: u64) -> Result<Vec<admin_op::Model>> {
+    Ok(
+      admin_op::Entity::find()
+        .order_by_desc(admin_op::Column::Id)
+        .limit(limit)
+        .all(self.db)
+        .await?,
+    )
+  }
+
+  /// Reverse an operation younger than `window` with a compensating
+  /// transaction, returns the user's new balance
+  pub async fn undo(
+    &self,
+    op_id: i32,
+    admin_id: i64,
+    window: TimeDelta,
+  ) -> Result<(admin_op::Model, i64)> {
+    let txn = self.db.begin().await?;
+
+    let op = admin_op::Entity::find_by_id(op_id).one(&txn).await?.ok_or_else(
+      || Error::InvalidArgs(format!("Operation #{} not found", op_id)),
+    )?;
+    if op.undone_at.is_some() {
+      return Err(Error::InvalidArgs(format!(
+        "Operation #{} is already undone",
+        op_id
+      )));
+    }
+    let now = Utc::now().naive_utc();
+    if now - op.created_at > window {
+      return Err(Error::InvalidArgs(format!(
+        "Operation #{} is too old to undo",
+        op_id
+      )));
+    }
+
+    let user = user::Entity::find_by_id(op.user_id)
+      .one(&txn)
+      .await?
+      .ok_or(Error::UserNotFound)?;
+    let (delta, what) = match op.kind {
+      AdminOpKind::Deposit => (-op.amount, "deposit"),
+      AdminOpKind::Withdraw => (op.amount, "withdrawal"),
+    };
+    // the deposit may have been spent already
+    if user.balance + delta < 0 {
+      return Err(Error::InsufficientBalance);
+    }
+    let new_balance = user.balance + delta;
+
+    user::ActiveModel { balance: Set(new_balance), ..user.into() }
+      .update(&txn)
+      .await?;
+
+    transaction::ActiveModel {
+      id: NotSet,
+      user_id: Set(op.user_id),
+      amount: Set(delta),
+      tx_type: Set(TransactionType::Reversal),
+      description: Set(Some(format!("Undo of admin {} #{}", what, op.id))),
+      referrer_id: Set(None),
+      region: Set(None),
+      refund_of: Set(None),
+      created_at: Set(now),
+    }
+    .insert(&txn)
+    .await?;
+
+    let op = admin_op::ActiveModel {
+      undone_by: Set(Some(admin_id)),
+      undone_at: Set(Some(now)),
+      ..op.into()
+    }
+    .update(&txn)
+    .await?;
+
+    txn.commit().await?;
+    Ok((op, new_balance))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{entity::LicenseType, sv::test_utils::test_db};
+
+  #[tokio::test]
+  async fn test_undo_balance_ops() {
+    let db = test_db::setup().await;
+    let sv = AdminOp::new(&db);
+    let window = TimeDelta::hours(1);
+
+    sv::License::new(&db).create(1, LicenseType::Pro, 30).await.unwrap();
+    let (deposit, balance) = sv.deposit(99, 1, 5000).await.unwrap();
+    assert_eq!(balance, 5000);
+    sv::Balance::new(&db).spend(1, 3000, None, None).await.unwrap();
+
+    // most of the deposit is gone, so it can not be taken back
+    assert!(matches!(
+      sv.undo(deposit.id, 99, window).await,
+      Err(Error::InsufficientBalance)
+    ));
+    sv.deposit(99, 1, 3000).await.unwrap();
+    let (undone, balance) = sv.undo(deposit.id, 42, window).await.unwrap();
+    assert_eq!((balance, undone.undone_by), (0, Some(42)));
+    assert!(sv.undo(deposit.id, 42, window).await.is_err());
+
+    let txs = sv::Balance::new(&db).transactions(1, 10).await.unwrap();
+    assert!(txs.iter().any(|tx| tx.tx_type == TransactionType::Reversal
+      && tx.amount == -5000));
+
+    let (last, _) = sv.deposit(99, 1, 1000).await.unwrap();
+    assert!(sv.undo(last.id, 42, TimeDelta::zero() - window).await.is_err());
+    assert_eq!(sv.recent(HISTORY).await.unwrap()[0].id, last.id);
+  }
+}
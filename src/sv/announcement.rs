@@ -0,0 +1,178 @@
+use std::collections::HashSet;
+
+use sea_orm::sea_query::OnConflict;
+
+use crate::{
+  entity::{
+    announcement::{self, AnnouncementCategory},
+    announcement_read,
+  },
+  prelude::*,
+  sv,
+};
+
+pub struct Announcement<'a> {
+  db: &'a DatabaseConnection,
+}
+
+impl<'a> Announcement<'a> {
+  pub fn new(db: &'a DatabaseConnection) -> Self {
+    Self { db }
+  }
+
+  pub async fn create(
+    &self,
+    category: AnnouncementCategory,
+    title: &str,
+    body: &str,
+  ) -> Result<announcement::Model> {
+    Ok(
+      announcement::ActiveModel {
+        id: NotSet,
+        category: Set(category),
+        title: Set(title.to_string()),
+        body: Set(body.to_string()),
+        created_at: Set(Utc::now().naive_utc()),
+      }
+      .insert(self.db)
+      .await?,
+    )
+  }
+
+  pub async fn remove(&self, id: i32) -> Result<()> {
+    let res = announcement::Entity::delete_by_id(id).exec(self.db).await?;
+    if res.rows_affected == 0 {
+      return Err(Error::InvalidArgs(format!(
+        "Announcement #{} not found",
+        id
+      )));
+    }
+    Ok(())
+  }
+
+  pub async fn by_id(&self, id: i32) -> Result<Option<announcement::Model>> {
+    Ok(announcement::Entity::find_by_id(id).one(self.db).await?)
+  }
+
+  /// Latest announcements with a read flag for the user
+  pub async fn inbox(
+    &self,
+    tg_user_id: i64,
+    limit: u64,
+  ) -> Result<Vec<(announcement::Model, bool)>> {
+    let items = announcement::Entity::find()
+      .order_by_desc(announcement::Column::Id)
+      .limit(limit)
+      .all(self.db)
+      .await?;
+
+    let read = self.read_ids(tg_user_id).await?;
+    Ok(
+      items
+        .into_iter()
+        .map(|item| {
+          let is_read = read.contains(&item.id);
+          (item, is_read)
+        })
+        .collect(),
+    )
+  }
+
+  pub async fn unread_count(&self, tg_user_id: i64) -> Result<u64> {
+    let total = announcement::Entity::find().count(self.db).await?;
+    let read = announcement_read::Entity::find()
+      .filter(announcement_read::Column::TgUserId.eq(tg_user_id))
+      .count(self.db)
+      .await?;
+    Ok(total.saturating_sub(read))
+  }
+
+  pub async fn mark_read(&self, tg_user_id: i64, id: i32) -> Result<()> {
+    self.mark_many(tg_user_id, vec![id]).await
+  }
+
+  pub async fn mark_all_read(&self, tg_user_id: i64) -> Result<()> {
+    let read = self.read_ids(tg_user_id).await?;
+    let unread: Vec<i32> = announcement::Entity::find()
+      .all(self.db)
+      .await?
+      .into_iter()
+      .map(|item| item.id)
+      .filter(|id| !read.contains(id))
+      .collect();
+    self.mark_many(tg_user_id, unread).await
+  }
+
+  async fn read_ids(&self, tg_user_id: i64) -> Result<HashSet<i32>> {
+    Ok(
+      announcement_read::Entity::find()
+        .filter(announcement_read::Column::TgUserId.eq(tg_user_id))
+        .all(self.db)
+        .await?
+        .into_iter()
+        .map(|read| read.announcement_id)
+        .collect(),
+    )
+  }
+
+  async fn mark_many(&self, tg_user_id: i64, ids: Vec<i32>) -> Result<()> {
+    if ids.is_empty() {
+      return Ok(());
+    }
+
+    sv::User::new(self.db).get_or_create(tg_user_id).await?;
+
+    let now = Utc::now().naive_utc();
+    let reads = ids.into_iter().map(|id| announcement_read::ActiveModel {
+      tg_user_id: Set(tg_user_id),
+      announcement_id: Set(id),
+      read_at: Set(now),
+    });
+
+    announcement_read::Entity::insert_many(reads)
+      .on_conflict(
+        OnConflict::columns([
+          announcement_read::Column::TgUserId,
+          announcement_read::Column::AnnouncementId,
+        ])
+        .do_nothing()
+        .to_owned(),
+      )
+      .do_nothing()
+      .exec(self.db)
+      .await?;
+
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::sv::test_utils::test_db;
+
+  #[tokio::test]
+  async fn test_read_markers() {
+    let db = test_db::setup().await;
+    let sv = Announcement::new(&db);
+
+    let first =
+      sv.create(AnnouncementCategory::Release, "v1", "notes").await.unwrap();
+    sv.create(AnnouncementCategory::Offer, "sale", "-50%").await.unwrap();
+    assert_eq!(sv.unread_count(1).await.unwrap(), 2);
+
+    sv.mark_read(1, first.id).await.unwrap();
+    sv.mark_read(1, first.id).await.unwrap();
+    assert_eq!(sv.unread_count(1).await.unwrap(), 1);
+
+    let inbox = sv.inbox(1, 10).await.unwrap();
+    assert_eq!(inbox.len(), 2);
+    // newest first
+    assert!(!inbox[0].1);
+    assert!(inbox[1].1);
+
+    sv.mark_all_read(1).await.unwrap();
+    assert_eq!(sv.unread_count(1).await.unwrap(), 0);
+    assert_eq!(sv.unread_count(2).await.unwrap(), 2);
+  }
+}
@@ -0,0 +1,110 @@
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use crate::{entity::api_token, prelude::*, sv};
+
+/// Prefix makes leaked tokens easy to recognize in logs and scanners
+const TOKEN_PREFIX: &str = "yacsp_";
+
+pub struct ApiToken<'a> {
+  db: &'a DatabaseConnection,
+}
+
+fn hash_token(token: &str) -> String {
+  hex::encode(Sha256::digest(token.as_bytes()))
+}
+
+impl<'a> ApiToken<'a> {
+  pub fn new(db: &'a DatabaseConnection) -> Self {
+    Self { db }
+  }
+
+  pub async fn by_user(
+    &self,
+    tg_user_id: i64,
+  ) -> Result<Option<api_token::Model>> {
+    Ok(api_token::Entity::find_by_id(tg_user_id).one(self.db).await?)
+  }
+
+  /// Issue a new token, invalidating the previous one.
+  /// The plain token is returned only here and never stored.
+  pub async fn generate(
+    &self,
+    tg_user_id: i64,
+    product: Option<String>,
+  ) -> Result<String> {
+    sv::User::new(self.db).get_or_create(tg_user_id).await?;
+    if let Some(product) = &product {
+      sv::Product::new(self.db).get(product).await?;
+    }
+
+    let token = format!("{}{}", TOKEN_PREFIX, Uuid::new_v4().simple());
+    let now = Utc::now().naive_utc();
+
+    let txn = self.db.begin().await?;
+    api_token::Entity::delete_by_id(tg_user_id).exec(&txn).await?;
+    api_token::ActiveModel {
+      tg_user_id: Set(tg_user_id),
+      token_hash: Set(hash_token(&token)),
+      created_at: Set(now),
+      last_used_at: Set(None),
+      product: Set(product),
+    }
+    .insert(&txn)
+    .await?;
+    txn.commit().await?;
+
+    Ok(token)
+  }
+
+  /// Returns false if the user had no token
+  pub async fn revoke(&self, tg_user_id: i64) -> Result<bool> {
+    let res = api_token::Entity::delete_by_id(tg_user_id).exec(self.db).await?;
+    Ok(res.rows_affected > 0)
+  }
+
+  /// Resolve a token to its record, which holds the owner and product scope
+  pub async fn authenticate(&self, token: &str) -> Result<api_token::Model> {
+    let record = api_token::Entity::find()
+      .filter(api_token::Column::TokenHash.eq(hash_token(token)))
+      .one(self.db)
+      .await?
+      .ok_or(Error::Unauthorized)?;
+
+    Ok(
+      api_token::ActiveModel {
+        last_used_at: Set(Some(Utc::now().naive_utc())),
+        ..record.into()
+      }
+      .update(self.db)
+      .await?,
+    )
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::sv::test_utils::test_db;
+
+  #[tokio::test]
+  async fn test_regenerate_invalidates_old_token() {
+    let db = test_db::setup().await;
+    let sv = ApiToken::new(&db);
+
+    let old = sv.generate(12345, None).await.unwrap();
+    assert!(old.starts_with(TOKEN_PREFIX));
+    assert_eq!(sv.authenticate(&old).await.unwrap().tg_user_id, 12345);
+    assert!(sv.by_user(12345).await.unwrap().unwrap().last_used_at.is_some());
+
+    let new = sv.generate(12345, Some("default".into())).await.unwrap();
+    assert!(matches!(sv.authenticate(&old).await, Err(Error::Unauthorized)));
+    let owner = sv.authenticate(&new).await.unwrap();
+    assert_eq!(owner.tg_user_id, 12345);
+    assert_eq!(owner.product.as_deref(), Some("default"));
+    assert!(sv.generate(12345, Some("nope".into())).await.is_err());
+
+    assert!(sv.revoke(12345).await.unwrap());
+    assert!(sv.authenticate(&new).await.is_err());
+  }
+}
@@ -0,0 +1,210 @@
+use std::collections::HashMap;
+
+use chrono::NaiveDate;
+use sea_orm::sea_query::{Expr, OnConflict};
+
+use crate::{
+  entity::api_usage::{self, ApiKind},
+  prelude::*,
+};
+
+/// Requests counted in memory since the last flush
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Counts {
+  pub requests: i64,
+  pub throttled: i64,
+}
+
+/// Requests of one license over a period
+#[derive(Debug, Clone)]
+pub struct Talker {
+  pub license_key: String,
+  pub requests: i64,
+  pub throttled: i64,
+}
+
+pub struct ApiUsage<'a> {
+  db: &'a DatabaseConnection,
+}
+
+impl<'a> ApiUsage<'a> {
+  pub fn new(db: &'a DatabaseConnection) -> Self {
+    Self { db }
+  }
+
+  /// Add counts to the rollup of `day`
+  pub async fn add(
+    &self,
+    day: NaiveDate,
+    counts: &[((String, ApiKind), Counts)],
+  ) -> Result<()> {
+    let txn = self.db.begin().await?;
+    for ((key, kind), counts) in counts {
+      api_usage::Entity::insert(api_usage::ActiveModel {
+        license_key: Set(key.clone()),
+        day: Set(day),
+        kind: Set(*kind),
+        requests: Set(counts.requests),
+        throttled: Set(counts.throttled),
+      })
+      .on_conflict(
+        OnConflict::columns([
+          api_usage::Column::LicenseKey,
+          api_usage::Column::Day,
+          api_usage::Column::Kind,
+        ])
+        .value(
+          api_usage::Column::Requests,
+          Expr::col(api_usage::Column::Requests).add(counts.requests),
+        )
+        .value(
+          api_usage::Column::Throttled,
+          Expr::col(api_usage::Column::Throttled).add(counts.throttled),
+        )
+        .to_owned(),
+      )
+      .exec_without_returning(&txn)
+      .await?;
+    }
+    txn.commit().await?;
+    Ok(())
+  }
+
+  /// Licenses with the most requests from `since` on
+  pub async fn top(&self, since: NaiveDate, limit: u64) -> Result<Vec<Talker>> {
+    let rows: Vec<(String, i64, i64)> = api_usage::Entity::find()
+      .select_only()
+      .column(api_usage::Column::LicenseKey)
+      .column_as(api_usage::Column::Requests.sum(), "requests")
+      .column_as(api_usage::Column::Throttled.sum(), "throttled")
+      .filter(api_usage::Column::Day.gte(since))
+      .group_by(api_usage::Column::LicenseKey)
+      .order_by_desc(api_usage::Column::Requests.sum())
+      .limit(limit)
+      .into_tuple()
+      .all(self.db)
+      .await?;
+    Ok(
+      rows
+        .into_iter()
+        .map(|(license_key, requests, throttled)| Talker {
+          license_key,
+          requests,
+          throttled,
+        })
+        .collect(),
+    )
+  }
+
+  /// Rollup rows of one license from `since` on, newest day first
+  pub async fn history(
+    &self,
+    key: &str,
+    since: NaiveDate,
+  ) -> Result<Vec<api_usage::Model>> {
+    Ok(
+      api_usage::Entity::find()
+        .filter(api_usage::Column::LicenseKey.eq(key))
+        .filter(api_usage::Column::Day.gte(since))
+        .order_by_desc(api_usage::Column::Day)
+        .order_by_asc(api_usage::Column::Kind)
+        .all(self.db)
+        .await?,
+    )
+  }
+
+  /// Licenses throttled on at least `min_days` days from `since` on
+  pub async fn abusers(
+    &self,
+    since: NaiveDate,
+    min_days: usize,
+  ) -> Result<Vec<String>> {
+    let rows: Vec<(String, NaiveDate)> = api_usage::Entity::find()
+      .select_only()
+      .column(api_usage::Column::LicenseKey)
+      .column(api_usage::Column::Day)
+      .filter(api_usage::Column::Day.gte(since))
+      .filter(api_usage::Column::Throttled.gt(0))
+      .distinct()
+      .into_tuple()
+      .all(self.db)
+      .await?;
+
+    let mut days: HashMap<String, usize> = HashMap::new();
+    for (key, _) in rows {
+      *days.entry(key).or_default() += 1;
+    }
+    Ok(
+      days
+        .into_iter()
+        .filter(|(_, days)| *days >= min_days)
+        .map(|(key, _)| key)
+        .collect(),
+    )
+  }
+
+  /// Forget days before `before`
+  pub async fn prune(&self, before: NaiveDate) -> Result<u64> {
+    let result = api_usage::Entity::delete_many()
+      .filter(api_usage::Column::Day.lt(before))
+      .exec(self.db)
+      .await?;
+    Ok(result.rows_affected)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::sv::test_utils::test_db;
+
+  #[tokio::test]
+  async fn test_rollup() {
+    let db = test_db::setup().await;
+    let sv = ApiUsage::new(&db);
+    let today = Utc::now().date_naive();
+    let count = |key: &str, kind, requests, throttled| {
+      ((key.to_string(), kind), Counts { requests, throttled })
+    };
+
+    sv.add(
+      today,
+      &[
+        count("A", ApiKind::Heartbeat, 10, 0),
+        count("B", ApiKind::Heartbeat, 50, 5),
+      ],
+    )
+    .await
+    .unwrap();
+    // flushes of the same day add up
+    sv.add(
+      today,
+      &[
+        count("A", ApiKind::Heartbeat, 5, 0),
+        count("B", ApiKind::Metrics, 20, 0),
+      ],
+    )
+    .await
+    .unwrap();
+    sv.add(today - TimeDelta::days(1), &[count("B", ApiKind::Metrics, 7, 3)])
+      .await
+      .unwrap();
+
+    let top = sv.top(today, 10).await.unwrap();
+    assert_eq!(top.len(), 2);
+    assert_eq!((top[0].license_key.as_str(), top[0].requests), ("B", 70));
+    assert_eq!(top[0].throttled, 5);
+    assert_eq!((top[1].license_key.as_str(), top[1].requests), ("A", 15));
+
+    let history = sv.history("B", today - TimeDelta::days(7)).await.unwrap();
+    assert_eq!(history.len(), 3);
+    assert_eq!(history[2].day, today - TimeDelta::days(1));
+
+    let week = today - TimeDelta::days(6);
+    assert_eq!(sv.abusers(week, 2).await.unwrap(), ["B"]);
+    assert!(sv.abusers(today, 2).await.unwrap().is_empty());
+
+    assert_eq!(sv.prune(today).await.unwrap(), 1);
+    assert!(sv.abusers(week, 2).await.unwrap().is_empty());
+  }
+}
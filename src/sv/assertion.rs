@@ -0,0 +1,115 @@
+use jsonwebtoken::{Algorithm, EncodingKey, Header};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+  entity::{LicenseType, license},
+  prelude::*,
+};
+
+/// How long a client may trust an assertion without asking again
+pub const LIFETIME: TimeDelta = TimeDelta::minutes(15);
+
+/// Claims of the JWT returned by `/api/validate`, signed with HS256 under
+/// the server secret so clients holding it can verify offline. Offline
+/// tokens carry the same claims with a longer lifetime, signed with Ed25519.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Assertion {
+  /// License key
+  pub sub: String,
+  pub hwid: String,
+  pub license_type: LicenseType,
+  pub max_sessions: i32,
+  pub product: String,
+  /// Unix time the license itself expires
+  pub license_exp: i64,
+  pub iat: i64,
+  /// Backdated by the skew allowance so a lagging client clock accepts it
+  pub nbf: i64,
+  /// Unix time the assertion expires, never after the license and its
+  /// skew allowance
+  pub exp: i64,
+}
+
+impl Assertion {
+  pub fn new(
+    license: &license::Model,
+    hwid: &str,
+    now: DateTime,
+    lifetime: TimeDelta,
+    skew: TimeDelta,
+  ) -> Self {
+    let license_exp = license.expires_at.and_utc().timestamp();
+    let iat = now.and_utc().timestamp();
+    let skew = skew.num_seconds();
+    Self {
+      sub: license.key.clone(),
+      hwid: hwid.to_string(),
+      license_type: license.license_type.clone(),
+      max_sessions: license.max_sessions,
+      product: license.product.clone(),
+      license_exp,
+      iat,
+      nbf: iat - skew,
+      exp: (iat + lifetime.num_seconds()).min(license_exp + skew),
+    }
+  }
+
+  pub fn sign(&self, secret: &str) -> Result<String> {
+    let key = EncodingKey::from_secret(secret.as_bytes());
+    jsonwebtoken::encode(&Header::new(Algorithm::HS256), self, &key)
+      .map_err(|e| Error::Internal(format!("Failed to sign assertion: {}", e)))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use jsonwebtoken::{DecodingKey, Validation};
+
+  use super::*;
+  use crate::sv::{self, test_utils::test_db};
+
+  #[tokio::test]
+  async fn test_assertion_roundtrip() {
+    let db = test_db::setup().await;
+    let license =
+      sv::License::new(&db).create(1, LicenseType::Pro, 30).await.unwrap();
+    let now = Utc::now().naive_utc();
+
+    let assertion =
+      Assertion::new(&license, "hwid", now, LIFETIME, TimeDelta::zero());
+    assert_eq!(assertion.exp - assertion.iat, LIFETIME.num_seconds());
+    assert_eq!(assertion.nbf, assertion.iat);
+    let token = assertion.sign("secret").unwrap();
+
+    let validation = Validation::new(Algorithm::HS256);
+    let key = DecodingKey::from_secret(b"secret");
+    let claims =
+      jsonwebtoken::decode::<Assertion>(&token, &key, &validation).unwrap();
+    assert_eq!(claims.claims.sub, license.key);
+    assert_eq!(claims.claims.hwid, "hwid");
+    assert_eq!(claims.claims.max_sessions, license.max_sessions);
+
+    let wrong = DecodingKey::from_secret(b"other");
+    assert!(
+      jsonwebtoken::decode::<Assertion>(&token, &wrong, &validation).is_err()
+    );
+  }
+
+  #[tokio::test]
+  async fn test_assertion_skew() {
+    let db = test_db::setup().await;
+    let sv = sv::License::new(&db);
+    let license = sv.create(1, LicenseType::Pro, 1).await.unwrap();
+    let skew = TimeDelta::minutes(2);
+
+    // Just past expiry the key still works within the allowance
+    let now = license.expires_at + TimeDelta::minutes(1);
+    assert!(license.is_expired(now, TimeDelta::zero()));
+    assert!(!license.is_expired(now, skew));
+
+    let assertion = Assertion::new(&license, "hwid", now, LIFETIME, skew);
+    assert_eq!(assertion.nbf, assertion.iat - skew.num_seconds());
+    assert_eq!(assertion.exp, assertion.license_exp + skew.num_seconds());
+    assert!(assertion.exp > assertion.iat);
+  }
+}
@@ -0,0 +1,264 @@
+use std::{collections::HashSet, fmt, path::Path};
+
+use base64::Engine;
+use ring::{
+  aead::{AES_256_GCM, Aad, LessSafeKey, NONCE_LEN, Nonce, UnboundKey},
+  rand::{SecureRandom, SystemRandom},
+};
+
+use crate::prelude::*;
+
+/// Leads every encrypted backup, bumped if the format ever changes
+const MAGIC: &[u8] = b"LICBAK1\0";
+
+/// Leads every SQLite database file
+const SQLITE_HEADER: &[u8] = b"SQLite format 3\0";
+
+/// Extension of encrypted backup files
+pub const EXTENSION: &str = "enc";
+
+/// Format of the timestamp in backup file names
+const STAMP: &str = "%Y-%m-%d_%H-%M-%S";
+
+/// Name of a new snapshot, `manual` ones were asked for with /backup
+pub fn file_name(manual: bool, now: DateTime) -> String {
+  let kind = if manual { "manual_backup" } else { "backup" };
+  format!("{}_{}.db", kind, now.format(STAMP))
+}
+
+/// Plain database out of an uploaded backup, sealed ones are opened with
+/// `key`
+pub fn unpack(data: Vec<u8>, key: Option<&BackupKey>) -> Result<Vec<u8>> {
+  let plain = if data.starts_with(MAGIC) {
+    let key = key.ok_or_else(|| {
+      Error::InvalidArgs("Backup is encrypted but BACKUP_KEY is not set".into())
+    })?;
+    key.open(&data)?
+  } else {
+    data
+  };
+  if !plain.starts_with(SQLITE_HEADER) {
+    return Err(Error::InvalidArgs("Not an SQLite database".into()));
+  }
+  Ok(plain)
+}
+
+/// A stored backup, on disk or in the bucket
+#[derive(Debug, Clone, PartialEq)]
+pub struct Snapshot {
+  pub name: String,
+  pub size: u64,
+  pub created_at: DateTime,
+}
+
+impl Snapshot {
+  /// None for files that aren't backups, those are never pruned
+  pub fn new(name: &str, size: u64) -> Option<Self> {
+    let (_, rest) = name.split_once("backup_")?;
+    let stamp = rest.get(..19)?;
+    let created_at = DateTime::parse_from_str(stamp, STAMP).ok()?;
+    Some(Self { name: name.to_string(), size, created_at })
+  }
+}
+
+/// Snapshots kept in `dir`, newest first
+pub async fn local(dir: &Path) -> Result<Vec<Snapshot>> {
+  let mut snapshots = Vec::new();
+  let mut entries = match tokio::fs::read_dir(dir).await {
+    Ok(entries) => entries,
+    Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(snapshots),
+    Err(e) => return Err(e.into()),
+  };
+  while let Some(entry) = entries.next_entry().await? {
+    let size = entry.metadata().await?.len();
+    if let Some(snapshot) =
+      entry.file_name().to_str().and_then(|name| Snapshot::new(name, size))
+    {
+      snapshots.push(snapshot);
+    }
+  }
+  snapshots.sort_by_key(|s| std::cmp::Reverse(s.created_at));
+  Ok(snapshots)
+}
+
+/// Grandfather-father rotation: the newest snapshot of each of the last
+/// `daily` days and of each of the last `weekly` ISO weeks survive. Both at
+/// zero keeps everything.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Retention {
+  pub daily: usize,
+  pub weekly: usize,
+}
+
+impl Default for Retention {
+  fn default() -> Self {
+    Self { daily: 7, weekly: 4 }
+  }
+}
+
+impl Retention {
+  /// Snapshots the policy drops
+  pub fn expired<'a>(&self, snapshots: &'a [Snapshot]) -> Vec<&'a Snapshot> {
+    if self.daily == 0 && self.weekly == 0 {
+      return Vec::new();
+    }
+    let mut sorted: Vec<_> = snapshots.iter().collect();
+    sorted.sort_by_key(|s| std::cmp::Reverse(s.created_at));
+
+    let (mut days, mut weeks) = (HashSet::new(), HashSet::new());
+    sorted
+      .into_iter()
+      .filter(|snapshot| {
+        let day = snapshot.created_at.date();
+        let week = (day.iso_week().year(), day.iso_week().week());
+        let daily = days.len() < self.daily && days.insert(day);
+        let weekly = weeks.len() < self.weekly && weeks.insert(week);
+        !daily && !weekly
+      })
+      .collect()
+  }
+}
+
+/// AES-256-GCM key backups are sealed with before they leave the server.
+/// A sealed file is the magic, a random nonce and the ciphertext with its
+/// tag, so the key alone is enough to restore it.
+#[derive(Clone)]
+pub struct BackupKey([u8; 32]);
+
+impl fmt::Debug for BackupKey {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.write_str("BackupKey(..)")
+  }
+}
+
+impl BackupKey {
+  /// 32 bytes as hex or base64, e.g. from `openssl rand -hex 32`
+  pub fn parse(raw: &str) -> Result<Self> {
+    let raw = raw.trim();
+    let bytes = hex::decode(raw)
+      .or_else(|_| base64::prelude::BASE64_STANDARD.decode(raw))
+      .map_err(|_| {
+        Error::InvalidArgs("Backup key is not hex or base64".into())
+      })?;
+    let key = bytes.try_into().map_err(|_| {
+      Error::InvalidArgs("Backup key must be 32 bytes long".into())
+    })?;
+    Ok(Self(key))
+  }
+
+  fn key(&self) -> LessSafeKey {
+    let unbound =
+      UnboundKey::new(&AES_256_GCM, &self.0).expect("key is 32 bytes long");
+    LessSafeKey::new(unbound)
+  }
+
+  pub fn seal(&self, plain: &[u8]) -> Result<Vec<u8>> {
+    let mut nonce = [0u8; NONCE_LEN];
+    SystemRandom::new()
+      .fill(&mut nonce)
+      .map_err(|_| Error::Internal("No randomness for the nonce".into()))?;
+
+    let mut data = plain.to_vec();
+    self
+      .key()
+      .seal_in_place_append_tag(
+        Nonce::assume_unique_for_key(nonce),
+        Aad::from(MAGIC),
+        &mut data,
+      )
+      .map_err(|_| Error::Internal("Failed to encrypt backup".into()))?;
+
+    let mut sealed = Vec::with_capacity(MAGIC.len() + NONCE_LEN + data.len());
+    sealed.extend_from_slice(MAGIC);
+    sealed.extend_from_slice(&nonce);
+    sealed.extend_from_slice(&data);
+    Ok(sealed)
+  }
+
+  pub fn open(&self, sealed: &[u8]) -> Result<Vec<u8>> {
+    let invalid = || Error::InvalidArgs("Not an encrypted backup".into());
+    let rest = sealed.strip_prefix(MAGIC).ok_or_else(invalid)?;
+    if rest.len() < NONCE_LEN {
+      return Err(invalid());
+    }
+    let (nonce, data) = rest.split_at(NONCE_LEN);
+    let nonce =
+      Nonce::try_assume_unique_for_key(nonce).map_err(|_| invalid())?;
+
+    let mut data = data.to_vec();
+    let plain =
+      self.key().open_in_place(nonce, Aad::from(MAGIC), &mut data).map_err(
+        |_| Error::InvalidArgs("Wrong backup key or corrupted file".into()),
+      )?;
+    Ok(plain.to_vec())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_seal_roundtrip() {
+    let key = BackupKey::parse(&"ab".repeat(32)).unwrap();
+    let sealed = key.seal(b"SQLite format 3").unwrap();
+    assert!(sealed.starts_with(MAGIC));
+    assert_eq!(key.open(&sealed).unwrap(), b"SQLite format 3");
+
+    // fresh nonce every time
+    assert_ne!(key.seal(b"SQLite format 3").unwrap(), sealed);
+
+    let other = BackupKey::parse(&"cd".repeat(32)).unwrap();
+    assert!(other.open(&sealed).is_err());
+    let mut tampered = sealed.clone();
+    *tampered.last_mut().unwrap() ^= 1;
+    assert!(key.open(&tampered).is_err());
+    assert!(key.open(b"SQLite format 3").is_err());
+
+    let db = key.seal(SQLITE_HEADER).unwrap();
+    assert_eq!(unpack(db.clone(), Some(&key)).unwrap(), SQLITE_HEADER);
+    assert!(unpack(db, None).is_err());
+    assert!(unpack(SQLITE_HEADER.to_vec(), None).is_ok());
+    assert!(unpack(b"PK\x03\x04".to_vec(), Some(&key)).is_err());
+
+    assert!(BackupKey::parse("short").is_err());
+    let base64 = base64::prelude::BASE64_STANDARD.encode([7u8; 32]);
+    assert!(BackupKey::parse(&base64).is_ok());
+  }
+
+  #[test]
+  fn test_retention() {
+    let start = DateTime::parse_from_str("2026-01-05_00-00-00", STAMP).unwrap();
+    // two backups a day for six weeks from Monday 2026-01-05
+    let snapshots: Vec<_> = (0..84)
+      .map(|i| {
+        let at = start + TimeDelta::hours(12 * i);
+        Snapshot::new(&file_name(false, at), 1).unwrap()
+      })
+      .collect();
+
+    let expired = Retention { daily: 3, weekly: 2 }.expired(&snapshots);
+    let kept: Vec<_> = snapshots
+      .iter()
+      .filter(|s| !expired.contains(s))
+      .map(|s| s.name.as_str())
+      .collect();
+    assert_eq!(
+      kept,
+      [
+        // newest of the week before
+        "backup_2026-02-08_12-00-00.db",
+        // newest of the last three days, the last one also stands for its
+        // week
+        "backup_2026-02-13_12-00-00.db",
+        "backup_2026-02-14_12-00-00.db",
+        "backup_2026-02-15_12-00-00.db",
+      ]
+    );
+
+    assert!(Retention { daily: 0, weekly: 0 }.expired(&snapshots).is_empty());
+    assert!(Snapshot::new("licenses.db", 1).is_none());
+    let manual = Snapshot::new("manual_backup_2026-01-05_10-00-00.db.enc", 1);
+    assert_eq!(manual.unwrap().created_at, start + TimeDelta::hours(10));
+  }
+}
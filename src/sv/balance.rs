@@ -3,6 +3,27 @@ use crate::{
   prelude::*,
 };
 
+/// Newest transactions of a user with the balance each one left
+#[derive(Debug, Clone)]
+pub struct Ledger {
+  pub balance: i64,
+  /// Newest first, with the running balance after the transaction
+  pub entries: Vec<(transaction::Model, i64)>,
+  /// Transactions of the user overall
+  pub total: u64,
+  /// Balance changes no transaction accounts for, zero when the books add up
+  pub drift: i64,
+}
+
+/// Refund paid back to the user
+#[derive(Debug, Clone)]
+pub struct Refunded {
+  pub refund: transaction::Model,
+  /// Purchase the refund is booked against
+  pub purchase: transaction::Model,
+  pub balance: i64,
+}
+
 pub struct Balance<'a> {
   db: &'a DatabaseConnection,
 }
@@ -26,15 +47,39 @@ impl<'a> Balance<'a> {
     user_id: i64,
     amount: i64,
     description: Option<String>,
+  ) -> Result<i64> {
+    self.credit(user_id, amount, TransactionType::Deposit, description).await
+  }
+
+  /// Add to the balance, recording the transaction as `tx_type`
+  pub async fn credit(
+    &self,
+    user_id: i64,
+    amount: i64,
+    tx_type: TransactionType,
+    description: Option<String>,
+  ) -> Result<i64> {
+    let txn = self.db.begin().await?;
+    let new_balance =
+      Self::credit_in(&txn, user_id, amount, tx_type, description).await?;
+    txn.commit().await?;
+    Ok(new_balance)
+  }
+
+  /// Same as `credit`, on the caller's transaction
+  pub async fn credit_in(
+    txn: &sea_orm::DatabaseTransaction,
+    user_id: i64,
+    amount: i64,
+    tx_type: TransactionType,
+    description: Option<String>,
   ) -> Result<i64> {
     if amount <= 0 {
       return Err(Error::InvalidArgs("Deposit amount must be positive".into()));
     }
 
-    let txn = self.db.begin().await?;
-
     let user = user::Entity::find_by_id(user_id)
-      .one(&txn)
+      .one(txn)
       .await?
       .ok_or(Error::UserNotFound)?;
 
@@ -42,7 +87,7 @@ impl<'a> Balance<'a> {
     let new_balance = user.balance + amount;
 
     user::ActiveModel { balance: Set(new_balance), ..user.into() }
-      .update(&txn)
+      .update(txn)
       .await?;
 
     let now = Utc::now().naive_utc();
@@ -50,15 +95,16 @@ impl<'a> Balance<'a> {
       id: NotSet,
       user_id: Set(user_id),
       amount: Set(amount),
-      tx_type: Set(TransactionType::Deposit),
+      tx_type: Set(tx_type),
       description: Set(description),
       referrer_id: Set(None),
+      region: Set(None),
+      refund_of: Set(None),
       created_at: Set(now),
     }
-    .insert(&txn)
+    .insert(txn)
     .await?;
 
-    txn.commit().await?;
     Ok(new_balance)
   }
 
@@ -68,15 +114,42 @@ impl<'a> Balance<'a> {
     amount: i64,
     description: Option<String>,
     referrer_id: Option<i64>,
+  ) -> Result<i64> {
+    self.spend_in(user_id, amount, description, referrer_id, None).await
+  }
+
+  /// Same as `spend`, recording the country whose regional price was paid
+  pub async fn spend_in(
+    &self,
+    user_id: i64,
+    amount: i64,
+    description: Option<String>,
+    referrer_id: Option<i64>,
+    region: Option<String>,
+  ) -> Result<i64> {
+    let txn = self.db.begin().await?;
+    let new_balance =
+      Self::charge_in(&txn, user_id, amount, description, referrer_id, region)
+        .await?;
+    txn.commit().await?;
+    Ok(new_balance)
+  }
+
+  /// Same as `spend_in`, on the caller's transaction
+  pub async fn charge_in(
+    txn: &sea_orm::DatabaseTransaction,
+    user_id: i64,
+    amount: i64,
+    description: Option<String>,
+    referrer_id: Option<i64>,
+    region: Option<String>,
   ) -> Result<i64> {
     if amount <= 0 {
       return Err(Error::InvalidArgs("Spend amount must be positive".into()));
     }
 
-    let txn = self.db.begin().await?;
-
     let user = user::Entity::find_by_id(user_id)
-      .one(&txn)
+      .one(txn)
       .await?
       .ok_or(Error::UserNotFound)?;
 
@@ -87,7 +160,7 @@ impl<'a> Balance<'a> {
     let new_balance = user.balance - amount;
 
     user::ActiveModel { balance: Set(new_balance), ..user.into() }
-      .update(&txn)
+      .update(txn)
       .await?;
 
     let now = Utc::now().naive_utc();
@@ -98,12 +171,13 @@ impl<'a> Balance<'a> {
       tx_type: Set(TransactionType::Purchase),
       description: Set(description),
       referrer_id: Set(referrer_id),
+      region: Set(region),
+      refund_of: Set(None),
       created_at: Set(now),
     }
-    .insert(&txn)
+    .insert(txn)
     .await?;
 
-    txn.commit().await?;
     Ok(new_balance)
   }
 
@@ -142,6 +216,8 @@ impl<'a> Balance<'a> {
         referrer_id
       ))),
       referrer_id: Set(Some(referrer_id)),
+      region: Set(None),
+      refund_of: Set(None),
       created_at: Set(now),
     }
     .insert(&txn)
@@ -152,16 +228,26 @@ impl<'a> Balance<'a> {
   }
 
   pub async fn withdraw(&self, user_id: i64, amount: i64) -> Result<i64> {
+    let txn = self.db.begin().await?;
+    let new_balance = Self::withdraw_in(&txn, user_id, amount).await?;
+    txn.commit().await?;
+    Ok(new_balance)
+  }
+
+  /// Same as `withdraw`, on the caller's transaction
+  pub async fn withdraw_in(
+    txn: &sea_orm::DatabaseTransaction,
+    user_id: i64,
+    amount: i64,
+  ) -> Result<i64> {
     if amount <= 0 {
       return Err(Error::InvalidArgs(
         "Withdrawal amount must be positive".into(),
       ));
     }
 
-    let txn = self.db.begin().await?;
-
     let user = user::Entity::find_by_id(user_id)
-      .one(&txn)
+      .one(txn)
       .await?
       .ok_or(Error::UserNotFound)?;
 
@@ -176,7 +262,7 @@ impl<'a> Balance<'a> {
     let new_balance = user.balance - amount;
 
     user::ActiveModel { balance: Set(new_balance), ..user.into() }
-      .update(&txn)
+      .update(txn)
       .await?;
 
     let now = Utc::now().naive_utc();
@@ -187,15 +273,184 @@ impl<'a> Balance<'a> {
       tx_type: Set(TransactionType::Withdrawal),
       description: Set(Some("Crypto withdrawal".to_string())),
       referrer_id: Set(None),
+      region: Set(None),
+      refund_of: Set(None),
       created_at: Set(now),
     }
-    .insert(&txn)
+    .insert(txn)
     .await?;
 
-    txn.commit().await?;
     Ok(new_balance)
   }
 
+  /// Credit `amount` back against the newest purchase of the user that has
+  /// that much left to refund
+  pub async fn refund(
+    &self,
+    user_id: i64,
+    amount: i64,
+    reason: Option<String>,
+  ) -> Result<Refunded> {
+    if amount <= 0 {
+      return Err(Error::InvalidArgs("Refund amount must be positive".into()));
+    }
+
+    let txn = self.db.begin().await?;
+    let user = user::Entity::find_by_id(user_id)
+      .one(&txn)
+      .await?
+      .ok_or(Error::UserNotFound)?;
+    let purchases = transaction::Entity::find()
+      .filter(transaction::Column::UserId.eq(user_id))
+      .filter(transaction::Column::TxType.eq(TransactionType::Purchase))
+      .order_by_desc(transaction::Column::Id)
+      .all(&txn)
+      .await?;
+    let refunds = transaction::Entity::find()
+      .filter(transaction::Column::UserId.eq(user_id))
+      .filter(transaction::Column::TxType.eq(TransactionType::Refund))
+      .all(&txn)
+      .await?;
+    let refunded = |purchase: &transaction::Model| -> i64 {
+      refunds
+        .iter()
+        .filter(|refund| refund.refund_of == Some(purchase.id))
+        .map(|refund| refund.amount)
+        .sum()
+    };
+    let purchase = purchases
+      .into_iter()
+      .find(|purchase| -purchase.amount - refunded(purchase) >= amount)
+      .ok_or_else(|| {
+        Error::InvalidArgs(format!(
+          "No purchase of user {} has that much left to refund",
+          user_id
+        ))
+      })?;
+
+    let new_balance = user.balance + amount;
+    user::ActiveModel { balance: Set(new_balance), ..user.into() }
+      .update(&txn)
+      .await?;
+    let refund = transaction::ActiveModel {
+      id: NotSet,
+      user_id: Set(user_id),
+      amount: Set(amount),
+      tx_type: Set(TransactionType::Refund),
+      description: Set(Some(
+        reason
+          .unwrap_or_else(|| format!("Refund of purchase #{}", purchase.id)),
+      )),
+      referrer_id: Set(None),
+      region: Set(None),
+      refund_of: Set(Some(purchase.id)),
+      created_at: Set(Utc::now().naive_utc()),
+    }
+    .insert(&txn)
+    .await?;
+    Self::claw_back_in(&txn, &purchase, amount).await?;
+    txn.commit().await?;
+
+    Ok(Refunded { refund, purchase, balance: new_balance })
+  }
+
+  /// Take back the share of the referral commission a purchase paid that
+  /// matches the refunded `amount`
+  async fn claw_back_in(
+    txn: &sea_orm::DatabaseTransaction,
+    purchase: &transaction::Model,
+    amount: i64,
+  ) -> Result<()> {
+    let Some(referrer_id) = purchase.referrer_id else {
+      return Ok(());
+    };
+    // the commission is booked right after the purchase, before anything
+    // else the buyer does
+    let next = transaction::Entity::find()
+      .filter(transaction::Column::UserId.eq(purchase.user_id))
+      .filter(transaction::Column::Id.gt(purchase.id))
+      .order_by_asc(transaction::Column::Id)
+      .one(txn)
+      .await?;
+    let mut bonus = transaction::Entity::find()
+      .filter(transaction::Column::UserId.eq(referrer_id))
+      .filter(transaction::Column::ReferrerId.eq(purchase.user_id))
+      .filter(transaction::Column::TxType.eq(TransactionType::ReferralBonus))
+      .filter(transaction::Column::RefundOf.is_null())
+      .filter(transaction::Column::Id.gt(purchase.id));
+    if let Some(next) = next {
+      bonus = bonus.filter(transaction::Column::Id.lt(next.id));
+    }
+    let Some(bonus) =
+      bonus.order_by_asc(transaction::Column::Id).one(txn).await?
+    else {
+      return Ok(());
+    };
+
+    let clawback = bonus.amount * amount / -purchase.amount;
+    if clawback <= 0 {
+      return Ok(());
+    }
+    let referrer = user::Entity::find_by_id(referrer_id)
+      .one(txn)
+      .await?
+      .ok_or(Error::UserNotFound)?;
+    let balance = referrer.balance - clawback;
+    user::ActiveModel { balance: Set(balance), ..referrer.into() }
+      .update(txn)
+      .await?;
+    transaction::ActiveModel {
+      id: NotSet,
+      user_id: Set(referrer_id),
+      amount: Set(-clawback),
+      tx_type: Set(TransactionType::ReferralBonus),
+      description: Set(Some(format!(
+        "Referral bonus taken back, purchase #{} refunded",
+        purchase.id
+      ))),
+      referrer_id: Set(Some(purchase.user_id)),
+      region: Set(None),
+      refund_of: Set(Some(bonus.id)),
+      created_at: Set(Utc::now().naive_utc()),
+    }
+    .insert(txn)
+    .await?;
+    Ok(())
+  }
+
+  /// Number of purchases and their total (positive) amount since a moment
+  pub async fn purchases_since(&self, since: DateTime) -> Result<(u64, i64)> {
+    let purchases = transaction::Entity::find()
+      .filter(transaction::Column::TxType.eq(TransactionType::Purchase))
+      .filter(transaction::Column::CreatedAt.gte(since))
+      .all(self.db)
+      .await?;
+
+    let total = purchases.iter().map(|tx| -tx.amount).sum();
+    Ok((purchases.len() as u64, total))
+  }
+
+  /// Newest transactions first, of one user or everyone
+  pub async fn page(
+    &self,
+    user_id: Option<i64>,
+    offset: u64,
+    limit: u64,
+  ) -> Result<Vec<transaction::Model>> {
+    let mut query = transaction::Entity::find();
+    if let Some(user_id) = user_id {
+      query = query.filter(transaction::Column::UserId.eq(user_id));
+    }
+    Ok(
+      query
+        .order_by_desc(transaction::Column::Id)
+        .offset(offset)
+        .limit(limit)
+        .all(self.db)
+        .await?,
+    )
+  }
+
   pub async fn transactions(
     &self,
     user_id: i64,
@@ -210,6 +465,52 @@ impl<'a> Balance<'a> {
         .await?,
     )
   }
+
+  /// Last `limit` transactions of the user with running balances
+  pub async fn ledger(&self, user_id: i64, limit: u64) -> Result<Ledger> {
+    let balance = self.get(user_id).await?;
+    let txs = self.page(Some(user_id), 0, limit).await?;
+    let total = transaction::Entity::find()
+      .filter(transaction::Column::UserId.eq(user_id))
+      .count(self.db)
+      .await?;
+    let recorded: Option<Option<i64>> = transaction::Entity::find()
+      .select_only()
+      .column_as(transaction::Column::Amount.sum(), "sum")
+      .filter(transaction::Column::UserId.eq(user_id))
+      .into_tuple()
+      .one(self.db)
+      .await?;
+    let recorded = recorded.flatten().unwrap_or(0);
+
+    // walk back from what the transactions add up to
+    let mut running = recorded;
+    let entries = txs
+      .into_iter()
+      .map(|tx| {
+        let after = running;
+        running -= tx.amount;
+        (tx, after)
+      })
+      .collect();
+    Ok(Ledger { balance, entries, total, drift: balance - recorded })
+  }
+
+  /// Page of the user's transactions, newest first, and the page count
+  pub async fn transactions_page(
+    &self,
+    user_id: i64,
+    page: u64,
+    per_page: u64,
+  ) -> Result<(Vec<transaction::Model>, u64)> {
+    let paginator = transaction::Entity::find()
+      .filter(transaction::Column::UserId.eq(user_id))
+      .order_by_desc(transaction::Column::CreatedAt)
+      .order_by_desc(transaction::Column::Id)
+      .paginate(self.db, per_page);
+    let pages = paginator.num_pages().await?;
+    Ok((paginator.fetch_page(page).await?, pages))
+  }
 }
 
 #[cfg(test)]
@@ -230,9 +531,11 @@ mod tests {
       referred_by: Set(None),
       commission_rate: Set(25),
       discount_percent: Set(3),
-      referral_sales: Set(0),
-      referral_earnings: Set(0),
       referral_code: Set(None),
+      deletion_requested_at: Set(None),
+      deleted_at: Set(None),
+      storefront: Set(None),
+      is_reseller: Set(false),
     }
     .insert(&db)
     .await
@@ -259,9 +562,11 @@ mod tests {
       referred_by: Set(None),
       commission_rate: Set(25),
       discount_percent: Set(3),
-      referral_sales: Set(0),
-      referral_earnings: Set(0),
       referral_code: Set(None),
+      deletion_requested_at: Set(None),
+      deleted_at: Set(None),
+      storefront: Set(None),
+      is_reseller: Set(false),
     }
     .insert(&db)
     .await
@@ -288,9 +593,11 @@ mod tests {
       referred_by: Set(None),
       commission_rate: Set(25),
       discount_percent: Set(3),
-      referral_sales: Set(0),
-      referral_earnings: Set(0),
       referral_code: Set(None),
+      deletion_requested_at: Set(None),
+      deleted_at: Set(None),
+      storefront: Set(None),
+      is_reseller: Set(false),
     }
     .insert(&db)
     .await
@@ -314,9 +621,11 @@ mod tests {
       referred_by: Set(None),
       commission_rate: Set(25),
       discount_percent: Set(3),
-      referral_sales: Set(0),
-      referral_earnings: Set(0),
       referral_code: Set(None),
+      deletion_requested_at: Set(None),
+      deleted_at: Set(None),
+      storefront: Set(None),
+      is_reseller: Set(false),
     }
     .insert(&db)
     .await
@@ -340,9 +649,11 @@ mod tests {
       referred_by: Set(None),
       commission_rate: Set(25),
       discount_percent: Set(3),
-      referral_sales: Set(0),
-      referral_earnings: Set(0),
       referral_code: Set(None),
+      deletion_requested_at: Set(None),
+      deleted_at: Set(None),
+      storefront: Set(None),
+      is_reseller: Set(false),
     }
     .insert(&db)
     .await
@@ -352,4 +663,115 @@ mod tests {
 
     assert_eq!(new_balance, 500);
   }
+
+  #[tokio::test]
+  async fn test_transactions_page() {
+    let db = test_db::setup().await;
+    crate::sv::User::new(&db).get_or_create(1).await.unwrap();
+    let sv = Balance::new(&db);
+    for amount in 1..=5 {
+      sv.deposit(1, amount, None).await.unwrap();
+    }
+
+    let (first, pages) = sv.transactions_page(1, 0, 2).await.unwrap();
+    assert_eq!(pages, 3);
+    assert_eq!(first.iter().map(|tx| tx.amount).collect::<Vec<_>>(), [5, 4]);
+    let (last, _) = sv.transactions_page(1, 2, 2).await.unwrap();
+    assert_eq!(last.iter().map(|tx| tx.amount).collect::<Vec<_>>(), [1]);
+    assert!(sv.transactions_page(1, 3, 2).await.unwrap().0.is_empty());
+    assert_eq!(sv.transactions_page(2, 0, 2).await.unwrap().1, 0);
+  }
+
+  #[tokio::test]
+  async fn test_refund() {
+    let db = test_db::setup().await;
+    crate::sv::User::new(&db).get_or_create(1).await.unwrap();
+    let sv = Balance::new(&db);
+    sv.deposit(1, 1000, None).await.unwrap();
+    sv.spend(1, 600, Some("Month".into()), None).await.unwrap();
+    sv.spend(1, 100, Some("Week".into()), None).await.unwrap();
+
+    // the newest purchase is too small, the one before takes it
+    let refunded = sv.refund(1, 400, None).await.unwrap();
+    assert_eq!(refunded.purchase.amount, -600);
+    assert_eq!(refunded.refund.refund_of, Some(refunded.purchase.id));
+    assert_eq!(refunded.refund.tx_type, TransactionType::Refund);
+    assert_eq!(refunded.balance, 700);
+
+    let refunded =
+      sv.refund(1, 100, Some("Broken build".into())).await.unwrap();
+    assert_eq!(refunded.purchase.amount, -100);
+    assert_eq!(refunded.refund.description.as_deref(), Some("Broken build"));
+
+    // 200 of the month is left, nothing else
+    assert!(matches!(
+      sv.refund(1, 300, None).await,
+      Err(Error::InvalidArgs(_))
+    ));
+    assert_eq!(sv.refund(1, 200, None).await.unwrap().balance, 1000);
+    assert!(matches!(sv.refund(1, 1, None).await, Err(Error::InvalidArgs(_))));
+    assert!(matches!(sv.refund(2, 1, None).await, Err(Error::UserNotFound)));
+  }
+
+  #[tokio::test]
+  async fn test_refund_claws_back_commission() {
+    let db = test_db::setup().await;
+    let users = crate::sv::User::new(&db);
+    users.get_or_create(1).await.unwrap();
+    users.get_or_create(2).await.unwrap();
+    let sv = Balance::new(&db);
+    let referral = crate::sv::Referral::new(&db);
+    sv.deposit(2, 1000, None).await.unwrap();
+    sv.spend(2, 1000, None, Some(1)).await.unwrap();
+    let commission = referral.pay_commission(1, Some(2), 1000).await.unwrap();
+    assert!(commission.amount > 0);
+    sv.deposit(2, 500, None).await.unwrap();
+    // a later commission on a deposit is not the purchase's
+    referral.pay_commission(1, Some(2), 500).await.unwrap();
+    let earned = sv.get(1).await.unwrap();
+
+    sv.refund(2, 500, None).await.unwrap();
+    assert_eq!(sv.get(1).await.unwrap(), earned - commission.amount / 2);
+    let clawback = transaction::Entity::find()
+      .filter(transaction::Column::UserId.eq(1))
+      .filter(transaction::Column::RefundOf.is_not_null())
+      .one(&db)
+      .await
+      .unwrap()
+      .unwrap();
+    assert_eq!(clawback.tx_type, TransactionType::ReferralBonus);
+    assert_eq!(clawback.amount, -(commission.amount / 2));
+
+    // the ledger and the referral totals add up, the sale is still counted
+    assert_eq!(sv.ledger(1, 10).await.unwrap().drift, 0);
+    let (sales, earnings) = referral.all_totals().await.unwrap()[&1];
+    assert_eq!((sales, earnings), (2, earned - commission.amount / 2));
+  }
+
+  #[tokio::test]
+  async fn test_ledger() {
+    let db = test_db::setup().await;
+    crate::sv::User::new(&db).get_or_create(1).await.unwrap();
+    let sv = Balance::new(&db);
+    sv.deposit(1, 1000, None).await.unwrap();
+    sv.spend(1, 300, None, None).await.unwrap();
+    sv.deposit(1, 50, None).await.unwrap();
+
+    let ledger = sv.ledger(1, 2).await.unwrap();
+    assert_eq!((ledger.balance, ledger.total, ledger.drift), (750, 3, 0));
+    let running: Vec<_> =
+      ledger.entries.iter().map(|(tx, after)| (tx.amount, *after)).collect();
+    assert_eq!(running, [(50, 750), (-300, 700)]);
+
+    // a balance edited behind the ledger's back
+    user::Entity::update_many()
+      .col_expr(user::Column::Balance, 900.into())
+      .filter(user::Column::TgUserId.eq(1))
+      .exec(&db)
+      .await
+      .unwrap();
+    let ledger = sv.ledger(1, 10).await.unwrap();
+    assert_eq!(ledger.drift, 150);
+    assert_eq!(ledger.entries.last().map(|(_, after)| *after), Some(1000));
+  }
 }
@@ -0,0 +1,119 @@
+use chrono::NaiveDate;
+use sea_orm::sea_query::{Expr, OnConflict};
+
+use crate::{
+  entity::bot_usage::{self, UsageKind},
+  prelude::*,
+};
+
+/// Uses and failures of one command or callback over a period
+#[derive(Debug, Clone)]
+pub struct UsageTotal {
+  pub kind: UsageKind,
+  pub name: String,
+  pub uses: i64,
+  pub failures: i64,
+}
+
+pub struct BotUsage<'a> {
+  db: &'a DatabaseConnection,
+}
+
+impl<'a> BotUsage<'a> {
+  pub fn new(db: &'a DatabaseConnection) -> Self {
+    Self { db }
+  }
+
+  /// Count one run of a command or callback for today
+  pub async fn record(
+    &self,
+    kind: UsageKind,
+    name: &str,
+    ok: bool,
+  ) -> Result<()> {
+    let failure = if ok { 0 } else { 1 };
+    bot_usage::Entity::insert(bot_usage::ActiveModel {
+      kind: Set(kind),
+      name: Set(name.to_string()),
+      day: Set(Utc::now().date_naive()),
+      uses: Set(1),
+      failures: Set(failure),
+    })
+    .on_conflict(
+      OnConflict::columns([
+        bot_usage::Column::Kind,
+        bot_usage::Column::Name,
+        bot_usage::Column::Day,
+      ])
+      .value(bot_usage::Column::Uses, Expr::col(bot_usage::Column::Uses).add(1))
+      .value(
+        bot_usage::Column::Failures,
+        Expr::col(bot_usage::Column::Failures).add(failure),
+      )
+      .to_owned(),
+    )
+    .exec_without_returning(self.db)
+    .await?;
+    Ok(())
+  }
+
+  /// Totals per command and callback from `since` on (all time if `None`),
+  /// most used first
+  pub async fn totals(
+    &self,
+    since: Option<NaiveDate>,
+  ) -> Result<Vec<UsageTotal>> {
+    let mut query = bot_usage::Entity::find()
+      .select_only()
+      .column(bot_usage::Column::Kind)
+      .column(bot_usage::Column::Name)
+      .column_as(bot_usage::Column::Uses.sum(), "uses")
+      .column_as(bot_usage::Column::Failures.sum(), "failures")
+      .group_by(bot_usage::Column::Kind)
+      .group_by(bot_usage::Column::Name);
+    if let Some(since) = since {
+      query = query.filter(bot_usage::Column::Day.gte(since));
+    }
+
+    let rows: Vec<(UsageKind, String, i64, i64)> =
+      query.into_tuple().all(self.db).await?;
+    let mut totals: Vec<_> = rows
+      .into_iter()
+      .map(|(kind, name, uses, failures)| UsageTotal {
+        kind,
+        name,
+        uses,
+        failures,
+      })
+      .collect();
+    totals
+      .sort_by(|a, b| b.uses.cmp(&a.uses).then_with(|| a.name.cmp(&b.name)));
+    Ok(totals)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::sv::test_utils::test_db;
+
+  #[tokio::test]
+  async fn test_usage_counters() {
+    let db = test_db::setup().await;
+    let sv = BotUsage::new(&db);
+
+    sv.record(UsageKind::Command, "start", true).await.unwrap();
+    sv.record(UsageKind::Command, "start", true).await.unwrap();
+    sv.record(UsageKind::Callback, "Buy", false).await.unwrap();
+
+    let totals = sv.totals(None).await.unwrap();
+    assert_eq!(totals.len(), 2);
+    assert_eq!(totals[0].name, "start");
+    assert_eq!((totals[0].uses, totals[0].failures), (2, 0));
+    assert_eq!(totals[1].kind, UsageKind::Callback);
+    assert_eq!((totals[1].uses, totals[1].failures), (1, 1));
+
+    let tomorrow = Utc::now().date_naive() + TimeDelta::days(1);
+    assert!(sv.totals(Some(tomorrow)).await.unwrap().is_empty());
+  }
+}
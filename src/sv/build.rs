@@ -1,9 +1,57 @@
 use std::path::Path;
 
-use tokio::fs;
+use sha2::{Digest, Sha256};
+use tokio::{fs, io::AsyncReadExt};
 
 use crate::{entity::*, prelude::*};
 
+/// Platforms builds are published for, as `os-arch`
+pub const PLATFORMS: &[&str] = &[
+  "windows-x64",
+  "windows-arm64",
+  "linux-x64",
+  "linux-arm64",
+  "macos-x64",
+  "macos-arm64",
+];
+
+/// Platform of builds published without one
+pub const DEFAULT_PLATFORM: &str = "windows-x64";
+
+/// Known platform matching `s`, `win-x64` and `linux-aarch64` are accepted
+pub fn normalize_platform(s: &str) -> Option<&'static str> {
+  let s = s.trim().to_lowercase();
+  let (os, arch) = s.split_once('-')?;
+  let os = match os {
+    "win" | "windows" => "windows",
+    "linux" => "linux",
+    "mac" | "macos" | "darwin" => "macos",
+    _ => return None,
+  };
+  let arch = match arch {
+    "x64" | "x86_64" | "amd64" => "x64",
+    "arm64" | "aarch64" => "arm64",
+    _ => return None,
+  };
+  let platform = format!("{}-{}", os, arch);
+  PLATFORMS.iter().find(|p| **p == platform).copied()
+}
+
+/// Hex SHA-256 of a file, read in chunks to keep large builds out of memory
+pub async fn sha256_file(path: impl AsRef<Path>) -> Result<String> {
+  let mut file = fs::File::open(path).await?;
+  let mut hasher = Sha256::new();
+  let mut buf = vec![0; 64 * 1024];
+  loop {
+    let read = file.read(&mut buf).await?;
+    if read == 0 {
+      break;
+    }
+    hasher.update(&buf[..read]);
+  }
+  Ok(hex::encode(hasher.finalize()))
+}
+
 pub struct Build<'a> {
   db: &'a DatabaseConnection,
 }
@@ -34,12 +82,24 @@ impl<'a> Build<'a> {
     Ok(build)
   }
 
+  /// Versions are unique across products, downloads resolve them alone
   pub async fn create(
     &self,
     version: String,
     file_path: String,
     changelog: Option<String>,
+    product: &str,
+    channel: build::Channel,
+    platform: &str,
   ) -> Result<build::Model> {
+    let product = crate::sv::Product::new(self.db).get(product).await?;
+    if self.by_version(&version).await?.is_some() {
+      return Err(Error::InvalidArgs(format!(
+        "Version {} is already published",
+        version
+      )));
+    }
+    let checksum = sha256_file(&file_path).await?;
     let now = Utc::now().naive_utc();
 
     let build = build::ActiveModel {
@@ -50,11 +110,102 @@ impl<'a> Build<'a> {
       is_active: Set(true),
       created_at: Set(now),
       downloads: Set(0),
+      product: Set(product.slug),
+      checksum: Set(Some(checksum)),
+      channel: Set(channel),
+      platform: Set(platform.to_string()),
     };
 
     Ok(build.insert(self.db).await?)
   }
 
+  /// Attach the file of `version` for another platform, replacing an
+  /// earlier upload for it
+  pub async fn add_artifact(
+    &self,
+    version: &str,
+    platform: &str,
+    file_path: String,
+  ) -> Result<build_artifact::Model> {
+    let build = self.by_version(version).await?.ok_or(Error::BuildNotFound)?;
+    if build.platform == platform {
+      return Err(Error::InvalidArgs(format!(
+        "v{} was published for {}, publish a new version to replace it",
+        version, platform
+      )));
+    }
+    let checksum = sha256_file(&file_path).await?;
+    let now = Utc::now().naive_utc();
+
+    let existing = build_artifact::Entity::find()
+      .filter(build_artifact::Column::Version.eq(version))
+      .filter(build_artifact::Column::Platform.eq(platform))
+      .one(self.db)
+      .await?;
+    let artifact = match existing {
+      Some(artifact) => {
+        build_artifact::ActiveModel {
+          file_path: Set(file_path),
+          checksum: Set(Some(checksum)),
+          created_at: Set(now),
+          ..artifact.into()
+        }
+        .update(self.db)
+        .await?
+      }
+      None => {
+        build_artifact::ActiveModel {
+          id: NotSet,
+          version: Set(version.to_string()),
+          platform: Set(platform.to_string()),
+          file_path: Set(file_path),
+          checksum: Set(Some(checksum)),
+          created_at: Set(now),
+        }
+        .insert(self.db)
+        .await?
+      }
+    };
+    Ok(artifact)
+  }
+
+  /// Files of `version` for other platforms than the published one
+  pub async fn artifacts(
+    &self,
+    version: &str,
+  ) -> Result<Vec<build_artifact::Model>> {
+    Ok(
+      build_artifact::Entity::find()
+        .filter(build_artifact::Column::Version.eq(version))
+        .order_by_asc(build_artifact::Column::Platform)
+        .all(self.db)
+        .await?,
+    )
+  }
+
+  /// File and checksum of `build` for `platform`, the published file when
+  /// no platform is given
+  pub async fn file_for(
+    &self,
+    build: &build::Model,
+    platform: Option<&str>,
+  ) -> Result<Option<(String, Option<String>)>> {
+    match platform {
+      None => Ok(Some((build.file_path.clone(), build.checksum.clone()))),
+      Some(platform) if platform == build.platform => {
+        Ok(Some((build.file_path.clone(), build.checksum.clone())))
+      }
+      Some(platform) => Ok(
+        build_artifact::Entity::find()
+          .filter(build_artifact::Column::Version.eq(build.version.as_str()))
+          .filter(build_artifact::Column::Platform.eq(platform))
+          .one(self.db)
+          .await?
+          .map(|artifact| (artifact.file_path, artifact.checksum)),
+      ),
+    }
+  }
+
   pub async fn increment_downloads(&self, version: &str) -> Result<()> {
     let build = build::Entity::find()
       .filter(build::Column::Version.eq(version))
@@ -118,6 +269,16 @@ impl<'a> Build<'a> {
     Ok(builds)
   }
 
+  /// Active builds users on `channel` get
+  pub async fn active_on(
+    &self,
+    channel: build::Channel,
+  ) -> Result<Vec<build::Model>> {
+    let mut builds = self.active().await?;
+    builds.retain(|build| channel.serves(build.channel));
+    Ok(builds)
+  }
+
   #[allow(dead_code)]
   pub async fn count(&self) -> Result<u64> {
     Ok(build::Entity::find().count(self.db).await?)
@@ -160,9 +321,122 @@ impl<'a> Build<'a> {
     if path.exists() {
       fs::remove_file(path).await.ok();
     }
+    for artifact in self.artifacts(version).await? {
+      fs::remove_file(&artifact.file_path).await.ok();
+    }
 
+    build_artifact::Entity::delete_many()
+      .filter(build_artifact::Column::Version.eq(version))
+      .exec(self.db)
+      .await?;
     build::Entity::delete_by_id(build.id).exec(self.db).await?;
 
     Ok(build)
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use std::io::Write;
+
+  use super::*;
+  use crate::sv::test_utils::test_db;
+
+  #[tokio::test]
+  async fn test_create_records_checksum() {
+    let db = test_db::setup().await;
+    let sv = Build::new(&db);
+
+    let mut file = tempfile::NamedTempFile::new().unwrap();
+    file.write_all(b"hello").unwrap();
+    let path = file.path().to_string_lossy().to_string();
+
+    let product = crate::sv::product::DEFAULT;
+    let (stable, beta) = (build::Channel::Stable, build::Channel::Beta);
+    let build = sv
+      .create(
+        "1.0".into(),
+        path.clone(),
+        None,
+        product,
+        stable,
+        DEFAULT_PLATFORM,
+      )
+      .await
+      .unwrap();
+    assert_eq!(
+      build.checksum.as_deref(),
+      Some("2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824")
+    );
+
+    // beta builds reach beta testers only
+    sv.create(
+      "1.1".into(),
+      path.clone(),
+      None,
+      product,
+      beta,
+      DEFAULT_PLATFORM,
+    )
+    .await
+    .unwrap();
+    let versions = |builds: Vec<build::Model>| {
+      builds.into_iter().map(|b| b.version).collect::<Vec<_>>()
+    };
+    let stable = sv.active_on(build::Channel::Stable).await.unwrap();
+    assert_eq!(versions(stable), ["1.0"]);
+    assert_eq!(sv.active_on(build::Channel::Beta).await.unwrap().len(), 2);
+  }
+
+  #[tokio::test]
+  async fn test_platform_artifacts() {
+    let db = test_db::setup().await;
+    let sv = Build::new(&db);
+
+    let mut file = tempfile::NamedTempFile::new().unwrap();
+    file.write_all(b"hello").unwrap();
+    let path = file.path().to_string_lossy().to_string();
+
+    let product = crate::sv::product::DEFAULT;
+    let build = sv
+      .create(
+        "1.0".into(),
+        "panel.exe".into(),
+        None,
+        product,
+        build::Channel::Stable,
+        DEFAULT_PLATFORM,
+      )
+      .await;
+    // the published file must exist to be hashed
+    assert!(build.is_err());
+    let build = sv
+      .create(
+        "1.0".into(),
+        path.clone(),
+        None,
+        product,
+        build::Channel::Stable,
+        DEFAULT_PLATFORM,
+      )
+      .await
+      .unwrap();
+
+    assert!(
+      sv.add_artifact("1.0", DEFAULT_PLATFORM, path.clone()).await.is_err()
+    );
+    sv.add_artifact("1.0", "linux-x64", path.clone()).await.unwrap();
+    // uploading again replaces the file
+    sv.add_artifact("1.0", "linux-x64", path.clone()).await.unwrap();
+    assert_eq!(sv.artifacts("1.0").await.unwrap().len(), 1);
+
+    let (file, _) =
+      sv.file_for(&build, Some("linux-x64")).await.unwrap().unwrap();
+    assert_eq!(file, path);
+    assert!(sv.file_for(&build, Some("macos-arm64")).await.unwrap().is_none());
+
+    assert_eq!(normalize_platform("Win-AMD64"), Some("windows-x64"));
+    assert_eq!(normalize_platform("darwin-aarch64"), Some("macos-arm64"));
+    assert_eq!(normalize_platform("linux-mips"), None);
+  }
+}
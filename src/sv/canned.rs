@@ -0,0 +1,99 @@
+use crate::{entity::canned_response, prelude::*};
+
+pub struct Canned<'a> {
+  db: &'a DatabaseConnection,
+}
+
+impl<'a> Canned<'a> {
+  pub fn new(db: &'a DatabaseConnection) -> Self {
+    Self { db }
+  }
+
+  pub async fn all(&self) -> Result<Vec<canned_response::Model>> {
+    Ok(
+      canned_response::Entity::find()
+        .order_by_asc(canned_response::Column::Name)
+        .all(self.db)
+        .await?,
+    )
+  }
+
+  pub async fn by_name(
+    &self,
+    name: &str,
+  ) -> Result<Option<canned_response::Model>> {
+    Ok(canned_response::Entity::find_by_id(name).one(self.db).await?)
+  }
+
+  /// Create a response or overwrite the text of an existing one
+  pub async fn set(
+    &self,
+    name: &str,
+    text: &str,
+  ) -> Result<canned_response::Model> {
+    if let Some(existing) = self.by_name(name).await? {
+      return Ok(
+        canned_response::ActiveModel {
+          text: Set(text.to_string()),
+          ..existing.into()
+        }
+        .update(self.db)
+        .await?,
+      );
+    }
+
+    Ok(
+      canned_response::ActiveModel {
+        name: Set(name.to_string()),
+        text: Set(text.to_string()),
+        created_at: Set(Utc::now().naive_utc()),
+      }
+      .insert(self.db)
+      .await?,
+    )
+  }
+
+  pub async fn remove(&self, name: &str) -> Result<()> {
+    let res = canned_response::Entity::delete_by_id(name).exec(self.db).await?;
+    if res.rows_affected == 0 {
+      return Err(Error::InvalidArgs(format!(
+        "Canned response '{}' not found",
+        name
+      )));
+    }
+    Ok(())
+  }
+}
+
+/// Replace `{name}` placeholders with their values.
+/// Unknown placeholders are left as is so typos stay visible.
+pub fn expand(template: &str, vars: &[(&str, String)]) -> String {
+  vars.iter().fold(template.to_string(), |text, (name, value)| {
+    text.replace(&format!("{{{}}}", name), value)
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::sv::test_utils::test_db;
+
+  #[tokio::test]
+  async fn test_set_overwrites_and_expands() {
+    let db = test_db::setup().await;
+    let sv = Canned::new(&db);
+
+    sv.set("payment_delay", "Hold on").await.unwrap();
+    sv.set("payment_delay", "Key {key} is valid until {expiry}").await.unwrap();
+    assert_eq!(sv.all().await.unwrap().len(), 1);
+
+    let response = sv.by_name("payment_delay").await.unwrap().unwrap();
+    let text = expand(
+      &response.text,
+      &[("key", "abc".into()), ("expiry", "01.01.2026".into())],
+    );
+    assert_eq!(text, "Key abc is valid until 01.01.2026");
+
+    assert_eq!(expand("{unknown}", &[]), "{unknown}");
+  }
+}
@@ -0,0 +1,693 @@
+use std::collections::HashSet;
+
+use sea_orm::sea_query::{Expr, OnConflict};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+  entity::{
+    LicenseType,
+    feature_flag::{self, FieldScope},
+    license, plan, promo_campaign,
+    promo_code::{self, PromoGrant},
+    sale, user, user_settings,
+  },
+  prelude::*,
+  sv::{self, pricing::Plan as Kind},
+};
+
+/// Bumped when the snapshot layout changes incompatibly
+pub const SNAPSHOT_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlanEntry {
+  pub name: String,
+  pub title: String,
+  pub days: i32,
+  pub calendar: bool,
+  pub price_nano: i64,
+  pub max_sessions: i32,
+  pub active: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PromoCodeEntry {
+  pub code: String,
+  pub grant: PromoGrant,
+  pub amount: i64,
+  pub max_uses: Option<i32>,
+  pub expires_at: Option<DateTime>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CampaignEntry {
+  pub code: String,
+  pub license_type: LicenseType,
+  pub days: i32,
+  pub max_claims: Option<i32>,
+  pub starts_at: DateTime,
+  pub ends_at: DateTime,
+}
+
+/// Sales are matched by name and start, their ids differ between servers
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SaleEntry {
+  pub name: String,
+  pub percent: i32,
+  pub starts_at: DateTime,
+  pub ends_at: DateTime,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FlagEntry {
+  pub scope: FieldScope,
+  pub target: String,
+  pub feature: String,
+  pub enabled: bool,
+}
+
+/// Quiet hours of an admin, see `sv::admin_alert`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AlertEntry {
+  pub tg_user_id: i64,
+  pub quiet_from_hour: Option<i32>,
+  pub quiet_to_hour: Option<i32>,
+}
+
+/// Server configuration as exported by `/config export`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Snapshot {
+  pub version: u32,
+  pub exported_at: DateTime,
+  #[serde(default)]
+  pub plans: Vec<PlanEntry>,
+  #[serde(default)]
+  pub promo_codes: Vec<PromoCodeEntry>,
+  #[serde(default)]
+  pub promo_campaigns: Vec<CampaignEntry>,
+  #[serde(default)]
+  pub sales: Vec<SaleEntry>,
+  #[serde(default)]
+  pub feature_flags: Vec<FlagEntry>,
+  #[serde(default)]
+  pub alerts: Vec<AlertEntry>,
+}
+
+impl Snapshot {
+  pub fn parse(data: &[u8]) -> Result<Self> {
+    let snapshot: Self = json::from_slice(data).map_err(|e| {
+      Error::InvalidArgs(format!("Not a config snapshot: {}", e))
+    })?;
+    if snapshot.version != SNAPSHOT_VERSION {
+      return Err(Error::InvalidArgs(format!(
+        "Snapshot version {} is not supported, expected {}",
+        snapshot.version, SNAPSHOT_VERSION
+      )));
+    }
+    Ok(snapshot)
+  }
+
+  pub fn to_json(&self) -> String {
+    json::to_string_pretty(self).expect("Snapshot is always serializable")
+  }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Change {
+  Add,
+  /// Names of the fields that differ
+  Update(Vec<String>),
+  /// Left out of the import, with the reason
+  Skip(String),
+}
+
+/// What importing a snapshot does to one entry
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diff {
+  pub section: &'static str,
+  pub key: String,
+  pub change: Change,
+}
+
+/// Write a changed entry does
+enum Op {
+  Plan(plan::Model),
+  PromoCode(PromoCodeEntry),
+  Campaign(CampaignEntry),
+  Sale(SaleEntry, Option<i32>),
+  Flag(FlagEntry),
+  Alert(AlertEntry),
+}
+
+/// Fields of two entries whose values differ
+fn changed<T: Serialize>(old: &T, new: &T) -> Vec<String> {
+  let (Ok(json::Value::Object(old)), Ok(json::Value::Object(new))) =
+    (json::to_value(old), json::to_value(new))
+  else {
+    return Vec::new();
+  };
+  new
+    .iter()
+    .filter(|(field, value)| old.get(*field) != Some(value))
+    .map(|(field, _)| field.clone())
+    .collect()
+}
+
+/// Add or update, None when the entry is already the same
+fn compare<T: Serialize>(old: Option<&T>, new: &T) -> Option<Change> {
+  match old {
+    None => Some(Change::Add),
+    Some(old) => {
+      let fields = changed(old, new);
+      (!fields.is_empty()).then_some(Change::Update(fields))
+    }
+  }
+}
+
+fn check_hour(hour: Option<i32>) -> bool {
+  hour.is_none_or(|hour| (0..24).contains(&hour))
+}
+
+fn duplicate(section: &str, key: &str) -> Error {
+  Error::InvalidArgs(format!("{} {} is listed twice", section, key))
+}
+
+pub struct ConfigSync<'a> {
+  db: &'a DatabaseConnection,
+}
+
+impl<'a> ConfigSync<'a> {
+  pub fn new(db: &'a DatabaseConnection) -> Self {
+    Self { db }
+  }
+
+  pub async fn export(&self) -> Result<Snapshot> {
+    let plans = sv::Plan::new(self.db)
+      .all()
+      .await?
+      .into_iter()
+      .map(|(_, plan)| PlanEntry {
+        name: plan.name,
+        title: plan.title,
+        days: plan.days,
+        calendar: plan.calendar,
+        price_nano: plan.price_nano,
+        max_sessions: plan.max_sessions,
+        active: plan.active,
+      })
+      .collect();
+    let promo_codes = sv::PromoCode::new(self.db)
+      .all()
+      .await?
+      .into_iter()
+      .map(|code| PromoCodeEntry {
+        code: code.code,
+        grant: code.grant,
+        amount: code.amount,
+        max_uses: code.max_uses,
+        expires_at: code.expires_at,
+      })
+      .collect();
+    let promo_campaigns = sv::PromoCampaign::new(self.db)
+      .all()
+      .await?
+      .into_iter()
+      .map(|campaign| CampaignEntry {
+        code: campaign.code,
+        license_type: campaign.license_type,
+        days: campaign.days,
+        max_claims: campaign.max_claims,
+        starts_at: campaign.starts_at,
+        ends_at: campaign.ends_at,
+      })
+      .collect();
+    // ended sales change nothing anymore
+    let sales = sv::Pricing::new(self.db)
+      .upcoming_sales()
+      .await?
+      .into_iter()
+      .map(|sale| SaleEntry {
+        name: sale.name,
+        percent: sale.percent,
+        starts_at: sale.starts_at,
+        ends_at: sale.ends_at,
+      })
+      .collect();
+    let feature_flags = feature_flag::Entity::find()
+      .order_by_asc(feature_flag::Column::Target)
+      .order_by_asc(feature_flag::Column::Feature)
+      .all(self.db)
+      .await?
+      .into_iter()
+      .map(|flag| FlagEntry {
+        scope: flag.scope,
+        target: flag.target,
+        feature: flag.feature,
+        enabled: flag.enabled,
+      })
+      .collect();
+    let alerts = user_settings::Entity::find()
+      .filter(user_settings::Column::QuietFromHour.is_not_null())
+      .order_by_asc(user_settings::Column::TgUserId)
+      .all(self.db)
+      .await?
+      .into_iter()
+      .map(|settings| AlertEntry {
+        tg_user_id: settings.tg_user_id,
+        quiet_from_hour: settings.quiet_from_hour,
+        quiet_to_hour: settings.quiet_to_hour,
+      })
+      .collect();
+
+    Ok(Snapshot {
+      version: SNAPSHOT_VERSION,
+      exported_at: Utc::now().naive_utc(),
+      plans,
+      promo_codes,
+      promo_campaigns,
+      sales,
+      feature_flags,
+      alerts,
+    })
+  }
+
+  /// Everything the commands creating these entries would refuse
+  pub fn validate(snapshot: &Snapshot) -> Result<()> {
+    let mut seen = HashSet::new();
+    for entry in &snapshot.plans {
+      let kind = Kind::parse(&entry.name).ok_or_else(|| {
+        Error::InvalidArgs(format!("Unknown plan {}", entry.name))
+      })?;
+      let plan = plan::Model {
+        name: entry.name.clone(),
+        title: entry.title.clone(),
+        days: entry.days,
+        calendar: entry.calendar,
+        price_nano: entry.price_nano,
+        max_sessions: entry.max_sessions,
+        active: entry.active,
+        updated_at: snapshot.exported_at,
+      };
+      sv::plan::check(kind, &plan).map_err(|e| {
+        Error::InvalidArgs(format!("Plan {}: {}", entry.name, e.user_message()))
+      })?;
+      if !seen.insert(("plan", entry.name.clone())) {
+        return Err(duplicate("Plan", &entry.name));
+      }
+    }
+
+    for entry in &snapshot.promo_codes {
+      if entry.code != sv::PromoCampaign::normalize(&entry.code) {
+        return Err(Error::InvalidArgs(format!(
+          "Promo code {} must be lowercase",
+          entry.code
+        )));
+      }
+      sv::PromoCampaign::check_code(&entry.code)?;
+      if entry.amount <= 0 || entry.max_uses.is_some_and(|max| max <= 0) {
+        return Err(Error::InvalidArgs(format!(
+          "Promo code {} needs a positive amount and max uses",
+          entry.code
+        )));
+      }
+      if !seen.insert(("code", entry.code.clone())) {
+        return Err(duplicate("Promo code", &entry.code));
+      }
+    }
+
+    for entry in &snapshot.promo_campaigns {
+      if entry.code != sv::PromoCampaign::normalize(&entry.code) {
+        return Err(Error::InvalidArgs(format!(
+          "Campaign {} must be lowercase",
+          entry.code
+        )));
+      }
+      sv::PromoCampaign::check_code(&entry.code)?;
+      if entry.days <= 0
+        || entry.max_claims.is_some_and(|max| max <= 0)
+        || entry.ends_at <= entry.starts_at
+      {
+        return Err(Error::InvalidArgs(format!(
+          "Campaign {} needs positive days and claims and must end after \
+          it starts",
+          entry.code
+        )));
+      }
+      if !seen.insert(("campaign", entry.code.clone())) {
+        return Err(duplicate("Campaign", &entry.code));
+      }
+    }
+
+    for entry in &snapshot.sales {
+      if !(1..100).contains(&entry.percent) || entry.ends_at <= entry.starts_at
+      {
+        return Err(Error::InvalidArgs(format!(
+          "Sale {} needs a 1-99% discount and must end after it starts",
+          entry.name
+        )));
+      }
+      let key = format!("{} {}", entry.name, entry.starts_at);
+      if !seen.insert(("sale", key.clone())) {
+        return Err(duplicate("Sale", &key));
+      }
+    }
+
+    for entry in &snapshot.feature_flags {
+      let feature = sv::FeatureFlag::normalize_feature(&entry.feature)?;
+      if feature != entry.feature {
+        return Err(Error::InvalidArgs(format!(
+          "Feature {} must be lowercase",
+          entry.feature
+        )));
+      }
+      let key = format!("{:?} {} {}", entry.scope, entry.target, entry.feature);
+      if !seen.insert(("flag", key.clone())) {
+        return Err(duplicate("Feature flag", &key));
+      }
+    }
+
+    for entry in &snapshot.alerts {
+      let hours = (entry.quiet_from_hour, entry.quiet_to_hour);
+      if !check_hour(hours.0)
+        || !check_hour(hours.1)
+        || hours.0.is_some() != hours.1.is_some()
+      {
+        return Err(Error::InvalidArgs(format!(
+          "Quiet hours of {} must both be set to 0-23 or both be empty",
+          entry.tg_user_id
+        )));
+      }
+      if !seen.insert(("alert", entry.tg_user_id.to_string())) {
+        return Err(duplicate("Quiet hours of", &entry.tg_user_id.to_string()));
+      }
+    }
+    Ok(())
+  }
+
+  /// What importing the snapshot would change, nothing is written
+  pub async fn diff(&self, snapshot: &Snapshot) -> Result<Vec<Diff>> {
+    Self::validate(snapshot)?;
+    Ok(self.plan(snapshot).await?.into_iter().map(|(diff, _)| diff).collect())
+  }
+
+  /// Add and update everything the snapshot has in one transaction. Entries
+  /// only this server has are kept.
+  pub async fn apply(&self, snapshot: &Snapshot) -> Result<Vec<Diff>> {
+    Self::validate(snapshot)?;
+    let planned = self.plan(snapshot).await?;
+    let now = Utc::now().naive_utc();
+
+    let txn = self.db.begin().await?;
+    let mut diffs = Vec::new();
+    for (diff, op) in planned {
+      diffs.push(diff);
+      let Some(op) = op else { continue };
+      match op {
+        Op::Plan(mut plan) => {
+          plan.updated_at = now;
+          sv::Plan::save(&txn, &plan).await?;
+        }
+        Op::PromoCode(entry) => {
+          promo_code::Entity::insert(promo_code::ActiveModel {
+            code: Set(entry.code),
+            grant: Set(entry.grant),
+            amount: Set(entry.amount),
+            max_uses: Set(entry.max_uses),
+            expires_at: Set(entry.expires_at),
+            created_at: Set(now),
+          })
+          .on_conflict(
+            OnConflict::column(promo_code::Column::Code)
+              .update_columns([
+                promo_code::Column::Grant,
+                promo_code::Column::Amount,
+                promo_code::Column::MaxUses,
+                promo_code::Column::ExpiresAt,
+              ])
+              .to_owned(),
+          )
+          .exec_without_returning(&txn)
+          .await?;
+        }
+        Op::Campaign(entry) => {
+          promo_campaign::Entity::insert(promo_campaign::ActiveModel {
+            code: Set(entry.code),
+            license_type: Set(entry.license_type),
+            days: Set(entry.days),
+            max_claims: Set(entry.max_claims),
+            starts_at: Set(entry.starts_at),
+            ends_at: Set(entry.ends_at),
+            created_at: Set(now),
+          })
+          .on_conflict(
+            OnConflict::column(promo_campaign::Column::Code)
+              .update_columns([
+                promo_campaign::Column::LicenseType,
+                promo_campaign::Column::Days,
+                promo_campaign::Column::MaxClaims,
+                promo_campaign::Column::StartsAt,
+                promo_campaign::Column::EndsAt,
+              ])
+              .to_owned(),
+          )
+          .exec_without_returning(&txn)
+          .await?;
+        }
+        Op::Sale(entry, id) => {
+          let sale = sale::ActiveModel {
+            id: id.map_or(NotSet, Set),
+            name: Set(entry.name),
+            percent: Set(entry.percent),
+            starts_at: Set(entry.starts_at),
+            ends_at: Set(entry.ends_at),
+            created_at: if id.is_some() { NotSet } else { Set(now) },
+          };
+          if id.is_some() {
+            sale.update(&txn).await?;
+          } else {
+            sale.insert(&txn).await?;
+          }
+        }
+        Op::Flag(entry) => {
+          feature_flag::Entity::insert(feature_flag::ActiveModel {
+            scope: Set(entry.scope),
+            target: Set(entry.target),
+            feature: Set(entry.feature),
+            enabled: Set(entry.enabled),
+            updated_at: Set(now),
+          })
+          .on_conflict(
+            OnConflict::columns([
+              feature_flag::Column::Scope,
+              feature_flag::Column::Target,
+              feature_flag::Column::Feature,
+            ])
+            .update_columns([
+              feature_flag::Column::Enabled,
+              feature_flag::Column::UpdatedAt,
+            ])
+            .to_owned(),
+          )
+          .exec_without_returning(&txn)
+          .await?;
+        }
+        Op::Alert(entry) => {
+          user_settings::Entity::update_many()
+            .col_expr(
+              user_settings::Column::QuietFromHour,
+              Expr::value(entry.quiet_from_hour),
+            )
+            .col_expr(
+              user_settings::Column::QuietToHour,
+              Expr::value(entry.quiet_to_hour),
+            )
+            .filter(user_settings::Column::TgUserId.eq(entry.tg_user_id))
+            .exec(&txn)
+            .await?;
+        }
+      }
+    }
+    txn.commit().await?;
+    Ok(diffs)
+  }
+
+  /// Diff of every entry that differs, with the write it takes
+  async fn plan(&self, snapshot: &Snapshot) -> Result<Vec<(Diff, Option<Op>)>> {
+    let current = self.export().await?;
+    let mut planned = Vec::new();
+    let mut push = |section, key: String, change: Change, op: Option<Op>| {
+      planned.push((Diff { section, key, change }, op));
+    };
+
+    for entry in &snapshot.plans {
+      let old = current.plans.iter().find(|p| p.name == entry.name);
+      if let Some(change) = compare(old, entry) {
+        let plan = plan::Model {
+          name: entry.name.clone(),
+          title: entry.title.clone(),
+          days: entry.days,
+          calendar: entry.calendar,
+          price_nano: entry.price_nano,
+          max_sessions: entry.max_sessions,
+          active: entry.active,
+          updated_at: snapshot.exported_at,
+        };
+        push("plan", entry.name.clone(), change, Some(Op::Plan(plan)));
+      }
+    }
+
+    for entry in &snapshot.promo_codes {
+      let old = current.promo_codes.iter().find(|c| c.code == entry.code);
+      if let Some(change) = compare(old, entry) {
+        let op = Op::PromoCode(entry.clone());
+        push("promo code", entry.code.clone(), change, Some(op));
+      }
+    }
+
+    for entry in &snapshot.promo_campaigns {
+      let old = current.promo_campaigns.iter().find(|c| c.code == entry.code);
+      if let Some(change) = compare(old, entry) {
+        let op = Op::Campaign(entry.clone());
+        push("campaign", entry.code.clone(), change, Some(op));
+      }
+    }
+
+    for entry in &snapshot.sales {
+      let key = format!("{} {}", entry.name, entry.starts_at);
+      let old = current
+        .sales
+        .iter()
+        .find(|s| s.name == entry.name && s.starts_at == entry.starts_at);
+      let Some(change) = compare(old, entry) else { continue };
+      let id = match old {
+        Some(_) => sale::Entity::find()
+          .filter(sale::Column::Name.eq(&entry.name))
+          .filter(sale::Column::StartsAt.eq(entry.starts_at))
+          .one(self.db)
+          .await?
+          .map(|sale| sale.id),
+        None => None,
+      };
+      push("sale", key, change, Some(Op::Sale(entry.clone(), id)));
+    }
+
+    for entry in &snapshot.feature_flags {
+      let key = format!("{:?} {} {}", entry.scope, entry.target, entry.feature);
+      let old = current.feature_flags.iter().find(|f| {
+        f.scope == entry.scope
+          && f.target == entry.target
+          && f.feature == entry.feature
+      });
+      let Some(change) = compare(old, entry) else { continue };
+      let exists = match entry.scope {
+        FieldScope::User => match entry.target.parse::<i64>() {
+          Ok(id) => user::Entity::find_by_id(id).one(self.db).await?.is_some(),
+          Err(_) => false,
+        },
+        FieldScope::License => license::Entity::find_by_id(&entry.target)
+          .one(self.db)
+          .await?
+          .is_some(),
+      };
+      if exists {
+        push("flag", key, change, Some(Op::Flag(entry.clone())));
+      } else {
+        let reason = format!("no such {:?} here", entry.scope).to_lowercase();
+        push("flag", key, Change::Skip(reason), None);
+      }
+    }
+
+    for entry in &snapshot.alerts {
+      let key = entry.tg_user_id.to_string();
+      let settings = user_settings::Entity::find_by_id(entry.tg_user_id)
+        .one(self.db)
+        .await?;
+      let Some(settings) = settings else {
+        push(
+          "quiet hours",
+          key,
+          Change::Skip("never used the bot".into()),
+          None,
+        );
+        continue;
+      };
+      let old = AlertEntry {
+        tg_user_id: settings.tg_user_id,
+        quiet_from_hour: settings.quiet_from_hour,
+        quiet_to_hour: settings.quiet_to_hour,
+      };
+      if let Some(change) = compare(Some(&old), entry) {
+        push("quiet hours", key, change, Some(Op::Alert(entry.clone())));
+      }
+    }
+    Ok(planned)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::sv::{referral::NANO_USDT, test_utils::test_db};
+
+  #[tokio::test]
+  async fn test_export_import() {
+    let staging = test_db::setup().await;
+    let sv = ConfigSync::new(&staging);
+    sv::Plan::new(&staging)
+      .set(Kind::Month, sv::plan::PlanField::Price(12 * NANO_USDT))
+      .await
+      .unwrap();
+    sv::PromoCode::new(&staging)
+      .create("Stream5", PromoGrant::Balance, 5 * NANO_USDT, Some(10), None)
+      .await
+      .unwrap();
+    sv::User::new(&staging).get_or_create(7).await.unwrap();
+    sv::FeatureFlag::new(&staging)
+      .set(FieldScope::User, "7", "auto_trade", true)
+      .await
+      .unwrap();
+    sv::Pricing::new(&staging)
+      .start_sale("Weekend", 20, TimeDelta::days(2))
+      .await
+      .unwrap();
+    let snapshot =
+      Snapshot::parse(sv.export().await.unwrap().to_json().as_bytes()).unwrap();
+
+    // nothing differs from where it came from
+    assert!(sv.diff(&snapshot).await.unwrap().is_empty());
+
+    let production = test_db::setup().await;
+    let sv = ConfigSync::new(&production);
+    let diff = sv.diff(&snapshot).await.unwrap();
+    let find = |section: &str| {
+      diff.iter().find(|d| d.section == section).unwrap().change.clone()
+    };
+    assert_eq!(find("plan"), Change::Update(vec!["price_nano".into()]));
+    assert_eq!(find("promo code"), Change::Add);
+    assert_eq!(find("sale"), Change::Add);
+    assert!(matches!(find("flag"), Change::Skip(_)));
+
+    // preview writes nothing
+    assert!(sv::PromoCode::new(&production).all().await.unwrap().is_empty());
+
+    sv.apply(&snapshot).await.unwrap();
+    let month = sv::Plan::new(&production).get(Kind::Month).await.unwrap();
+    assert_eq!(month.price_nano, 12 * NANO_USDT);
+    assert_eq!(sv::PromoCode::new(&production).all().await.unwrap().len(), 1);
+    assert!(
+      sv::Pricing::new(&production).active_sale().await.unwrap().is_some()
+    );
+
+    // only the skipped flag is left
+    let diff = sv.diff(&snapshot).await.unwrap();
+    assert_eq!(diff.len(), 1);
+    assert_eq!(diff[0].section, "flag");
+
+    let mut broken = snapshot.clone();
+    broken.plans[0].days = 0;
+    assert!(matches!(sv.apply(&broken).await, Err(Error::InvalidArgs(_))));
+    broken = snapshot.clone();
+    broken.promo_codes.push(broken.promo_codes[0].clone());
+    assert!(matches!(sv.diff(&broken).await, Err(Error::InvalidArgs(_))));
+    assert!(Snapshot::parse(b"{\"version\": 2}").is_err());
+  }
+}
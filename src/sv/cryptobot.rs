@@ -1,8 +1,11 @@
 #![allow(dead_code)]
 
-use std::collections::HashMap;
+use std::{
+  collections::HashMap,
+  sync::{Arc, Mutex},
+};
 
-use reqwest::Client;
+use reqwest::{Client, RequestBuilder};
 use serde::{Deserialize, Serialize};
 
 use crate::prelude::*;
@@ -20,6 +23,16 @@ pub enum InvoiceStatus {
   Expired,
 }
 
+impl InvoiceStatus {
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      InvoiceStatus::Active => "active",
+      InvoiceStatus::Paid => "paid",
+      InvoiceStatus::Expired => "expired",
+    }
+  }
+}
+
 /// Invoice response from CryptoBot API
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Invoice {
@@ -126,12 +139,83 @@ pub struct CreateInvoiceParams {
   pub allow_anonymous: Option<bool>,
 }
 
+/// Consecutive failed calls that open the circuit
+pub const TRIP_FAILURES: u32 = 5;
+/// How long an open circuit refuses calls before letting them probe again
+pub const COOL_DOWN_SECS: i64 = 60;
+
+/// Circuit breaker over the API: after [`TRIP_FAILURES`] transport or
+/// server failures in a row calls fail fast for [`COOL_DOWN_SECS`], then go
+/// through again until one succeeds (closing it) or fails (reopening it).
+/// Errors the API answers with, like a bad amount, don't count.
+#[derive(Debug, Default)]
+pub struct Breaker {
+  state: Mutex<BreakerState>,
+}
+
+#[derive(Debug, Default)]
+struct BreakerState {
+  failures: u32,
+  last_error: Option<String>,
+  /// When the circuit first opened, kept through failed probes
+  down_since: Option<DateTime>,
+  /// Start of the current cool-down
+  opened_at: Option<DateTime>,
+}
+
+/// Ongoing outage, for alerts and banners
+#[derive(Debug, Clone, PartialEq)]
+pub struct Outage {
+  pub since: DateTime,
+  pub failures: u32,
+  pub last_error: String,
+}
+
+impl Breaker {
+  fn lock(&self) -> std::sync::MutexGuard<'_, BreakerState> {
+    self.state.lock().unwrap_or_else(|e| e.into_inner())
+  }
+
+  /// Whether calls are refused right now
+  pub fn is_open(&self, now: DateTime) -> bool {
+    self
+      .lock()
+      .opened_at
+      .is_some_and(|at| now - at < TimeDelta::seconds(COOL_DOWN_SECS))
+  }
+
+  /// The ongoing outage, also while calls probe after the cool-down
+  pub fn outage(&self) -> Option<Outage> {
+    let state = self.lock();
+    state.down_since.map(|since| Outage {
+      since,
+      failures: state.failures,
+      last_error: state.last_error.clone().unwrap_or_default(),
+    })
+  }
+
+  pub fn record_success(&self) {
+    *self.lock() = BreakerState::default();
+  }
+
+  pub fn record_failure(&self, error: &str, now: DateTime) {
+    let mut state = self.lock();
+    state.failures += 1;
+    state.last_error = Some(error.to_string());
+    if state.failures >= TRIP_FAILURES {
+      state.down_since.get_or_insert(now);
+      state.opened_at = Some(now);
+    }
+  }
+}
+
 /// CryptoBot client for payment processing
 #[derive(Clone)]
 pub struct CryptoBot {
   client: Client,
   base_url: String,
   api_token: String,
+  breaker: Arc<Breaker>,
 }
 
 impl CryptoBot {
@@ -143,7 +227,12 @@ impl CryptoBot {
       MAINNET_URL.to_string()
     };
 
-    Self { client: Client::new(), base_url, api_token }
+    Self {
+      client: Client::new(),
+      base_url,
+      api_token,
+      breaker: Arc::new(Breaker::default()),
+    }
   }
 
   /// Make an API request
@@ -161,26 +250,7 @@ impl CryptoBot {
       request = request.query(&p);
     }
 
-    let response = request
-      .send()
-      .await
-      .map_err(|e| Error::CryptoBot(format!("Request failed: {}", e)))?;
-
-    let api_response: ApiResponse<T> = response.json().await.map_err(|e| {
-      Error::CryptoBot(format!("Failed to parse response: {}", e))
-    })?;
-
-    if api_response.ok {
-      api_response
-        .result
-        .ok_or_else(|| Error::CryptoBot("Empty result".to_string()))
-    } else {
-      let err = api_response.error.map_or_else(
-        || "Unknown error".to_string(),
-        |e| format!("{}: {}", e.name, e.code),
-      );
-      Err(Error::CryptoBot(err))
-    }
+    self.call(request).await
   }
 
   /// Make a POST request with JSON body
@@ -191,20 +261,45 @@ impl CryptoBot {
   ) -> Result<T> {
     let url = format!("{}{}", self.base_url, method);
 
-    let response = self
+    let request = self
       .client
       .post(&url)
       .header("Crypto-Pay-API-Token", &self.api_token)
-      .json(body)
+      .json(body);
+
+    self.call(request).await
+  }
+
+  /// Send a request through the breaker
+  async fn call<T: for<'de> Deserialize<'de>>(
+    &self,
+    request: RequestBuilder,
+  ) -> Result<T> {
+    let now = Utc::now().naive_utc();
+    if self.breaker.is_open(now) {
+      return Err(Error::PaymentsUnavailable);
+    }
+
+    let outage = |e: String| {
+      self.breaker.record_failure(&e, now);
+      Error::CryptoBot(e)
+    };
+
+    let response = request
       .send()
       .await
-      .map_err(|e| Error::CryptoBot(format!("Request failed: {}", e)))?;
+      .map_err(|e| outage(format!("Request failed: {}", e)))?;
+    if response.status().is_server_error() {
+      return Err(outage(format!("Server error: {}", response.status())));
+    }
 
-    let api_response: ApiResponse<T> = response.json().await.map_err(|e| {
-      Error::CryptoBot(format!("Failed to parse response: {}", e))
-    })?;
+    let api_response: ApiResponse<T> = response
+      .json()
+      .await
+      .map_err(|e| outage(format!("Failed to parse response: {}", e)))?;
 
     if api_response.ok {
+      self.breaker.record_success();
       api_response
         .result
         .ok_or_else(|| Error::CryptoBot("Empty result".to_string()))
@@ -213,10 +308,21 @@ impl CryptoBot {
         || "Unknown error".to_string(),
         |e| format!("{}: {}", e.name, e.code),
       );
+      // the API answered, the request itself was wrong
+      self.breaker.record_success();
       Err(Error::CryptoBot(err))
     }
   }
 
+  /// Whether payments can be attempted, false while the circuit is open
+  pub fn is_available(&self) -> bool {
+    !self.breaker.is_open(Utc::now().naive_utc())
+  }
+
+  pub fn outage(&self) -> Option<Outage> {
+    self.breaker.outage()
+  }
+
   /// Test API connection and get app info
   pub async fn get_me(&self) -> Result<AppInfo> {
     self.request("getMe", None).await
@@ -251,16 +357,31 @@ impl CryptoBot {
     }
 
     if let Some(s) = status {
-      let status_str = match s {
-        InvoiceStatus::Active => "active",
-        InvoiceStatus::Paid => "paid",
-        InvoiceStatus::Expired => "expired",
-      };
-      params.insert("status".to_string(), status_str.to_string());
+      params.insert("status".to_string(), s.as_str().to_string());
     }
 
     let params = if params.is_empty() { None } else { Some(params) };
+    self.invoices(params).await
+  }
 
+  /// One page of invoices with the status, newest first
+  pub async fn list_invoices(
+    &self,
+    status: InvoiceStatus,
+    offset: usize,
+    count: usize,
+  ) -> Result<Vec<Invoice>> {
+    let mut params = HashMap::new();
+    params.insert("status".to_string(), status.as_str().to_string());
+    params.insert("offset".to_string(), offset.to_string());
+    params.insert("count".to_string(), count.to_string());
+    self.invoices(Some(params)).await
+  }
+
+  async fn invoices(
+    &self,
+    params: Option<HashMap<String, String>>,
+  ) -> Result<Vec<Invoice>> {
     #[derive(Deserialize)]
     struct ItemsResponse {
       items: Vec<Invoice>,
@@ -382,6 +503,11 @@ impl CryptoBot {
     json::from_str(payload).ok()
   }
 
+  /// Verify a webhook body against this app's token
+  pub fn verify_webhook(&self, body: &[u8], signature: &str) -> bool {
+    Self::verify_signature(&self.api_token, body, signature)
+  }
+
   /// Verify webhook signature
   pub fn verify_signature(
     api_token: &str,
@@ -406,9 +532,8 @@ impl CryptoBot {
       .expect("HMAC can take key of any size");
     mac.update(body);
 
-    // Verify signature
-    let expected = hex::encode(mac.finalize().into_bytes());
-    expected == signature
+    // Verify signature in constant time
+    hex::decode(signature).is_ok_and(|sig| mac.verify_slice(&sig).is_ok())
   }
 }
 
@@ -447,4 +572,44 @@ mod tests {
     assert_eq!(parsed.discount_percent.unwrap(), 3);
     assert_eq!(parsed.referrer_id.unwrap(), 67890);
   }
+
+  #[test]
+  fn test_breaker() {
+    let breaker = Breaker::default();
+    let start = Utc::now().naive_utc();
+    let at = |secs| start + TimeDelta::seconds(secs);
+
+    for _ in 1..TRIP_FAILURES {
+      breaker.record_failure("timeout", start);
+    }
+    assert!(!breaker.is_open(start));
+    assert!(breaker.outage().is_none());
+
+    breaker.record_failure("timeout", at(1));
+    assert!(breaker.is_open(at(1)));
+    assert!(breaker.is_open(at(COOL_DOWN_SECS)));
+
+    // probing after the cool-down, a failure reopens the same outage
+    assert!(!breaker.is_open(at(COOL_DOWN_SECS + 1)));
+    breaker.record_failure("502", at(COOL_DOWN_SECS + 1));
+    assert!(breaker.is_open(at(COOL_DOWN_SECS + 2)));
+    let outage = breaker.outage().unwrap();
+    assert_eq!(outage.since, at(1));
+    assert_eq!(outage.failures, TRIP_FAILURES + 1);
+    assert_eq!(outage.last_error, "502");
+
+    breaker.record_success();
+    assert!(!breaker.is_open(at(COOL_DOWN_SECS + 2)));
+    assert!(breaker.outage().is_none());
+  }
+
+  #[test]
+  fn test_verify_signature() {
+    let body = br#"{"update_id":1}"#;
+    let signature =
+      "0dc254d4eff6219ba2c93d0fc43b964f110cba3be303df459301c9dabac9bfeb";
+    assert!(CryptoBot::verify_signature("token", body, signature));
+    assert!(!CryptoBot::verify_signature("other", body, signature));
+    assert!(!CryptoBot::verify_signature("token", body, "not hex"));
+  }
 }
@@ -0,0 +1,204 @@
+use std::collections::HashMap;
+
+use sea_orm::sea_query::OnConflict;
+
+use crate::{
+  entity::{
+    custom_field::{self, FieldScope},
+    license, user,
+  },
+  prelude::*,
+};
+
+const MAX_NAME_LEN: usize = 32;
+const MAX_VALUE_LEN: usize = 256;
+
+pub struct CustomField<'a> {
+  db: &'a DatabaseConnection,
+}
+
+impl<'a> CustomField<'a> {
+  pub fn new(db: &'a DatabaseConnection) -> Self {
+    Self { db }
+  }
+
+  /// Names are lowercase identifiers, e.g. `crm_id`
+  fn normalize_name(name: &str) -> Result<String> {
+    let name = name.trim().to_lowercase();
+    let valid = !name.is_empty()
+      && name.len() <= MAX_NAME_LEN
+      && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if !valid {
+      return Err(Error::InvalidArgs(format!(
+        "Field name must be 1-{} letters, digits or '_'",
+        MAX_NAME_LEN
+      )));
+    }
+    Ok(name)
+  }
+
+  async fn ensure_target(&self, scope: FieldScope, target: &str) -> Result<()> {
+    match scope {
+      FieldScope::User => {
+        let id = target.parse::<i64>().map_err(|_| Error::UserNotFound)?;
+        user::Entity::find_by_id(id)
+          .one(self.db)
+          .await?
+          .ok_or(Error::UserNotFound)?;
+      }
+      FieldScope::License => {
+        license::Entity::find_by_id(target)
+          .one(self.db)
+          .await?
+          .ok_or(Error::LicenseNotFound)?;
+      }
+    }
+    Ok(())
+  }
+
+  pub async fn set(
+    &self,
+    scope: FieldScope,
+    target: &str,
+    name: &str,
+    value: &str,
+  ) -> Result<custom_field::Model> {
+    let name = Self::normalize_name(name)?;
+    let value = value.trim();
+    if value.is_empty() || value.chars().count() > MAX_VALUE_LEN {
+      return Err(Error::InvalidArgs(format!(
+        "Field value must be 1-{} characters",
+        MAX_VALUE_LEN
+      )));
+    }
+    self.ensure_target(scope, target).await?;
+
+    let field = custom_field::ActiveModel {
+      scope: Set(scope),
+      target: Set(target.to_string()),
+      name: Set(name.clone()),
+      value: Set(value.to_string()),
+      updated_at: Set(Utc::now().naive_utc()),
+    };
+    custom_field::Entity::insert(field)
+      .on_conflict(
+        OnConflict::columns([
+          custom_field::Column::Scope,
+          custom_field::Column::Target,
+          custom_field::Column::Name,
+        ])
+        .update_columns([
+          custom_field::Column::Value,
+          custom_field::Column::UpdatedAt,
+        ])
+        .to_owned(),
+      )
+      .exec_without_returning(self.db)
+      .await?;
+
+    custom_field::Entity::find_by_id((scope, target.to_string(), name))
+      .one(self.db)
+      .await?
+      .ok_or_else(|| Error::Internal("Custom field vanished".into()))
+  }
+
+  pub async fn remove(
+    &self,
+    scope: FieldScope,
+    target: &str,
+    name: &str,
+  ) -> Result<()> {
+    let name = Self::normalize_name(name)?;
+    let res =
+      custom_field::Entity::delete_by_id((scope, target.to_string(), name))
+        .exec(self.db)
+        .await?;
+    if res.rows_affected == 0 {
+      return Err(Error::InvalidArgs("No such field".into()));
+    }
+    Ok(())
+  }
+
+  pub async fn of(
+    &self,
+    scope: FieldScope,
+    target: &str,
+  ) -> Result<Vec<custom_field::Model>> {
+    Ok(
+      custom_field::Entity::find()
+        .filter(custom_field::Column::Scope.eq(scope))
+        .filter(custom_field::Column::Target.eq(target))
+        .order_by_asc(custom_field::Column::Name)
+        .all(self.db)
+        .await?,
+    )
+  }
+
+  /// Every field of a scope grouped by target, for exports
+  pub async fn all(
+    &self,
+    scope: FieldScope,
+  ) -> Result<HashMap<String, Vec<custom_field::Model>>> {
+    let fields = custom_field::Entity::find()
+      .filter(custom_field::Column::Scope.eq(scope))
+      .order_by_asc(custom_field::Column::Name)
+      .all(self.db)
+      .await?;
+
+    let mut grouped: HashMap<String, Vec<_>> = HashMap::new();
+    for field in fields {
+      grouped.entry(field.target.clone()).or_default().push(field);
+    }
+    Ok(grouped)
+  }
+}
+
+/// `name=value` pairs on one line
+pub fn format_inline(fields: &[custom_field::Model]) -> String {
+  fields
+    .iter()
+    .map(|f| format!("{}={}", f.name, f.value))
+    .collect::<Vec<_>>()
+    .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{
+    entity::LicenseType,
+    sv::{self, test_utils::test_db},
+  };
+
+  #[tokio::test]
+  async fn test_fields_follow_regenerated_key() {
+    let db = test_db::setup().await;
+    let sv = CustomField::new(&db);
+
+    let license =
+      sv::License::new(&db).create(1, LicenseType::Pro, 30).await.unwrap();
+
+    sv.set(FieldScope::User, "1", "CRM_id", "42").await.unwrap();
+    sv.set(FieldScope::User, "1", "crm_id", "43").await.unwrap();
+    sv.set(FieldScope::License, &license.key, "reseller", "acme")
+      .await
+      .unwrap();
+
+    let fields = sv.of(FieldScope::User, "1").await.unwrap();
+    assert_eq!(fields.len(), 1);
+    assert_eq!(fields[0].name, "crm_id");
+    assert_eq!(fields[0].value, "43");
+
+    assert!(sv.set(FieldScope::User, "2", "crm_id", "1").await.is_err());
+    assert!(sv.set(FieldScope::User, "1", "bad name", "1").await.is_err());
+
+    let regenerated =
+      sv::License::new(&db).regenerate_key(&license.key, 1).await.unwrap();
+    let all = sv.all(FieldScope::License).await.unwrap();
+    assert_eq!(format_inline(&all[&regenerated.key]), "reseller=acme");
+    assert!(!all.contains_key(&license.key));
+
+    sv.remove(FieldScope::User, "1", "crm_id").await.unwrap();
+    assert!(sv.remove(FieldScope::User, "1", "crm_id").await.is_err());
+  }
+}
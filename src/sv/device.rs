@@ -0,0 +1,128 @@
+use crate::{entity::license_device, prelude::*};
+
+/// Unusual activation the license owner should confirm
+#[derive(Debug, Clone, PartialEq)]
+pub enum Anomaly {
+  NewDevice { device_id: i32 },
+  NewCountry { device_id: i32, country: String },
+}
+
+pub struct Device<'a> {
+  db: &'a DatabaseConnection,
+}
+
+impl<'a> Device<'a> {
+  pub fn new(db: &'a DatabaseConnection) -> Self {
+    Self { db }
+  }
+
+  /// Record a new session opened by `hwid` on the license.
+  /// Reports machines and countries never seen with this key before,
+  /// except for the very first activation.
+  pub async fn touch(
+    &self,
+    key: &str,
+    hwid: &str,
+    ip: Option<String>,
+    country: Option<String>,
+  ) -> Result<Option<Anomaly>> {
+    let now = Utc::now().naive_utc();
+
+    let devices = self.by_license(key).await?;
+    let first_use = devices.is_empty();
+    let new_country = country.as_ref().filter(|country| {
+      !devices.iter().any(|d| d.country.as_ref() == Some(*country))
+    });
+
+    if let Some(device) = devices.iter().find(|d| d.hwid == hwid) {
+      let anomaly = new_country.map(|country| Anomaly::NewCountry {
+        device_id: device.id,
+        country: country.clone(),
+      });
+
+      let sessions = device.sessions + 1;
+      let ip = ip.or_else(|| device.ip.clone());
+      let country = country.or_else(|| device.country.clone());
+      license_device::ActiveModel {
+        ip: Set(ip),
+        country: Set(country),
+        last_seen: Set(now),
+        sessions: Set(sessions),
+        ..device.clone().into()
+      }
+      .update(self.db)
+      .await?;
+      return Ok(anomaly);
+    }
+
+    let device = license_device::ActiveModel {
+      id: NotSet,
+      license_key: Set(key.to_string()),
+      hwid: Set(hwid.to_string()),
+      ip: Set(ip),
+      country: Set(country),
+      first_seen: Set(now),
+      last_seen: Set(now),
+      sessions: Set(1),
+    }
+    .insert(self.db)
+    .await?;
+
+    Ok((!first_use).then_some(Anomaly::NewDevice { device_id: device.id }))
+  }
+
+  pub async fn by_id(&self, id: i32) -> Result<Option<license_device::Model>> {
+    Ok(license_device::Entity::find_by_id(id).one(self.db).await?)
+  }
+
+  /// All machines seen with the license, most recent first
+  pub async fn by_license(
+    &self,
+    key: &str,
+  ) -> Result<Vec<license_device::Model>> {
+    Ok(
+      license_device::Entity::find()
+        .filter(license_device::Column::LicenseKey.eq(key))
+        .order_by_desc(license_device::Column::LastSeen)
+        .all(self.db)
+        .await?,
+    )
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{entity::LicenseType, sv, sv::test_utils::test_db};
+
+  #[tokio::test]
+  async fn test_touch_tracks_distinct_devices() {
+    let db = test_db::setup().await;
+    let license =
+      sv::License::new(&db).create(12345, LicenseType::Pro, 30).await.unwrap();
+    let key = &license.key;
+    let de = || Some("DE".to_string());
+
+    let sv = Device::new(&db);
+    // the first activation is expected, nothing to report
+    assert_eq!(sv.touch(key, "hwid-a", None, de()).await.unwrap(), None);
+    assert_eq!(sv.touch(key, "hwid-a", None, de()).await.unwrap(), None);
+
+    let ip = Some("10.0.0.1".to_string());
+    let anomaly = sv.touch(key, "hwid-b", ip, de()).await.unwrap();
+    assert!(matches!(anomaly, Some(Anomaly::NewDevice { .. })));
+
+    let anomaly = sv.touch(key, "hwid-a", None, Some("BR".into())).await;
+    assert!(matches!(
+      anomaly.unwrap(),
+      Some(Anomaly::NewCountry { country, .. }) if country == "BR"
+    ));
+
+    let devices = sv.by_license(key).await.unwrap();
+    assert_eq!(devices.len(), 2);
+
+    let a = devices.iter().find(|d| d.hwid == "hwid-a").unwrap();
+    assert_eq!(a.sessions, 3);
+    assert_eq!(a.country.as_deref(), Some("BR"));
+  }
+}
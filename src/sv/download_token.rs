@@ -0,0 +1,215 @@
+use uuid::Uuid;
+
+use crate::{
+  entity::{download, download_token, license},
+  prelude::*,
+};
+
+/// How long after the first request an interrupted download may resume
+pub const RESUME_WINDOW: TimeDelta = TimeDelta::hours(6);
+
+pub struct DownloadTokens<'a> {
+  db: &'a DatabaseConnection,
+}
+
+impl<'a> DownloadTokens<'a> {
+  pub fn new(db: &'a DatabaseConnection) -> Self {
+    Self { db }
+  }
+
+  pub async fn create(
+    &self,
+    tg_user_id: i64,
+    version: &str,
+    platform: Option<&str>,
+    lifetime: TimeDelta,
+  ) -> Result<download_token::Model> {
+    let now = Utc::now().naive_utc();
+    let token = download_token::ActiveModel {
+      token: Set(Uuid::new_v4().to_string()),
+      tg_user_id: Set(tg_user_id),
+      version: Set(version.to_string()),
+      platform: Set(platform.map(str::to_string)),
+      created_at: Set(now),
+      expires_at: Set(now + lifetime),
+      used_at: Set(None),
+    };
+    Ok(token.insert(self.db).await?)
+  }
+
+  /// Mark an unexpired token used, `None` if it is unknown, expired or
+  /// already used. The update is the check, so two racing requests can't
+  /// both get the file.
+  pub async fn redeem(
+    &self,
+    token: &str,
+  ) -> Result<Option<download_token::Model>> {
+    let now = Utc::now().naive_utc();
+    let res = download_token::Entity::update_many()
+      .col_expr(download_token::Column::UsedAt, now.into())
+      .filter(download_token::Column::Token.eq(token))
+      .filter(download_token::Column::UsedAt.is_null())
+      .filter(download_token::Column::ExpiresAt.gt(now))
+      .exec(self.db)
+      .await?;
+    if res.rows_affected == 0 {
+      return Ok(None);
+    }
+    Ok(download_token::Entity::find_by_id(token).one(self.db).await?)
+  }
+
+  pub async fn by_token(
+    &self,
+    token: &str,
+  ) -> Result<Option<download_token::Model>> {
+    Ok(download_token::Entity::find_by_id(token).one(self.db).await?)
+  }
+
+  /// Whether a used token's download may still be resumed with a range
+  /// request from `ip`, the address its download was logged with
+  pub async fn resumable(&self, token: &str, ip: &str) -> Result<bool> {
+    let since = Utc::now().naive_utc() - RESUME_WINDOW;
+    let used = download_token::Entity::find_by_id(token)
+      .filter(download_token::Column::UsedAt.gt(since))
+      .one(self.db)
+      .await?;
+    if used.is_none() {
+      return Ok(false);
+    }
+    let logged = download::Entity::find()
+      .filter(download::Column::Token.eq(token))
+      .filter(download::Column::Ip.eq(ip))
+      .count(self.db)
+      .await?;
+    Ok(logged > 0)
+  }
+
+  /// Log a started download under the owner's license of `product` that
+  /// runs the longest
+  pub async fn record(
+    &self,
+    token: &download_token::Model,
+    product: &str,
+    ip: Option<String>,
+  ) -> Result<download::Model> {
+    let now = Utc::now().naive_utc();
+    let license = license::Entity::find()
+      .filter(license::Column::TgUserId.eq(token.tg_user_id))
+      .filter(license::Column::Product.eq(product))
+      .filter(license::Column::IsBlocked.eq(false))
+      .filter(license::Column::ExpiresAt.gt(now))
+      .order_by_desc(license::Column::ExpiresAt)
+      .one(self.db)
+      .await?;
+
+    let record = download::ActiveModel {
+      tg_user_id: Set(token.tg_user_id),
+      license_key: Set(license.map(|license| license.key)),
+      version: Set(token.version.clone()),
+      platform: Set(token.platform.clone()),
+      token: Set(token.token.clone()),
+      ip: Set(ip),
+      created_at: Set(now),
+      ..Default::default()
+    };
+    Ok(record.insert(self.db).await?)
+  }
+
+  /// Newest downloads of a version and how many there are in total
+  pub async fn downloads(
+    &self,
+    version: &str,
+    limit: u64,
+  ) -> Result<(Vec<download::Model>, u64)> {
+    let query =
+      download::Entity::find().filter(download::Column::Version.eq(version));
+    let total = query.clone().count(self.db).await?;
+    let recent = query
+      .order_by_desc(download::Column::Id)
+      .limit(limit)
+      .all(self.db)
+      .await?;
+    Ok((recent, total))
+  }
+
+  /// Drop expired links nobody used, used ones stay as the audit trail
+  pub async fn cleanup(&self) -> Result<u64> {
+    let now = Utc::now().naive_utc();
+    let res = download_token::Entity::delete_many()
+      .filter(download_token::Column::UsedAt.is_null())
+      .filter(download_token::Column::ExpiresAt.lt(now))
+      .exec(self.db)
+      .await?;
+    Ok(res.rows_affected)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{
+    entity::LicenseType,
+    sv::{self, test_utils::test_db},
+  };
+
+  #[tokio::test]
+  async fn test_single_use() {
+    let db = test_db::setup().await;
+    let sv = DownloadTokens::new(&db);
+
+    let token =
+      sv.create(1, "1.0", None, TimeDelta::minutes(10)).await.unwrap();
+    let used = sv.redeem(&token.token).await.unwrap().unwrap();
+    assert_eq!((used.tg_user_id, used.version.as_str()), (1, "1.0"));
+    assert!(used.used_at.is_some());
+    assert!(sv.redeem(&token.token).await.unwrap().is_none());
+    // but the download can be resumed where it was started
+    sv.record(&used, "default", Some("10.0.0.1".into())).await.unwrap();
+    assert!(sv.resumable(&token.token, "10.0.0.1").await.unwrap());
+    assert!(!sv.resumable(&token.token, "10.0.0.2").await.unwrap());
+
+    let expired =
+      sv.create(1, "1.0", None, TimeDelta::minutes(-1)).await.unwrap();
+    assert!(sv.redeem(&expired.token).await.unwrap().is_none());
+    assert!(!sv.resumable(&expired.token, "10.0.0.1").await.unwrap());
+    assert!(sv.redeem("missing").await.unwrap().is_none());
+
+    // the used token is kept, the expired one is dropped
+    assert_eq!(sv.cleanup().await.unwrap(), 1);
+    assert!(
+      download_token::Entity::find_by_id(token.token)
+        .one(&db)
+        .await
+        .unwrap()
+        .is_some()
+    );
+  }
+
+  #[tokio::test]
+  async fn test_download_log() {
+    let db = test_db::setup().await;
+    let sv = DownloadTokens::new(&db);
+    let license =
+      sv::License::new(&db).create(1, LicenseType::Pro, 30).await.unwrap();
+
+    let token = sv
+      .create(1, "1.0", Some("linux-x64"), TimeDelta::minutes(10))
+      .await
+      .unwrap();
+    let ip = Some("10.0.0.1".to_string());
+    let record = sv.record(&token, &license.product, ip).await.unwrap();
+    assert_eq!(record.license_key.as_deref(), Some(license.key.as_str()));
+    assert_eq!(record.platform.as_deref(), Some("linux-x64"));
+
+    // a user without a license of the product is still logged
+    let other =
+      sv.create(2, "1.0", None, TimeDelta::minutes(10)).await.unwrap();
+    let record = sv.record(&other, &license.product, None).await.unwrap();
+    assert!(record.license_key.is_none());
+
+    let (recent, total) = sv.downloads("1.0", 1).await.unwrap();
+    assert_eq!((recent.len(), total), (1, 2));
+    assert_eq!(recent[0].tg_user_id, 2);
+    assert_eq!(sv.downloads("2.0", 10).await.unwrap().1, 0);
+  }
+}
@@ -0,0 +1,163 @@
+use std::collections::HashSet;
+
+use crate::{
+  entity::{license, pending_invoice},
+  prelude::*,
+  sv::{self, pricing::Plan},
+};
+
+/// Purchase eligibility rules, a limit of 0 or an empty list disables one
+#[derive(Debug, Clone, Default)]
+pub struct Rules {
+  /// Refuse users who have a blocked license
+  pub block_banned: bool,
+  /// Upper-cased ISO codes purchases are refused from
+  pub blocked_countries: HashSet<String>,
+  /// Unexpired unpaid invoices a user may have open
+  pub max_unpaid_invoices: u64,
+  /// Unexpired licenses one user may hold, resellers aside. `sv::License`
+  /// enforces it on creation too
+  pub max_licenses: u64,
+  /// Sell the trial plan only to users who never had a license
+  pub trial_first_only: bool,
+  /// Risk score from which purchases need a manual review
+  pub review_risk_score: i32,
+}
+
+/// What is being bought
+#[derive(Debug, Clone, Copy)]
+pub enum Purchase<'p> {
+  New(Plan),
+  Extend(&'p license::Model),
+}
+
+pub struct Eligibility<'a> {
+  db: &'a DatabaseConnection,
+}
+
+impl<'a> Eligibility<'a> {
+  pub fn new(db: &'a DatabaseConnection) -> Self {
+    Self { db }
+  }
+
+  /// Evaluate every enabled rule, the first one that fails is the reason
+  pub async fn check(
+    &self,
+    tg_user_id: i64,
+    purchase: Purchase<'_>,
+    rules: &Rules,
+  ) -> Result<()> {
+    let now = Utc::now().naive_utc();
+    let licenses = license::Entity::find()
+      .filter(license::Column::TgUserId.eq(tg_user_id))
+      .all(self.db)
+      .await?;
+
+    if rules.block_banned && licenses.iter().any(|l| l.is_blocked) {
+      return Err(Error::Ineligible(Ineligible::Banned));
+    }
+
+    if rules.review_risk_score > 0 {
+      let score = sv::Risk::new(self.db).score(tg_user_id).await?;
+      if score >= rules.review_risk_score {
+        return Err(Error::Ineligible(Ineligible::HighRisk(score)));
+      }
+    }
+
+    if !rules.blocked_countries.is_empty()
+      && let Some(country) =
+        sv::Pricing::new(self.db).country(tg_user_id).await?
+      && rules.blocked_countries.contains(&country)
+    {
+      return Err(Error::Ineligible(Ineligible::Country(country)));
+    }
+
+    if rules.max_unpaid_invoices > 0 {
+      let unpaid = pending_invoice::Entity::find()
+        .filter(pending_invoice::Column::UserId.eq(tg_user_id))
+        .filter(pending_invoice::Column::ExpiresAt.gt(now))
+        .count(self.db)
+        .await?;
+      if unpaid >= rules.max_unpaid_invoices {
+        return Err(Error::Ineligible(Ineligible::UnpaidInvoices(
+          rules.max_unpaid_invoices,
+        )));
+      }
+    }
+
+    let adds_license = match purchase {
+      Purchase::New(plan) => {
+        if rules.trial_first_only && plan.is_trial() && !licenses.is_empty() {
+          return Err(Error::Ineligible(Ineligible::TrialTaken));
+        }
+        true
+      }
+      // extending an expired key brings it back
+      Purchase::Extend(license) => license.expires_at <= now,
+    };
+
+    if adds_license && rules.max_licenses > 0 {
+      let exempt = sv::User::new(self.db)
+        .by_id(tg_user_id)
+        .await?
+        .is_some_and(|user| user.is_cap_exempt());
+      let license = sv::License::new(self.db);
+      if !exempt
+        && license.active_count(tg_user_id).await? >= rules.max_licenses
+      {
+        return Err(Error::Ineligible(Ineligible::LicenseLimit(
+          rules.max_licenses,
+        )));
+      }
+    }
+
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{entity::LicenseType, sv::test_utils::test_db};
+
+  #[tokio::test]
+  async fn test_purchase_rules() {
+    let db = test_db::setup().await;
+    let sv = Eligibility::new(&db);
+    let license = sv::License::new(&db);
+
+    let rules = Rules {
+      block_banned: true,
+      max_licenses: 2,
+      trial_first_only: true,
+      ..Default::default()
+    };
+
+    assert!(sv.check(1, Purchase::New(Plan::Trial), &rules).await.is_ok());
+
+    let first = license.create(1, LicenseType::Pro, 30).await.unwrap();
+    assert!(matches!(
+      sv.check(1, Purchase::New(Plan::Trial), &rules).await,
+      Err(Error::Ineligible(Ineligible::TrialTaken))
+    ));
+    assert!(sv.check(1, Purchase::New(Plan::Month), &rules).await.is_ok());
+
+    license.create(1, LicenseType::Pro, 30).await.unwrap();
+    assert!(matches!(
+      sv.check(1, Purchase::New(Plan::Month), &rules).await,
+      Err(Error::Ineligible(Ineligible::LicenseLimit(2)))
+    ));
+    // extending does not add a license
+    assert!(sv.check(1, Purchase::Extend(&first), &rules).await.is_ok());
+
+    license.set_blocked(&first.key, true).await.unwrap();
+    assert!(matches!(
+      sv.check(1, Purchase::Extend(&first), &rules).await,
+      Err(Error::Ineligible(Ineligible::Banned))
+    ));
+
+    // disabled rules let everything through
+    let open = Rules::default();
+    assert!(sv.check(1, Purchase::New(Plan::Trial), &open).await.is_ok());
+  }
+}
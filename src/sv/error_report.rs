@@ -0,0 +1,164 @@
+use chrono::NaiveDate;
+use sea_orm::sea_query::{Expr, OnConflict};
+
+use crate::{entity::error_report, prelude::*};
+
+/// Reported without a build version
+pub const UNKNOWN_VERSION: &str = "unknown";
+
+/// Longer messages, like whole stack traces, are cut to this
+const MESSAGE_MAX: usize = 512;
+
+/// One error of a day across builds, most frequent builds first
+#[derive(Debug, Clone, PartialEq)]
+pub struct ErrorSummary {
+  pub message: String,
+  pub total: i64,
+  pub versions: Vec<(String, i64)>,
+  pub last_key: String,
+}
+
+pub struct ErrorReports<'a> {
+  db: &'a DatabaseConnection,
+}
+
+impl<'a> ErrorReports<'a> {
+  pub fn new(db: &'a DatabaseConnection) -> Self {
+    Self { db }
+  }
+
+  /// Count `messages` reported by a client of `version` with `key`,
+  /// repeats of one message add up in a single row
+  pub async fn record(
+    &self,
+    key: &str,
+    version: Option<&str>,
+    messages: &[String],
+    now: DateTime,
+  ) -> Result<()> {
+    let mut counts: HashMap<String, i64> = HashMap::new();
+    for message in messages {
+      let message = message.trim();
+      if !message.is_empty() {
+        let message = message.chars().take(MESSAGE_MAX).collect();
+        *counts.entry(message).or_default() += 1;
+      }
+    }
+    let version = version
+      .map(str::trim)
+      .filter(|version| !version.is_empty())
+      .unwrap_or(UNKNOWN_VERSION);
+
+    for (message, count) in counts {
+      error_report::Entity::insert(error_report::ActiveModel {
+        day: Set(now.date()),
+        message: Set(message),
+        version: Set(version.to_string()),
+        count: Set(count),
+        last_key: Set(key.to_string()),
+        last_seen: Set(now),
+      })
+      .on_conflict(
+        OnConflict::columns([
+          error_report::Column::Day,
+          error_report::Column::Message,
+          error_report::Column::Version,
+        ])
+        .value(
+          error_report::Column::Count,
+          Expr::col(error_report::Column::Count).add(count),
+        )
+        .update_columns([
+          error_report::Column::LastKey,
+          error_report::Column::LastSeen,
+        ])
+        .to_owned(),
+      )
+      .exec_without_returning(self.db)
+      .await?;
+    }
+    Ok(())
+  }
+
+  /// Errors of `day` merged across builds, most reported first
+  pub async fn digest(&self, day: NaiveDate) -> Result<Vec<ErrorSummary>> {
+    let rows = error_report::Entity::find()
+      .filter(error_report::Column::Day.eq(day))
+      .order_by_desc(error_report::Column::Count)
+      .all(self.db)
+      .await?;
+
+    let mut summaries: Vec<ErrorSummary> = Vec::new();
+    let mut index: HashMap<String, usize> = HashMap::new();
+    for row in rows {
+      let i = *index.entry(row.message.clone()).or_insert_with(|| {
+        summaries.push(ErrorSummary {
+          message: row.message.clone(),
+          total: 0,
+          versions: Vec::new(),
+          last_key: row.last_key.clone(),
+        });
+        summaries.len() - 1
+      });
+      let summary = &mut summaries[i];
+      summary.total += row.count;
+      summary.versions.push((row.version, row.count));
+    }
+    summaries.sort_by_key(|summary| std::cmp::Reverse(summary.total));
+    Ok(summaries)
+  }
+
+  /// Drop the counts of days before `before`
+  pub async fn prune(&self, before: NaiveDate) -> Result<u64> {
+    Ok(
+      error_report::Entity::delete_many()
+        .filter(error_report::Column::Day.lt(before))
+        .exec(self.db)
+        .await?
+        .rows_affected,
+    )
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::sv::test_utils::test_db;
+
+  #[tokio::test]
+  async fn test_digest() {
+    let db = test_db::setup().await;
+    let sv = ErrorReports::new(&db);
+    let now = NaiveDate::from_ymd_opt(2026, 3, 10)
+      .unwrap()
+      .and_hms_opt(12, 0, 0)
+      .unwrap();
+    let errors =
+      |list: &[&str]| list.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+
+    sv.record("A", Some("1.2.0"), &errors(&["timeout", "timeout", " "]), now)
+      .await
+      .unwrap();
+    sv.record("B", Some("1.3.0"), &errors(&["timeout", "crash"]), now)
+      .await
+      .unwrap();
+    sv.record("C", None, &errors(&["crash"]), now).await.unwrap();
+    sv.record("D", Some("1.3.0"), &errors(&["old"]), now - TimeDelta::days(1))
+      .await
+      .unwrap();
+
+    let digest = sv.digest(now.date()).await.unwrap();
+    assert_eq!(digest.len(), 2);
+    assert_eq!(digest[0].message, "timeout");
+    assert_eq!(digest[0].total, 3);
+    assert_eq!(
+      digest[0].versions,
+      vec![("1.2.0".to_string(), 2), ("1.3.0".to_string(), 1)]
+    );
+    assert_eq!(digest[1].total, 2);
+    assert!(digest[1].versions.contains(&(UNKNOWN_VERSION.to_string(), 1)));
+
+    assert_eq!(sv.prune(now.date()).await.unwrap(), 1);
+    assert_eq!(sv.digest(now.date()).await.unwrap().len(), 2);
+  }
+}
@@ -0,0 +1,188 @@
+use crate::{entity::faq, prelude::*};
+
+pub struct Faq<'a> {
+  db: &'a DatabaseConnection,
+}
+
+impl<'a> Faq<'a> {
+  pub fn new(db: &'a DatabaseConnection) -> Self {
+    Self { db }
+  }
+
+  pub async fn all(&self) -> Result<Vec<faq::Model>> {
+    Ok(faq::Entity::find().order_by_asc(faq::Column::Id).all(self.db).await?)
+  }
+
+  pub async fn by_id(&self, id: i32) -> Result<Option<faq::Model>> {
+    Ok(faq::Entity::find_by_id(id).one(self.db).await?)
+  }
+
+  pub async fn add(
+    &self,
+    question: &str,
+    answer: &str,
+    keywords: &str,
+  ) -> Result<faq::Model> {
+    let entry = faq::ActiveModel {
+      id: NotSet,
+      question: Set(question.to_string()),
+      answer: Set(answer.to_string()),
+      keywords: Set(keywords.to_string()),
+      created_at: Set(Utc::now().naive_utc()),
+    };
+    Ok(entry.insert(self.db).await?)
+  }
+
+  pub async fn edit(
+    &self,
+    id: i32,
+    question: &str,
+    answer: &str,
+    keywords: &str,
+  ) -> Result<faq::Model> {
+    let entry = self
+      .by_id(id)
+      .await?
+      .ok_or_else(|| Error::InvalidArgs(format!("FAQ #{} not found", id)))?;
+
+    Ok(
+      faq::ActiveModel {
+        question: Set(question.to_string()),
+        answer: Set(answer.to_string()),
+        keywords: Set(keywords.to_string()),
+        ..entry.into()
+      }
+      .update(self.db)
+      .await?,
+    )
+  }
+
+  pub async fn remove(&self, id: i32) -> Result<()> {
+    let res = faq::Entity::delete_by_id(id).exec(self.db).await?;
+    if res.rows_affected == 0 {
+      return Err(Error::InvalidArgs(format!("FAQ #{} not found", id)));
+    }
+    Ok(())
+  }
+
+  /// Entries matching the query, best first.
+  /// Matching is typo-tolerant so "instal" still finds "install".
+  pub async fn search(
+    &self,
+    query: &str,
+    limit: usize,
+  ) -> Result<Vec<faq::Model>> {
+    let terms = tokenize(query);
+    if terms.is_empty() {
+      return Ok(Vec::new());
+    }
+
+    let mut scored: Vec<_> = self
+      .all()
+      .await?
+      .into_iter()
+      .map(|entry| (score(&terms, &entry), entry))
+      .filter(|(score, _)| *score > 0)
+      .collect();
+
+    scored.sort_by(|(a, x), (b, y)| b.cmp(a).then(x.id.cmp(&y.id)));
+    Ok(scored.into_iter().take(limit).map(|(_, entry)| entry).collect())
+  }
+}
+
+fn tokenize(text: &str) -> Vec<String> {
+  text
+    .split(|c: char| !c.is_alphanumeric())
+    .filter(|word| word.chars().count() >= 2)
+    .map(str::to_lowercase)
+    .collect()
+}
+
+fn score(terms: &[String], entry: &faq::Model) -> u32 {
+  let question = tokenize(&entry.question);
+  let keywords = tokenize(&entry.keywords);
+
+  terms
+    .iter()
+    .map(|term| {
+      let best = |words: &[String]| {
+        words.iter().map(|word| similarity(term, word)).max().unwrap_or(0)
+      };
+      let (in_question, in_keywords) = (best(&question), best(&keywords));
+      // keywords are curated by admins, so they weigh a bit more
+      if in_keywords > 0 {
+        in_question.max(in_keywords + 1)
+      } else {
+        in_question
+      }
+    })
+    .sum()
+}
+
+/// 3 for an exact match, 2 for a prefix, 1 for a small typo
+fn similarity(a: &str, b: &str) -> u32 {
+  if a == b {
+    return 3;
+  }
+
+  let shorter = a.chars().count().min(b.chars().count());
+  if shorter >= 3 && (a.starts_with(b) || b.starts_with(a)) {
+    return 2;
+  }
+
+  let max_typos = match shorter {
+    0..4 => 0,
+    4..7 => 1,
+    _ => 2,
+  };
+  if max_typos > 0 && levenshtein(a, b) <= max_typos {
+    return 1;
+  }
+
+  0
+}
+
+fn levenshtein(a: &str, b: &str) -> usize {
+  let b: Vec<char> = b.chars().collect();
+  let mut prev: Vec<usize> = (0..=b.len()).collect();
+
+  for (i, ca) in a.chars().enumerate() {
+    let mut cur = vec![i + 1; b.len() + 1];
+    for (j, cb) in b.iter().enumerate() {
+      let cost = (ca != *cb) as usize;
+      cur[j + 1] = (prev[j] + cost).min(prev[j + 1] + 1).min(cur[j] + 1);
+    }
+    prev = cur;
+  }
+
+  prev[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::sv::test_utils::test_db;
+
+  #[tokio::test]
+  async fn test_search_is_fuzzy() {
+    let db = test_db::setup().await;
+    let sv = Faq::new(&db);
+
+    let install = sv
+      .add("How to install the panel?", "Download it...", "setup download")
+      .await
+      .unwrap();
+    let invalid = sv
+      .add("Why is my key invalid?", "Check that...", "license error expired")
+      .await
+      .unwrap();
+
+    let found = sv.search("instal", 5).await.unwrap();
+    assert_eq!(found.first().map(|f| f.id), Some(install.id));
+
+    let found = sv.search("licence expierd", 5).await.unwrap();
+    assert_eq!(found.first().map(|f| f.id), Some(invalid.id));
+
+    assert!(sv.search("refund", 5).await.unwrap().is_empty());
+  }
+}
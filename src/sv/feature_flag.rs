@@ -0,0 +1,170 @@
+use sea_orm::sea_query::OnConflict;
+
+use crate::{
+  entity::{
+    feature_flag::{self, FieldScope},
+    license, user,
+  },
+  prelude::*,
+};
+
+const MAX_FEATURE_LEN: usize = 32;
+
+pub struct FeatureFlag<'a> {
+  db: &'a DatabaseConnection,
+}
+
+impl<'a> FeatureFlag<'a> {
+  pub fn new(db: &'a DatabaseConnection) -> Self {
+    Self { db }
+  }
+
+  /// Features are lowercase identifiers the client knows, e.g. `auto_trade`
+  pub fn normalize_feature(feature: &str) -> Result<String> {
+    let feature = feature.trim().to_lowercase();
+    let valid = !feature.is_empty()
+      && feature.len() <= MAX_FEATURE_LEN
+      && feature.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if !valid {
+      return Err(Error::InvalidArgs(format!(
+        "Feature must be 1-{} letters, digits or '_'",
+        MAX_FEATURE_LEN
+      )));
+    }
+    Ok(feature)
+  }
+
+  pub async fn set(
+    &self,
+    scope: FieldScope,
+    target: &str,
+    feature: &str,
+    enabled: bool,
+  ) -> Result<feature_flag::Model> {
+    let feature = Self::normalize_feature(feature)?;
+    match scope {
+      FieldScope::User => {
+        let id = target.parse::<i64>().map_err(|_| Error::UserNotFound)?;
+        user::Entity::find_by_id(id)
+          .one(self.db)
+          .await?
+          .ok_or(Error::UserNotFound)?;
+      }
+      FieldScope::License => {
+        license::Entity::find_by_id(target)
+          .one(self.db)
+          .await?
+          .ok_or(Error::LicenseNotFound)?;
+      }
+    }
+
+    let flag = feature_flag::ActiveModel {
+      scope: Set(scope),
+      target: Set(target.to_string()),
+      feature: Set(feature.clone()),
+      enabled: Set(enabled),
+      updated_at: Set(Utc::now().naive_utc()),
+    };
+    feature_flag::Entity::insert(flag)
+      .on_conflict(
+        OnConflict::columns([
+          feature_flag::Column::Scope,
+          feature_flag::Column::Target,
+          feature_flag::Column::Feature,
+        ])
+        .update_columns([
+          feature_flag::Column::Enabled,
+          feature_flag::Column::UpdatedAt,
+        ])
+        .to_owned(),
+      )
+      .exec_without_returning(self.db)
+      .await?;
+
+    feature_flag::Entity::find_by_id((scope, target.to_string(), feature))
+      .one(self.db)
+      .await?
+      .ok_or_else(|| Error::Internal("Feature flag vanished".into()))
+  }
+
+  /// Drop the override, the client falls back to its own default
+  pub async fn clear(
+    &self,
+    scope: FieldScope,
+    target: &str,
+    feature: &str,
+  ) -> Result<()> {
+    let feature = Self::normalize_feature(feature)?;
+    let res =
+      feature_flag::Entity::delete_by_id((scope, target.to_string(), feature))
+        .exec(self.db)
+        .await?;
+    if res.rows_affected == 0 {
+      return Err(Error::InvalidArgs("No such flag".into()));
+    }
+    Ok(())
+  }
+
+  pub async fn of(
+    &self,
+    scope: FieldScope,
+    target: &str,
+  ) -> Result<Vec<feature_flag::Model>> {
+    Ok(
+      feature_flag::Entity::find()
+        .filter(feature_flag::Column::Scope.eq(scope))
+        .filter(feature_flag::Column::Target.eq(target))
+        .order_by_asc(feature_flag::Column::Feature)
+        .all(self.db)
+        .await?,
+    )
+  }
+
+  /// Flags sent to a client, license flags win over the owner's
+  pub async fn resolve(
+    &self,
+    license: &license::Model,
+  ) -> Result<HashMap<String, bool>> {
+    let mut features = HashMap::new();
+    let user_flags =
+      self.of(FieldScope::User, &license.tg_user_id.to_string()).await?;
+    let license_flags = self.of(FieldScope::License, &license.key).await?;
+    for flag in user_flags.into_iter().chain(license_flags) {
+      features.insert(flag.feature, flag.enabled);
+    }
+    Ok(features)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{entity::LicenseType, sv, sv::test_utils::test_db};
+
+  #[tokio::test]
+  async fn test_license_flags_override_user_flags() {
+    let db = test_db::setup().await;
+    let sv = FeatureFlag::new(&db);
+    let license =
+      sv::License::new(&db).create(1, LicenseType::Pro, 30).await.unwrap();
+
+    sv.set(FieldScope::User, "1", "Overlay", false).await.unwrap();
+    sv.set(FieldScope::User, "1", "auto_trade", false).await.unwrap();
+    sv.set(FieldScope::License, &license.key, "auto_trade", true)
+      .await
+      .unwrap();
+
+    let features = sv.resolve(&license).await.unwrap();
+    assert_eq!(features.get("overlay"), Some(&false));
+    assert_eq!(features.get("auto_trade"), Some(&true));
+
+    sv.clear(FieldScope::License, &license.key, "auto_trade").await.unwrap();
+    let features = sv.resolve(&license).await.unwrap();
+    assert_eq!(features.get("auto_trade"), Some(&false));
+
+    assert!(matches!(
+      sv.set(FieldScope::User, "404", "overlay", true).await,
+      Err(Error::UserNotFound)
+    ));
+  }
+}
@@ -0,0 +1,350 @@
+use std::collections::HashSet;
+
+use sea_orm::sea_query::OnConflict;
+
+use crate::{
+  entity::{
+    free_game, free_item,
+    freebie_claim::{self, FreebieKind},
+  },
+  prelude::*,
+};
+
+/// Claims a user may make per rolling day by default
+pub const DEFAULT_DAILY_LIMIT: u64 = 3;
+
+/// Free game or item, whether scraped from Steam or added by an admin
+#[derive(Debug, Clone, PartialEq)]
+pub struct Freebie {
+  pub kind: FreebieKind,
+  /// Package id of a game, def id of an item
+  pub id: i32,
+  pub app_id: i32,
+  pub name: String,
+  pub manual: bool,
+}
+
+impl Freebie {
+  /// Store page where the user picks it up
+  pub fn url(&self) -> String {
+    match self.kind {
+      FreebieKind::Game => {
+        format!("https://store.steampowered.com/app/{}", self.app_id)
+      }
+      FreebieKind::Item => {
+        format!(
+          "https://store.steampowered.com/points/shop/app/{}",
+          self.app_id
+        )
+      }
+    }
+  }
+}
+
+impl From<free_game::Model> for Freebie {
+  fn from(game: free_game::Model) -> Self {
+    Self {
+      kind: FreebieKind::Game,
+      id: game.pkg_id,
+      app_id: game.app_id,
+      name: game.name,
+      manual: game.manual,
+    }
+  }
+}
+
+impl From<free_item::Model> for Freebie {
+  fn from(item: free_item::Model) -> Self {
+    Self {
+      kind: FreebieKind::Item,
+      id: item.def_id,
+      app_id: item.app_id,
+      name: item.name,
+      manual: item.manual,
+    }
+  }
+}
+
+pub struct Freebies<'a> {
+  db: &'a DatabaseConnection,
+}
+
+impl<'a> Freebies<'a> {
+  pub fn new(db: &'a DatabaseConnection) -> Self {
+    Self { db }
+  }
+
+  /// Games first, then items, each by name
+  pub async fn all(&self) -> Result<Vec<Freebie>> {
+    let games = free_game::Entity::find()
+      .order_by_asc(free_game::Column::Name)
+      .all(self.db)
+      .await?;
+    let items = free_item::Entity::find()
+      .order_by_asc(free_item::Column::Name)
+      .all(self.db)
+      .await?;
+
+    Ok(
+      games
+        .into_iter()
+        .map(Freebie::from)
+        .chain(items.into_iter().map(Freebie::from))
+        .collect(),
+    )
+  }
+
+  pub async fn get(
+    &self,
+    kind: FreebieKind,
+    id: i32,
+  ) -> Result<Option<Freebie>> {
+    Ok(match kind {
+      FreebieKind::Game => {
+        free_game::Entity::find_by_id(id).one(self.db).await?.map(Freebie::from)
+      }
+      FreebieKind::Item => {
+        free_item::Entity::find_by_id(id).one(self.db).await?.map(Freebie::from)
+      }
+    })
+  }
+
+  /// Add or rename a freebie, it is kept across Steam scans
+  pub async fn add(
+    &self,
+    kind: FreebieKind,
+    id: i32,
+    app_id: i32,
+    name: &str,
+  ) -> Result<Freebie> {
+    let name = name.trim();
+    if name.is_empty() {
+      return Err(Error::InvalidArgs("Freebie name is empty".into()));
+    }
+
+    let now = Utc::now().naive_utc();
+    match kind {
+      FreebieKind::Game => {
+        free_game::Entity::insert(free_game::ActiveModel {
+          pkg_id: Set(id),
+          app_id: Set(app_id),
+          name: Set(name.to_string()),
+          updated_at: Set(now),
+          manual: Set(true),
+        })
+        .on_conflict(
+          OnConflict::column(free_game::Column::PkgId)
+            .update_columns([
+              free_game::Column::AppId,
+              free_game::Column::Name,
+              free_game::Column::UpdatedAt,
+              free_game::Column::Manual,
+            ])
+            .to_owned(),
+        )
+        .exec(self.db)
+        .await?;
+      }
+      FreebieKind::Item => {
+        free_item::Entity::insert(free_item::ActiveModel {
+          def_id: Set(id),
+          app_id: Set(app_id),
+          name: Set(name.to_string()),
+          updated_at: Set(now),
+          manual: Set(true),
+        })
+        .on_conflict(
+          OnConflict::column(free_item::Column::DefId)
+            .update_columns([
+              free_item::Column::AppId,
+              free_item::Column::Name,
+              free_item::Column::UpdatedAt,
+              free_item::Column::Manual,
+            ])
+            .to_owned(),
+        )
+        .exec(self.db)
+        .await?;
+      }
+    }
+
+    self.get(kind, id).await?.ok_or(Error::FreebieNotFound)
+  }
+
+  /// Scraped freebies come back with the next Steam scan if still free
+  pub async fn remove(&self, kind: FreebieKind, id: i32) -> Result<()> {
+    let res = match kind {
+      FreebieKind::Game => {
+        free_game::Entity::delete_by_id(id).exec(self.db).await?
+      }
+      FreebieKind::Item => {
+        free_item::Entity::delete_by_id(id).exec(self.db).await?
+      }
+    };
+    if res.rows_affected == 0 {
+      return Err(Error::FreebieNotFound);
+    }
+    Ok(())
+  }
+
+  /// Freebies the user hasn't claimed yet
+  pub async fn available(&self, tg_user_id: i64) -> Result<Vec<Freebie>> {
+    let claimed: HashSet<_> = freebie_claim::Entity::find()
+      .filter(freebie_claim::Column::TgUserId.eq(tg_user_id))
+      .all(self.db)
+      .await?
+      .into_iter()
+      .map(|claim| (claim.kind, claim.ref_id))
+      .collect();
+
+    Ok(
+      self
+        .all()
+        .await?
+        .into_iter()
+        .filter(|freebie| !claimed.contains(&(freebie.kind, freebie.id)))
+        .collect(),
+    )
+  }
+
+  /// Claims of the user within the last day
+  pub async fn claimed_today(&self, tg_user_id: i64) -> Result<u64> {
+    let since = Utc::now().naive_utc() - TimeDelta::days(1);
+    Ok(
+      freebie_claim::Entity::find()
+        .filter(freebie_claim::Column::TgUserId.eq(tg_user_id))
+        .filter(freebie_claim::Column::ClaimedAt.gte(since))
+        .count(self.db)
+        .await?,
+    )
+  }
+
+  /// Grant a freebie once per user, at most `daily_limit` a day
+  pub async fn claim(
+    &self,
+    tg_user_id: i64,
+    kind: FreebieKind,
+    id: i32,
+    daily_limit: u64,
+  ) -> Result<Freebie> {
+    let freebie = self.get(kind, id).await?.ok_or(Error::FreebieNotFound)?;
+
+    let already = freebie_claim::Entity::find()
+      .filter(freebie_claim::Column::TgUserId.eq(tg_user_id))
+      .filter(freebie_claim::Column::Kind.eq(kind))
+      .filter(freebie_claim::Column::RefId.eq(id))
+      .one(self.db)
+      .await?;
+    if already.is_some() {
+      return Err(Error::FreebieClaimed);
+    }
+    if self.claimed_today(tg_user_id).await? >= daily_limit {
+      return Err(Error::FreebieLimit(daily_limit));
+    }
+
+    freebie_claim::ActiveModel {
+      tg_user_id: Set(tg_user_id),
+      kind: Set(kind),
+      ref_id: Set(id),
+      name: Set(freebie.name.clone()),
+      claimed_at: Set(Utc::now().naive_utc()),
+      ..Default::default()
+    }
+    .insert(self.db)
+    .await?;
+
+    Ok(freebie)
+  }
+
+  /// Latest claims, of one user or everyone
+  pub async fn history(
+    &self,
+    tg_user_id: Option<i64>,
+    limit: u64,
+  ) -> Result<Vec<freebie_claim::Model>> {
+    let mut query = freebie_claim::Entity::find();
+    if let Some(tg_user_id) = tg_user_id {
+      query = query.filter(freebie_claim::Column::TgUserId.eq(tg_user_id));
+    }
+    Ok(
+      query
+        .order_by_desc(freebie_claim::Column::ClaimedAt)
+        .order_by_desc(freebie_claim::Column::Id)
+        .limit(limit)
+        .all(self.db)
+        .await?,
+    )
+  }
+
+  /// Claims per freebie since `since`
+  pub async fn claim_counts(
+    &self,
+    since: DateTime,
+  ) -> Result<HashMap<(FreebieKind, i32), u64>> {
+    let claims = freebie_claim::Entity::find()
+      .filter(freebie_claim::Column::ClaimedAt.gte(since))
+      .all(self.db)
+      .await?;
+
+    let mut counts = HashMap::new();
+    for claim in claims {
+      *counts.entry((claim.kind, claim.ref_id)).or_default() += 1;
+    }
+    Ok(counts)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::sv::{Steam, User, test_utils::test_db};
+
+  #[tokio::test]
+  async fn test_claim_limits_and_cache_refresh() {
+    let db = test_db::setup().await;
+    User::new(&db).get_or_create(1).await.unwrap();
+    let sv = Freebies::new(&db);
+
+    sv.add(FreebieKind::Game, 10, 100, "Admin Game").await.unwrap();
+    sv.add(FreebieKind::Item, 20, 200, "Sticker").await.unwrap();
+    sv.add(FreebieKind::Item, 21, 200, "Badge").await.unwrap();
+
+    // a scan replaces scraped games only
+    Steam::new(&db)
+      .replace_free_games_cache(vec![
+        (10, 999, "Scraped duplicate".into()),
+        (11, 101, "Scraped Game".into()),
+      ])
+      .await
+      .unwrap();
+    let game = sv.get(FreebieKind::Game, 10).await.unwrap().unwrap();
+    assert_eq!((game.name.as_str(), game.manual), ("Admin Game", true));
+    assert_eq!(sv.all().await.unwrap().len(), 4);
+
+    sv.claim(1, FreebieKind::Game, 10, 2).await.unwrap();
+    assert!(matches!(
+      sv.claim(1, FreebieKind::Game, 10, 2).await,
+      Err(Error::FreebieClaimed)
+    ));
+    sv.claim(1, FreebieKind::Item, 20, 2).await.unwrap();
+    assert!(matches!(
+      sv.claim(1, FreebieKind::Item, 21, 2).await,
+      Err(Error::FreebieLimit(2))
+    ));
+    assert!(matches!(
+      sv.claim(1, FreebieKind::Game, 404, 2).await,
+      Err(Error::FreebieNotFound)
+    ));
+
+    let available = sv.available(1).await.unwrap();
+    let names: Vec<_> = available.iter().map(|f| f.name.as_str()).collect();
+    assert_eq!(names, ["Scraped Game", "Badge"]);
+
+    let history = sv.history(Some(1), 10).await.unwrap();
+    assert_eq!(history.len(), 2);
+    assert_eq!(history[0].name, "Sticker");
+
+    sv.remove(FreebieKind::Item, 21).await.unwrap();
+    assert!(sv.remove(FreebieKind::Item, 21).await.is_err());
+  }
+}
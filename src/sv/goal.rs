@@ -0,0 +1,145 @@
+use chrono::NaiveDate;
+
+use crate::{
+  entity::goal::{self, GoalKind},
+  prelude::*,
+  sv,
+};
+
+pub struct Goal<'a> {
+  db: &'a DatabaseConnection,
+}
+
+impl<'a> Goal<'a> {
+  pub fn new(db: &'a DatabaseConnection) -> Self {
+    Self { db }
+  }
+
+  pub async fn get(&self, tg_user_id: i64) -> Result<Option<goal::Model>> {
+    Ok(goal::Entity::find_by_id(tg_user_id).one(self.db).await?)
+  }
+
+  /// Set the daily goal. Changing it restarts today's progress,
+  /// the streak survives.
+  pub async fn set(
+    &self,
+    tg_user_id: i64,
+    kind: GoalKind,
+    target: f64,
+  ) -> Result<goal::Model> {
+    if !target.is_finite() || target <= 0.0 {
+      return Err(Error::InvalidArgs("Goal must be positive".into()));
+    }
+    sv::User::new(self.db).get_or_create(tg_user_id).await?;
+
+    let today = Utc::now().date_naive();
+    let model = match self.get(tg_user_id).await? {
+      Some(existing) => {
+        goal::ActiveModel {
+          kind: Set(kind),
+          target: Set(target),
+          day: Set(today),
+          progress: Set(0.0),
+          ..existing.into()
+        }
+        .update(self.db)
+        .await?
+      }
+      None => {
+        goal::ActiveModel {
+          tg_user_id: Set(tg_user_id),
+          kind: Set(kind),
+          target: Set(target),
+          day: Set(today),
+          progress: Set(0.0),
+          streak: Set(0),
+          best_streak: Set(0),
+          last_achieved: Set(None),
+        }
+        .insert(self.db)
+        .await?
+      }
+    };
+    Ok(model)
+  }
+
+  pub async fn remove(&self, tg_user_id: i64) -> Result<bool> {
+    let res = goal::Entity::delete_by_id(tg_user_id).exec(self.db).await?;
+    Ok(res.rows_affected > 0)
+  }
+
+  /// Add telemetry towards today's goal.
+  /// Returns the goal when this update completed it.
+  pub async fn track(
+    &self,
+    tg_user_id: i64,
+    kind: GoalKind,
+    amount: f64,
+  ) -> Result<Option<goal::Model>> {
+    let Some(goal) = self.get(tg_user_id).await? else {
+      return Ok(None);
+    };
+    if goal.kind != kind || amount <= 0.0 {
+      return Ok(None);
+    }
+
+    let today = Utc::now().date_naive();
+    let before = if goal.day == today { goal.progress } else { 0.0 };
+    let progress = before + amount;
+    let reached = before < goal.target && progress >= goal.target;
+
+    let mut model: goal::ActiveModel = goal.clone().into();
+    model.day = Set(today);
+    model.progress = Set(progress);
+    if reached {
+      let streak = current_streak(&goal, today - chrono::Days::new(1)) + 1;
+      model.streak = Set(streak);
+      model.best_streak = Set(goal.best_streak.max(streak));
+      model.last_achieved = Set(Some(today));
+    }
+    let goal = model.update(self.db).await?;
+
+    Ok(reached.then_some(goal))
+  }
+}
+
+/// Streak as of `today`, zero once a whole day was missed
+pub fn current_streak(goal: &goal::Model, today: NaiveDate) -> i32 {
+  match goal.last_achieved {
+    Some(day) if today - day <= TimeDelta::days(1) => goal.streak,
+    _ => 0,
+  }
+}
+
+/// Today's progress, zero if nothing arrived yet today
+pub fn today_progress(goal: &goal::Model, today: NaiveDate) -> f64 {
+  if goal.day == today { goal.progress } else { 0.0 }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::sv::test_utils::test_db;
+
+  #[tokio::test]
+  async fn test_goal_reached_once_per_day() {
+    let db = test_db::setup().await;
+    let sv = Goal::new(&db);
+
+    sv.set(12345, GoalKind::Runtime, 4.0).await.unwrap();
+    assert!(sv.track(12345, GoalKind::Xp, 100.0).await.unwrap().is_none());
+    assert!(sv.track(12345, GoalKind::Runtime, 3.0).await.unwrap().is_none());
+
+    let reached = sv.track(12345, GoalKind::Runtime, 1.5).await.unwrap();
+    let reached = reached.expect("goal should be reached");
+    assert_eq!(reached.streak, 1);
+    assert_eq!(reached.best_streak, 1);
+
+    // already done today, no second congratulation
+    assert!(sv.track(12345, GoalKind::Runtime, 5.0).await.unwrap().is_none());
+
+    let today = Utc::now().date_naive();
+    assert_eq!(current_streak(&reached, today + chrono::Days::new(1)), 1);
+    assert_eq!(current_streak(&reached, today + chrono::Days::new(2)), 0);
+  }
+}
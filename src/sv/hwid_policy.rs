@@ -0,0 +1,175 @@
+use std::collections::HashSet;
+
+use sea_orm::Condition;
+
+use crate::{
+  entity::{LicenseType, hwid_exemption, license, license_device},
+  prelude::*,
+};
+
+/// How many distinct licenses one machine may use within a window.
+/// A limit of 0 disables that check.
+#[derive(Debug, Clone, Copy)]
+pub struct Policy {
+  pub max_licenses: u64,
+  pub max_trials: u64,
+  pub window: TimeDelta,
+}
+
+/// What an exemption applies to
+#[derive(Debug, Clone)]
+pub enum Exempt {
+  Hwid(String),
+  User(i64),
+}
+
+pub struct HwidPolicy<'a> {
+  db: &'a DatabaseConnection,
+}
+
+impl<'a> HwidPolicy<'a> {
+  pub fn new(db: &'a DatabaseConnection) -> Self {
+    Self { db }
+  }
+
+  pub async fn exempt(
+    &self,
+    target: Exempt,
+    note: &str,
+  ) -> Result<hwid_exemption::Model> {
+    let (hwid, tg_user_id) = match target {
+      Exempt::Hwid(hwid) => (Some(hwid), None),
+      Exempt::User(id) => (None, Some(id)),
+    };
+
+    let exemption = hwid_exemption::ActiveModel {
+      id: NotSet,
+      hwid: Set(hwid),
+      tg_user_id: Set(tg_user_id),
+      note: Set(note.to_string()),
+      created_at: Set(Utc::now().naive_utc()),
+    };
+    Ok(exemption.insert(self.db).await?)
+  }
+
+  pub async fn unexempt(&self, id: i32) -> Result<()> {
+    let res = hwid_exemption::Entity::delete_by_id(id).exec(self.db).await?;
+    if res.rows_affected == 0 {
+      return Err(Error::InvalidArgs(format!("Exemption #{} not found", id)));
+    }
+    Ok(())
+  }
+
+  pub async fn exemptions(&self) -> Result<Vec<hwid_exemption::Model>> {
+    Ok(
+      hwid_exemption::Entity::find()
+        .order_by_asc(hwid_exemption::Column::Id)
+        .all(self.db)
+        .await?,
+    )
+  }
+
+  async fn is_exempt(&self, hwid: &str, tg_user_id: i64) -> Result<bool> {
+    let found = hwid_exemption::Entity::find()
+      .filter(
+        Condition::any()
+          .add(hwid_exemption::Column::Hwid.eq(hwid))
+          .add(hwid_exemption::Column::TgUserId.eq(tg_user_id)),
+      )
+      .one(self.db)
+      .await?;
+    Ok(found.is_some())
+  }
+
+  /// Reject a new session if the machine already ran too many other
+  /// licenses recently
+  pub async fn check(
+    &self,
+    license: &license::Model,
+    hwid: &str,
+    policy: &Policy,
+  ) -> Result<()> {
+    // support keys run on the customer's machine by design
+    if license.loaned_by.is_some() {
+      return Ok(());
+    }
+
+    let is_trial = license.license_type == LicenseType::Trial;
+    if policy.max_licenses == 0 && (policy.max_trials == 0 || !is_trial) {
+      return Ok(());
+    }
+
+    let since = Utc::now().naive_utc() - policy.window;
+    let keys: HashSet<String> = license_device::Entity::find()
+      .filter(license_device::Column::Hwid.eq(hwid))
+      .filter(license_device::Column::LastSeen.gt(since))
+      .filter(license_device::Column::LicenseKey.ne(license.key.as_str()))
+      .all(self.db)
+      .await?
+      .into_iter()
+      .map(|device| device.license_key)
+      .collect();
+    if keys.is_empty() {
+      return Ok(());
+    }
+
+    let others = license::Entity::find()
+      .filter(license::Column::Key.is_in(keys))
+      .filter(license::Column::LoanedBy.is_null())
+      .all(self.db)
+      .await?;
+    let trials = others
+      .iter()
+      .filter(|other| other.license_type == LicenseType::Trial)
+      .count() as u64;
+
+    let over_total =
+      policy.max_licenses > 0 && others.len() as u64 >= policy.max_licenses;
+    let over_trials =
+      is_trial && policy.max_trials > 0 && trials >= policy.max_trials;
+    if !over_total && !over_trials {
+      return Ok(());
+    }
+    if self.is_exempt(hwid, license.tg_user_id).await? {
+      return Ok(());
+    }
+
+    Err(Error::HwidLimit { trial: over_trials })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{sv, sv::test_utils::test_db};
+
+  #[tokio::test]
+  async fn test_trial_farming_is_blocked() {
+    let db = test_db::setup().await;
+    let license = sv::License::new(&db);
+    let device = sv::Device::new(&db);
+    let sv = HwidPolicy::new(&db);
+    let policy =
+      Policy { max_licenses: 3, max_trials: 1, window: TimeDelta::days(7) };
+
+    let first = license.create(1, LicenseType::Trial, 7).await.unwrap();
+    sv.check(&first, "pc", &policy).await.unwrap();
+    device.touch(&first.key, "pc", None, None).await.unwrap();
+
+    // a second trial from another account on the same machine
+    let second = license.create(2, LicenseType::Trial, 7).await.unwrap();
+    assert!(matches!(
+      sv.check(&second, "pc", &policy).await,
+      Err(Error::HwidLimit { trial: true })
+    ));
+    // the license that was already there keeps working
+    sv.check(&first, "pc", &policy).await.unwrap();
+
+    // Pro keys only count against the overall limit
+    let pro = license.create(2, LicenseType::Pro, 30).await.unwrap();
+    sv.check(&pro, "pc", &policy).await.unwrap();
+
+    sv.exempt(Exempt::User(2), "family PC").await.unwrap();
+    sv.check(&second, "pc", &policy).await.unwrap();
+  }
+}
@@ -0,0 +1,99 @@
+use crate::{
+  entity::incident::{self, IncidentKind},
+  prelude::*,
+};
+
+/// Repeats of the same incident within this window are not re-reported
+const DEDUP_MINUTES: i64 = 60;
+
+pub struct Incident<'a> {
+  db: &'a DatabaseConnection,
+}
+
+impl<'a> Incident<'a> {
+  pub fn new(db: &'a DatabaseConnection) -> Self {
+    Self { db }
+  }
+
+  /// Record an incident. Returns `None` if the same key and HWID were
+  /// already reported recently, so callers only alert once.
+  pub async fn record(
+    &self,
+    kind: IncidentKind,
+    license_key: Option<&str>,
+    ip: Option<&str>,
+    hwid: Option<&str>,
+    details: &str,
+  ) -> Result<Option<incident::Model>> {
+    let now = Utc::now().naive_utc();
+    let since = now - TimeDelta::minutes(DEDUP_MINUTES);
+
+    let mut recent = incident::Entity::find()
+      .filter(incident::Column::Kind.eq(kind))
+      .filter(incident::Column::CreatedAt.gt(since));
+    recent = match license_key {
+      Some(key) => recent.filter(incident::Column::LicenseKey.eq(key)),
+      None => recent.filter(incident::Column::LicenseKey.is_null()),
+    };
+    recent = match hwid {
+      Some(hwid) => recent.filter(incident::Column::Hwid.eq(hwid)),
+      None => recent.filter(incident::Column::Hwid.is_null()),
+    };
+    if recent.one(self.db).await?.is_some() {
+      return Ok(None);
+    }
+
+    let incident = incident::ActiveModel {
+      id: NotSet,
+      kind: Set(kind),
+      license_key: Set(license_key.map(str::to_string)),
+      ip: Set(ip.map(str::to_string)),
+      hwid: Set(hwid.map(str::to_string)),
+      details: Set(details.to_string()),
+      created_at: Set(now),
+    };
+    Ok(Some(incident.insert(self.db).await?))
+  }
+
+  pub async fn recent(&self, limit: u64) -> Result<Vec<incident::Model>> {
+    Ok(
+      incident::Entity::find()
+        .order_by_desc(incident::Column::CreatedAt)
+        .limit(limit)
+        .all(self.db)
+        .await?,
+    )
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{entity::LicenseType, sv, sv::test_utils::test_db};
+
+  #[tokio::test]
+  async fn test_honeypot_is_reported_once() {
+    let db = test_db::setup().await;
+    let license = sv::License::new(&db);
+    let sv = Incident::new(&db);
+
+    let honeypot = license.create_honeypot(1).await.unwrap();
+    assert!(matches!(
+      license.validate(&honeypot.key).await,
+      Err(Error::Honeypot)
+    ));
+    let real = license.create(1, LicenseType::Pro, 30).await.unwrap();
+    assert!(license.validate(&real.key).await.is_ok());
+
+    let key = Some(honeypot.key.as_str());
+    let kind = IncidentKind::Honeypot;
+    let first = sv.record(kind, key, Some("1.2.3.4"), Some("hw1"), "");
+    assert!(first.await.unwrap().is_some());
+    let repeat = sv.record(kind, key, Some("1.2.3.4"), Some("hw1"), "");
+    assert!(repeat.await.unwrap().is_none());
+    let other = sv.record(kind, key, Some("5.6.7.8"), Some("hw2"), "");
+    assert!(other.await.unwrap().is_some());
+
+    assert_eq!(sv.recent(10).await.unwrap().len(), 2);
+  }
+}
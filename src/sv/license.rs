@@ -1,18 +1,149 @@
+use std::fmt;
+
+use chrono::{Months, Timelike};
 use uuid::Uuid;
 
 pub use crate::prelude::*;
 use crate::{
-  entity::{LicenseType, license, promo},
-  sv,
+  entity::{
+    LicenseType,
+    custom_field::{self, FieldScope},
+    feature_flag, license, license_device, license_stats, promo, user,
+    user_settings,
+  },
+  sv::{self, assertion::Assertion, signing_key::Keyring},
 };
 
+/// Upper bound admins may raise a license's session limit to
+pub const MAX_SESSIONS: i32 = 50;
+/// Days before expiry the owner is reminded, nearest first
+pub const EXPIRY_REMINDERS: [i64; 3] = [1, 3, 7];
+/// Most keys /genbulk creates at once
+pub const GIFT_BATCH_MAX: usize = 1000;
+
+/// How long a license runs for, calendar months land on the same day of a
+/// later month instead of counting 30 days each
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Term {
+  Exact(TimeDelta),
+  Months(u32),
+}
+
+impl Term {
+  pub fn days(days: u64) -> Self {
+    Term::Exact(TimeDelta::days(days as i64))
+  }
+
+  /// Accepts humantime (`30d`, `2w`, `1h30m`) and calendar forms: `1mo`,
+  /// `2months`, `1q`, `1y`
+  pub fn parse(s: &str) -> Option<Self> {
+    let s = s.trim().to_lowercase();
+    let split = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    let (count, unit) = s.split_at(split);
+    let months = match unit.trim() {
+      "mo" | "mon" | "month" | "months" => Some(1),
+      "q" | "quarter" | "quarters" => Some(3),
+      "y" | "yr" | "year" | "years" => Some(12),
+      _ => None,
+    };
+    if let Some(months) = months {
+      let count = count.parse::<u32>().ok().filter(|n| *n > 0)?;
+      return Some(Term::Months(count.checked_mul(months)?));
+    }
+    humantime::parse_duration(&s)
+      .ok()
+      .and_then(|d| TimeDelta::from_std(d).ok())
+      .map(Term::Exact)
+  }
+
+  /// When a term started at `from` runs out
+  pub fn after(self, from: DateTime) -> DateTime {
+    match self {
+      Term::Exact(delta) => from + delta,
+      Term::Months(months) => from
+        .checked_add_months(Months::new(months))
+        .unwrap_or(from + TimeDelta::days(30 * months as i64)),
+    }
+  }
+}
+
+impl fmt::Display for Term {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      Term::Exact(delta) => f.write_str(&utils::format_duration(*delta)),
+      Term::Months(1) => f.write_str("1 month"),
+      Term::Months(months) => write!(f, "{} months", months),
+    }
+  }
+}
+
+/// Hours a restricted license may be used in, in the owner's timezone
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Schedule {
+  pub from_hour: i32,
+  pub to_hour: i32,
+  pub utc_offset_mins: i32,
+}
+
+impl Schedule {
+  /// Whether `now` (UTC) falls into the window, which may wrap midnight
+  pub fn allows(&self, now: DateTime) -> bool {
+    let local = now + TimeDelta::minutes(self.utc_offset_mins as i64);
+    let hour = local.hour() as i32;
+    if self.from_hour <= self.to_hour {
+      (self.from_hour..self.to_hour).contains(&hour)
+    } else {
+      hour >= self.from_hour || hour < self.to_hour
+    }
+  }
+}
+
 pub struct License<'a> {
   db: &'a DatabaseConnection,
+  /// Unexpired licenses a regular user may hold, 0 is unlimited
+  max_active: u64,
+  /// Grace past expiry tolerated when validating
+  skew: TimeDelta,
 }
 
 impl<'a> License<'a> {
   pub fn new(db: &'a DatabaseConnection) -> Self {
-    Self { db }
+    Self { db, max_active: 0, skew: TimeDelta::zero() }
+  }
+
+  /// Make `create` and `link_to_user` refuse users already holding
+  /// `max_active` licenses
+  pub fn capped(self, max_active: u64) -> Self {
+    Self { max_active, ..self }
+  }
+
+  /// Tolerate clocks off by up to `skew` when checking expiry
+  pub fn with_skew(self, skew: TimeDelta) -> Self {
+    Self { skew, ..self }
+  }
+
+  /// Unexpired licenses of a user, honeypots and loaners aside
+  pub async fn active_count(&self, tg_user_id: i64) -> Result<u64> {
+    let now = Utc::now().naive_utc();
+    Ok(
+      license::Entity::find()
+        .filter(license::Column::TgUserId.eq(tg_user_id))
+        .filter(license::Column::ExpiresAt.gt(now))
+        .filter(license::Column::IsHoneypot.eq(false))
+        .filter(license::Column::LoanedBy.is_null())
+        .count(self.db)
+        .await?,
+    )
+  }
+
+  async fn check_cap(&self, user: &user::Model) -> Result<()> {
+    if self.max_active > 0
+      && !user.is_cap_exempt()
+      && self.active_count(user.tg_user_id).await? >= self.max_active
+    {
+      return Err(Error::Ineligible(Ineligible::LicenseLimit(self.max_active)));
+    }
+    Ok(())
   }
 
   pub async fn create(
@@ -21,10 +152,20 @@ impl<'a> License<'a> {
     ty: LicenseType,
     days: u64,
   ) -> Result<license::Model> {
-    sv::User::new(self.db).get_or_create(tg_user_id).await?;
+    self.create_term(tg_user_id, ty, Term::days(days)).await
+  }
+
+  pub async fn create_term(
+    &self,
+    tg_user_id: i64,
+    ty: LicenseType,
+    term: Term,
+  ) -> Result<license::Model> {
+    let user = sv::User::new(self.db).get_or_create(tg_user_id).await?;
+    self.check_cap(&user).await?;
 
     let now = Utc::now().naive_utc();
-    let expires_at = now + Duration::from_hours(24 * days);
+    let expires_at = term.after(now);
     let key = Uuid::new_v4();
 
     let license = license::ActiveModel {
@@ -35,6 +176,15 @@ impl<'a> License<'a> {
       expires_at: Set(expires_at),
       created_at: Set(now),
       max_sessions: Set(1), // TODO: based on buy
+      is_honeypot: Set(false),
+      allowed_from_hour: Set(None),
+      allowed_to_hour: Set(None),
+      region: Set(None),
+      product: Set(sv::product::DEFAULT.to_string()),
+      auto_renew: Set(false),
+      expiry_notified_at: Set(None),
+      blocked_until: Set(None),
+      loaned_by: Set(None),
     };
 
     Ok(license.insert(self.db).await?)
@@ -46,32 +196,156 @@ impl<'a> License<'a> {
   ///
   /// Note: Uses tg_user_id = 0 as a placeholder for "unlinked" licenses.
   /// Ensures a placeholder user with ID 0 exists for foreign key constraint.
-  #[allow(dead_code)]
   pub async fn create_gift(
     &self,
     ty: LicenseType,
-    days: u64,
+    term: Term,
   ) -> Result<license::Model> {
     // Ensure placeholder user exists (ID 0 represents "no owner")
     sv::User::new(self.db).get_or_create(0).await?;
+    Self::insert_gift(self.db, ty, term, Utc::now().naive_utc()).await
+  }
+
+  /// Create `count` gift licenses at once, all or none
+  pub async fn create_gifts(
+    &self,
+    ty: LicenseType,
+    term: Term,
+    count: usize,
+  ) -> Result<Vec<license::Model>> {
+    if !(1..=GIFT_BATCH_MAX).contains(&count) {
+      return Err(Error::InvalidArgs(format!(
+        "Batch size must be between 1 and {}",
+        GIFT_BATCH_MAX
+      )));
+    }
+    sv::User::new(self.db).get_or_create(0).await?;
 
     let now = Utc::now().naive_utc();
-    let expires_at = now + Duration::from_hours(24 * days);
-    let key = Uuid::new_v4();
+    let txn = self.db.begin().await?;
+    let mut gifts = Vec::with_capacity(count);
+    for _ in 0..count {
+      gifts.push(Self::insert_gift(&txn, ty.clone(), term, now).await?);
+    }
+    txn.commit().await?;
+    Ok(gifts)
+  }
 
+  async fn insert_gift<C: ConnectionTrait>(
+    db: &C,
+    ty: LicenseType,
+    term: Term,
+    now: DateTime,
+  ) -> Result<license::Model> {
     let license = license::ActiveModel {
-      key: Set(key.to_string()),
+      key: Set(Uuid::new_v4().to_string()),
       tg_user_id: Set(0), // Not linked to any user yet
       license_type: Set(ty),
       is_blocked: Set(false),
-      expires_at: Set(expires_at),
+      expires_at: Set(term.after(now)),
       created_at: Set(now),
       max_sessions: Set(1),
+      is_honeypot: Set(false),
+      allowed_from_hour: Set(None),
+      allowed_to_hour: Set(None),
+      region: Set(None),
+      product: Set(sv::product::DEFAULT.to_string()),
+      auto_renew: Set(false),
+      expiry_notified_at: Set(None),
+      blocked_until: Set(None),
+      loaned_by: Set(None),
+    };
+
+    Ok(license.insert(db).await?)
+  }
+
+  /// Gift keys nobody has redeemed yet, newest first
+  pub async fn gifts(&self) -> Result<Vec<license::Model>> {
+    Ok(
+      license::Entity::find()
+        .filter(license::Column::TgUserId.eq(0))
+        .filter(license::Column::IsBlocked.eq(false))
+        .filter(license::Column::IsHoneypot.eq(false))
+        .order_by_desc(license::Column::CreatedAt)
+        .all(self.db)
+        .await?,
+    )
+  }
+
+  /// Create a decoy key owned by `tg_user_id` (an admin).
+  /// It looks like a regular Pro key but never validates.
+  pub async fn create_honeypot(
+    &self,
+    tg_user_id: i64,
+  ) -> Result<license::Model> {
+    let license = self.create(tg_user_id, LicenseType::Pro, 365).await?;
+    Ok(
+      license::ActiveModel { is_honeypot: Set(true), ..license.into() }
+        .update(self.db)
+        .await?,
+    )
+  }
+
+  /// Full-featured key for `tg_user_id` that expires after `hours`, issued
+  /// by support to debug a customer's machine
+  pub async fn create_loaner(
+    &self,
+    admin_id: i64,
+    tg_user_id: i64,
+    hours: i64,
+  ) -> Result<license::Model> {
+    sv::User::new(self.db).get_or_create(tg_user_id).await?;
+
+    let now = Utc::now().naive_utc();
+    let license = license::ActiveModel {
+      key: Set(Uuid::new_v4().to_string()),
+      tg_user_id: Set(tg_user_id),
+      license_type: Set(LicenseType::Pro),
+      is_blocked: Set(false),
+      expires_at: Set(now + TimeDelta::hours(hours)),
+      created_at: Set(now),
+      max_sessions: Set(1),
+      is_honeypot: Set(false),
+      allowed_from_hour: Set(None),
+      allowed_to_hour: Set(None),
+      region: Set(None),
+      product: Set(sv::product::DEFAULT.to_string()),
+      auto_renew: Set(false),
+      expiry_notified_at: Set(None),
+      blocked_until: Set(None),
+      loaned_by: Set(Some(admin_id)),
     };
 
     Ok(license.insert(self.db).await?)
   }
 
+  /// Recently issued loaners, newest first
+  pub async fn loaners(&self, limit: u64) -> Result<Vec<license::Model>> {
+    Ok(
+      license::Entity::find()
+        .filter(license::Column::LoanedBy.is_not_null())
+        .order_by_desc(license::Column::CreatedAt)
+        .limit(limit)
+        .all(self.db)
+        .await?,
+    )
+  }
+
+  /// Signed, time-limited license blob the client trusts through server
+  /// outages, valid for `lifetime` but never past the license expiry
+  pub async fn issue_offline_token(
+    &self,
+    key: &str,
+    hwid: &str,
+    keyring: &Keyring,
+    lifetime: TimeDelta,
+  ) -> Result<(String, Assertion)> {
+    let license = self.validate(key).await?;
+    let now = Utc::now().naive_utc();
+    let claims = Assertion::new(&license, hwid, now, lifetime, self.skew);
+    Ok((keyring.sign(&claims)?, claims))
+  }
+
   pub async fn by_key(&self, key: &str) -> Result<Option<license::Model>> {
     let license = license::Entity::find_by_id(key).one(self.db).await?;
     Ok(license)
@@ -92,25 +366,103 @@ impl<'a> License<'a> {
     Ok(query.all(self.db).await?)
   }
 
+  /// Newest licenses first, of one user or everyone
+  pub async fn page(
+    &self,
+    tg_user_id: Option<i64>,
+    offset: u64,
+    limit: u64,
+  ) -> Result<Vec<license::Model>> {
+    let mut query = license::Entity::find();
+    if let Some(tg_user_id) = tg_user_id {
+      query = query.filter(license::Column::TgUserId.eq(tg_user_id));
+    }
+    Ok(
+      query
+        .order_by_desc(license::Column::CreatedAt)
+        .offset(offset)
+        .limit(limit)
+        .all(self.db)
+        .await?,
+    )
+  }
+
   pub async fn validate(&self, key: &str) -> Result<license::Model> {
     let license = license::Entity::find_by_id(key)
       .one(self.db)
       .await?
       .ok_or(Error::LicenseNotFound)?;
 
+    if license.is_honeypot {
+      return Err(Error::Honeypot);
+    }
+
     let now = Utc::now().naive_utc();
-    if license.is_blocked || license.expires_at < now {
+    if license.is_blocked || license.is_expired(now, self.skew) {
       return Err(Error::LicenseInvalid);
     }
 
+    if let Some(until) = license.suspended_until(now) {
+      return Err(Error::LicenseSuspended(until));
+    }
+
+    if let Some(schedule) = self.schedule(&license).await?
+      && !schedule.allows(now)
+    {
+      return Err(Error::OutsideSchedule {
+        from: schedule.from_hour,
+        to: schedule.to_hour,
+      });
+    }
+
     Ok(license)
   }
 
-  pub async fn expires(
+  /// Usage window of the license resolved against the owner's timezone
+  pub async fn schedule(
+    &self,
+    license: &license::Model,
+  ) -> Result<Option<Schedule>> {
+    let (Some(from_hour), Some(to_hour)) =
+      (license.allowed_from_hour, license.allowed_to_hour)
+    else {
+      return Ok(None);
+    };
+
+    let utc_offset_mins = user_settings::Entity::find_by_id(license.tg_user_id)
+      .one(self.db)
+      .await?
+      .map(|s| s.utc_offset_mins)
+      .unwrap_or(0);
+
+    Ok(Some(Schedule { from_hour, to_hour, utc_offset_mins }))
+  }
+
+  /// Restrict the license to `hours` (local `from..to`), `None` lifts it
+  pub async fn set_schedule(
     &self,
     key: &str,
-    duration: Duration,
-  ) -> Result<DateTime> {
+    hours: Option<(i32, i32)>,
+  ) -> Result<license::Model> {
+    let license = license::Entity::find_by_id(key)
+      .one(self.db)
+      .await?
+      .ok_or(Error::LicenseNotFound)?;
+
+    let (from, to) = hours.unzip();
+    Ok(
+      license::ActiveModel {
+        allowed_from_hour: Set(from),
+        allowed_to_hour: Set(to),
+        ..license.into()
+      }
+      .update(self.db)
+      .await?,
+    )
+  }
+
+  /// Make the key run for `term` from now, whatever was left of it
+  pub async fn expires(&self, key: &str, term: Term) -> Result<DateTime> {
     let txn = self.db.begin().await?;
 
     let license = license::Entity::find_by_id(key)
@@ -118,8 +470,7 @@ impl<'a> License<'a> {
       .await?
       .ok_or(Error::LicenseNotFound)?;
 
-    let delta = TimeDelta::from_std(duration).unwrap_or(TimeDelta::zero());
-    let new_exp = Utc::now().naive_utc() + delta;
+    let new_exp = term.after(Utc::now().naive_utc());
 
     license::ActiveModel {
       expires_at: Set(new_exp),
@@ -133,25 +484,341 @@ impl<'a> License<'a> {
     Ok(new_exp)
   }
 
+  /// Add `days` to the current expiry, or to now if the key already expired
+  pub async fn extend(&self, key: &str, days: u64) -> Result<DateTime> {
+    self.extend_term(key, Term::days(days)).await
+  }
+
+  /// Add `term` to the current expiry, or to now if the key already expired
+  pub async fn extend_term(&self, key: &str, term: Term) -> Result<DateTime> {
+    Self::extend_term_in(self.db, key, term, None).await
+  }
+
+  /// `extend_term` on any connection, also recording the country of a
+  /// regional price if one was paid
+  pub async fn extend_term_in<C: ConnectionTrait>(
+    db: &C,
+    key: &str,
+    term: Term,
+    region: Option<String>,
+  ) -> Result<DateTime> {
+    let license = license::Entity::find_by_id(key)
+      .one(db)
+      .await?
+      .ok_or(Error::LicenseNotFound)?;
+
+    let from = license.expires_at.max(Utc::now().naive_utc());
+    let expires_at = term.after(from);
+    let region = region.or(license.region.clone());
+    license::ActiveModel {
+      expires_at: Set(expires_at),
+      region: Set(region),
+      ..license.into()
+    }
+    .update(db)
+    .await?;
+    Ok(expires_at)
+  }
+
+  pub async fn set_auto_renew(
+    &self,
+    key: &str,
+    auto_renew: bool,
+  ) -> Result<license::Model> {
+    let license = license::Entity::find_by_id(key)
+      .one(self.db)
+      .await?
+      .ok_or(Error::LicenseNotFound)?;
+
+    Ok(
+      license::ActiveModel { auto_renew: Set(auto_renew), ..license.into() }
+        .update(self.db)
+        .await?,
+    )
+  }
+
+  /// Opted-in keys expiring within `within`, or that expired less than
+  /// `within` ago while the renewal could not run
+  pub async fn renewal_due(
+    &self,
+    within: TimeDelta,
+  ) -> Result<Vec<license::Model>> {
+    let now = Utc::now().naive_utc();
+    Ok(
+      license::Entity::find()
+        .filter(license::Column::AutoRenew.eq(true))
+        .filter(license::Column::IsBlocked.eq(false))
+        .filter(license::Column::IsHoneypot.eq(false))
+        .filter(license::Column::LoanedBy.is_null())
+        .filter(license::Column::ExpiresAt.lte(now + within))
+        .filter(license::Column::ExpiresAt.gt(now - within))
+        .all(self.db)
+        .await?,
+    )
+  }
+
+  /// Keys whose owner is due an expiry reminder, with the days left it is for
+  pub async fn expiry_reminders_due(
+    &self,
+  ) -> Result<Vec<(license::Model, i64)>> {
+    let now = Utc::now().naive_utc();
+    let furthest = EXPIRY_REMINDERS[EXPIRY_REMINDERS.len() - 1];
+    let licenses = license::Entity::find()
+      .filter(license::Column::IsBlocked.eq(false))
+      .filter(license::Column::IsHoneypot.eq(false))
+      .filter(license::Column::LoanedBy.is_null())
+      .filter(license::Column::TgUserId.ne(0))
+      .filter(license::Column::ExpiresAt.gt(now))
+      .filter(license::Column::ExpiresAt.lte(now + TimeDelta::days(furthest)))
+      .all(self.db)
+      .await?;
+
+    Ok(
+      licenses
+        .into_iter()
+        .filter_map(|license| {
+          let left = license.expires_at - now;
+          let days = EXPIRY_REMINDERS
+            .into_iter()
+            .find(|&days| left <= TimeDelta::days(days))?;
+          // extending moves the window, so reminders start over
+          let window = license.expires_at - TimeDelta::days(days);
+          let sent = license.expiry_notified_at.is_some_and(|at| at >= window);
+          (!sent).then_some((license, days))
+        })
+        .collect(),
+    )
+  }
+
+  pub async fn mark_expiry_notified(&self, key: &str) -> Result<()> {
+    license::Entity::update_many()
+      .col_expr(
+        license::Column::ExpiryNotifiedAt,
+        Utc::now().naive_utc().into(),
+      )
+      .filter(license::Column::Key.eq(key))
+      .exec(self.db)
+      .await?;
+    Ok(())
+  }
+
   pub async fn set_blocked(&self, key: &str, blocked: bool) -> Result<()> {
     let license = license::Entity::find_by_id(key)
       .one(self.db)
       .await?
       .ok_or(Error::LicenseNotFound)?;
 
-    license::ActiveModel { is_blocked: Set(blocked), ..license.into() }
+    // unblocking also lifts a running cool-down
+    let blocked_until = if blocked { license.blocked_until } else { None };
+    license::ActiveModel {
+      is_blocked: Set(blocked),
+      blocked_until: Set(blocked_until),
+      ..license.into()
+    }
+    .update(self.db)
+    .await?;
+
+    Ok(())
+  }
+
+  /// Block the key for `duration`, it unblocks itself afterwards
+  pub async fn suspend(
+    &self,
+    key: &str,
+    duration: TimeDelta,
+  ) -> Result<DateTime> {
+    let license = license::Entity::find_by_id(key)
+      .one(self.db)
+      .await?
+      .ok_or(Error::LicenseNotFound)?;
+
+    let until = Utc::now().naive_utc() + duration;
+    license::ActiveModel { blocked_until: Set(Some(until)), ..license.into() }
       .update(self.db)
       .await?;
 
-    Ok(())
+    Ok(until)
   }
 
-  pub fn is_promo_active(&self) -> bool {
-    let now = Utc::now();
-    // TODO: configurable promo periods
-    let start = Utc.with_ymd_and_hms(2025, 12, 14, 13, 0, 0).unwrap();
-    let end = Utc.with_ymd_and_hms(2025, 12, 21, 23, 59, 59).unwrap();
-    now >= start && now <= end
+  /// Change how many sessions may run on the key at once
+  pub async fn set_max_sessions(
+    &self,
+    key: &str,
+    max_sessions: i32,
+  ) -> Result<license::Model> {
+    if !(1..=MAX_SESSIONS).contains(&max_sessions) {
+      return Err(Error::InvalidArgs(format!(
+        "Session limit must be between 1 and {}",
+        MAX_SESSIONS
+      )));
+    }
+    let license = license::Entity::find_by_id(key)
+      .one(self.db)
+      .await?
+      .ok_or(Error::LicenseNotFound)?;
+
+    Ok(
+      license::ActiveModel {
+        max_sessions: Set(max_sessions),
+        ..license.into()
+      }
+      .update(self.db)
+      .await?,
+    )
+  }
+
+  /// Replace the key of a license owned by `tg_user_id` with a fresh one.
+  /// Everything except the key is preserved; devices seen with the old
+  /// key are forgotten. The caller is responsible for dropping sessions.
+  pub async fn regenerate_key(
+    &self,
+    key: &str,
+    tg_user_id: i64,
+  ) -> Result<license::Model> {
+    let txn = self.db.begin().await?;
+
+    let license = license::Entity::find_by_id(key)
+      .one(&txn)
+      .await?
+      .filter(|l| l.tg_user_id == tg_user_id)
+      .ok_or(Error::LicenseNotFound)?;
+
+    let new_key = Uuid::new_v4().to_string();
+    let regenerated =
+      license::ActiveModel { key: Set(new_key.clone()), ..license.into() }
+        .insert(&txn)
+        .await?;
+
+    custom_field::Entity::update_many()
+      .col_expr(custom_field::Column::Target, new_key.as_str().into())
+      .filter(custom_field::Column::Scope.eq(FieldScope::License))
+      .filter(custom_field::Column::Target.eq(key))
+      .exec(&txn)
+      .await?;
+    feature_flag::Entity::update_many()
+      .col_expr(feature_flag::Column::Target, new_key.as_str().into())
+      .filter(feature_flag::Column::Scope.eq(FieldScope::License))
+      .filter(feature_flag::Column::Target.eq(key))
+      .exec(&txn)
+      .await?;
+    license_stats::Entity::update_many()
+      .col_expr(license_stats::Column::LicenseKey, new_key.as_str().into())
+      .filter(license_stats::Column::LicenseKey.eq(key))
+      .exec(&txn)
+      .await?;
+
+    license_device::Entity::delete_many()
+      .filter(license_device::Column::LicenseKey.eq(key))
+      .exec(&txn)
+      .await?;
+    license::Entity::delete_by_id(key).exec(&txn).await?;
+
+    txn.commit().await?;
+    Ok(regenerated)
+  }
+
+  /// License `from` may hand over to `to`, `transfer` checks it again
+  pub async fn transferable(
+    &self,
+    key: &str,
+    from: i64,
+    to: i64,
+  ) -> Result<license::Model> {
+    Self::check_transfer(self.db, key, from, to).await
+  }
+
+  async fn check_transfer<C: ConnectionTrait>(
+    db: &C,
+    key: &str,
+    from: i64,
+    to: i64,
+  ) -> Result<license::Model> {
+    let license = license::Entity::find_by_id(key)
+      .one(db)
+      .await?
+      .filter(|l| l.tg_user_id == from && !l.is_honeypot)
+      .ok_or(Error::LicenseNotFound)?;
+    if to == from || to == 0 {
+      return Err(Error::InvalidArgs("Pick another account".into()));
+    }
+    if license.is_blocked || license.loaned_by.is_some() {
+      return Err(Error::InvalidArgs("This key can't be transferred".into()));
+    }
+    if let Some(until) = license.suspended_until(Utc::now().naive_utc()) {
+      return Err(Error::LicenseSuspended(until));
+    }
+    user::Entity::find_by_id(to).one(db).await?.ok_or(Error::UserNotFound)?;
+    Ok(license)
+  }
+
+  /// Hand a license owned by `from` over to `to`. Auto-renewal is turned
+  /// off so the new owner is never charged unasked, and devices of the old
+  /// owner are forgotten. The caller is responsible for dropping sessions.
+  pub async fn transfer(
+    &self,
+    key: &str,
+    from: i64,
+    to: i64,
+  ) -> Result<license::Model> {
+    let txn = self.db.begin().await?;
+    let license = Self::check_transfer(&txn, key, from, to).await?;
+
+    let transferred = license::ActiveModel {
+      tg_user_id: Set(to),
+      auto_renew: Set(false),
+      expiry_notified_at: Set(None),
+      ..license.into()
+    }
+    .update(&txn)
+    .await?;
+    license_device::Entity::delete_many()
+      .filter(license_device::Column::LicenseKey.eq(key))
+      .exec(&txn)
+      .await?;
+
+    txn.commit().await?;
+    Ok(transferred)
+  }
+
+  /// Move the license to another product, e.g. right after a purchase
+  pub async fn set_product(
+    &self,
+    key: &str,
+    product: &str,
+  ) -> Result<license::Model> {
+    let license = license::Entity::find_by_id(key)
+      .one(self.db)
+      .await?
+      .ok_or(Error::LicenseNotFound)?;
+    let product = sv::Product::new(self.db).get(product).await?;
+
+    Ok(
+      license::ActiveModel { product: Set(product.slug), ..license.into() }
+        .update(self.db)
+        .await?,
+    )
+  }
+
+  /// Bind the license to the country whose regional price was paid
+  pub async fn set_region(
+    &self,
+    key: &str,
+    region: Option<String>,
+  ) -> Result<license::Model> {
+    let license = license::Entity::find_by_id(key)
+      .one(self.db)
+      .await?
+      .ok_or(Error::LicenseNotFound)?;
+
+    Ok(
+      license::ActiveModel { region: Set(region), ..license.into() }
+        .update(self.db)
+        .await?,
+    )
+  }
+
+  pub async fn is_promo_active(&self, code: &str) -> bool {
+    sv::PromoCampaign::new(self.db).is_active(code).await
   }
 
   #[allow(dead_code)]
@@ -177,7 +844,7 @@ impl<'a> License<'a> {
     tg_user_id: i64,
   ) -> Result<license::Model> {
     // Ensure the user exists
-    sv::User::new(self.db).get_or_create(tg_user_id).await?;
+    let user = sv::User::new(self.db).get_or_create(tg_user_id).await?;
 
     let license = license::Entity::find_by_id(key)
       .one(self.db)
@@ -188,6 +855,10 @@ impl<'a> License<'a> {
     if license.tg_user_id != 0 && license.tg_user_id != tg_user_id {
       return Err(Error::LicenseAlreadyLinked);
     }
+    // A gift counts towards the cap like a bought key
+    if license.tg_user_id == 0 {
+      self.check_cap(&user).await?;
+    }
 
     // Calculate new expiration: if this is the first link (activation),
     // start the timer from now instead of from creation time
@@ -212,33 +883,41 @@ impl<'a> License<'a> {
     Ok(updated)
   }
 
-  pub async fn claim_promo(
+  pub async fn has_claimed(
     &self,
     tg_user_id: i64,
     promo_name: &str,
-  ) -> Result<license::Model> {
-    if !self.is_promo_active() {
-      return Err(Error::Promo(Promo::Inactive));
-    }
-
-    // ensure exists
-    sv::User::new(self.db).get_or_create(tg_user_id).await?;
-
-    let existing =
+  ) -> Result<bool> {
+    let claimed =
       promo::Entity::find_by_id((tg_user_id, promo_name.to_string()))
         .one(self.db)
         .await?;
+    Ok(claimed.is_some())
+  }
 
-    if existing.is_some() {
+  /// Redeem a promo campaign code for a fresh license
+  pub async fn claim_promo(
+    &self,
+    tg_user_id: i64,
+    code: &str,
+  ) -> Result<license::Model> {
+    let code = sv::PromoCampaign::normalize(code);
+    if self.has_claimed(tg_user_id, &code).await? {
       return Err(Error::Promo(Promo::Claimed));
     }
+    let campaign = sv::PromoCampaign::new(self.db).redeemable(&code).await?;
+
+    // ensure exists
+    sv::User::new(self.db).get_or_create(tg_user_id).await?;
 
-    let license = self.create(tg_user_id, LicenseType::Trial, 7).await?;
+    let license = self
+      .create(tg_user_id, campaign.license_type, campaign.days as u64)
+      .await?;
     let now = Utc::now().naive_utc();
 
     promo::ActiveModel {
       tg_user_id: Set(tg_user_id),
-      promo_name: Set(promo_name.to_string()),
+      promo_name: Set(campaign.code),
       claimed_at: Set(now),
     }
     .insert(self.db)
@@ -291,6 +970,233 @@ mod tests {
     ));
   }
 
+  #[tokio::test]
+  async fn test_suspend_license() {
+    let db = test_db::setup().await;
+    let sv = License::new(&db);
+
+    let license = sv.create(12345, LicenseType::Pro, 30).await.unwrap();
+    let until = sv.suspend(&license.key, TimeDelta::hours(24)).await.unwrap();
+    assert!(matches!(
+      sv.validate(&license.key).await,
+      Err(Error::LicenseSuspended(at)) if at == until
+    ));
+
+    // an elapsed cool-down no longer blocks
+    sv.suspend(&license.key, TimeDelta::hours(-1)).await.unwrap();
+    sv.validate(&license.key).await.unwrap();
+
+    // unban is the admin override
+    sv.suspend(&license.key, TimeDelta::hours(24)).await.unwrap();
+    sv.set_blocked(&license.key, false).await.unwrap();
+    sv.validate(&license.key).await.unwrap();
+  }
+
+  #[tokio::test]
+  async fn test_loaner_license() {
+    let db = test_db::setup().await;
+    let sv = License::new(&db).capped(1);
+
+    sv.create(1, LicenseType::Pro, 30).await.unwrap();
+    // loaners bypass the cap and don't count towards it
+    let loaner = sv.create_loaner(99, 1, 2).await.unwrap();
+    assert_eq!(loaner.loaned_by, Some(99));
+    assert_eq!(sv.active_count(1).await.unwrap(), 1);
+    sv.validate(&loaner.key).await.unwrap();
+
+    let loaners = sv.loaners(10).await.unwrap();
+    assert_eq!(loaners.len(), 1);
+    assert_eq!(loaners[0].key, loaner.key);
+  }
+
+  #[tokio::test]
+  async fn test_active_license_cap() {
+    let db = test_db::setup().await;
+    let sv = License::new(&db).capped(2);
+
+    sv.create(1, LicenseType::Trial, 30).await.unwrap();
+    sv.create(1, LicenseType::Pro, 30).await.unwrap();
+    assert!(matches!(
+      sv.create(1, LicenseType::Pro, 30).await,
+      Err(Error::Ineligible(Ineligible::LicenseLimit(2)))
+    ));
+
+    let gift = sv.create_gift(LicenseType::Pro, Term::days(30)).await.unwrap();
+    assert!(matches!(
+      sv.link_to_user(&gift.key, 1).await,
+      Err(Error::Ineligible(Ineligible::LicenseLimit(2)))
+    ));
+
+    sv::User::new(&db).set_reseller(1, true).await.unwrap();
+    sv.create(1, LicenseType::Pro, 30).await.unwrap();
+    sv.link_to_user(&gift.key, 1).await.unwrap();
+    assert_eq!(sv.active_count(1).await.unwrap(), 4);
+  }
+
+  #[tokio::test]
+  async fn test_issue_offline_token() {
+    let db = test_db::setup().await;
+    let sv = License::new(&db);
+    let keyring = sv::SigningKeys::new(&db).keyring().await.unwrap();
+
+    let license = sv.create(1, LicenseType::Pro, 1).await.unwrap();
+    let (token, claims) = sv
+      .issue_offline_token(&license.key, "hwid", &keyring, TimeDelta::days(7))
+      .await
+      .unwrap();
+    assert_eq!(token.split('.').count(), 3);
+    // capped by the license expiry
+    assert_eq!(claims.exp, license.expires_at.and_utc().timestamp());
+
+    sv.set_blocked(&license.key, true).await.unwrap();
+    let blocked = sv
+      .issue_offline_token(&license.key, "hwid", &keyring, TimeDelta::days(7))
+      .await;
+    assert!(matches!(blocked, Err(Error::LicenseInvalid)));
+  }
+
+  #[tokio::test]
+  async fn test_page_licenses() {
+    let db = test_db::setup().await;
+    let sv = License::new(&db);
+
+    sv.create(1, LicenseType::Trial, 30).await.unwrap();
+    sv.create(2, LicenseType::Pro, 30).await.unwrap();
+    sv.create(2, LicenseType::Pro, 30).await.unwrap();
+
+    assert_eq!(sv.page(None, 0, 10).await.unwrap().len(), 3);
+    assert_eq!(sv.page(None, 1, 10).await.unwrap().len(), 2);
+    assert_eq!(sv.page(Some(2), 0, 10).await.unwrap().len(), 2);
+    assert_eq!(sv.page(Some(2), 0, 1).await.unwrap().len(), 1);
+  }
+
+  #[tokio::test]
+  async fn test_set_max_sessions() {
+    let db = test_db::setup().await;
+    let sv = License::new(&db);
+
+    let license = sv.create(12345, LicenseType::Pro, 30).await.unwrap();
+    assert_eq!(license.max_sessions, 1);
+
+    let license = sv.set_max_sessions(&license.key, 3).await.unwrap();
+    assert_eq!(license.max_sessions, 3);
+
+    assert!(sv.set_max_sessions(&license.key, 0).await.is_err());
+    assert!(sv.set_max_sessions(&license.key, MAX_SESSIONS + 1).await.is_err());
+    assert!(matches!(
+      sv.set_max_sessions("missing", 2).await,
+      Err(Error::LicenseNotFound)
+    ));
+  }
+
+  #[tokio::test]
+  async fn test_auto_renewal_due() {
+    let db = test_db::setup().await;
+    let sv = License::new(&db);
+    let day = TimeDelta::days(1);
+
+    let soon = sv.create(12345, LicenseType::Pro, 1).await.unwrap();
+    let later = sv.create(12345, LicenseType::Pro, 30).await.unwrap();
+    assert!(sv.renewal_due(day).await.unwrap().is_empty());
+
+    sv.set_auto_renew(&soon.key, true).await.unwrap();
+    sv.set_auto_renew(&later.key, true).await.unwrap();
+    let due = sv.renewal_due(day).await.unwrap();
+    assert_eq!(due.len(), 1);
+    assert_eq!(due[0].key, soon.key);
+
+    // renewing adds to the time left instead of starting over
+    let expires_at = sv.extend(&soon.key, 30).await.unwrap();
+    assert_eq!(expires_at, soon.expires_at + TimeDelta::days(30));
+    assert!(sv.renewal_due(day).await.unwrap().is_empty());
+  }
+
+  #[tokio::test]
+  async fn test_expiry_reminders() {
+    let db = test_db::setup().await;
+    let sv = License::new(&db);
+
+    let license = sv.create(12345, LicenseType::Pro, 5).await.unwrap();
+    sv.create(12345, LicenseType::Pro, 30).await.unwrap();
+
+    let due = sv.expiry_reminders_due().await.unwrap();
+    assert_eq!(due.len(), 1);
+    assert_eq!((due[0].0.key.as_str(), due[0].1), (license.key.as_str(), 7));
+
+    sv.mark_expiry_notified(&license.key).await.unwrap();
+    assert!(sv.expiry_reminders_due().await.unwrap().is_empty());
+
+    // two days later the 3-day reminder is due
+    let now = Utc::now().naive_utc();
+    license::ActiveModel {
+      expires_at: Set(now + TimeDelta::hours(60)),
+      expiry_notified_at: Set(Some(now - TimeDelta::days(2))),
+      ..license.into()
+    }
+    .update(&db)
+    .await
+    .unwrap();
+    let due = sv.expiry_reminders_due().await.unwrap();
+    assert_eq!(due[0].1, 3);
+  }
+
+  #[tokio::test]
+  async fn test_night_schedule() {
+    let db = test_db::setup().await;
+    let sv = License::new(&db);
+
+    let license = sv.create(12345, LicenseType::Pro, 30).await.unwrap();
+    let license = sv.set_schedule(&license.key, Some((20, 8))).await.unwrap();
+
+    let at = |h| {
+      chrono::NaiveDate::from_ymd_opt(2026, 1, 1)
+        .unwrap()
+        .and_hms_opt(h, 30, 0)
+        .unwrap()
+    };
+    let schedule = sv.schedule(&license).await.unwrap().unwrap();
+    assert!(schedule.allows(at(23)));
+    assert!(schedule.allows(at(7)));
+    assert!(!schedule.allows(at(12)));
+
+    // 18:30 UTC is 21:30 at UTC+3
+    sv::Settings::new(&db).set_utc_offset(12345, 180).await.unwrap();
+    let schedule = sv.schedule(&license).await.unwrap().unwrap();
+    assert!(schedule.allows(at(18)));
+    assert!(!schedule.allows(at(6)));
+
+    sv.set_schedule(&license.key, None).await.unwrap();
+    assert!(sv.validate(&license.key).await.is_ok());
+  }
+
+  #[test]
+  fn test_terms() {
+    assert_eq!(Term::parse("30d"), Some(Term::days(30)));
+    assert_eq!(Term::parse("1mo"), Some(Term::Months(1)));
+    assert_eq!(Term::parse("2 months"), Some(Term::Months(2)));
+    assert_eq!(Term::parse("1q"), Some(Term::Months(3)));
+    assert_eq!(Term::parse("1y"), Some(Term::Months(12)));
+    assert_eq!(Term::parse("0mo"), None);
+    assert_eq!(Term::parse("soon"), None);
+
+    // calendar months keep the day of month, clamped to the month's end
+    let at = |d: &str| {
+      chrono::NaiveDateTime::parse_from_str(d, "%Y-%m-%d %H:%M").unwrap()
+    };
+    let jan = at("2026-01-31 12:00");
+    assert_eq!(Term::Months(1).after(jan), at("2026-02-28 12:00"));
+    assert_eq!(Term::Months(3).after(jan), at("2026-04-30 12:00"));
+
+    assert_eq!(Term::Months(3).to_string(), "3 months");
+    assert_eq!(Term::days(30).to_string(), "30d");
+    assert_eq!(
+      utils::format_duration(TimeDelta::minutes(2 * 1440 + 301)),
+      "2d 5h"
+    );
+    assert_eq!(utils::format_duration(TimeDelta::minutes(61)), "1h 1m");
+    assert_eq!(utils::format_duration(TimeDelta::seconds(20)), "0m");
+  }
+
   #[tokio::test]
   async fn test_extend_license() {
     let db = test_db::setup().await;
@@ -299,10 +1205,7 @@ mod tests {
     let license = sv.create(12345, LicenseType::Trial, 1).await.unwrap();
 
     let old_exp = license.expires_at;
-    let new_exp = sv
-      .expires(&license.key, Duration::from_secs(30 * 24 * 60 * 60))
-      .await
-      .unwrap();
+    let new_exp = sv.expires(&license.key, Term::days(30)).await.unwrap();
 
     assert!(new_exp > old_exp);
   }
@@ -313,7 +1216,7 @@ mod tests {
     let sv = License::new(&db);
 
     // Create a gift license (not linked to any user)
-    let gift = sv.create_gift(LicenseType::Pro, 30).await.unwrap();
+    let gift = sv.create_gift(LicenseType::Pro, Term::days(30)).await.unwrap();
     assert_eq!(gift.tg_user_id, 0);
 
     let original_created_at = gift.created_at;
@@ -357,7 +1260,7 @@ mod tests {
     let sv = License::new(&db);
 
     // Create a gift license and link it
-    let gift = sv.create_gift(LicenseType::Pro, 30).await.unwrap();
+    let gift = sv.create_gift(LicenseType::Pro, Term::days(30)).await.unwrap();
     let activated = sv.link_to_user(&gift.key, 12345).await.unwrap();
     let first_expires_at = activated.expires_at;
 
@@ -365,4 +1268,126 @@ mod tests {
     let relinked = sv.link_to_user(&gift.key, 12345).await.unwrap();
     assert_eq!(relinked.expires_at, first_expires_at);
   }
+
+  #[tokio::test]
+  async fn test_bulk_gifts() {
+    let db = test_db::setup().await;
+    let sv = License::new(&db);
+
+    let gifts =
+      sv.create_gifts(LicenseType::Pro, Term::days(30), 5).await.unwrap();
+    assert_eq!(gifts.len(), 5);
+    let keys: std::collections::HashSet<_> =
+      gifts.iter().map(|l| l.key.as_str()).collect();
+    assert_eq!(keys.len(), 5);
+    assert!(gifts.iter().all(|l| l.tg_user_id == 0));
+    assert_eq!(sv.gifts().await.unwrap().len(), 5);
+
+    assert!(
+      sv.create_gifts(LicenseType::Pro, Term::days(30), 0).await.is_err()
+    );
+    assert!(
+      sv.create_gifts(LicenseType::Pro, Term::days(30), GIFT_BATCH_MAX + 1)
+        .await
+        .is_err()
+    );
+  }
+
+  #[tokio::test]
+  async fn test_outstanding_gifts() {
+    let db = test_db::setup().await;
+    let sv = License::new(&db);
+
+    let week = sv.create_gift(LicenseType::Trial, Term::days(7)).await.unwrap();
+    let month = sv.create_gift(LicenseType::Pro, Term::days(30)).await.unwrap();
+    sv.create(1, LicenseType::Pro, 30).await.unwrap();
+    assert_eq!(sv.gifts().await.unwrap().len(), 2);
+
+    // redeemed and revoked gifts are no longer outstanding
+    sv.link_to_user(&week.key, 1).await.unwrap();
+    let gifts = sv.gifts().await.unwrap();
+    assert_eq!(gifts.len(), 1);
+    assert_eq!(gifts[0].key, month.key);
+    sv.set_blocked(&month.key, true).await.unwrap();
+    assert!(sv.gifts().await.unwrap().is_empty());
+  }
+
+  #[tokio::test]
+  async fn test_regenerate_key() {
+    let db = test_db::setup().await;
+    let sv = License::new(&db);
+
+    let license = sv.create(12345, LicenseType::Pro, 30).await.unwrap();
+    let device = sv::Device::new(&db);
+    device.touch(&license.key, "hwid", None, None).await.unwrap();
+    let flags = sv::FeatureFlag::new(&db);
+    flags.set(FieldScope::License, &license.key, "esp", false).await.unwrap();
+    license_stats::ActiveModel {
+      license_key: Set(license.key.clone()),
+      weekly_xp: Set(10),
+      total_xp: Set(500),
+      runtime_hours: Set(2.5),
+      last_updated: Set(license.created_at),
+    }
+    .insert(&db)
+    .await
+    .unwrap();
+
+    // Only the owner may regenerate
+    assert!(matches!(
+      sv.regenerate_key(&license.key, 99999).await,
+      Err(Error::LicenseNotFound)
+    ));
+
+    let regenerated = sv.regenerate_key(&license.key, 12345).await.unwrap();
+    assert_ne!(regenerated.key, license.key);
+    assert_eq!(regenerated.expires_at, license.expires_at);
+    assert!(sv.by_key(&license.key).await.unwrap().is_none());
+
+    let devices = sv::Device::new(&db).by_license(&license.key).await.unwrap();
+    assert!(devices.is_empty());
+
+    // a kill switch keeps holding on the new key
+    let features = flags.resolve(&regenerated).await.unwrap();
+    assert_eq!(features.get("esp"), Some(&false));
+    // and so does its usage history
+    let stats = sv::Stats::new(&db);
+    let usage = stats.by_licenses([regenerated.key.clone()]).await.unwrap();
+    assert_eq!(usage.len(), 1);
+    assert_eq!(usage[0].total_xp, 500);
+    assert!(stats.by_licenses([license.key]).await.unwrap().is_empty());
+  }
+
+  #[tokio::test]
+  async fn test_transfer() {
+    let db = test_db::setup().await;
+    let sv = License::new(&db);
+
+    let license = sv.create(1, LicenseType::Pro, 30).await.unwrap();
+    sv.set_auto_renew(&license.key, true).await.unwrap();
+    sv::Device::new(&db).touch(&license.key, "hwid", None, None).await.unwrap();
+
+    // the recipient has to have started the bot
+    assert!(matches!(
+      sv.transfer(&license.key, 1, 2).await,
+      Err(Error::UserNotFound)
+    ));
+    sv::User::new(&db).get_or_create(2).await.unwrap();
+    // only the owner may give it away
+    assert!(matches!(
+      sv.transfer(&license.key, 2, 1).await,
+      Err(Error::LicenseNotFound)
+    ));
+    assert!(sv.transfer(&license.key, 1, 1).await.is_err());
+
+    let moved = sv.transfer(&license.key, 1, 2).await.unwrap();
+    assert_eq!(moved.tg_user_id, 2);
+    assert_eq!(moved.expires_at, license.expires_at);
+    assert!(!moved.auto_renew);
+    let devices = sv::Device::new(&db).by_license(&license.key).await.unwrap();
+    assert!(devices.is_empty());
+
+    sv.set_blocked(&license.key, true).await.unwrap();
+    assert!(sv.transfer(&license.key, 2, 1).await.is_err());
+  }
 }
@@ -1,20 +1,104 @@
+pub mod account;
+pub mod admin_alert;
+pub mod admin_op;
+pub mod announcement;
+pub mod api_token;
+pub mod api_usage;
+pub mod assertion;
+pub mod backup;
 pub mod balance;
+pub mod bot_usage;
 pub mod build;
+pub mod canned;
+pub mod config_sync;
 pub mod cryptobot;
+pub mod custom_field;
+pub mod device;
+pub mod download_token;
+pub mod eligibility;
+pub mod error_report;
+pub mod faq;
+pub mod feature_flag;
+pub mod freebie;
+pub mod goal;
+pub mod hwid_policy;
+pub mod incident;
 pub mod license;
+pub mod outbox;
 pub mod payment;
+pub mod plan;
+pub mod pricing;
+pub mod product;
+pub mod promo_campaign;
+pub mod promo_code;
+pub mod promo_kit;
+pub mod rating;
+pub mod reconcile;
 pub mod referral;
+pub mod report;
+pub mod review;
+pub mod risk;
+pub mod s3;
+pub mod session;
+pub mod settings;
+pub mod sharing;
+pub mod sheets;
+pub mod signing_key;
 pub mod stats;
 pub mod steam;
+pub mod storefront;
+pub mod terms;
 #[cfg(test)]
 pub mod test_utils;
+pub mod ticket;
+pub mod upgrade_offer;
 pub mod user;
 
+pub use account::Account;
+pub use admin_alert::AdminAlerts;
+pub use admin_op::AdminOp;
+pub use announcement::Announcement;
+pub use api_token::ApiToken;
+pub use api_usage::ApiUsage;
 pub use balance::Balance;
+pub use bot_usage::BotUsage;
 pub use build::Build;
+pub use canned::Canned;
+pub use config_sync::ConfigSync;
+pub use custom_field::CustomField;
+pub use device::Device;
+pub use download_token::DownloadTokens;
+pub use eligibility::Eligibility;
+pub use error_report::ErrorReports;
+pub use faq::Faq;
+pub use feature_flag::FeatureFlag;
+pub use freebie::Freebies;
+pub use goal::Goal;
+pub use hwid_policy::HwidPolicy;
+pub use incident::Incident;
 pub use license::License;
+pub use outbox::Outbox;
 pub use payment::Payment;
+pub use plan::Plan;
+pub use pricing::Pricing;
+pub use product::Product;
+pub use promo_campaign::PromoCampaign;
+pub use promo_code::PromoCode;
+pub use promo_kit::PromoKit;
+pub use rating::Rating;
+pub use reconcile::Reconcile;
 pub use referral::Referral;
+pub use report::Report;
+pub use review::Reviews;
+pub use risk::Risk;
+pub use session::Session;
+pub use settings::Settings;
+pub use sharing::Sharing;
+pub use signing_key::SigningKeys;
 pub use stats::Stats;
 pub use steam::Steam;
+pub use storefront::Storefront;
+pub use terms::Terms;
+pub use ticket::Ticket;
+pub use upgrade_offer::UpgradeOffers;
 pub use user::User;
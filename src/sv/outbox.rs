@@ -0,0 +1,124 @@
+use crate::{entity::outbox, prelude::*};
+
+/// Messages that keep failing are given up after this many tries
+pub const MAX_ATTEMPTS: i32 = 6;
+
+pub struct Outbox<'a> {
+  db: &'a DatabaseConnection,
+}
+
+impl<'a> Outbox<'a> {
+  pub fn new(db: &'a DatabaseConnection) -> Self {
+    Self { db }
+  }
+
+  /// Queue a message on the caller's transaction, so it is sent only if
+  /// the change it announces commits
+  pub async fn push<C: ConnectionTrait>(
+    conn: &C,
+    tg_user_id: i64,
+    text: String,
+  ) -> Result<()> {
+    let now = Utc::now().naive_utc();
+    outbox::ActiveModel {
+      id: NotSet,
+      tg_user_id: Set(tg_user_id),
+      text: Set(text),
+      attempts: Set(0),
+      last_error: Set(None),
+      next_attempt_at: Set(now),
+      sent_at: Set(None),
+      created_at: Set(now),
+    }
+    .insert(conn)
+    .await?;
+    Ok(())
+  }
+
+  /// Unsent messages whose retry time has come, oldest first
+  pub async fn due(&self, limit: u64) -> Result<Vec<outbox::Model>> {
+    let now = Utc::now().naive_utc();
+    Ok(
+      outbox::Entity::find()
+        .filter(outbox::Column::SentAt.is_null())
+        .filter(outbox::Column::Attempts.lt(MAX_ATTEMPTS))
+        .filter(outbox::Column::NextAttemptAt.lte(now))
+        .order_by_asc(outbox::Column::Id)
+        .limit(limit)
+        .all(self.db)
+        .await?,
+    )
+  }
+
+  pub async fn mark_sent(&self, id: i32) -> Result<()> {
+    outbox::Entity::update_many()
+      .col_expr(outbox::Column::SentAt, Utc::now().naive_utc().into())
+      .filter(outbox::Column::Id.eq(id))
+      .exec(self.db)
+      .await?;
+    Ok(())
+  }
+
+  /// Retry later with exponential backoff: 1, 2, 4... minutes
+  pub async fn mark_failed(
+    &self,
+    message: outbox::Model,
+    error: &str,
+  ) -> Result<()> {
+    let attempts = message.attempts + 1;
+    let backoff = TimeDelta::minutes(1 << (attempts - 1).min(10));
+    outbox::ActiveModel {
+      attempts: Set(attempts),
+      last_error: Set(Some(error.to_string())),
+      next_attempt_at: Set(Utc::now().naive_utc() + backoff),
+      ..message.into()
+    }
+    .update(self.db)
+    .await?;
+    Ok(())
+  }
+
+  /// Drop sent messages older than `age`
+  pub async fn cleanup(&self, age: TimeDelta) -> Result<u64> {
+    let before = Utc::now().naive_utc() - age;
+    let res = outbox::Entity::delete_many()
+      .filter(outbox::Column::SentAt.lt(before))
+      .exec(self.db)
+      .await?;
+    Ok(res.rows_affected)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::sv::test_utils::test_db;
+
+  #[tokio::test]
+  async fn test_only_committed_messages_are_sent() {
+    let db = test_db::setup().await;
+    let sv = Outbox::new(&db);
+
+    let txn = db.begin().await.unwrap();
+    Outbox::push(&txn, 1, "rolled back".into()).await.unwrap();
+    txn.rollback().await.unwrap();
+
+    let txn = db.begin().await.unwrap();
+    Outbox::push(&txn, 1, "committed".into()).await.unwrap();
+    txn.commit().await.unwrap();
+
+    let due = sv.due(10).await.unwrap();
+    assert_eq!(due.len(), 1);
+    assert_eq!(due[0].text, "committed");
+
+    // A failed send waits for its backoff
+    sv.mark_failed(due[0].clone(), "blocked").await.unwrap();
+    assert!(sv.due(10).await.unwrap().is_empty());
+
+    let id = due[0].id;
+    sv.mark_sent(id).await.unwrap();
+    let sent = outbox::Entity::find_by_id(id).one(&db).await.unwrap().unwrap();
+    assert!(sent.sent_at.is_some());
+    assert_eq!(sent.attempts, 1);
+  }
+}
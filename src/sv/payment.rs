@@ -1,13 +1,27 @@
 use crate::{
-  entity::pending_invoice,
+  entity::{TransactionType, pending_invoice},
   prelude::*,
   sv::{
     balance::Balance,
     cryptobot::{CryptoBot, InvoiceStatus},
+    outbox::Outbox,
     referral::{NANO_USDT, Referral},
   },
 };
 
+/// Description of deposits credited from a CryptoBot invoice, the only link
+/// between the two
+pub const DEPOSIT_PREFIX: &str = "CryptoBot deposit #";
+
+pub fn deposit_description(invoice_id: i64) -> String {
+  format!("{}{}", DEPOSIT_PREFIX, invoice_id)
+}
+
+/// Invoice a deposit was credited from
+pub fn deposit_invoice(description: &str) -> Option<i64> {
+  description.strip_prefix(DEPOSIT_PREFIX)?.parse().ok()
+}
+
 pub struct Payment<'a> {
   db: &'a DatabaseConnection,
 }
@@ -109,29 +123,8 @@ impl<'a> Payment<'a> {
 
       if let Some(inv) = invoice {
         if inv.status == InvoiceStatus::Paid {
-          let balance = Balance::new(self.db);
-          balance
-            .deposit(
-              pending_inv.user_id,
-              pending_inv.amount_nano,
-              Some(format!("CryptoBot deposit #{}", pending_inv.invoice_id)),
-            )
-            .await?;
-
-          if let Some(referrer_id) = pending_inv.referrer_id {
-            let referral = Referral::new(self.db);
-            let _ =
-              referral.record_sale(referrer_id, pending_inv.amount_nano).await;
-          }
-
-          self.delete_pending(pending_inv.invoice_id).await?;
-
-          results.push(PaymentResult {
-            invoice_id: pending_inv.invoice_id,
-            amount_nano: pending_inv.amount_nano,
-            user_id: pending_inv.user_id,
-            referrer_id: pending_inv.referrer_id,
-          });
+          // the user is looking at the result, no message needed
+          results.extend(self.credit(pending_inv, false).await?);
         } else if inv.status == InvoiceStatus::Expired {
           self.delete_pending(pending_inv.invoice_id).await?;
         }
@@ -140,4 +133,111 @@ impl<'a> Payment<'a> {
 
     Ok(results)
   }
+
+  /// Credit an invoice reported paid by the webhook and queue the
+  /// confirmation. None if it is unknown or was already credited.
+  pub async fn process_paid(
+    &self,
+    invoice_id: i64,
+  ) -> Result<Option<PaymentResult>> {
+    match pending_invoice::Entity::find_by_id(invoice_id).one(self.db).await? {
+      Some(pending_inv) => self.credit(pending_inv, true).await,
+      None => Ok(None),
+    }
+  }
+
+  /// Claims the invoice by deleting it in the same transaction as the
+  /// deposit, so the webhook and "Check Payments" racing each other can't
+  /// credit it twice. `notify` queues the confirmation in that transaction,
+  /// the referral commission is paid in it too.
+  async fn credit(
+    &self,
+    pending_inv: pending_invoice::Model,
+    notify: bool,
+  ) -> Result<Option<PaymentResult>> {
+    let txn = self.db.begin().await?;
+    let claimed = pending_invoice::Entity::delete_by_id(pending_inv.invoice_id)
+      .exec(&txn)
+      .await?;
+    if claimed.rows_affected == 0 {
+      return Ok(None);
+    }
+
+    Balance::credit_in(
+      &txn,
+      pending_inv.user_id,
+      pending_inv.amount_nano,
+      TransactionType::Deposit,
+      Some(deposit_description(pending_inv.invoice_id)),
+    )
+    .await?;
+    if notify {
+      let text = format!(
+        "✅ <b>Payment Received!</b>\n\n\
+        <b>{:.2} USDT</b> has been added to your balance.",
+        pending_inv.amount_nano as f64 / NANO_USDT as f64
+      );
+      Outbox::push(&txn, pending_inv.user_id, text).await?;
+    }
+    if let Some(referrer_id) = pending_inv.referrer_id {
+      Referral::pay_commission_in(
+        &txn,
+        referrer_id,
+        Some(pending_inv.user_id),
+        pending_inv.amount_nano,
+      )
+      .await?;
+    }
+    txn.commit().await?;
+
+    Ok(Some(PaymentResult {
+      invoice_id: pending_inv.invoice_id,
+      amount_nano: pending_inv.amount_nano,
+      user_id: pending_inv.user_id,
+      referrer_id: pending_inv.referrer_id,
+    }))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::sv::{self, test_utils::test_db};
+
+  #[tokio::test]
+  async fn test_paid_invoice_credited_once() {
+    let db = test_db::setup().await;
+    let sv = Payment::new(&db);
+    sv::User::new(&db).get_or_create(1).await.unwrap();
+
+    sv.save_pending(77, 1, 5.0, None).await.unwrap();
+
+    let paid = sv.process_paid(77).await.unwrap().unwrap();
+    assert_eq!(paid.amount_nano, 5 * NANO_USDT);
+    assert!(sv.process_paid(77).await.unwrap().is_none());
+    assert!(sv.process_paid(78).await.unwrap().is_none());
+
+    assert_eq!(Balance::new(&db).get(1).await.unwrap(), 5 * NANO_USDT);
+
+    // One confirmation, queued with the deposit
+    let queued = Outbox::new(&db).due(10).await.unwrap();
+    assert_eq!(queued.len(), 1);
+    assert_eq!(queued[0].tg_user_id, 1);
+  }
+
+  #[tokio::test]
+  async fn test_commission_paid_with_deposit() {
+    let db = test_db::setup().await;
+    let sv = Payment::new(&db);
+    let referrer = sv::User::new(&db).get_or_create(2).await.unwrap();
+    sv::User::new(&db).get_or_create(1).await.unwrap();
+
+    sv.save_pending(80, 1, 4.0, Some(2)).await.unwrap();
+    sv.process_paid(80).await.unwrap().unwrap();
+
+    let commission = 4 * NANO_USDT * referrer.commission_rate as i64 / 100;
+    assert_eq!(Balance::new(&db).get(2).await.unwrap(), commission);
+    let queued = Outbox::new(&db).due(10).await.unwrap();
+    assert!(queued.iter().any(|item| item.tg_user_id == 2));
+  }
 }
@@ -0,0 +1,238 @@
+use sea_orm::sea_query::OnConflict;
+
+use crate::{
+  entity::plan,
+  prelude::*,
+  sv::{
+    license::{MAX_SESSIONS, Term},
+    pricing::Plan as Kind,
+    referral::NANO_USDT,
+  },
+};
+
+/// Longest a single plan can run
+pub const MAX_DAYS: i32 = 3650;
+
+/// A setting of a plan that admins can change
+#[derive(Debug, Clone, PartialEq)]
+pub enum PlanField {
+  Title(String),
+  Days(i32),
+  Price(i64),
+  Sessions(i32),
+  Calendar(bool),
+  Active(bool),
+}
+
+impl PlanField {
+  /// Parse `/setplan` arguments, prices are in USDT
+  pub fn parse(field: &str, value: Option<&str>) -> Result<Self> {
+    let invalid = |what: &str| Error::InvalidArgs(format!("Invalid {}", what));
+    match (field, value) {
+      ("on", None) => Ok(PlanField::Active(true)),
+      ("off", None) => Ok(PlanField::Active(false)),
+      ("title", Some(title)) => Ok(PlanField::Title(title.trim().to_string())),
+      ("days", Some(days)) => {
+        days.parse().map(PlanField::Days).map_err(|_| invalid("days"))
+      }
+      ("calendar", Some("on")) => Ok(PlanField::Calendar(true)),
+      ("calendar", Some("off")) => Ok(PlanField::Calendar(false)),
+      ("sessions", Some(max)) => {
+        max.parse().map(PlanField::Sessions).map_err(|_| invalid("sessions"))
+      }
+      ("price", Some(price)) => price
+        .trim_end_matches("USDT")
+        .trim()
+        .parse::<f64>()
+        .ok()
+        .filter(|usdt| usdt.is_finite())
+        .map(|usdt| PlanField::Price((usdt * NANO_USDT as f64).round() as i64))
+        .ok_or_else(|| invalid("price")),
+      _ => Err(Error::InvalidArgs(
+        "Expected price, days, sessions, title or calendar with a value, \
+          or on/off"
+          .into(),
+      )),
+    }
+  }
+}
+
+/// Settings admins may give a plan
+pub fn check(kind: Kind, plan: &plan::Model) -> Result<()> {
+  if plan.title.is_empty() || plan.title.chars().count() > 32 {
+    return Err(Error::InvalidArgs(
+      "Plan title must be 1-32 characters".into(),
+    ));
+  }
+  if !(1..=MAX_DAYS).contains(&plan.days) {
+    return Err(Error::InvalidArgs(format!(
+      "Plan length must be between 1 and {} days",
+      MAX_DAYS
+    )));
+  }
+  if plan.price_nano <= 0 {
+    return Err(Error::InvalidArgs("Plan price must be positive".into()));
+  }
+  if !(1..=MAX_SESSIONS).contains(&plan.max_sessions) {
+    return Err(Error::InvalidArgs(format!(
+      "Session limit must be between 1 and {}",
+      MAX_SESSIONS
+    )));
+  }
+  if plan.calendar && kind.months() == 0 {
+    return Err(Error::InvalidArgs(
+      "Only month-based plans can run for calendar months".into(),
+    ));
+  }
+  Ok(())
+}
+
+/// How long a license bought with the plan runs
+pub fn term(kind: Kind, plan: &plan::Model) -> Term {
+  match kind.months() {
+    months if plan.calendar && months > 0 => Term::Months(months),
+    _ => Term::days(plan.days as u64),
+  }
+}
+
+pub struct Plan<'a> {
+  db: &'a DatabaseConnection,
+}
+
+impl<'a> Plan<'a> {
+  pub fn new(db: &'a DatabaseConnection) -> Self {
+    Self { db }
+  }
+
+  /// Settings the plan is sold with until an admin changes them
+  pub fn defaults(kind: Kind) -> plan::Model {
+    plan::Model {
+      name: kind.as_str().to_string(),
+      title: kind.name().to_string(),
+      days: kind.days() as i32,
+      calendar: false,
+      price_nano: kind.base_price(),
+      max_sessions: 1,
+      active: true,
+      updated_at: Utc::now().naive_utc(),
+    }
+  }
+
+  pub async fn get(&self, kind: Kind) -> Result<plan::Model> {
+    Ok(
+      plan::Entity::find_by_id(kind.as_str())
+        .one(self.db)
+        .await?
+        .unwrap_or_else(|| Self::defaults(kind)),
+    )
+  }
+
+  /// Every plan, including inactive ones
+  pub async fn all(&self) -> Result<Vec<(Kind, plan::Model)>> {
+    let mut rows: HashMap<String, plan::Model> = plan::Entity::find()
+      .all(self.db)
+      .await?
+      .into_iter()
+      .map(|row| (row.name.clone(), row))
+      .collect();
+    Ok(
+      Kind::ALL
+        .into_iter()
+        .map(|kind| {
+          let row = rows.remove(kind.as_str());
+          (kind, row.unwrap_or_else(|| Self::defaults(kind)))
+        })
+        .collect(),
+    )
+  }
+
+  pub async fn set(&self, kind: Kind, field: PlanField) -> Result<plan::Model> {
+    let mut plan = self.get(kind).await?;
+    match field {
+      PlanField::Title(title) => plan.title = title,
+      PlanField::Days(days) => plan.days = days,
+      PlanField::Price(price) => plan.price_nano = price,
+      PlanField::Sessions(max) => plan.max_sessions = max,
+      PlanField::Calendar(calendar) => plan.calendar = calendar,
+      PlanField::Active(active) => plan.active = active,
+    }
+    check(kind, &plan)?;
+    plan.updated_at = Utc::now().naive_utc();
+    Self::save(self.db, &plan).await?;
+    Ok(plan)
+  }
+
+  /// Insert or overwrite the plan, on the caller's connection or transaction
+  pub async fn save<C: ConnectionTrait>(
+    db: &C,
+    plan: &plan::Model,
+  ) -> Result<()> {
+    let model = plan::ActiveModel {
+      name: Set(plan.name.clone()),
+      title: Set(plan.title.clone()),
+      days: Set(plan.days),
+      calendar: Set(plan.calendar),
+      price_nano: Set(plan.price_nano),
+      max_sessions: Set(plan.max_sessions),
+      active: Set(plan.active),
+      updated_at: Set(plan.updated_at),
+    };
+    plan::Entity::insert(model)
+      .on_conflict(
+        OnConflict::column(plan::Column::Name)
+          .update_columns([
+            plan::Column::Title,
+            plan::Column::Days,
+            plan::Column::Calendar,
+            plan::Column::PriceNano,
+            plan::Column::MaxSessions,
+            plan::Column::Active,
+            plan::Column::UpdatedAt,
+          ])
+          .to_owned(),
+      )
+      .exec_without_returning(db)
+      .await?;
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::sv::{self, test_utils::test_db};
+
+  #[tokio::test]
+  async fn test_plan_settings() {
+    let db = test_db::setup().await;
+    let sv = Plan::new(&db);
+    let pricing = sv::Pricing::new(&db);
+
+    let month = sv.get(Kind::Month).await.unwrap();
+    assert_eq!(month.price_nano, Kind::Month.base_price());
+    assert_eq!(sv.all().await.unwrap().len(), Kind::ALL.len());
+
+    let price = PlanField::parse("price", Some("12.5")).unwrap();
+    assert_eq!(price, PlanField::Price(12_500_000));
+    sv.set(Kind::Month, price).await.unwrap();
+    sv.set(Kind::Month, PlanField::Sessions(3)).await.unwrap();
+    assert!(sv.set(Kind::Month, PlanField::Days(0)).await.is_err());
+
+    let quote = pricing.quote(1, sv::product::DEFAULT, Kind::Month).await;
+    let quote = quote.unwrap();
+    assert_eq!(quote.base, 12_500_000);
+    assert_eq!((quote.term, quote.max_sessions), (Term::days(30), 3));
+
+    let calendar = PlanField::parse("calendar", Some("on")).unwrap();
+    sv.set(Kind::Month, calendar.clone()).await.unwrap();
+    assert!(sv.set(Kind::Trial, calendar).await.is_err());
+    let quote = pricing.quote(1, sv::product::DEFAULT, Kind::Month).await;
+    assert_eq!(quote.unwrap().term, Term::Months(1));
+
+    sv.set(Kind::Month, PlanField::Active(false)).await.unwrap();
+    assert!(matches!(
+      pricing.quote(1, sv::product::DEFAULT, Kind::Month).await,
+      Err(Error::PlanUnavailable(_))
+    ));
+  }
+}
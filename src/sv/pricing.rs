@@ -0,0 +1,672 @@
+use serde::Serialize;
+
+use crate::{
+  entity::{
+    license, license_device, product, region_price, sale, user_settings,
+  },
+  prelude::*,
+  sv::{
+    self,
+    license::Term,
+    referral::{MONTH_PRICE, NANO_USDT, QUARTER_PRICE},
+  },
+};
+
+/// Extension discounts by length in months, longest first
+pub const VOLUME_TIERS: [(u32, i32); 2] = [(12, 25), (6, 15)];
+
+/// Plans sold for balance. Title, length and price here are the defaults,
+/// admins override them in the `plans` table
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Plan {
+  Trial,
+  Month,
+  Quarter,
+  HalfYear,
+  Year,
+  Night,
+}
+
+impl Plan {
+  pub const ALL: [Plan; 6] = [
+    Plan::Trial,
+    Plan::Month,
+    Plan::Quarter,
+    Plan::HalfYear,
+    Plan::Year,
+    Plan::Night,
+  ];
+  /// Offered for new licenses, longer ones are extension-only
+  pub const PURCHASE: [Plan; 4] =
+    [Plan::Trial, Plan::Month, Plan::Quarter, Plan::Night];
+
+  pub fn parse(s: &str) -> Option<Self> {
+    Self::ALL.into_iter().find(|plan| plan.as_str() == s)
+  }
+
+  pub fn as_str(self) -> &'static str {
+    match self {
+      Plan::Trial => "trial",
+      Plan::Month => "month",
+      Plan::Quarter => "quarter",
+      Plan::HalfYear => "halfyear",
+      Plan::Year => "year",
+      Plan::Night => "night",
+    }
+  }
+
+  pub fn name(self) -> &'static str {
+    match self {
+      Plan::Trial => "1 Day Trial",
+      Plan::Month => "1 Month",
+      Plan::Quarter => "3 Months",
+      Plan::HalfYear => "6 Months",
+      Plan::Year => "12 Months",
+      Plan::Night => "Night Month",
+    }
+  }
+
+  pub fn days(self) -> u64 {
+    match self {
+      Plan::Trial => 1,
+      Plan::Month | Plan::Night => 30,
+      Plan::Quarter => 90,
+      Plan::HalfYear => 180,
+      Plan::Year => 365,
+    }
+  }
+
+  pub fn months(self) -> u32 {
+    match self {
+      Plan::Trial => 0,
+      Plan::Month | Plan::Night => 1,
+      Plan::Quarter => 3,
+      Plan::HalfYear => 6,
+      Plan::Year => 12,
+    }
+  }
+
+  pub fn base_price(self) -> i64 {
+    match self {
+      Plan::Trial => NANO_USDT,
+      Plan::Month => MONTH_PRICE,
+      Plan::Quarter => QUARTER_PRICE,
+      Plan::HalfYear => 6 * MONTH_PRICE,
+      Plan::Year => 12 * MONTH_PRICE,
+      Plan::Night => 6 * NANO_USDT,
+    }
+  }
+
+  /// Trials are never discounted and pay no referral commission
+  pub fn is_trial(self) -> bool {
+    self == Plan::Trial
+  }
+
+  /// Local hours the plan is restricted to
+  pub fn schedule(self) -> Option<(i32, i32)> {
+    match self {
+      Plan::Night => Some((20, 8)),
+      _ => None,
+    }
+  }
+
+  /// Whether regional prices apply to the plan
+  pub fn is_regional(self) -> bool {
+    self == Plan::Month
+  }
+}
+
+/// Final price of a plan for a particular user
+#[derive(Debug, Clone)]
+pub struct Quote {
+  pub plan: Plan,
+  /// Title, length and session limit the plan is currently sold with
+  pub title: String,
+  /// Length, calendar months on calendar plans
+  pub term: Term,
+  pub max_sessions: i32,
+  pub product: product::Model,
+  pub base: i64,
+  pub price: i64,
+  pub referral_percent: i32,
+  /// Length discount, extensions only
+  pub volume_percent: i32,
+  /// Happy hour running when the quote was made, extensions only
+  pub sale: Option<sale::Model>,
+  /// One-time starter discount of a trial user, spent by the purchase
+  pub offer_percent: i32,
+  /// Regional price that was applied, the license gets bound to it
+  pub region: Option<region_price::Model>,
+}
+
+impl Quote {
+  pub fn region_code(&self) -> Option<String> {
+    self.region.as_ref().map(|r| r.country.clone())
+  }
+}
+
+/// Public price list for the website, without per-user discounts
+#[derive(Debug, Clone, Serialize)]
+pub struct Catalog {
+  pub currency: &'static str,
+  pub products: Vec<CatalogProduct>,
+  /// Sales running now or scheduled, they discount extensions
+  pub sales: Vec<CatalogSale>,
+  pub volume_tiers: Vec<VolumeTier>,
+  /// Percent of the price the Month plan costs in these countries
+  pub regions: Vec<CatalogRegion>,
+  pub generated_at: DateTime,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CatalogProduct {
+  pub slug: String,
+  pub name: String,
+  pub plans: Vec<CatalogPlan>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CatalogPlan {
+  pub plan: &'static str,
+  pub title: String,
+  pub days: i32,
+  pub max_sessions: i32,
+  pub price_nano: i64,
+  pub price: f64,
+  /// Only sold as an extension of an existing key
+  pub extension_only: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CatalogSale {
+  pub name: String,
+  pub percent: i32,
+  pub starts_at: DateTime,
+  pub ends_at: DateTime,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct VolumeTier {
+  pub min_months: u32,
+  pub percent: i32,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CatalogRegion {
+  pub country: String,
+  pub percent: i32,
+}
+
+/// Plans a license can be extended with, shortest first
+pub fn extension_plans(license: &license::Model) -> &'static [Plan] {
+  if license.allowed_from_hour.is_some() {
+    &[Plan::Night]
+  } else {
+    &[Plan::Month, Plan::Quarter, Plan::HalfYear, Plan::Year]
+  }
+}
+
+/// Discount for extending by `months` at once
+pub fn volume_percent(months: u32) -> i32 {
+  VOLUME_TIERS
+    .iter()
+    .find(|(min, _)| months >= *min)
+    .map_or(0, |(_, percent)| *percent)
+}
+
+/// Upper-cased ISO country code, `None` if it does not look like one
+pub fn normalize_country(code: &str) -> Option<String> {
+  let code = code.trim();
+  (code.len() == 2 && code.chars().all(|c| c.is_ascii_alphabetic()))
+    .then(|| code.to_ascii_uppercase())
+}
+
+/// A regionally priced key only works from the country it was sold for.
+/// Connections without a known country can't prove it and are refused.
+pub fn check_region(
+  license: &license::Model,
+  country: Option<&str>,
+) -> Result<()> {
+  match (&license.region, country) {
+    (Some(region), Some(country)) if region.eq_ignore_ascii_case(country) => {
+      Ok(())
+    }
+    (Some(region), _) => Err(Error::RegionMismatch(region.clone())),
+    (None, _) => Ok(()),
+  }
+}
+
+pub struct Pricing<'a> {
+  db: &'a DatabaseConnection,
+}
+
+impl<'a> Pricing<'a> {
+  pub fn new(db: &'a DatabaseConnection) -> Self {
+    Self { db }
+  }
+
+  /// Apply referral and regional discounts to the base price of `plan`
+  /// of `product`
+  pub async fn quote(
+    &self,
+    tg_user_id: i64,
+    product: &str,
+    plan: Plan,
+  ) -> Result<Quote> {
+    self.quote_with(tg_user_id, product, plan, false).await
+  }
+
+  /// Same as `quote`, plus volume tiers and a running happy hour
+  pub async fn quote_extension(
+    &self,
+    tg_user_id: i64,
+    product: &str,
+    plan: Plan,
+  ) -> Result<Quote> {
+    self.quote_with(tg_user_id, product, plan, true).await
+  }
+
+  async fn quote_with(
+    &self,
+    tg_user_id: i64,
+    product: &str,
+    plan: Plan,
+    extension: bool,
+  ) -> Result<Quote> {
+    let settings = sv::Plan::new(self.db).get(plan).await?;
+    if !settings.active {
+      return Err(Error::PlanUnavailable(settings.title));
+    }
+    let product = sv::Product::new(self.db).get(product).await?;
+    let base = settings.price_nano * product.price_percent as i64 / 100;
+    let term = sv::plan::term(plan, &settings);
+    let (title, max_sessions) = (settings.title, settings.max_sessions);
+    if plan.is_trial() {
+      return Ok(Quote {
+        plan,
+        title,
+        term,
+        max_sessions,
+        product,
+        base,
+        price: base,
+        referral_percent: 0,
+        volume_percent: 0,
+        sale: None,
+        offer_percent: 0,
+        region: None,
+      });
+    }
+
+    let referred_by = sv::User::new(self.db)
+      .by_id(tg_user_id)
+      .await?
+      .and_then(|u| u.referred_by);
+    let referral_percent =
+      sv::Referral::new(self.db).discount_percent(referred_by).await;
+
+    let region = match plan.is_regional() {
+      true => self.region_of(tg_user_id).await?,
+      false => None,
+    };
+
+    let (volume_percent, sale) = match extension {
+      true => (volume_percent(plan.months()), self.active_sale().await?),
+      false => (0, None),
+    };
+
+    let offer_percent = sv::UpgradeOffers::new(self.db)
+      .active(tg_user_id)
+      .await?
+      .map_or(0, |offer| offer.percent);
+
+    let mut price = base * (100 - referral_percent) as i64 / 100;
+    price = price * (100 - volume_percent) as i64 / 100;
+    if let Some(sale) = &sale {
+      price = price * (100 - sale.percent) as i64 / 100;
+    }
+    price = price * (100 - offer_percent) as i64 / 100;
+    if let Some(region) = &region {
+      price = price * region.percent as i64 / 100;
+    }
+
+    Ok(Quote {
+      plan,
+      title,
+      term,
+      max_sessions,
+      product,
+      base,
+      price,
+      referral_percent,
+      volume_percent,
+      sale,
+      offer_percent,
+      region,
+    })
+  }
+
+  /// Biggest happy-hour discount running right now
+  pub async fn active_sale(&self) -> Result<Option<sale::Model>> {
+    let now = Utc::now().naive_utc();
+    Ok(
+      sale::Entity::find()
+        .filter(sale::Column::StartsAt.lte(now))
+        .filter(sale::Column::EndsAt.gt(now))
+        .order_by_desc(sale::Column::Percent)
+        .one(self.db)
+        .await?,
+    )
+  }
+
+  /// Start a happy hour now, lasting `duration`
+  pub async fn start_sale(
+    &self,
+    name: &str,
+    percent: i32,
+    duration: TimeDelta,
+  ) -> Result<sale::Model> {
+    if !(1..100).contains(&percent) {
+      return Err(Error::InvalidArgs(
+        "Sale discount must be between 1% and 99%".into(),
+      ));
+    }
+
+    let now = Utc::now().naive_utc();
+    let sale = sale::ActiveModel {
+      id: NotSet,
+      name: Set(name.to_string()),
+      percent: Set(percent),
+      starts_at: Set(now),
+      ends_at: Set(now + duration),
+      created_at: Set(now),
+    };
+    Ok(sale.insert(self.db).await?)
+  }
+
+  /// End a sale early
+  pub async fn stop_sale(&self, id: i32) -> Result<()> {
+    let sale = sale::Entity::find_by_id(id)
+      .one(self.db)
+      .await?
+      .ok_or_else(|| Error::InvalidArgs(format!("Sale #{} not found", id)))?;
+
+    let now = Utc::now().naive_utc();
+    sale::ActiveModel { ends_at: Set(now.min(sale.ends_at)), ..sale.into() }
+      .update(self.db)
+      .await?;
+    Ok(())
+  }
+
+  /// Sales that have not ended yet
+  pub async fn upcoming_sales(&self) -> Result<Vec<sale::Model>> {
+    let now = Utc::now().naive_utc();
+    Ok(
+      sale::Entity::find()
+        .filter(sale::Column::EndsAt.gt(now))
+        .order_by_asc(sale::Column::StartsAt)
+        .all(self.db)
+        .await?,
+    )
+  }
+
+  /// Self-declared country, or the one the user's panels last connected from
+  pub async fn country(&self, tg_user_id: i64) -> Result<Option<String>> {
+    let declared = user_settings::Entity::find_by_id(tg_user_id)
+      .one(self.db)
+      .await?
+      .and_then(|s| s.country);
+    if declared.is_some() {
+      return Ok(declared);
+    }
+
+    let keys: Vec<String> = license::Entity::find()
+      .filter(license::Column::TgUserId.eq(tg_user_id))
+      .all(self.db)
+      .await?
+      .into_iter()
+      .map(|l| l.key)
+      .collect();
+    if keys.is_empty() {
+      return Ok(None);
+    }
+
+    Ok(
+      license_device::Entity::find()
+        .filter(license_device::Column::LicenseKey.is_in(keys))
+        .filter(license_device::Column::Country.is_not_null())
+        .order_by_desc(license_device::Column::LastSeen)
+        .one(self.db)
+        .await?
+        .and_then(|d| d.country),
+    )
+  }
+
+  async fn region_of(
+    &self,
+    tg_user_id: i64,
+  ) -> Result<Option<region_price::Model>> {
+    let Some(country) = self.country(tg_user_id).await? else {
+      return Ok(None);
+    };
+    Ok(region_price::Entity::find_by_id(country).one(self.db).await?)
+  }
+
+  pub async fn set_region(
+    &self,
+    country: &str,
+    percent: i32,
+  ) -> Result<region_price::Model> {
+    let country = normalize_country(country)
+      .ok_or_else(|| Error::InvalidArgs("Invalid country code".into()))?;
+    if !(1..100).contains(&percent) {
+      return Err(Error::InvalidArgs(
+        "Regional price must be between 1% and 99%".into(),
+      ));
+    }
+
+    let now = Utc::now().naive_utc();
+    if let Some(existing) =
+      region_price::Entity::find_by_id(&country).one(self.db).await?
+    {
+      return Ok(
+        region_price::ActiveModel { percent: Set(percent), ..existing.into() }
+          .update(self.db)
+          .await?,
+      );
+    }
+
+    let region = region_price::ActiveModel {
+      country: Set(country),
+      percent: Set(percent),
+      created_at: Set(now),
+    };
+    Ok(region.insert(self.db).await?)
+  }
+
+  pub async fn remove_region(&self, country: &str) -> Result<()> {
+    let country = country.trim().to_ascii_uppercase();
+    let res =
+      region_price::Entity::delete_by_id(&country).exec(self.db).await?;
+    if res.rows_affected == 0 {
+      return Err(Error::InvalidArgs(format!(
+        "No regional price for {}",
+        country
+      )));
+    }
+    Ok(())
+  }
+
+  pub async fn regions(&self) -> Result<Vec<region_price::Model>> {
+    Ok(
+      region_price::Entity::find()
+        .order_by_asc(region_price::Column::Country)
+        .all(self.db)
+        .await?,
+    )
+  }
+
+  /// Active plans of every product at their list prices
+  pub async fn catalog(&self) -> Result<Catalog> {
+    let plans = sv::Plan::new(self.db).all().await?;
+    let products = sv::Product::new(self.db)
+      .all()
+      .await?
+      .into_iter()
+      .map(|product| CatalogProduct {
+        plans: plans
+          .iter()
+          .filter(|(_, settings)| settings.active)
+          .map(|(plan, settings)| {
+            let price_nano =
+              settings.price_nano * product.price_percent as i64 / 100;
+            CatalogPlan {
+              plan: plan.as_str(),
+              title: settings.title.clone(),
+              days: settings.days,
+              max_sessions: settings.max_sessions,
+              price_nano,
+              price: price_nano as f64 / NANO_USDT as f64,
+              extension_only: !Plan::PURCHASE.contains(plan),
+            }
+          })
+          .collect(),
+        slug: product.slug,
+        name: product.name,
+      })
+      .collect();
+
+    let sales = self
+      .upcoming_sales()
+      .await?
+      .into_iter()
+      .map(|sale| CatalogSale {
+        name: sale.name,
+        percent: sale.percent,
+        starts_at: sale.starts_at,
+        ends_at: sale.ends_at,
+      })
+      .collect();
+    let regions = self
+      .regions()
+      .await?
+      .into_iter()
+      .map(|region| CatalogRegion {
+        country: region.country,
+        percent: region.percent,
+      })
+      .collect();
+    let volume_tiers = VOLUME_TIERS
+      .iter()
+      .map(|&(min_months, percent)| VolumeTier { min_months, percent })
+      .collect();
+
+    Ok(Catalog {
+      currency: "USDT",
+      products,
+      sales,
+      volume_tiers,
+      regions,
+      generated_at: Utc::now().naive_utc(),
+    })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{
+    entity::LicenseType,
+    sv::{product::DEFAULT, test_utils::test_db},
+  };
+
+  #[tokio::test]
+  async fn test_regional_quote() {
+    let db = test_db::setup().await;
+    let sv = Pricing::new(&db);
+
+    sv.set_region("in", 50).await.unwrap();
+    let license =
+      sv::License::new(&db).create(1, LicenseType::Pro, 30).await.unwrap();
+
+    // no known country yet
+    let quote = sv.quote(1, DEFAULT, Plan::Month).await.unwrap();
+    assert_eq!(quote.price, MONTH_PRICE);
+
+    sv::Settings::new(&db).set_country(1, Some("IN".into())).await.unwrap();
+    let quote = sv.quote(1, DEFAULT, Plan::Month).await.unwrap();
+    assert_eq!(quote.price, MONTH_PRICE / 2);
+    assert_eq!(quote.region_code().as_deref(), Some("IN"));
+
+    // only the month plan is regional
+    let quote = sv.quote(1, DEFAULT, Plan::Quarter).await.unwrap();
+    assert_eq!(quote.price, QUARTER_PRICE);
+
+    let bound = sv::License::new(&db)
+      .set_region(&license.key, Some("IN".into()))
+      .await
+      .unwrap();
+    assert!(check_region(&bound, Some("in")).is_ok());
+    assert!(matches!(
+      check_region(&bound, Some("DE")),
+      Err(Error::RegionMismatch(_))
+    ));
+    // a stripped or unknown country is no way around it
+    assert!(matches!(
+      check_region(&bound, None),
+      Err(Error::RegionMismatch(_))
+    ));
+    assert!(check_region(&license, None).is_ok());
+  }
+
+  #[tokio::test]
+  async fn test_extension_discounts() {
+    let db = test_db::setup().await;
+    let sv = Pricing::new(&db);
+
+    assert_eq!(volume_percent(3), 0);
+    assert_eq!(volume_percent(6), 15);
+    assert_eq!(volume_percent(12), 25);
+
+    // volume tiers only apply to extensions
+    let quote = sv.quote(1, DEFAULT, Plan::HalfYear).await.unwrap();
+    assert_eq!(quote.price, 6 * MONTH_PRICE);
+    let quote = sv.quote_extension(1, DEFAULT, Plan::HalfYear).await.unwrap();
+    assert_eq!(quote.price, 6 * MONTH_PRICE * 85 / 100);
+    assert_eq!(quote.volume_percent, 15);
+
+    let sale = sv.start_sale("Happy hour", 20, TimeDelta::hours(2)).await;
+    let sale = sale.unwrap();
+    let quote = sv.quote_extension(1, DEFAULT, Plan::Month).await.unwrap();
+    assert_eq!(quote.price, MONTH_PRICE * 80 / 100);
+    assert_eq!(quote.sale.map(|s| s.id), Some(sale.id));
+
+    sv.stop_sale(sale.id).await.unwrap();
+    assert!(sv.active_sale().await.unwrap().is_none());
+    let quote = sv.quote_extension(1, DEFAULT, Plan::Month).await.unwrap();
+    assert_eq!(quote.price, MONTH_PRICE);
+  }
+
+  #[tokio::test]
+  async fn test_catalog() {
+    let db = test_db::setup().await;
+    let sv = Pricing::new(&db);
+
+    sv::Plan::new(&db)
+      .set(Plan::Trial, sv::plan::PlanField::Active(false))
+      .await
+      .unwrap();
+    sv.start_sale("Happy hour", 20, TimeDelta::hours(2)).await.unwrap();
+
+    let catalog = sv.catalog().await.unwrap();
+    let product = catalog.products.iter().find(|p| p.slug == DEFAULT).unwrap();
+    assert!(product.plans.iter().all(|p| p.plan != "trial"));
+    let month = product.plans.iter().find(|p| p.plan == "month").unwrap();
+    assert_eq!(month.price_nano, MONTH_PRICE);
+    assert!(!month.extension_only);
+    let year = product.plans.iter().find(|p| p.plan == "year").unwrap();
+    assert!(year.extension_only);
+    assert_eq!(catalog.sales.len(), 1);
+    assert_eq!(catalog.volume_tiers.len(), VOLUME_TIERS.len());
+  }
+}
@@ -0,0 +1,121 @@
+use crate::{entity::product, prelude::*};
+
+/// Product everything belonged to before products existed
+pub const DEFAULT: &str = "default";
+
+const MAX_SLUG_LEN: usize = 16;
+
+pub struct Product<'a> {
+  db: &'a DatabaseConnection,
+}
+
+impl<'a> Product<'a> {
+  pub fn new(db: &'a DatabaseConnection) -> Self {
+    Self { db }
+  }
+
+  pub async fn all(&self) -> Result<Vec<product::Model>> {
+    Ok(
+      product::Entity::find()
+        .order_by_asc(product::Column::CreatedAt)
+        .all(self.db)
+        .await?,
+    )
+  }
+
+  pub async fn get(&self, slug: &str) -> Result<product::Model> {
+    product::Entity::find_by_id(slug)
+      .one(self.db)
+      .await?
+      .ok_or_else(|| Error::ProductNotFound(slug.to_string()))
+  }
+
+  /// Create a product or update its name and price
+  pub async fn upsert(
+    &self,
+    slug: &str,
+    name: &str,
+    price_percent: i32,
+  ) -> Result<product::Model> {
+    let slug = slug.trim().to_lowercase();
+    let valid = !slug.is_empty()
+      && slug.len() <= MAX_SLUG_LEN
+      && slug.chars().all(|c| c.is_ascii_alphanumeric() || c == '-');
+    if !valid {
+      return Err(Error::InvalidArgs(format!(
+        "Product id must be 1-{} letters, digits or '-'",
+        MAX_SLUG_LEN
+      )));
+    }
+    if !(1..=1000).contains(&price_percent) {
+      return Err(Error::InvalidArgs(
+        "Price must be between 1% and 1000% of the base plans".into(),
+      ));
+    }
+    let name = name.trim();
+    if name.is_empty() {
+      return Err(Error::InvalidArgs("Product name is empty".into()));
+    }
+
+    let model = match product::Entity::find_by_id(&slug).one(self.db).await? {
+      Some(existing) => {
+        product::ActiveModel {
+          name: Set(name.to_string()),
+          price_percent: Set(price_percent),
+          ..existing.into()
+        }
+        .update(self.db)
+        .await?
+      }
+      None => {
+        product::ActiveModel {
+          slug: Set(slug),
+          name: Set(name.to_string()),
+          price_percent: Set(price_percent),
+          created_at: Set(Utc::now().naive_utc()),
+        }
+        .insert(self.db)
+        .await?
+      }
+    };
+    Ok(model)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{
+    entity::LicenseType,
+    sv::{self, pricing::Plan, referral::MONTH_PRICE, test_utils::test_db},
+  };
+
+  #[tokio::test]
+  async fn test_products_priced_separately() {
+    let db = test_db::setup().await;
+    let sv = Product::new(&db);
+
+    let tool = sv.upsert("Tool", "Second tool", 50).await.unwrap();
+    assert_eq!(tool.slug, "tool");
+    assert!(sv.upsert("bad slug", "x", 100).await.is_err());
+    assert_eq!(sv.all().await.unwrap().len(), 2);
+
+    let pricing = sv::Pricing::new(&db);
+    let quote = pricing.quote(1, "tool", Plan::Month).await.unwrap();
+    assert_eq!(quote.price, MONTH_PRICE / 2);
+    let quote = pricing.quote(1, DEFAULT, Plan::Month).await.unwrap();
+    assert_eq!(quote.price, MONTH_PRICE);
+    assert!(matches!(
+      pricing.quote(1, "nope", Plan::Month).await,
+      Err(Error::ProductNotFound(_))
+    ));
+
+    let license = sv::License::new(&db);
+    let key = license.create(1, LicenseType::Pro, 30).await.unwrap().key;
+    assert_eq!(
+      license.set_product(&key, "tool").await.unwrap().product,
+      "tool"
+    );
+    assert!(license.set_product(&key, "nope").await.is_err());
+  }
+}
@@ -0,0 +1,225 @@
+use crate::entity::{LicenseType, promo, promo_campaign};
+pub use crate::prelude::*;
+
+pub struct PromoCampaign<'a> {
+  db: &'a DatabaseConnection,
+}
+
+impl<'a> PromoCampaign<'a> {
+  pub fn new(db: &'a DatabaseConnection) -> Self {
+    Self { db }
+  }
+
+  /// Codes are case-insensitive, users type them by hand
+  pub fn normalize(code: &str) -> String {
+    code.trim().to_lowercase()
+  }
+
+  /// Codes of campaigns and giveaway codes alike, normalized
+  pub fn check_code(code: &str) -> Result<()> {
+    if code.is_empty()
+      || !code
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+    {
+      return Err(Error::InvalidArgs(
+        "Promo code may only contain letters, digits, '_' and '-'".into(),
+      ));
+    }
+    Ok(())
+  }
+
+  pub async fn create(
+    &self,
+    code: &str,
+    license_type: LicenseType,
+    days: i32,
+    max_claims: Option<i32>,
+    starts_at: DateTime,
+    duration: TimeDelta,
+  ) -> Result<promo_campaign::Model> {
+    let code = Self::normalize(code);
+    Self::check_code(&code)?;
+    if days <= 0 {
+      return Err(Error::InvalidArgs("Days must be positive".into()));
+    }
+    if max_claims.is_some_and(|max| max <= 0) {
+      return Err(Error::InvalidArgs("Max claims must be positive".into()));
+    }
+    if self.get(&code).await?.is_some() {
+      return Err(Error::Promo(Promo::Exists));
+    }
+
+    let campaign = promo_campaign::ActiveModel {
+      code: Set(code),
+      license_type: Set(license_type),
+      days: Set(days),
+      max_claims: Set(max_claims),
+      starts_at: Set(starts_at),
+      ends_at: Set(starts_at + duration),
+      created_at: Set(Utc::now().naive_utc()),
+    };
+    Ok(campaign.insert(self.db).await?)
+  }
+
+  pub async fn get(&self, code: &str) -> Result<Option<promo_campaign::Model>> {
+    Ok(
+      promo_campaign::Entity::find_by_id(Self::normalize(code))
+        .one(self.db)
+        .await?,
+    )
+  }
+
+  /// Newest campaigns first
+  pub async fn all(&self) -> Result<Vec<promo_campaign::Model>> {
+    Ok(
+      promo_campaign::Entity::find()
+        .order_by_desc(promo_campaign::Column::StartsAt)
+        .all(self.db)
+        .await?,
+    )
+  }
+
+  pub async fn claims(&self, code: &str) -> Result<u64> {
+    Ok(
+      promo::Entity::find()
+        .filter(promo::Column::PromoName.eq(Self::normalize(code)))
+        .count(self.db)
+        .await?,
+    )
+  }
+
+  /// Running and not exhausted, so the code can be redeemed right now
+  pub async fn is_active(&self, code: &str) -> bool {
+    match self.redeemable(code).await {
+      Ok(_) => true,
+      Err(Error::Promo(_)) => false,
+      Err(e) => {
+        warn!("Failed to check promo {}: {}", code, e);
+        false
+      }
+    }
+  }
+
+  /// Campaign if it may be claimed, otherwise why not
+  pub async fn redeemable(&self, code: &str) -> Result<promo_campaign::Model> {
+    let campaign =
+      self.get(code).await?.ok_or(Error::Promo(Promo::NotFound))?;
+    if !campaign.is_running(Utc::now().naive_utc()) {
+      return Err(Error::Promo(Promo::Inactive));
+    }
+    if let Some(max) = campaign.max_claims
+      && self.claims(&campaign.code).await? >= max as u64
+    {
+      return Err(Error::Promo(Promo::Exhausted));
+    }
+    Ok(campaign)
+  }
+
+  /// Move the campaign window, e.g. to run the free trial week again.
+  /// Users who already claimed it can't claim again.
+  pub async fn reschedule(
+    &self,
+    code: &str,
+    starts_at: DateTime,
+    duration: TimeDelta,
+  ) -> Result<promo_campaign::Model> {
+    let campaign =
+      self.get(code).await?.ok_or(Error::Promo(Promo::NotFound))?;
+    Ok(
+      promo_campaign::ActiveModel {
+        starts_at: Set(starts_at),
+        ends_at: Set(starts_at + duration),
+        ..campaign.into()
+      }
+      .update(self.db)
+      .await?,
+    )
+  }
+
+  /// End the campaign now, claimed licenses stay valid
+  pub async fn stop(&self, code: &str) -> Result<promo_campaign::Model> {
+    let campaign =
+      self.get(code).await?.ok_or(Error::Promo(Promo::NotFound))?;
+    let now = Utc::now().naive_utc();
+    Ok(
+      promo_campaign::ActiveModel {
+        ends_at: Set(campaign.ends_at.min(now)),
+        ..campaign.into()
+      }
+      .update(self.db)
+      .await?,
+    )
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::sv::{self, test_utils::test_db};
+
+  #[tokio::test]
+  async fn test_campaign_claims() {
+    let db = test_db::setup().await;
+    let sv = PromoCampaign::new(&db);
+    let license = sv::License::new(&db);
+    let now = Utc::now().naive_utc();
+
+    let campaign = sv
+      .create(
+        "Spring-24",
+        LicenseType::Pro,
+        3,
+        Some(1),
+        now,
+        TimeDelta::days(7),
+      )
+      .await
+      .unwrap();
+    assert_eq!(campaign.code, "spring-24");
+    assert!(matches!(
+      sv.create(
+        "spring-24",
+        LicenseType::Trial,
+        1,
+        None,
+        now,
+        TimeDelta::days(1)
+      )
+      .await,
+      Err(Error::Promo(Promo::Exists))
+    ));
+    assert!(sv.is_active("SPRING-24").await);
+
+    let claimed = license.claim_promo(1, "SPRING-24").await.unwrap();
+    assert_eq!(claimed.license_type, LicenseType::Pro);
+    assert!(claimed.expires_at > now + TimeDelta::days(2));
+    assert!(matches!(
+      license.claim_promo(1, "spring-24").await,
+      Err(Error::Promo(Promo::Claimed))
+    ));
+    assert!(matches!(
+      license.claim_promo(2, "spring-24").await,
+      Err(Error::Promo(Promo::Exhausted))
+    ));
+    assert!(!sv.is_active("spring-24").await);
+    assert!(matches!(
+      license.claim_promo(2, "nope").await,
+      Err(Error::Promo(Promo::NotFound))
+    ));
+
+    let later = now + TimeDelta::days(1);
+    sv.create("later", LicenseType::Trial, 7, None, later, TimeDelta::days(1))
+      .await
+      .unwrap();
+    assert!(matches!(
+      license.claim_promo(2, "later").await,
+      Err(Error::Promo(Promo::Inactive))
+    ));
+
+    sv.reschedule("later", now, TimeDelta::days(1)).await.unwrap();
+    assert!(sv.is_active("later").await);
+    sv.stop("later").await.unwrap();
+    assert!(!sv.is_active("later").await);
+  }
+}
@@ -0,0 +1,203 @@
+pub use crate::prelude::*;
+use crate::{
+  entity::{
+    LicenseType, TransactionType, license, promo,
+    promo_code::{self, PromoGrant},
+  },
+  sv,
+};
+
+/// Claims of promo codes are kept apart from campaign claims by this prefix
+const CLAIM_PREFIX: &str = "code:";
+
+/// What a redeemed code granted
+pub enum Redeemed {
+  /// New balance in nanoUSDT
+  Balance {
+    amount: i64,
+    balance: i64,
+  },
+  License(license::Model),
+}
+
+pub struct PromoCode<'a> {
+  db: &'a DatabaseConnection,
+}
+
+impl<'a> PromoCode<'a> {
+  pub fn new(db: &'a DatabaseConnection) -> Self {
+    Self { db }
+  }
+
+  fn claim_name(code: &str) -> String {
+    format!("{}{}", CLAIM_PREFIX, code)
+  }
+
+  pub async fn create(
+    &self,
+    code: &str,
+    grant: PromoGrant,
+    amount: i64,
+    max_uses: Option<i32>,
+    expires_at: Option<DateTime>,
+  ) -> Result<promo_code::Model> {
+    let code = sv::PromoCampaign::normalize(code);
+    sv::PromoCampaign::check_code(&code)?;
+    if amount <= 0 {
+      return Err(Error::InvalidArgs("Amount must be positive".into()));
+    }
+    if max_uses.is_some_and(|max| max <= 0) {
+      return Err(Error::InvalidArgs("Max uses must be positive".into()));
+    }
+    if self.get(&code).await?.is_some() {
+      return Err(Error::Promo(Promo::Exists));
+    }
+
+    let promo_code = promo_code::ActiveModel {
+      code: Set(code),
+      grant: Set(grant),
+      amount: Set(amount),
+      max_uses: Set(max_uses),
+      expires_at: Set(expires_at),
+      created_at: Set(Utc::now().naive_utc()),
+    };
+    Ok(promo_code.insert(self.db).await?)
+  }
+
+  pub async fn get(&self, code: &str) -> Result<Option<promo_code::Model>> {
+    Ok(
+      promo_code::Entity::find_by_id(sv::PromoCampaign::normalize(code))
+        .one(self.db)
+        .await?,
+    )
+  }
+
+  /// Newest codes first
+  pub async fn all(&self) -> Result<Vec<promo_code::Model>> {
+    Ok(
+      promo_code::Entity::find()
+        .order_by_desc(promo_code::Column::CreatedAt)
+        .all(self.db)
+        .await?,
+    )
+  }
+
+  /// Delete the code, what it granted stays with users
+  pub async fn remove(&self, code: &str) -> Result<()> {
+    let result =
+      promo_code::Entity::delete_by_id(sv::PromoCampaign::normalize(code))
+        .exec(self.db)
+        .await?;
+    if result.rows_affected == 0 {
+      return Err(Error::Promo(Promo::NotFound));
+    }
+    Ok(())
+  }
+
+  pub async fn uses(&self, code: &str) -> Result<u64> {
+    Ok(
+      promo::Entity::find()
+        .filter(promo::Column::PromoName.eq(Self::claim_name(code)))
+        .count(self.db)
+        .await?,
+    )
+  }
+
+  /// Grant the code once per user
+  pub async fn redeem(&self, tg_user_id: i64, code: &str) -> Result<Redeemed> {
+    let code = self.get(code).await?.ok_or(Error::Promo(Promo::NotFound))?;
+    let claim_name = Self::claim_name(&code.code);
+
+    sv::User::new(self.db).get_or_create(tg_user_id).await?;
+    if sv::License::new(self.db).has_claimed(tg_user_id, &claim_name).await? {
+      return Err(Error::Promo(Promo::Claimed));
+    }
+    let now = Utc::now().naive_utc();
+    if code.expires_at.is_some_and(|expires_at| expires_at < now) {
+      return Err(Error::Promo(Promo::Inactive));
+    }
+    if let Some(max) = code.max_uses
+      && self.uses(&code.code).await? >= max as u64
+    {
+      return Err(Error::Promo(Promo::Exhausted));
+    }
+
+    // Claim first so a double tap can't grant twice
+    promo::ActiveModel {
+      tg_user_id: Set(tg_user_id),
+      promo_name: Set(claim_name),
+      claimed_at: Set(now),
+    }
+    .insert(self.db)
+    .await?;
+
+    Ok(match code.grant {
+      PromoGrant::Balance => {
+        let balance = sv::Balance::new(self.db)
+          .credit(
+            tg_user_id,
+            code.amount,
+            TransactionType::PromoCredit,
+            Some(format!("Promo code {}", code.code)),
+          )
+          .await?;
+        Redeemed::Balance { amount: code.amount, balance }
+      }
+      PromoGrant::Trial => Redeemed::License(
+        sv::License::new(self.db)
+          .create(tg_user_id, LicenseType::Trial, code.amount as u64)
+          .await?,
+      ),
+    })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::sv::{referral::NANO_USDT, test_utils::test_db};
+
+  #[tokio::test]
+  async fn test_redeem_codes() {
+    let db = test_db::setup().await;
+    let sv = PromoCode::new(&db);
+
+    sv.create("Stream5", PromoGrant::Balance, 5 * NANO_USDT, Some(1), None)
+      .await
+      .unwrap();
+    sv.create("week", PromoGrant::Trial, 7, None, None).await.unwrap();
+
+    let Redeemed::Balance { balance, .. } =
+      sv.redeem(1, "STREAM5").await.unwrap()
+    else {
+      panic!("expected balance");
+    };
+    assert_eq!(balance, 5 * NANO_USDT);
+    assert!(matches!(
+      sv.redeem(1, "stream5").await,
+      Err(Error::Promo(Promo::Claimed))
+    ));
+    assert!(matches!(
+      sv.redeem(2, "stream5").await,
+      Err(Error::Promo(Promo::Exhausted))
+    ));
+
+    let Redeemed::License(license) = sv.redeem(2, "week").await.unwrap() else {
+      panic!("expected license");
+    };
+    assert_eq!(license.license_type, LicenseType::Trial);
+    assert_eq!(sv.uses("week").await.unwrap(), 1);
+
+    // Campaign claims live in the same table without clashing
+    assert!(!sv::License::new(&db).has_claimed(2, "week").await.unwrap());
+
+    let yesterday = Utc::now().naive_utc() - TimeDelta::days(1);
+    sv.create("old", PromoGrant::Trial, 1, None, Some(yesterday))
+      .await
+      .unwrap();
+    assert!(matches!(
+      sv.redeem(3, "old").await,
+      Err(Error::Promo(Promo::Inactive))
+    ));
+  }
+}
@@ -0,0 +1,135 @@
+use crate::{
+  entity::promo_asset::{self, PromoAssetKind},
+  prelude::*,
+  sv::canned,
+};
+
+/// Longest banner or template title, it is shown on a button
+pub const TITLE_MAX: usize = 40;
+
+pub struct PromoKit<'a> {
+  db: &'a DatabaseConnection,
+}
+
+impl<'a> PromoKit<'a> {
+  pub fn new(db: &'a DatabaseConnection) -> Self {
+    Self { db }
+  }
+
+  pub async fn all(&self) -> Result<Vec<promo_asset::Model>> {
+    Ok(
+      promo_asset::Entity::find()
+        .order_by_asc(promo_asset::Column::Id)
+        .all(self.db)
+        .await?,
+    )
+  }
+
+  pub async fn by_id(&self, id: i32) -> Result<Option<promo_asset::Model>> {
+    Ok(promo_asset::Entity::find_by_id(id).one(self.db).await?)
+  }
+
+  /// Add an image already uploaded to Telegram
+  pub async fn add_banner(
+    &self,
+    title: &str,
+    file_id: &str,
+  ) -> Result<promo_asset::Model> {
+    self.insert(PromoAssetKind::Banner, title, Some(file_id), None).await
+  }
+
+  /// Add a copy template, `{link}` and `{code}` are filled per creator
+  pub async fn add_template(
+    &self,
+    title: &str,
+    text: &str,
+  ) -> Result<promo_asset::Model> {
+    if text.trim().is_empty() {
+      return Err(Error::InvalidArgs("Template text is empty".into()));
+    }
+    self.insert(PromoAssetKind::Template, title, None, Some(text)).await
+  }
+
+  async fn insert(
+    &self,
+    kind: PromoAssetKind,
+    title: &str,
+    file_id: Option<&str>,
+    text: Option<&str>,
+  ) -> Result<promo_asset::Model> {
+    let title = title.trim();
+    if title.is_empty() || title.chars().count() > TITLE_MAX {
+      return Err(Error::InvalidArgs(format!(
+        "Title must be 1 to {} characters",
+        TITLE_MAX
+      )));
+    }
+
+    Ok(
+      promo_asset::ActiveModel {
+        kind: Set(kind),
+        title: Set(title.to_string()),
+        file_id: Set(file_id.map(str::to_string)),
+        text: Set(text.map(|text| text.trim().to_string())),
+        created_at: Set(Utc::now().naive_utc()),
+        ..Default::default()
+      }
+      .insert(self.db)
+      .await?,
+    )
+  }
+
+  pub async fn remove(&self, id: i32) -> Result<()> {
+    let res = promo_asset::Entity::delete_by_id(id).exec(self.db).await?;
+    if res.rows_affected == 0 {
+      return Err(Error::InvalidArgs(format!("Promo asset #{} not found", id)));
+    }
+    Ok(())
+  }
+}
+
+/// Fill a template with the creator's invite link and referral code
+pub fn render(template: &str, link: &str, code: &str) -> String {
+  canned::expand(
+    template,
+    &[("link", link.to_string()), ("code", code.to_string())],
+  )
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::sv::test_utils::test_db;
+
+  #[tokio::test]
+  async fn test_promo_assets() {
+    let db = test_db::setup().await;
+    let sv = PromoKit::new(&db);
+
+    let banner =
+      sv.add_banner("Summer banner", "AgACAgIAAxkBAAI").await.unwrap();
+    let template = sv
+      .add_template("Short post", "Get 10% off with {code}: {link}")
+      .await
+      .unwrap();
+    assert!(sv.add_template("Empty", "  ").await.is_err());
+    assert!(sv.add_banner(&"x".repeat(TITLE_MAX + 1), "id").await.is_err());
+
+    let assets = sv.all().await.unwrap();
+    assert_eq!(assets, vec![banner.clone(), template.clone()]);
+    assert_eq!(assets[0].kind, PromoAssetKind::Banner);
+
+    assert_eq!(
+      render(
+        template.text.as_deref().unwrap(),
+        "https://t.me/bot?start=neo",
+        "neo"
+      ),
+      "Get 10% off with neo: https://t.me/bot?start=neo"
+    );
+
+    sv.remove(banner.id).await.unwrap();
+    assert!(sv.remove(banner.id).await.is_err());
+    assert_eq!(sv.all().await.unwrap(), vec![template]);
+  }
+}
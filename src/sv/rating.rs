@@ -0,0 +1,131 @@
+use crate::{
+  entity::rating::{self, RatingKind},
+  prelude::*,
+};
+
+pub struct Rating<'a> {
+  db: &'a DatabaseConnection,
+}
+
+/// Satisfaction score over a period
+#[derive(Debug, Clone, Default)]
+pub struct RatingSummary {
+  pub asked: u64,
+  pub rated: u64,
+  pub average: Option<f64>,
+}
+
+impl<'a> Rating<'a> {
+  pub fn new(db: &'a DatabaseConnection) -> Self {
+    Self { db }
+  }
+
+  /// Store a pending prompt, its id goes into the rating buttons
+  pub async fn request(
+    &self,
+    tg_user_id: i64,
+    kind: RatingKind,
+    subject: &str,
+  ) -> Result<rating::Model> {
+    Ok(
+      rating::ActiveModel {
+        id: NotSet,
+        tg_user_id: Set(tg_user_id),
+        kind: Set(kind),
+        subject: Set(subject.to_string()),
+        score: Set(None),
+        asked_at: Set(Utc::now().naive_utc()),
+        rated_at: Set(None),
+      }
+      .insert(self.db)
+      .await?,
+    )
+  }
+
+  /// Answer a prompt. Each prompt can only be rated once.
+  pub async fn rate(
+    &self,
+    id: i32,
+    tg_user_id: i64,
+    score: i32,
+  ) -> Result<rating::Model> {
+    if !(1..=5).contains(&score) {
+      return Err(Error::InvalidArgs("Rating must be from 1 to 5".into()));
+    }
+
+    let prompt = rating::Entity::find_by_id(id)
+      .one(self.db)
+      .await?
+      .filter(|r| r.tg_user_id == tg_user_id)
+      .ok_or_else(|| Error::InvalidArgs("Rating not found".into()))?;
+
+    if prompt.score.is_some() {
+      return Err(Error::InvalidArgs("You have already rated this".into()));
+    }
+
+    Ok(
+      rating::ActiveModel {
+        score: Set(Some(score)),
+        rated_at: Set(Some(Utc::now().naive_utc())),
+        ..prompt.into()
+      }
+      .update(self.db)
+      .await?,
+    )
+  }
+
+  pub async fn summary(
+    &self,
+    kind: RatingKind,
+    since: DateTime,
+  ) -> Result<RatingSummary> {
+    let prompts = rating::Entity::find()
+      .filter(rating::Column::Kind.eq(kind))
+      .filter(rating::Column::AskedAt.gte(since))
+      .all(self.db)
+      .await?;
+
+    let scores: Vec<i32> = prompts.iter().filter_map(|r| r.score).collect();
+    let average = (!scores.is_empty())
+      .then(|| scores.iter().sum::<i32>() as f64 / scores.len() as f64);
+
+    Ok(RatingSummary {
+      asked: prompts.len() as u64,
+      rated: scores.len() as u64,
+      average,
+    })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{sv, sv::test_utils::test_db};
+
+  #[tokio::test]
+  async fn test_rate_once_and_summarize() {
+    let db = test_db::setup().await;
+    sv::User::new(&db).get_or_create(1).await.unwrap();
+    let sv = Rating::new(&db);
+    let since = Utc::now().naive_utc() - TimeDelta::days(7);
+
+    let a = sv.request(1, RatingKind::Support, "1").await.unwrap();
+    let b = sv.request(1, RatingKind::Support, "2").await.unwrap();
+    sv.request(1, RatingKind::Support, "3").await.unwrap();
+    sv.request(1, RatingKind::Purchase, "key").await.unwrap();
+
+    assert!(sv.rate(a.id, 1, 6).await.is_err());
+    assert!(sv.rate(a.id, 2, 5).await.is_err());
+    sv.rate(a.id, 1, 5).await.unwrap();
+    assert!(sv.rate(a.id, 1, 1).await.is_err());
+    sv.rate(b.id, 1, 2).await.unwrap();
+
+    let summary = sv.summary(RatingKind::Support, since).await.unwrap();
+    assert_eq!(summary.asked, 3);
+    assert_eq!(summary.rated, 2);
+    assert_eq!(summary.average, Some(3.5));
+
+    let summary = sv.summary(RatingKind::Purchase, since).await.unwrap();
+    assert_eq!(summary.average, None);
+  }
+}
@@ -0,0 +1,398 @@
+use std::collections::HashSet;
+
+use crate::{
+  entity::{TransactionType, pending_invoice, transaction, user},
+  prelude::*,
+  sv::{
+    balance::Balance,
+    cryptobot::{CryptoBot, Invoice, InvoiceStatus},
+    outbox::Outbox,
+    payment::{DEPOSIT_PREFIX, deposit_description, deposit_invoice},
+    referral::{NANO_USDT, Referral},
+  },
+};
+
+/// Invoices fetched per getInvoices call, the API maximum
+const PAGE: usize = 1000;
+/// Stop paging past this many invoices, a night never has that many
+const MAX_PAGES: usize = 20;
+
+/// Description of the transaction undoing a deposit nothing paid for
+fn reversal_description(tx_id: i32) -> String {
+  format!("Reconciliation reversal of tx #{}", tx_id)
+}
+
+fn parse_date(date: &str) -> Option<DateTime> {
+  chrono::DateTime::parse_from_rfc3339(date).ok().map(|d| d.naive_utc())
+}
+
+/// Deposit invoice CryptoBot reports as paid
+#[derive(Debug, Clone, PartialEq)]
+pub struct PaidInvoice {
+  pub invoice_id: i64,
+  pub user_id: i64,
+  pub amount_nano: i64,
+  pub referrer_id: Option<i64>,
+  pub paid_at: DateTime,
+}
+
+impl PaidInvoice {
+  /// None unless it is a paid deposit with a readable payload
+  pub fn from_invoice(invoice: &Invoice) -> Option<Self> {
+    if invoice.status != InvoiceStatus::Paid {
+      return None;
+    }
+    let payload = CryptoBot::parse_payload(invoice.payload.as_deref()?)?;
+    if payload.payment_type != "deposit" {
+      return None;
+    }
+    let amount = invoice.amount.parse::<f64>().ok()?;
+    Some(Self {
+      invoice_id: invoice.invoice_id,
+      user_id: payload.user_id,
+      amount_nano: (amount * NANO_USDT as f64).round() as i64,
+      referrer_id: payload.referrer_id,
+      paid_at: parse_date(invoice.paid_at.as_deref()?)?,
+    })
+  }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Discrepancy {
+  /// Paid at CryptoBot, never credited
+  Uncredited(PaidInvoice),
+  /// Credited, but CryptoBot doesn't know the invoice as paid
+  Unpaid { tx: transaction::Model, invoice_id: i64 },
+}
+
+#[derive(Debug, Default)]
+pub struct Report {
+  pub paid: usize,
+  pub credited: usize,
+  pub discrepancies: Vec<Discrepancy>,
+}
+
+pub struct Reconcile<'a> {
+  db: &'a DatabaseConnection,
+}
+
+impl<'a> Reconcile<'a> {
+  pub fn new(db: &'a DatabaseConnection) -> Self {
+    Self { db }
+  }
+
+  /// Cross-check deposits paid or credited since `since`
+  pub async fn check(
+    &self,
+    cryptobot: &CryptoBot,
+    since: DateTime,
+  ) -> Result<Report> {
+    let paid = Self::paid_since(cryptobot, since).await?;
+    let credited = self.credited_since(since).await?;
+    let mut report = Report {
+      paid: paid.len(),
+      credited: credited.len(),
+      ..Default::default()
+    };
+
+    let (uncredited, unconfirmed) = self.compare(paid, credited).await?;
+    report
+      .discrepancies
+      .extend(uncredited.into_iter().map(Discrepancy::Uncredited));
+
+    // paid just before the window, or never paid at all
+    if !unconfirmed.is_empty() {
+      let ids = unconfirmed.iter().map(|(id, _)| *id).collect();
+      let invoices = cryptobot.get_invoices(Some(ids), None).await?;
+      for (invoice_id, tx) in unconfirmed {
+        let paid = invoices.iter().any(|invoice| {
+          invoice.invoice_id == invoice_id
+            && invoice.status == InvoiceStatus::Paid
+        });
+        if !paid {
+          report.discrepancies.push(Discrepancy::Unpaid { tx, invoice_id });
+        }
+      }
+    }
+    Ok(report)
+  }
+
+  async fn paid_since(
+    cryptobot: &CryptoBot,
+    since: DateTime,
+  ) -> Result<Vec<PaidInvoice>> {
+    let mut paid = Vec::new();
+    for page in 0..MAX_PAGES {
+      let invoices =
+        cryptobot.list_invoices(InvoiceStatus::Paid, page * PAGE, PAGE).await?;
+      let mut older = true;
+      for invoice in &invoices {
+        let paid_at = invoice.paid_at.as_deref().and_then(parse_date);
+        if paid_at.is_some_and(|at| at < since) {
+          continue;
+        }
+        older = false;
+        paid.extend(PaidInvoice::from_invoice(invoice));
+      }
+      // newest first, invoices live an hour so paid dates barely interleave
+      if invoices.len() < PAGE || older {
+        break;
+      }
+    }
+    Ok(paid)
+  }
+
+  /// Paid invoices without a deposit, and deposits whose invoice isn't among
+  /// the paid ones
+  pub async fn compare(
+    &self,
+    paid: Vec<PaidInvoice>,
+    credited: Vec<(i64, transaction::Model)>,
+  ) -> Result<(Vec<PaidInvoice>, Vec<(i64, transaction::Model)>)> {
+    let ids: Vec<i64> = paid.iter().map(|invoice| invoice.invoice_id).collect();
+    // credited before the window still counts
+    let known = self.credited(&ids).await?;
+    let uncredited = paid
+      .into_iter()
+      .filter(|invoice| !known.contains(&invoice.invoice_id))
+      .collect();
+    let ids: HashSet<i64> = ids.into_iter().collect();
+    let unconfirmed =
+      credited.into_iter().filter(|(id, _)| !ids.contains(id)).collect();
+    Ok((uncredited, unconfirmed))
+  }
+
+  /// CryptoBot deposits credited since `since` and not reversed, with their
+  /// invoice
+  pub async fn credited_since(
+    &self,
+    since: DateTime,
+  ) -> Result<Vec<(i64, transaction::Model)>> {
+    let deposits = transaction::Entity::find()
+      .filter(transaction::Column::TxType.eq(TransactionType::Deposit))
+      .filter(transaction::Column::Description.starts_with(DEPOSIT_PREFIX))
+      .filter(transaction::Column::CreatedAt.gte(since))
+      .all(self.db)
+      .await?;
+
+    let reversals: HashSet<String> = transaction::Entity::find()
+      .select_only()
+      .column(transaction::Column::Description)
+      .filter(transaction::Column::TxType.eq(TransactionType::Reversal))
+      .filter(
+        transaction::Column::Description
+          .is_in(deposits.iter().map(|tx| reversal_description(tx.id))),
+      )
+      .into_tuple::<Option<String>>()
+      .all(self.db)
+      .await?
+      .into_iter()
+      .flatten()
+      .collect();
+
+    Ok(
+      deposits
+        .into_iter()
+        .filter(|tx| !reversals.contains(&reversal_description(tx.id)))
+        .filter_map(|tx| {
+          let invoice = deposit_invoice(tx.description.as_deref()?)?;
+          Some((invoice, tx))
+        })
+        .collect(),
+    )
+  }
+
+  /// Invoices among `ids` with a deposit, whenever it was credited
+  async fn credited(&self, ids: &[i64]) -> Result<HashSet<i64>> {
+    if ids.is_empty() {
+      return Ok(HashSet::new());
+    }
+    let descriptions: Vec<Option<String>> = transaction::Entity::find()
+      .select_only()
+      .column(transaction::Column::Description)
+      .filter(transaction::Column::TxType.eq(TransactionType::Deposit))
+      .filter(
+        transaction::Column::Description
+          .is_in(ids.iter().map(|id| deposit_description(*id))),
+      )
+      .into_tuple()
+      .all(self.db)
+      .await?;
+    Ok(
+      descriptions
+        .iter()
+        .flatten()
+        .filter_map(|d| deposit_invoice(d))
+        .collect(),
+    )
+  }
+
+  /// Credit a paid invoice that was missed, the same way the webhook would.
+  /// None if it was credited meanwhile.
+  pub async fn credit(&self, invoice: &PaidInvoice) -> Result<Option<i64>> {
+    let txn = self.db.begin().await?;
+    // claimed first, so a late webhook can't credit it as well
+    pending_invoice::Entity::delete_by_id(invoice.invoice_id)
+      .exec(&txn)
+      .await?;
+    let exists = transaction::Entity::find()
+      .filter(transaction::Column::TxType.eq(TransactionType::Deposit))
+      .filter(
+        transaction::Column::Description
+          .eq(deposit_description(invoice.invoice_id)),
+      )
+      .count(&txn)
+      .await?;
+    if exists > 0 {
+      return Ok(None);
+    }
+
+    let new_balance = Balance::credit_in(
+      &txn,
+      invoice.user_id,
+      invoice.amount_nano,
+      TransactionType::Deposit,
+      Some(deposit_description(invoice.invoice_id)),
+    )
+    .await?;
+    let text = format!(
+      "✅ <b>Payment Received!</b>\n\n\
+      <b>{:.2} USDT</b> has been added to your balance.",
+      invoice.amount_nano as f64 / NANO_USDT as f64
+    );
+    Outbox::push(&txn, invoice.user_id, text).await?;
+    txn.commit().await?;
+
+    if let Some(referrer_id) = invoice.referrer_id {
+      let _ = Referral::new(self.db)
+        .pay_commission(referrer_id, Some(invoice.user_id), invoice.amount_nano)
+        .await;
+    }
+    Ok(Some(new_balance))
+  }
+
+  /// Take back a deposit nothing paid for, returns the user and the new
+  /// balance
+  pub async fn reverse(&self, tx_id: i32) -> Result<(i64, i64)> {
+    let txn = self.db.begin().await?;
+    let tx = transaction::Entity::find_by_id(tx_id)
+      .one(&txn)
+      .await?
+      .filter(|tx| {
+        tx.tx_type == TransactionType::Deposit
+          && tx.description.as_deref().and_then(deposit_invoice).is_some()
+      })
+      .ok_or_else(|| {
+        Error::InvalidArgs(format!("Transaction #{} is no deposit", tx_id))
+      })?;
+
+    let description = reversal_description(tx.id);
+    let reversed = transaction::Entity::find()
+      .filter(transaction::Column::TxType.eq(TransactionType::Reversal))
+      .filter(transaction::Column::Description.eq(&description))
+      .count(&txn)
+      .await?;
+    if reversed > 0 {
+      return Err(Error::InvalidArgs(format!(
+        "Transaction #{} is already reversed",
+        tx_id
+      )));
+    }
+
+    let user = user::Entity::find_by_id(tx.user_id)
+      .one(&txn)
+      .await?
+      .ok_or(Error::UserNotFound)?;
+    // the deposit may have been spent already
+    if user.balance < tx.amount {
+      return Err(Error::InsufficientBalance);
+    }
+    let new_balance = user.balance - tx.amount;
+    user::ActiveModel { balance: Set(new_balance), ..user.into() }
+      .update(&txn)
+      .await?;
+
+    transaction::ActiveModel {
+      id: NotSet,
+      user_id: Set(tx.user_id),
+      amount: Set(-tx.amount),
+      tx_type: Set(TransactionType::Reversal),
+      description: Set(Some(description)),
+      referrer_id: Set(None),
+      region: Set(None),
+      refund_of: Set(None),
+      created_at: Set(Utc::now().naive_utc()),
+    }
+    .insert(&txn)
+    .await?;
+
+    txn.commit().await?;
+    Ok((tx.user_id, new_balance))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::sv::{self, test_utils::test_db};
+
+  fn paid(invoice_id: i64, user_id: i64, usdt: i64) -> PaidInvoice {
+    PaidInvoice {
+      invoice_id,
+      user_id,
+      amount_nano: usdt * NANO_USDT,
+      referrer_id: None,
+      paid_at: DateTime::default(),
+    }
+  }
+
+  #[tokio::test]
+  async fn test_reconcile() {
+    let db = test_db::setup().await;
+    let sv = Reconcile::new(&db);
+    let payment = sv::Payment::new(&db);
+    let balance = sv::Balance::new(&db);
+    sv::User::new(&db).get_or_create(1).await.unwrap();
+    let since = Utc::now().naive_utc() - TimeDelta::hours(1);
+
+    // #10 went through the webhook, #11 was missed, #12 was never paid
+    payment.save_pending(10, 1, 5.0, None).await.unwrap();
+    payment.process_paid(10).await.unwrap().unwrap();
+    payment.save_pending(11, 1, 3.0, None).await.unwrap();
+    balance
+      .deposit(1, 2 * NANO_USDT, Some(deposit_description(12)))
+      .await
+      .unwrap();
+
+    let credited = sv.credited_since(since).await.unwrap();
+    assert_eq!(credited.len(), 2);
+    let (uncredited, unconfirmed) =
+      sv.compare(vec![paid(10, 1, 5), paid(11, 1, 3)], credited).await.unwrap();
+    assert_eq!(uncredited, [paid(11, 1, 3)]);
+    assert_eq!(unconfirmed.len(), 1);
+    assert_eq!(unconfirmed[0].0, 12);
+
+    // fixes apply once
+    assert_eq!(sv.credit(&uncredited[0]).await.unwrap(), Some(10 * NANO_USDT));
+    assert_eq!(sv.credit(&uncredited[0]).await.unwrap(), None);
+    assert!(payment.process_paid(11).await.unwrap().is_none());
+
+    let tx = &unconfirmed[0].1;
+    assert_eq!(sv.reverse(tx.id).await.unwrap(), (1, 8 * NANO_USDT));
+    assert!(matches!(sv.reverse(tx.id).await, Err(Error::InvalidArgs(_))));
+
+    let credited = sv.credited_since(since).await.unwrap();
+    let (uncredited, unconfirmed) =
+      sv.compare(vec![paid(10, 1, 5), paid(11, 1, 3)], credited).await.unwrap();
+    assert!(uncredited.is_empty() && unconfirmed.is_empty());
+
+    // spent deposits can't be taken back
+    balance
+      .deposit(1, 20 * NANO_USDT, Some(deposit_description(13)))
+      .await
+      .unwrap();
+    let tx = sv.credited_since(since).await.unwrap().pop().unwrap().1;
+    balance.spend(1, 25 * NANO_USDT, None, None).await.unwrap();
+    assert!(matches!(sv.reverse(tx.id).await, Err(Error::InsufficientBalance)));
+  }
+}
@@ -1,8 +1,25 @@
+use sea_orm::Condition;
+
 use crate::{
-  entity::{user, user::UserRole},
+  entity::{
+    TransactionType, commission_boost, transaction, user, user::UserRole,
+  },
   prelude::*,
+  sv::outbox::Outbox,
 };
 
+/// Largest commission multiplier a boost may have, in percent
+pub const MAX_BOOST_PERCENT: i32 = 500;
+
+/// Commission paid for one sale
+#[allow(dead_code)]
+#[derive(Debug, Clone)]
+pub struct Commission {
+  pub amount: i64,
+  /// Boost that multiplied it
+  pub boost: Option<commission_boost::Model>,
+}
+
 pub struct Referral<'a> {
   db: &'a DatabaseConnection,
 }
@@ -75,26 +92,249 @@ impl<'a> Referral<'a> {
     referrer_id: i64,
     sale_amount: i64,
   ) -> Result<i64> {
+    Ok(self.pay_commission(referrer_id, None, sale_amount).await?.amount)
+  }
+
+  /// Credit the referrer's commission for a sale to `buyer`, multiplied by
+  /// the best running boost. The only place commission is calculated.
+  pub async fn pay_commission(
+    &self,
+    referrer_id: i64,
+    buyer: Option<i64>,
+    sale_amount: i64,
+  ) -> Result<Commission> {
     let txn = self.db.begin().await?;
+    let commission =
+      Self::pay_commission_in(&txn, referrer_id, buyer, sale_amount).await?;
+    txn.commit().await?;
+    Ok(commission)
+  }
 
+  /// `pay_commission` within the caller's transaction, so the commission
+  /// and its notification stand or fall with the sale
+  pub async fn pay_commission_in(
+    txn: &sea_orm::DatabaseTransaction,
+    referrer_id: i64,
+    buyer: Option<i64>,
+    sale_amount: i64,
+  ) -> Result<Commission> {
+    let boost = Self::boost_in(txn, referrer_id).await?;
     let referrer = user::Entity::find_by_id(referrer_id)
-      .one(&txn)
+      .one(txn)
       .await?
       .ok_or(Error::ReferralNotFound)?;
 
-    let commission = (sale_amount * referrer.commission_rate as i64) / 100;
+    let mut amount = (sale_amount * referrer.commission_rate as i64) / 100;
+    if let Some(boost) = &boost {
+      amount = amount * boost.percent as i64 / 100;
+    }
 
     user::ActiveModel {
-      referral_sales: Set(referrer.referral_sales + 1),
-      referral_earnings: Set(referrer.referral_earnings + commission),
-      balance: Set(referrer.balance + commission),
+      balance: Set(referrer.balance + amount),
       ..referrer.into()
     }
-    .update(&txn)
+    .update(txn)
     .await?;
 
-    txn.commit().await?;
-    Ok(commission)
+    // Recorded even without commission, the ledger is what stats count
+    let mut description = match buyer {
+      Some(buyer) => format!("Referral bonus from user {}", buyer),
+      None => "Referral bonus".to_string(),
+    };
+    if let Some(boost) = &boost {
+      description.push_str(&format!(
+        " ({} boost #{})",
+        boost.multiplier(),
+        boost.id
+      ));
+    }
+    transaction::ActiveModel {
+      id: NotSet,
+      user_id: Set(referrer_id),
+      amount: Set(amount),
+      tx_type: Set(TransactionType::ReferralBonus),
+      description: Set(Some(description)),
+      referrer_id: Set(buyer),
+      region: Set(None),
+      refund_of: Set(None),
+      created_at: Set(Utc::now().naive_utc()),
+    }
+    .insert(txn)
+    .await?;
+
+    if amount > 0 {
+      let text = format!(
+        "💸 <b>Referral commission</b>\n\n\
+        <b>{:.2} USDT</b> has been added to your balance for a sale \
+        you referred.",
+        amount as f64 / NANO_USDT as f64
+      );
+      Outbox::push(txn, referrer_id, text).await?;
+    }
+
+    Ok(Commission { amount, boost })
+  }
+
+  /// Biggest boost running for the referrer right now
+  pub async fn active_boost(
+    &self,
+    referrer_id: i64,
+  ) -> Result<Option<commission_boost::Model>> {
+    Self::boost_in(self.db, referrer_id).await
+  }
+
+  async fn boost_in<C: ConnectionTrait>(
+    db: &C,
+    referrer_id: i64,
+  ) -> Result<Option<commission_boost::Model>> {
+    let now = Utc::now().naive_utc();
+    Ok(
+      commission_boost::Entity::find()
+        .filter(commission_boost::Column::StartsAt.lte(now))
+        .filter(commission_boost::Column::EndsAt.gt(now))
+        .filter(
+          Condition::any()
+            .add(commission_boost::Column::CreatorId.is_null())
+            .add(commission_boost::Column::CreatorId.eq(referrer_id)),
+        )
+        .order_by_desc(commission_boost::Column::Percent)
+        .one(db)
+        .await?,
+    )
+  }
+
+  /// Schedule a boost for `creators`, or for everyone if empty.
+  /// One row is created per creator.
+  pub async fn start_boost(
+    &self,
+    percent: i32,
+    starts_at: DateTime,
+    duration: TimeDelta,
+    creators: &[i64],
+  ) -> Result<Vec<commission_boost::Model>> {
+    if !(101..=MAX_BOOST_PERCENT).contains(&percent) {
+      return Err(Error::InvalidArgs(format!(
+        "Boost must be between 101% and {}%",
+        MAX_BOOST_PERCENT
+      )));
+    }
+    if duration <= TimeDelta::zero() {
+      return Err(Error::InvalidArgs("Boost must last some time".into()));
+    }
+    for &creator in creators {
+      self.validate_referrer(creator).await?;
+    }
+
+    let now = Utc::now().naive_utc();
+    let targets: Vec<Option<i64>> = match creators {
+      [] => vec![None],
+      _ => creators.iter().copied().map(Some).collect(),
+    };
+    let mut boosts = Vec::with_capacity(targets.len());
+    for creator_id in targets {
+      let boost = commission_boost::ActiveModel {
+        id: NotSet,
+        percent: Set(percent),
+        creator_id: Set(creator_id),
+        starts_at: Set(starts_at),
+        ends_at: Set(starts_at + duration),
+        created_at: Set(now),
+        end_announced: Set(false),
+      };
+      boosts.push(boost.insert(self.db).await?);
+    }
+    Ok(boosts)
+  }
+
+  /// End a boost early, creators are told by the usual end broadcast.
+  /// Boosts that have not started yet are simply dropped.
+  pub async fn stop_boost(&self, id: i32) -> Result<commission_boost::Model> {
+    let boost = commission_boost::Entity::find_by_id(id)
+      .one(self.db)
+      .await?
+      .ok_or_else(|| Error::InvalidArgs(format!("Boost #{} not found", id)))?;
+
+    let now = Utc::now().naive_utc();
+    if boost.starts_at > now {
+      commission_boost::Entity::delete_by_id(id).exec(self.db).await?;
+      return Ok(boost);
+    }
+    Ok(
+      commission_boost::ActiveModel {
+        ends_at: Set(now.min(boost.ends_at)),
+        ..boost.into()
+      }
+      .update(self.db)
+      .await?,
+    )
+  }
+
+  /// Boosts that have not ended yet
+  pub async fn upcoming_boosts(&self) -> Result<Vec<commission_boost::Model>> {
+    let now = Utc::now().naive_utc();
+    Ok(
+      commission_boost::Entity::find()
+        .filter(commission_boost::Column::EndsAt.gt(now))
+        .order_by_asc(commission_boost::Column::StartsAt)
+        .all(self.db)
+        .await?,
+    )
+  }
+
+  /// Ended boosts creators were not told about yet
+  pub async fn ended_boosts(&self) -> Result<Vec<commission_boost::Model>> {
+    let now = Utc::now().naive_utc();
+    Ok(
+      commission_boost::Entity::find()
+        .filter(commission_boost::Column::EndsAt.lte(now))
+        .filter(commission_boost::Column::EndAnnounced.eq(false))
+        .all(self.db)
+        .await?,
+    )
+  }
+
+  pub async fn mark_boost_announced(&self, id: i32) -> Result<()> {
+    commission_boost::Entity::update_many()
+      .col_expr(commission_boost::Column::EndAnnounced, true.into())
+      .filter(commission_boost::Column::Id.eq(id))
+      .exec(self.db)
+      .await?;
+    Ok(())
+  }
+
+  /// Sales and commission of referrers, summed from the ledger
+  async fn totals(
+    &self,
+    referrer: Option<i64>,
+  ) -> Result<HashMap<i64, (i64, i64)>> {
+    let mut query = transaction::Entity::find()
+      .select_only()
+      .column(transaction::Column::UserId)
+      .column_as(transaction::Column::Id.count(), "sales")
+      .column_as(transaction::Column::Amount.sum(), "earnings")
+      .column_as(transaction::Column::RefundOf.count(), "clawbacks")
+      .filter(transaction::Column::TxType.eq(TransactionType::ReferralBonus))
+      .group_by(transaction::Column::UserId);
+    if let Some(referrer) = referrer {
+      query = query.filter(transaction::Column::UserId.eq(referrer));
+    }
+
+    // commission taken back from refunds lowers the earnings, not the sales
+    let rows: Vec<(i64, i64, Option<i64>, i64)> =
+      query.into_tuple().all(self.db).await?;
+    Ok(
+      rows
+        .into_iter()
+        .map(|(user_id, sales, earnings, clawbacks)| {
+          (user_id, (sales - clawbacks, earnings.unwrap_or(0)))
+        })
+        .collect(),
+    )
+  }
+
+  /// Sales and commission of every referrer, for admin stats
+  pub async fn all_totals(&self) -> Result<HashMap<i64, (i64, i64)>> {
+    self.totals(None).await
   }
 
   /// Get referral stats for a user
@@ -103,12 +343,14 @@ impl<'a> Referral<'a> {
       .one(self.db)
       .await?
       .ok_or(Error::UserNotFound)?;
+    let (total_sales, total_earnings) =
+      self.totals(Some(user_id)).await?.remove(&user_id).unwrap_or((0, 0));
 
     Ok(ReferralStats {
       commission_rate: user.commission_rate,
       discount_percent: user.discount_percent,
-      total_sales: user.referral_sales,
-      total_earnings: user.referral_earnings,
+      total_sales,
+      total_earnings,
       can_withdraw: user.role == UserRole::Creator
         || user.role == UserRole::Admin,
     })
@@ -197,7 +439,7 @@ impl<'a> Referral<'a> {
 pub struct ReferralStats {
   pub commission_rate: i32,
   pub discount_percent: i32,
-  pub total_sales: i32,
+  pub total_sales: i64,
   pub total_earnings: i64,
   pub can_withdraw: bool,
 }
@@ -220,9 +462,11 @@ mod tests {
       referred_by: Set(None),
       commission_rate: Set(25),
       discount_percent: Set(3),
-      referral_sales: Set(0),
-      referral_earnings: Set(0),
       referral_code: Set(None),
+      deletion_requested_at: Set(None),
+      deleted_at: Set(None),
+      storefront: Set(None),
+      is_reseller: Set(false),
     }
     .insert(&db)
     .await
@@ -246,9 +490,11 @@ mod tests {
       referred_by: Set(None),
       commission_rate: Set(25),
       discount_percent: Set(3),
-      referral_sales: Set(0),
-      referral_earnings: Set(0),
       referral_code: Set(None),
+      deletion_requested_at: Set(None),
+      deleted_at: Set(None),
+      storefront: Set(None),
+      is_reseller: Set(false),
     }
     .insert(&db)
     .await
@@ -263,8 +509,9 @@ mod tests {
 
     let user =
       user::Entity::find_by_id(12345i64).one(&db).await.unwrap().unwrap();
-    assert_eq!(user.referral_sales, 1);
-    assert_eq!(user.referral_earnings, 2_500_000);
+    let stats = Referral::new(&db).stats(12345).await.unwrap();
+    assert_eq!(stats.total_sales, 1);
+    assert_eq!(stats.total_earnings, 2_500_000);
     assert_eq!(user.balance, 2_500_000);
   }
 
@@ -281,9 +528,11 @@ mod tests {
       referred_by: Set(None),
       commission_rate: Set(25),
       discount_percent: Set(3),
-      referral_sales: Set(0),
-      referral_earnings: Set(0),
       referral_code: Set(None),
+      deletion_requested_at: Set(None),
+      deleted_at: Set(None),
+      storefront: Set(None),
+      is_reseller: Set(false),
     }
     .insert(&db)
     .await
@@ -297,11 +546,29 @@ mod tests {
 
     let user =
       user::Entity::find_by_id(12345i64).one(&db).await.unwrap().unwrap();
-    assert_eq!(user.referral_sales, 1);
-    assert_eq!(user.referral_earnings, 2_500_000);
+    let stats = Referral::new(&db).stats(12345).await.unwrap();
+    assert_eq!(stats.total_sales, 1);
+    assert_eq!(stats.total_earnings, 2_500_000);
     assert_eq!(user.balance, 2_500_000);
   }
 
+  #[tokio::test]
+  async fn test_stats_come_from_ledger() {
+    let db = test_db::setup().await;
+    let sv = Referral::new(&db);
+    crate::sv::User::new(&db).get_or_create(1).await.unwrap();
+
+    let paid = sv.record_sale(1, MONTH_PRICE).await.unwrap();
+    sv.set_commission_rate(1, 0).await.unwrap();
+    assert_eq!(sv.record_sale(1, MONTH_PRICE).await.unwrap(), 0);
+
+    // Sales without commission still count
+    let stats = sv.stats(1).await.unwrap();
+    assert_eq!(stats.total_sales, 2);
+    assert_eq!(stats.total_earnings, paid);
+    assert_eq!(sv.all_totals().await.unwrap()[&1], (2, paid));
+  }
+
   #[tokio::test]
   async fn test_custom_referral_code() {
     let db = test_db::setup().await;
@@ -315,9 +582,11 @@ mod tests {
       referred_by: Set(None),
       commission_rate: Set(25),
       discount_percent: Set(3),
-      referral_sales: Set(0),
-      referral_earnings: Set(0),
       referral_code: Set(Some("CREATOR123".to_string())),
+      deletion_requested_at: Set(None),
+      deleted_at: Set(None),
+      storefront: Set(None),
+      is_reseller: Set(false),
     }
     .insert(&db)
     .await
@@ -350,9 +619,11 @@ mod tests {
       referred_by: Set(None),
       commission_rate: Set(25),
       discount_percent: Set(3),
-      referral_sales: Set(0),
-      referral_earnings: Set(0),
       referral_code: Set(Some("USER123".to_string())),
+      deletion_requested_at: Set(None),
+      deleted_at: Set(None),
+      storefront: Set(None),
+      is_reseller: Set(false),
     }
     .insert(&db)
     .await
@@ -381,9 +652,11 @@ mod tests {
       referred_by: Set(None),
       commission_rate: Set(25),
       discount_percent: Set(3),
-      referral_sales: Set(0),
-      referral_earnings: Set(0),
       referral_code: Set(Some("CREATOR_CODE".to_string())),
+      deletion_requested_at: Set(None),
+      deleted_at: Set(None),
+      storefront: Set(None),
+      is_reseller: Set(false),
     }
     .insert(&db)
     .await
@@ -398,9 +671,11 @@ mod tests {
       referred_by: Set(None),
       commission_rate: Set(25),
       discount_percent: Set(3),
-      referral_sales: Set(0),
-      referral_earnings: Set(0),
       referral_code: Set(None),
+      deletion_requested_at: Set(None),
+      deleted_at: Set(None),
+      storefront: Set(None),
+      is_reseller: Set(false),
     }
     .insert(&db)
     .await
@@ -415,9 +690,11 @@ mod tests {
       referred_by: Set(None),
       commission_rate: Set(10),
       discount_percent: Set(0),
-      referral_sales: Set(0),
-      referral_earnings: Set(0),
       referral_code: Set(None),
+      deletion_requested_at: Set(None),
+      deleted_at: Set(None),
+      storefront: Set(None),
+      is_reseller: Set(false),
     }
     .insert(&db)
     .await
@@ -441,4 +718,45 @@ mod tests {
     let display = referral.display_code(99999).await;
     assert!(display.is_none());
   }
+
+  #[tokio::test]
+  async fn test_commission_boost() {
+    let db = test_db::setup().await;
+    let referral = Referral::new(&db);
+    let users = crate::sv::User::new(&db);
+    for id in [1, 2] {
+      users.get_or_create(id).await.unwrap();
+      referral.set_commission_rate(id, 20).await.unwrap();
+    }
+    let now = Utc::now().naive_utc();
+    let week = TimeDelta::days(7);
+
+    assert!(referral.start_boost(100, now, week, &[]).await.is_err());
+    let later = referral
+      .start_boost(300, now + TimeDelta::days(1), week, &[])
+      .await
+      .unwrap();
+    let boost = referral.start_boost(200, now, week, &[1]).await.unwrap();
+
+    let paid = referral.pay_commission(1, Some(7), MONTH_PRICE).await.unwrap();
+    assert_eq!(paid.amount, 4_000_000);
+    assert_eq!(paid.boost.map(|b| b.id), Some(boost[0].id));
+    let paid = referral.pay_commission(2, Some(7), MONTH_PRICE).await.unwrap();
+    assert_eq!((paid.amount, paid.boost), (2_000_000, None));
+
+    let txs = crate::sv::Balance::new(&db).transactions(1, 5).await.unwrap();
+    assert_eq!(
+      txs[0].description.as_deref(),
+      Some(format!("Referral bonus from user 7 (2x boost #{})", boost[0].id))
+        .as_deref()
+    );
+
+    referral.stop_boost(boost[0].id).await.unwrap();
+    referral.stop_boost(later[0].id).await.unwrap();
+    assert!(referral.upcoming_boosts().await.unwrap().is_empty());
+    let ended = referral.ended_boosts().await.unwrap();
+    assert_eq!(ended.len(), 1);
+    referral.mark_boost_announced(ended[0].id).await.unwrap();
+    assert!(referral.ended_boosts().await.unwrap().is_empty());
+  }
 }
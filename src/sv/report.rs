@@ -0,0 +1,223 @@
+use std::collections::HashSet;
+
+use chrono::NaiveDate;
+use reqwest::Client;
+use sea_orm::Condition;
+
+use crate::{
+  entity::{TransactionType, license, session, transaction, user},
+  prelude::*,
+  sv::{referral::NANO_USDT, sheets::Sheets},
+};
+
+/// Numbers of one UTC day, one spreadsheet row
+#[derive(Debug, Clone, PartialEq)]
+pub struct DailyMetrics {
+  pub date: NaiveDate,
+  pub new_users: u64,
+  pub total_users: u64,
+  /// Users with at least one session during the day
+  pub active_users: u64,
+  pub new_licenses: u64,
+  pub purchases: u64,
+  /// Spent on licenses less refunds, in nanoUSDT
+  pub revenue: i64,
+  /// Added to balances, in nanoUSDT
+  pub deposits: i64,
+}
+
+impl DailyMetrics {
+  pub const HEADER: [&str; 8] = [
+    "date",
+    "new_users",
+    "total_users",
+    "active_users",
+    "new_licenses",
+    "purchases",
+    "revenue_usdt",
+    "deposits_usdt",
+  ];
+
+  pub fn row(&self) -> Vec<String> {
+    let usdt = |nano: i64| format!("{:.2}", nano as f64 / NANO_USDT as f64);
+    vec![
+      self.date.to_string(),
+      self.new_users.to_string(),
+      self.total_users.to_string(),
+      self.active_users.to_string(),
+      self.new_licenses.to_string(),
+      self.purchases.to_string(),
+      usdt(self.revenue),
+      usdt(self.deposits),
+    ]
+  }
+
+  /// Header and the row, all fields are plain numbers so nothing is quoted
+  pub fn to_csv(&self) -> String {
+    format!("{}\n{}\n", Self::HEADER.join(","), self.row().join(","))
+  }
+}
+
+/// Where the daily numbers go
+pub enum Sink {
+  Sheet(Sheets),
+  /// CSV posted to any URL, e.g. a Zapier or Apps Script hook
+  Csv {
+    client: Client,
+    url: String,
+  },
+}
+
+impl Sink {
+  pub fn csv(url: String) -> Self {
+    Sink::Csv { client: Client::new(), url }
+  }
+
+  pub fn name(&self) -> &'static str {
+    match self {
+      Sink::Sheet(_) => "google sheet",
+      Sink::Csv { .. } => "csv url",
+    }
+  }
+
+  pub async fn send(&self, metrics: &DailyMetrics) -> Result<()> {
+    match self {
+      Sink::Sheet(sheets) => sheets.append(&metrics.row()).await,
+      Sink::Csv { client, url } => {
+        client
+          .post(url)
+          .header("Content-Type", "text/csv")
+          .body(metrics.to_csv())
+          .send()
+          .await
+          .and_then(|r| r.error_for_status())
+          .map_err(|e| Error::Export(format!("CSV upload failed: {}", e)))?;
+        Ok(())
+      }
+    }
+  }
+}
+
+pub struct Report<'a> {
+  db: &'a DatabaseConnection,
+}
+
+impl<'a> Report<'a> {
+  pub fn new(db: &'a DatabaseConnection) -> Self {
+    Self { db }
+  }
+
+  pub async fn daily(&self, date: NaiveDate) -> Result<DailyMetrics> {
+    let from = date.and_hms_opt(0, 0, 0).expect("midnight exists");
+    let to = from + TimeDelta::days(1);
+
+    let new_users = user::Entity::find()
+      .filter(user::Column::RegDate.gte(from))
+      .filter(user::Column::RegDate.lt(to))
+      .count(self.db)
+      .await?;
+    let total_users = user::Entity::find()
+      .filter(user::Column::RegDate.lt(to))
+      .filter(user::Column::DeletedAt.is_null())
+      .count(self.db)
+      .await?;
+    let new_licenses = license::Entity::find()
+      .filter(license::Column::CreatedAt.gte(from))
+      .filter(license::Column::CreatedAt.lt(to))
+      .filter(license::Column::IsHoneypot.eq(false))
+      .filter(license::Column::LoanedBy.is_null())
+      .count(self.db)
+      .await?;
+
+    let keys: HashSet<String> = session::Entity::find()
+      .filter(session::Column::StartedAt.lt(to))
+      .filter(
+        Condition::any()
+          .add(session::Column::EndedAt.is_null())
+          .add(session::Column::EndedAt.gte(from)),
+      )
+      .all(self.db)
+      .await?
+      .into_iter()
+      .map(|s| s.license_key)
+      .collect();
+    let active_users = license::Entity::find()
+      .filter(license::Column::Key.is_in(keys))
+      .filter(license::Column::LoanedBy.is_null())
+      .all(self.db)
+      .await?
+      .into_iter()
+      .map(|l| l.tg_user_id)
+      .collect::<HashSet<_>>()
+      .len() as u64;
+
+    let txs = transaction::Entity::find()
+      .filter(transaction::Column::CreatedAt.gte(from))
+      .filter(transaction::Column::CreatedAt.lt(to))
+      .all(self.db)
+      .await?;
+    let purchases: Vec<_> =
+      txs.iter().filter(|tx| tx.tx_type == TransactionType::Purchase).collect();
+    let refunds: i64 = txs
+      .iter()
+      .filter(|tx| tx.tx_type == TransactionType::Refund)
+      .map(|tx| tx.amount)
+      .sum();
+    let revenue = purchases.iter().map(|tx| -tx.amount).sum::<i64>() - refunds;
+    let deposits = txs
+      .iter()
+      .filter(|tx| tx.tx_type == TransactionType::Deposit)
+      .map(|tx| tx.amount)
+      .sum();
+
+    Ok(DailyMetrics {
+      date,
+      new_users,
+      total_users,
+      active_users,
+      new_licenses,
+      purchases: purchases.len() as u64,
+      revenue,
+      deposits,
+    })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{
+    entity::LicenseType,
+    sv::{self, test_utils::test_db},
+  };
+
+  #[tokio::test]
+  async fn test_daily_metrics() {
+    let db = test_db::setup().await;
+    let sv = Report::new(&db);
+
+    let license =
+      sv::License::new(&db).create(1, LicenseType::Pro, 30).await.unwrap();
+    sv::Balance::new(&db).deposit(1, 20 * NANO_USDT, None).await.unwrap();
+    sv::License::new(&db).create(2, LicenseType::Pro, 30).await.unwrap();
+    sv::Session::new(&db)
+      .start(&license.key, "s1", None, None, None, None)
+      .await
+      .unwrap();
+
+    let today = Utc::now().date_naive();
+    let metrics = sv.daily(today).await.unwrap();
+    assert_eq!(metrics.new_licenses, 2);
+    assert_eq!(metrics.active_users, 1);
+    assert_eq!(metrics.deposits, 20 * NANO_USDT);
+
+    let csv = metrics.to_csv();
+    let mut lines = csv.lines();
+    assert_eq!(lines.next().unwrap(), DailyMetrics::HEADER.join(","));
+    assert!(lines.next().unwrap().ends_with(",0.00,20.00"));
+
+    let yesterday = sv.daily(today - TimeDelta::days(1)).await.unwrap();
+    assert_eq!(yesterday.new_licenses, 0);
+    assert_eq!(yesterday.active_users, 0);
+  }
+}
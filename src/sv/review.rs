@@ -0,0 +1,177 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+  entity::{
+    UserRole,
+    review::{self, ReviewKind, ReviewStatus},
+  },
+  prelude::*,
+};
+
+/// What resumes once a review is approved
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Action {
+  /// The purchase screen the user saw, it is updated with the outcome
+  Purchase {
+    product: String,
+    plan: String,
+    chat_id: i64,
+    message_id: i32,
+  },
+  Extension {
+    key: String,
+    plan: String,
+    chat_id: i64,
+    message_id: i32,
+  },
+  Payout {
+    amount: i64,
+  },
+  RoleChange {
+    role: UserRole,
+  },
+  /// License handed over by the user the review is for
+  Transfer {
+    key: String,
+    to: i64,
+  },
+}
+
+impl Action {
+  pub fn kind(&self) -> ReviewKind {
+    match self {
+      Action::Purchase { .. } => ReviewKind::Purchase,
+      Action::Extension { .. } => ReviewKind::Extension,
+      Action::Payout { .. } => ReviewKind::Payout,
+      Action::RoleChange { .. } => ReviewKind::RoleChange,
+      Action::Transfer { .. } => ReviewKind::Transfer,
+    }
+  }
+}
+
+pub fn action(review: &review::Model) -> Result<Action> {
+  json::from_value(review.action.clone())
+    .map_err(|e| Error::Internal(format!("Review #{}: {}", review.id, e)))
+}
+
+pub struct Reviews<'a> {
+  db: &'a DatabaseConnection,
+}
+
+impl<'a> Reviews<'a> {
+  pub fn new(db: &'a DatabaseConnection) -> Self {
+    Self { db }
+  }
+
+  /// Queue the action, unless the user already has one of its kind waiting.
+  /// The flag tells whether a new review was created.
+  pub async fn submit(
+    &self,
+    tg_user_id: i64,
+    action: Action,
+    reason: &str,
+    requested_by: Option<i64>,
+  ) -> Result<(review::Model, bool)> {
+    let kind = action.kind();
+    let waiting = review::Entity::find()
+      .filter(review::Column::TgUserId.eq(tg_user_id))
+      .filter(review::Column::Kind.eq(kind))
+      .filter(review::Column::Status.eq(ReviewStatus::Pending))
+      .one(self.db)
+      .await?;
+    if let Some(review) = waiting {
+      return Ok((review, false));
+    }
+
+    let review = review::ActiveModel {
+      id: NotSet,
+      tg_user_id: Set(tg_user_id),
+      kind: Set(kind),
+      action: Set(
+        json::to_value(&action).map_err(|e| Error::Internal(e.to_string()))?,
+      ),
+      reason: Set(reason.to_string()),
+      requested_by: Set(requested_by),
+      status: Set(ReviewStatus::Pending),
+      created_at: Set(Utc::now().naive_utc()),
+      decided_at: Set(None),
+      decided_by: Set(None),
+    };
+    Ok((review.insert(self.db).await?, true))
+  }
+
+  /// Oldest first
+  pub async fn pending(&self) -> Result<Vec<review::Model>> {
+    Ok(
+      review::Entity::find()
+        .filter(review::Column::Status.eq(ReviewStatus::Pending))
+        .order_by_asc(review::Column::Id)
+        .all(self.db)
+        .await?,
+    )
+  }
+
+  /// Approve or reject a pending review, only one admin gets to decide
+  pub async fn decide(
+    &self,
+    id: i32,
+    admin_id: i64,
+    approve: bool,
+  ) -> Result<review::Model> {
+    let status =
+      if approve { ReviewStatus::Approved } else { ReviewStatus::Rejected };
+    let result = review::Entity::update_many()
+      .col_expr(review::Column::Status, status.into())
+      .col_expr(review::Column::DecidedAt, Utc::now().naive_utc().into())
+      .col_expr(review::Column::DecidedBy, admin_id.into())
+      .filter(review::Column::Id.eq(id))
+      .filter(review::Column::Status.eq(ReviewStatus::Pending))
+      .exec(self.db)
+      .await?;
+
+    let review = review::Entity::find_by_id(id)
+      .one(self.db)
+      .await?
+      .ok_or_else(|| Error::InvalidArgs(format!("Review #{} not found", id)))?;
+    if result.rows_affected == 0 {
+      return Err(Error::InvalidArgs(format!(
+        "Review #{} was already {:?}",
+        id, review.status
+      )));
+    }
+    Ok(review)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::sv::test_utils::test_db;
+
+  #[tokio::test]
+  async fn test_review_is_decided_once() {
+    let db = test_db::setup().await;
+    let sv = Reviews::new(&db);
+
+    let payout = Action::Payout { amount: 5 };
+    let (review, created) =
+      sv.submit(1, payout.clone(), "risk 70", Some(9)).await.unwrap();
+    assert!(created);
+    // one waiting review of a kind per user
+    let (again, created) =
+      sv.submit(1, payout, "risk 70", Some(9)).await.unwrap();
+    assert!(!created);
+    assert_eq!(again.id, review.id);
+
+    let role = Action::RoleChange { role: UserRole::Creator };
+    sv.submit(1, role, "risk 70", Some(9)).await.unwrap();
+    assert_eq!(sv.pending().await.unwrap().len(), 2);
+
+    let approved = sv.decide(review.id, 7, true).await.unwrap();
+    assert_eq!(approved.status, ReviewStatus::Approved);
+    assert_eq!(action(&approved).unwrap(), Action::Payout { amount: 5 });
+    assert!(sv.decide(review.id, 8, false).await.is_err());
+    assert_eq!(sv.pending().await.unwrap().len(), 1);
+  }
+}
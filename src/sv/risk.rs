@@ -0,0 +1,252 @@
+use std::collections::HashSet;
+
+use sea_orm::sea_query::OnConflict;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+  entity::{
+    LicenseType, TransactionType, license, license_device, pending_invoice,
+    risk_score, transaction,
+  },
+  prelude::*,
+};
+
+/// What raised a user's score
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Signal {
+  /// A trial ran on a machine where another account's trial ran too
+  TrialAbuse,
+  /// Machines shared with other accounts
+  SharedHwid,
+  /// Balance operations that were reversed
+  Refunds,
+  /// Regional keys used abroad, or devices all over the world
+  CountryMismatch,
+  /// Invoices that expired unpaid
+  FailedPayments,
+}
+
+impl Signal {
+  pub fn label(self) -> &'static str {
+    match self {
+      Signal::TrialAbuse => "trial abuse",
+      Signal::SharedHwid => "shared HWIDs",
+      Signal::Refunds => "refunds",
+      Signal::CountryMismatch => "country mismatch",
+      Signal::FailedPayments => "failed payments",
+    }
+  }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Factor {
+  pub signal: Signal,
+  pub points: i32,
+}
+
+/// Distinct device countries that count as a mismatch on their own
+const MAX_COUNTRIES: usize = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Level {
+  Low,
+  Medium,
+  High,
+}
+
+impl Level {
+  pub fn of(score: i32) -> Self {
+    match score {
+      ..30 => Level::Low,
+      30..60 => Level::Medium,
+      _ => Level::High,
+    }
+  }
+
+  pub fn icon(self) -> &'static str {
+    match self {
+      Level::Low => "🟢",
+      Level::Medium => "🟡",
+      Level::High => "🔴",
+    }
+  }
+}
+
+/// Stored factors of a score
+pub fn factors(score: &risk_score::Model) -> Vec<Factor> {
+  json::from_value(score.factors.clone()).unwrap_or_default()
+}
+
+pub struct Risk<'a> {
+  db: &'a DatabaseConnection,
+}
+
+impl<'a> Risk<'a> {
+  pub fn new(db: &'a DatabaseConnection) -> Self {
+    Self { db }
+  }
+
+  pub async fn get(
+    &self,
+    tg_user_id: i64,
+  ) -> Result<Option<risk_score::Model>> {
+    Ok(risk_score::Entity::find_by_id(tg_user_id).one(self.db).await?)
+  }
+
+  /// Score of the user, 0 if never assessed
+  pub async fn score(&self, tg_user_id: i64) -> Result<i32> {
+    Ok(self.get(tg_user_id).await?.map_or(0, |risk| risk.score))
+  }
+
+  /// Evaluate every signal of the user
+  pub async fn assess(&self, tg_user_id: i64) -> Result<Vec<Factor>> {
+    let mut factors = Vec::new();
+    let mut add = |signal, points: i32| {
+      if points > 0 {
+        factors.push(Factor { signal, points });
+      }
+    };
+
+    let licenses = license::Entity::find()
+      .filter(license::Column::TgUserId.eq(tg_user_id))
+      .all(self.db)
+      .await?;
+    let keys: Vec<_> = licenses.iter().map(|l| l.key.clone()).collect();
+    let devices = license_device::Entity::find()
+      .filter(license_device::Column::LicenseKey.is_in(keys.clone()))
+      .all(self.db)
+      .await?;
+
+    let hwids: HashSet<_> = devices.iter().map(|d| d.hwid.clone()).collect();
+    let others = license_device::Entity::find()
+      .filter(license_device::Column::Hwid.is_in(hwids))
+      .filter(license_device::Column::LicenseKey.is_not_in(keys))
+      .find_also_related(license::Entity)
+      .all(self.db)
+      .await?;
+    let others: Vec<_> = others
+      .into_iter()
+      .filter_map(|(_, license)| license)
+      .filter(|license| license.tg_user_id != tg_user_id)
+      .collect();
+
+    let accounts: HashSet<_> = others.iter().map(|l| l.tg_user_id).collect();
+    add(Signal::SharedHwid, (accounts.len() as i32 * 15).min(30));
+
+    let on_trial =
+      licenses.iter().any(|l| l.license_type == LicenseType::Trial);
+    if on_trial && others.iter().any(|l| l.license_type == LicenseType::Trial) {
+      add(Signal::TrialAbuse, 35);
+    }
+
+    let regions: HashMap<_, _> = licenses
+      .iter()
+      .filter_map(|l| l.region.as_ref().map(|region| (&l.key, region)))
+      .collect();
+    let used_abroad = devices.iter().any(|d| {
+      matches!(
+        (regions.get(&d.license_key), &d.country),
+        (Some(region), Some(country)) if *region != country
+      )
+    });
+    let countries: HashSet<_> =
+      devices.iter().filter_map(|d| d.country.as_ref()).collect();
+    if used_abroad || countries.len() >= MAX_COUNTRIES {
+      add(Signal::CountryMismatch, 25);
+    }
+
+    let refunds = transaction::Entity::find()
+      .filter(transaction::Column::UserId.eq(tg_user_id))
+      .filter(
+        transaction::Column::TxType
+          .is_in([TransactionType::Reversal, TransactionType::Refund]),
+      )
+      .count(self.db)
+      .await?;
+    add(Signal::Refunds, (refunds as i32 * 10).min(30));
+
+    let expired = pending_invoice::Entity::find()
+      .filter(pending_invoice::Column::UserId.eq(tg_user_id))
+      .filter(pending_invoice::Column::ExpiresAt.lte(Utc::now().naive_utc()))
+      .count(self.db)
+      .await?;
+    add(Signal::FailedPayments, (expired as i32 * 5).min(20));
+
+    Ok(factors)
+  }
+
+  /// Assess the user again and store the result
+  pub async fn rescore(&self, tg_user_id: i64) -> Result<risk_score::Model> {
+    let factors = self.assess(tg_user_id).await?;
+    let score = factors.iter().map(|f| f.points).sum::<i32>().min(100);
+    let risk = risk_score::Model {
+      tg_user_id,
+      score,
+      factors: json::to_value(&factors)
+        .map_err(|e| Error::Internal(e.to_string()))?,
+      updated_at: Utc::now().naive_utc(),
+    };
+
+    risk_score::Entity::insert(risk_score::ActiveModel::from(risk.clone()))
+      .on_conflict(
+        OnConflict::column(risk_score::Column::TgUserId)
+          .update_columns([
+            risk_score::Column::Score,
+            risk_score::Column::Factors,
+            risk_score::Column::UpdatedAt,
+          ])
+          .to_owned(),
+      )
+      .exec_without_returning(self.db)
+      .await?;
+    Ok(risk)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::sv::{self, test_utils::test_db};
+
+  #[tokio::test]
+  async fn test_shared_trial_machine_raises_score() {
+    let db = test_db::setup().await;
+    let sv = Risk::new(&db);
+    let license = sv::License::new(&db);
+    let device = sv::Device::new(&db);
+
+    let first = license.create(1, LicenseType::Trial, 7).await.unwrap();
+    device.touch(&first.key, "pc", None, None).await.unwrap();
+    assert_eq!(sv.rescore(1).await.unwrap().score, 0);
+
+    let second = license.create(2, LicenseType::Trial, 7).await.unwrap();
+    device.touch(&second.key, "pc", None, None).await.unwrap();
+
+    let risk = sv.rescore(2).await.unwrap();
+    assert_eq!(risk.score, 50);
+    let signals: Vec<_> = factors(&risk).iter().map(|f| f.signal).collect();
+    assert_eq!(signals, [Signal::SharedHwid, Signal::TrialAbuse]);
+    assert_eq!(Level::of(risk.score), Level::Medium);
+
+    // stored score is replaced, not duplicated
+    sv.rescore(2).await.unwrap();
+    assert_eq!(sv.score(2).await.unwrap(), 50);
+    assert_eq!(sv.score(3).await.unwrap(), 0);
+  }
+
+  #[tokio::test]
+  async fn test_refunds_raise_score() {
+    let db = test_db::setup().await;
+    sv::User::new(&db).get_or_create(1).await.unwrap();
+    let balance = sv::Balance::new(&db);
+    balance.deposit(1, 1000, None).await.unwrap();
+    balance.spend(1, 500, None, None).await.unwrap();
+    balance.refund(1, 200, None).await.unwrap();
+
+    let risk = Risk::new(&db).rescore(1).await.unwrap();
+    assert_eq!(risk.score, 10);
+    let signals: Vec<_> = factors(&risk).iter().map(|f| f.signal).collect();
+    assert_eq!(signals, [Signal::Refunds]);
+  }
+}
@@ -0,0 +1,295 @@
+use std::fmt;
+
+use hmac::{Hmac, Mac};
+use reqwest::{Client, Method, Url};
+use sha2::{Digest, Sha256};
+
+use crate::{prelude::*, sv::backup::Snapshot};
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Uploads objects to an S3-compatible bucket with path-style URLs and
+/// SigV4 signatures, which AWS, MinIO, R2 and B2 all accept
+#[derive(Clone)]
+pub struct S3 {
+  client: Client,
+  endpoint: Url,
+  region: String,
+  bucket: String,
+  access_key: String,
+  secret_key: String,
+  /// Prepended to every object key, e.g. `backups/`
+  prefix: String,
+}
+
+impl fmt::Debug for S3 {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_struct("S3")
+      .field("endpoint", &self.endpoint.as_str())
+      .field("region", &self.region)
+      .field("bucket", &self.bucket)
+      .field("prefix", &self.prefix)
+      .finish_non_exhaustive()
+  }
+}
+
+impl S3 {
+  pub fn new(
+    endpoint: &str,
+    region: String,
+    bucket: String,
+    access_key: String,
+    secret_key: String,
+    prefix: String,
+  ) -> Result<Self> {
+    let endpoint = Url::parse(endpoint)
+      .map_err(|e| Error::InvalidArgs(format!("Invalid S3 endpoint: {}", e)))?;
+    Ok(Self {
+      client: Client::new(),
+      endpoint,
+      region,
+      bucket,
+      access_key,
+      secret_key,
+      prefix,
+    })
+  }
+
+  pub fn bucket(&self) -> &str {
+    &self.bucket
+  }
+
+  /// Store `body` under the prefixed `name`, returns the object key
+  pub async fn put(&self, name: &str, body: Vec<u8>) -> Result<String> {
+    let key = format!("{}{}", self.prefix, name);
+    let path = format!("/{}/{}", self.bucket, uri_encode(&key, true));
+    self.send(Method::PUT, &path, "", body).await?;
+    Ok(key)
+  }
+
+  pub async fn delete(&self, name: &str) -> Result<()> {
+    let key = format!("{}{}", self.prefix, name);
+    let path = format!("/{}/{}", self.bucket, uri_encode(&key, true));
+    self.send(Method::DELETE, &path, "", Vec::new()).await?;
+    Ok(())
+  }
+
+  /// Backups under the prefix, names without it, newest first
+  pub async fn list(&self) -> Result<Vec<Snapshot>> {
+    let path = format!("/{}", self.bucket);
+    let mut snapshots = Vec::new();
+    let mut token: Option<String> = None;
+    loop {
+      // parameters sorted by name, as the signature wants them
+      let mut query = String::new();
+      if let Some(token) = &token {
+        query.push_str(&format!(
+          "continuation-token={}&",
+          uri_encode(token, false)
+        ));
+      }
+      query.push_str(&format!(
+        "list-type=2&prefix={}",
+        uri_encode(&self.prefix, false)
+      ));
+
+      let xml = self.send(Method::GET, &path, &query, Vec::new()).await?;
+      for contents in elements(&xml, "Contents") {
+        let (Some(key), Some(size)) =
+          (elements(contents, "Key").next(), elements(contents, "Size").next())
+        else {
+          continue;
+        };
+        let name = key.strip_prefix(&self.prefix).unwrap_or(key);
+        if let Some(snapshot) = Snapshot::new(name, size.parse().unwrap_or(0)) {
+          snapshots.push(snapshot);
+        }
+      }
+
+      token =
+        elements(&xml, "NextContinuationToken").next().map(str::to_string);
+      if elements(&xml, "IsTruncated").next() != Some("true") || token.is_none()
+      {
+        break;
+      }
+    }
+    snapshots.sort_by_key(|s| std::cmp::Reverse(s.created_at));
+    Ok(snapshots)
+  }
+
+  /// Signed request, Ok carries the response body
+  async fn send(
+    &self,
+    method: Method,
+    path: &str,
+    query: &str,
+    body: Vec<u8>,
+  ) -> Result<String> {
+    let mut url = self.endpoint.clone();
+    url.set_path(path);
+    url.set_query((!query.is_empty()).then_some(query));
+
+    let host = match (url.host_str(), url.port()) {
+      (Some(host), Some(port)) => format!("{}:{}", host, port),
+      (Some(host), None) => host.to_string(),
+      (None, _) => {
+        return Err(Error::Internal("S3 endpoint has no host".into()));
+      }
+    };
+    let now = Utc::now().naive_utc();
+    let payload_hash = hex::encode(Sha256::digest(&body));
+    let authorization = self.authorization(
+      method.as_str(),
+      path,
+      query,
+      &host,
+      &payload_hash,
+      now,
+    );
+
+    let res = self
+      .client
+      .request(method.clone(), url)
+      .header("x-amz-content-sha256", &payload_hash)
+      .header("x-amz-date", now.format("%Y%m%dT%H%M%SZ").to_string())
+      .header("authorization", authorization)
+      .body(body)
+      .send()
+      .await
+      .map_err(|e| Error::Internal(format!("S3 {} failed: {}", method, e)))?;
+    let status = res.status();
+    let text = res.text().await.unwrap_or_default();
+    if !status.is_success() {
+      return Err(Error::Internal(format!(
+        "S3 {} failed with {}: {}",
+        method,
+        status,
+        text.chars().take(200).collect::<String>()
+      )));
+    }
+    Ok(text)
+  }
+
+  /// `Authorization` header of a request signing the host, the payload hash
+  /// and the date
+  fn authorization(
+    &self,
+    method: &str,
+    path: &str,
+    query: &str,
+    host: &str,
+    payload_hash: &str,
+    now: DateTime,
+  ) -> String {
+    let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+    let date = now.format("%Y%m%d").to_string();
+    let signed_headers = "host;x-amz-content-sha256;x-amz-date";
+    let canonical = format!(
+      "{}\n{}\n{}\nhost:{}\nx-amz-content-sha256:{}\nx-amz-date:{}\n\n{}\n{}",
+      method,
+      path,
+      query,
+      host,
+      payload_hash,
+      amz_date,
+      signed_headers,
+      payload_hash
+    );
+    let scope = format!("{}/{}/s3/aws4_request", date, self.region);
+    let string_to_sign = format!(
+      "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+      amz_date,
+      scope,
+      hex::encode(Sha256::digest(canonical.as_bytes()))
+    );
+    let key = signing_key(&self.secret_key, &date, &self.region, "s3");
+    let signature = hex::encode(hmac(&key, string_to_sign.as_bytes()));
+    format!(
+      "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+      self.access_key, scope, signed_headers, signature
+    )
+  }
+}
+
+fn hmac(key: &[u8], data: &[u8]) -> Vec<u8> {
+  let mut mac =
+    HmacSha256::new_from_slice(key).expect("HMAC accepts keys of any size");
+  mac.update(data);
+  mac.finalize().into_bytes().to_vec()
+}
+
+fn signing_key(
+  secret: &str,
+  date: &str,
+  region: &str,
+  service: &str,
+) -> Vec<u8> {
+  let key = hmac(format!("AWS4{}", secret).as_bytes(), date.as_bytes());
+  let key = hmac(&key, region.as_bytes());
+  let key = hmac(&key, service.as_bytes());
+  hmac(&key, b"aws4_request")
+}
+
+/// Percent-encode the way SigV4 expects, slashes are kept in paths only
+fn uri_encode(value: &str, path: bool) -> String {
+  value
+    .bytes()
+    .map(|b| match b {
+      b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+        (b as char).to_string()
+      }
+      b'/' if path => "/".to_string(),
+      _ => format!("%{:02X}", b),
+    })
+    .collect()
+}
+
+/// Text of every `<name>` element, enough for the flat XML S3 answers with
+fn elements<'a>(xml: &'a str, name: &str) -> impl Iterator<Item = &'a str> {
+  let (open, close) = (format!("<{}>", name), format!("</{}>", name));
+  let mut rest = xml;
+  std::iter::from_fn(move || {
+    let start = rest.find(&open)? + open.len();
+    let len = rest[start..].find(&close)?;
+    let text = &rest[start..start + len];
+    rest = &rest[start + len + close.len()..];
+    Some(text)
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_signing() {
+    // example from the AWS SigV4 documentation
+    let key = signing_key(
+      "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY",
+      "20120215",
+      "us-east-1",
+      "iam",
+    );
+    assert_eq!(
+      hex::encode(key),
+      "f4780e2d9f65fa895f9c67b32ce1baf0b0d8a43505a000a1a9e090d414db404d"
+    );
+
+    assert_eq!(uri_encode("backups/a b+c.db", true), "backups/a%20b%2Bc.db");
+    assert_eq!(uri_encode("backups/", false), "backups%2F");
+  }
+
+  #[test]
+  fn test_list_xml() {
+    let xml = "<ListBucketResult><IsTruncated>false</IsTruncated>\
+      <Contents><Key>backups/backup_2026-01-05_00-00-00.db</Key>\
+      <Size>4096</Size></Contents><Contents><Key>backups/x</Key>\
+      <Size>1</Size></Contents></ListBucketResult>";
+    let keys: Vec<_> = elements(xml, "Contents")
+      .filter_map(|contents| elements(contents, "Key").next())
+      .collect();
+    assert_eq!(keys, ["backups/backup_2026-01-05_00-00-00.db", "backups/x"]);
+    assert_eq!(elements(xml, "IsTruncated").next(), Some("false"));
+    assert_eq!(elements(xml, "Missing").next(), None);
+  }
+}
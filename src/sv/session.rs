@@ -0,0 +1,203 @@
+use sea_orm::sea_query::Expr;
+
+use crate::{entity::session, prelude::*};
+
+pub struct Session<'a> {
+  db: &'a DatabaseConnection,
+}
+
+impl<'a> Session<'a> {
+  pub fn new(db: &'a DatabaseConnection) -> Self {
+    Self { db }
+  }
+
+  /// Record a new session. A reused id closes its open row and gets a new
+  /// one, the history of the old one stays
+  pub async fn start(
+    &self,
+    key: &str,
+    session_id: &str,
+    hwid: Option<&str>,
+    platform: Option<&str>,
+    ip: Option<&str>,
+    country: Option<&str>,
+  ) -> Result<()> {
+    let now = Utc::now().naive_utc();
+    let txn = self.db.begin().await?;
+    session::Entity::update_many()
+      .col_expr(session::Column::EndedAt, now.into())
+      .col_expr(session::Column::EndReason, "reused".into())
+      .filter(session::Column::SessionId.eq(session_id))
+      .filter(session::Column::EndedAt.is_null())
+      .exec(&txn)
+      .await?;
+    session::ActiveModel {
+      id: NotSet,
+      session_id: Set(session_id.to_string()),
+      license_key: Set(key.to_string()),
+      hwid: Set(hwid.map(str::to_string)),
+      platform: Set(platform.map(str::to_string)),
+      ip: Set(ip.map(str::to_string)),
+      country: Set(country.map(str::to_string)),
+      started_at: Set(now),
+      last_seen: Set(now),
+      ended_at: Set(None),
+      end_reason: Set(None),
+      xp: Set(0),
+    }
+    .insert(&txn)
+    .await?;
+    txn.commit().await?;
+    Ok(())
+  }
+
+  /// Flush last_seen of live sessions in one transaction
+  pub async fn touch(&self, seen: &[(String, DateTime)]) -> Result<()> {
+    if seen.is_empty() {
+      return Ok(());
+    }
+
+    let txn = self.db.begin().await?;
+    for (session_id, last_seen) in seen {
+      session::Entity::update_many()
+        .col_expr(session::Column::LastSeen, (*last_seen).into())
+        .filter(session::Column::SessionId.eq(session_id.as_str()))
+        .filter(session::Column::EndedAt.is_null())
+        .exec(&txn)
+        .await?;
+    }
+    txn.commit().await?;
+    Ok(())
+  }
+
+  pub async fn end(&self, session_id: &str, reason: &str) -> Result<()> {
+    session::Entity::update_many()
+      .col_expr(session::Column::EndedAt, Utc::now().naive_utc().into())
+      .col_expr(session::Column::EndReason, reason.into())
+      .filter(session::Column::SessionId.eq(session_id))
+      .filter(session::Column::EndedAt.is_null())
+      .exec(self.db)
+      .await?;
+    Ok(())
+  }
+
+  /// End every open session of the license
+  pub async fn end_license(&self, key: &str, reason: &str) -> Result<()> {
+    session::Entity::update_many()
+      .col_expr(session::Column::EndedAt, Utc::now().naive_utc().into())
+      .col_expr(session::Column::EndReason, reason.into())
+      .filter(session::Column::LicenseKey.eq(key))
+      .filter(session::Column::EndedAt.is_null())
+      .exec(self.db)
+      .await?;
+    Ok(())
+  }
+
+  /// Close open sessions silent for `lifetime`, they end at their last
+  /// heartbeat. Returns how many were closed.
+  pub async fn expire_stale(&self, lifetime: TimeDelta) -> Result<u64> {
+    let cutoff = Utc::now().naive_utc() - lifetime;
+    let res = session::Entity::update_many()
+      .col_expr(
+        session::Column::EndedAt,
+        Expr::col(session::Column::LastSeen).into(),
+      )
+      .col_expr(session::Column::EndReason, "expired".into())
+      .filter(session::Column::LastSeen.lt(cutoff))
+      .filter(session::Column::EndedAt.is_null())
+      .exec(self.db)
+      .await?;
+    Ok(res.rows_affected)
+  }
+
+  /// Sessions that have not ended yet
+  pub async fn active(&self) -> Result<Vec<session::Model>> {
+    Ok(
+      session::Entity::find()
+        .filter(session::Column::EndedAt.is_null())
+        .all(self.db)
+        .await?,
+    )
+  }
+
+  /// Latest sessions of the license, newest first
+  pub async fn history(
+    &self,
+    key: &str,
+    limit: u64,
+  ) -> Result<Vec<session::Model>> {
+    Ok(
+      session::Entity::find()
+        .filter(session::Column::LicenseKey.eq(key))
+        .order_by_desc(session::Column::StartedAt)
+        .limit(limit)
+        .all(self.db)
+        .await?,
+    )
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{
+    entity::LicenseType,
+    sv::{self, test_utils::test_db},
+  };
+
+  #[tokio::test]
+  async fn test_sessions_outlive_memory() {
+    let db = test_db::setup().await;
+    let sv = Session::new(&db);
+
+    let license =
+      sv::License::new(&db).create(1, LicenseType::Pro, 30).await.unwrap();
+    let key = license.key.as_str();
+
+    sv.start(key, "a", Some("hw"), Some("linux-x64"), Some("10.0.0.1"), None)
+      .await
+      .unwrap();
+    sv.start(key, "b", None, None, None, None).await.unwrap();
+    assert_eq!(sv.active().await.unwrap().len(), 2);
+
+    // "a" keeps beating, "b" went silent an hour ago
+    let now = Utc::now().naive_utc();
+    sv.touch(&[("a".into(), now), ("b".into(), now - TimeDelta::hours(1))])
+      .await
+      .unwrap();
+    assert_eq!(sv.expire_stale(TimeDelta::minutes(2)).await.unwrap(), 1);
+
+    let active = sv.active().await.unwrap();
+    assert_eq!(active.len(), 1);
+    assert_eq!(active[0].session_id, "a");
+
+    sv.end_license(key, "dropped").await.unwrap();
+    assert!(sv.active().await.unwrap().is_empty());
+
+    let history = sv.history(key, 10).await.unwrap();
+    assert_eq!(history.len(), 2);
+    let b = history.iter().find(|s| s.session_id == "b").unwrap();
+    assert_eq!(b.end_reason.as_deref(), Some("expired"));
+    assert_eq!(b.ended_at, Some(b.last_seen));
+    let a = history.iter().find(|s| s.session_id == "a").unwrap();
+    assert_eq!(a.ip.as_deref(), Some("10.0.0.1"));
+
+    // a reused id opens a new row and leaves the old one be
+    let other =
+      sv::License::new(&db).create(2, LicenseType::Pro, 30).await.unwrap();
+    sv.start(key, "b", None, None, None, None).await.unwrap();
+    sv.start(&other.key, "b", None, None, Some("10.0.0.2"), None)
+      .await
+      .unwrap();
+    let active = sv.active().await.unwrap();
+    assert_eq!(active.len(), 1);
+    assert_eq!(active[0].license_key, other.key);
+    assert_eq!(active[0].xp, 0);
+
+    let history = sv.history(key, 10).await.unwrap();
+    assert_eq!(history.len(), 3);
+    let reasons: Vec<_> =
+      history.iter().filter_map(|s| s.end_reason.as_deref()).collect();
+    assert!(reasons.contains(&"expired") && reasons.contains(&"reused"));
+  }
+}
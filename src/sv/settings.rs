@@ -0,0 +1,287 @@
+use std::collections::HashSet;
+
+use crate::{
+  entity::{build::Channel, user_settings},
+  prelude::*,
+  sv,
+};
+
+pub struct Settings<'a> {
+  db: &'a DatabaseConnection,
+}
+
+impl<'a> Settings<'a> {
+  pub fn new(db: &'a DatabaseConnection) -> Self {
+    Self { db }
+  }
+
+  pub async fn get_or_create(
+    &self,
+    tg_user_id: i64,
+  ) -> Result<user_settings::Model> {
+    if let Some(settings) =
+      user_settings::Entity::find_by_id(tg_user_id).one(self.db).await?
+    {
+      return Ok(settings);
+    }
+
+    sv::User::new(self.db).get_or_create(tg_user_id).await?;
+
+    let settings = user_settings::ActiveModel {
+      tg_user_id: Set(tg_user_id),
+      language: Set("en".to_string()),
+      onboarded_at: Set(None),
+      downtime_alert_mins: Set(None),
+      farm_from_hour: Set(None),
+      farm_to_hour: Set(None),
+      security_alerts: Set(true),
+      utc_offset_mins: Set(0),
+      country: Set(None),
+      channel: Set(Channel::Stable),
+      quiet_from_hour: Set(None),
+      quiet_to_hour: Set(None),
+      leaderboard: Set(false),
+    };
+
+    Ok(settings.insert(self.db).await?)
+  }
+
+  pub async fn set_language(&self, tg_user_id: i64, lang: &str) -> Result<()> {
+    let settings = self.get_or_create(tg_user_id).await?;
+
+    user_settings::ActiveModel {
+      language: Set(lang.to_string()),
+      ..settings.into()
+    }
+    .update(self.db)
+    .await?;
+
+    Ok(())
+  }
+
+  pub async fn is_onboarded(&self, tg_user_id: i64) -> Result<bool> {
+    Ok(self.get_or_create(tg_user_id).await?.onboarded_at.is_some())
+  }
+
+  /// Mark the onboarding wizard as done so it never runs again
+  pub async fn mark_onboarded(&self, tg_user_id: i64) -> Result<()> {
+    let settings = self.get_or_create(tg_user_id).await?;
+    if settings.onboarded_at.is_some() {
+      return Ok(());
+    }
+
+    let now = Utc::now().naive_utc();
+    user_settings::ActiveModel {
+      onboarded_at: Set(Some(now)),
+      ..settings.into()
+    }
+    .update(self.db)
+    .await?;
+
+    Ok(())
+  }
+
+  /// Enable downtime alerts after `mins` of silence, `None` disables them.
+  /// `hours` limits alerts to a UTC window like 8..23 (may wrap midnight).
+  pub async fn set_downtime_alert(
+    &self,
+    tg_user_id: i64,
+    mins: Option<i32>,
+    hours: Option<(i32, i32)>,
+  ) -> Result<user_settings::Model> {
+    let settings = self.get_or_create(tg_user_id).await?;
+
+    Ok(
+      user_settings::ActiveModel {
+        downtime_alert_mins: Set(mins),
+        farm_from_hour: Set(hours.map(|(from, _)| from)),
+        farm_to_hour: Set(hours.map(|(_, to)| to)),
+        ..settings.into()
+      }
+      .update(self.db)
+      .await?,
+    )
+  }
+
+  pub async fn set_security_alerts(
+    &self,
+    tg_user_id: i64,
+    enabled: bool,
+  ) -> Result<()> {
+    let settings = self.get_or_create(tg_user_id).await?;
+
+    user_settings::ActiveModel {
+      security_alerts: Set(enabled),
+      ..settings.into()
+    }
+    .update(self.db)
+    .await?;
+
+    Ok(())
+  }
+
+  pub async fn set_utc_offset(
+    &self,
+    tg_user_id: i64,
+    offset_mins: i32,
+  ) -> Result<user_settings::Model> {
+    let settings = self.get_or_create(tg_user_id).await?;
+
+    Ok(
+      user_settings::ActiveModel {
+        utc_offset_mins: Set(offset_mins),
+        ..settings.into()
+      }
+      .update(self.db)
+      .await?,
+    )
+  }
+
+  pub async fn set_leaderboard(
+    &self,
+    tg_user_id: i64,
+    enabled: bool,
+  ) -> Result<()> {
+    let settings = self.get_or_create(tg_user_id).await?;
+
+    user_settings::ActiveModel { leaderboard: Set(enabled), ..settings.into() }
+      .update(self.db)
+      .await?;
+
+    Ok(())
+  }
+
+  /// Hold non-critical admin alerts between local `from` and `to` hours,
+  /// `None` delivers them right away
+  pub async fn set_quiet_hours(
+    &self,
+    tg_user_id: i64,
+    hours: Option<(i32, i32)>,
+  ) -> Result<user_settings::Model> {
+    let settings = self.get_or_create(tg_user_id).await?;
+
+    Ok(
+      user_settings::ActiveModel {
+        quiet_from_hour: Set(hours.map(|(from, _)| from)),
+        quiet_to_hour: Set(hours.map(|(_, to)| to)),
+        ..settings.into()
+      }
+      .update(self.db)
+      .await?,
+    )
+  }
+
+  /// Self-declared country for regional pricing, `None` to detect it
+  pub async fn set_country(
+    &self,
+    tg_user_id: i64,
+    country: Option<String>,
+  ) -> Result<user_settings::Model> {
+    let settings = self.get_or_create(tg_user_id).await?;
+
+    Ok(
+      user_settings::ActiveModel { country: Set(country), ..settings.into() }
+        .update(self.db)
+        .await?,
+    )
+  }
+
+  pub async fn set_channel(
+    &self,
+    tg_user_id: i64,
+    channel: Channel,
+  ) -> Result<user_settings::Model> {
+    let settings = self.get_or_create(tg_user_id).await?;
+
+    Ok(
+      user_settings::ActiveModel { channel: Set(channel), ..settings.into() }
+        .update(self.db)
+        .await?,
+    )
+  }
+
+  /// Release channel of the user, stable until they opt in
+  pub async fn channel(&self, tg_user_id: i64) -> Result<Channel> {
+    Ok(
+      user_settings::Entity::find_by_id(tg_user_id)
+        .one(self.db)
+        .await?
+        .map(|settings| settings.channel)
+        .unwrap_or_default(),
+    )
+  }
+
+  /// Users following `channel`
+  pub async fn on_channel(&self, channel: Channel) -> Result<HashSet<i64>> {
+    let users: Vec<i64> = user_settings::Entity::find()
+      .select_only()
+      .column(user_settings::Column::TgUserId)
+      .filter(user_settings::Column::Channel.eq(channel))
+      .into_tuple()
+      .all(self.db)
+      .await?;
+    Ok(users.into_iter().collect())
+  }
+
+  /// Settings of users who opted into downtime alerts
+  pub async fn downtime_watchers(&self) -> Result<Vec<user_settings::Model>> {
+    Ok(
+      user_settings::Entity::find()
+        .filter(user_settings::Column::DowntimeAlertMins.is_not_null())
+        .all(self.db)
+        .await?,
+    )
+  }
+}
+
+/// Whether `hour` falls into the farming window, `to` is exclusive
+pub fn in_farming_hours(settings: &user_settings::Model, hour: i32) -> bool {
+  match (settings.farm_from_hour, settings.farm_to_hour) {
+    (Some(from), Some(to)) if from <= to => (from..to).contains(&hour),
+    (Some(from), Some(to)) => hour >= from || hour < to,
+    _ => true,
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::sv::test_utils::test_db;
+
+  #[tokio::test]
+  async fn test_onboarding_runs_once() {
+    let db = test_db::setup().await;
+    let sv = Settings::new(&db);
+
+    assert!(!sv.is_onboarded(12345).await.unwrap());
+
+    sv.set_language(12345, "ru").await.unwrap();
+    sv.mark_onboarded(12345).await.unwrap();
+    let first = sv.get_or_create(12345).await.unwrap();
+    assert_eq!(first.language, "ru");
+    assert!(first.onboarded_at.is_some());
+
+    sv.mark_onboarded(12345).await.unwrap();
+    let second = sv.get_or_create(12345).await.unwrap();
+    assert_eq!(first.onboarded_at, second.onboarded_at);
+  }
+
+  #[tokio::test]
+  async fn test_downtime_alert_window() {
+    let db = test_db::setup().await;
+    let sv = Settings::new(&db);
+
+    let all_day = sv.set_downtime_alert(1, Some(15), None).await.unwrap();
+    assert!(in_farming_hours(&all_day, 3));
+
+    let night =
+      sv.set_downtime_alert(2, Some(30), Some((22, 6))).await.unwrap();
+    assert!(in_farming_hours(&night, 23));
+    assert!(in_farming_hours(&night, 5));
+    assert!(!in_farming_hours(&night, 12));
+
+    sv.set_downtime_alert(1, None, None).await.unwrap();
+    let watchers = sv.downtime_watchers().await.unwrap();
+    assert_eq!(watchers.iter().map(|s| s.tg_user_id).collect::<Vec<_>>(), [2]);
+  }
+}
@@ -0,0 +1,224 @@
+use std::collections::{HashMap, HashSet};
+
+use chrono::NaiveDate;
+use sea_orm::sea_query::OnConflict;
+
+use crate::{
+  entity::{hwid_exemption, license, license_alert, session},
+  prelude::*,
+};
+
+/// How many machines or addresses one license may be seen on within a
+/// window before it is flagged. A limit of 0 ignores that signal.
+#[derive(Debug, Clone, Copy)]
+pub struct Policy {
+  pub max_hwids: u64,
+  pub max_ips: u64,
+  pub window: TimeDelta,
+}
+
+impl Policy {
+  pub fn is_enabled(&self) -> bool {
+    self.max_hwids > 0 || self.max_ips > 0
+  }
+}
+
+#[derive(Default)]
+struct Seen<'s> {
+  hwids: HashSet<&'s str>,
+  ips: HashSet<&'s str>,
+  countries: HashSet<&'s str>,
+  sessions: i32,
+}
+
+pub struct Sharing<'a> {
+  db: &'a DatabaseConnection,
+}
+
+impl<'a> Sharing<'a> {
+  pub fn new(db: &'a DatabaseConnection) -> Self {
+    Self { db }
+  }
+
+  /// Flag licenses over the policy among sessions seen within its window
+  /// before `now`, recorded under the day of `now`. Blocked keys, decoys,
+  /// support loaners and HWID-exempt owners are left out.
+  pub async fn scan(
+    &self,
+    policy: &Policy,
+    now: DateTime,
+  ) -> Result<Vec<license_alert::Model>> {
+    if !policy.is_enabled() {
+      return Ok(Vec::new());
+    }
+
+    let sessions = session::Entity::find()
+      .filter(session::Column::LastSeen.gte(now - policy.window))
+      .all(self.db)
+      .await?;
+    let mut seen: HashMap<&str, Seen> = HashMap::new();
+    for s in &sessions {
+      let entry = seen.entry(s.license_key.as_str()).or_default();
+      entry.hwids.extend(s.hwid.as_deref());
+      entry.ips.extend(s.ip.as_deref());
+      entry.countries.extend(s.country.as_deref());
+      entry.sessions += 1;
+    }
+    let over = |count: usize, max: u64| max > 0 && count as u64 > max;
+    seen.retain(|_, s| {
+      over(s.hwids.len(), policy.max_hwids) || over(s.ips.len(), policy.max_ips)
+    });
+    if seen.is_empty() {
+      return Ok(Vec::new());
+    }
+
+    let licenses = license::Entity::find()
+      .filter(license::Column::Key.is_in(seen.keys().copied()))
+      .all(self.db)
+      .await?;
+    let exempt: HashSet<i64> = hwid_exemption::Entity::find()
+      .filter(hwid_exemption::Column::TgUserId.is_not_null())
+      .all(self.db)
+      .await?
+      .into_iter()
+      .filter_map(|exemption| exemption.tg_user_id)
+      .collect();
+
+    let day = now.date();
+    let txn = self.db.begin().await?;
+    let mut alerts = Vec::new();
+    for license in licenses {
+      if license.is_blocked
+        || license.is_honeypot
+        || license.loaned_by.is_some()
+        || exempt.contains(&license.tg_user_id)
+      {
+        continue;
+      }
+      let Some(s) = seen.get(license.key.as_str()) else {
+        continue;
+      };
+      let alert = license_alert::Model {
+        license_key: license.key.clone(),
+        day,
+        hwids: s.hwids.len() as i32,
+        ips: s.ips.len() as i32,
+        countries: s.countries.len() as i32,
+        sessions: s.sessions,
+        created_at: now,
+      };
+      // a rerun on the same day keeps the latest counts
+      license_alert::Entity::insert(license_alert::ActiveModel::from(
+        alert.clone(),
+      ))
+      .on_conflict(
+        OnConflict::columns([
+          license_alert::Column::LicenseKey,
+          license_alert::Column::Day,
+        ])
+        .update_columns([
+          license_alert::Column::Hwids,
+          license_alert::Column::Ips,
+          license_alert::Column::Countries,
+          license_alert::Column::Sessions,
+          license_alert::Column::CreatedAt,
+        ])
+        .to_owned(),
+      )
+      .exec_without_returning(&txn)
+      .await?;
+      alerts.push(alert);
+    }
+    txn.commit().await?;
+
+    alerts.sort_by_key(|a| std::cmp::Reverse((a.hwids, a.ips)));
+    Ok(alerts)
+  }
+
+  /// Past findings of one license, newest first
+  pub async fn history(
+    &self,
+    key: &str,
+    limit: u64,
+  ) -> Result<Vec<license_alert::Model>> {
+    Ok(
+      license_alert::Entity::find()
+        .filter(license_alert::Column::LicenseKey.eq(key))
+        .order_by_desc(license_alert::Column::Day)
+        .limit(limit)
+        .all(self.db)
+        .await?,
+    )
+  }
+
+  /// Forget findings before `before`
+  pub async fn prune(&self, before: NaiveDate) -> Result<u64> {
+    let result = license_alert::Entity::delete_many()
+      .filter(license_alert::Column::Day.lt(before))
+      .exec(self.db)
+      .await?;
+    Ok(result.rows_affected)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{
+    entity::LicenseType,
+    sv::{self, test_utils::test_db},
+  };
+
+  #[tokio::test]
+  async fn test_scan_flags_shared_keys() {
+    let db = test_db::setup().await;
+    let sv = Sharing::new(&db);
+    let licenses = sv::License::new(&db);
+    let sessions = sv::Session::new(&db);
+
+    let shared = licenses.create(1, LicenseType::Pro, 30).await.unwrap();
+    let normal = licenses.create(2, LicenseType::Pro, 30).await.unwrap();
+    let banned = licenses.create(3, LicenseType::Pro, 30).await.unwrap();
+    licenses.set_blocked(&banned.key, true).await.unwrap();
+
+    for (i, (hwid, ip)) in
+      [("a", "1.1.1.1"), ("b", "2.2.2.2"), ("c", "3.3.3.3")].iter().enumerate()
+    {
+      for key in [&shared.key, &banned.key] {
+        let id = format!("{}-{}", key, i);
+        sessions
+          .start(key, &id, Some(hwid), None, Some(ip), Some("DE"))
+          .await
+          .unwrap();
+      }
+    }
+    // one machine reconnecting from two addresses
+    for (id, ip) in [("n1", "4.4.4.4"), ("n2", "5.5.5.5")] {
+      sessions
+        .start(&normal.key, id, Some("x"), None, Some(ip), None)
+        .await
+        .unwrap();
+    }
+
+    let policy =
+      Policy { max_hwids: 2, max_ips: 0, window: TimeDelta::hours(24) };
+    let now = Utc::now().naive_utc();
+    let alerts = sv.scan(&policy, now).await.unwrap();
+    assert_eq!(alerts.len(), 1);
+    assert_eq!(alerts[0].license_key, shared.key);
+    assert_eq!((alerts[0].hwids, alerts[0].ips), (3, 3));
+    assert_eq!((alerts[0].countries, alerts[0].sessions), (1, 3));
+
+    // rescanning the same day updates the finding instead of adding one
+    sv.scan(&policy, now).await.unwrap();
+    assert_eq!(sv.history(&shared.key, 10).await.unwrap().len(), 1);
+
+    let off = Policy { max_hwids: 0, max_ips: 0, ..policy };
+    assert!(sv.scan(&off, now).await.unwrap().is_empty());
+    // sessions outside the window don't count
+    let later = now + TimeDelta::days(2);
+    assert!(sv.scan(&policy, later).await.unwrap().is_empty());
+
+    assert_eq!(sv.prune(later.date()).await.unwrap(), 1);
+  }
+}
@@ -0,0 +1,144 @@
+use std::{path::Path, sync::Mutex};
+
+use jsonwebtoken::{Algorithm, EncodingKey, Header};
+use reqwest::{Client, Url};
+use serde::{Deserialize, Serialize};
+
+use crate::prelude::*;
+
+pub const SHEETS_URL: &str = "https://sheets.googleapis.com/v4/spreadsheets/";
+const SCOPE: &str = "https://www.googleapis.com/auth/spreadsheets";
+
+/// Fields of a service account key file we need
+#[derive(Debug, Clone, Deserialize)]
+pub struct ServiceAccount {
+  pub client_email: String,
+  pub private_key: String,
+  pub token_uri: String,
+}
+
+#[derive(Serialize)]
+struct Claims<'a> {
+  iss: &'a str,
+  scope: &'a str,
+  aud: &'a str,
+  iat: i64,
+  exp: i64,
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+  access_token: String,
+  expires_in: i64,
+}
+
+/// Appends rows to a Google Sheet as a service account.
+/// The sheet has to be shared with the account's email.
+pub struct Sheets {
+  client: Client,
+  account: ServiceAccount,
+  key: EncodingKey,
+  spreadsheet_id: String,
+  range: String,
+  /// Access token and when it expires
+  token: Mutex<Option<(String, DateTime)>>,
+}
+
+impl Sheets {
+  pub fn new(
+    account: ServiceAccount,
+    spreadsheet_id: String,
+    range: String,
+  ) -> Result<Self> {
+    let key = EncodingKey::from_rsa_pem(account.private_key.as_bytes())
+      .map_err(|e| Error::Export(format!("Invalid private key: {}", e)))?;
+    Ok(Self {
+      client: Client::new(),
+      account,
+      key,
+      spreadsheet_id,
+      range,
+      token: Mutex::new(None),
+    })
+  }
+
+  /// Read the JSON key downloaded from the cloud console
+  pub fn from_key_file(
+    path: impl AsRef<Path>,
+    spreadsheet_id: String,
+    range: String,
+  ) -> Result<Self> {
+    let raw = std::fs::read_to_string(path)?;
+    let account = json::from_str(&raw)
+      .map_err(|e| Error::Export(format!("Invalid key file: {}", e)))?;
+    Self::new(account, spreadsheet_id, range)
+  }
+
+  /// Cached access token, renewed a minute before it expires
+  async fn access_token(&self) -> Result<String> {
+    let now = Utc::now().naive_utc();
+    if let Some((token, expires_at)) = self.token.lock().unwrap().as_ref()
+      && *expires_at - TimeDelta::minutes(1) > now
+    {
+      return Ok(token.clone());
+    }
+
+    let iat = now.and_utc().timestamp();
+    let claims = Claims {
+      iss: &self.account.client_email,
+      scope: SCOPE,
+      aud: &self.account.token_uri,
+      iat,
+      exp: iat + 3600,
+    };
+    let assertion =
+      jsonwebtoken::encode(&Header::new(Algorithm::RS256), &claims, &self.key)
+        .map_err(|e| Error::Export(format!("Failed to sign token: {}", e)))?;
+
+    let response = self
+      .client
+      .post(&self.account.token_uri)
+      .form(&[
+        ("grant_type", "urn:ietf:params:oauth:grant-type:jwt-bearer"),
+        ("assertion", assertion.as_str()),
+      ])
+      .send()
+      .await
+      .and_then(|r| r.error_for_status())
+      .map_err(|e| Error::Export(format!("Token request failed: {}", e)))?;
+    let token: TokenResponse = response
+      .json()
+      .await
+      .map_err(|e| Error::Export(format!("Invalid token response: {}", e)))?;
+
+    let expires_at = now + TimeDelta::seconds(token.expires_in);
+    *self.token.lock().unwrap() =
+      Some((token.access_token.clone(), expires_at));
+    Ok(token.access_token)
+  }
+
+  /// Append one row after the last filled row of the range
+  pub async fn append(&self, row: &[String]) -> Result<()> {
+    let mut url = Url::parse(SHEETS_URL).expect("invalid sheets url");
+    url
+      .path_segments_mut()
+      .expect("sheets url is a base")
+      .pop_if_empty()
+      .push(&self.spreadsheet_id)
+      .push("values")
+      .push(&format!("{}:append", self.range));
+    url.query_pairs_mut().append_pair("valueInputOption", "USER_ENTERED");
+
+    let token = self.access_token().await?;
+    self
+      .client
+      .post(url)
+      .bearer_auth(token)
+      .json(&json::json!({ "values": [row] }))
+      .send()
+      .await
+      .and_then(|r| r.error_for_status())
+      .map_err(|e| Error::Export(format!("Append failed: {}", e)))?;
+    Ok(())
+  }
+}
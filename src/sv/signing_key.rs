@@ -0,0 +1,161 @@
+use base64::{Engine, prelude::BASE64_STANDARD};
+use jsonwebtoken::{Algorithm, EncodingKey, Header};
+use ring::{
+  rand::SystemRandom,
+  signature::{Ed25519KeyPair, KeyPair},
+};
+use serde::Serialize;
+
+use crate::{entity::signing_key, prelude::*};
+
+/// Loaded signing keys, newest first. The first one signs new tokens.
+#[derive(Debug, Clone, Default)]
+pub struct Keyring {
+  keys: Vec<signing_key::Model>,
+}
+
+/// Public half of a key, as published to clients
+#[derive(Debug, Clone, Serialize)]
+pub struct PublicKey {
+  pub kid: String,
+  pub alg: &'static str,
+  pub public_key: String,
+  /// Signs new tokens, the others only verify old ones
+  pub active: bool,
+}
+
+impl Keyring {
+  pub fn public_keys(&self) -> Vec<PublicKey> {
+    self
+      .keys
+      .iter()
+      .enumerate()
+      .map(|(i, key)| PublicKey {
+        kid: key.kid.clone(),
+        alg: "EdDSA",
+        public_key: key.public_key.clone(),
+        active: i == 0,
+      })
+      .collect()
+  }
+
+  /// EdDSA JWT with the active key id in the header
+  pub fn sign<T: Serialize>(&self, claims: &T) -> Result<String> {
+    let key = self
+      .keys
+      .first()
+      .ok_or_else(|| Error::Internal("No signing key".into()))?;
+    let der = BASE64_STANDARD
+      .decode(&key.private_key)
+      .map_err(|e| Error::Internal(format!("Corrupt signing key: {}", e)))?;
+
+    let mut header = Header::new(Algorithm::EdDSA);
+    header.kid = Some(key.kid.clone());
+    jsonwebtoken::encode(&header, claims, &EncodingKey::from_ed_der(&der))
+      .map_err(|e| Error::Internal(format!("Failed to sign token: {}", e)))
+  }
+}
+
+pub struct SigningKeys<'a> {
+  db: &'a DatabaseConnection,
+}
+
+impl<'a> SigningKeys<'a> {
+  pub fn new(db: &'a DatabaseConnection) -> Self {
+    Self { db }
+  }
+
+  /// Generate a key pair that becomes the active one
+  pub async fn rotate(&self) -> Result<signing_key::Model> {
+    let pkcs8 = Ed25519KeyPair::generate_pkcs8(&SystemRandom::new())
+      .map_err(|_| Error::Internal("Failed to generate a key pair".into()))?;
+    let pair = Ed25519KeyPair::from_pkcs8(pkcs8.as_ref())
+      .map_err(|_| Error::Internal("Generated key pair is invalid".into()))?;
+    let public_key = pair.public_key().as_ref();
+
+    // short and stable, enough to tell a handful of keys apart
+    let kid = hex::encode(&public_key[..8]);
+    let key = signing_key::ActiveModel {
+      kid: Set(kid),
+      private_key: Set(BASE64_STANDARD.encode(pkcs8.as_ref())),
+      public_key: Set(BASE64_STANDARD.encode(public_key)),
+      created_at: Set(Utc::now().naive_utc()),
+    };
+    Ok(key.insert(self.db).await?)
+  }
+
+  pub async fn all(&self) -> Result<Vec<signing_key::Model>> {
+    Ok(
+      signing_key::Entity::find()
+        .order_by_desc(signing_key::Column::CreatedAt)
+        .all(self.db)
+        .await?,
+    )
+  }
+
+  /// Stop publishing an old key, tokens it signed no longer verify.
+  /// The active key can only be replaced with `rotate`.
+  pub async fn retire(&self, kid: &str) -> Result<()> {
+    let keys = self.all().await?;
+    match keys.iter().position(|key| key.kid == kid) {
+      None => Err(Error::InvalidArgs(format!("Key {} not found", kid))),
+      Some(0) => {
+        Err(Error::InvalidArgs("Rotate before retiring the active key".into()))
+      }
+      Some(_) => {
+        signing_key::Entity::delete_by_id(kid).exec(self.db).await?;
+        Ok(())
+      }
+    }
+  }
+
+  /// Keys to sign with, generating the first one on a fresh database
+  pub async fn keyring(&self) -> Result<Keyring> {
+    let mut keys = self.all().await?;
+    if keys.is_empty() {
+      info!("Generating the first offline token signing key");
+      keys.push(self.rotate().await?);
+    }
+    Ok(Keyring { keys })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use jsonwebtoken::{DecodingKey, Validation};
+
+  use super::*;
+  use crate::sv::test_utils::test_db;
+
+  #[tokio::test]
+  async fn test_rotate_and_verify() {
+    let db = test_db::setup().await;
+    let sv = SigningKeys::new(&db);
+
+    let first = sv.keyring().await.unwrap();
+    let old_kid = first.public_keys()[0].kid.clone();
+    let claims = json::json!({ "sub": "key", "exp": i64::MAX });
+    let token = first.sign(&claims).unwrap();
+
+    sv.rotate().await.unwrap();
+    let keyring = sv.keyring().await.unwrap();
+    let public = keyring.public_keys();
+    assert_eq!(public.len(), 2);
+    assert!(public[0].active && public[0].kid != old_kid);
+
+    // the old token still verifies with its published key
+    let header = jsonwebtoken::decode_header(&token).unwrap();
+    let key = public.iter().find(|k| Some(&k.kid) == header.kid.as_ref());
+    let raw = BASE64_STANDARD.decode(&key.unwrap().public_key).unwrap();
+    let decoded = jsonwebtoken::decode::<json::Value>(
+      &token,
+      &DecodingKey::from_ed_der(&raw),
+      &Validation::new(Algorithm::EdDSA),
+    );
+    assert!(decoded.is_ok());
+
+    assert!(sv.retire(&public[0].kid).await.is_err());
+    sv.retire(&old_kid).await.unwrap();
+    assert_eq!(sv.keyring().await.unwrap().public_keys().len(), 1);
+  }
+}
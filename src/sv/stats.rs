@@ -1,11 +1,21 @@
 use std::io::Read;
 
 use base64::Engine;
+use chrono::NaiveDate;
 use flate2::read::GzDecoder;
 use json::json;
+use ruzstd::decoding::StreamingDecoder;
+use sea_orm::{
+  Condition,
+  sea_query::{Expr, OnConflict, Query, SelectStatement},
+};
 use serde::{Deserialize, Serialize};
 
-use crate::{entity::*, prelude::*, sv};
+use crate::{
+  entity::{goal::GoalKind, *},
+  prelude::*,
+  sv,
+};
 
 #[derive(Debug, Serialize, Deserialize, Default, Clone)]
 pub struct MetaStats {
@@ -32,13 +42,15 @@ pub struct NetworkMeta {
   pub gc_timeouts: u32,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 #[serde(tag = "type", content = "data")]
 pub enum MetricEvent {
   #[serde(rename = "shutdown")]
   Shutdown { uptime: f64 },
   #[serde(rename = "state")]
   State { state: String, duration: f64 },
+  #[serde(rename = "xp")]
+  Xp { gained: u64 },
   #[serde(rename = "srt")]
   Srt { routes: Vec<String> },
   #[serde(rename = "performance")]
@@ -47,16 +59,266 @@ pub enum MetricEvent {
     avg_ram_mb: Option<u32>,
     avg_ai_ms: Option<f32>,
   },
+  /// Errors the client ran into since its last report. `version` is how
+  /// schema 1 clients name their build, later ones set `build` instead.
+  #[serde(rename = "errors")]
+  Errors {
+    #[serde(default)]
+    version: Option<String>,
+    errors: Vec<String>,
+  },
+}
+
+/// Event types this server understands
+const EVENT_TYPES: &[&str] =
+  &["shutdown", "state", "xp", "srt", "performance", "errors"];
+
+/// Telemetry schema this server speaks. Payloads without `schema_version`
+/// are schema 1, newer ones are read as far as they are understood.
+pub const SCHEMA_VERSION: u32 = 2;
+
+fn legacy_schema() -> u32 {
+  1
+}
+
+/// Compression of a telemetry payload, like `Content-Encoding`. Older
+/// clients only gzip and don't send it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Encoding {
+  #[default]
+  Gzip,
+  Zstd,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct MetricPayload {
+  #[serde(default = "legacy_schema")]
+  pub schema_version: u32,
   #[serde(rename = "type")]
   pub event_type: String,
   pub license_key: String,
+  /// Set by panels that run several instances, older builds omit it
+  #[serde(default)]
+  pub instance_id: Option<String>,
+  /// Heartbeat session the metric belongs to, credits it with the XP
+  #[serde(default)]
+  pub session_id: Option<String>,
+  /// Build version of the client, since schema 2
+  #[serde(default)]
+  pub build: Option<String>,
   pub data: json::Value,
 }
 
+/// Why a telemetry payload was refused, labels the rejection counters
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RejectReason {
+  TooLarge,
+  Encoding,
+  Json,
+  Schema,
+  Invalid,
+  /// An event type of a newer schema, skipped rather than refused
+  Unsupported,
+}
+
+impl RejectReason {
+  pub fn label(self) -> &'static str {
+    match self {
+      RejectReason::TooLarge => "too_large",
+      RejectReason::Encoding => "encoding",
+      RejectReason::Json => "json",
+      RejectReason::Schema => "schema",
+      RejectReason::Invalid => "invalid",
+      RejectReason::Unsupported => "unsupported",
+    }
+  }
+}
+
+/// A payload `Stats::decode_metric` refused, with the schema it claimed
+/// once that is known
+#[derive(Debug)]
+pub struct Rejected {
+  pub reason: RejectReason,
+  pub schema_version: Option<u32>,
+  pub error: Error,
+}
+
+impl MetricPayload {
+  /// Whether the event is one this server knows, newer clients may send
+  /// types it doesn't
+  pub fn is_supported(&self) -> bool {
+    EVENT_TYPES.contains(&self.event_type.as_str())
+  }
+
+  /// Typed event of the payload, validated
+  pub fn event(&self) -> Result<MetricEvent> {
+    if self.event_type.len() > EVENT_TYPE_MAX {
+      return Err(Error::InvalidArgs("Unknown event type".into()));
+    }
+    let event_json = json!({
+      "type": self.event_type,
+      "data": self.data
+    });
+    let event: MetricEvent = json::from_value(event_json).map_err(|e| {
+      Error::InvalidArgs(format!("Unknown event format: {}", e))
+    })?;
+    event.validate()?;
+    Ok(event)
+  }
+}
+
+/// Rows written by one statement of the daily snapshot, well under the
+/// SQLite variable limit
+const SNAPSHOT_CHUNK: usize = 500;
+
+/// Longest accepted `instance_id`, matches the column size
+const INSTANCE_ID_MAX: usize = 64;
+
+/// Telemetry comes straight from clients, these bound what one payload may
+/// carry and how far it can move the totals
+const RAW_MAX: usize = 1024 * 1024;
+const JSON_MAX: u64 = 4 * 1024 * 1024;
+const KEY_MAX: usize = 64;
+const EVENT_TYPE_MAX: usize = 32;
+const STATE_MAX: usize = 32;
+/// Distinct states kept per rollup, new names past it are dropped
+const STATES_MAX: usize = 64;
+const ROUTES_MAX: usize = 64;
+const ROUTE_MAX: usize = 128;
+/// A month, longer uptimes or state durations are bogus
+const SECONDS_MAX: f64 = 31.0 * 24.0 * 3600.0;
+const XP_MAX: u64 = 1_000_000;
+const ERRORS_MAX: usize = 32;
+const VERSION_MAX: usize = 32;
+
+/// Instances that haven't reported for this long are shown as stuck
+pub const INSTANCE_SILENT_MINS: i64 = 30;
+
+impl MetricEvent {
+  /// Reject values no client sends, so a forged payload can't poison the
+  /// totals or grow the metadata without bound
+  fn validate(&self) -> Result<()> {
+    let seconds =
+      |value: f64| value.is_finite() && (0.0..=SECONDS_MAX).contains(&value);
+    let valid = match self {
+      MetricEvent::Shutdown { uptime } => seconds(*uptime),
+      MetricEvent::State { state, duration } => {
+        !state.is_empty()
+          && state.chars().count() <= STATE_MAX
+          && !state.chars().any(char::is_control)
+          && seconds(*duration)
+      }
+      MetricEvent::Xp { gained } => *gained <= XP_MAX,
+      MetricEvent::Srt { routes } => {
+        routes.len() <= ROUTES_MAX
+          && routes.iter().all(|route| route.chars().count() <= ROUTE_MAX)
+      }
+      MetricEvent::Performance { avg_fps, avg_ai_ms, .. } => {
+        avg_fps.is_none_or(|fps| fps.is_finite() && fps >= 0.0)
+          && avg_ai_ms.is_none_or(|ms| ms.is_finite() && ms >= 0.0)
+      }
+      MetricEvent::Errors { version, errors } => {
+        errors.len() <= ERRORS_MAX
+          && version.as_ref().is_none_or(|version| {
+            version.chars().count() <= VERSION_MAX
+              && !version.chars().any(char::is_control)
+          })
+      }
+    };
+    if !valid {
+      return Err(Error::InvalidArgs("Metric value out of range".into()));
+    }
+    Ok(())
+  }
+
+  /// Fold the event into a runtime counter and metadata
+  fn apply(self, runtime_hours: &mut f64, meta: &mut MetaStats) {
+    match self {
+      MetricEvent::Shutdown { uptime } => {
+        *runtime_hours += uptime / 3600.0;
+      }
+      MetricEvent::State { state, duration } => {
+        if meta.states.len() < STATES_MAX || meta.states.contains_key(&state) {
+          *meta.states.entry(state).or_insert(0.0) += duration;
+        }
+      }
+      MetricEvent::Srt { routes } => {
+        meta.network.routes = routes;
+      }
+      // XP is only tracked on the user, license and session totals
+      MetricEvent::Xp { .. } => {}
+      // kept in error_reports
+      MetricEvent::Errors { .. } => {}
+      MetricEvent::Performance { avg_fps, avg_ram_mb, avg_ai_ms } => {
+        if let Some(fps) = avg_fps {
+          meta.performance.avg_fps = fps;
+        }
+        if let Some(ram) = avg_ram_mb {
+          meta.performance.avg_ram_mb = ram;
+        }
+        if let Some(ai) = avg_ai_ms {
+          meta.performance.avg_ai_ms = ai;
+        }
+      }
+    }
+  }
+}
+
+fn parse_meta(meta: &Option<json::Value>) -> MetaStats {
+  match meta {
+    Some(val) => json::from_value(val.clone()).unwrap_or_default(),
+    None => MetaStats::default(),
+  }
+}
+
+/// XP and runtime gained on one day
+#[derive(Debug, Clone, PartialEq)]
+pub struct DailyGain {
+  pub day: NaiveDate,
+  pub xp: i64,
+  pub runtime_hours: f64,
+}
+
+/// Growth between consecutive snapshots, credited to the later one. The
+/// first snapshot is only the baseline, a weekly reset doesn't matter as
+/// totals are compared.
+pub fn daily_gains(trend: &[stats_daily::Model]) -> Vec<DailyGain> {
+  trend
+    .windows(2)
+    .map(|pair| DailyGain {
+      day: pair[1].day,
+      xp: (pair[1].total_xp - pair[0].total_xp).max(0),
+      runtime_hours: (pair[1].runtime_hours - pair[0].runtime_hours).max(0.0),
+    })
+    .collect()
+}
+
+/// What `/top` ranks users by
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Board {
+  WeeklyXp,
+  Drops,
+}
+
+impl Board {
+  fn column(self) -> stats::Column {
+    match self {
+      Board::WeeklyXp => stats::Column::WeeklyXp,
+      Board::Drops => stats::Column::DropsCount,
+    }
+  }
+}
+
+/// Users who opted into the leaderboard
+fn on_board() -> SelectStatement {
+  Query::select()
+    .column(user_settings::Column::TgUserId)
+    .from(user_settings::Entity)
+    .and_where(user_settings::Column::Leaderboard.eq(true))
+    .to_owned()
+}
+
 #[derive(Debug, Serialize)]
 pub struct UserStatsDisplay {
   pub weekly_xp: u64,
@@ -100,74 +362,314 @@ impl<'a> Stats<'a> {
     Ok(stats.insert(self.db).await?)
   }
 
-  pub async fn process_metric(&self, raw_base64: &str) -> Result<()> {
-    let compressed = base64::prelude::BASE64_STANDARD
-      .decode(raw_base64)
-      .map_err(|_| Error::InvalidArgs("Invalid base64".into()))?;
+  /// Unpack a base64-encoded telemetry payload compressed with `encoding`,
+  /// checking it against the size and value limits before anything is
+  /// stored. Events of a newer schema this server doesn't know pass, see
+  /// `MetricPayload::is_supported`.
+  pub fn decode_metric(
+    raw_base64: &str,
+    encoding: Encoding,
+  ) -> std::result::Result<MetricPayload, Rejected> {
+    let reject = |reason, schema_version, message: String| Rejected {
+      reason,
+      schema_version,
+      error: Error::InvalidArgs(message),
+    };
+
+    if raw_base64.len() > RAW_MAX {
+      return Err(reject(
+        RejectReason::TooLarge,
+        None,
+        "Payload too large".into(),
+      ));
+    }
+    let compressed =
+      base64::prelude::BASE64_STANDARD.decode(raw_base64).map_err(|_| {
+        reject(RejectReason::Encoding, None, "Invalid base64".into())
+      })?;
 
     let mut json_str = String::new();
-    let decoder = GzDecoder::new(&compressed[..]);
-    decoder.take(4 * 1024 * 1024).read_to_string(&mut json_str).map_err(
-      |err| Error::InvalidArgs(format!("Decompression failed: {err}")),
-    )?;
+    let decompression = |err: std::io::Error| {
+      reject(
+        RejectReason::Encoding,
+        None,
+        format!("Decompression failed: {err}"),
+      )
+    };
+    let decoder: Box<dyn Read> = match encoding {
+      Encoding::Gzip => Box::new(GzDecoder::new(&compressed[..])),
+      Encoding::Zstd => Box::new(
+        StreamingDecoder::new(&compressed[..])
+          .map_err(|err| decompression(std::io::Error::other(err)))?,
+      ),
+    };
+    // one byte over the limit tells a bomb from a payload that just fits
+    decoder
+      .take(JSON_MAX + 1)
+      .read_to_string(&mut json_str)
+      .map_err(decompression)?;
+    if json_str.len() as u64 > JSON_MAX {
+      return Err(reject(
+        RejectReason::TooLarge,
+        None,
+        "Payload too large".into(),
+      ));
+    }
+
+    let payload: MetricPayload = json::from_str(&json_str).map_err(|e| {
+      reject(RejectReason::Json, None, format!("Invalid JSON: {}", e))
+    })?;
+    let schema = Some(payload.schema_version);
+    if payload.schema_version == 0 {
+      return Err(reject(
+        RejectReason::Schema,
+        schema,
+        "Invalid schema version".into(),
+      ));
+    }
+    if payload.license_key.len() > KEY_MAX {
+      return Err(reject(
+        RejectReason::Invalid,
+        schema,
+        "Invalid license key".into(),
+      ));
+    }
+    if payload.session_id.as_ref().is_some_and(|id| id.len() > KEY_MAX) {
+      return Err(reject(
+        RejectReason::Invalid,
+        schema,
+        "Invalid session id".into(),
+      ));
+    }
+    if payload.build.as_ref().is_some_and(|build| {
+      build.chars().count() > VERSION_MAX || build.chars().any(char::is_control)
+    }) {
+      return Err(reject(
+        RejectReason::Invalid,
+        schema,
+        "Invalid build".into(),
+      ));
+    }
+    if !payload.is_supported() && payload.schema_version > SCHEMA_VERSION {
+      return Ok(payload);
+    }
+    payload.event().map_err(|error| Rejected {
+      reason: RejectReason::Invalid,
+      schema_version: schema,
+      error,
+    })?;
+    Ok(payload)
+  }
 
-    let payload: MetricPayload = json::from_str(&json_str)
-      .map_err(|e| Error::InvalidArgs(format!("Invalid JSON: {}", e)))?;
+  /// Apply a decoded payload sent with `license`.
+  /// Returns the daily goal if this metric completed it.
+  pub async fn record_metric(
+    &self,
+    license: &license::Model,
+    payload: MetricPayload,
+  ) -> Result<Option<goal::Model>> {
+    // support sessions on a loaner would skew the customer's numbers
+    if license.loaned_by.is_some() || !payload.is_supported() {
+      return Ok(None);
+    }
 
-    let license = sv::License::new(self.db)
-      .by_key(&payload.license_key)
-      .await?
-      .ok_or(Error::LicenseNotFound)?;
+    let instance_id = payload
+      .instance_id
+      .as_deref()
+      .map(str::trim)
+      .filter(|id| !id.is_empty())
+      .map(|id| id.chars().take(INSTANCE_ID_MAX).collect::<String>());
 
     let stats = self.get_or_create(license.tg_user_id).await?;
-    let mut meta: MetaStats = match &stats.meta {
-      Some(val) => json::from_value(val.clone()).unwrap_or_default(),
-      None => MetaStats::default(),
-    };
+    let mut meta = parse_meta(&stats.meta);
 
-    let event_json = json!({
-      "type": payload.event_type,
-      "data": payload.data
-    });
+    let event = payload.event()?;
 
-    let event: MetricEvent = json::from_value(event_json).map_err(|e| {
-      Error::InvalidArgs(format!("Unknown event format: {}", e))
-    })?;
-
-    let mut model: stats::ActiveModel = stats.clone().into();
+    if let Some(instance_id) = instance_id {
+      self.record_instance(license, &instance_id, event.clone()).await?;
+    }
 
-    match event {
+    let progress = match &event {
+      MetricEvent::Xp { gained } => Some((GoalKind::Xp, *gained as f64)),
       MetricEvent::Shutdown { uptime } => {
-        model.runtime_hours = Set(stats.runtime_hours + (uptime / 3600.0));
-      }
-      MetricEvent::State { state, duration } => {
-        *meta.states.entry(state).or_insert(0.0) += duration;
-      }
-      MetricEvent::Srt { routes } => {
-        meta.network.routes = routes;
-      }
-      MetricEvent::Performance { avg_fps, avg_ram_mb, avg_ai_ms } => {
-        if let Some(fps) = avg_fps {
-          meta.performance.avg_fps = fps;
-        }
-        if let Some(ram) = avg_ram_mb {
-          meta.performance.avg_ram_mb = ram;
-        }
-        if let Some(ai) = avg_ai_ms {
-          meta.performance.avg_ai_ms = ai;
-        }
+        Some((GoalKind::Runtime, uptime / 3600.0))
       }
+      _ => None,
+    };
+
+    let mut model: stats::ActiveModel = stats.clone().into();
+    if let MetricEvent::Xp { gained } = &event {
+      model.weekly_xp = Set(stats.weekly_xp.saturating_add(*gained as i64));
+      model.total_xp = Set(stats.total_xp.saturating_add(*gained as i64));
     }
 
     let now = Utc::now().naive_utc();
+    if let MetricEvent::Errors { version, errors } = &event {
+      let build = payload.build.as_deref().or(version.as_deref());
+      sv::ErrorReports::new(self.db)
+        .record(&license.key, build, errors, now)
+        .await?;
+    }
+
+    let mut runtime_hours = stats.runtime_hours;
+    event.apply(&mut runtime_hours, &mut meta);
+
+    model.runtime_hours = Set(runtime_hours);
     model.last_updated = Set(now);
     model.meta = Set(Some(json::to_value(meta).unwrap()));
 
     model.update(self.db).await?;
 
+    if let Some((kind, amount)) = progress {
+      let (xp, hours) = match kind {
+        GoalKind::Xp => (amount as i64, 0.0),
+        GoalKind::Runtime => (0, amount),
+      };
+      self.record_license(&license.key, xp, hours, now).await?;
+      if xp > 0
+        && let Some(session_id) = &payload.session_id
+      {
+        session::Entity::update_many()
+          .col_expr(session::Column::Xp, Expr::col(session::Column::Xp).add(xp))
+          .filter(session::Column::SessionId.eq(session_id))
+          .filter(session::Column::LicenseKey.eq(&license.key))
+          .filter(session::Column::EndedAt.is_null())
+          .exec(self.db)
+          .await?;
+      }
+    }
+
+    match progress {
+      Some((kind, amount)) => {
+        sv::Goal::new(self.db).track(license.tg_user_id, kind, amount).await
+      }
+      None => Ok(None),
+    }
+  }
+
+  async fn record_license(
+    &self,
+    key: &str,
+    xp: i64,
+    hours: f64,
+    now: DateTime,
+  ) -> Result<()> {
+    license_stats::Entity::insert(license_stats::ActiveModel {
+      license_key: Set(key.to_string()),
+      weekly_xp: Set(xp),
+      total_xp: Set(xp),
+      runtime_hours: Set(hours),
+      last_updated: Set(now),
+    })
+    .on_conflict(
+      OnConflict::column(license_stats::Column::LicenseKey)
+        .value(
+          license_stats::Column::WeeklyXp,
+          Expr::col(license_stats::Column::WeeklyXp).add(xp),
+        )
+        .value(
+          license_stats::Column::TotalXp,
+          Expr::col(license_stats::Column::TotalXp).add(xp),
+        )
+        .value(
+          license_stats::Column::RuntimeHours,
+          Expr::col(license_stats::Column::RuntimeHours).add(hours),
+        )
+        .update_column(license_stats::Column::LastUpdated)
+        .to_owned(),
+    )
+    .exec_without_returning(self.db)
+    .await?;
     Ok(())
   }
 
+  /// Totals of the given keys, most XP first. Keys without metrics are
+  /// left out.
+  pub async fn by_licenses(
+    &self,
+    keys: impl IntoIterator<Item = String>,
+  ) -> Result<Vec<license_stats::Model>> {
+    Ok(
+      license_stats::Entity::find()
+        .filter(license_stats::Column::LicenseKey.is_in(keys))
+        .order_by_desc(license_stats::Column::TotalXp)
+        .all(self.db)
+        .await?,
+    )
+  }
+
+  async fn record_instance(
+    &self,
+    license: &license::Model,
+    instance_id: &str,
+    event: MetricEvent,
+  ) -> Result<()> {
+    let tg_user_id = license.tg_user_id;
+    let now = Utc::now().naive_utc();
+    let existing =
+      instance_stats::Entity::find_by_id((tg_user_id, instance_id.to_string()))
+        .one(self.db)
+        .await?;
+
+    let (mut runtime_hours, mut meta) = match &existing {
+      Some(rollup) => (rollup.runtime_hours, parse_meta(&rollup.meta)),
+      None => (0.0, MetaStats::default()),
+    };
+    event.apply(&mut runtime_hours, &mut meta);
+    let meta = Some(json::to_value(meta).unwrap());
+
+    match existing {
+      Some(rollup) => {
+        instance_stats::ActiveModel {
+          runtime_hours: Set(runtime_hours),
+          meta: Set(meta),
+          last_seen: Set(now),
+          product: Set(license.product.clone()),
+          ..rollup.into()
+        }
+        .update(self.db)
+        .await?;
+      }
+      None => {
+        instance_stats::ActiveModel {
+          tg_user_id: Set(tg_user_id),
+          instance_id: Set(instance_id.to_string()),
+          runtime_hours: Set(runtime_hours),
+          meta: Set(meta),
+          first_seen: Set(now),
+          last_seen: Set(now),
+          product: Set(license.product.clone()),
+        }
+        .insert(self.db)
+        .await?;
+      }
+    }
+
+    Ok(())
+  }
+
+  /// Per-instance breakdown, most recently active first
+  pub async fn instances(
+    &self,
+    tg_user_id: i64,
+  ) -> Result<Vec<(instance_stats::Model, MetaStats)>> {
+    let rollups = instance_stats::Entity::find()
+      .filter(instance_stats::Column::TgUserId.eq(tg_user_id))
+      .order_by_desc(instance_stats::Column::LastSeen)
+      .all(self.db)
+      .await?;
+
+    Ok(
+      rollups
+        .into_iter()
+        .map(|rollup| {
+          let meta = parse_meta(&rollup.meta);
+          (rollup, meta)
+        })
+        .collect(),
+    )
+  }
+
   pub async fn display_stats(
     &self,
     tg_user_id: i64,
@@ -186,21 +688,204 @@ impl<'a> Stats<'a> {
       meta,
     })
   }
-  pub async fn reset_weekly_xp(db: &DatabaseConnection) -> Result<()> {
-    use sea_orm::sea_query::Expr;
+  /// Copy every user's totals into `stats_daily` under `day`, replacing
+  /// an earlier snapshot of that day. Returns how many users were copied.
+  pub async fn snapshot(&self, day: NaiveDate) -> Result<usize> {
+    let rows = stats::Entity::find().all(self.db).await?;
+    let txn = self.db.begin().await?;
+    for chunk in rows.chunks(SNAPSHOT_CHUNK) {
+      let models = chunk.iter().map(|stats| stats_daily::ActiveModel {
+        tg_user_id: Set(stats.tg_user_id),
+        day: Set(day),
+        weekly_xp: Set(stats.weekly_xp),
+        total_xp: Set(stats.total_xp),
+        drops_count: Set(stats.drops_count),
+        runtime_hours: Set(stats.runtime_hours),
+      });
+      stats_daily::Entity::insert_many(models)
+        .on_conflict(
+          OnConflict::columns([
+            stats_daily::Column::TgUserId,
+            stats_daily::Column::Day,
+          ])
+          .update_columns([
+            stats_daily::Column::WeeklyXp,
+            stats_daily::Column::TotalXp,
+            stats_daily::Column::DropsCount,
+            stats_daily::Column::RuntimeHours,
+          ])
+          .to_owned(),
+        )
+        .exec_without_returning(&txn)
+        .await?;
+    }
+    txn.commit().await?;
+    Ok(rows.len())
+  }
+
+  /// Daily snapshots of a user over the `days` days up to `today`, oldest
+  /// first. Days the snapshot didn't run are missing.
+  pub async fn trend(
+    &self,
+    tg_user_id: i64,
+    today: NaiveDate,
+    days: u64,
+  ) -> Result<Vec<stats_daily::Model>> {
+    let since = today - chrono::Days::new(days.saturating_sub(1));
+    Ok(
+      stats_daily::Entity::find()
+        .filter(stats_daily::Column::TgUserId.eq(tg_user_id))
+        .filter(stats_daily::Column::Day.between(since, today))
+        .order_by_asc(stats_daily::Column::Day)
+        .all(self.db)
+        .await?,
+    )
+  }
+
+  /// Snapshots of all users summed by day, over the `days` days up to
+  /// `today`, oldest first
+  pub async fn global_trend(
+    &self,
+    today: NaiveDate,
+    days: u64,
+  ) -> Result<Vec<stats_daily::Model>> {
+    let since = today - chrono::Days::new(days.saturating_sub(1));
+    type DayRow =
+      (NaiveDate, Option<i64>, Option<i64>, Option<i64>, Option<f64>);
+    let rows: Vec<DayRow> = stats_daily::Entity::find()
+      .select_only()
+      .column(stats_daily::Column::Day)
+      .column_as(Expr::col(stats_daily::Column::WeeklyXp).sum(), "weekly_xp")
+      .column_as(Expr::col(stats_daily::Column::TotalXp).sum(), "total_xp")
+      .column_as(Expr::col(stats_daily::Column::DropsCount).sum(), "drops")
+      .column_as(Expr::col(stats_daily::Column::RuntimeHours).sum(), "runtime")
+      .filter(stats_daily::Column::Day.between(since, today))
+      .group_by(stats_daily::Column::Day)
+      .order_by_asc(stats_daily::Column::Day)
+      .into_tuple()
+      .all(self.db)
+      .await?;
+
+    Ok(
+      rows
+        .into_iter()
+        .map(|(day, weekly_xp, total_xp, drops, runtime)| stats_daily::Model {
+          tg_user_id: 0,
+          day,
+          weekly_xp: weekly_xp.unwrap_or(0),
+          total_xp: total_xp.unwrap_or(0),
+          drops_count: drops.unwrap_or(0) as i32,
+          runtime_hours: runtime.unwrap_or(0.0),
+        })
+        .collect(),
+    )
+  }
+
+  /// Daily gains of one user, or of everyone, over the last `days` days.
+  /// Today counts from the last snapshot to the live totals.
+  pub async fn gains(
+    &self,
+    tg_user_id: Option<i64>,
+    days: u64,
+  ) -> Result<Vec<DailyGain>> {
+    let today = Utc::now().date_naive();
+    // one more day for the baseline of the oldest
+    let (mut trend, total_xp, runtime_hours) = match tg_user_id {
+      Some(tg_user_id) => {
+        let stats = self.get_or_create(tg_user_id).await?;
+        let trend = self.trend(tg_user_id, today, days + 1).await?;
+        (trend, stats.total_xp, stats.runtime_hours)
+      }
+      None => {
+        let stats = self.aggregate().await?;
+        let trend = self.global_trend(today, days + 1).await?;
+        (trend, stats.total_xp as i64, stats.total_runtime_hours)
+      }
+    };
+    trend.retain(|snapshot| snapshot.day != today);
+    trend.push(stats_daily::Model {
+      tg_user_id: tg_user_id.unwrap_or(0),
+      day: today,
+      weekly_xp: 0,
+      total_xp,
+      drops_count: 0,
+      runtime_hours,
+    });
+    Ok(daily_gains(&trend))
+  }
+
+  /// Top `limit` opted-in users on `board` as `(tg_user_id, value)`, users
+  /// with nothing to show are left out
+  pub async fn leaderboard(
+    &self,
+    board: Board,
+    limit: u64,
+  ) -> Result<Vec<(i64, i64)>> {
+    let column = board.column();
+    Ok(
+      stats::Entity::find()
+        .select_only()
+        .column(stats::Column::TgUserId)
+        .column(column)
+        .filter(stats::Column::TgUserId.in_subquery(on_board()))
+        .filter(column.gt(0))
+        .order_by_desc(column)
+        .order_by_asc(stats::Column::TgUserId)
+        .limit(limit)
+        .into_tuple()
+        .all(self.db)
+        .await?,
+    )
+  }
+
+  /// Place of a user on `board`, `None` until they opt in and score.
+  /// Ties are broken by user id, as in `leaderboard`.
+  pub async fn rank(
+    &self,
+    board: Board,
+    tg_user_id: i64,
+  ) -> Result<Option<u64>> {
+    let column = board.column();
+    let value: Option<i64> = stats::Entity::find_by_id(tg_user_id)
+      .select_only()
+      .column(column)
+      .filter(stats::Column::TgUserId.in_subquery(on_board()))
+      .into_tuple()
+      .one(self.db)
+      .await?;
+    let Some(value) = value.filter(|&value| value > 0) else {
+      return Ok(None);
+    };
 
+    let ahead = stats::Entity::find()
+      .filter(stats::Column::TgUserId.in_subquery(on_board()))
+      .filter(
+        Condition::any().add(column.gt(value)).add(
+          Condition::all()
+            .add(column.eq(value))
+            .add(stats::Column::TgUserId.lt(tg_user_id)),
+        ),
+      )
+      .count(self.db)
+      .await?;
+    Ok(Some(ahead + 1))
+  }
+
+  pub async fn reset_weekly_xp(db: &DatabaseConnection) -> Result<()> {
     stats::Entity::update_many()
       .col_expr(stats::Column::WeeklyXp, Expr::value(0i64))
       .exec(db)
       .await?;
+    license_stats::Entity::update_many()
+      .col_expr(license_stats::Column::WeeklyXp, Expr::value(0i64))
+      .exec(db)
+      .await?;
 
     Ok(())
   }
 
   #[allow(dead_code)]
   pub async fn aggregate(&self) -> Result<AggregatedStats> {
-    use sea_orm::sea_query::Expr;
-
     type StatsRow = (Option<i64>, Option<i64>, Option<i64>, Option<f64>);
     let result: Option<StatsRow> = stats::Entity::find()
       .select_only()
@@ -238,3 +923,309 @@ pub struct AggregatedStats {
   pub total_runtime_hours: f64,
   pub active_instances: u32,
 }
+
+#[cfg(test)]
+mod tests {
+  use std::io::Write;
+
+  use flate2::{Compression, write::GzEncoder};
+
+  use super::*;
+  use crate::{entity::LicenseType, sv::test_utils::test_db};
+
+  async fn submit(
+    sv: &Stats<'_>,
+    license: &license::Model,
+    payload: json::Value,
+  ) {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(payload.to_string().as_bytes()).unwrap();
+    let raw =
+      base64::prelude::BASE64_STANDARD.encode(encoder.finish().unwrap());
+
+    let payload = Stats::decode_metric(&raw, Encoding::Gzip).unwrap();
+    sv.record_metric(license, payload).await.unwrap();
+  }
+
+  #[tokio::test]
+  async fn test_metrics_are_attributed_per_instance() {
+    let db = test_db::setup().await;
+    let sv = Stats::new(&db);
+    let license =
+      sv::License::new(&db).create(12345, LicenseType::Pro, 30).await.unwrap();
+
+    for (instance, uptime) in [("a", 3600.0), ("b", 7200.0), ("a", 3600.0)] {
+      let payload = json!({
+        "type": "shutdown",
+        "license_key": license.key,
+        "instance_id": instance,
+        "data": { "uptime": uptime },
+      });
+      submit(&sv, &license, payload).await;
+    }
+    // legacy payloads without an instance only count towards the total
+    let payload = json!({
+      "type": "shutdown",
+      "license_key": license.key,
+      "data": { "uptime": 3600.0 },
+    });
+    submit(&sv, &license, payload).await;
+
+    let total = sv.display_stats(12345).await.unwrap();
+    assert_eq!(total.runtime_hours, 5.0);
+
+    let mut instances = sv.instances(12345).await.unwrap();
+    instances.sort_by(|(a, _), (b, _)| a.instance_id.cmp(&b.instance_id));
+    let runtime: Vec<_> = instances
+      .iter()
+      .map(|(rollup, _)| (rollup.instance_id.as_str(), rollup.runtime_hours))
+      .collect();
+    assert_eq!(runtime, vec![("a", 2.0), ("b", 2.0)]);
+  }
+
+  #[tokio::test]
+  async fn test_metrics_are_attributed_per_license() {
+    let db = test_db::setup().await;
+    let sv = Stats::new(&db);
+    let licenses = sv::License::new(&db);
+    let first = licenses.create(7, LicenseType::Pro, 30).await.unwrap();
+    let second = licenses.create(7, LicenseType::Pro, 30).await.unwrap();
+    sv::Session::new(&db)
+      .start(&first.key, "s1", None, None, None, None)
+      .await
+      .unwrap();
+
+    for (license, session, gained) in
+      [(&first, Some("s1"), 100), (&first, None, 50), (&second, None, 30)]
+    {
+      let payload = json!({
+        "type": "xp",
+        "license_key": license.key,
+        "session_id": session,
+        "data": { "gained": gained },
+      });
+      submit(&sv, license, payload).await;
+    }
+    let payload = json!({
+      "type": "shutdown",
+      "license_key": second.key,
+      "data": { "uptime": 7200.0 },
+    });
+    submit(&sv, &second, payload).await;
+
+    assert_eq!(sv.display_stats(7).await.unwrap().total_xp, 180);
+    let totals: Vec<_> = sv
+      .by_licenses([first.key.clone(), second.key.clone()])
+      .await
+      .unwrap()
+      .into_iter()
+      .map(|s| (s.license_key, s.total_xp, s.runtime_hours))
+      .collect();
+    assert_eq!(
+      totals,
+      vec![(first.key.clone(), 150, 0.0), (second.key.clone(), 30, 2.0)]
+    );
+
+    let sessions = sv::Session::new(&db).history(&first.key, 10).await.unwrap();
+    assert_eq!(sessions[0].xp, 100);
+
+    Stats::reset_weekly_xp(&db).await.unwrap();
+    let weekly = sv.by_licenses([first.key]).await.unwrap();
+    assert_eq!((weekly[0].weekly_xp, weekly[0].total_xp), (0, 150));
+  }
+
+  #[tokio::test]
+  async fn test_daily_snapshots() {
+    let db = test_db::setup().await;
+    let sv = Stats::new(&db);
+    let license =
+      sv::License::new(&db).create(5, LicenseType::Pro, 30).await.unwrap();
+    sv.get_or_create(6).await.unwrap();
+
+    let day = NaiveDate::from_ymd_opt(2026, 3, 1).unwrap();
+    let xp = |gained: u64| {
+      json!({
+        "type": "xp",
+        "license_key": license.key,
+        "data": { "gained": gained },
+      })
+    };
+    submit(&sv, &license, xp(10)).await;
+    assert_eq!(sv.snapshot(day).await.unwrap(), 2);
+    submit(&sv, &license, xp(5)).await;
+    // a rerun on the same day replaces its snapshot
+    sv.snapshot(day).await.unwrap();
+    submit(&sv, &license, xp(20)).await;
+    sv.snapshot(day + chrono::Days::new(2)).await.unwrap();
+
+    let today = day + chrono::Days::new(2);
+    let totals = |trend: Vec<stats_daily::Model>| {
+      trend.into_iter().map(|s| (s.day, s.total_xp)).collect::<Vec<_>>()
+    };
+    assert_eq!(
+      totals(sv.trend(5, today, 30).await.unwrap()),
+      vec![(day, 15), (today, 35)]
+    );
+    assert_eq!(totals(sv.trend(5, today, 1).await.unwrap()), vec![(today, 35)]);
+    assert_eq!(sv.trend(6, today, 30).await.unwrap().len(), 2);
+
+    let global = sv.global_trend(today, 30).await.unwrap();
+    assert_eq!(global.len(), 2);
+    assert_eq!(global[1].total_xp, 35);
+    let gains: Vec<_> =
+      daily_gains(&global).into_iter().map(|g| (g.day, g.xp)).collect();
+    assert_eq!(gains, vec![(today, 20)]);
+  }
+
+  #[tokio::test]
+  async fn test_leaderboard() {
+    let db = test_db::setup().await;
+    let sv = Stats::new(&db);
+    let settings = sv::Settings::new(&db);
+    for (user, weekly_xp, drops, opted_in) in
+      [(1, 50, 3, true), (2, 80, 0, true), (3, 90, 1, false), (4, 80, 0, true)]
+    {
+      let model = sv.get_or_create(user).await.unwrap();
+      stats::ActiveModel {
+        weekly_xp: Set(weekly_xp),
+        drops_count: Set(drops),
+        ..model.into()
+      }
+      .update(&db)
+      .await
+      .unwrap();
+      settings.set_leaderboard(user, opted_in).await.unwrap();
+    }
+
+    // user 3 leads but didn't opt in
+    assert_eq!(
+      sv.leaderboard(Board::WeeklyXp, 10).await.unwrap(),
+      vec![(2, 80), (4, 80), (1, 50)]
+    );
+    assert_eq!(
+      sv.leaderboard(Board::WeeklyXp, 1).await.unwrap(),
+      vec![(2, 80)]
+    );
+    assert_eq!(sv.leaderboard(Board::Drops, 10).await.unwrap(), vec![(1, 3)]);
+
+    assert_eq!(sv.rank(Board::WeeklyXp, 4).await.unwrap(), Some(2));
+    assert_eq!(sv.rank(Board::WeeklyXp, 1).await.unwrap(), Some(3));
+    assert_eq!(sv.rank(Board::WeeklyXp, 3).await.unwrap(), None);
+    assert_eq!(sv.rank(Board::Drops, 2).await.unwrap(), None);
+  }
+
+  fn encode(payload: &[u8]) -> String {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(payload).unwrap();
+    base64::prelude::BASE64_STANDARD.encode(encoder.finish().unwrap())
+  }
+
+  #[test]
+  fn test_decode_limits() {
+    let metric = |kind: &str, data: json::Value| {
+      let payload = json!({ "type": kind, "license_key": "KEY", "data": data });
+      Stats::decode_metric(
+        &encode(payload.to_string().as_bytes()),
+        Encoding::Gzip,
+      )
+    };
+
+    assert!(metric("shutdown", json!({ "uptime": 3600.0 })).is_ok());
+    assert!(metric("shutdown", json!({ "uptime": -1.0 })).is_err());
+    assert!(metric("shutdown", json!({ "uptime": 1e300 })).is_err());
+    assert!(metric("xp", json!({ "gained": u64::MAX })).is_err());
+    assert!(metric("state", json!({ "state": "", "duration": 1.0 })).is_err());
+    let routes = vec!["route"; ROUTES_MAX + 1];
+    assert!(metric("srt", json!({ "routes": routes })).is_err());
+    assert!(metric("errors", json!({ "errors": ["boom"] })).is_ok());
+    let errors = vec!["boom"; ERRORS_MAX + 1];
+    assert!(metric("errors", json!({ "errors": errors })).is_err());
+    assert!(metric("bogus", json!({})).is_err());
+
+    // a gzip bomb stops at the limit instead of filling memory
+    let bomb = vec![b' '; JSON_MAX as usize + 1];
+    assert!(Stats::decode_metric(&encode(&bomb), Encoding::Gzip).is_err());
+    assert!(Stats::decode_metric("not base64!", Encoding::Gzip).is_err());
+  }
+
+  #[test]
+  fn test_schema_versions() {
+    let decode = |payload: json::Value| {
+      Stats::decode_metric(
+        &encode(payload.to_string().as_bytes()),
+        Encoding::Gzip,
+      )
+    };
+    let reason = |payload| decode(payload).map(|_| ()).map_err(|r| r.reason);
+
+    let legacy =
+      json!({ "type": "xp", "license_key": "K", "data": { "gained": 1 } });
+    assert_eq!(decode(legacy).unwrap().schema_version, 1);
+    let current = decode(json!({
+      "schema_version": SCHEMA_VERSION,
+      "type": "errors",
+      "license_key": "K",
+      "build": "2.0.1",
+      "data": { "errors": ["boom"] },
+    }))
+    .unwrap();
+    assert_eq!(current.build.as_deref(), Some("2.0.1"));
+
+    // newer clients may send events this server doesn't know yet
+    let future = decode(json!({
+      "schema_version": SCHEMA_VERSION + 1,
+      "type": "fishing",
+      "license_key": "K",
+      "data": { "fish": 3 },
+    }))
+    .unwrap();
+    assert!(!future.is_supported());
+    let unknown = json!({
+      "schema_version": SCHEMA_VERSION,
+      "type": "fishing",
+      "license_key": "K",
+      "data": {},
+    });
+    assert_eq!(reason(unknown), Err(RejectReason::Invalid));
+
+    let zero = json!({ "schema_version": 0, "type": "xp", "license_key": "K", "data": {} });
+    assert_eq!(reason(zero), Err(RejectReason::Schema));
+    assert_eq!(reason(json!([1, 2])), Err(RejectReason::Json));
+    let raw = Stats::decode_metric("not base64!", Encoding::Gzip);
+    assert_eq!(
+      raw.map(|_| ()).map_err(|r| r.reason),
+      Err(RejectReason::Encoding)
+    );
+  }
+
+  #[test]
+  fn test_decode_zstd() {
+    use ruzstd::encoding::{CompressionLevel, compress_to_vec};
+
+    let zstd = |payload: &[u8]| {
+      let compressed = compress_to_vec(payload, CompressionLevel::Fastest);
+      base64::prelude::BASE64_STANDARD.encode(compressed)
+    };
+    let payload = json!({
+      "type": "xp",
+      "license_key": "KEY",
+      "data": { "gained": 5 },
+    })
+    .to_string();
+
+    let decoded =
+      Stats::decode_metric(&zstd(payload.as_bytes()), Encoding::Zstd).unwrap();
+    assert_eq!(decoded.license_key, "KEY");
+    // the encoding has to match the payload
+    assert!(
+      Stats::decode_metric(&encode(payload.as_bytes()), Encoding::Zstd)
+        .is_err()
+    );
+    assert!(
+      Stats::decode_metric(&zstd(payload.as_bytes()), Encoding::Gzip).is_err()
+    );
+
+    let bomb = vec![b' '; JSON_MAX as usize + 1];
+    assert!(Stats::decode_metric(&zstd(&bomb), Encoding::Zstd).is_err());
+  }
+}
@@ -1,3 +1,5 @@
+use sea_orm::sea_query::OnConflict;
+
 use crate::{
   entity::{free_game, free_item},
   prelude::*,
@@ -12,13 +14,17 @@ impl<'a> Steam<'a> {
     Self { db }
   }
 
+  /// Swap scraped games for fresh ones, admin additions stay untouched
   pub async fn replace_free_games_cache(
     &self,
     items: Vec<(i32, i32, String)>,
   ) -> Result<()> {
     let txn = self.db.begin().await?;
 
-    free_game::Entity::delete_many().exec(&txn).await?;
+    free_game::Entity::delete_many()
+      .filter(free_game::Column::Manual.eq(false))
+      .exec(&txn)
+      .await?;
 
     if !items.is_empty() {
       let now = Utc::now().naive_utc();
@@ -30,10 +36,16 @@ impl<'a> Steam<'a> {
           app_id: Set(app_id),
           name: Set(name),
           updated_at: Set(now),
+          manual: Set(false),
         })
         .collect();
 
-      free_game::Entity::insert_many(models).exec(&txn).await?;
+      free_game::Entity::insert_many(models)
+        .on_conflict(
+          OnConflict::column(free_game::Column::PkgId).do_nothing().to_owned(),
+        )
+        .exec_without_returning(&txn)
+        .await?;
     }
     txn.commit().await?;
 
@@ -44,19 +56,28 @@ impl<'a> Steam<'a> {
     Ok(free_game::Entity::find().all(self.db).await?)
   }
 
+  /// Swap scraped items for fresh ones, admin additions stay untouched
   pub async fn replace_free_items_cache(
     &self,
     items: Vec<free_item::Model>,
   ) -> Result<()> {
     let txn = self.db.begin().await?;
 
-    free_item::Entity::delete_many().exec(&txn).await?;
+    free_item::Entity::delete_many()
+      .filter(free_item::Column::Manual.eq(false))
+      .exec(&txn)
+      .await?;
 
     if !items.is_empty() {
       let active_models: Vec<free_item::ActiveModel> =
         items.into_iter().map(|item| item.into()).collect();
 
-      free_item::Entity::insert_many(active_models).exec(&txn).await?;
+      free_item::Entity::insert_many(active_models)
+        .on_conflict(
+          OnConflict::column(free_item::Column::DefId).do_nothing().to_owned(),
+        )
+        .exec_without_returning(&txn)
+        .await?;
     }
     txn.commit().await?;
 
@@ -0,0 +1,154 @@
+use crate::{
+  entity::storefront,
+  prelude::*,
+  sv::{self, product},
+};
+
+pub struct Storefront<'a> {
+  db: &'a DatabaseConnection,
+}
+
+impl<'a> Storefront<'a> {
+  pub fn new(db: &'a DatabaseConnection) -> Self {
+    Self { db }
+  }
+
+  pub async fn all(&self) -> Result<Vec<storefront::Model>> {
+    Ok(
+      storefront::Entity::find()
+        .order_by_asc(storefront::Column::CreatedAt)
+        .all(self.db)
+        .await?,
+    )
+  }
+
+  pub async fn get(&self, product: &str) -> Result<Option<storefront::Model>> {
+    Ok(storefront::Entity::find_by_id(product).one(self.db).await?)
+  }
+
+  /// Bind a bot to the product or update its branding.
+  /// The custom welcome message is kept.
+  pub async fn upsert(
+    &self,
+    product: &str,
+    bot_token: &str,
+    title: &str,
+    support: &str,
+  ) -> Result<storefront::Model> {
+    let product = sv::Product::new(self.db).get(product).await?;
+    if product.slug == product::DEFAULT {
+      return Err(Error::InvalidArgs(
+        "The default product is sold by the main bot".into(),
+      ));
+    }
+    // tokens look like 123456:ABC-DEF...
+    let valid_token = bot_token.split_once(':').is_some_and(|(id, secret)| {
+      id.parse::<u64>().is_ok() && !secret.is_empty()
+    });
+    if !valid_token {
+      return Err(Error::InvalidArgs("Invalid bot token".into()));
+    }
+    let title = title.trim();
+    if title.is_empty() {
+      return Err(Error::InvalidArgs("Storefront title is empty".into()));
+    }
+    let support = support.trim().trim_start_matches('@');
+    let valid_support = (4..=32).contains(&support.len())
+      && support.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if !valid_support {
+      return Err(Error::InvalidArgs(
+        "Support must be a Telegram username".into(),
+      ));
+    }
+
+    let model = match self.get(&product.slug).await? {
+      Some(existing) => {
+        storefront::ActiveModel {
+          bot_token: Set(bot_token.to_string()),
+          title: Set(title.to_string()),
+          support: Set(support.to_string()),
+          ..existing.into()
+        }
+        .update(self.db)
+        .await?
+      }
+      None => {
+        storefront::ActiveModel {
+          product: Set(product.slug),
+          bot_token: Set(bot_token.to_string()),
+          title: Set(title.to_string()),
+          support: Set(support.to_string()),
+          welcome: Set(None),
+          created_at: Set(Utc::now().naive_utc()),
+        }
+        .insert(self.db)
+        .await?
+      }
+    };
+    Ok(model)
+  }
+
+  /// Replace the welcome message, None restores the generated one
+  pub async fn set_welcome(
+    &self,
+    product: &str,
+    welcome: Option<&str>,
+  ) -> Result<storefront::Model> {
+    let storefront = self
+      .get(product)
+      .await?
+      .ok_or_else(|| Error::ProductNotFound(product.to_string()))?;
+    let welcome = welcome.map(str::trim).filter(|w| !w.is_empty());
+
+    Ok(
+      storefront::ActiveModel {
+        welcome: Set(welcome.map(str::to_string)),
+        ..storefront.into()
+      }
+      .update(self.db)
+      .await?,
+    )
+  }
+
+  /// Returns false if the product had no storefront
+  pub async fn remove(&self, product: &str) -> Result<bool> {
+    let res = storefront::Entity::delete_by_id(product).exec(self.db).await?;
+    Ok(res.rows_affected > 0)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::sv::test_utils::test_db;
+
+  #[tokio::test]
+  async fn test_storefront_branding() {
+    let db = test_db::setup().await;
+    let sv = Storefront::new(&db);
+
+    sv::Product::new(&db).upsert("aim", "Aim Helper", 150).await.unwrap();
+
+    let token = "123456:secret";
+    assert!(sv.upsert(product::DEFAULT, token, "Main", "shop").await.is_err());
+    assert!(sv.upsert("aim", "nope", "Aim", "shop_help").await.is_err());
+    assert!(sv.upsert("aim", token, "Aim", "@a b").await.is_err());
+    assert!(matches!(
+      sv.upsert("missing", token, "Aim", "shop_help").await,
+      Err(Error::ProductNotFound(_))
+    ));
+
+    let created = sv.upsert("aim", token, "Aim", "@shop_help").await.unwrap();
+    assert_eq!(created.support, "shop_help");
+
+    sv.set_welcome("aim", Some("Hi from <b>Aim</b>")).await.unwrap();
+    let updated =
+      sv.upsert("aim", token, "Aim Pro", "shop_help").await.unwrap();
+    assert_eq!(updated.title, "Aim Pro");
+    assert_eq!(updated.welcome.as_deref(), Some("Hi from <b>Aim</b>"));
+    assert_eq!(sv.all().await.unwrap().len(), 1);
+
+    assert!(sv.remove("aim").await.unwrap());
+    assert!(!sv.remove("aim").await.unwrap());
+  }
+}
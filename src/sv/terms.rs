@@ -0,0 +1,128 @@
+use sea_orm::sea_query::OnConflict;
+
+use crate::{
+  entity::{terms, terms_acceptance},
+  prelude::*,
+  sv,
+};
+
+pub struct Terms<'a> {
+  db: &'a DatabaseConnection,
+}
+
+impl<'a> Terms<'a> {
+  pub fn new(db: &'a DatabaseConnection) -> Self {
+    Self { db }
+  }
+
+  /// Latest published version, None while no terms exist
+  pub async fn current(&self) -> Result<Option<terms::Model>> {
+    Ok(
+      terms::Entity::find()
+        .order_by_desc(terms::Column::Version)
+        .one(self.db)
+        .await?,
+    )
+  }
+
+  /// Publish a new version, everyone has to accept it again
+  pub async fn publish(&self, text: &str) -> Result<terms::Model> {
+    let text = text.trim();
+    if text.is_empty() {
+      return Err(Error::InvalidArgs("Terms text is empty".into()));
+    }
+
+    Ok(
+      terms::ActiveModel {
+        version: NotSet,
+        text: Set(text.to_string()),
+        created_at: Set(Utc::now().naive_utc()),
+      }
+      .insert(self.db)
+      .await?,
+    )
+  }
+
+  /// Only the current version can be accepted, an old button is stale
+  pub async fn accept(&self, tg_user_id: i64, version: i32) -> Result<()> {
+    match self.current().await? {
+      Some(current) if current.version == version => {}
+      _ => {
+        return Err(Error::InvalidArgs(
+          "These terms were updated, please review the new version".into(),
+        ));
+      }
+    }
+
+    sv::User::new(self.db).get_or_create(tg_user_id).await?;
+
+    terms_acceptance::Entity::insert(terms_acceptance::ActiveModel {
+      tg_user_id: Set(tg_user_id),
+      version: Set(version),
+      accepted_at: Set(Utc::now().naive_utc()),
+    })
+    .on_conflict(
+      OnConflict::columns([
+        terms_acceptance::Column::TgUserId,
+        terms_acceptance::Column::Version,
+      ])
+      .do_nothing()
+      .to_owned(),
+    )
+    .exec_without_returning(self.db)
+    .await?;
+    Ok(())
+  }
+
+  pub async fn accepted(
+    &self,
+    tg_user_id: i64,
+    version: i32,
+  ) -> Result<Option<terms_acceptance::Model>> {
+    Ok(
+      terms_acceptance::Entity::find_by_id((tg_user_id, version))
+        .one(self.db)
+        .await?,
+    )
+  }
+
+  /// Current terms if the user still has to accept them
+  pub async fn pending(&self, tg_user_id: i64) -> Result<Option<terms::Model>> {
+    let Some(current) = self.current().await? else {
+      return Ok(None);
+    };
+    if self.accepted(tg_user_id, current.version).await?.is_some() {
+      return Ok(None);
+    }
+    Ok(Some(current))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::sv::test_utils::test_db;
+
+  #[tokio::test]
+  async fn test_new_version_requires_acceptance() {
+    let db = test_db::setup().await;
+    let sv = Terms::new(&db);
+
+    assert!(sv.pending(1).await.unwrap().is_none());
+
+    let v1 = sv.publish("Be nice").await.unwrap();
+    assert_eq!(sv.pending(1).await.unwrap().unwrap().version, v1.version);
+
+    sv.accept(1, v1.version).await.unwrap();
+    sv.accept(1, v1.version).await.unwrap();
+    assert!(sv.pending(1).await.unwrap().is_none());
+
+    let v2 = sv.publish("Be very nice").await.unwrap();
+    assert_eq!(sv.pending(1).await.unwrap().unwrap().version, v2.version);
+    assert!(sv.accept(1, v1.version).await.is_err());
+    assert!(sv.accepted(1, v1.version).await.unwrap().is_some());
+
+    sv.accept(1, v2.version).await.unwrap();
+    assert!(sv.pending(1).await.unwrap().is_none());
+  }
+}
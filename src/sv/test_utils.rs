@@ -33,6 +33,197 @@ pub mod test_db {
     let stmt = schema.create_table_from_entity(pending_invoice::Entity);
     db.execute(db.get_database_backend().build(&stmt)).await.unwrap();
 
+    // Create license_devices table
+    let stmt = schema.create_table_from_entity(license_device::Entity);
+    db.execute(db.get_database_backend().build(&stmt)).await.unwrap();
+
+    // Create user_settings table
+    let stmt = schema.create_table_from_entity(user_settings::Entity);
+    db.execute(db.get_database_backend().build(&stmt)).await.unwrap();
+
+    // Create faq table
+    let stmt = schema.create_table_from_entity(faq::Entity);
+    db.execute(db.get_database_backend().build(&stmt)).await.unwrap();
+
+    // Create ticket tables
+    let stmt = schema.create_table_from_entity(ticket::Entity);
+    db.execute(db.get_database_backend().build(&stmt)).await.unwrap();
+    let stmt = schema.create_table_from_entity(ticket_message::Entity);
+    db.execute(db.get_database_backend().build(&stmt)).await.unwrap();
+
+    // Create canned_responses table
+    let stmt = schema.create_table_from_entity(canned_response::Entity);
+    db.execute(db.get_database_backend().build(&stmt)).await.unwrap();
+
+    // Create ratings table
+    let stmt = schema.create_table_from_entity(rating::Entity);
+    db.execute(db.get_database_backend().build(&stmt)).await.unwrap();
+
+    // Create announcement tables
+    let stmt = schema.create_table_from_entity(announcement::Entity);
+    db.execute(db.get_database_backend().build(&stmt)).await.unwrap();
+    let stmt = schema.create_table_from_entity(announcement_read::Entity);
+    db.execute(db.get_database_backend().build(&stmt)).await.unwrap();
+
+    // Create api_tokens table
+    let stmt = schema.create_table_from_entity(api_token::Entity);
+    db.execute(db.get_database_backend().build(&stmt)).await.unwrap();
+
+    // Create user_stats table
+    let stmt = schema.create_table_from_entity(stats::Entity);
+    db.execute(db.get_database_backend().build(&stmt)).await.unwrap();
+
+    // Create instance_stats table
+    let stmt = schema.create_table_from_entity(instance_stats::Entity);
+    db.execute(db.get_database_backend().build(&stmt)).await.unwrap();
+
+    // Create goals table
+    let stmt = schema.create_table_from_entity(goal::Entity);
+    db.execute(db.get_database_backend().build(&stmt)).await.unwrap();
+
+    // Create incidents table
+    let stmt = schema.create_table_from_entity(incident::Entity);
+    db.execute(db.get_database_backend().build(&stmt)).await.unwrap();
+
+    // Create hwid_exemptions table
+    let stmt = schema.create_table_from_entity(hwid_exemption::Entity);
+    db.execute(db.get_database_backend().build(&stmt)).await.unwrap();
+
+    // Create region_prices table
+    let stmt = schema.create_table_from_entity(region_price::Entity);
+    db.execute(db.get_database_backend().build(&stmt)).await.unwrap();
+
+    // Create sales table
+    let stmt = schema.create_table_from_entity(sale::Entity);
+    db.execute(db.get_database_backend().build(&stmt)).await.unwrap();
+
+    // Create commission_boosts table
+    let stmt = schema.create_table_from_entity(commission_boost::Entity);
+    db.execute(db.get_database_backend().build(&stmt)).await.unwrap();
+
+    // Create terms tables
+    let stmt = schema.create_table_from_entity(terms::Entity);
+    db.execute(db.get_database_backend().build(&stmt)).await.unwrap();
+    let stmt = schema.create_table_from_entity(terms_acceptance::Entity);
+    db.execute(db.get_database_backend().build(&stmt)).await.unwrap();
+
+    // Create custom_fields table
+    let stmt = schema.create_table_from_entity(custom_field::Entity);
+    db.execute(db.get_database_backend().build(&stmt)).await.unwrap();
+
+    // Create feature_flags table
+    let stmt = schema.create_table_from_entity(feature_flag::Entity);
+    db.execute(db.get_database_backend().build(&stmt)).await.unwrap();
+
+    // Create sessions table
+    let stmt = schema.create_table_from_entity(session::Entity);
+    db.execute(db.get_database_backend().build(&stmt)).await.unwrap();
+
+    // Create products table with the default product, like the migration
+    let stmt = schema.create_table_from_entity(product::Entity);
+    db.execute(db.get_database_backend().build(&stmt)).await.unwrap();
+    crate::sv::Product::new(&db)
+      .upsert(crate::sv::product::DEFAULT, "YACS Panel", 100)
+      .await
+      .unwrap();
+
+    // Create storefronts table
+    let stmt = schema.create_table_from_entity(storefront::Entity);
+    db.execute(db.get_database_backend().build(&stmt)).await.unwrap();
+
+    // Create admin_ops table
+    let stmt = schema.create_table_from_entity(admin_op::Entity);
+    db.execute(db.get_database_backend().build(&stmt)).await.unwrap();
+
+    // Create plans table, empty plans are sold at their defaults
+    let stmt = schema.create_table_from_entity(plan::Entity);
+    db.execute(db.get_database_backend().build(&stmt)).await.unwrap();
+
+    // Create freebie tables
+    for stmt in [
+      schema.create_table_from_entity(free_game::Entity),
+      schema.create_table_from_entity(free_item::Entity),
+      schema.create_table_from_entity(freebie_claim::Entity),
+    ] {
+      db.execute(db.get_database_backend().build(&stmt)).await.unwrap();
+    }
+
+    // Create promo_campaigns table
+    let stmt = schema.create_table_from_entity(promo_campaign::Entity);
+    db.execute(db.get_database_backend().build(&stmt)).await.unwrap();
+
+    // Create bot_usage table
+    let stmt = schema.create_table_from_entity(bot_usage::Entity);
+    db.execute(db.get_database_backend().build(&stmt)).await.unwrap();
+
+    // Create signing_keys table
+    let stmt = schema.create_table_from_entity(signing_key::Entity);
+    db.execute(db.get_database_backend().build(&stmt)).await.unwrap();
+
+    // Create download_tokens table
+    let stmt = schema.create_table_from_entity(download_token::Entity);
+    db.execute(db.get_database_backend().build(&stmt)).await.unwrap();
+
+    // Create upgrade_offers table
+    let stmt = schema.create_table_from_entity(upgrade_offer::Entity);
+    db.execute(db.get_database_backend().build(&stmt)).await.unwrap();
+
+    // Create risk_scores table
+    let stmt = schema.create_table_from_entity(risk_score::Entity);
+    db.execute(db.get_database_backend().build(&stmt)).await.unwrap();
+
+    // Create reviews table
+    let stmt = schema.create_table_from_entity(review::Entity);
+    db.execute(db.get_database_backend().build(&stmt)).await.unwrap();
+
+    // Create builds table
+    let stmt = schema.create_table_from_entity(build::Entity);
+    db.execute(db.get_database_backend().build(&stmt)).await.unwrap();
+
+    // Create build_artifacts table
+    let stmt = schema.create_table_from_entity(build_artifact::Entity);
+    db.execute(db.get_database_backend().build(&stmt)).await.unwrap();
+
+    // Create downloads table
+    let stmt = schema.create_table_from_entity(download::Entity);
+    db.execute(db.get_database_backend().build(&stmt)).await.unwrap();
+
+    // Create held_alerts table
+    let stmt = schema.create_table_from_entity(held_alert::Entity);
+    db.execute(db.get_database_backend().build(&stmt)).await.unwrap();
+
+    // Create api_usage table
+    let stmt = schema.create_table_from_entity(api_usage::Entity);
+    db.execute(db.get_database_backend().build(&stmt)).await.unwrap();
+
+    // Create license_alerts table
+    let stmt = schema.create_table_from_entity(license_alert::Entity);
+    db.execute(db.get_database_backend().build(&stmt)).await.unwrap();
+
+    // Create license_stats table
+    let stmt = schema.create_table_from_entity(license_stats::Entity);
+    db.execute(db.get_database_backend().build(&stmt)).await.unwrap();
+
+    // Create stats_daily table
+    let stmt = schema.create_table_from_entity(stats_daily::Entity);
+    db.execute(db.get_database_backend().build(&stmt)).await.unwrap();
+
+    // Create error_reports table
+    let stmt = schema.create_table_from_entity(error_report::Entity);
+    db.execute(db.get_database_backend().build(&stmt)).await.unwrap();
+
+    // Create outbox table
+    let stmt = schema.create_table_from_entity(outbox::Entity);
+    db.execute(db.get_database_backend().build(&stmt)).await.unwrap();
+
+    // Create promo_codes table
+    let stmt = schema.create_table_from_entity(promo_code::Entity);
+    db.execute(db.get_database_backend().build(&stmt)).await.unwrap();
+
+    // Create promo_assets table
+    let stmt = schema.create_table_from_entity(promo_asset::Entity);
+    db.execute(db.get_database_backend().build(&stmt)).await.unwrap();
+
     db
   }
 }
@@ -0,0 +1,331 @@
+use crate::{
+  entity::{
+    LicenseType,
+    ticket::{self, TicketPriority, TicketStatus},
+    ticket_message,
+  },
+  prelude::*,
+  sv,
+};
+
+pub struct Ticket<'a> {
+  db: &'a DatabaseConnection,
+}
+
+impl<'a> Ticket<'a> {
+  pub fn new(db: &'a DatabaseConnection) -> Self {
+    Self { db }
+  }
+
+  pub async fn by_id(&self, id: i32) -> Result<Option<ticket::Model>> {
+    Ok(ticket::Entity::find_by_id(id).one(self.db).await?)
+  }
+
+  /// Latest ticket of the user that is not closed yet
+  pub async fn active_for(
+    &self,
+    tg_user_id: i64,
+  ) -> Result<Option<ticket::Model>> {
+    Ok(
+      ticket::Entity::find()
+        .filter(ticket::Column::TgUserId.eq(tg_user_id))
+        .filter(ticket::Column::Status.ne(TicketStatus::Closed))
+        .order_by_desc(ticket::Column::Id)
+        .one(self.db)
+        .await?,
+    )
+  }
+
+  /// Add a user message to their active ticket, opening a new one if needed.
+  /// Returns the ticket and whether it was just created.
+  pub async fn submit(
+    &self,
+    tg_user_id: i64,
+    text: &str,
+  ) -> Result<(ticket::Model, bool)> {
+    sv::User::new(self.db).get_or_create(tg_user_id).await?;
+
+    let now = Utc::now().naive_utc();
+    let priority = self.default_priority(tg_user_id).await?;
+    let txn = self.db.begin().await?;
+
+    let existing = ticket::Entity::find()
+      .filter(ticket::Column::TgUserId.eq(tg_user_id))
+      .filter(ticket::Column::Status.ne(TicketStatus::Closed))
+      .order_by_desc(ticket::Column::Id)
+      .one(&txn)
+      .await?;
+
+    let is_new = existing.is_none();
+    let ticket = match existing {
+      // a follow-up keeps the original wait, so the SLA clock isn't reset
+      Some(ticket) => {
+        let waiting_since = ticket.waiting_since.unwrap_or(now);
+        ticket::ActiveModel {
+          status: Set(TicketStatus::Open),
+          updated_at: Set(now),
+          waiting_since: Set(Some(waiting_since)),
+          ..ticket.into()
+        }
+        .update(&txn)
+        .await?
+      }
+      None => {
+        ticket::ActiveModel {
+          id: NotSet,
+          tg_user_id: Set(tg_user_id),
+          status: Set(TicketStatus::Open),
+          created_at: Set(now),
+          updated_at: Set(now),
+          priority: Set(priority),
+          waiting_since: Set(Some(now)),
+          reminders_sent: Set(0),
+        }
+        .insert(&txn)
+        .await?
+      }
+    };
+
+    Self::push_message(&txn, ticket.id, tg_user_id, false, text).await?;
+
+    txn.commit().await?;
+    Ok((ticket, is_new))
+  }
+
+  /// Record a support reply and mark the ticket as answered
+  pub async fn reply(
+    &self,
+    id: i32,
+    admin_id: i64,
+    text: &str,
+  ) -> Result<ticket::Model> {
+    let txn = self.db.begin().await?;
+
+    let ticket = ticket::Entity::find_by_id(id)
+      .one(&txn)
+      .await?
+      .ok_or(Error::TicketNotFound)?;
+
+    if ticket.status == TicketStatus::Closed {
+      return Err(Error::TicketClosed);
+    }
+
+    Self::push_message(&txn, id, admin_id, true, text).await?;
+
+    let ticket = ticket::ActiveModel {
+      status: Set(TicketStatus::Answered),
+      updated_at: Set(Utc::now().naive_utc()),
+      waiting_since: Set(None),
+      reminders_sent: Set(0),
+      ..ticket.into()
+    }
+    .update(&txn)
+    .await?;
+
+    txn.commit().await?;
+    Ok(ticket)
+  }
+
+  pub async fn close(&self, id: i32) -> Result<ticket::Model> {
+    let ticket = self.by_id(id).await?.ok_or(Error::TicketNotFound)?;
+
+    if ticket.status == TicketStatus::Closed {
+      return Err(Error::TicketClosed);
+    }
+
+    Ok(
+      ticket::ActiveModel {
+        status: Set(TicketStatus::Closed),
+        updated_at: Set(Utc::now().naive_utc()),
+        waiting_since: Set(None),
+        ..ticket.into()
+      }
+      .update(self.db)
+      .await?,
+    )
+  }
+
+  pub async fn messages(
+    &self,
+    ticket_id: i32,
+  ) -> Result<Vec<ticket_message::Model>> {
+    Ok(
+      ticket_message::Entity::find()
+        .filter(ticket_message::Column::TicketId.eq(ticket_id))
+        .order_by_asc(ticket_message::Column::Id)
+        .all(self.db)
+        .await?,
+    )
+  }
+
+  /// Support queue: tickets that are not closed, highest priority first,
+  /// then the longest waiting. Answered tickets go after waiting ones.
+  pub async fn unresolved(&self) -> Result<Vec<ticket::Model>> {
+    let mut tickets = ticket::Entity::find()
+      .filter(ticket::Column::Status.ne(TicketStatus::Closed))
+      .all(self.db)
+      .await?;
+
+    tickets.sort_by(|a, b| {
+      b.priority
+        .cmp(&a.priority)
+        .then(b.waiting_since.is_some().cmp(&a.waiting_since.is_some()))
+        .then(a.waiting_since.cmp(&b.waiting_since))
+        .then(a.updated_at.cmp(&b.updated_at))
+    });
+    Ok(tickets)
+  }
+
+  pub async fn count_opened_since(&self, since: DateTime) -> Result<u64> {
+    Ok(
+      ticket::Entity::find()
+        .filter(ticket::Column::CreatedAt.gte(since))
+        .count(self.db)
+        .await?,
+    )
+  }
+
+  pub async fn set_priority(
+    &self,
+    id: i32,
+    priority: TicketPriority,
+  ) -> Result<ticket::Model> {
+    let ticket = self.by_id(id).await?.ok_or(Error::TicketNotFound)?;
+    Ok(
+      ticket::ActiveModel { priority: Set(priority), ..ticket.into() }
+        .update(self.db)
+        .await?,
+    )
+  }
+
+  /// Waiting tickets that are due for the next SLA reminder
+  pub async fn overdue(&self, sla: TimeDelta) -> Result<Vec<ticket::Model>> {
+    let now = Utc::now().naive_utc();
+    let waiting = ticket::Entity::find()
+      .filter(ticket::Column::Status.eq(TicketStatus::Open))
+      .filter(ticket::Column::WaitingSince.lte(now - sla))
+      .all(self.db)
+      .await?;
+
+    Ok(
+      waiting
+        .into_iter()
+        .filter(|ticket| {
+          ticket.waiting_since.is_some_and(|since| {
+            reminder_due(now - since, sla, ticket.reminders_sent)
+          })
+        })
+        .collect(),
+    )
+  }
+
+  pub async fn mark_reminded(&self, ticket: ticket::Model) -> Result<()> {
+    let reminders_sent = ticket.reminders_sent + 1;
+    ticket::ActiveModel {
+      reminders_sent: Set(reminders_sent),
+      ..ticket.into()
+    }
+    .update(self.db)
+    .await?;
+    Ok(())
+  }
+
+  /// Paying customers go first in the queue
+  async fn default_priority(&self, tg_user_id: i64) -> Result<TicketPriority> {
+    let now = Utc::now().naive_utc();
+    let has_pro = sv::License::new(self.db)
+      .by_user(tg_user_id, false)
+      .await?
+      .iter()
+      .any(|l| l.license_type == LicenseType::Pro && l.expires_at > now);
+
+    Ok(if has_pro { TicketPriority::High } else { TicketPriority::Normal })
+  }
+
+  async fn push_message<C: ConnectionTrait>(
+    conn: &C,
+    ticket_id: i32,
+    author_id: i64,
+    from_admin: bool,
+    text: &str,
+  ) -> Result<()> {
+    ticket_message::ActiveModel {
+      id: NotSet,
+      ticket_id: Set(ticket_id),
+      author_id: Set(author_id),
+      from_admin: Set(from_admin),
+      text: Set(text.to_string()),
+      created_at: Set(Utc::now().naive_utc()),
+    }
+    .insert(conn)
+    .await?;
+    Ok(())
+  }
+}
+
+/// Reminders escalate with doubling gaps: after 1x, 2x, 4x... the SLA
+pub fn reminder_due(waiting: TimeDelta, sla: TimeDelta, sent: i32) -> bool {
+  let factor = 1i32.checked_shl(sent.clamp(0, 30) as u32).unwrap_or(i32::MAX);
+  waiting >= sla * factor
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::sv::test_utils::test_db;
+
+  #[tokio::test]
+  async fn test_ticket_lifecycle() {
+    let db = test_db::setup().await;
+    let sv = Ticket::new(&db);
+
+    let (ticket, is_new) = sv.submit(12345, "my key is invalid").await.unwrap();
+    assert!(is_new);
+
+    // follow-ups land in the same ticket
+    let (same, is_new) = sv.submit(12345, "still broken").await.unwrap();
+    assert!(!is_new);
+    assert_eq!(same.id, ticket.id);
+
+    let answered = sv.reply(ticket.id, 1, "try again").await.unwrap();
+    assert_eq!(answered.status, TicketStatus::Answered);
+    assert_eq!(sv.messages(ticket.id).await.unwrap().len(), 3);
+
+    sv.close(ticket.id).await.unwrap();
+    assert!(matches!(
+      sv.reply(ticket.id, 1, "late").await,
+      Err(Error::TicketClosed)
+    ));
+
+    let (next, is_new) = sv.submit(12345, "new issue").await.unwrap();
+    assert!(is_new);
+    assert_ne!(next.id, ticket.id);
+  }
+
+  #[tokio::test]
+  async fn test_queue_order_and_reminders() {
+    let db = test_db::setup().await;
+    let sv = Ticket::new(&db);
+
+    let (normal, _) = sv.submit(1, "help").await.unwrap();
+    let (answered, _) = sv.submit(2, "help").await.unwrap();
+    sv.reply(answered.id, 99, "done").await.unwrap();
+    let (high, _) = sv.submit(3, "help").await.unwrap();
+    sv.set_priority(high.id, TicketPriority::High).await.unwrap();
+
+    let queue: Vec<_> =
+      sv.unresolved().await.unwrap().into_iter().map(|t| t.id).collect();
+    assert_eq!(queue, vec![high.id, normal.id, answered.id]);
+
+    // fresh tickets aren't overdue, zero SLA makes every waiting one due
+    assert!(sv.overdue(TimeDelta::hours(1)).await.unwrap().is_empty());
+    assert_eq!(sv.overdue(TimeDelta::zero()).await.unwrap().len(), 2);
+
+    let sla = TimeDelta::hours(4);
+    assert!(!reminder_due(TimeDelta::hours(3), sla, 0));
+    assert!(reminder_due(TimeDelta::hours(4), sla, 0));
+    assert!(!reminder_due(TimeDelta::hours(7), sla, 1));
+    assert!(reminder_due(TimeDelta::hours(8), sla, 1));
+    assert!(reminder_due(TimeDelta::hours(16), sla, 2));
+  }
+}
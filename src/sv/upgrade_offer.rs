@@ -0,0 +1,187 @@
+use crate::{
+  entity::{
+    LicenseType, TransactionType, license, stats, transaction, upgrade_offer,
+    user,
+  },
+  prelude::*,
+};
+
+/// When a trial user counts as heavy and what they are offered
+#[derive(Debug, Clone)]
+pub struct Nudge {
+  /// Only users registered this recently are considered
+  pub within_days: i64,
+  /// Panel runtime that qualifies, 0 ignores it
+  pub min_runtime_hours: f64,
+  /// Earned XP that qualifies, 0 ignores it
+  pub min_xp: i64,
+  /// Discount off the first paid plan, 0 disables the nudges
+  pub percent: i32,
+  /// How long the offer can be used
+  pub valid_hours: i64,
+}
+
+impl Default for Nudge {
+  fn default() -> Self {
+    Self {
+      within_days: 3,
+      min_runtime_hours: 24.0,
+      min_xp: 0,
+      percent: 0,
+      valid_hours: 48,
+    }
+  }
+}
+
+impl Nudge {
+  pub fn enabled(&self) -> bool {
+    self.percent > 0 && (self.min_runtime_hours > 0.0 || self.min_xp > 0)
+  }
+
+  fn qualifies(&self, stats: &stats::Model) -> bool {
+    (self.min_runtime_hours > 0.0
+      && stats.runtime_hours >= self.min_runtime_hours)
+      || (self.min_xp > 0 && stats.total_xp >= self.min_xp)
+  }
+}
+
+pub struct UpgradeOffers<'a> {
+  db: &'a DatabaseConnection,
+}
+
+impl<'a> UpgradeOffers<'a> {
+  pub fn new(db: &'a DatabaseConnection) -> Self {
+    Self { db }
+  }
+
+  /// New trial users past the usage thresholds who never paid and were
+  /// never offered anything
+  pub async fn candidates(&self, nudge: &Nudge) -> Result<Vec<stats::Model>> {
+    if !nudge.enabled() {
+      return Ok(Vec::new());
+    }
+    let since = Utc::now().naive_utc() - TimeDelta::days(nudge.within_days);
+    let recent = stats::Entity::find()
+      .inner_join(user::Entity)
+      .filter(user::Column::RegDate.gte(since))
+      .filter(user::Column::DeletedAt.is_null())
+      .all(self.db)
+      .await?;
+
+    let mut candidates = Vec::new();
+    for stats in recent.into_iter().filter(|s| nudge.qualifies(s)) {
+      let user_id = stats.tg_user_id;
+      if upgrade_offer::Entity::find_by_id(user_id)
+        .one(self.db)
+        .await?
+        .is_some()
+      {
+        continue;
+      }
+      let on_trial = license::Entity::find()
+        .filter(license::Column::TgUserId.eq(user_id))
+        .filter(license::Column::LicenseType.eq(LicenseType::Trial))
+        .count(self.db)
+        .await?
+        > 0;
+      let paid = transaction::Entity::find()
+        .filter(transaction::Column::UserId.eq(user_id))
+        .filter(transaction::Column::TxType.eq(TransactionType::Purchase))
+        .count(self.db)
+        .await?
+        > 0;
+      if on_trial && !paid {
+        candidates.push(stats);
+      }
+    }
+    Ok(candidates)
+  }
+
+  /// Record the offer, a user never gets a second one
+  pub async fn create(
+    &self,
+    tg_user_id: i64,
+    nudge: &Nudge,
+  ) -> Result<upgrade_offer::Model> {
+    let now = Utc::now().naive_utc();
+    let offer = upgrade_offer::ActiveModel {
+      tg_user_id: Set(tg_user_id),
+      percent: Set(nudge.percent),
+      sent_at: Set(now),
+      expires_at: Set(now + TimeDelta::hours(nudge.valid_hours)),
+      used_at: Set(None),
+    };
+    Ok(offer.insert(self.db).await?)
+  }
+
+  /// Offer the user can still spend
+  pub async fn active(
+    &self,
+    tg_user_id: i64,
+  ) -> Result<Option<upgrade_offer::Model>> {
+    Ok(
+      upgrade_offer::Entity::find_by_id(tg_user_id)
+        .filter(upgrade_offer::Column::UsedAt.is_null())
+        .filter(upgrade_offer::Column::ExpiresAt.gt(Utc::now().naive_utc()))
+        .one(self.db)
+        .await?,
+    )
+  }
+
+  /// Spend the offer on a purchase, false if it was already used
+  pub async fn redeem(&self, tg_user_id: i64) -> Result<bool> {
+    Self::redeem_in(self.db, tg_user_id).await
+  }
+
+  /// `redeem` on any connection
+  pub async fn redeem_in<C: ConnectionTrait>(
+    db: &C,
+    tg_user_id: i64,
+  ) -> Result<bool> {
+    let result = upgrade_offer::Entity::update_many()
+      .col_expr(upgrade_offer::Column::UsedAt, Utc::now().naive_utc().into())
+      .filter(upgrade_offer::Column::TgUserId.eq(tg_user_id))
+      .filter(upgrade_offer::Column::UsedAt.is_null())
+      .exec(db)
+      .await?;
+    Ok(result.rows_affected > 0)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::sv::{self, test_utils::test_db};
+
+  #[tokio::test]
+  async fn test_heavy_trial_users_get_one_offer() {
+    let db = test_db::setup().await;
+    let sv = UpgradeOffers::new(&db);
+    let nudge =
+      Nudge { percent: 15, min_runtime_hours: 10.0, ..Default::default() };
+
+    for (user_id, hours) in [(1, 12.0), (2, 3.0)] {
+      sv::License::new(&db)
+        .create(user_id, LicenseType::Trial, 7)
+        .await
+        .unwrap();
+      let stats = sv::Stats::new(&db).get_or_create(user_id).await.unwrap();
+      stats::ActiveModel { runtime_hours: Set(hours), ..stats.into() }
+        .update(&db)
+        .await
+        .unwrap();
+    }
+
+    let candidates = sv.candidates(&nudge).await.unwrap();
+    assert_eq!(candidates.len(), 1);
+    assert_eq!(candidates[0].tg_user_id, 1);
+
+    sv.create(1, &nudge).await.unwrap();
+    assert!(sv.candidates(&nudge).await.unwrap().is_empty());
+    assert_eq!(sv.active(1).await.unwrap().unwrap().percent, 15);
+
+    assert!(sv.redeem(1).await.unwrap());
+    assert!(!sv.redeem(1).await.unwrap());
+    assert!(sv.active(1).await.unwrap().is_none());
+  }
+}
@@ -28,9 +28,11 @@ impl<'a> User<'a> {
       referred_by: Set(None),
       commission_rate: Set(10),
       discount_percent: Set(3),
-      referral_sales: Set(0),
-      referral_earnings: Set(0),
       referral_code: Set(None),
+      deletion_requested_at: Set(None),
+      deleted_at: Set(None),
+      storefront: Set(None),
+      is_reseller: Set(false),
     };
 
     Ok(user.insert(self.db).await?)
@@ -41,6 +43,27 @@ impl<'a> User<'a> {
     Ok(user)
   }
 
+  /// Remember which bot the user came from, None for the main one
+  pub async fn set_storefront(
+    &self,
+    tg_user_id: i64,
+    storefront: Option<&str>,
+  ) -> Result<()> {
+    let user = self.get_or_create(tg_user_id).await?;
+    if user.storefront.as_deref() == storefront {
+      return Ok(());
+    }
+
+    user::ActiveModel {
+      storefront: Set(storefront.map(str::to_string)),
+      is_reseller: Set(false),
+      ..user.into()
+    }
+    .update(self.db)
+    .await?;
+    Ok(())
+  }
+
   pub async fn set_role(&self, tg_user_id: i64, role: UserRole) -> Result<()> {
     let user = user::Entity::find_by_id(tg_user_id)
       .one(self.db)
@@ -54,6 +77,19 @@ impl<'a> User<'a> {
     Ok(())
   }
 
+  pub async fn set_reseller(&self, tg_user_id: i64, on: bool) -> Result<()> {
+    let user = user::Entity::find_by_id(tg_user_id)
+      .one(self.db)
+      .await?
+      .ok_or(Error::UserNotFound)?;
+
+    user::ActiveModel { is_reseller: Set(on), ..user.into() }
+      .update(self.db)
+      .await?;
+
+    Ok(())
+  }
+
   /// Set the referrer for a user (using referrer's user_id)
   /// Anyone can set any existing user as their referrer
   /// Discount is applied based on the referrer's discount_percent
@@ -98,6 +134,22 @@ impl<'a> User<'a> {
     Ok(users)
   }
 
+  /// Oldest users first, for the admin API
+  pub async fn page(
+    &self,
+    offset: u64,
+    limit: u64,
+  ) -> Result<Vec<user::Model>> {
+    Ok(
+      user::Entity::find()
+        .order_by_asc(user::Column::RegDate)
+        .offset(offset)
+        .limit(limit)
+        .all(self.db)
+        .await?,
+    )
+  }
+
   pub async fn all_with_licenses(
     &self,
   ) -> Result<Vec<(user::Model, Vec<license::Model>)>> {
@@ -114,9 +166,22 @@ impl<'a> User<'a> {
     Ok(user::Entity::find().count(self.db).await?)
   }
 
-  /// Get all users who have at least one active (non-blocked, non-expired) license.
+  pub async fn count_registered_since(&self, since: DateTime) -> Result<u64> {
+    Ok(
+      user::Entity::find()
+        .filter(user::Column::RegDate.gte(since))
+        .count(self.db)
+        .await?,
+    )
+  }
+
+  /// Get all users who have at least one active (non-blocked, non-expired) license
+  /// of `product`.
   /// An active license is one where: is_blocked = false AND expires_at > now.
-  pub async fn with_active_licenses(&self) -> Result<Vec<user::Model>> {
+  pub async fn with_active_licenses(
+    &self,
+    product: &str,
+  ) -> Result<Vec<user::Model>> {
     let now = Utc::now().naive_utc();
 
     // Find users who have at least one active license
@@ -124,6 +189,7 @@ impl<'a> User<'a> {
       .inner_join(license::Entity)
       .filter(license::Column::IsBlocked.eq(false))
       .filter(license::Column::ExpiresAt.gt(now))
+      .filter(license::Column::Product.eq(product))
       .group_by(user::Column::TgUserId)
       .all(self.db)
       .await?;
@@ -131,6 +197,32 @@ impl<'a> User<'a> {
     Ok(users)
   }
 
+  /// Everyone a broadcast goes to, optionally only users with a license
+  /// that is still running
+  pub async fn broadcast_targets(&self, active_only: bool) -> Result<Vec<i64>> {
+    let mut query = user::Entity::find()
+      .filter(user::Column::DeletedAt.is_null())
+      .filter(user::Column::TgUserId.ne(0));
+    if active_only {
+      let now = Utc::now().naive_utc();
+      query = query
+        .inner_join(license::Entity)
+        .filter(license::Column::IsBlocked.eq(false))
+        .filter(license::Column::IsHoneypot.eq(false))
+        .filter(license::Column::ExpiresAt.gt(now))
+        .group_by(user::Column::TgUserId);
+    }
+    Ok(
+      query
+        .order_by_asc(user::Column::TgUserId)
+        .all(self.db)
+        .await?
+        .into_iter()
+        .map(|user| user.tg_user_id)
+        .collect(),
+    )
+  }
+
   /// Find a user by their custom referral code
   pub async fn by_referral_code(
     &self,
@@ -230,9 +322,11 @@ mod tests {
       referred_by: Set(None),
       commission_rate: Set(25),
       discount_percent: Set(3),
-      referral_sales: Set(0),
-      referral_earnings: Set(0),
       referral_code: Set(None),
+      deletion_requested_at: Set(None),
+      deleted_at: Set(None),
+      storefront: Set(None),
+      is_reseller: Set(false),
     }
     .insert(&db)
     .await
@@ -256,4 +350,27 @@ mod tests {
       user_sv.set_referral_code(12345, Some("my_code".to_string())).await;
     assert!(result.is_ok());
   }
+
+  #[tokio::test]
+  async fn test_broadcast_targets() {
+    let db = test_db::setup().await;
+    let users = User::new(&db);
+    let licenses = crate::sv::License::new(&db);
+
+    users.get_or_create(1).await.unwrap();
+    let expired =
+      licenses.create(2, crate::entity::LicenseType::Pro, 30).await.unwrap();
+    licenses.create(3, crate::entity::LicenseType::Pro, 30).await.unwrap();
+    licenses.create(3, crate::entity::LicenseType::Pro, 60).await.unwrap();
+    license::ActiveModel {
+      expires_at: Set(Utc::now().naive_utc() - TimeDelta::days(1)),
+      ..expired.into()
+    }
+    .update(&db)
+    .await
+    .unwrap();
+
+    assert_eq!(users.broadcast_targets(false).await.unwrap(), vec![1, 2, 3]);
+    assert_eq!(users.broadcast_targets(true).await.unwrap(), vec![3]);
+  }
 }
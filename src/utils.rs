@@ -5,12 +5,48 @@ pub fn format_date(date: DateTime) -> String {
 }
 
 pub fn format_duration(duration: TimeDelta) -> String {
-  format!(
-    "{}d {}h {}m",
-    duration.num_days(),
-    duration.num_hours() % 24,
-    duration.num_minutes() % 60
-  )
+  humanize(duration, ["d", "h", "m"])
+}
+
+/// The two largest non-zero parts of `duration`, e.g. `30d` or `2d 5h`, with
+/// the given day, hour and minute suffixes
+pub fn humanize(duration: TimeDelta, units: [&str; 3]) -> String {
+  let minutes = duration.num_minutes().max(0);
+  let parts = [minutes / (24 * 60), minutes / 60 % 24, minutes % 60];
+  let text: Vec<String> = parts
+    .iter()
+    .zip(units)
+    .skip_while(|(n, _)| **n == 0)
+    .take(2)
+    .filter(|(n, _)| **n > 0)
+    .map(|(n, unit)| format!("{}{}", n, unit))
+    .collect();
+  if text.is_empty() {
+    return format!("0{}", units[2]);
+  }
+  text.join(" ")
+}
+
+/// Admin duration like `30d`, `2w` or a calendar `1mo`, counted from now
+pub fn parse_duration(s: &str) -> Option<TimeDelta> {
+  let now = Utc::now().naive_utc();
+  crate::sv::license::Term::parse(s).map(|term| term.after(now) - now)
+}
+
+/// Coarsen an IP address to its network (/24 for IPv4, /48 for IPv6),
+/// which is enough to tell locations apart without exposing the address.
+pub fn mask_ip(ip: &str) -> String {
+  match ip.parse::<std::net::IpAddr>() {
+    Ok(std::net::IpAddr::V4(v4)) => {
+      let [a, b, c, _] = v4.octets();
+      format!("{a}.{b}.{c}.0/24")
+    }
+    Ok(std::net::IpAddr::V6(v6)) => {
+      let s = v6.segments();
+      format!("{:x}:{:x}:{:x}::/48", s[0], s[1], s[2])
+    }
+    Err(_) => "unknown".to_string(),
+  }
 }
 
 /// Maximum message length for Telegram Bot API (4096 characters).